@@ -0,0 +1,94 @@
+//! Binds an agent's policy file to the hash its vault committed on-chain.
+//!
+//! The vault owner publishes the SHA-256 of the policy file depositors
+//! approved with `set_policy_commitment`. An agent only runs while its local
+//! file hashes to that `policy_commitment`, so the policy actually deployed is
+//! provably the one on record. To roll out a new policy, commit its hash
+//! first, then replace the file.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Discriminator;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use yield_vault::state::VaultState;
+
+/// The hash `set_policy_commitment` expects for a policy file
+pub fn policy_hash(config: &[u8]) -> [u8; 32] {
+    Sha256::digest(config).into()
+}
+
+/// Read a vault's `policy_commitment` over JSON-RPC
+pub fn fetch_commitment(rpc_url: &str, vault: &Pubkey) -> Result<[u8; 32]> {
+    let response: Value = ureq::post(rpc_url)
+        .send_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getAccountInfo",
+            "params": [vault.to_string(), { "encoding": "base64", "commitment": "confirmed" }],
+        }))?
+        .into_json()?;
+    if let Some(error) = response.get("error") {
+        bail!("getAccountInfo {vault}: {error}");
+    }
+    let account = &response["result"]["value"];
+    if account["owner"].as_str() != Some(&yield_vault::ID.to_string()) {
+        bail!("{vault} is not a yield vault account");
+    }
+    let data = BASE64.decode(
+        account["data"][0]
+            .as_str()
+            .ok_or_else(|| anyhow!("{vault}: no account data"))?,
+    )?;
+    let body = data
+        .strip_prefix(VaultState::DISCRIMINATOR)
+        .and_then(|body| body.get(..std::mem::size_of::<VaultState>()))
+        .ok_or_else(|| anyhow!("{vault} is not a vault"))?;
+    let state: VaultState = bytemuck::pod_read_unaligned(body);
+    Ok(state.policy_commitment)
+}
+
+/// The local policy file's hash against the vault's commitment
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PolicyCheck {
+    pub local: [u8; 32],
+    pub committed: [u8; 32],
+}
+
+impl PolicyCheck {
+    /// Whether the agent may run: the owner committed a hash and the local
+    /// file matches it
+    pub fn approved(&self) -> bool {
+        self.committed != [0; 32] && self.local == self.committed
+    }
+}
+
+impl std::fmt::Display for PolicyCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.committed == [0; 32] {
+            write!(
+                f,
+                "local {}, no commitment on-chain",
+                hex::encode(self.local)
+            )
+        } else {
+            write!(
+                f,
+                "local {}, committed {}",
+                hex::encode(self.local),
+                hex::encode(self.committed)
+            )
+        }
+    }
+}
+
+/// Hash the policy file at `path` and compare it with the vault's commitment
+pub fn check(rpc_url: &str, vault: &Pubkey, path: &str) -> Result<PolicyCheck> {
+    let config = std::fs::read(path).with_context(|| format!("read {path}"))?;
+    Ok(PolicyCheck {
+        local: policy_hash(&config),
+        committed: fetch_commitment(rpc_url, vault)?,
+    })
+}
@@ -0,0 +1,112 @@
+//! Checks an agent's policy file against its vault's on-chain commitment.
+//!
+//! `hash` prints the value to pass to `set_policy_commitment`; `verify` exits
+//! non-zero unless the file matches the commitment. `run` starts the agent
+//! command only on an approved file, then re-checks every
+//! `POLL_INTERVAL_SECS` (default 30): the agent is stopped as soon as the
+//! file or the commitment stops matching, and restarted on a newly approved
+//! file so it picks the policy up without a redeploy. RPC errors while
+//! running are logged and retried rather than stopping the agent:
+//!
+//! ```text
+//! SOLANA_RPC_URL=<rpc> SOLANA_VAULT=<vault> \
+//!     agent-policy hash <file> | verify <file> | run <file> <command> [args...]
+//! ```
+
+use std::process::{Child, Command};
+use std::time::Duration;
+
+use agent_policy::{check, policy_hash};
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, bail, Context, Result};
+
+const USAGE: &str =
+    "usage: agent-policy hash <file> | verify <file> | run <file> <command> [args...]";
+
+fn spawn(command: &[String]) -> Result<Child> {
+    Command::new(&command[0])
+        .args(&command[1..])
+        .spawn()
+        .with_context(|| format!("spawn {}", command[0]))
+}
+
+fn stop(agent: &mut Child) -> Result<()> {
+    agent.kill()?;
+    agent.wait()?;
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let arg = |index: usize| args.get(index).ok_or_else(|| anyhow!(USAGE));
+
+    if arg(0)? == "hash" {
+        let config = std::fs::read(arg(1)?)?;
+        println!("{}", hex::encode(policy_hash(&config)));
+        return Ok(());
+    }
+
+    let rpc_url = std::env::var("SOLANA_RPC_URL").context("SOLANA_RPC_URL is not set")?;
+    let vault: Pubkey = std::env::var("SOLANA_VAULT")
+        .context("SOLANA_VAULT is not set")?
+        .parse()?;
+    let path = arg(1)?;
+
+    match arg(0)?.as_str() {
+        "verify" => {
+            let result = check(&rpc_url, &vault, path)?;
+            if !result.approved() {
+                bail!("{path} does not match the vault's policy commitment: {result}");
+            }
+            println!("{path} matches the policy commitment of {vault}");
+        }
+        "run" => {
+            let command = args
+                .get(2..)
+                .filter(|c| !c.is_empty())
+                .ok_or_else(|| anyhow!(USAGE))?;
+            let interval = Duration::from_secs(
+                std::env::var("POLL_INTERVAL_SECS")
+                    .ok()
+                    .and_then(|secs| secs.parse().ok())
+                    .unwrap_or(30),
+            );
+
+            let mut approved = check(&rpc_url, &vault, path)?;
+            if !approved.approved() {
+                bail!("refusing to start: {path} does not match the policy commitment: {approved}");
+            }
+            let mut agent = spawn(command)?;
+            loop {
+                std::thread::sleep(interval);
+                if let Some(status) = agent.try_wait()? {
+                    bail!("agent exited with {status}");
+                }
+                let result = match check(&rpc_url, &vault, path) {
+                    Ok(result) => result,
+                    Err(err) => {
+                        eprintln!("policy check failed, retrying: {err:#}");
+                        continue;
+                    }
+                };
+                if !result.approved() {
+                    stop(&mut agent)?;
+                    bail!(
+                        "stopped agent: {path} no longer matches the policy commitment: {result}"
+                    );
+                }
+                if result != approved {
+                    eprintln!(
+                        "policy {} approved, restarting agent",
+                        hex::encode(result.local)
+                    );
+                    stop(&mut agent)?;
+                    agent = spawn(command)?;
+                    approved = result;
+                }
+            }
+        }
+        _ => bail!(USAGE),
+    }
+    Ok(())
+}
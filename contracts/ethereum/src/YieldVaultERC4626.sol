@@ -9,6 +9,13 @@ contract YieldVaultERC4626 {
     uint256 public totalShares;
     uint256 public totalAssets;
 
+    // Cross-chain NAV held by the sibling Solana vault, attested by a relayer
+    address public navRelayer;
+    uint256 public externalNav;
+    uint256 public externalNavObservedAt;
+
+    event ExternalNavReported(uint256 oldExternalNav, uint256 newExternalNav, uint256 observedAt);
+
     constructor() {
         owner = msg.sender;
     }
@@ -34,4 +41,21 @@ contract YieldVaultERC4626 {
         require(msg.sender == owner, "owner only");
         totalAssets += amount;
     }
+
+    function setNavRelayer(address relayer) external {
+        require(msg.sender == owner, "owner only");
+        navRelayer = relayer;
+    }
+
+    function reportExternalNav(uint256 nav, uint256 observedAt) external {
+        require(msg.sender == navRelayer, "relayer only");
+        require(observedAt > externalNavObservedAt, "stale attestation");
+        emit ExternalNavReported(externalNav, nav, observedAt);
+        externalNav = nav;
+        externalNavObservedAt = observedAt;
+    }
+
+    function totalNav() external view returns (uint256) {
+        return totalAssets + externalNav;
+    }
 }
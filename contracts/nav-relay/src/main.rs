@@ -0,0 +1,249 @@
+//! Mirrors yield vault NAV between a Solana vault and its EVM sibling.
+//!
+//! Every poll the relay reads each side's locally held assets and attests
+//! them to the other side: `report_external_nav` on Solana and
+//! `reportExternalNav` on the EVM vault. The relay key must be registered on
+//! both vaults (`set_nav_relayer` / `setNavRelayer`). Each attestation carries
+//! the source chain's observation time, so both vaults ignore reports that
+//! arrive out of order.
+//!
+//! Vault and relay health are exported for Prometheus on `METRICS_ADDR`
+//! (default `0.0.0.0:9464`); see `metrics`.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anchor_client::anchor_lang::AccountDeserialize;
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::{read_keypair_file, Keypair};
+use anchor_client::{Client, Cluster, Program};
+use anyhow::{anyhow, Context, Result};
+use ethers::prelude::*;
+
+use yield_vault::constants::{FEE_REPORT_SEED, PRICE_HISTORY_SEED};
+use yield_vault::state::VaultState;
+
+mod metrics;
+
+use metrics::Metrics;
+
+abigen!(
+    SiblingVault,
+    r#"[
+        function totalAssets() external view returns (uint256)
+        function reportExternalNav(uint256 nav, uint256 observedAt) external
+    ]"#
+);
+
+type EvmClient = SignerMiddleware<Provider<Http>, LocalWallet>;
+
+struct Config {
+    solana_rpc_url: String,
+    solana_ws_url: String,
+    relayer_keypair: String,
+    vault: Pubkey,
+    solana_decimals: u32,
+    evm_rpc_url: String,
+    evm_private_key: String,
+    evm_vault: Address,
+    evm_decimals: u32,
+    poll_interval: Duration,
+    metrics_addr: SocketAddr,
+}
+
+impl Config {
+    fn from_env() -> Result<Self> {
+        fn var(name: &str) -> Result<String> {
+            std::env::var(name).with_context(|| format!("{name} is not set"))
+        }
+
+        Ok(Self {
+            solana_rpc_url: var("SOLANA_RPC_URL")?,
+            solana_ws_url: var("SOLANA_WS_URL")?,
+            relayer_keypair: var("RELAYER_KEYPAIR")?,
+            vault: var("SOLANA_VAULT")?.parse()?,
+            solana_decimals: var("SOLANA_DECIMALS")?.parse()?,
+            evm_rpc_url: var("EVM_RPC_URL")?,
+            evm_private_key: var("EVM_PRIVATE_KEY")?,
+            evm_vault: var("EVM_VAULT")?.parse()?,
+            evm_decimals: var("EVM_DECIMALS")?.parse()?,
+            poll_interval: Duration::from_secs(
+                std::env::var("POLL_INTERVAL_SECS")
+                    .unwrap_or_else(|_| "60".to_string())
+                    .parse()?,
+            ),
+            metrics_addr: std::env::var("METRICS_ADDR")
+                .unwrap_or_else(|_| "0.0.0.0:9464".to_string())
+                .parse()?,
+        })
+    }
+}
+
+/// Rescale an amount between token decimal conventions
+fn rescale(amount: U256, from_decimals: u32, to_decimals: u32) -> U256 {
+    if from_decimals >= to_decimals {
+        amount / U256::exp10((from_decimals - to_decimals) as usize)
+    } else {
+        amount * U256::exp10((to_decimals - from_decimals) as usize)
+    }
+}
+
+/// Assets held by the Solana vault itself (excluding the mirrored EVM
+/// component), and the Solana block time they were observed at
+async fn read_solana_nav(
+    program: &Program<Arc<Keypair>>,
+    vault: &Pubkey,
+    metrics: &Metrics,
+) -> Result<(u64, i64)> {
+    let rpc = program.rpc();
+    let response = rpc
+        .get_account_with_commitment(vault, CommitmentConfig::confirmed())
+        .await?;
+    let account = response
+        .value
+        .ok_or_else(|| anyhow!("vault {vault} not found"))?;
+    let state = VaultState::try_deserialize(&mut account.data.as_slice())?;
+    metrics.observe_vault(&state);
+    let observed_at = rpc.get_block_time(response.context.slot).await?;
+
+    let local_nav = state
+        .total_assets
+        .checked_sub(state.external_nav)
+        .ok_or_else(|| anyhow!("external NAV exceeds total assets"))?;
+    Ok((local_nav, observed_at))
+}
+
+/// Assets held by the EVM vault and the block timestamp they were observed at
+async fn read_evm_nav(evm: &SiblingVault<EvmClient>) -> Result<(U256, i64)> {
+    let block = evm
+        .client()
+        .get_block(BlockNumber::Latest)
+        .await?
+        .ok_or_else(|| anyhow!("latest block unavailable"))?;
+    let number = block.number.ok_or_else(|| anyhow!("pending block"))?;
+    let nav = evm.total_assets().block(number).call().await?;
+    Ok((nav, block.timestamp.as_u64() as i64))
+}
+
+async fn relay_once(
+    config: &Config,
+    program: &Program<Arc<Keypair>>,
+    evm: &SiblingVault<EvmClient>,
+    metrics: &Metrics,
+) -> Result<()> {
+    let solana_errors = || metrics.rpc_errors.with_label_values(&["solana"]).inc();
+    let evm_errors = || metrics.rpc_errors.with_label_values(&["evm"]).inc();
+
+    // EVM -> Solana
+    let started = Instant::now();
+    let (evm_nav, evm_observed_at) = read_evm_nav(evm).await.inspect_err(|_| evm_errors())?;
+    let external_nav = rescale(evm_nav, config.evm_decimals, config.solana_decimals);
+    let external_nav = u64::try_from(external_nav).map_err(|_| anyhow!("EVM NAV overflows u64"))?;
+
+    let (fee_report, _) =
+        Pubkey::find_program_address(&[FEE_REPORT_SEED, config.vault.as_ref()], &yield_vault::ID);
+    let (price_history, _) = Pubkey::find_program_address(
+        &[PRICE_HISTORY_SEED, config.vault.as_ref()],
+        &yield_vault::ID,
+    );
+
+    let signature = program
+        .request()
+        .accounts(yield_vault::accounts::ReportExternalNav {
+            relayer: program.payer(),
+            vault: config.vault,
+            fee_report,
+            price_history,
+            event_authority: yield_vault::interface::event_authority(),
+            program: yield_vault::ID,
+        })
+        .args(yield_vault::instruction::ReportExternalNav {
+            external_nav,
+            observed_at: evm_observed_at,
+        })
+        .send()
+        .await
+        .inspect_err(|_| solana_errors())?;
+    metrics
+        .relay_seconds
+        .with_label_values(&["evm_to_solana"])
+        .observe(started.elapsed().as_secs_f64());
+    println!(
+        "solana <- evm: external_nav={external_nav} observed_at={evm_observed_at} tx={signature}"
+    );
+
+    // Solana -> EVM
+    let started = Instant::now();
+    let (solana_nav, solana_observed_at) = read_solana_nav(program, &config.vault, metrics)
+        .await
+        .inspect_err(|_| solana_errors())?;
+    let nav = rescale(
+        U256::from(solana_nav),
+        config.solana_decimals,
+        config.evm_decimals,
+    );
+
+    let receipt = evm
+        .report_external_nav(nav, U256::from(solana_observed_at))
+        .send()
+        .await
+        .inspect_err(|_| evm_errors())?
+        .await
+        .inspect_err(|_| evm_errors())?;
+    metrics
+        .relay_seconds
+        .with_label_values(&["solana_to_evm"])
+        .observe(started.elapsed().as_secs_f64());
+    println!(
+        "evm <- solana: external_nav={nav} observed_at={solana_observed_at} tx={:?}",
+        receipt.map(|r| r.transaction_hash)
+    );
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let config = Config::from_env()?;
+
+    let relayer = read_keypair_file(&config.relayer_keypair)
+        .map_err(|e| anyhow!("failed to read relayer keypair: {e}"))?;
+    let client = Client::new_with_options(
+        Cluster::Custom(config.solana_rpc_url.clone(), config.solana_ws_url.clone()),
+        Arc::new(relayer),
+        CommitmentConfig::confirmed(),
+    );
+    let program = client.program(yield_vault::ID)?;
+
+    let provider = Provider::<Http>::try_from(config.evm_rpc_url.as_str())?;
+    let chain_id = provider.get_chainid().await?.as_u64();
+    let wallet = config
+        .evm_private_key
+        .parse::<LocalWallet>()?
+        .with_chain_id(chain_id);
+    let evm = SiblingVault::new(
+        config.evm_vault,
+        Arc::new(SignerMiddleware::new(provider, wallet)),
+    );
+
+    let metrics = Arc::new(Metrics::new()?);
+    let server = metrics::serve(metrics.clone(), config.metrics_addr);
+    tokio::spawn(async move {
+        if let Err(err) = server.await {
+            eprintln!("metrics server failed: {err:#}");
+        }
+    });
+
+    loop {
+        match relay_once(&config, &program, &evm, &metrics).await {
+            Ok(()) => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                metrics.last_success.set(now as i64);
+            }
+            Err(err) => eprintln!("relay failed: {err:#}"),
+        }
+        tokio::time::sleep(config.poll_interval).await;
+    }
+}
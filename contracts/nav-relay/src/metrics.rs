@@ -0,0 +1,144 @@
+//! Prometheus metrics for the relay, served as text on `METRICS_ADDR`.
+//!
+//! Timestamps are exported as gauges so alerts can compare them with `time()`:
+//! a stuck relay shows up as `relay_last_success_timestamp_seconds` falling
+//! behind, a stale mirrored NAV as `vault_external_nav_observed_at_seconds`.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::Result;
+use prometheus::{
+    Encoder, Gauge, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use yield_vault::state::VaultState;
+
+pub struct Metrics {
+    registry: Registry,
+    /// `total_assets` of the Solana vault, in base units
+    pub tvl: IntGauge,
+    /// Underlying tokens per share, both in whole units
+    pub share_price: Gauge,
+    /// Shares queued for redemption at the next epoch settlement
+    pub pending_redeem_shares: IntGauge,
+    /// Assets the vault asked managers to pull back from strategies
+    pub pending_deallocation: IntGauge,
+    pub external_nav_observed_at: IntGauge,
+    pub last_success: IntGauge,
+    /// Duration of each relay direction, labelled by `direction`
+    pub relay_seconds: HistogramVec,
+    /// Failed RPC calls, labelled by `chain`
+    pub rpc_errors: IntCounterVec,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new();
+        let tvl = IntGauge::new("vault_total_assets", "Vault total assets in base units")?;
+        let share_price = Gauge::new(
+            "vault_share_price",
+            "Underlying tokens per vault share, both in whole units",
+        )?;
+        let pending_redeem_shares = IntGauge::new(
+            "vault_pending_redeem_shares",
+            "Shares queued for redemption at the next epoch settlement",
+        )?;
+        let pending_deallocation = IntGauge::new(
+            "vault_pending_deallocation",
+            "Assets requested back from strategies and not yet returned",
+        )?;
+        let external_nav_observed_at = IntGauge::new(
+            "vault_external_nav_observed_at_seconds",
+            "Observation time of the NAV last mirrored into the Solana vault",
+        )?;
+        let last_success = IntGauge::new(
+            "relay_last_success_timestamp_seconds",
+            "Unix time of the last relay round that completed both directions",
+        )?;
+        let relay_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "relay_duration_seconds",
+                "Time to relay NAV in one direction",
+            ),
+            &["direction"],
+        )?;
+        let rpc_errors = IntCounterVec::new(
+            Opts::new("relay_rpc_errors_total", "Failed RPC calls or transactions"),
+            &["chain"],
+        )?;
+
+        registry.register(Box::new(tvl.clone()))?;
+        registry.register(Box::new(share_price.clone()))?;
+        registry.register(Box::new(pending_redeem_shares.clone()))?;
+        registry.register(Box::new(pending_deallocation.clone()))?;
+        registry.register(Box::new(external_nav_observed_at.clone()))?;
+        registry.register(Box::new(last_success.clone()))?;
+        registry.register(Box::new(relay_seconds.clone()))?;
+        registry.register(Box::new(rpc_errors.clone()))?;
+
+        Ok(Self {
+            registry,
+            tvl,
+            share_price,
+            pending_redeem_shares,
+            pending_deallocation,
+            external_nav_observed_at,
+            last_success,
+            relay_seconds,
+            rpc_errors,
+        })
+    }
+
+    /// Record the vault gauges from a freshly read state
+    pub fn observe_vault(&self, state: &VaultState) {
+        self.tvl.set(state.total_assets as i64);
+        let price = if state.total_shares == 0 {
+            1.0
+        } else {
+            state.total_assets as f64 / state.total_shares as f64
+        };
+        self.share_price
+            .set(price * 10f64.powi(state.decimals_offset as i32));
+        self.pending_redeem_shares
+            .set(state.epoch_pending_redeems as i64);
+        self.pending_deallocation
+            .set(state.pending_deallocation as i64);
+        self.external_nav_observed_at
+            .set(state.external_nav_observed_at);
+    }
+
+    fn render(&self) -> Result<Vec<u8>> {
+        let mut body = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut body)?;
+        Ok(body)
+    }
+}
+
+/// Answer every connection on `addr` with the current metrics, whatever the
+/// request path
+pub async fn serve(metrics: Arc<Metrics>, addr: SocketAddr) -> Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            let mut request = [0u8; 1024];
+            if stream.read(&mut request).await.is_err() {
+                return;
+            }
+            let Ok(body) = metrics.render() else {
+                return;
+            };
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(head.as_bytes()).await;
+            let _ = stream.write_all(&body).await;
+        });
+    }
+}
@@ -0,0 +1,170 @@
+//! Snapshot airdrop allocations.
+//!
+//! Splits an airdrop pro rata over the holders recorded in one position
+//! history round and builds the Merkle root passed to `create_snapshot`,
+//! along with each holder's proof for `claim_airdrop`.
+
+use serde::Serialize;
+use solana_program::keccak::hashv;
+use solana_program::pubkey::Pubkey;
+use yield_vault::utils::airdrop_leaf;
+
+use crate::PositionHistoryTree;
+
+/// One holder's share of an airdrop
+#[derive(Clone, Debug, Serialize)]
+pub struct Allocation {
+    pub claimant: Pubkey,
+    pub amount: u64,
+}
+
+/// Split `total_amount` over the holders snapshotted in `round`, by share
+/// balance. Rounding dust stays in the airdrop and returns on clawback.
+pub fn allocate(history: &PositionHistoryTree, round: u64, total_amount: u64) -> Vec<Allocation> {
+    let holders = history.round_balances(round);
+    let total_shares: u128 = holders.iter().map(|(_, shares)| *shares as u128).sum();
+    if total_shares == 0 {
+        return Vec::new();
+    }
+    holders
+        .into_iter()
+        .map(|(claimant, shares)| Allocation {
+            claimant,
+            amount: (total_amount as u128 * shares as u128 / total_shares) as u64,
+        })
+        .filter(|allocation| allocation.amount > 0)
+        .collect()
+}
+
+fn hash_sorted(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    if a <= b {
+        hashv(&[a, b]).to_bytes()
+    } else {
+        hashv(&[b, a]).to_bytes()
+    }
+}
+
+/// Sorted-pair Merkle tree over allocations, matching `verify_sorted_proof`
+pub struct AirdropTree {
+    allocations: Vec<Allocation>,
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl AirdropTree {
+    pub fn new(allocations: Vec<Allocation>) -> Self {
+        let mut level: Vec<[u8; 32]> = allocations
+            .iter()
+            .map(|allocation| airdrop_leaf(&allocation.claimant, allocation.amount))
+            .collect();
+        let mut levels = Vec::new();
+        while level.len() > 1 {
+            // An unpaired node is promoted unchanged
+            let parents = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_sorted(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(std::mem::replace(&mut level, parents));
+        }
+        levels.push(level);
+        Self {
+            allocations,
+            levels,
+        }
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.levels
+            .last()
+            .and_then(|level| level.first())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The allocation and proof of `claimant`, if they are owed anything
+    pub fn proof(&self, claimant: &Pubkey) -> Option<(Allocation, Vec<[u8; 32]>)> {
+        let index = self
+            .allocations
+            .iter()
+            .position(|allocation| allocation.claimant == *claimant)?;
+        let mut position = index;
+        let mut proof = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(position ^ 1) {
+                proof.push(*sibling);
+            }
+            position /= 2;
+        }
+        Some((self.allocations[index].clone(), proof))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yield_vault::utils::verify_sorted_proof;
+
+    use super::*;
+
+    fn allocations(count: u8) -> Vec<Allocation> {
+        (1..=count)
+            .map(|byte| Allocation {
+                claimant: Pubkey::new_from_array([byte; 32]),
+                amount: byte as u64 * 1_000,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn leaf_and_node_hashes_match_the_verifier() {
+        // The leaf is keccak256 of the 40-byte claimant || amount
+        let claimant = Pubkey::new_from_array([7; 32]);
+        let mut preimage = [0u8; 40];
+        preimage[..32].copy_from_slice(claimant.as_ref());
+        preimage[32..].copy_from_slice(&5_000u64.to_le_bytes());
+        let leaf = hashv(&[&preimage]).to_bytes();
+        assert_eq!(airdrop_leaf(&claimant, 5_000), leaf);
+
+        // A node is keccak256 of the 64-byte sorted pair, in either order
+        let other = [0xff; 32];
+        let node = hashv(&[&[leaf, other].concat()]).to_bytes();
+        assert_eq!(hash_sorted(&leaf, &other), node);
+        assert_eq!(hash_sorted(&other, &leaf), node);
+        assert!(verify_sorted_proof(&[other], &node, leaf));
+    }
+
+    #[test]
+    fn every_claimant_verifies_on_chain() {
+        // Odd counts promote an unpaired node, which takes no proof step
+        for count in 1..=7 {
+            let tree = AirdropTree::new(allocations(count));
+            for byte in 1..=count {
+                let claimant = Pubkey::new_from_array([byte; 32]);
+                let (allocation, proof) = tree.proof(&claimant).unwrap();
+                assert_eq!(allocation.amount, byte as u64 * 1_000);
+                let leaf = airdrop_leaf(&claimant, allocation.amount);
+                assert!(verify_sorted_proof(&proof, &tree.root(), leaf));
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_a_tampered_leaf_or_proof() {
+        let tree = AirdropTree::new(allocations(5));
+        let claimant = Pubkey::new_from_array([2; 32]);
+        let (allocation, mut proof) = tree.proof(&claimant).unwrap();
+
+        let inflated = airdrop_leaf(&claimant, allocation.amount + 1);
+        assert!(!verify_sorted_proof(&proof, &tree.root(), inflated));
+        let impostor = airdrop_leaf(&Pubkey::new_from_array([9; 32]), allocation.amount);
+        assert!(!verify_sorted_proof(&proof, &tree.root(), impostor));
+
+        let leaf = airdrop_leaf(&claimant, allocation.amount);
+        proof[0][31] ^= 1;
+        assert!(!verify_sorted_proof(&proof, &tree.root(), leaf));
+        assert!(!verify_sorted_proof(&[], &tree.root(), leaf));
+        assert!(tree.proof(&Pubkey::new_from_array([9; 32])).is_none());
+    }
+}
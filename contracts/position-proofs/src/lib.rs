@@ -0,0 +1,269 @@
+//! Historical balance proofs for a yield vault's compressed position history.
+//!
+//! `record_position_snapshots` appends one leaf per holder to an SPL
+//! concurrent Merkle tree and emits a `PositionSnapshotted` event carrying
+//! the leaf's fields and index. Replaying those events rebuilds the tree, from
+//! which a proof of any holder's balance in any round can be produced and
+//! verified against the root stored in the on-chain tree account.
+
+use std::collections::HashMap;
+
+pub mod airdrop;
+
+use serde::Serialize;
+use solana_program::keccak::hashv;
+use solana_program::pubkey::Pubkey;
+use vault_events::PositionSnapshotted;
+use yield_vault::utils::position_snapshot_leaf;
+
+/// Leaf hash of a snapshot event, as computed on-chain
+pub fn leaf_of(event: &PositionSnapshotted) -> [u8; 32] {
+    position_snapshot_leaf(
+        &Pubkey::new_from_array(event.vault.to_bytes()),
+        &Pubkey::new_from_array(event.user.to_bytes()),
+        event.round,
+        event.shares,
+        event.total_assets,
+        event.total_shares,
+        event.slot,
+    )
+}
+
+/// Parent node hash, matching `spl-concurrent-merkle-tree`
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[left, right]).to_bytes()
+}
+
+/// A proof that `snapshot` is leaf `leaf_index` of the tree with `root`
+#[derive(Debug, Serialize)]
+pub struct SnapshotProof {
+    pub snapshot: PositionSnapshotted,
+    pub leaf: [u8; 32],
+    pub leaf_index: u64,
+    /// Sibling hashes from the leaf up to the root
+    pub proof: Vec<[u8; 32]>,
+    pub root: [u8; 32],
+}
+
+/// Off-chain replica of a vault's position history tree
+pub struct PositionHistoryTree {
+    depth: usize,
+    snapshots: Vec<PositionSnapshotted>,
+    /// First leaf recorded for each `(user, round)`
+    first: HashMap<(Pubkey, u64), usize>,
+}
+
+impl PositionHistoryTree {
+    /// Rebuild the tree of `depth` from its snapshot events. Every leaf from
+    /// 0 to the last must be present.
+    pub fn from_events(depth: usize, mut events: Vec<PositionSnapshotted>) -> anyhow::Result<Self> {
+        events.sort_by_key(|event| event.leaf_index);
+        let mut first = HashMap::new();
+        for (index, event) in events.iter().enumerate() {
+            anyhow::ensure!(
+                event.leaf_index == index as u64,
+                "missing snapshot for leaf {index}"
+            );
+            let user = Pubkey::new_from_array(event.user.to_bytes());
+            first.entry((user, event.round)).or_insert(index);
+        }
+        anyhow::ensure!(events.len() <= 1 << depth, "more snapshots than leaves");
+
+        Ok(Self {
+            depth,
+            snapshots: events,
+            first,
+        })
+    }
+
+    /// Hashes of every level, leaves first, with empty subtrees elided
+    fn levels(&self) -> Vec<Vec<[u8; 32]>> {
+        let mut empty = [0u8; 32];
+        let mut level: Vec<[u8; 32]> = self.snapshots.iter().map(leaf_of).collect();
+        let mut levels = Vec::with_capacity(self.depth + 1);
+        for _ in 0..self.depth {
+            let parents = level
+                .chunks(2)
+                .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&empty)))
+                .collect();
+            levels.push(std::mem::replace(&mut level, parents));
+            empty = hash_pair(&empty, &empty);
+        }
+        if level.is_empty() {
+            level.push(empty);
+        }
+        levels.push(level);
+        levels
+    }
+
+    /// Each holder's share balance in their first snapshot of `round`
+    pub fn round_balances(&self, round: u64) -> Vec<(Pubkey, u64)> {
+        let mut balances: Vec<_> = self
+            .first
+            .iter()
+            .filter(|((_, r), _)| *r == round)
+            .map(|((user, _), index)| (*user, self.snapshots[*index].shares))
+            .collect();
+        balances.sort_by_key(|(user, _)| *user);
+        balances
+    }
+
+    /// Current root of the tree
+    pub fn root(&self) -> [u8; 32] {
+        self.levels()[self.depth][0]
+    }
+
+    /// Proof of `user`'s first snapshot in `round`, if one was recorded
+    pub fn prove(&self, user: &Pubkey, round: u64) -> Option<SnapshotProof> {
+        let index = *self.first.get(&(*user, round))?;
+        let levels = self.levels();
+
+        let mut empty = [0u8; 32];
+        let mut proof = Vec::with_capacity(self.depth);
+        let mut position = index;
+        for level in &levels[..self.depth] {
+            proof.push(*level.get(position ^ 1).unwrap_or(&empty));
+            empty = hash_pair(&empty, &empty);
+            position /= 2;
+        }
+
+        Some(SnapshotProof {
+            snapshot: self.snapshots[index].clone(),
+            leaf: leaf_of(&self.snapshots[index]),
+            leaf_index: index as u64,
+            proof,
+            root: levels[self.depth][0],
+        })
+    }
+}
+
+/// Check `proof` against its root
+pub fn verify(proof: &SnapshotProof) -> bool {
+    let mut node = leaf_of(&proof.snapshot);
+    let mut position = proof.leaf_index;
+    for sibling in &proof.proof {
+        node = if position.is_multiple_of(2) {
+            hash_pair(&node, sibling)
+        } else {
+            hash_pair(sibling, &node)
+        };
+        position /= 2;
+    }
+    node == proof.root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEPTH: usize = 3;
+
+    fn snapshot(leaf_index: u64, user: u8, round: u64, shares: u64) -> PositionSnapshotted {
+        PositionSnapshotted {
+            vault: vault_events::Pubkey::new_from_array([9; 32]),
+            event_seq: leaf_index,
+            user: vault_events::Pubkey::new_from_array([user; 32]),
+            round,
+            leaf_index,
+            shares,
+            total_assets: 3_000,
+            total_shares: 3_000,
+            slot: 100 + round,
+        }
+    }
+
+    /// Three holders snapshotted in round 1, two of them again in round 2
+    fn tree() -> PositionHistoryTree {
+        let events = vec![
+            snapshot(0, 1, 1, 1_000),
+            snapshot(1, 2, 1, 500),
+            snapshot(2, 3, 1, 1_500),
+            snapshot(3, 1, 2, 1_200),
+            snapshot(4, 2, 2, 300),
+        ];
+        PositionHistoryTree::from_events(DEPTH, events).unwrap()
+    }
+
+    fn user(byte: u8) -> Pubkey {
+        Pubkey::new_from_array([byte; 32])
+    }
+
+    #[test]
+    fn proves_every_snapshot() {
+        let tree = tree();
+        for (byte, round, index) in [(1, 1, 0), (2, 1, 1), (3, 1, 2), (1, 2, 3), (2, 2, 4)] {
+            let proof = tree.prove(&user(byte), round).unwrap();
+            assert_eq!(proof.leaf_index, index);
+            assert_eq!(proof.proof.len(), DEPTH);
+            assert_eq!(proof.root, tree.root());
+            assert!(verify(&proof));
+        }
+        assert!(tree.prove(&user(3), 2).is_none());
+    }
+
+    #[test]
+    fn rejects_a_tampered_leaf() {
+        let mut proof = tree().prove(&user(2), 1).unwrap();
+        proof.snapshot.shares += 1;
+        assert!(!verify(&proof));
+    }
+
+    #[test]
+    fn rejects_a_tampered_proof() {
+        let tree = tree();
+        let mut proof = tree.prove(&user(2), 1).unwrap();
+        proof.proof[1][0] ^= 1;
+        assert!(!verify(&proof));
+
+        // A valid path claimed for another leaf position fails too
+        let mut proof = tree.prove(&user(2), 1).unwrap();
+        proof.leaf_index = 0;
+        assert!(!verify(&proof));
+    }
+
+    #[test]
+    fn root_follows_the_concurrent_merkle_tree() {
+        // Leaves hash the event fields as `record_position_snapshots` does,
+        // and each parent is keccak256 of its 64-byte children
+        let event = snapshot(0, 1, 1, 1_000);
+        let mut preimage = Vec::new();
+        preimage.extend_from_slice(&event.vault.to_bytes());
+        preimage.extend_from_slice(&event.user.to_bytes());
+        for field in [event.round, event.shares, event.total_assets, event.total_shares, event.slot]
+        {
+            preimage.extend_from_slice(&field.to_le_bytes());
+        }
+        let leaf = hashv(&[&preimage]).to_bytes();
+        assert_eq!(leaf_of(&event), leaf);
+
+        let tree = PositionHistoryTree::from_events(1, vec![event]).unwrap();
+        let node = hashv(&[&[leaf, [0; 32]].concat()]).to_bytes();
+        assert_eq!(tree.root(), node);
+
+        // An empty tree's root is the zero leaf hashed up every level
+        let empty = PositionHistoryTree::from_events(2, Vec::new()).unwrap();
+        let level_1 = hash_pair(&[0; 32], &[0; 32]);
+        assert_eq!(empty.root(), hash_pair(&level_1, &level_1));
+    }
+
+    #[test]
+    fn rejects_gaps_and_overflow() {
+        let gap = vec![snapshot(0, 1, 1, 1_000), snapshot(2, 2, 1, 500)];
+        assert!(PositionHistoryTree::from_events(DEPTH, gap).is_err());
+        let full = (0..3).map(|index| snapshot(index, 1, index, 1)).collect();
+        assert!(PositionHistoryTree::from_events(1, full).is_err());
+    }
+
+    #[test]
+    fn round_balances_take_the_first_snapshot() {
+        let mut events: Vec<_> = (0..3).map(|i| snapshot(i, i as u8 + 1, 1, 100)).collect();
+        // A second snapshot of the same holder in the round is ignored
+        events.push(snapshot(3, 1, 1, 900));
+        let tree = PositionHistoryTree::from_events(DEPTH, events).unwrap();
+        assert_eq!(
+            tree.round_balances(1),
+            [(user(1), 100), (user(2), 100), (user(3), 100)]
+        );
+        assert!(tree.round_balances(2).is_empty());
+    }
+}
@@ -0,0 +1,92 @@
+//! Serves historical balance proofs from a vault's snapshot events.
+//!
+//! Reads `PositionSnapshotted` events as JSON lines (the serde form of
+//! `vault_events::VaultEvent`, as written by the indexer). `prove` prints the
+//! proof of a holder's balance in a round; `airdrop` splits an amount over a
+//! round's holders and prints the root for `create_snapshot`, plus a holder's
+//! claim proof when one is given:
+//!
+//! ```text
+//! SOLANA_VAULT=<vault> SNAPSHOT_EVENTS=events.jsonl TREE_DEPTH=20 \
+//!     position-proofs prove <user> <round>
+//! SOLANA_VAULT=<vault> SNAPSHOT_EVENTS=events.jsonl TREE_DEPTH=20 \
+//!     position-proofs airdrop <round> <total_amount> [claimant]
+//! ```
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use anyhow::{anyhow, bail, Context, Result};
+use position_proofs::airdrop::{allocate, AirdropTree};
+use position_proofs::{verify, PositionHistoryTree};
+use solana_program::pubkey::Pubkey;
+use vault_events::VaultEvent;
+
+const USAGE: &str =
+    "usage: position-proofs prove <user> <round> | airdrop <round> <total_amount> [claimant]";
+
+fn load_history(vault: &Pubkey, path: &str, depth: usize) -> Result<PositionHistoryTree> {
+    let mut snapshots = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let VaultEvent::PositionSnapshotted(event) = serde_json::from_str(&line)? {
+            if event.vault.to_bytes() == vault.to_bytes() {
+                snapshots.push(event);
+            }
+        }
+    }
+    PositionHistoryTree::from_events(depth, snapshots)
+}
+
+fn main() -> Result<()> {
+    let vault: Pubkey = std::env::var("SOLANA_VAULT")
+        .context("SOLANA_VAULT is not set")?
+        .parse()?;
+    let events_path = std::env::var("SNAPSHOT_EVENTS").context("SNAPSHOT_EVENTS is not set")?;
+    let depth: usize = std::env::var("TREE_DEPTH")
+        .context("TREE_DEPTH is not set")?
+        .parse()?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let arg = |index: usize| args.get(index).ok_or_else(|| anyhow!(USAGE));
+    let history = load_history(&vault, &events_path, depth)?;
+
+    match arg(0)?.as_str() {
+        "prove" => {
+            let user: Pubkey = arg(1)?.parse()?;
+            let round: u64 = arg(2)?.parse()?;
+            let proof = history
+                .prove(&user, round)
+                .ok_or_else(|| anyhow!("no snapshot of {user} in round {round}"))?;
+            anyhow::ensure!(
+                verify(&proof),
+                "proof does not verify against the replayed root"
+            );
+            println!("{}", serde_json::to_string_pretty(&proof)?);
+        }
+        "airdrop" => {
+            let round: u64 = arg(1)?.parse()?;
+            let total_amount: u64 = arg(2)?.parse()?;
+            let tree = AirdropTree::new(allocate(&history, round, total_amount));
+            let claim = match args.get(3) {
+                Some(claimant) => Some(
+                    tree.proof(&claimant.parse()?)
+                        .ok_or_else(|| anyhow!("{claimant} has no allocation"))?,
+                ),
+                None => None,
+            };
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&serde_json::json!({
+                    "merkle_root": tree.root(),
+                    "claim": claim,
+                }))?
+            );
+        }
+        _ => bail!(USAGE),
+    }
+    Ok(())
+}
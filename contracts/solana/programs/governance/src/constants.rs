@@ -0,0 +1,20 @@
+/// PDA seed for governance accounts
+pub const GOVERNANCE_SEED: &[u8] = b"governance";
+
+/// PDA seed for the governance escrow token account (holds shares locked by voters)
+pub const ESCROW_SEED: &[u8] = b"escrow";
+
+/// PDA seed for proposal accounts
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+
+/// PDA seed for vote record accounts
+pub const VOTE_SEED: &[u8] = b"vote";
+
+/// Maximum number of accounts a proposal instruction may reference
+pub const MAX_PROPOSAL_ACCOUNTS: usize = 16;
+
+/// Maximum size of a proposal's instruction data in bytes
+pub const MAX_PROPOSAL_DATA_LEN: usize = 512;
+
+/// Minimum voting period (1 hour)
+pub const MIN_VOTING_PERIOD: i64 = 3_600;
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum GovernanceError {
+    #[msg("Voting period is below the minimum allowed")]
+    InvalidVotingPeriod,
+
+    #[msg("Timelock delay must not be negative")]
+    InvalidTimelockDelay,
+
+    #[msg("Proposal references too many accounts")]
+    TooManyAccounts,
+
+    #[msg("Proposal instruction data is too large")]
+    DataTooLarge,
+
+    #[msg("Proposals may not target the governance program itself")]
+    InvalidTargetProgram,
+
+    #[msg("Proposer must hold voting shares")]
+    NoVotingPower,
+
+    #[msg("Vote amount must be greater than zero")]
+    InvalidAmount,
+
+    #[msg("Proposal is not open for voting")]
+    VotingClosed,
+
+    #[msg("Voting period has not ended yet")]
+    VotingNotEnded,
+
+    #[msg("Proposal is not in the voting state")]
+    InvalidProposalStatus,
+
+    #[msg("Proposal timelock has not expired")]
+    TimelockNotExpired,
+
+    #[msg("Proposal did not pass")]
+    ProposalNotPassed,
+
+    #[msg("Remaining accounts do not match the proposal instruction")]
+    AccountMismatch,
+
+    #[msg("Caller is not authorized for this operation")]
+    Unauthorized,
+
+    #[msg("Arithmetic overflow or underflow")]
+    ArithmeticOverflow,
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct GovernanceCreated {
+    pub governance: Pubkey,
+    pub share_mint: Pubkey,
+    pub creator: Pubkey,
+    pub voting_period: i64,
+    pub timelock_delay: i64,
+    pub quorum: u64,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub governance: Pubkey,
+    pub proposal: Pubkey,
+    pub proposer: Pubkey,
+    pub index: u64,
+    pub program_id: Pubkey,
+    pub description_hash: [u8; 32],
+    pub voting_ends_at: i64,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct VoteCast {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub approve: bool,
+    pub weight: u64,
+}
+
+#[event]
+pub struct VoteRelinquished {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub weight: u64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal: Pubkey,
+    pub program_id: Pubkey,
+    pub votes_for: u64,
+    pub votes_against: u64,
+}
+
+#[event]
+pub struct ProposalCancelled {
+    pub proposal: Pubkey,
+}
@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::constants::*;
+use crate::error::GovernanceError;
+use crate::events::GovernanceCreated;
+use crate::state::Governance;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateGovernanceParams {
+    pub voting_period: i64,
+    pub timelock_delay: i64,
+    pub quorum: u64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateGovernance<'info> {
+    /// The account creating (and paying for) the governance
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// The vault share mint used for voting power
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    /// The governance state account (PDA) — also the signing authority for executed proposals
+    #[account(
+        init,
+        payer = creator,
+        space = Governance::LEN,
+        seeds = [GOVERNANCE_SEED, share_mint.key().as_ref(), creator.key().as_ref()],
+        bump,
+    )]
+    pub governance: Box<Account<'info, Governance>>,
+
+    /// Escrow token account (PDA) — holds shares locked by voters
+    #[account(
+        init,
+        payer = creator,
+        seeds = [ESCROW_SEED, governance.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = governance,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(ctx: Context<CreateGovernance>, params: CreateGovernanceParams) -> Result<()> {
+    require!(
+        params.voting_period >= MIN_VOTING_PERIOD,
+        GovernanceError::InvalidVotingPeriod
+    );
+    require!(
+        params.timelock_delay >= 0,
+        GovernanceError::InvalidTimelockDelay
+    );
+
+    let governance = &mut ctx.accounts.governance;
+    governance.creator = ctx.accounts.creator.key();
+    governance.share_mint = ctx.accounts.share_mint.key();
+    governance.voting_period = params.voting_period;
+    governance.timelock_delay = params.timelock_delay;
+    governance.quorum = params.quorum;
+    governance.proposal_count = 0;
+    governance.bump = ctx.bumps.governance;
+    governance.escrow_bump = ctx.bumps.escrow;
+
+    emit_cpi!(GovernanceCreated {
+        governance: governance.key(),
+        share_mint: governance.share_mint,
+        creator: governance.creator,
+        voting_period: params.voting_period,
+        timelock_delay: params.timelock_delay,
+        quorum: params.quorum,
+    });
+
+    Ok(())
+}
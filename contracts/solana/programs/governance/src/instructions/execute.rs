@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+use crate::constants::*;
+use crate::error::GovernanceError;
+use crate::events::ProposalExecuted;
+use crate::state::{Governance, Proposal, ProposalStatus};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        seeds = [GOVERNANCE_SEED, governance.share_mint.as_ref(), governance.creator.as_ref()],
+        bump = governance.bump,
+    )]
+    pub governance: Box<Account<'info, Governance>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PROPOSAL_SEED,
+            governance.key().as_ref(),
+            &proposal.index.to_le_bytes(),
+        ],
+        bump = proposal.bump,
+        has_one = governance,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+    // Remaining accounts: every account referenced by the proposal
+    // instruction, plus the target program account.
+}
+
+/// Permissionless: anyone may execute a passed proposal once its timelock expires.
+/// The governance PDA signs the stored instruction, so it can act as the vault
+/// owner or as the program upgrade authority.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>) -> Result<()> {
+    let clock = Clock::get()?;
+    let governance = &ctx.accounts.governance;
+    let proposal = &ctx.accounts.proposal;
+
+    require!(
+        proposal.status == ProposalStatus::Voting,
+        GovernanceError::InvalidProposalStatus
+    );
+    require!(
+        clock.unix_timestamp >= proposal.executable_at,
+        GovernanceError::TimelockNotExpired
+    );
+    require!(
+        governance.is_passed(proposal.votes_for, proposal.votes_against),
+        GovernanceError::ProposalNotPassed
+    );
+
+    let remaining = ctx.remaining_accounts;
+    require!(
+        remaining.iter().any(|a| a.key() == proposal.program_id),
+        GovernanceError::AccountMismatch
+    );
+    for meta in proposal.accounts.iter() {
+        require!(
+            remaining.iter().any(|a| a.key() == meta.pubkey),
+            GovernanceError::AccountMismatch
+        );
+    }
+
+    let instruction = Instruction {
+        program_id: proposal.program_id,
+        accounts: proposal
+            .accounts
+            .iter()
+            .map(|meta| AccountMeta {
+                pubkey: meta.pubkey,
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: proposal.data.clone(),
+    };
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        GOVERNANCE_SEED,
+        governance.share_mint.as_ref(),
+        governance.creator.as_ref(),
+        &[governance.bump],
+    ]];
+
+    invoke_signed(&instruction, remaining, signer_seeds)?;
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.status = ProposalStatus::Executed;
+
+    emit_cpi!(ProposalExecuted {
+        proposal: proposal.key(),
+        program_id: proposal.program_id,
+        votes_for: proposal.votes_for,
+        votes_against: proposal.votes_against,
+    });
+
+    Ok(())
+}
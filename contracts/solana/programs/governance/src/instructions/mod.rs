@@ -0,0 +1,9 @@
+pub mod create_governance;
+pub mod proposal;
+pub mod vote;
+pub mod execute;
+
+pub use create_governance::*;
+pub use proposal::*;
+pub use vote::*;
+pub use execute::*;
@@ -0,0 +1,169 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::constants::*;
+use crate::error::GovernanceError;
+use crate::events::{ProposalCancelled, ProposalCreated};
+use crate::state::{Governance, Proposal, ProposalAccountMeta, ProposalStatus};
+
+// ──────────────────────────────────────────
+// Create Proposal
+// ──────────────────────────────────────────
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateProposalParams {
+    pub description_hash: [u8; 32],
+    pub program_id: Pubkey,
+    pub accounts: Vec<ProposalAccountMeta>,
+    pub data: Vec<u8>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(params: CreateProposalParams)]
+pub struct CreateProposal<'info> {
+    /// The share holder creating the proposal
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GOVERNANCE_SEED, governance.share_mint.as_ref(), governance.creator.as_ref()],
+        bump = governance.bump,
+    )]
+    pub governance: Box<Account<'info, Governance>>,
+
+    /// The proposer's share token account (must hold voting shares)
+    #[account(
+        token::mint = governance.share_mint,
+        token::authority = proposer,
+    )]
+    pub proposer_share_account: Box<Account<'info, TokenAccount>>,
+
+    /// The proposal account (PDA), sized for the proposed instruction
+    #[account(
+        init,
+        payer = proposer,
+        space = Proposal::space(params.accounts.len(), params.data.len()),
+        seeds = [
+            PROPOSAL_SEED,
+            governance.key().as_ref(),
+            &governance.proposal_count.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_create_proposal(
+    ctx: Context<CreateProposal>,
+    params: CreateProposalParams,
+) -> Result<()> {
+    require!(
+        params.accounts.len() <= MAX_PROPOSAL_ACCOUNTS,
+        GovernanceError::TooManyAccounts
+    );
+    require!(
+        params.data.len() <= MAX_PROPOSAL_DATA_LEN,
+        GovernanceError::DataTooLarge
+    );
+    require!(
+        params.program_id != crate::ID,
+        GovernanceError::InvalidTargetProgram
+    );
+    require!(
+        ctx.accounts.proposer_share_account.amount > 0,
+        GovernanceError::NoVotingPower
+    );
+
+    let clock = Clock::get()?;
+    let governance = &mut ctx.accounts.governance;
+
+    let voting_ends_at = clock
+        .unix_timestamp
+        .checked_add(governance.voting_period)
+        .ok_or(GovernanceError::ArithmeticOverflow)?;
+    let executable_at = voting_ends_at
+        .checked_add(governance.timelock_delay)
+        .ok_or(GovernanceError::ArithmeticOverflow)?;
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.governance = governance.key();
+    proposal.proposer = ctx.accounts.proposer.key();
+    proposal.index = governance.proposal_count;
+    proposal.description_hash = params.description_hash;
+    proposal.program_id = params.program_id;
+    proposal.accounts = params.accounts;
+    proposal.data = params.data;
+    proposal.votes_for = 0;
+    proposal.votes_against = 0;
+    proposal.voting_ends_at = voting_ends_at;
+    proposal.executable_at = executable_at;
+    proposal.status = ProposalStatus::Voting;
+    proposal.bump = ctx.bumps.proposal;
+
+    governance.proposal_count = governance
+        .proposal_count
+        .checked_add(1)
+        .ok_or(GovernanceError::ArithmeticOverflow)?;
+
+    emit_cpi!(ProposalCreated {
+        governance: governance.key(),
+        proposal: proposal.key(),
+        proposer: proposal.proposer,
+        index: proposal.index,
+        program_id: proposal.program_id,
+        description_hash: proposal.description_hash,
+        voting_ends_at,
+        executable_at,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Cancel Proposal
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    /// The original proposer
+    pub proposer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            PROPOSAL_SEED,
+            proposal.governance.as_ref(),
+            &proposal.index.to_le_bytes(),
+        ],
+        bump = proposal.bump,
+        has_one = proposer @ GovernanceError::Unauthorized,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+}
+
+pub fn handle_cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    let clock = Clock::get()?;
+
+    require!(
+        proposal.status == ProposalStatus::Voting,
+        GovernanceError::InvalidProposalStatus
+    );
+    require!(
+        clock.unix_timestamp < proposal.voting_ends_at,
+        GovernanceError::VotingClosed
+    );
+
+    proposal.status = ProposalStatus::Cancelled;
+
+    emit_cpi!(ProposalCancelled {
+        proposal: proposal.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,216 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::error::GovernanceError;
+use crate::events::{VoteCast, VoteRelinquished};
+use crate::state::{Governance, Proposal, ProposalStatus, VoteRecord};
+
+// ──────────────────────────────────────────
+// Cast Vote
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    /// The share holder casting the vote
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(
+        seeds = [GOVERNANCE_SEED, governance.share_mint.as_ref(), governance.creator.as_ref()],
+        bump = governance.bump,
+    )]
+    pub governance: Box<Account<'info, Governance>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PROPOSAL_SEED,
+            governance.key().as_ref(),
+            &proposal.index.to_le_bytes(),
+        ],
+        bump = proposal.bump,
+        has_one = governance,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    /// The voter's share token account (shares are locked from here)
+    #[account(
+        mut,
+        token::mint = governance.share_mint,
+        token::authority = voter,
+    )]
+    pub voter_share_account: Box<Account<'info, TokenAccount>>,
+
+    /// Governance escrow (receives locked shares)
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, governance.key().as_ref()],
+        bump = governance.escrow_bump,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    /// One vote record per voter per proposal
+    #[account(
+        init,
+        payer = voter,
+        space = VoteRecord::LEN,
+        seeds = [VOTE_SEED, proposal.key().as_ref(), voter.key().as_ref()],
+        bump,
+    )]
+    pub vote_record: Box<Account<'info, VoteRecord>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_cast_vote(ctx: Context<CastVote>, approve: bool, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let proposal = &ctx.accounts.proposal;
+
+    require!(amount > 0, GovernanceError::InvalidAmount);
+    require!(
+        proposal.status == ProposalStatus::Voting,
+        GovernanceError::InvalidProposalStatus
+    );
+    require!(
+        clock.unix_timestamp < proposal.voting_ends_at,
+        GovernanceError::VotingClosed
+    );
+
+    // Lock the voter's shares for the lifetime of the vote so the same
+    // shares cannot be counted twice from different wallets
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.voter_share_account.to_account_info(),
+                to: ctx.accounts.escrow.to_account_info(),
+                authority: ctx.accounts.voter.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let proposal = &mut ctx.accounts.proposal;
+    if approve {
+        proposal.votes_for = proposal
+            .votes_for
+            .checked_add(amount)
+            .ok_or(GovernanceError::ArithmeticOverflow)?;
+    } else {
+        proposal.votes_against = proposal
+            .votes_against
+            .checked_add(amount)
+            .ok_or(GovernanceError::ArithmeticOverflow)?;
+    }
+
+    let vote_record = &mut ctx.accounts.vote_record;
+    vote_record.proposal = proposal.key();
+    vote_record.voter = ctx.accounts.voter.key();
+    vote_record.weight = amount;
+    vote_record.approve = approve;
+    vote_record.bump = ctx.bumps.vote_record;
+
+    emit_cpi!(VoteCast {
+        proposal: proposal.key(),
+        voter: vote_record.voter,
+        approve,
+        weight: amount,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Relinquish Vote
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RelinquishVote<'info> {
+    /// The voter reclaiming locked shares
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(
+        seeds = [GOVERNANCE_SEED, governance.share_mint.as_ref(), governance.creator.as_ref()],
+        bump = governance.bump,
+    )]
+    pub governance: Box<Account<'info, Governance>>,
+
+    #[account(has_one = governance)]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    /// The vote record — closed and rent returned to the voter
+    #[account(
+        mut,
+        seeds = [VOTE_SEED, proposal.key().as_ref(), voter.key().as_ref()],
+        bump = vote_record.bump,
+        has_one = voter @ GovernanceError::Unauthorized,
+        has_one = proposal,
+        close = voter,
+    )]
+    pub vote_record: Box<Account<'info, VoteRecord>>,
+
+    /// The voter's share token account (receives unlocked shares)
+    #[account(
+        mut,
+        token::mint = governance.share_mint,
+        token::authority = voter,
+    )]
+    pub voter_share_account: Box<Account<'info, TokenAccount>>,
+
+    /// Governance escrow (returns locked shares)
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, governance.key().as_ref()],
+        bump = governance.escrow_bump,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle_relinquish_vote(ctx: Context<RelinquishVote>) -> Result<()> {
+    let clock = Clock::get()?;
+    let proposal = &ctx.accounts.proposal;
+
+    // Shares stay locked until the outcome can no longer change
+    require!(
+        proposal.status != ProposalStatus::Voting
+            || clock.unix_timestamp >= proposal.voting_ends_at,
+        GovernanceError::VotingNotEnded
+    );
+
+    let governance = &ctx.accounts.governance;
+    let weight = ctx.accounts.vote_record.weight;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        GOVERNANCE_SEED,
+        governance.share_mint.as_ref(),
+        governance.creator.as_ref(),
+        &[governance.bump],
+    ]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow.to_account_info(),
+                to: ctx.accounts.voter_share_account.to_account_info(),
+                authority: ctx.accounts.governance.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        weight,
+    )?;
+
+    emit_cpi!(VoteRelinquished {
+        proposal: proposal.key(),
+        voter: ctx.accounts.voter.key(),
+        weight,
+    });
+
+    Ok(())
+}
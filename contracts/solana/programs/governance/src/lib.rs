@@ -0,0 +1,62 @@
+//! Share-holder governance for DeFAI vaults.
+//!
+//! A governance PDA is created per vault share mint. Share holders lock shares
+//! to vote on proposals; a passed proposal's instruction is executed with the
+//! governance PDA as signer after a timelock. Setting the PDA as a vault's owner
+//! and as the yield_vault program's upgrade authority moves both config changes
+//! and upgrades behind a holder vote instead of a single key.
+
+use anchor_lang::prelude::*;
+
+pub mod constants;
+pub mod error;
+pub mod events;
+pub mod instructions;
+pub mod state;
+
+use instructions::*;
+
+declare_id!("GoV1111111111111111111111111111111111111111");
+
+#[program]
+pub mod governance {
+    use super::*;
+
+    /// Create a governance for a vault share mint, with its voting escrow.
+    pub fn create_governance(
+        ctx: Context<CreateGovernance>,
+        params: CreateGovernanceParams,
+    ) -> Result<()> {
+        instructions::create_governance::handler(ctx, params)
+    }
+
+    /// Propose an instruction to be executed by the governance PDA. Share holders only.
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        params: CreateProposalParams,
+    ) -> Result<()> {
+        instructions::proposal::handle_create_proposal(ctx, params)
+    }
+
+    /// Cancel a proposal while voting is still open. Proposer only.
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        instructions::proposal::handle_cancel_proposal(ctx)
+    }
+
+    /// Lock shares in escrow and vote for or against a proposal.
+    pub fn cast_vote(ctx: Context<CastVote>, approve: bool, amount: u64) -> Result<()> {
+        instructions::vote::handle_cast_vote(ctx, approve, amount)
+    }
+
+    /// Reclaim locked shares once voting has ended or the proposal is resolved.
+    pub fn relinquish_vote(ctx: Context<RelinquishVote>) -> Result<()> {
+        instructions::vote::handle_relinquish_vote(ctx)
+    }
+
+    /// Execute a passed proposal after its timelock. Permissionless.
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>,
+    ) -> Result<()> {
+        instructions::execute::handler(ctx)
+    }
+}
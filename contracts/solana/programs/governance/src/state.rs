@@ -0,0 +1,166 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct Governance {
+    /// Account that created this governance — part of the PDA seeds
+    pub creator: Pubkey,
+    /// Vault share mint whose holders vote on proposals
+    pub share_mint: Pubkey,
+    /// Length of the voting window in seconds
+    pub voting_period: i64,
+    /// Delay between the end of voting and earliest execution, in seconds
+    pub timelock_delay: i64,
+    /// Minimum "for" votes (in share units) for a proposal to pass
+    pub quorum: u64,
+    /// Number of proposals created so far (next proposal index)
+    pub proposal_count: u64,
+
+    /// PDA bump seeds
+    pub bump: u8,
+    pub escrow_bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl Governance {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 8 + 8 + 8 + 8 + 1 + 1 + 64 = 162
+    pub const LEN: usize = 8 + 162;
+
+    /// Check whether a proposal with the given tallies has passed
+    pub fn is_passed(&self, votes_for: u64, votes_against: u64) -> bool {
+        votes_for > votes_against && votes_for >= self.quorum
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalStatus {
+    Voting,
+    Executed,
+    Cancelled,
+}
+
+/// Serializable form of an `AccountMeta` for the proposal instruction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProposalAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl ProposalAccountMeta {
+    /// 32 + 1 + 1
+    pub const LEN: usize = 34;
+}
+
+#[account]
+pub struct Proposal {
+    /// Governance this proposal belongs to
+    pub governance: Pubkey,
+    /// Share holder who created the proposal
+    pub proposer: Pubkey,
+    /// Sequential index within the governance
+    pub index: u64,
+    /// Hash of the off-chain description document
+    pub description_hash: [u8; 32],
+
+    /// Instruction executed by the governance PDA once the proposal passes
+    pub program_id: Pubkey,
+    pub accounts: Vec<ProposalAccountMeta>,
+    pub data: Vec<u8>,
+
+    /// Vote tallies (in share units)
+    pub votes_for: u64,
+    pub votes_against: u64,
+
+    /// End of the voting window (unix timestamp)
+    pub voting_ends_at: i64,
+    /// Earliest execution time (voting end + timelock delay)
+    pub executable_at: i64,
+
+    pub status: ProposalStatus,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl Proposal {
+    /// Account discriminator (8) + fixed fields + variable-length instruction
+    /// 32 + 32 + 8 + 32 + 32 + (4 + 34n) + (4 + d) + 8 + 8 + 8 + 8 + 1 + 1 + 64
+    pub fn space(num_accounts: usize, data_len: usize) -> usize {
+        8 + 32
+            + 32
+            + 8
+            + 32
+            + 32
+            + 4
+            + ProposalAccountMeta::LEN * num_accounts
+            + 4
+            + data_len
+            + 8
+            + 8
+            + 8
+            + 8
+            + 1
+            + 1
+            + 64
+    }
+}
+
+#[account]
+pub struct VoteRecord {
+    /// Proposal this vote was cast on
+    pub proposal: Pubkey,
+    /// Voter who locked shares in escrow
+    pub voter: Pubkey,
+    /// Shares locked in escrow (vote weight)
+    pub weight: u64,
+    /// Whether the vote is in favour of the proposal
+    pub approve: bool,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl VoteRecord {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 8 + 1 + 1 + 64 = 138
+    pub const LEN: usize = 8 + 138;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An account deserialized from `len` zeroed bytes, failing unless every
+    /// byte is read
+    fn zeroed<T: AnchorDeserialize>(len: usize) -> T {
+        T::try_from_slice(&vec![0; len]).unwrap()
+    }
+
+    /// Space an account takes, discriminator included
+    fn space<T: AnchorSerialize>(account: &T) -> usize {
+        8 + account.try_to_vec().unwrap().len()
+    }
+
+    #[test]
+    fn lens_match_serialized_size() {
+        assert_eq!(space(&zeroed::<Governance>(Governance::LEN - 8)), Governance::LEN);
+        assert_eq!(space(&zeroed::<VoteRecord>(VoteRecord::LEN - 8)), VoteRecord::LEN);
+    }
+
+    #[test]
+    fn proposal_space_fits_its_instruction() {
+        let mut proposal = zeroed::<Proposal>(Proposal::space(0, 0) - 8);
+        assert_eq!(space(&proposal), Proposal::space(0, 0));
+
+        let meta = ProposalAccountMeta {
+            pubkey: Pubkey::new_unique(),
+            is_signer: true,
+            is_writable: true,
+        };
+        proposal.accounts = vec![meta; 3];
+        proposal.data = vec![1; 100];
+        assert_eq!(space(&proposal), Proposal::space(3, 100));
+    }
+}
@@ -0,0 +1,53 @@
+//! Test fixture that turns on its caller.
+//!
+//! Whitelisted as a zap DEX (or wired in as a strategy adapter), it calls
+//! straight back into the yield vault with the accounts it was handed, the
+//! way a compromised integration would try to deposit against a vault whose
+//! state is mid-update. The vault must reject every such call. Localnet only.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::InstructionData;
+use yield_vault::program::YieldVault;
+
+declare_id!("MAL1111111111111111111111111111111111111111");
+
+#[program]
+pub mod malicious_adapter {
+    use super::*;
+
+    /// Posing as a swap, re-enter `yield_vault::deposit` for `amount`. The
+    /// remaining accounts are the deposit's accounts, in `Deposit` order.
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, Reenter<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let accounts = ctx
+            .remaining_accounts
+            .iter()
+            .map(|info| AccountMeta {
+                pubkey: info.key(),
+                is_signer: info.is_signer,
+                is_writable: info.is_writable,
+            })
+            .collect();
+        let mut infos = ctx.remaining_accounts.to_vec();
+        infos.push(ctx.accounts.yield_vault_program.to_account_info());
+
+        invoke(
+            &Instruction {
+                program_id: yield_vault::ID,
+                accounts,
+                data: yield_vault::instruction::Deposit { amount, memo: None }.data(),
+            },
+            &infos,
+        )?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Reenter<'info> {
+    pub yield_vault_program: Program<'info, YieldVault>,
+}
@@ -0,0 +1,23 @@
+/// PDA seed for meta vault accounts
+pub const META_VAULT_SEED: &[u8] = b"meta_vault";
+
+/// PDA seed for the system-owned account that holds the meta vault's tokens
+/// and acts as its user in the child vaults
+pub const META_AUTHORITY_SEED: &[u8] = b"meta_authority";
+
+/// PDA seed for the meta vault share mint
+pub const META_SHARE_MINT_SEED: &[u8] = b"meta_share_mint";
+
+/// PDA seed for the token account holding unallocated underlying
+pub const META_ASSETS_SEED: &[u8] = b"meta_assets";
+
+/// PDA seed for the token account holding a child vault's shares
+pub const CHILD_SHARES_SEED: &[u8] = b"child_shares";
+
+/// Maximum number of child vaults per meta vault
+pub const MAX_CHILDREN: usize = 8;
+
+/// Extra decimals meta vault shares carry over the underlying. The first
+/// deposit mints `10^META_DECIMALS_OFFSET` shares per asset unit, and as many
+/// virtual shares back the share price against donations.
+pub const META_DECIMALS_OFFSET: u8 = 3;
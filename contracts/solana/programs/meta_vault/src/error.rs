@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum MetaVaultError {
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+
+    #[msg("Only the meta vault's manager can do this")]
+    Unauthorized,
+
+    #[msg("Weights must cover every child and sum to at most 10000 bps")]
+    InvalidWeights,
+
+    #[msg("Maximum number of child vaults reached")]
+    MaxChildrenReached,
+
+    #[msg("Child vault is already part of the meta vault")]
+    ChildAlreadyAdded,
+
+    #[msg("Child vault index out of range")]
+    InvalidChild,
+
+    #[msg("Child vault accounts do not match the meta vault's children")]
+    ChildAccountsMismatch,
+
+    #[msg("Child vault holds a different underlying")]
+    MintMismatch,
+
+    #[msg("Allocation would take the child vault above its weight")]
+    WeightExceeded,
+
+    #[msg("Not enough unallocated underlying to pay the withdrawal")]
+    InsufficientLiquidity,
+
+    #[msg("Underlying has too many decimals for the meta vault's shares")]
+    UnsupportedDecimals,
+
+    #[msg("Arithmetic overflow or underflow")]
+    ArithmeticOverflow,
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct MetaVaultCreated {
+    pub meta_vault: Pubkey,
+    pub owner: Pubkey,
+    pub manager: Pubkey,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct ChildVaultAdded {
+    pub meta_vault: Pubkey,
+    pub vault: Pubkey,
+    pub index: u8,
+}
+
+#[event]
+pub struct WeightsSet {
+    pub meta_vault: Pubkey,
+    pub weights_bps: Vec<u16>,
+}
+
+#[event]
+pub struct MetaDeposited {
+    pub meta_vault: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub shares: u64,
+    /// Net asset value before the deposit
+    pub total_assets: u64,
+}
+
+#[event]
+pub struct MetaWithdrawn {
+    pub meta_vault: Pubkey,
+    pub user: Pubkey,
+    pub shares: u64,
+    pub amount: u64,
+    /// Net asset value before the withdrawal
+    pub total_assets: u64,
+}
+
+#[event]
+pub struct ChildAllocated {
+    pub meta_vault: Pubkey,
+    pub vault: Pubkey,
+    pub amount: u64,
+    /// Value held in the child after the allocation
+    pub child_value: u64,
+}
+
+#[event]
+pub struct ChildDeallocated {
+    pub meta_vault: Pubkey,
+    pub vault: Pubkey,
+    pub shares: u64,
+    /// Underlying returned to the meta vault
+    pub amount: u64,
+}
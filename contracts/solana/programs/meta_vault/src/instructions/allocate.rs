@@ -0,0 +1,282 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as ix_sysvar;
+use anchor_spl::token::{Token, TokenAccount};
+use yield_vault::constants::{EVENT_AUTHORITY_SEED, GLOBAL_STATS_SEED};
+use yield_vault::cpi::accounts::{Deposit, Withdraw};
+use yield_vault::program::YieldVault;
+use yield_vault::state::VaultState;
+
+use crate::constants::*;
+use crate::error::MetaVaultError;
+use crate::events::{ChildAllocated, ChildDeallocated};
+use crate::instructions::flows::{child_values, net_asset_value, shares_to_value};
+use crate::state::MetaVault;
+
+/// Accounts of one child vault's deposit and withdraw instructions, checked
+/// by the yield vault program itself
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct ChildFlow<'info> {
+    pub manager: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [META_VAULT_SEED, meta_vault.mint.as_ref(), meta_vault.owner.as_ref()],
+        bump = meta_vault.bump,
+        has_one = manager @ MetaVaultError::Unauthorized,
+    )]
+    pub meta_vault: Box<Account<'info, MetaVault>>,
+
+    /// The meta vault's user in the child; pays the child position's rent
+    #[account(
+        mut,
+        seeds = [META_AUTHORITY_SEED, meta_vault.key().as_ref()],
+        bump = meta_vault.vault_authority_bump,
+    )]
+    pub vault_authority: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [META_ASSETS_SEED, meta_vault.key().as_ref()],
+        bump = meta_vault.assets_bump,
+    )]
+    pub assets: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = meta_vault.child(index)?.vault @ MetaVaultError::ChildAccountsMismatch,
+    )]
+    pub child_vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [CHILD_SHARES_SEED, meta_vault.key().as_ref(), child_vault.key().as_ref()],
+        bump = meta_vault.child(index)?.shares_bump,
+    )]
+    pub child_shares: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: validated by the yield vault program
+    #[account(mut)]
+    pub child_vault_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the yield vault program
+    #[account(mut)]
+    pub child_share_mint: UncheckedAccount<'info>,
+
+    /// CHECK: the authority's position in the child, validated by the yield
+    /// vault program
+    #[account(mut)]
+    pub child_position: UncheckedAccount<'info>,
+
+    /// CHECK: the child's rewards pool, if it runs one
+    #[account(mut)]
+    pub child_rewards: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the child's price oracle, if it has one (allocations only)
+    pub child_oracle: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the child's market guard reserves, if set (allocations only)
+    pub child_market_share_reserve: Option<UncheckedAccount<'info>>,
+    /// CHECK: see above
+    pub child_market_asset_reserve: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the vault authority's Civic Pass, if the child is permissioned
+    /// (allocations only)
+    pub child_gateway_token: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the vault authority's compliance flag address, validated by the
+    /// yield vault program (allocations only)
+    pub child_wallet_flag: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the vault authority's portfolio address, validated by the yield
+    /// vault program (allocations only)
+    #[account(mut)]
+    pub child_portfolio: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the yield vault program's protocol stats account
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATS_SEED],
+        bump,
+        seeds::program = yield_vault::ID,
+    )]
+    pub child_global_stats: UncheckedAccount<'info>,
+
+    /// CHECK: the instructions sysvar, which the yield vault program reads
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// CHECK: the yield vault program's event authority
+    #[account(
+        seeds = [EVENT_AUTHORITY_SEED],
+        bump,
+        seeds::program = yield_vault::ID,
+    )]
+    pub child_event_authority: UncheckedAccount<'info>,
+
+    pub yield_vault_program: Program<'info, YieldVault>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// ──────────────────────────────────────────
+// Allocate
+// ──────────────────────────────────────────
+
+/// Deposit `amount` of unallocated underlying into child `index`, up to its
+/// weight of NAV. The remaining accounts are each child's `(vault, child
+/// shares)` pair, in child order. Manager only.
+pub fn handle_allocate<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ChildFlow<'info>>,
+    index: u8,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, MetaVaultError::InvalidAmount);
+
+    let meta_vault = &ctx.accounts.meta_vault;
+    let values = child_values(meta_vault, ctx.remaining_accounts)?;
+    let total_assets = net_asset_value(meta_vault.idle_assets, &values)?;
+    let child_value = values[index as usize]
+        .checked_add(amount)
+        .ok_or(MetaVaultError::ArithmeticOverflow)?;
+    require!(
+        child_value <= meta_vault.child_target(index, total_assets)?,
+        MetaVaultError::WeightExceeded
+    );
+
+    ctx.accounts.meta_vault.debit_idle(amount)?;
+
+    let meta_vault = &ctx.accounts.meta_vault;
+    let meta_key = meta_vault.key();
+    let seeds = meta_vault.authority_seeds(&meta_key);
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+    yield_vault::interface::deposit(
+        ctx.accounts.yield_vault_program.to_account_info(),
+        Deposit {
+            user: ctx.accounts.vault_authority.to_account_info(),
+            vault: ctx.accounts.child_vault.to_account_info(),
+            vault_token_account: ctx.accounts.child_vault_token_account.to_account_info(),
+            share_mint: ctx.accounts.child_share_mint.to_account_info(),
+            user_token_account: ctx.accounts.assets.to_account_info(),
+            user_share_account: ctx.accounts.child_shares.to_account_info(),
+            position: ctx.accounts.child_position.to_account_info(),
+            rewards: ctx.accounts.child_rewards.as_ref().map(|a| a.to_account_info()),
+            oracle: ctx.accounts.child_oracle.as_ref().map(|a| a.to_account_info()),
+            market_share_reserve: ctx
+                .accounts
+                .child_market_share_reserve
+                .as_ref()
+                .map(|a| a.to_account_info()),
+            market_asset_reserve: ctx
+                .accounts
+                .child_market_asset_reserve
+                .as_ref()
+                .map(|a| a.to_account_info()),
+            gateway_token: ctx
+                .accounts
+                .child_gateway_token
+                .as_ref()
+                .map(|a| a.to_account_info()),
+            wallet_flag: ctx
+                .accounts
+                .child_wallet_flag
+                .as_ref()
+                .ok_or(MetaVaultError::ChildAccountsMismatch)?
+                .to_account_info(),
+            portfolio: ctx
+                .accounts
+                .child_portfolio
+                .as_ref()
+                .ok_or(MetaVaultError::ChildAccountsMismatch)?
+                .to_account_info(),
+            global_stats: ctx.accounts.child_global_stats.to_account_info(),
+            instructions: ctx.accounts.instructions.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            event_authority: ctx.accounts.child_event_authority.to_account_info(),
+            program: ctx.accounts.yield_vault_program.to_account_info(),
+        },
+        amount,
+        None,
+        signer_seeds,
+    )?;
+
+    ctx.accounts.child_shares.reload()?;
+    let child_value = shares_to_value(
+        &*ctx.accounts.child_vault.load()?,
+        ctx.accounts.child_shares.amount,
+    )?;
+
+    emit_cpi!(ChildAllocated {
+        meta_vault: meta_key,
+        vault: ctx.accounts.child_vault.key(),
+        amount,
+        child_value,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Deallocate
+// ──────────────────────────────────────────
+
+/// Redeem `shares` of child `index` back into unallocated underlying,
+/// accepting at most `max_loss_bps` of realized loss. Manager only.
+pub fn handle_deallocate(
+    ctx: Context<ChildFlow>,
+    _index: u8,
+    shares: u64,
+    max_loss_bps: u16,
+) -> Result<()> {
+    require!(shares > 0, MetaVaultError::InvalidAmount);
+
+    let before = ctx.accounts.assets.amount;
+    let meta_key = ctx.accounts.meta_vault.key();
+    let seeds = ctx.accounts.meta_vault.authority_seeds(&meta_key);
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+    yield_vault::interface::withdraw(
+        ctx.accounts.yield_vault_program.to_account_info(),
+        Withdraw {
+            user: ctx.accounts.vault_authority.to_account_info(),
+            vault: ctx.accounts.child_vault.to_account_info(),
+            vault_token_account: ctx.accounts.child_vault_token_account.to_account_info(),
+            share_mint: ctx.accounts.child_share_mint.to_account_info(),
+            user_token_account: ctx.accounts.assets.to_account_info(),
+            recipient: None,
+            user_share_account: ctx.accounts.child_shares.to_account_info(),
+            position: ctx.accounts.child_position.to_account_info(),
+            rewards: ctx.accounts.child_rewards.as_ref().map(|a| a.to_account_info()),
+            global_stats: ctx.accounts.child_global_stats.to_account_info(),
+            instructions: ctx.accounts.instructions.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            event_authority: ctx.accounts.child_event_authority.to_account_info(),
+            program: ctx.accounts.yield_vault_program.to_account_info(),
+        },
+        shares,
+        max_loss_bps,
+        None,
+        signer_seeds,
+    )?;
+
+    // Only what the child paid out joins the unallocated underlying
+    ctx.accounts.assets.reload()?;
+    let amount = ctx
+        .accounts
+        .assets
+        .amount
+        .checked_sub(before)
+        .ok_or(MetaVaultError::ArithmeticOverflow)?;
+    ctx.accounts.meta_vault.credit_idle(amount)?;
+
+    emit_cpi!(ChildDeallocated {
+        meta_vault: meta_key,
+        vault: ctx.accounts.child_vault.key(),
+        shares,
+        amount,
+    });
+
+    Ok(())
+}
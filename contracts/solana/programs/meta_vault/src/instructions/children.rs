@@ -0,0 +1,143 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use yield_vault::constants::SHARE_MINT_SEED;
+use yield_vault::state::{UserPosition, VaultState};
+
+use crate::constants::*;
+use crate::error::MetaVaultError;
+use crate::events::{ChildVaultAdded, WeightsSet};
+use crate::state::{ChildVault, MetaVault};
+
+// ──────────────────────────────────────────
+// Add Child
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AddChild<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [META_VAULT_SEED, meta_vault.mint.as_ref(), owner.key().as_ref()],
+        bump = meta_vault.bump,
+        has_one = owner,
+    )]
+    pub meta_vault: Box<Account<'info, MetaVault>>,
+
+    #[account(
+        mut,
+        seeds = [META_AUTHORITY_SEED, meta_vault.key().as_ref()],
+        bump = meta_vault.vault_authority_bump,
+    )]
+    pub vault_authority: SystemAccount<'info>,
+
+    /// The yield vault being added
+    #[account(
+        constraint = child_vault.load()?.mint == meta_vault.mint @ MetaVaultError::MintMismatch,
+    )]
+    pub child_vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [
+            SHARE_MINT_SEED,
+            child_vault.key().as_ref(),
+            &child_vault.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = child_vault.load()?.share_mint_bump,
+        seeds::program = yield_vault::ID,
+    )]
+    pub child_share_mint: Box<Account<'info, Mint>>,
+
+    /// Holds the meta vault's shares of the child
+    #[account(
+        init,
+        payer = owner,
+        seeds = [CHILD_SHARES_SEED, meta_vault.key().as_ref(), child_vault.key().as_ref()],
+        bump,
+        token::mint = child_share_mint,
+        token::authority = vault_authority,
+    )]
+    pub child_shares: Box<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Add a yield vault over the same underlying with a zero weight. Owner only.
+pub fn handle_add_child(ctx: Context<AddChild>) -> Result<()> {
+    let child_vault = ctx.accounts.child_vault.key();
+    let meta_vault = &ctx.accounts.meta_vault;
+    require!(
+        (meta_vault.child_count as usize) < MAX_CHILDREN,
+        MetaVaultError::MaxChildrenReached
+    );
+    require!(
+        meta_vault.children().iter().all(|child| child.vault != child_vault),
+        MetaVaultError::ChildAlreadyAdded
+    );
+
+    // The child opens a position for the authority PDA on the first
+    // allocation, paid from its own lamports
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner.to_account_info(),
+                to: ctx.accounts.vault_authority.to_account_info(),
+            },
+        ),
+        ctx.accounts.rent.minimum_balance(UserPosition::LEN),
+    )?;
+
+    let meta_vault = &mut ctx.accounts.meta_vault;
+    let index = meta_vault.child_count;
+    meta_vault.children[index as usize] = ChildVault {
+        vault: child_vault,
+        weight_bps: 0,
+        shares_bump: ctx.bumps.child_shares,
+    };
+    meta_vault.child_count += 1;
+
+    emit_cpi!(ChildVaultAdded {
+        meta_vault: meta_vault.key(),
+        vault: child_vault,
+        index,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Weights
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetWeights<'info> {
+    pub manager: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [META_VAULT_SEED, meta_vault.mint.as_ref(), meta_vault.owner.as_ref()],
+        bump = meta_vault.bump,
+        has_one = manager @ MetaVaultError::Unauthorized,
+    )]
+    pub meta_vault: Box<Account<'info, MetaVault>>,
+}
+
+/// Set each child's target share of NAV, in child order. Manager only.
+pub fn handle_set_weights(ctx: Context<SetWeights>, weights_bps: Vec<u16>) -> Result<()> {
+    let meta_vault = &mut ctx.accounts.meta_vault;
+    meta_vault.set_weights(&weights_bps)?;
+
+    emit_cpi!(WeightsSet {
+        meta_vault: meta_vault.key(),
+        weights_bps,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::constants::*;
+use crate::error::MetaVaultError;
+use crate::events::MetaVaultCreated;
+use crate::state::{ChildVault, MetaVault};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateMetaVault<'info> {
+    /// The account creating (and paying for) the meta vault
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Underlying token shared by the meta vault and its children
+    #[account(
+        constraint = mint.decimals <= u8::MAX - META_DECIMALS_OFFSET
+            @ MetaVaultError::UnsupportedDecimals,
+    )]
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = MetaVault::LEN,
+        seeds = [META_VAULT_SEED, mint.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub meta_vault: Box<Account<'info, MetaVault>>,
+
+    /// System-owned PDA holding the meta vault's tokens. It is the meta
+    /// vault's user in each child vault, so it must be able to pay rent.
+    #[account(
+        mut,
+        seeds = [META_AUTHORITY_SEED, meta_vault.key().as_ref()],
+        bump,
+    )]
+    pub vault_authority: SystemAccount<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [META_SHARE_MINT_SEED, meta_vault.key().as_ref()],
+        bump,
+        mint::decimals = mint.decimals + META_DECIMALS_OFFSET,
+        mint::authority = vault_authority,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    /// Holds unallocated underlying
+    #[account(
+        init,
+        payer = owner,
+        seeds = [META_ASSETS_SEED, meta_vault.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+    )]
+    pub assets: Box<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(ctx: Context<CreateMetaVault>, manager: Pubkey) -> Result<()> {
+    // Keep the authority PDA alive; each child adds its position rent
+    let minimum = ctx.accounts.rent.minimum_balance(0);
+    let shortfall = minimum.saturating_sub(ctx.accounts.vault_authority.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: ctx.accounts.vault_authority.to_account_info(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+
+    let meta_vault = &mut ctx.accounts.meta_vault;
+    meta_vault.owner = ctx.accounts.owner.key();
+    meta_vault.manager = manager;
+    meta_vault.mint = ctx.accounts.mint.key();
+    meta_vault.share_mint = ctx.accounts.share_mint.key();
+    meta_vault.children = [ChildVault::default(); MAX_CHILDREN];
+    meta_vault.child_count = 0;
+    meta_vault.idle_assets = 0;
+    meta_vault.share_scale = 10u64.pow(META_DECIMALS_OFFSET as u32);
+    meta_vault.bump = ctx.bumps.meta_vault;
+    meta_vault.vault_authority_bump = ctx.bumps.vault_authority;
+    meta_vault.share_mint_bump = ctx.bumps.share_mint;
+    meta_vault.assets_bump = ctx.bumps.assets;
+
+    emit_cpi!(MetaVaultCreated {
+        meta_vault: meta_vault.key(),
+        owner: meta_vault.owner,
+        manager,
+        mint: meta_vault.mint,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,274 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+use yield_vault::state::VaultState;
+
+use crate::constants::*;
+use crate::error::MetaVaultError;
+use crate::events::{MetaDeposited, MetaWithdrawn};
+use crate::state::MetaVault;
+
+/// Underlying value of `shares` at a yield vault's share price, rounded down
+pub fn shares_to_value(vault: &VaultState, shares: u64) -> Result<u64> {
+    if vault.total_shares == 0 {
+        return Ok(0);
+    }
+    let value = (shares as u128)
+        .checked_mul(vault.total_assets as u128)
+        .ok_or(MetaVaultError::ArithmeticOverflow)?
+        / (vault.total_shares as u128);
+    u64::try_from(value).map_err(|_| error!(MetaVaultError::ArithmeticOverflow))
+}
+
+/// Value held in each child, read from `(child vault, child shares)` account
+/// pairs passed in child order
+pub fn child_values<'info>(
+    meta_vault: &Account<'info, MetaVault>,
+    accounts: &'info [AccountInfo<'info>],
+) -> Result<Vec<u64>> {
+    require!(
+        accounts.len() == 2 * meta_vault.child_count as usize,
+        MetaVaultError::ChildAccountsMismatch
+    );
+
+    let meta_key = meta_vault.key();
+    let mut values = Vec::with_capacity(meta_vault.child_count as usize);
+    for (child, pair) in meta_vault.children().iter().zip(accounts.chunks(2)) {
+        require_keys_eq!(pair[0].key(), child.vault, MetaVaultError::ChildAccountsMismatch);
+        require_keys_eq!(
+            pair[1].key(),
+            meta_vault.child_shares_address(&meta_key, child)?,
+            MetaVaultError::ChildAccountsMismatch
+        );
+        let vault = AccountLoader::<VaultState>::try_from(&pair[0])?;
+        let shares = Account::<TokenAccount>::try_from(&pair[1])?;
+        values.push(shares_to_value(&*vault.load()?, shares.amount)?);
+    }
+    Ok(values)
+}
+
+/// Unallocated underlying plus every child's shares at its share price
+pub fn net_asset_value(idle: u64, child_values: &[u64]) -> Result<u64> {
+    child_values.iter().try_fold(idle, |total, &value| {
+        total
+            .checked_add(value)
+            .ok_or(error!(MetaVaultError::ArithmeticOverflow))
+    })
+}
+
+// ──────────────────────────────────────────
+// Deposit
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MetaDeposit<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [META_VAULT_SEED, meta_vault.mint.as_ref(), meta_vault.owner.as_ref()],
+        bump = meta_vault.bump,
+    )]
+    pub meta_vault: Box<Account<'info, MetaVault>>,
+
+    /// CHECK: PDA signer only
+    #[account(
+        seeds = [META_AUTHORITY_SEED, meta_vault.key().as_ref()],
+        bump = meta_vault.vault_authority_bump,
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [META_SHARE_MINT_SEED, meta_vault.key().as_ref()],
+        bump = meta_vault.share_mint_bump,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [META_ASSETS_SEED, meta_vault.key().as_ref()],
+        bump = meta_vault.assets_bump,
+    )]
+    pub assets: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = meta_vault.mint,
+        token::authority = user,
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Receives the meta vault shares
+    #[account(
+        mut,
+        token::mint = share_mint,
+    )]
+    pub user_share_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Deposit underlying for meta vault shares at the current NAV. The remaining
+/// accounts are each child's `(vault, child shares)` pair, in child order.
+pub fn handle_deposit<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MetaDeposit<'info>>,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, MetaVaultError::InvalidAmount);
+
+    let meta_vault = &ctx.accounts.meta_vault;
+    let values = child_values(meta_vault, ctx.remaining_accounts)?;
+    let total_assets = net_asset_value(meta_vault.idle_assets, &values)?;
+    let shares = meta_vault.shares_for(amount, total_assets, ctx.accounts.share_mint.supply)?;
+    require!(shares > 0, MetaVaultError::InvalidAmount);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.assets.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    ctx.accounts.meta_vault.credit_idle(amount)?;
+
+    let meta_key = ctx.accounts.meta_vault.key();
+    let seeds = ctx.accounts.meta_vault.authority_seeds(&meta_key);
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+    token::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                to: ctx.accounts.user_share_account.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        shares,
+    )?;
+
+    emit_cpi!(MetaDeposited {
+        meta_vault: meta_key,
+        user: ctx.accounts.user.key(),
+        amount,
+        shares,
+        total_assets,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Withdraw
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MetaWithdraw<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [META_VAULT_SEED, meta_vault.mint.as_ref(), meta_vault.owner.as_ref()],
+        bump = meta_vault.bump,
+    )]
+    pub meta_vault: Box<Account<'info, MetaVault>>,
+
+    /// CHECK: PDA signer only
+    #[account(
+        seeds = [META_AUTHORITY_SEED, meta_vault.key().as_ref()],
+        bump = meta_vault.vault_authority_bump,
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [META_SHARE_MINT_SEED, meta_vault.key().as_ref()],
+        bump = meta_vault.share_mint_bump,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [META_ASSETS_SEED, meta_vault.key().as_ref()],
+        bump = meta_vault.assets_bump,
+    )]
+    pub assets: Box<Account<'info, TokenAccount>>,
+
+    /// Receives the underlying
+    #[account(
+        mut,
+        token::mint = meta_vault.mint,
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// The meta vault shares being burned
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = user,
+    )]
+    pub user_share_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Burn meta vault shares for their value in unallocated underlying. The
+/// manager deallocates from the children to keep enough on hand. The
+/// remaining accounts are each child's `(vault, child shares)` pair.
+pub fn handle_withdraw<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MetaWithdraw<'info>>,
+    shares: u64,
+) -> Result<()> {
+    require!(shares > 0, MetaVaultError::InvalidAmount);
+
+    let meta_vault = &ctx.accounts.meta_vault;
+    let values = child_values(meta_vault, ctx.remaining_accounts)?;
+    let total_assets = net_asset_value(meta_vault.idle_assets, &values)?;
+    let amount = meta_vault.assets_for(shares, total_assets, ctx.accounts.share_mint.supply)?;
+    require!(amount > 0, MetaVaultError::InvalidAmount);
+    ctx.accounts.meta_vault.debit_idle(amount)?;
+
+    token::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                from: ctx.accounts.user_share_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        shares,
+    )?;
+
+    let meta_key = ctx.accounts.meta_vault.key();
+    let seeds = ctx.accounts.meta_vault.authority_seeds(&meta_key);
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.assets.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    emit_cpi!(MetaWithdrawn {
+        meta_vault: meta_key,
+        user: ctx.accounts.user.key(),
+        shares,
+        amount,
+        total_assets,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,9 @@
+pub mod allocate;
+pub mod children;
+pub mod create_meta_vault;
+pub mod flows;
+
+pub use allocate::*;
+pub use children::*;
+pub use create_meta_vault::*;
+pub use flows::*;
@@ -0,0 +1,80 @@
+//! Vault-of-vaults over DeFAI yield vaults.
+//!
+//! A meta vault takes deposits of one underlying and spreads them across
+//! several yield vaults over the same underlying, so a single share token
+//! carries diversified exposure. The manager sets each child's weight and
+//! moves funds with `allocate` / `deallocate`, which deposit into and redeem
+//! from the children by CPI; an allocation may not take a child above its
+//! weight of NAV. NAV is the unallocated underlying the meta vault has
+//! recorded, never its token balance, plus the meta vault's shares of each
+//! child at that child's share price, so it follows the children's own NAV
+//! updates. Shares carry `META_DECIMALS_OFFSET` extra decimals, priced with
+//! as many virtual shares so donations cannot skew the share price.
+//! Withdrawals are paid from unallocated underlying.
+
+use anchor_lang::prelude::*;
+
+pub mod constants;
+pub mod error;
+pub mod events;
+pub mod instructions;
+pub mod state;
+
+use instructions::*;
+
+declare_id!("MTV1111111111111111111111111111111111111111");
+
+#[program]
+pub mod meta_vault {
+    use super::*;
+
+    /// Create a meta vault over `mint` whose allocations `manager` controls.
+    pub fn create_meta_vault(ctx: Context<CreateMetaVault>, manager: Pubkey) -> Result<()> {
+        instructions::create_meta_vault::handler(ctx, manager)
+    }
+
+    /// Add a yield vault over the same underlying as a child. Owner only.
+    pub fn add_child(ctx: Context<AddChild>) -> Result<()> {
+        instructions::children::handle_add_child(ctx)
+    }
+
+    /// Set each child's target share of NAV in bps. Manager only.
+    pub fn set_weights(ctx: Context<SetWeights>, weights_bps: Vec<u16>) -> Result<()> {
+        instructions::children::handle_set_weights(ctx, weights_bps)
+    }
+
+    /// Deposit underlying for meta vault shares at the current NAV.
+    pub fn deposit<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MetaDeposit<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::flows::handle_deposit(ctx, amount)
+    }
+
+    /// Burn meta vault shares for unallocated underlying at the current NAV.
+    pub fn withdraw<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MetaWithdraw<'info>>,
+        shares: u64,
+    ) -> Result<()> {
+        instructions::flows::handle_withdraw(ctx, shares)
+    }
+
+    /// Deposit unallocated underlying into a child, up to its weight. Manager only.
+    pub fn allocate<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ChildFlow<'info>>,
+        index: u8,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::allocate::handle_allocate(ctx, index, amount)
+    }
+
+    /// Redeem a child's shares back into unallocated underlying. Manager only.
+    pub fn deallocate(
+        ctx: Context<ChildFlow>,
+        index: u8,
+        shares: u64,
+        max_loss_bps: u16,
+    ) -> Result<()> {
+        instructions::allocate::handle_deallocate(ctx, index, shares, max_loss_bps)
+    }
+}
@@ -0,0 +1,157 @@
+use anchor_lang::prelude::*;
+use yield_vault::constants::BPS_DENOMINATOR;
+
+use crate::constants::{CHILD_SHARES_SEED, MAX_CHILDREN, META_AUTHORITY_SEED};
+use crate::error::MetaVaultError;
+
+/// A yield vault the meta vault allocates to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct ChildVault {
+    pub vault: Pubkey,
+    /// Share of the meta vault's NAV the child may hold, in bps
+    pub weight_bps: u16,
+    /// Bump of the token account holding the child's shares
+    pub shares_bump: u8,
+}
+
+#[account]
+pub struct MetaVault {
+    /// Can add child vaults — part of the PDA seeds
+    pub owner: Pubkey,
+    /// Sets weights and moves funds between the children
+    pub manager: Pubkey,
+    /// Underlying shared by the meta vault and every child
+    pub mint: Pubkey,
+    pub share_mint: Pubkey,
+    pub children: [ChildVault; MAX_CHILDREN],
+    pub child_count: u8,
+    /// Unallocated underlying the meta vault accounts for. Tokens sent to the
+    /// assets account by any other route are not counted, so they cannot move
+    /// the share price.
+    pub idle_assets: u64,
+    /// Shares one asset unit buys at a share price of 1, and the virtual
+    /// shares priced alongside the supply (see `shares_for`)
+    pub share_scale: u64,
+
+    /// PDA bump seeds
+    pub bump: u8,
+    pub vault_authority_bump: u8,
+    pub share_mint_bump: u8,
+    pub assets_bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl MetaVault {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 32 + 32 + (32 + 2 + 1)*8 + 1 + 8 + 8 + 1 + 1 + 1 + 1 + 64 = 493
+    pub const LEN: usize = 8 + 493;
+
+    /// Seeds the vault authority PDA signs with
+    pub fn authority_seeds<'a>(&'a self, meta_vault: &'a Pubkey) -> [&'a [u8]; 3] {
+        [
+            META_AUTHORITY_SEED,
+            meta_vault.as_ref(),
+            std::slice::from_ref(&self.vault_authority_bump),
+        ]
+    }
+
+    /// Shares `amount` of underlying buys against `total_assets` of NAV and
+    /// `supply` shares, rounded down. One virtual asset and `share_scale`
+    /// virtual shares are priced alongside the real ones, so a donation
+    /// cannot inflate the price enough to round a deposit down to nothing.
+    pub fn shares_for(&self, amount: u64, total_assets: u64, supply: u64) -> Result<u64> {
+        let shares = (amount as u128)
+            .checked_mul(supply as u128 + self.share_scale as u128)
+            .ok_or(MetaVaultError::ArithmeticOverflow)?
+            / (total_assets as u128 + 1);
+        u64::try_from(shares).map_err(|_| error!(MetaVaultError::ArithmeticOverflow))
+    }
+
+    /// Underlying `shares` redeem for against `total_assets` of NAV and
+    /// `supply` shares, rounded down (see `shares_for`)
+    pub fn assets_for(&self, shares: u64, total_assets: u64, supply: u64) -> Result<u64> {
+        let assets = (shares as u128)
+            .checked_mul(total_assets as u128 + 1)
+            .ok_or(MetaVaultError::ArithmeticOverflow)?
+            / (supply as u128 + self.share_scale as u128);
+        u64::try_from(assets).map_err(|_| error!(MetaVaultError::ArithmeticOverflow))
+    }
+
+    /// Add `amount` to the unallocated underlying
+    pub fn credit_idle(&mut self, amount: u64) -> Result<()> {
+        self.idle_assets = self
+            .idle_assets
+            .checked_add(amount)
+            .ok_or(MetaVaultError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Take `amount` from the unallocated underlying
+    pub fn debit_idle(&mut self, amount: u64) -> Result<()> {
+        self.idle_assets = self
+            .idle_assets
+            .checked_sub(amount)
+            .ok_or(MetaVaultError::InsufficientLiquidity)?;
+        Ok(())
+    }
+
+    pub fn children(&self) -> &[ChildVault] {
+        &self.children[..self.child_count as usize]
+    }
+
+    pub fn child(&self, index: u8) -> Result<&ChildVault> {
+        self.children()
+            .get(index as usize)
+            .ok_or(error!(MetaVaultError::InvalidChild))
+    }
+
+    /// Address of the token account holding `child`'s shares
+    pub fn child_shares_address(&self, meta_vault: &Pubkey, child: &ChildVault) -> Result<Pubkey> {
+        Pubkey::create_program_address(
+            &[
+                CHILD_SHARES_SEED,
+                meta_vault.as_ref(),
+                child.vault.as_ref(),
+                &[child.shares_bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| error!(MetaVaultError::ChildAccountsMismatch))
+    }
+
+    /// Most a child may hold out of `total_assets`
+    pub fn child_target(&self, index: u8, total_assets: u64) -> Result<u64> {
+        let target = (total_assets as u128)
+            .checked_mul(self.child(index)?.weight_bps as u128)
+            .ok_or(MetaVaultError::ArithmeticOverflow)?
+            / BPS_DENOMINATOR;
+        Ok(target as u64)
+    }
+
+    /// Replace every child's weight; the unweighted rest stays unallocated
+    pub fn set_weights(&mut self, weights_bps: &[u16]) -> Result<()> {
+        require!(
+            weights_bps.len() == self.child_count as usize,
+            MetaVaultError::InvalidWeights
+        );
+        let total: u128 = weights_bps.iter().map(|&weight| weight as u128).sum();
+        require!(total <= BPS_DENOMINATOR, MetaVaultError::InvalidWeights);
+
+        for (child, &weight) in self.children.iter_mut().zip(weights_bps) {
+            child.weight_bps = weight;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn len_matches_serialized_size() {
+        let account = MetaVault::try_from_slice(&[0; MetaVault::LEN - 8]).unwrap();
+        assert_eq!(8 + account.try_to_vec().unwrap().len(), MetaVault::LEN);
+    }
+}
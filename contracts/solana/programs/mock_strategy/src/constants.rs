@@ -0,0 +1,5 @@
+/// PDA seed for a vault's mock strategy
+pub const MOCK_STRATEGY_SEED: &[u8] = b"mock_strategy";
+
+/// PDA seed for the token account holding the mock strategy's funds
+pub const MOCK_FUNDS_SEED: &[u8] = b"mock_funds";
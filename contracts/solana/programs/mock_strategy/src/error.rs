@@ -0,0 +1,13 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum MockStrategyError {
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+
+    #[msg("Report latency must not be negative")]
+    InvalidLatency,
+
+    #[msg("The strategy's report latency has not passed since its last report")]
+    ReportNotDue,
+}
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use yield_vault::state::VaultState;
+
+use crate::constants::{MOCK_FUNDS_SEED, MOCK_STRATEGY_SEED};
+use crate::error::MockStrategyError;
+use crate::state::MockStrategy;
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The yield vault the strategy will be registered with
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(address = vault.load()?.mint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = MockStrategy::LEN,
+        seeds = [MOCK_STRATEGY_SEED, vault.key().as_ref()],
+        bump,
+    )]
+    pub mock_strategy: Account<'info, MockStrategy>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [MOCK_FUNDS_SEED, mock_strategy.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = mock_strategy,
+    )]
+    pub funds: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_initialize(ctx: Context<Initialize>, report_latency: i64) -> Result<()> {
+    require!(report_latency >= 0, MockStrategyError::InvalidLatency);
+
+    let mock_strategy = &mut ctx.accounts.mock_strategy;
+    mock_strategy.admin = ctx.accounts.admin.key();
+    mock_strategy.vault = ctx.accounts.vault.key();
+    mock_strategy.funds = ctx.accounts.funds.key();
+    mock_strategy.report_latency = report_latency;
+    mock_strategy.last_report_at = Clock::get()?.unix_timestamp;
+    mock_strategy.bump = ctx.bumps.mock_strategy;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetReportLatency<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut, has_one = admin)]
+    pub mock_strategy: Account<'info, MockStrategy>,
+}
+
+pub fn handle_set_report_latency(
+    ctx: Context<SetReportLatency>,
+    report_latency: i64,
+) -> Result<()> {
+    require!(report_latency >= 0, MockStrategyError::InvalidLatency);
+    ctx.accounts.mock_strategy.report_latency = report_latency;
+    Ok(())
+}
@@ -0,0 +1,7 @@
+pub mod initialize;
+pub mod report;
+pub mod simulate;
+
+pub use initialize::*;
+pub use report::*;
+pub use simulate::*;
@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use yield_vault::cpi::accounts::{Deallocate, Harvest};
+use yield_vault::program::YieldVault;
+
+use crate::error::MockStrategyError;
+use crate::state::MockStrategy;
+
+#[derive(Accounts)]
+pub struct Report<'info> {
+    /// Vault owner or manager; the vault checks it on the harvest
+    pub authority: Signer<'info>,
+
+    #[account(mut, has_one = vault, has_one = funds)]
+    pub mock_strategy: Account<'info, MockStrategy>,
+
+    pub funds: Account<'info, TokenAccount>,
+
+    /// CHECK: validated by the yield vault's `harvest`
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>,
+
+    /// The vault's record of this strategy
+    /// CHECK: validated by the yield vault's `harvest`
+    #[account(mut)]
+    pub strategy: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the yield vault's `harvest`
+    #[account(mut)]
+    pub fee_report: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the yield vault's `harvest`
+    #[account(mut)]
+    pub audit_log: UncheckedAccount<'info>,
+
+    /// CHECK: the yield vault's event authority, validated by its `harvest`
+    pub event_authority: UncheckedAccount<'info>,
+
+    pub yield_vault_program: Program<'info, YieldVault>,
+}
+
+/// Report the strategy's holdings to the vault as its current value, once
+/// `report_latency` has passed since the last report
+pub fn handle_report(ctx: Context<Report>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        ctx.accounts.mock_strategy.report_due(now),
+        MockStrategyError::ReportNotDue
+    );
+
+    yield_vault::cpi::harvest(
+        CpiContext::new(
+            ctx.accounts.yield_vault_program.to_account_info(),
+            Harvest {
+                authority: ctx.accounts.authority.to_account_info(),
+                vault: ctx.accounts.vault.to_account_info(),
+                strategy: ctx.accounts.strategy.to_account_info(),
+                fee_report: ctx.accounts.fee_report.to_account_info(),
+                audit_log: ctx.accounts.audit_log.to_account_info(),
+                event_authority: ctx.accounts.event_authority.to_account_info(),
+                program: ctx.accounts.yield_vault_program.to_account_info(),
+            },
+        ),
+        ctx.accounts.funds.amount,
+    )?;
+
+    ctx.accounts.mock_strategy.last_report_at = now;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// Vault owner or manager; the vault checks it on the deallocation
+    pub authority: Signer<'info>,
+
+    #[account(has_one = vault, has_one = funds)]
+    pub mock_strategy: Account<'info, MockStrategy>,
+
+    #[account(mut)]
+    pub funds: Account<'info, TokenAccount>,
+
+    /// CHECK: validated by the yield vault's `deallocate`
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>,
+
+    /// The vault's record of this strategy
+    /// CHECK: validated by the yield vault's `deallocate`
+    #[account(mut)]
+    pub strategy: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the yield vault's `deallocate`
+    #[account(mut)]
+    pub fee_report: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the yield vault's `deallocate`
+    #[account(mut)]
+    pub audit_log: UncheckedAccount<'info>,
+
+    /// CHECK: the yield vault's event authority, validated by its `deallocate`
+    pub event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the yield vault's `deallocate`
+    #[account(mut)]
+    pub vault_token_account: UncheckedAccount<'info>,
+
+    pub yield_vault_program: Program<'info, YieldVault>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Return `amount` of the strategy's holdings to the vault, co-signing the
+/// deallocation as the strategy authority
+pub fn handle_withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    require!(amount > 0, MockStrategyError::InvalidAmount);
+    let seeds = ctx.accounts.mock_strategy.signer_seeds();
+
+    yield_vault::cpi::deallocate(
+        CpiContext::new_with_signer(
+            ctx.accounts.yield_vault_program.to_account_info(),
+            Deallocate {
+                authority: ctx.accounts.authority.to_account_info(),
+                strategy_authority: ctx.accounts.mock_strategy.to_account_info(),
+                vault: ctx.accounts.vault.to_account_info(),
+                strategy: ctx.accounts.strategy.to_account_info(),
+                fee_report: ctx.accounts.fee_report.to_account_info(),
+                audit_log: ctx.accounts.audit_log.to_account_info(),
+                vault_token_account: ctx.accounts.vault_token_account.to_account_info(),
+                strategy_token_account: ctx.accounts.funds.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                event_authority: ctx.accounts.event_authority.to_account_info(),
+                program: ctx.accounts.yield_vault_program.to_account_info(),
+            },
+            &[&seeds],
+        ),
+        amount,
+    )
+}
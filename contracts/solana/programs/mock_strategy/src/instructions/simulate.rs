@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::error::MockStrategyError;
+use crate::state::MockStrategy;
+
+#[derive(Accounts)]
+pub struct SimulateProfit<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(has_one = admin, has_one = funds)]
+    pub mock_strategy: Account<'info, MockStrategy>,
+
+    #[account(mut)]
+    pub funds: Account<'info, TokenAccount>,
+
+    /// Pays the profit into the strategy
+    #[account(mut, token::mint = funds.mint, token::authority = admin)]
+    pub admin_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Grow the strategy's holdings by `amount`, paid in by the admin; the vault
+/// sees it as profit at the next report
+pub fn handle_simulate_profit(ctx: Context<SimulateProfit>, amount: u64) -> Result<()> {
+    require!(amount > 0, MockStrategyError::InvalidAmount);
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.admin_token_account.to_account_info(),
+                to: ctx.accounts.funds.to_account_info(),
+                authority: ctx.accounts.admin.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+#[derive(Accounts)]
+pub struct SimulateLoss<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(has_one = admin, has_one = funds)]
+    pub mock_strategy: Account<'info, MockStrategy>,
+
+    #[account(mut)]
+    pub funds: Account<'info, TokenAccount>,
+
+    /// Receives the lost funds
+    #[account(mut, token::mint = funds.mint)]
+    pub sink: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Shrink the strategy's holdings by `amount`, moved out to `sink`; the vault
+/// sees it as a loss at the next report
+pub fn handle_simulate_loss(ctx: Context<SimulateLoss>, amount: u64) -> Result<()> {
+    require!(amount > 0, MockStrategyError::InvalidAmount);
+    let seeds = ctx.accounts.mock_strategy.signer_seeds();
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.funds.to_account_info(),
+                to: ctx.accounts.sink.to_account_info(),
+                authority: ctx.accounts.mock_strategy.to_account_info(),
+            },
+            &[&seeds],
+        ),
+        amount,
+    )
+}
@@ -0,0 +1,63 @@
+//! Scriptable strategy adapter for integration tests and audits.
+//!
+//! A yield vault strategy is a token account whose authority co-signs
+//! deallocations, plus a report of its current value. The mock holds its
+//! funds in a token account owned by its PDA, registered with the vault as
+//! the strategy authority and token account, and lets the admin script
+//! its behaviour:
+//! - `simulate_profit` / `simulate_loss` move tokens in or out of the
+//!   holdings, which `report` then realizes into the vault's NAV.
+//! - `report_latency` makes `report` refuse to run until that many seconds
+//!   have passed, so the vault's stale-report checks can be exercised.
+//! - `withdraw` returns funds through the vault's `deallocate`.
+//!
+//! Localnet only.
+
+use anchor_lang::prelude::*;
+
+pub mod constants;
+pub mod error;
+pub mod instructions;
+pub mod state;
+
+use instructions::*;
+
+declare_id!("MCK1111111111111111111111111111111111111111");
+
+#[program]
+pub mod mock_strategy {
+    use super::*;
+
+    /// Create the mock strategy for `vault`, reporting at most once every
+    /// `report_latency` seconds.
+    pub fn initialize(ctx: Context<Initialize>, report_latency: i64) -> Result<()> {
+        instructions::initialize::handle_initialize(ctx, report_latency)
+    }
+
+    /// Change the minimum time between reports. Admin only.
+    pub fn set_report_latency(ctx: Context<SetReportLatency>, report_latency: i64) -> Result<()> {
+        instructions::initialize::handle_set_report_latency(ctx, report_latency)
+    }
+
+    /// Add `amount` to the holdings from the admin's tokens. Admin only.
+    pub fn simulate_profit(ctx: Context<SimulateProfit>, amount: u64) -> Result<()> {
+        instructions::simulate::handle_simulate_profit(ctx, amount)
+    }
+
+    /// Move `amount` of the holdings out to `sink`. Admin only.
+    pub fn simulate_loss(ctx: Context<SimulateLoss>, amount: u64) -> Result<()> {
+        instructions::simulate::handle_simulate_loss(ctx, amount)
+    }
+
+    /// Harvest the holdings into the vault's NAV. Signed by a vault owner or
+    /// manager.
+    pub fn report(ctx: Context<Report>) -> Result<()> {
+        instructions::report::handle_report(ctx)
+    }
+
+    /// Return `amount` to the vault through `deallocate`. Signed by a vault
+    /// owner or manager.
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        instructions::report::handle_withdraw(ctx, amount)
+    }
+}
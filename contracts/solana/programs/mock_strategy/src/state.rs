@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MOCK_STRATEGY_SEED;
+
+#[account]
+pub struct MockStrategy {
+    /// Scripts the strategy's profit, loss and latency
+    pub admin: Pubkey,
+    /// Yield vault the strategy is registered with — part of the PDA seeds
+    pub vault: Pubkey,
+    /// Token account the vault allocates to; this PDA is its authority and
+    /// the strategy authority the vault records
+    pub funds: Pubkey,
+    /// Seconds that must pass between reports (0 = report at any time)
+    pub report_latency: i64,
+    /// Last time the strategy reported to the vault (unix timestamp)
+    pub last_report_at: i64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl MockStrategy {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 32 + 8 + 8 + 1 + 64 = 177
+    pub const LEN: usize = 8 + 177;
+
+    /// Seeds the strategy PDA signs with
+    pub fn signer_seeds(&self) -> [&[u8]; 3] {
+        [
+            MOCK_STRATEGY_SEED,
+            self.vault.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+
+    /// Whether `report_latency` has passed since the last report
+    pub fn report_due(&self, now: i64) -> bool {
+        now >= self.last_report_at.saturating_add(self.report_latency)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn len_matches_serialized_size() {
+        let account = MockStrategy::try_from_slice(&[0; MockStrategy::LEN - 8]).unwrap();
+        assert_eq!(8 + account.try_to_vec().unwrap().len(), MockStrategy::LEN);
+    }
+}
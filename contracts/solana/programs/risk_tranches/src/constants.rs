@@ -0,0 +1,14 @@
+/// PDA seed for tranche vault accounts
+pub const TRANCHE_VAULT_SEED: &[u8] = b"tranche_vault";
+
+/// PDA seed for the senior tranche token mint
+pub const SENIOR_MINT_SEED: &[u8] = b"senior_mint";
+
+/// PDA seed for the junior tranche token mint
+pub const JUNIOR_MINT_SEED: &[u8] = b"junior_mint";
+
+/// PDA seed for the escrow token account holding the yield vault shares
+pub const ESCROW_SEED: &[u8] = b"escrow";
+
+/// Maximum senior target rate (50% a year)
+pub const MAX_SENIOR_RATE_BPS: u16 = 5_000;
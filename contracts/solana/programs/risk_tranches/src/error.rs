@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum TrancheError {
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+
+    #[msg("Tranche parameter is out of range")]
+    InvalidParams,
+
+    #[msg("Token mint does not belong to the tranche")]
+    MintMismatch,
+
+    #[msg("Tranche has been wiped out by losses")]
+    TrancheWipedOut,
+
+    #[msg("Junior tranche would fall below the minimum subordination")]
+    InsufficientSubordination,
+
+    #[msg("Arithmetic overflow or underflow")]
+    ArithmeticOverflow,
+}
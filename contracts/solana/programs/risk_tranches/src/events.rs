@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::state::TrancheKind;
+
+#[event]
+pub struct TrancheVaultCreated {
+    pub tranche_vault: Pubkey,
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    pub senior_rate_bps: u16,
+    pub min_subordination_bps: u16,
+}
+
+#[event]
+pub struct TrancheDeposited {
+    pub tranche_vault: Pubkey,
+    pub user: Pubkey,
+    pub kind: TrancheKind,
+    pub shares: u64,
+    pub value: u64,
+    pub tokens: u64,
+}
+
+#[event]
+pub struct TrancheWithdrawn {
+    pub tranche_vault: Pubkey,
+    pub user: Pubkey,
+    pub kind: TrancheKind,
+    pub tokens: u64,
+    pub value: u64,
+    pub shares: u64,
+}
+
+#[event]
+pub struct WaterfallSettled {
+    pub tranche_vault: Pubkey,
+    /// Value of the escrowed shares at the yield vault's share price
+    pub total_value: u64,
+    /// Signed change in each tranche's claim since the last settlement
+    pub senior_change: i64,
+    pub junior_change: i64,
+    pub senior_assets: u64,
+    pub junior_assets: u64,
+}
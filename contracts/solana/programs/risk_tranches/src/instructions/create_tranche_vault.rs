@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use yield_vault::constants::{BPS_DENOMINATOR, SHARE_MINT_SEED};
+use yield_vault::state::VaultState;
+
+use crate::constants::*;
+use crate::error::TrancheError;
+use crate::events::TrancheVaultCreated;
+use crate::state::TrancheVault;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct TrancheVaultParams {
+    pub senior_rate_bps: u16,
+    pub min_subordination_bps: u16,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateTrancheVault<'info> {
+    /// The account creating (and paying for) the tranche vault
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The yield vault whose shares back the tranches
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+        seeds::program = yield_vault::ID,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    /// The tranche vault state account (PDA) — also the tokens' mint authority
+    #[account(
+        init,
+        payer = authority,
+        space = TrancheVault::LEN,
+        seeds = [TRANCHE_VAULT_SEED, vault.key().as_ref(), authority.key().as_ref()],
+        bump,
+    )]
+    pub tranche_vault: Box<Account<'info, TrancheVault>>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [SENIOR_MINT_SEED, tranche_vault.key().as_ref()],
+        bump,
+        mint::decimals = vault.load()?.asset_decimals,
+        mint::authority = tranche_vault,
+    )]
+    pub senior_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [JUNIOR_MINT_SEED, tranche_vault.key().as_ref()],
+        bump,
+        mint::decimals = vault.load()?.asset_decimals,
+        mint::authority = tranche_vault,
+    )]
+    pub junior_mint: Box<Account<'info, Mint>>,
+
+    /// Escrow token account (PDA) — holds the yield vault shares
+    #[account(
+        init,
+        payer = authority,
+        seeds = [ESCROW_SEED, tranche_vault.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = tranche_vault,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(ctx: Context<CreateTrancheVault>, params: TrancheVaultParams) -> Result<()> {
+    require!(
+        params.senior_rate_bps <= MAX_SENIOR_RATE_BPS,
+        TrancheError::InvalidParams
+    );
+    require!(
+        (params.min_subordination_bps as u128) <= BPS_DENOMINATOR,
+        TrancheError::InvalidParams
+    );
+
+    let tranche_vault = &mut ctx.accounts.tranche_vault;
+    tranche_vault.vault = ctx.accounts.vault.key();
+    tranche_vault.authority = ctx.accounts.authority.key();
+    tranche_vault.senior_mint = ctx.accounts.senior_mint.key();
+    tranche_vault.junior_mint = ctx.accounts.junior_mint.key();
+    tranche_vault.senior_assets = 0;
+    tranche_vault.junior_assets = 0;
+    tranche_vault.senior_rate_bps = params.senior_rate_bps;
+    tranche_vault.min_subordination_bps = params.min_subordination_bps;
+    tranche_vault.last_settled_at = Clock::get()?.unix_timestamp;
+    tranche_vault.bump = ctx.bumps.tranche_vault;
+    tranche_vault.senior_mint_bump = ctx.bumps.senior_mint;
+    tranche_vault.junior_mint_bump = ctx.bumps.junior_mint;
+    tranche_vault.escrow_bump = ctx.bumps.escrow;
+
+    emit_cpi!(TrancheVaultCreated {
+        tranche_vault: tranche_vault.key(),
+        vault: tranche_vault.vault,
+        authority: tranche_vault.authority,
+        senior_rate_bps: params.senior_rate_bps,
+        min_subordination_bps: params.min_subordination_bps,
+    });
+
+    Ok(())
+}
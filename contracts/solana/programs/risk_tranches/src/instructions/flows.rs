@@ -0,0 +1,260 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+use yield_vault::state::VaultState;
+
+use crate::constants::*;
+use crate::error::TrancheError;
+use crate::events::{TrancheDeposited, TrancheWithdrawn};
+use crate::instructions::settle::{settle_waterfall, shares_to_value, value_to_shares};
+use crate::state::{TrancheKind, TrancheVault};
+
+// ──────────────────────────────────────────
+// Deposit
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TrancheDeposit<'info> {
+    pub user: Signer<'info>,
+
+    /// The yield vault, read for its share price
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [TRANCHE_VAULT_SEED, vault.key().as_ref(), tranche_vault.authority.as_ref()],
+        bump = tranche_vault.bump,
+        has_one = vault,
+    )]
+    pub tranche_vault: Box<Account<'info, TrancheVault>>,
+
+    /// Senior or junior mint, matching the deposited tranche
+    #[account(mut)]
+    pub tranche_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, tranche_vault.key().as_ref()],
+        bump = tranche_vault.escrow_bump,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    /// The user's yield vault shares being deposited
+    #[account(
+        mut,
+        token::mint = escrow.mint,
+        token::authority = user,
+    )]
+    pub user_share_account: Box<Account<'info, TokenAccount>>,
+
+    /// Receives the tranche tokens
+    #[account(
+        mut,
+        token::mint = tranche_mint,
+        token::authority = user,
+    )]
+    pub user_tranche_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle_deposit(ctx: Context<TrancheDeposit>, kind: TrancheKind, shares: u64) -> Result<()> {
+    require!(shares > 0, TrancheError::InvalidAmount);
+    require_keys_eq!(
+        ctx.accounts.tranche_mint.key(),
+        ctx.accounts.tranche_vault.mint(kind),
+        TrancheError::MintMismatch
+    );
+
+    let vault = ctx.accounts.vault.load()?;
+    let settled =
+        settle_waterfall(&mut ctx.accounts.tranche_vault, &vault, ctx.accounts.escrow.amount)?;
+    emit_cpi!(settled);
+
+    let value = shares_to_value(&vault, shares)?;
+    require!(value > 0, TrancheError::InvalidAmount);
+
+    let supply = ctx.accounts.tranche_mint.supply;
+    let assets = ctx.accounts.tranche_vault.assets(kind);
+    let tokens = if supply == 0 {
+        value
+    } else {
+        require!(assets > 0, TrancheError::TrancheWipedOut);
+        let tokens = (value as u128)
+            .checked_mul(supply as u128)
+            .ok_or(TrancheError::ArithmeticOverflow)?
+            / (assets as u128);
+        u64::try_from(tokens).map_err(|_| TrancheError::ArithmeticOverflow)?
+    };
+    require!(tokens > 0, TrancheError::InvalidAmount);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_share_account.to_account_info(),
+                to: ctx.accounts.escrow.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        shares,
+    )?;
+
+    let tranche_vault = &mut ctx.accounts.tranche_vault;
+    let claim = tranche_vault.assets_mut(kind);
+    *claim = claim
+        .checked_add(value)
+        .ok_or(TrancheError::ArithmeticOverflow)?;
+    if kind == TrancheKind::Senior {
+        tranche_vault.check_subordination()?;
+    }
+
+    let seeds = tranche_vault.signer_seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+    token::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.tranche_mint.to_account_info(),
+                to: ctx.accounts.user_tranche_account.to_account_info(),
+                authority: tranche_vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        tokens,
+    )?;
+
+    emit_cpi!(TrancheDeposited {
+        tranche_vault: tranche_vault.key(),
+        user: ctx.accounts.user.key(),
+        kind,
+        shares,
+        value,
+        tokens,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Withdraw
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TrancheWithdraw<'info> {
+    pub user: Signer<'info>,
+
+    /// The yield vault, read for its share price
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [TRANCHE_VAULT_SEED, vault.key().as_ref(), tranche_vault.authority.as_ref()],
+        bump = tranche_vault.bump,
+        has_one = vault,
+    )]
+    pub tranche_vault: Box<Account<'info, TrancheVault>>,
+
+    /// Senior or junior mint, matching the withdrawn tranche
+    #[account(mut)]
+    pub tranche_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, tranche_vault.key().as_ref()],
+        bump = tranche_vault.escrow_bump,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    /// Receives the yield vault shares
+    #[account(
+        mut,
+        token::mint = escrow.mint,
+    )]
+    pub user_share_account: Box<Account<'info, TokenAccount>>,
+
+    /// The user's tranche tokens being burned
+    #[account(
+        mut,
+        token::mint = tranche_mint,
+        token::authority = user,
+    )]
+    pub user_tranche_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle_withdraw(
+    ctx: Context<TrancheWithdraw>,
+    kind: TrancheKind,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, TrancheError::InvalidAmount);
+    require_keys_eq!(
+        ctx.accounts.tranche_mint.key(),
+        ctx.accounts.tranche_vault.mint(kind),
+        TrancheError::MintMismatch
+    );
+
+    let vault = ctx.accounts.vault.load()?;
+    let settled =
+        settle_waterfall(&mut ctx.accounts.tranche_vault, &vault, ctx.accounts.escrow.amount)?;
+    emit_cpi!(settled);
+
+    let supply = ctx.accounts.tranche_mint.supply;
+    let assets = ctx.accounts.tranche_vault.assets(kind);
+    let value = (amount as u128)
+        .checked_mul(assets as u128)
+        .ok_or(TrancheError::ArithmeticOverflow)?
+        .checked_div(supply as u128)
+        .ok_or(TrancheError::ArithmeticOverflow)?;
+    let value = u64::try_from(value).map_err(|_| TrancheError::ArithmeticOverflow)?;
+    let shares = value_to_shares(&vault, value)?.min(ctx.accounts.escrow.amount);
+
+    token::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.tranche_mint.to_account_info(),
+                from: ctx.accounts.user_tranche_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let tranche_vault = &mut ctx.accounts.tranche_vault;
+    *tranche_vault.assets_mut(kind) = assets - value;
+    if kind == TrancheKind::Junior {
+        tranche_vault.check_subordination()?;
+    }
+
+    if shares > 0 {
+        let seeds = tranche_vault.signer_seeds();
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow.to_account_info(),
+                    to: ctx.accounts.user_share_account.to_account_info(),
+                    authority: tranche_vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            shares,
+        )?;
+    }
+
+    emit_cpi!(TrancheWithdrawn {
+        tranche_vault: tranche_vault.key(),
+        user: ctx.accounts.user.key(),
+        kind,
+        tokens: amount,
+        value,
+        shares,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,7 @@
+pub mod create_tranche_vault;
+pub mod flows;
+pub mod settle;
+
+pub use create_tranche_vault::*;
+pub use flows::*;
+pub use settle::*;
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use yield_vault::state::VaultState;
+
+use crate::constants::*;
+use crate::error::TrancheError;
+use crate::events::WaterfallSettled;
+use crate::state::TrancheVault;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Settle<'info> {
+    /// The yield vault, read for its share price
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [TRANCHE_VAULT_SEED, vault.key().as_ref(), tranche_vault.authority.as_ref()],
+        bump = tranche_vault.bump,
+        has_one = vault,
+    )]
+    pub tranche_vault: Box<Account<'info, TrancheVault>>,
+
+    #[account(
+        seeds = [ESCROW_SEED, tranche_vault.key().as_ref()],
+        bump = tranche_vault.escrow_bump,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+}
+
+pub fn handler(ctx: Context<Settle>) -> Result<()> {
+    let vault = ctx.accounts.vault.load()?;
+    let settled =
+        settle_waterfall(&mut ctx.accounts.tranche_vault, &vault, ctx.accounts.escrow.amount)?;
+    emit_cpi!(settled);
+
+    Ok(())
+}
+
+/// Underlying value of `shares` at the yield vault's share price, rounded down
+pub fn shares_to_value(vault: &VaultState, shares: u64) -> Result<u64> {
+    if vault.total_shares == 0 {
+        return Ok(0);
+    }
+    let value = (shares as u128)
+        .checked_mul(vault.total_assets as u128)
+        .ok_or(TrancheError::ArithmeticOverflow)?
+        / (vault.total_shares as u128);
+    u64::try_from(value).map_err(|_| error!(TrancheError::ArithmeticOverflow))
+}
+
+/// Yield vault shares worth `value` of underlying, rounded down
+pub fn value_to_shares(vault: &VaultState, value: u64) -> Result<u64> {
+    if vault.total_assets == 0 {
+        return Ok(0);
+    }
+    let shares = (value as u128)
+        .checked_mul(vault.total_shares as u128)
+        .ok_or(TrancheError::ArithmeticOverflow)?
+        / (vault.total_assets as u128);
+    u64::try_from(shares).map_err(|_| error!(TrancheError::ArithmeticOverflow))
+}
+
+/// Mark the escrowed shares to market and run the waterfall, returning the
+/// event for the caller to emit
+pub fn settle_waterfall(
+    tranche_vault: &mut Account<TrancheVault>,
+    vault: &VaultState,
+    escrow_shares: u64,
+) -> Result<WaterfallSettled> {
+    let total_value = shares_to_value(vault, escrow_shares)?;
+    let (senior_change, junior_change) =
+        tranche_vault.apply_waterfall(total_value, Clock::get()?.unix_timestamp)?;
+
+    Ok(WaterfallSettled {
+        tranche_vault: tranche_vault.key(),
+        total_value,
+        senior_change,
+        junior_change,
+        senior_assets: tranche_vault.senior_assets,
+        junior_assets: tranche_vault.junior_assets,
+    })
+}
@@ -0,0 +1,50 @@
+//! Senior/junior risk tranches over a DeFAI yield vault.
+//!
+//! A tranche vault holds shares of one yield vault on behalf of two classes of
+//! depositors. Each `settle` marks the escrowed shares to the vault's share
+//! price and runs a waterfall: gains pay the senior tranche its target rate
+//! first and the rest goes to the junior tranche; losses are absorbed by the
+//! junior tranche until it is exhausted, and only then by the senior.
+//! Keepers settle in the same transaction as the vault's `update_nav`, and
+//! every deposit and withdrawal settles first.
+
+use anchor_lang::prelude::*;
+
+pub mod constants;
+pub mod error;
+pub mod events;
+pub mod instructions;
+pub mod state;
+
+use instructions::*;
+use state::TrancheKind;
+
+declare_id!("TRN1111111111111111111111111111111111111111");
+
+#[program]
+pub mod risk_tranches {
+    use super::*;
+
+    /// Create a senior/junior tranche vault over a yield vault's shares.
+    pub fn create_tranche_vault(
+        ctx: Context<CreateTrancheVault>,
+        params: TrancheVaultParams,
+    ) -> Result<()> {
+        instructions::create_tranche_vault::handler(ctx, params)
+    }
+
+    /// Deposit yield vault shares into the senior or junior tranche.
+    pub fn deposit(ctx: Context<TrancheDeposit>, kind: TrancheKind, shares: u64) -> Result<()> {
+        instructions::flows::handle_deposit(ctx, kind, shares)
+    }
+
+    /// Burn tranche tokens for yield vault shares worth their claim.
+    pub fn withdraw(ctx: Context<TrancheWithdraw>, kind: TrancheKind, amount: u64) -> Result<()> {
+        instructions::flows::handle_withdraw(ctx, kind, amount)
+    }
+
+    /// Run the waterfall at the yield vault's current share price. Permissionless.
+    pub fn settle(ctx: Context<Settle>) -> Result<()> {
+        instructions::settle::handler(ctx)
+    }
+}
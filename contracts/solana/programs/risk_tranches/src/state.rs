@@ -0,0 +1,143 @@
+use anchor_lang::prelude::*;
+use yield_vault::constants::{BPS_DENOMINATOR, SECONDS_PER_YEAR};
+
+use crate::constants::TRANCHE_VAULT_SEED;
+use crate::error::TrancheError;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TrancheKind {
+    /// Priority on NAV decreases, paid up to its target rate
+    Senior,
+    /// Absorbs first losses and earns the gains above the senior's target
+    Junior,
+}
+
+#[account]
+pub struct TrancheVault {
+    /// Yield vault whose shares back the tranches
+    pub vault: Pubkey,
+    /// Account that created the tranche vault — part of the PDA seeds
+    pub authority: Pubkey,
+    pub senior_mint: Pubkey,
+    pub junior_mint: Pubkey,
+    /// Underlying value owed to each tranche as of the last settlement.
+    /// Their sum is what the escrowed shares were worth at that point.
+    pub senior_assets: u64,
+    pub junior_assets: u64,
+    /// Annual return the senior tranche is paid before the junior, in bps
+    pub senior_rate_bps: u16,
+    /// Minimum junior share of the total value, in bps, enforced on senior
+    /// deposits and junior withdrawals
+    pub min_subordination_bps: u16,
+    /// Unix time of the last settlement
+    pub last_settled_at: i64,
+
+    /// PDA bump seeds
+    pub bump: u8,
+    pub senior_mint_bump: u8,
+    pub junior_mint_bump: u8,
+    pub escrow_bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl TrancheVault {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 32 + 32 + 8 + 8 + 2 + 2 + 8 + 1 + 1 + 1 + 1 + 64 = 224
+    pub const LEN: usize = 8 + 224;
+
+    pub fn signer_seeds(&self) -> [&[u8]; 4] {
+        [
+            TRANCHE_VAULT_SEED,
+            self.vault.as_ref(),
+            self.authority.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+
+    pub fn total_assets(&self) -> Result<u64> {
+        self.senior_assets
+            .checked_add(self.junior_assets)
+            .ok_or(error!(TrancheError::ArithmeticOverflow))
+    }
+
+    pub fn mint(&self, kind: TrancheKind) -> Pubkey {
+        match kind {
+            TrancheKind::Senior => self.senior_mint,
+            TrancheKind::Junior => self.junior_mint,
+        }
+    }
+
+    pub fn assets(&self, kind: TrancheKind) -> u64 {
+        match kind {
+            TrancheKind::Senior => self.senior_assets,
+            TrancheKind::Junior => self.junior_assets,
+        }
+    }
+
+    pub fn assets_mut(&mut self, kind: TrancheKind) -> &mut u64 {
+        match kind {
+            TrancheKind::Senior => &mut self.senior_assets,
+            TrancheKind::Junior => &mut self.junior_assets,
+        }
+    }
+
+    /// Check the junior tranche still covers `min_subordination_bps` of the
+    /// total. A vault with no senior claim has nothing to protect.
+    pub fn check_subordination(&self) -> Result<()> {
+        if self.senior_assets == 0 {
+            return Ok(());
+        }
+        let junior = (self.junior_assets as u128) * BPS_DENOMINATOR;
+        let required = (self.total_assets()? as u128) * (self.min_subordination_bps as u128);
+        require!(junior >= required, TrancheError::InsufficientSubordination);
+        Ok(())
+    }
+
+    /// Split the change from the tracked total to `total_value` between the
+    /// tranches. Gains pay the senior its target rate accrued since the last
+    /// settlement, then the junior; losses hit the junior first.
+    /// Returns the signed change to each tranche's claim.
+    pub fn apply_waterfall(&mut self, total_value: u64, now: i64) -> Result<(i64, i64)> {
+        let tracked = self.total_assets()?;
+        let elapsed = now.saturating_sub(self.last_settled_at).max(0) as u128;
+        self.last_settled_at = now;
+
+        let (senior_change, junior_change) = if total_value >= tracked {
+            let gain = total_value - tracked;
+            let senior_due = (self.senior_assets as u128)
+                .checked_mul(self.senior_rate_bps as u128)
+                .and_then(|v| v.checked_mul(elapsed))
+                .ok_or(TrancheError::ArithmeticOverflow)?
+                / (BPS_DENOMINATOR * SECONDS_PER_YEAR);
+            let senior_gain = (gain as u128).min(senior_due) as u64;
+            let junior_gain = gain - senior_gain;
+            self.senior_assets += senior_gain;
+            self.junior_assets += junior_gain;
+            (senior_gain as i128, junior_gain as i128)
+        } else {
+            let loss = tracked - total_value;
+            let junior_loss = loss.min(self.junior_assets);
+            let senior_loss = loss - junior_loss;
+            self.junior_assets -= junior_loss;
+            self.senior_assets -= senior_loss;
+            (-(senior_loss as i128), -(junior_loss as i128))
+        };
+
+        Ok((
+            i64::try_from(senior_change).map_err(|_| TrancheError::ArithmeticOverflow)?,
+            i64::try_from(junior_change).map_err(|_| TrancheError::ArithmeticOverflow)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn len_matches_serialized_size() {
+        let account = TrancheVault::try_from_slice(&[0; TrancheVault::LEN - 8]).unwrap();
+        assert_eq!(8 + account.try_to_vec().unwrap().len(), TrancheVault::LEN);
+    }
+}
@@ -1,20 +1,256 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+
 /// PDA seed for vault state accounts
 pub const VAULT_SEED: &[u8] = b"vault";
 
+/// PDA seed for the protocol-wide config
+pub const PROTOCOL_CONFIG_SEED: &[u8] = b"protocol_config";
+
 /// PDA seed for vault share mint
 pub const SHARE_MINT_SEED: &[u8] = b"share_mint";
 
 /// PDA seed for vault token account (holds underlying assets)
 pub const VAULT_TOKEN_SEED: &[u8] = b"vault_token";
 
+/// PDA seed for per-user position accounts
+pub const POSITION_SEED: &[u8] = b"position";
+
+/// PDA seed for the vault's lifetime fee report
+pub const FEE_REPORT_SEED: &[u8] = b"fee_report";
+
+/// PDA seed for strategy accounts
+pub const STRATEGY_SEED: &[u8] = b"strategy";
+
+/// PDA seed for the vault's share-price history
+pub const PRICE_HISTORY_SEED: &[u8] = b"price_history";
+
+/// PDA seed for the vault's strategy registry
+pub const STRATEGY_REGISTRY_SEED: &[u8] = b"strategy_registry";
+
+/// PDA seed for a pending manager proposal
+pub const MANAGER_PROPOSAL_SEED: &[u8] = b"manager_proposal";
+
+/// PDA seed for a share holder's vote on a manager proposal
+pub const MANAGER_VOTE_SEED: &[u8] = b"manager_vote";
+
+/// PDA seed for the share escrow that holds shares locked by manager votes
+pub const VOTE_ESCROW_SEED: &[u8] = b"vote_escrow";
+
+/// PDA seed for the vault's depositor rewards pool
+pub const REWARDS_SEED: &[u8] = b"rewards";
+
+/// PDA seed for the token account funding depositor rewards
+pub const REWARDS_TOKEN_SEED: &[u8] = b"rewards_token";
+
+/// PDA seed for a user's queued epoch deposit/redemption
+pub const EPOCH_REQUEST_SEED: &[u8] = b"epoch_request";
+
+/// PDA seed for the settlement record of an epoch
+pub const EPOCH_SETTLEMENT_SEED: &[u8] = b"epoch_settlement";
+
+/// PDA seed for the share escrow holding queued redemptions and unclaimed epoch shares
+pub const EPOCH_ESCROW_SEED: &[u8] = b"epoch_escrow";
+
+/// PDA seed for a session key's withdrawal allowance
+pub const SESSION_SEED: &[u8] = b"session";
+
+/// PDA seed for the vault's compressed position history (tree authority)
+pub const POSITION_HISTORY_SEED: &[u8] = b"position_history";
+
+/// PDA seed for the vault's compressed receipt tree (tree creator)
+pub const RECEIPT_TREE_SEED: &[u8] = b"receipt_tree";
+
+/// PDA seed for the vault's strategy disclosure document record
+pub const DISCLOSURE_SEED: &[u8] = b"disclosure";
+
+/// PDA seed for a snapshot airdrop distributor
+pub const AIRDROP_SEED: &[u8] = b"airdrop";
+
+/// PDA seed for the token account an airdrop is paid from
+pub const AIRDROP_TOKEN_SEED: &[u8] = b"airdrop_token";
+
+/// PDA seed marking an airdrop allocation as claimed
+pub const AIRDROP_CLAIM_SEED: &[u8] = b"airdrop_claim";
+
+/// PDA seed for the protocol-wide strategy whitelist
+pub const STRATEGY_WHITELIST_SEED: &[u8] = b"strategy_whitelist";
+
+/// PDA seed for a principal/yield tranche of a vault
+pub const TRANCHE_SEED: &[u8] = b"tranche";
+
+/// PDA seed for a tranche's principal token (PT) mint
+pub const TRANCHE_PRINCIPAL_SEED: &[u8] = b"tranche_principal";
+
+/// PDA seed for a tranche's yield token (YT) mint
+pub const TRANCHE_YIELD_SEED: &[u8] = b"tranche_yield";
+
+/// PDA seed for the share account backing a tranche's tokens
+pub const TRANCHE_ESCROW_SEED: &[u8] = b"tranche_escrow";
+
+/// PDA seed for a manager key's lifetime reputation profile
+pub const MANAGER_PROFILE_SEED: &[u8] = b"manager_profile";
+
+/// PDA seed for a manager's bond in a vault
+pub const MANAGER_BOND_SEED: &[u8] = b"manager_bond";
+
+/// PDA seed for the token account holding a manager's bond
+pub const MANAGER_BOND_TOKEN_SEED: &[u8] = b"manager_bond_token";
+
+/// PDA seed for a user's deposit/withdraw intent
+pub const INTENT_SEED: &[u8] = b"intent";
+
+/// PDA seed for a vault's rebalancing policy
+pub const POLICY_SEED: &[u8] = b"policy";
+
+/// PDA seed for the protocol's oracle feed registry
+pub const FEED_REGISTRY_SEED: &[u8] = b"feed_registry";
+
+/// PDA seed for a vault's proof-of-reserves verifier
+pub const RESERVES_VERIFIER_SEED: &[u8] = b"reserves_verifier";
+
+/// PDA seed for a vault's crank bounty config
+pub const CRANK_BOUNTY_SEED: &[u8] = b"crank_bounty";
+
+/// PDA seed for the token account crank bounties are paid from
+pub const CRANK_BOUNTY_TOKEN_SEED: &[u8] = b"crank_bounty_token";
+
+/// PDA seed for the protocol's compliance flag on a wallet
+pub const WALLET_FLAG_SEED: &[u8] = b"wallet_flag";
+
+/// PDA seed for a vault's audit log of administrative actions
+pub const AUDIT_LOG_SEED: &[u8] = b"audit_log";
+
+/// PDA seed for the protocol-wide TVL, share price and vault count
+pub const GLOBAL_STATS_SEED: &[u8] = b"global_stats";
+
+/// PDA seed for the list of vaults a user holds positions in
+pub const PORTFOLIO_SEED: &[u8] = b"portfolio";
+
+/// PDA seed of the event authority that signs event self-CPIs (`emit_cpi!`)
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+/// SPL account compression program
+pub const SPL_ACCOUNT_COMPRESSION_ID: Pubkey =
+    pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+
+/// SPL noop program, used by account compression to log tree changes
+pub const SPL_NOOP_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+/// Metaplex Bubblegum program, which mints compressed NFTs
+pub const BUBBLEGUM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+
+/// Pyth receiver program, owner of `PriceUpdateV2` accounts
+pub const PYTH_RECEIVER_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Civic gateway program, owner of Civic Pass gateway token accounts
+pub const CIVIC_GATEWAY_ID: Pubkey = pubkey!("gatem74V238djXdzWnJf94Wo1DcnuGkfijbf3AuBhfs");
+
+/// Share mints get at least this many decimals; vaults of lower-decimal
+/// assets scale shares up so share prices keep adequate precision
+pub const MIN_SHARE_DECIMALS: u8 = 6;
+
+/// Most decimals an owner may request for a share mint, unless the asset has
+/// more; keeps `total_shares` within u64 at realistic TVLs
+pub const MAX_SHARE_DECIMALS: u8 = 9;
+
 /// Maximum number of managers per vault
 pub const MAX_MANAGERS: usize = 3;
 
+/// Shortest share-holder voting window on manager proposals, so holders have
+/// time to veto (3 days)
+pub const MIN_MANAGER_VOTE_PERIOD: i64 = 259_200;
+
+/// Number of NAV samples kept in a vault's price history ring buffer
+pub const PRICE_HISTORY_CAPACITY: usize = 256;
+
+/// Number of administrative actions kept in a vault's audit log
+pub const AUDIT_LOG_CAPACITY: usize = 64;
+
+/// Audit log entries returned per `get_audit_log` page, keeping the page
+/// within the 1 KB return data limit
+pub const AUDIT_LOG_PAGE_SIZE: usize = 12;
+
+/// Maximum number of strategies registered per vault
+pub const MAX_STRATEGIES: usize = 64;
+
+/// Maximum number of strategy rules in a rebalancing policy
+pub const MAX_POLICY_RULES: usize = 8;
+
+/// Public inputs of the proof-of-reserves circuit: the vault, the proven
+/// reserves and the observation time
+pub const RESERVES_PUBLIC_INPUTS: usize = 3;
+
+/// Maximum number of asset mints with an approved price feed
+pub const MAX_PRICE_FEEDS: usize = 32;
+
+/// Maximum number of DEX programs whitelisted for zap deposits
+pub const MAX_ZAP_PROGRAMS: usize = 4;
+
+/// Maximum number of strategy ids on the protocol strategy whitelist
+pub const MAX_WHITELISTED_STRATEGIES: usize = 32;
+
+/// Maximum number of vaults listed in a user's portfolio
+pub const MAX_PORTFOLIO_VAULTS: usize = 32;
+
+/// Maximum number of underlying mints the protocol `GlobalStats` keeps
+/// totals for
+pub const MAX_STATS_MINTS: usize = 16;
+
+/// Longest receipt base URI; with the terms appended, receipt URIs stay
+/// within Bubblegum's 200-byte limit
+pub const MAX_RECEIPT_BASE_URI_LEN: usize = 64;
+
+/// Longest strategy disclosure document URI
+pub const MAX_DISCLOSURE_URI_LEN: usize = 200;
+
 /// Maximum fee in basis points (50%)
 pub const MAX_FEE_BPS: u16 = 5_000;
 
+/// Maximum crank bounty a stop-loss may pay, in basis points (1%)
+pub const MAX_STOP_LOSS_BOUNTY_BPS: u16 = 100;
+
+/// Maximum exit fee a vault may charge early withdrawals, in basis points (2%)
+pub const MAX_EXIT_FEE_BPS: u16 = 200;
+
+/// Maximum entry or exit fee the liquidity fee curve may reach, in basis
+/// points (2%)
+pub const MAX_LIQUIDITY_FEE_BPS: u16 = 200;
+
+/// How strongly a strategy's debt ceiling follows its realized returns: a
+/// report returning 1% on the strategy's debt moves the ceiling by 10%
+pub const DEBT_CEILING_RESPONSE: u128 = 10;
+
 /// Basis points denominator (100%)
 pub const BPS_DENOMINATOR: u128 = 10_000;
 
+/// Fixed-point scale of the rewards-per-share accumulator
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
 /// Seconds in a year (365 days)
 pub const SECONDS_PER_YEAR: u128 = 31_536_000;
+
+/// Seconds holders keep the previous fee rates after an increase, giving
+/// them time to exit before the new rates apply (14 days)
+pub const FEE_INCREASE_GRACE_PERIOD: i64 = 1_209_600;
+
+/// Seconds a proposed strategy disclosure waits before it replaces the
+/// current one, giving holders time to review it and exit (14 days)
+pub const DISCLOSURE_TIMELOCK: i64 = 1_209_600;
+
+/// Length of the window the vault's outflow limit applies to (24 hours)
+pub const OUTFLOW_WINDOW: i64 = 86_400;
+
+/// Length of the window the vault's deposit inflow limit applies to (1 hour)
+pub const INFLOW_WINDOW: i64 = 3_600;
+
+/// Maximum assets (in base units) a vault may report with zero shares outstanding
+pub const INVARIANT_DUST_TOLERANCE: u64 = 1_000;
+
+/// Oracle staleness limit `strict-checks` builds apply to vaults that set
+/// none, in seconds
+pub const STRICT_MAX_ORACLE_STALENESS: i64 = 60;
+
+/// Deepest instruction stack height at which `strict-checks` builds accept
+/// flows: 1 is a top-level instruction, 2 a CPI from one calling program
+pub const STRICT_MAX_STACK_HEIGHT: usize = 2;
@@ -43,4 +43,316 @@ pub enum VaultError {
 
     #[msg("Insufficient assets in vault for withdrawal")]
     InsufficientAssets,
+
+    #[msg("Managers must be added through a share-holder vote")]
+    ManagerVoteRequired,
+
+    #[msg("Share-holder manager voting is not enabled for this vault")]
+    ManagerVotingDisabled,
+
+    #[msg("Manager vote period is below the minimum; voting cannot be turned off once enabled")]
+    ManagerVotePeriodTooShort,
+
+    #[msg("The voting window for this proposal has closed")]
+    VotingClosed,
+
+    #[msg("The voting window for this proposal has not ended")]
+    VotingNotEnded,
+
+    #[msg("Strategy still holds vault funds")]
+    StrategyHasDebt,
+
+    #[msg("Deallocation exceeds the strategy's balance")]
+    InsufficientStrategyFunds,
+
+    #[msg("Vault has no outstanding shares")]
+    NoSharesOutstanding,
+
+    #[msg("Vault balance matches tracked assets; nothing to skim")]
+    NothingToSkim,
+
+    #[msg("The vault's underlying and share tokens cannot be rescued")]
+    CannotRescueVaultToken,
+
+    #[msg("Only tokens in the vault's associated token accounts can be rescued")]
+    CannotRescueHolderTokens,
+
+    #[msg("Position still holds shares")]
+    PositionNotEmpty,
+
+    #[msg("Vault is winding down")]
+    VaultWindingDown,
+
+    #[msg("Vault must finish winding down before it can be closed")]
+    WindDownNotComplete,
+
+    #[msg("Vault accounting invariant violated")]
+    InvariantViolation,
+
+    #[msg("Strategy registry is full")]
+    StrategyRegistryFull,
+
+    #[msg("Strategy is not in the vault's registry")]
+    StrategyNotRegistered,
+
+    #[msg("Vault still has assets attested on another chain")]
+    ExternalNavOutstanding,
+
+    #[msg("NAV attestation is older than the last one applied")]
+    StaleNavAttestation,
+
+    #[msg("Withdrawal is too soon after the last deposit")]
+    WithdrawTooSoon,
+
+    #[msg("NAV updates cannot share a transaction with deposits or withdrawals")]
+    NavUpdateWithUserFlow,
+
+    #[msg("Liquidity reserve is held for small withdrawals")]
+    LiquidityReserved,
+
+    #[msg("Configuration value is out of range")]
+    InvalidConfig,
+
+    #[msg("Vault liquidity is at or above its target")]
+    LiquiditySufficient,
+
+    #[msg("A deallocation request is already pending")]
+    DeallocationAlreadyRequested,
+
+    #[msg("Allocations are suspended until the overdue deallocation is fulfilled")]
+    AllocationsSuspended,
+
+    #[msg("Treasury account for the vault's fee payout mode was not provided")]
+    FeeTreasuryMissing,
+
+    #[msg("No rewards to claim")]
+    NoRewardsToClaim,
+
+    #[msg("Deposits and withdrawals are queued per epoch on this vault")]
+    EpochGated,
+
+    #[msg("Epoch gating is not enabled on this vault")]
+    EpochsDisabled,
+
+    #[msg("Claim the request from the previous epoch first")]
+    EpochRequestUnclaimed,
+
+    #[msg("The current epoch has not ended")]
+    EpochNotEnded,
+
+    #[msg("The current epoch still has queued requests")]
+    EpochRequestsPending,
+
+    #[msg("Vaults created from the template must pass the protocol config")]
+    ProtocolConfigRequired,
+
+    #[msg("Vault accounts have not been initialized")]
+    VaultNotInitialized,
+
+    #[msg("Vault accounts are already initialized")]
+    VaultAlreadyInitialized,
+
+    #[msg("DEX program is not whitelisted for zap deposits")]
+    ZapProgramNotWhitelisted,
+
+    #[msg("Zap input token is already the vault's underlying")]
+    ZapInputIsUnderlying,
+
+    #[msg("Swap returned less than the minimum output")]
+    SlippageExceeded,
+
+    #[msg("Session has expired")]
+    SessionExpired,
+
+    #[msg("Withdrawal exceeds the session's remaining allowance")]
+    SessionLimitExceeded,
+
+    #[msg("Snapshot accounts must be (position, share account) pairs of this vault")]
+    InvalidSnapshotAccounts,
+
+    #[msg("Merkle proof does not match the airdrop root")]
+    InvalidProof,
+
+    #[msg("Airdrop has expired")]
+    AirdropExpired,
+
+    #[msg("Airdrop has not expired yet")]
+    AirdropNotExpired,
+
+    #[msg("Share price is further below its pre-loss level than max_loss_bps allows")]
+    LossExceedsMax,
+
+    #[msg("Tranche has matured")]
+    TrancheMatured,
+
+    #[msg("Tranche has not matured")]
+    TrancheNotMatured,
+
+    #[msg("Tranche has not been settled")]
+    TrancheNotSettled,
+
+    #[msg("Tranche has already been settled")]
+    TrancheAlreadySettled,
+
+    #[msg("Strategy id is not on the protocol whitelist")]
+    StrategyNotWhitelisted,
+
+    #[msg("Strategy whitelist is full")]
+    StrategyWhitelistFull,
+
+    #[msg("Strategy has not reported within the vault's max report age")]
+    StrategyStale,
+
+    #[msg("Oracle account does not match the vault's configured price oracle")]
+    InvalidOracle,
+
+    #[msg("Oracle confidence interval is too wide to trust the price")]
+    OracleUntrusted,
+
+    #[msg("Managers must pass their reputation profile")]
+    ManagerProfileRequired,
+
+    #[msg("Manager has not bonded the vault's minimum manager bond")]
+    ManagerBondTooLow,
+
+    #[msg("Manager bond already has a slash pending")]
+    SlashPending,
+
+    #[msg("Manager bond has no slash pending")]
+    NoSlashPending,
+
+    #[msg("Slash appeal period has not ended")]
+    SlashAppealPeriodActive,
+
+    #[msg("Bond cannot be withdrawn while the key manages the vault")]
+    ManagerStillActive,
+
+    #[msg("Share price has not reached the intent's limit")]
+    IntentNotTriggered,
+
+    #[msg("Intent has expired")]
+    IntentExpired,
+
+    #[msg("Source account does not hold the token the intent spends")]
+    IntentSourceMismatch,
+
+    #[msg("Position has no stop-loss set")]
+    StopLossNotSet,
+
+    #[msg("Share price is above the stop-loss level")]
+    StopLossNotTriggered,
+
+    #[msg("A fee holiday is in effect and cannot be changed")]
+    FeeHolidayActive,
+
+    #[msg("Market pool accounts do not match the vault config")]
+    InvalidMarketPool,
+
+    #[msg("Shares trade on the market at too deep a discount to NAV")]
+    MarketBelowNav,
+
+    #[msg("Trade does not conform to the vault's rebalancing policy")]
+    PolicyViolation,
+
+    #[msg("Vault enforces a rebalancing policy; use rebalance")]
+    PolicyRequired,
+
+    #[msg("Rebalance accounts do not match the trades")]
+    InvalidRebalanceAccounts,
+
+    #[msg("Batch accounts must be (vault, fee report, price history, audit log, oracle, verifier) groups per value")]
+    InvalidNavBatchAccounts,
+
+    #[msg("Oracle is not the registry's approved feed for the vault's asset")]
+    FeedNotRegistered,
+
+    #[msg("Feed registry has no free slot")]
+    FeedRegistryFull,
+
+    #[msg("Oracle price is older than the feed's staleness limit")]
+    OracleStale,
+
+    #[msg("Proof of reserves does not verify")]
+    InvalidReservesProof,
+
+    #[msg("NAV increase is not covered by a recent proof of reserves")]
+    ReservesNotProven,
+
+    #[msg("Crank bounty accounts must all be given and belong to the vault")]
+    InvalidCrankBountyAccounts,
+
+    #[msg("Deposits and withdrawals are outside the vault's flow window")]
+    FlowWindowClosed,
+
+    #[msg("Depositor has no valid pass of the vault's gatekeeper network")]
+    CredentialRequired,
+
+    #[msg("Depositor's gatekeeper pass has expired")]
+    CredentialExpired,
+
+    #[msg("Wallet is on a sanctions list")]
+    WalletSanctioned,
+
+    #[msg("Wallet is in a restricted jurisdiction")]
+    WalletGeofenced,
+
+    #[msg("Fees cannot be raised again until the last increase's grace period ends")]
+    FeeGracePeriodActive,
+
+    #[msg("Allocation would take the strategy's debt above its debt ceiling")]
+    DebtCeilingExceeded,
+
+    #[msg("Vault is in a call to an external program and cannot be re-entered")]
+    Reentrancy,
+
+    #[msg("Strategy adapter version is below the vault's minimum")]
+    StrategyVersionTooLow,
+
+    #[msg("Vault only allocates to strategies marked audited")]
+    StrategyNotAudited,
+
+    #[msg("Strategy is unrated or its risk score exceeds the vault's limit")]
+    StrategyRiskTooHigh,
+
+    #[msg("Routed strategies must be drawable and passed in withdraw priority order")]
+    InvalidWithdrawRoute,
+
+    #[msg("Instruction moves real funds and is unavailable to paper-trading vaults")]
+    PaperVault,
+
+    #[msg("Benchmark level is older than the benchmark feed's staleness limit")]
+    BenchmarkStale,
+
+    #[msg("Donation recipient account for the vault's fee payout mode was not provided")]
+    DonationAccountMissing,
+
+    #[msg("Receipt tree belongs to a different vault")]
+    InvalidReceiptTree,
+
+    #[msg("Epoch request already belongs to that owner")]
+    EpochRequestSameOwner,
+
+    #[msg("Not enough rounding dust has accumulated to mint a share")]
+    NoDustToSweep,
+
+    #[msg("Called through too many programs for a strict build")]
+    CpiTooDeep,
+
+    #[msg("The vault's lifecycle does not allow this status change")]
+    InvalidStatusTransition,
+
+    #[msg("Deposit nonce was already used; the deposit may have landed before")]
+    DuplicateDepositNonce,
+
+    #[msg("Withdrawal exceeds the vault's window outflow limit; queue it as a withdraw intent")]
+    OutflowLimitReached,
+
+    #[msg("Deposit exceeds the vault's inflow limit for this hour; retry once it reopens")]
+    InflowRateLimited,
+
+    #[msg("No strategy disclosure update has been proposed")]
+    NoDisclosurePending,
+
+    #[msg("Proposed strategy disclosure is still within its timelock")]
+    DisclosureTimelockActive,
 }
@@ -1,73 +1,1019 @@
+//! Events the vault emits through a CPI to itself. Every event about a vault
+//! carries the vault's next `event_seq`, so an indexer that sees a gap in the
+//! sequence can backfill just the missing range.
+
 use anchor_lang::prelude::*;
 
+use crate::state::{
+    ComplianceFlag, CrankKind, IntentKind, ReceiptKind, SlashReason, VaultStatus,
+};
+
+/// A vault finished initialization (step 2)
 #[event]
 pub struct VaultInitialized {
     pub vault: Pubkey,
+    pub event_seq: u64,
     pub mint: Pubkey,
     pub owner: Pubkey,
     pub deposit_cap: u64,
     pub management_fee_bps: u16,
     pub performance_fee_bps: u16,
+    /// Whether the vault only simulates its flows
+    pub paper: bool,
 }
 
+/// The vault moved to a new lifecycle stage
+#[event]
+pub struct VaultStatusChanged {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub previous: VaultStatus,
+    pub status: VaultStatus,
+}
+
+/// Underlying deposited and shares minted to the depositor
 #[event]
 pub struct Deposited {
     pub vault: Pubkey,
+    pub event_seq: u64,
     pub user: Pubkey,
     pub amount: u64,
     pub shares_minted: u64,
+    /// Underlying withheld as the liquidity entry fee and left in the vault
+    pub entry_fee: u64,
+    /// Integrator tag echoed from the instruction (referral, agent run id, ...)
+    pub memo: Option<[u8; 32]>,
 }
 
+/// Shares burned and underlying returned to the holder
 #[event]
 pub struct Withdrawn {
     pub vault: Pubkey,
+    pub event_seq: u64,
+    /// Share holder who signed the withdrawal
     pub user: Pubkey,
+    /// Token account the underlying was paid to
+    pub recipient: Pubkey,
     pub shares_burned: u64,
     pub amount_returned: u64,
+    /// Underlying withheld as the early and liquidity exit fees and left in
+    /// the vault
+    pub exit_fee: u64,
+    /// Integrator tag echoed from the instruction (referral, agent run id, ...)
+    pub memo: Option<[u8; 32]>,
 }
 
+/// A user closed their empty position account
+#[event]
+pub struct PositionClosed {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub share_account_closed: bool,
+}
+
+/// Assets donated to the vault, raising the share price
+#[event]
+pub struct Donated {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub donor: Pubkey,
+    pub amount: u64,
+    pub total_assets: u64,
+}
+
+/// Untracked tokens in the vault token account were reconciled
+#[event]
+pub struct Skimmed {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub amount: u64,
+    /// true = absorbed into NAV, false = swept to treasury
+    pub absorbed: bool,
+    pub total_assets: u64,
+}
+
+/// A manager reported a new NAV
 #[event]
 pub struct NavUpdated {
     pub vault: Pubkey,
+    pub event_seq: u64,
     pub old_total_assets: u64,
     pub new_total_assets: u64,
     pub manager: Pubkey,
+    /// Hash of the valuation inputs and model output the manager stored
+    /// off-chain, for adjudicating disputes against the committed data
+    pub valuation_commitment: Option<[u8; 32]>,
 }
 
+/// Accrued fees were paid to the treasury
 #[event]
 pub struct FeesCollected {
     pub vault: Pubkey,
+    pub event_seq: u64,
     pub fee_shares_minted: u64,
     pub fee_amount: u64,
+    /// Part of `fee_amount` paid to the donation recipient
+    pub donated: u64,
 }
 
+/// Rounding dust from share conversions was minted to the treasury as shares
+#[event]
+pub struct DustSwept {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    /// Whole asset units of dust swept
+    pub dust_amount: u64,
+    pub shares_minted: u64,
+}
+
+/// The owner set the share of collected fees donated to a public-goods
+/// recipient
+#[event]
+pub struct FeeDonationSet {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    /// Default key = no donation
+    pub recipient: Pubkey,
+    pub donation_bps: u16,
+}
+
+/// Deposits and withdrawals were paused
 #[event]
 pub struct VaultPausedEvent {
     pub vault: Pubkey,
+    pub event_seq: u64,
 }
 
+/// Deposits and withdrawals were resumed
 #[event]
 pub struct VaultUnpausedEvent {
     pub vault: Pubkey,
+    pub event_seq: u64,
+}
+
+/// The vault began winding down ahead of closing
+#[event]
+pub struct WindDownStarted {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub total_debt: u64,
 }
 
+/// A manager was authorized
 #[event]
 pub struct ManagerAdded {
     pub vault: Pubkey,
+    pub event_seq: u64,
     pub manager: Pubkey,
 }
 
+/// A manager was deauthorized
 #[event]
 pub struct ManagerRemoved {
     pub vault: Pubkey,
+    pub event_seq: u64,
     pub manager: Pubkey,
 }
 
+/// The owner changed the vault's caps or fees
 #[event]
 pub struct ConfigUpdated {
     pub vault: Pubkey,
+    pub event_seq: u64,
     pub deposit_cap: u64,
     pub min_deposit: u64,
     pub management_fee_bps: u16,
     pub performance_fee_bps: u16,
 }
+
+/// The owner started or cleared a deposit cap schedule
+#[event]
+pub struct DepositCapScheduled {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub start_cap: u64,
+    pub increment: u64,
+    /// Seconds between increases (0 = schedule cleared)
+    pub interval: i64,
+    pub started_at: i64,
+}
+
+/// The owner set or removed the vault's deposit and withdrawal window
+#[event]
+pub struct FlowWindowSet {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    /// Window period in seconds (0 = always open)
+    pub period: i64,
+    pub open: i64,
+    pub close: i64,
+}
+
+/// The owner set or removed the vault's early exit fee
+#[event]
+pub struct ExitFeeSet {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    /// Full exit fee in basis points (0 = none)
+    pub fee_bps: u16,
+    /// Seconds after a deposit the full fee applies
+    pub full_period: i64,
+    /// Seconds after a deposit the fee reaches zero
+    pub decay_end: i64,
+}
+
+/// The owner made the vault permissioned under a Civic gatekeeper network,
+/// or opened it again (default key)
+#[event]
+pub struct GatekeeperNetworkSet {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub gatekeeper_network: Pubkey,
+}
+
+/// A manager was proposed for share-holder vote
+#[event]
+pub struct ManagerProposed {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub manager: Pubkey,
+    pub voting_ends_at: i64,
+}
+
+/// A share holder locked shares to vote on a manager proposal
+#[event]
+pub struct ManagerVoteCast {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub manager: Pubkey,
+    pub voter: Pubkey,
+    pub approve: bool,
+    pub weight: u64,
+}
+
+/// Voting on a manager proposal closed
+#[event]
+pub struct ManagerProposalResolved {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub manager: Pubkey,
+    pub approved: bool,
+    pub votes_for: u64,
+    pub votes_against: u64,
+}
+
+/// A strategy was registered with the vault
+#[event]
+pub struct StrategyAdded {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub strategy: Pubkey,
+    pub strategy_id: Pubkey,
+    pub authority: Pubkey,
+}
+
+/// A strategy was removed from the vault
+#[event]
+pub struct StrategyRemoved {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub strategy: Pubkey,
+}
+
+/// Assets were deployed to a strategy
+#[event]
+pub struct StrategyAllocated {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub strategy: Pubkey,
+    pub amount: u64,
+    pub current_debt: u64,
+}
+
+/// A strategy returned funds and reported its gain or loss
+#[event]
+pub struct StrategyReport {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub strategy: Pubkey,
+    pub strategy_id: Pubkey,
+    pub gain: u64,
+    pub loss: u64,
+    pub current_debt: u64,
+    pub total_gain: u64,
+    pub total_loss: u64,
+    /// Debt ceiling after the report's adjustment (0 = no ceiling)
+    pub debt_ceiling: u64,
+}
+
+/// The owner set or removed the bounds a strategy's debt ceiling moves within
+#[event]
+pub struct DebtCeilingBoundsSet {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub strategy: Pubkey,
+    pub min_debt_ceiling: u64,
+    /// Upper bound (0 = no ceiling)
+    pub max_debt_ceiling: u64,
+    /// Ceiling after clamping to the new bounds
+    pub debt_ceiling: u64,
+}
+
+/// The owner set where a strategy sits in the withdrawal route
+#[event]
+pub struct WithdrawRouteSet {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub strategy: Pubkey,
+    pub withdraw_priority: u8,
+    /// Most pulled per withdrawal (0 = removed from the route)
+    pub instant_withdraw_limit: u64,
+}
+
+/// A withdrawal pulled its liquidity shortfall from strategies
+#[event]
+pub struct WithdrawalRouted {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub user: Pubkey,
+    /// Underlying pulled back from strategies
+    pub pulled: u64,
+    /// Strategies drawn on
+    pub strategies: u8,
+}
+
+/// Foreign tokens were returned from a vault-owned account
+#[event]
+pub struct TokensRescued {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+/// The cross-chain NAV relayer was changed
+#[event]
+pub struct NavRelayerSet {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub relayer: Pubkey,
+}
+
+/// The owner committed to a new agent policy file hash
+#[event]
+pub struct PolicyCommitted {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    /// SHA-256 of the approved policy file (zero = commitment cleared)
+    pub commitment: [u8; 32],
+}
+
+/// The relayer attested the NAV held by sibling vaults on other chains
+#[event]
+pub struct ExternalNavReported {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub relayer: Pubkey,
+    pub old_external_nav: u64,
+    pub new_external_nav: u64,
+    pub observed_at: i64,
+    pub total_assets: u64,
+}
+
+/// Liquid assets fell below the vault's liquidity target
+#[event]
+pub struct LiquidityLow {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub liquid_assets: u64,
+    pub target: u64,
+}
+
+/// The manager was asked to return funds from a strategy
+#[event]
+pub struct DeallocationRequested {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub strategy: Pubkey,
+    pub requester: Pubkey,
+    pub amount: u64,
+    pub deadline: i64,
+}
+
+/// The depositor rewards pool was funded
+#[event]
+pub struct RewardsFunded {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub amount: u64,
+    pub reward_rate: u64,
+    pub end_time: i64,
+}
+
+/// The owner ended the rewards campaign and took back the unemitted rewards
+#[event]
+pub struct RewardsDefunded {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub amount: u64,
+}
+
+/// A user claimed depositor rewards
+#[event]
+pub struct RewardsClaimed {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+/// A deposit was queued for the current epoch
+#[event]
+pub struct EpochDepositRequested {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub epoch: u64,
+    pub amount: u64,
+}
+
+/// A redemption was queued for the current epoch
+#[event]
+pub struct EpochRedeemRequested {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub epoch: u64,
+    pub shares: u64,
+}
+
+/// A queued epoch request was assigned to a new owner, who now claims it
+#[event]
+pub struct EpochRequestTransferred {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub epoch: u64,
+    pub deposit_assets: u64,
+    pub redeem_shares: u64,
+}
+
+/// An epoch's queued flows were settled
+#[event]
+pub struct EpochSettled {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub epoch: u64,
+    pub total_assets: u64,
+    pub total_shares: u64,
+    pub deposits: u64,
+    pub shares_minted: u64,
+    pub redeems: u64,
+    /// Shares the redemption gate let through; the rest return to requesters
+    pub redeems_filled: u64,
+    pub assets_paid: u64,
+}
+
+/// The owner set the share of NAV one epoch's redemptions may take
+#[event]
+pub struct RedemptionGateSet {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    /// 0 = no gate
+    pub redemption_gate_bps: u16,
+}
+
+/// A user claimed the proceeds of a settled epoch request
+#[event]
+pub struct EpochClaimed {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub epoch: u64,
+    pub shares: u64,
+    pub assets: u64,
+}
+
+/// The protocol template defaults or limits changed
+#[event]
+pub struct ProtocolConfigUpdated {
+    pub admin: Pubkey,
+    pub max_management_fee_bps: u16,
+    pub max_performance_fee_bps: u16,
+}
+
+/// Another token was swapped into the underlying and deposited
+#[event]
+pub struct ZapDeposited {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub input_mint: Pubkey,
+    pub input_amount: u64,
+    /// Underlying received from the swap and deposited
+    pub amount: u64,
+    pub dex_program: Pubkey,
+}
+
+/// A user granted a session key a withdrawal allowance
+#[event]
+pub struct SessionCreated {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub session_key: Pubkey,
+    pub max_amount: u64,
+    pub expires_at: i64,
+}
+
+/// A user revoked a session key
+#[event]
+pub struct SessionRevoked {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub session_key: Pubkey,
+    /// Underlying withdrawn through the session over its lifetime
+    pub spent: u64,
+}
+
+/// A new position snapshot round was opened
+#[event]
+pub struct PositionHistoryRoundStarted {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub round: u64,
+    pub started_at: i64,
+}
+
+/// A holder's position was appended to the compressed position history
+#[event]
+pub struct PositionSnapshotted {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub round: u64,
+    /// Index of the leaf in the history tree
+    pub leaf_index: u64,
+    pub shares: u64,
+    pub total_assets: u64,
+    pub total_shares: u64,
+    pub slot: u64,
+}
+
+/// A compressed NFT receipt recording a flow's terms was minted to its user
+#[event]
+pub struct ReceiptMinted {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub owner: Pubkey,
+    pub kind: ReceiptKind,
+    pub merkle_tree: Pubkey,
+    /// Index of the leaf in the receipt tree, from which the asset id derives
+    pub leaf_index: u64,
+    /// Underlying deposited or paid out
+    pub amount: u64,
+    /// Shares minted or burned
+    pub shares: u64,
+    /// Price of the flow, `amount` per share
+    pub share_price_q64: u128,
+    pub timestamp: i64,
+}
+
+/// A snapshot airdrop was created and funded
+#[event]
+pub struct AirdropCreated {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub airdrop: Pubkey,
+    pub reward_mint: Pubkey,
+    pub snapshot_slot: u64,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub expires_at: i64,
+}
+
+/// A holder claimed their airdrop allocation
+#[event]
+pub struct AirdropClaimed {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub airdrop: Pubkey,
+    pub claimant: Pubkey,
+    pub amount: u64,
+}
+
+/// The owner reclaimed the unclaimed tokens of an expired airdrop
+#[event]
+pub struct AirdropClawedBack {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub airdrop: Pubkey,
+    pub amount: u64,
+}
+
+/// A principal/yield tranche was created
+#[event]
+pub struct TrancheCreated {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub tranche: Pubkey,
+    pub maturity: i64,
+    pub start_price_q64: u128,
+}
+
+/// Shares were escrowed into a tranche for PT and YT
+#[event]
+pub struct TrancheMinted {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub tranche: Pubkey,
+    pub user: Pubkey,
+    pub shares: u64,
+    pub amount: u64,
+}
+
+/// A tranche was settled at its maturity share price
+#[event]
+pub struct TrancheSettled {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub tranche: Pubkey,
+    pub settlement_price_q64: u128,
+}
+
+/// PT and/or YT were burned for escrowed shares
+#[event]
+pub struct TrancheRedeemed {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub tranche: Pubkey,
+    pub user: Pubkey,
+    pub principal_amount: u64,
+    pub yield_amount: u64,
+    pub shares: u64,
+}
+
+/// The protocol admin added or removed a strategy id on the whitelist
+#[event]
+pub struct StrategyWhitelistUpdated {
+    pub strategy_id: Pubkey,
+    pub allowed: bool,
+}
+
+/// The protocol admin recorded a whitelisted strategy's metadata
+#[event]
+pub struct StrategyMetadataSet {
+    pub strategy_id: Pubkey,
+    pub version_major: u16,
+    pub version_minor: u16,
+    pub version_patch: u16,
+    pub audited: bool,
+    /// Higher is riskier (0 = unrated)
+    pub risk_score: u8,
+}
+
+/// The owner set the version, audit and risk requirements strategies must
+/// meet to be registered with or allocated to the vault
+#[event]
+pub struct StrategyRequirementsSet {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub min_version_major: u16,
+    pub min_version_minor: u16,
+    pub min_version_patch: u16,
+    pub audited_only: bool,
+    /// 0 = no limit
+    pub max_risk_score: u8,
+}
+
+/// The protocol admin registered or removed the price feed for a mint
+#[event]
+pub struct PriceFeedUpdated {
+    pub mint: Pubkey,
+    /// Approved oracle (default key = feed removed)
+    pub oracle: Pubkey,
+    pub max_staleness: i64,
+}
+
+/// The owner raised fees; shares outstanding keep the previous rates until
+/// `grace_ends_at` unless their holders redeem them
+#[event]
+pub struct FeeIncreaseGraced {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub fee_epoch: u64,
+    pub grandfathered_shares: u64,
+    pub previous_management_fee_bps: u16,
+    pub previous_performance_fee_bps: u16,
+    pub grace_ends_at: i64,
+}
+
+/// The protocol admin blocked a wallet from depositing
+#[event]
+pub struct WalletFlagged {
+    pub wallet: Pubkey,
+    pub flag: ComplianceFlag,
+}
+
+/// The protocol admin cleared a wallet's compliance flag
+#[event]
+pub struct WalletUnflagged {
+    pub wallet: Pubkey,
+}
+
+/// A vault picked up its asset's registered price feed
+#[event]
+pub struct PriceFeedSynced {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub oracle: Pubkey,
+    pub max_staleness: i64,
+}
+
+/// The owner set the index performance fees are charged relative to
+#[event]
+pub struct BenchmarkSet {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    /// Pyth feed of the index (default key = absolute performance fees)
+    pub oracle: Pubkey,
+    /// Index level performance is measured from
+    pub level: u64,
+}
+
+/// A vault picked up a newer level of its benchmark index
+#[event]
+pub struct BenchmarkSynced {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub level: u64,
+    pub published_at: i64,
+}
+
+/// A strategy has not reported within the vault's `max_report_age`
+#[event]
+pub struct StrategyStale {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub strategy: Pubkey,
+    pub strategy_id: Pubkey,
+    pub last_report: i64,
+    pub current_debt: u64,
+}
+
+/// The owner removed a manager for cause
+#[event]
+pub struct ManagerSlashed {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub manager: Pubkey,
+    /// The manager's lifetime incident count, including this one
+    pub slashing_incidents: u32,
+}
+
+/// A manager added to its bond in a vault
+#[event]
+pub struct ManagerBondPosted {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub manager: Pubkey,
+    pub amount: u64,
+    pub bonded: u64,
+}
+
+/// The owner proposed slashing a manager's bond; executable after the appeal period
+#[event]
+pub struct SlashProposed {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub manager: Pubkey,
+    pub amount: u64,
+    pub reason: SlashReason,
+    pub executable_at: i64,
+}
+
+/// The owner withdrew a pending slash, e.g. after an upheld appeal
+#[event]
+pub struct SlashCancelled {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub manager: Pubkey,
+    pub amount: u64,
+}
+
+/// A manager's bond was slashed into the vault
+#[event]
+pub struct ManagerBondSlashed {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub manager: Pubkey,
+    pub amount: u64,
+    pub reason: SlashReason,
+    pub total_assets: u64,
+}
+
+/// A former manager withdrew its bond
+#[event]
+pub struct ManagerBondWithdrawn {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub manager: Pubkey,
+    pub amount: u64,
+}
+
+/// A user posted a standing deposit or withdraw order
+#[event]
+pub struct IntentPosted {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub intent: Pubkey,
+    pub kind: IntentKind,
+    pub amount: u64,
+    pub limit_price_q64: u128,
+    pub expires_at: i64,
+}
+
+/// An intent was (partly) filled
+#[event]
+pub struct IntentExecuted {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub intent: Pubkey,
+    pub executor: Pubkey,
+    pub kind: IntentKind,
+    /// Underlying deposited or shares redeemed
+    pub amount: u64,
+    pub price_q64: u128,
+    pub remaining: u64,
+}
+
+/// A user cancelled an intent
+#[event]
+pub struct IntentCancelled {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub intent: Pubkey,
+    pub remaining: u64,
+}
+
+/// A user set or cleared the stop-loss on their position
+#[event]
+pub struct StopLossSet {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub reference_price_q64: u128,
+    pub stop_loss_bps: u16,
+    pub bounty_bps: u16,
+}
+
+/// A crank exited a position whose stop-loss triggered
+#[event]
+pub struct StopLossExecuted {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub crank: Pubkey,
+    pub price_q64: u128,
+    /// Shares redeemed to the user
+    pub shares: u64,
+    /// Shares redeemed to the crank as its bounty
+    pub bounty_shares: u64,
+}
+
+/// Fee accrual hit the vault's cap on outstanding fees and the excess was forgone
+#[event]
+pub struct AccruedFeeCapReached {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    /// Outstanding accrued fees after the cap was applied
+    pub cap: u64,
+    /// Fee units dropped by this accrual
+    pub forgone: u64,
+}
+
+/// The owner scheduled or cleared a fee holiday
+#[event]
+pub struct FeeHolidayScheduled {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub start: i64,
+    pub end: i64,
+}
+
+/// The share price on the configured market, against NAV, for arbitrageurs
+#[event]
+pub struct MarketDiscountSignal {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub market_price_q64: u128,
+    pub nav_price_q64: u128,
+    pub discount_bps: u64,
+    /// Whether the discount is deep enough to block deposits
+    pub deposits_blocked: bool,
+}
+
+/// The owner replaced the vault's rebalancing policy
+#[event]
+pub struct PolicySet {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub rule_count: u8,
+    /// Whether managers must allocate through `rebalance`
+    pub enforced: bool,
+}
+
+/// A manager executed a policy-checked rebalance
+#[event]
+pub struct Rebalanced {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub trades: u8,
+}
+
+/// The owner configured the vault's proof-of-reserves verifier
+#[event]
+pub struct ReservesVerifierSet {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub max_unproven_increase_bps: u16,
+    pub max_proof_age: i64,
+    /// Whether large NAV increases need a proof
+    pub enforced: bool,
+}
+
+/// A proof that strategy positions held at least `reserves` was verified
+#[event]
+pub struct ReservesProven {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub reserves: u64,
+    pub observed_at: i64,
+}
+
+/// The owner configured the vault's crank bounty
+#[event]
+pub struct CrankBountySet {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub bounty: u64,
+    pub min_interval: i64,
+}
+
+/// The owner added underlying to the crank bounty budget
+#[event]
+pub struct CrankBountyFunded {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub amount: u64,
+    /// Budget left after funding
+    pub balance: u64,
+}
+
+/// The owner withdrew underlying from the crank bounty budget
+#[event]
+pub struct CrankBountyDefunded {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub amount: u64,
+    /// Budget left after the withdrawal
+    pub balance: u64,
+}
+
+/// A permissionless crank was paid its bounty
+#[event]
+pub struct CrankBountyPaid {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub crank: Pubkey,
+    pub kind: CrankKind,
+    pub amount: u64,
+}
+
+/// The vault's strategy disclosure document took effect, on creation or once
+/// a proposed replacement passed its timelock
+#[event]
+pub struct DisclosureUpdated {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub content_hash: [u8; 32],
+    pub uri: String,
+}
+
+/// The owner proposed a replacement strategy disclosure document, applicable
+/// from `effective_at`
+#[event]
+pub struct DisclosureProposed {
+    pub vault: Pubkey,
+    pub event_seq: u64,
+    pub content_hash: [u8; 32],
+    pub uri: String,
+    pub effective_at: i64,
+}
@@ -3,32 +3,58 @@ use anchor_lang::prelude::*;
 use crate::constants::*;
 use crate::error::VaultError;
 use crate::events::{
-    ConfigUpdated, ManagerAdded, ManagerRemoved, VaultPausedEvent, VaultUnpausedEvent,
+    ConfigUpdated, DepositCapScheduled, ExitFeeSet, FeeDonationSet, FeeHolidayScheduled,
+    FeeIncreaseGraced, FlowWindowSet, GatekeeperNetworkSet, ManagerAdded, ManagerRemoved,
+    ManagerSlashed, PolicyCommitted, PriceFeedSynced, StrategyRequirementsSet, VaultPausedEvent,
+    VaultUnpausedEvent, WindDownStarted,
 };
-use crate::state::VaultState;
+use crate::instructions::audit_log::record_audit;
+use crate::state::{
+    AuditAction, AuditLog, FeePayoutMode, FeeReport, FeedRegistry, ManagerBond, ManagerProfile,
+    ProtocolConfig, StrategyVersion, VaultState, VaultStatus,
+};
+use crate::utils::EventCpi;
 
 // ──────────────────────────────────────────
 // Pause
 // ──────────────────────────────────────────
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Pause<'info> {
     pub owner: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [VAULT_SEED, vault.mint.as_ref(), vault.owner.as_ref()],
-        bump = vault.bump,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
         has_one = owner @ VaultError::Unauthorized,
     )]
-    pub vault: Box<Account<'info, VaultState>>,
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
 }
 
 pub fn handle_pause(ctx: Context<Pause>) -> Result<()> {
-    ctx.accounts.vault.paused = true;
-    emit!(VaultPausedEvent {
+    ctx.accounts.vault.load_mut()?.transition(
+        VaultStatus::Paused,
+        EventCpi {
+            authority: &ctx.accounts.event_authority,
+            bump: ctx.bumps.event_authority,
+        },
+    )?;
+    emit_cpi!(VaultPausedEvent {
         vault: ctx.accounts.vault.key(),
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
     });
+    record_audit(&ctx.accounts.audit_log, ctx.accounts.owner.key(), AuditAction::Pause, &[])?;
     Ok(())
 }
 
@@ -36,24 +62,107 @@ pub fn handle_pause(ctx: Context<Pause>) -> Result<()> {
 // Unpause
 // ──────────────────────────────────────────
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Unpause<'info> {
     pub owner: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [VAULT_SEED, vault.mint.as_ref(), vault.owner.as_ref()],
-        bump = vault.bump,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
         has_one = owner @ VaultError::Unauthorized,
     )]
-    pub vault: Box<Account<'info, VaultState>>,
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
 }
 
 pub fn handle_unpause(ctx: Context<Unpause>) -> Result<()> {
-    ctx.accounts.vault.paused = false;
-    emit!(VaultUnpausedEvent {
+    ctx.accounts.vault.load_mut()?.transition(
+        VaultStatus::Active,
+        EventCpi {
+            authority: &ctx.accounts.event_authority,
+            bump: ctx.bumps.event_authority,
+        },
+    )?;
+    emit_cpi!(VaultUnpausedEvent {
         vault: ctx.accounts.vault.key(),
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+    });
+    record_audit(&ctx.accounts.audit_log, ctx.accounts.owner.key(), AuditAction::Unpause, &[])?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Begin Wind-Down
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BeginWinddown<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    /// The vault's lifetime fee report
+    #[account(
+        mut,
+        seeds = [FEE_REPORT_SEED, vault.key().as_ref()],
+        bump = fee_report.bump,
+        has_one = vault,
+    )]
+    pub fee_report: Box<Account<'info, FeeReport>>,
+}
+
+pub fn handle_begin_winddown(ctx: Context<BeginWinddown>) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let mut vault = ctx.accounts.vault.load_mut()?;
+
+    // Crystallize fees up to now; nothing accrues from here on
+    let clock = Clock::get()?;
+    let total_assets = vault.total_assets;
+    let events = EventCpi {
+        authority: &ctx.accounts.event_authority,
+        bump: ctx.bumps.event_authority,
+    };
+    let (mgmt_fee, perf_fee) = vault.accrue_fees(total_assets, clock.unix_timestamp, events)?;
+    ctx.accounts.fee_report.record_accrual(mgmt_fee, perf_fee)?;
+    vault.transition(VaultStatus::WindingDown, events)?;
+
+    emit_cpi!(WindDownStarted {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
+        total_debt: vault.total_debt,
     });
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::BeginWinddown,
+        &[],
+    )?;
     Ok(())
 }
 
@@ -69,17 +178,44 @@ pub struct UpdateConfigParams {
     pub performance_fee_bps: u16,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdateConfig<'info> {
     pub owner: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [VAULT_SEED, vault.mint.as_ref(), vault.owner.as_ref()],
-        bump = vault.bump,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
         has_one = owner @ VaultError::Unauthorized,
     )]
-    pub vault: Box<Account<'info, VaultState>>,
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    /// Protocol limits — required for vaults created from the template
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump = protocol_config.bump,
+    )]
+    pub protocol_config: Option<Box<Account<'info, ProtocolConfig>>>,
+
+    /// The vault's lifetime fee report; fees accrue at the old rates up to
+    /// a rate change
+    #[account(
+        mut,
+        seeds = [FEE_REPORT_SEED, vault.key().as_ref()],
+        bump = fee_report.bump,
+        has_one = vault,
+    )]
+    pub fee_report: Box<Account<'info, FeeReport>>,
 }
 
 pub fn handle_update_config(ctx: Context<UpdateConfig>, params: UpdateConfigParams) -> Result<()> {
@@ -92,19 +228,78 @@ pub fn handle_update_config(ctx: Context<UpdateConfig>, params: UpdateConfigPara
         VaultError::InvalidFeeConfig
     );
 
-    let vault = &mut ctx.accounts.vault;
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    if vault.is_from_template() {
+        ctx.accounts
+            .protocol_config
+            .as_ref()
+            .ok_or(VaultError::ProtocolConfigRequired)?
+            .check_fees(params.management_fee_bps, params.performance_fee_bps)?;
+    }
+
+    // Fees accrue at the old rates up to a change. After an increase the
+    // shares outstanding keep the old rates through a grace period, so their
+    // holders can exit before paying more.
+    let previous_mgmt_bps = vault.management_fee_bps;
+    let previous_perf_bps = vault.performance_fee_bps;
+    let changed = params.management_fee_bps != previous_mgmt_bps
+        || params.performance_fee_bps != previous_perf_bps;
+    let raised = params.management_fee_bps > previous_mgmt_bps
+        || params.performance_fee_bps > previous_perf_bps;
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        !raised || !vault.fee_grace_active(now),
+        VaultError::FeeGracePeriodActive
+    );
+    if changed {
+        let total_assets = vault.total_assets;
+        let events = EventCpi {
+            authority: &ctx.accounts.event_authority,
+            bump: ctx.bumps.event_authority,
+        };
+        let (mgmt_fee, perf_fee) = vault.accrue_fees(total_assets, now, events)?;
+        ctx.accounts.fee_report.record_accrual(mgmt_fee, perf_fee)?;
+    }
+    if raised {
+        vault.start_fee_grace(previous_mgmt_bps, previous_perf_bps, now)?;
+    }
+
     vault.deposit_cap = params.deposit_cap;
     vault.min_deposit = params.min_deposit;
     vault.management_fee_bps = params.management_fee_bps;
     vault.performance_fee_bps = params.performance_fee_bps;
 
-    emit!(ConfigUpdated {
-        vault: vault.key(),
+    emit_cpi!(ConfigUpdated {
+        vault: ctx.accounts.vault.key(),
+        event_seq: vault.next_event_seq(),
         deposit_cap: params.deposit_cap,
         min_deposit: params.min_deposit,
         management_fee_bps: params.management_fee_bps,
         performance_fee_bps: params.performance_fee_bps,
     });
+    if raised {
+        emit_cpi!(FeeIncreaseGraced {
+            vault: ctx.accounts.vault.key(),
+            event_seq: vault.next_event_seq(),
+            fee_epoch: vault.fee_epoch,
+            grandfathered_shares: vault.grandfathered_shares,
+            previous_management_fee_bps: previous_mgmt_bps,
+            previous_performance_fee_bps: previous_perf_bps,
+            grace_ends_at: vault.fee_grace_ends_at,
+        });
+    }
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::UpdateConfig,
+        &[
+            &params.deposit_cap.to_le_bytes(),
+            &params.min_deposit.to_le_bytes(),
+            &params.management_fee_bps.to_le_bytes(),
+            &params.performance_fee_bps.to_le_bytes(),
+        ],
+    )?;
 
     Ok(())
 }
@@ -113,27 +308,77 @@ pub fn handle_update_config(ctx: Context<UpdateConfig>, params: UpdateConfigPara
 // Add Manager
 // ──────────────────────────────────────────
 
+#[event_cpi]
 #[derive(Accounts)]
+#[instruction(manager: Pubkey)]
 pub struct AddManager<'info> {
+    #[account(mut)]
     pub owner: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [VAULT_SEED, vault.mint.as_ref(), vault.owner.as_ref()],
-        bump = vault.bump,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
         has_one = owner @ VaultError::Unauthorized,
     )]
-    pub vault: Box<Account<'info, VaultState>>,
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    /// The manager's reputation profile (created on its first vault)
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = ManagerProfile::LEN,
+        seeds = [MANAGER_PROFILE_SEED, manager.as_ref()],
+        bump,
+    )]
+    pub manager_profile: Box<Account<'info, ManagerProfile>>,
+
+    /// The manager's bond, required when the vault sets a minimum bond
+    #[account(
+        seeds = [MANAGER_BOND_SEED, vault.key().as_ref(), manager.as_ref()],
+        bump = manager_bond.bump,
+    )]
+    pub manager_bond: Option<Box<Account<'info, ManagerBond>>>,
+
+    pub system_program: Program<'info, System>,
 }
 
 pub fn handle_add_manager(ctx: Context<AddManager>, manager: Pubkey) -> Result<()> {
-    ctx.accounts.vault.add_manager(manager)?;
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    require!(
+        vault.manager_vote_period == 0,
+        VaultError::ManagerVoteRequired
+    );
+    let bonded = ctx.accounts.manager_bond.as_ref().map_or(0, |bond| bond.amount);
+    require!(bonded >= vault.min_manager_bond, VaultError::ManagerBondTooLow);
+    vault.add_manager(manager)?;
+
+    let profile = &mut ctx.accounts.manager_profile;
+    profile.open(manager, ctx.bumps.manager_profile, Clock::get()?.unix_timestamp);
+    profile.record_vault_added();
 
-    emit!(ManagerAdded {
+    emit_cpi!(ManagerAdded {
         vault: ctx.accounts.vault.key(),
+        event_seq: vault.next_event_seq(),
         manager,
     });
 
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::AddManager,
+        &[manager.as_ref()],
+    )?;
+
     Ok(())
 }
 
@@ -141,26 +386,1135 @@ pub fn handle_add_manager(ctx: Context<AddManager>, manager: Pubkey) -> Result<(
 // Remove Manager
 // ──────────────────────────────────────────
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct RemoveManager<'info> {
     pub owner: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [VAULT_SEED, vault.mint.as_ref(), vault.owner.as_ref()],
-        bump = vault.bump,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
         has_one = owner @ VaultError::Unauthorized,
     )]
-    pub vault: Box<Account<'info, VaultState>>,
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
 }
 
 pub fn handle_remove_manager(ctx: Context<RemoveManager>, manager: Pubkey) -> Result<()> {
-    ctx.accounts.vault.remove_manager(manager)?;
+    ctx.accounts.vault.load_mut()?.remove_manager(manager)?;
+
+    emit_cpi!(ManagerRemoved {
+        vault: ctx.accounts.vault.key(),
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        manager,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::RemoveManager,
+        &[manager.as_ref()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Slash Manager
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(manager: Pubkey)]
+pub struct SlashManager<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        seeds = [MANAGER_PROFILE_SEED, manager.as_ref()],
+        bump = manager_profile.bump,
+    )]
+    pub manager_profile: Box<Account<'info, ManagerProfile>>,
+}
+
+/// Remove `manager` for cause, recording the incident on its profile
+pub fn handle_slash_manager(ctx: Context<SlashManager>, manager: Pubkey) -> Result<()> {
+    ctx.accounts.vault.load_mut()?.remove_manager(manager)?;
+    ctx.accounts.manager_profile.record_slashing();
 
-    emit!(ManagerRemoved {
+    emit_cpi!(ManagerSlashed {
         vault: ctx.accounts.vault.key(),
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
         manager,
+        slashing_incidents: ctx.accounts.manager_profile.slashing_incidents,
     });
 
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SlashManager,
+        &[manager.as_ref()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Withdraw Delay
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct SetWithdrawDelay<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+pub fn handle_set_withdraw_delay(ctx: Context<SetWithdrawDelay>, slots: u64) -> Result<()> {
+    ctx.accounts.vault.load_mut()?.withdraw_delay_slots = slots;
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetWithdrawDelay,
+        &[&slots.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Liquidity Reserve
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct SetLiquidityReserve<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+pub fn handle_set_liquidity_reserve(
+    ctx: Context<SetLiquidityReserve>,
+    reserve_ratio_bps: u16,
+    small_withdrawal_threshold: u64,
+) -> Result<()> {
+    require!(
+        reserve_ratio_bps as u128 <= BPS_DENOMINATOR,
+        VaultError::InvalidConfig
+    );
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.reserve_ratio_bps = reserve_ratio_bps;
+    vault.small_withdrawal_threshold = small_withdrawal_threshold;
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetLiquidityReserve,
+        &[&reserve_ratio_bps.to_le_bytes(), &small_withdrawal_threshold.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Outflow Limit
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct SetOutflowLimit<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+pub fn handle_set_outflow_limit(
+    ctx: Context<SetOutflowLimit>,
+    max_window_outflow: u64,
+    max_window_outflow_bps: u16,
+) -> Result<()> {
+    require!(
+        max_window_outflow_bps as u128 <= BPS_DENOMINATOR,
+        VaultError::InvalidConfig
+    );
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.max_window_outflow = max_window_outflow;
+    vault.max_window_outflow_bps = max_window_outflow_bps;
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetOutflowLimit,
+        &[&max_window_outflow.to_le_bytes(), &max_window_outflow_bps.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Inflow Limit
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct SetInflowLimit<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+pub fn handle_set_inflow_limit(ctx: Context<SetInflowLimit>, max_window_inflow: u64) -> Result<()> {
+    ctx.accounts.vault.load_mut()?.max_window_inflow = max_window_inflow;
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetInflowLimit,
+        &[&max_window_inflow.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Liquidity Fee Curve
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct SetLiquidityFeeCurve<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+/// Price liquidity: below `kink_bps` of total assets held liquid, exits pay
+/// up to `max_exit_fee_bps` and deposits less of `max_entry_fee_bps`, both
+/// linear in the liquid share. `kink_bps = 0` removes the curve.
+pub fn handle_set_liquidity_fee_curve(
+    ctx: Context<SetLiquidityFeeCurve>,
+    kink_bps: u16,
+    max_entry_fee_bps: u16,
+    max_exit_fee_bps: u16,
+) -> Result<()> {
+    require!(
+        max_entry_fee_bps <= MAX_LIQUIDITY_FEE_BPS && max_exit_fee_bps <= MAX_LIQUIDITY_FEE_BPS,
+        VaultError::InvalidFeeConfig
+    );
+    require!(kink_bps as u128 <= BPS_DENOMINATOR, VaultError::InvalidConfig);
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.liquidity_fee_kink_bps = kink_bps;
+    vault.max_liquidity_entry_fee_bps = max_entry_fee_bps;
+    vault.max_liquidity_exit_fee_bps = max_exit_fee_bps;
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetLiquidityFeeCurve,
+        &[
+            &kink_bps.to_le_bytes(),
+            &max_entry_fee_bps.to_le_bytes(),
+            &max_exit_fee_bps.to_le_bytes(),
+        ],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Liquidity Target
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct SetLiquidityTarget<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+pub fn handle_set_liquidity_target(
+    ctx: Context<SetLiquidityTarget>,
+    target_liquidity_bps: u16,
+    deallocation_window: i64,
+) -> Result<()> {
+    require!(
+        target_liquidity_bps as u128 <= BPS_DENOMINATOR,
+        VaultError::InvalidConfig
+    );
+    require!(deallocation_window >= 0, VaultError::InvalidConfig);
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.target_liquidity_bps = target_liquidity_bps;
+    vault.deallocation_window = deallocation_window;
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetLiquidityTarget,
+        &[&target_liquidity_bps.to_le_bytes(), &deallocation_window.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Fee Payout Mode
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct SetFeePayoutMode<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+pub fn handle_set_fee_payout_mode(
+    ctx: Context<SetFeePayoutMode>,
+    mode: FeePayoutMode,
+) -> Result<()> {
+    ctx.accounts.vault.load_mut()?.fee_payout_mode = mode as u8;
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetFeePayoutMode,
+        &[&[mode as u8]],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Fee Donation
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetFeeDonation<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+/// Route `donation_bps` of every fee collection to `recipient`, a charity or
+/// public-goods wallet; a default recipient with zero bps stops donating
+pub fn handle_set_fee_donation(
+    ctx: Context<SetFeeDonation>,
+    recipient: Pubkey,
+    donation_bps: u16,
+) -> Result<()> {
+    require!(
+        donation_bps as u128 <= BPS_DENOMINATOR
+            && (recipient == Pubkey::default()) == (donation_bps == 0),
+        VaultError::InvalidConfig
+    );
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.donation_recipient = recipient;
+    vault.donation_bps = donation_bps;
+
+    emit_cpi!(FeeDonationSet {
+        vault: ctx.accounts.vault.key(),
+        event_seq: vault.next_event_seq(),
+        recipient,
+        donation_bps,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetFeeDonation,
+        &[recipient.as_ref(), &donation_bps.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Max Report Age
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct SetMaxReportAge<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+pub fn handle_set_max_report_age(ctx: Context<SetMaxReportAge>, seconds: i64) -> Result<()> {
+    require!(seconds >= 0, VaultError::InvalidConfig);
+    ctx.accounts.vault.load_mut()?.max_report_age = seconds;
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetMaxReportAge,
+        &[&seconds.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Price Oracle
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct SetPriceOracle<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED],
+        bump = feed_registry.bump,
+    )]
+    pub feed_registry: Box<Account<'info, FeedRegistry>>,
+}
+
+pub fn handle_set_price_oracle(
+    ctx: Context<SetPriceOracle>,
+    oracle: Pubkey,
+    max_conf_bps: u16,
+) -> Result<()> {
+    require!(max_conf_bps as u128 <= BPS_DENOMINATOR, VaultError::InvalidConfig);
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    let max_staleness = if oracle == Pubkey::default() {
+        0
+    } else {
+        ctx.accounts
+            .feed_registry
+            .feed(&vault.mint)
+            .filter(|feed| feed.oracle == oracle)
+            .ok_or(VaultError::FeedNotRegistered)?
+            .max_staleness
+    };
+    vault.price_oracle = oracle;
+    vault.max_oracle_conf_bps = max_conf_bps;
+    vault.max_oracle_staleness = max_staleness;
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetPriceOracle,
+        &[oracle.as_ref(), &max_conf_bps.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Sync Price Feed
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SyncPriceFeed<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED],
+        bump = feed_registry.bump,
+    )]
+    pub feed_registry: Box<Account<'info, FeedRegistry>>,
+}
+
+/// Point a vault that checks an oracle at the feed currently registered for
+/// its asset, picking up replaced oracles and changed staleness limits
+pub fn handle_sync_price_feed(ctx: Context<SyncPriceFeed>) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    require!(
+        vault.price_oracle != Pubkey::default(),
+        VaultError::InvalidOracle
+    );
+    let feed = ctx
+        .accounts
+        .feed_registry
+        .feed(&vault.mint)
+        .ok_or(VaultError::FeedNotRegistered)?;
+    vault.price_oracle = feed.oracle;
+    vault.max_oracle_staleness = feed.max_staleness;
+
+    emit_cpi!(PriceFeedSynced {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
+        oracle: feed.oracle,
+        max_staleness: feed.max_staleness,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Policy Commitment
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPolicyCommitment<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+pub fn handle_set_policy_commitment(
+    ctx: Context<SetPolicyCommitment>,
+    commitment: [u8; 32],
+) -> Result<()> {
+    ctx.accounts.vault.load_mut()?.policy_commitment = commitment;
+
+    emit_cpi!(PolicyCommitted {
+        vault: ctx.accounts.vault.key(),
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        commitment,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetPolicyCommitment,
+        &[&commitment],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Gatekeeper Network
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetGatekeeperNetwork<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+/// Require depositors to hold a pass of the Civic `gatekeeper_network`;
+/// the default key opens the vault to anyone again. Existing holders keep
+/// their shares and may always withdraw.
+pub fn handle_set_gatekeeper_network(
+    ctx: Context<SetGatekeeperNetwork>,
+    gatekeeper_network: Pubkey,
+) -> Result<()> {
+    ctx.accounts.vault.load_mut()?.gatekeeper_network = gatekeeper_network;
+
+    emit_cpi!(GatekeeperNetworkSet {
+        vault: ctx.accounts.vault.key(),
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        gatekeeper_network,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetGatekeeperNetwork,
+        &[gatekeeper_network.as_ref()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Manager Bond
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct SetManagerBond<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+pub fn handle_set_manager_bond(
+    ctx: Context<SetManagerBond>,
+    min_bond: u64,
+    appeal_period: i64,
+) -> Result<()> {
+    require!(appeal_period >= 0, VaultError::InvalidConfig);
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.min_manager_bond = min_bond;
+    vault.slash_appeal_period = appeal_period;
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetManagerBond,
+        &[&min_bond.to_le_bytes(), &appeal_period.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Max Accrued Fee
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct SetMaxAccruedFee<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+pub fn handle_set_max_accrued_fee(
+    ctx: Context<SetMaxAccruedFee>,
+    max_accrued_fee_bps: u16,
+) -> Result<()> {
+    require!(
+        max_accrued_fee_bps as u128 <= BPS_DENOMINATOR,
+        VaultError::InvalidConfig
+    );
+
+    ctx.accounts.vault.load_mut()?.max_accrued_fee_bps = max_accrued_fee_bps;
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetMaxAccruedFee,
+        &[&max_accrued_fee_bps.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Schedule Fee Holiday
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ScheduleFeeHoliday<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+/// Waive fees between `start` and `end`; `start = end = 0` clears a holiday
+/// that has not begun. Holidays cannot be backdated, and one already in
+/// effect cannot be changed until fees have accrued past its end.
+pub fn handle_schedule_fee_holiday(
+    ctx: Context<ScheduleFeeHoliday>,
+    start: i64,
+    end: i64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let clearing = start == 0 && end == 0;
+    require!(clearing || (start >= now && end > start), VaultError::InvalidConfig);
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    require!(!vault.fee_holiday_in_effect(now), VaultError::FeeHolidayActive);
+    vault.fee_holiday_start = start;
+    vault.fee_holiday_end = end;
+
+    emit_cpi!(FeeHolidayScheduled {
+        vault: ctx.accounts.vault.key(),
+        event_seq: vault.next_event_seq(),
+        start,
+        end,
+    });
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::ScheduleFeeHoliday,
+        &[&start.to_le_bytes(), &end.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Schedule Deposit Cap
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ScheduleDepositCap<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+/// Ramp the deposit cap from `start_cap` now, raising it by `increment`
+/// every `interval` seconds up to `deposit_cap` (if non-zero). `interval = 0`
+/// clears the schedule, leaving `deposit_cap` alone in effect.
+pub fn handle_schedule_deposit_cap(
+    ctx: Context<ScheduleDepositCap>,
+    start_cap: u64,
+    increment: u64,
+    interval: i64,
+) -> Result<()> {
+    require!(interval >= 0, VaultError::InvalidConfig);
+    let now = Clock::get()?.unix_timestamp;
+    let clearing = interval == 0;
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.cap_schedule_start_cap = if clearing { 0 } else { start_cap };
+    vault.cap_schedule_increment = if clearing { 0 } else { increment };
+    vault.cap_schedule_interval = interval;
+    vault.cap_schedule_started_at = if clearing { 0 } else { now };
+
+    emit_cpi!(DepositCapScheduled {
+        vault: ctx.accounts.vault.key(),
+        event_seq: vault.next_event_seq(),
+        start_cap: vault.cap_schedule_start_cap,
+        increment: vault.cap_schedule_increment,
+        interval,
+        started_at: vault.cap_schedule_started_at,
+    });
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::ScheduleDepositCap,
+        &[&start_cap.to_le_bytes(), &increment.to_le_bytes(), &interval.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Flow Window
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetFlowWindow<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+/// Only accept deposits and withdrawals between the `open` and `close`
+/// offsets of every `period` seconds, e.g. `(86400, 14 * 3600, 16 * 3600)`
+/// for 14:00 to 16:00 UTC daily or a period of 604800 for weekly windows.
+/// `period = 0` removes the window.
+pub fn handle_set_flow_window(
+    ctx: Context<SetFlowWindow>,
+    period: i64,
+    open: i64,
+    close: i64,
+) -> Result<()> {
+    let clearing = period == 0 && open == 0 && close == 0;
+    let within_period = (0..period).contains(&open) && (0..period).contains(&close);
+    require!(
+        clearing || (within_period && open != close),
+        VaultError::InvalidConfig
+    );
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.flow_window_period = period;
+    vault.flow_window_open = open;
+    vault.flow_window_close = close;
+
+    emit_cpi!(FlowWindowSet {
+        vault: ctx.accounts.vault.key(),
+        event_seq: vault.next_event_seq(),
+        period,
+        open,
+        close,
+    });
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetFlowWindow,
+        &[&period.to_le_bytes(), &open.to_le_bytes(), &close.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────────────────────────────────────────
+// Set the early exit fee
+// ──────────────────────────────────────────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetExitFee<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+/// Charge withdrawals `fee_bps` of the assets returned when made within
+/// `full_period` seconds of the holder's last deposit, falling linearly to
+/// zero at `decay_end` seconds, e.g. `(100, 7 * 86400, 30 * 86400)`.
+/// `fee_bps = 0` removes the fee.
+pub fn handle_set_exit_fee(
+    ctx: Context<SetExitFee>,
+    fee_bps: u16,
+    full_period: i64,
+    decay_end: i64,
+) -> Result<()> {
+    require!(fee_bps <= MAX_EXIT_FEE_BPS, VaultError::InvalidFeeConfig);
+    require!(
+        (0..=decay_end).contains(&full_period) && (fee_bps == 0 || decay_end > 0),
+        VaultError::InvalidConfig
+    );
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.exit_fee_bps = fee_bps;
+    vault.exit_fee_full_period = full_period;
+    vault.exit_fee_decay_end = decay_end;
+
+    emit_cpi!(ExitFeeSet {
+        vault: ctx.accounts.vault.key(),
+        event_seq: vault.next_event_seq(),
+        fee_bps,
+        full_period,
+        decay_end,
+    });
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetExitFee,
+        &[&fee_bps.to_le_bytes(), &full_period.to_le_bytes(), &decay_end.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Strategy Requirements
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetStrategyRequirements<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+/// Only register and allocate to strategies whose protocol registry entry
+/// runs at least `min_version`, is audited when `audited_only` is set, and is
+/// rated at most `max_risk_score` (0 = no limit). Strategies already holding
+/// debt keep it, but receive no new allocations while they fall short.
+pub fn handle_set_strategy_requirements(
+    ctx: Context<SetStrategyRequirements>,
+    min_version: StrategyVersion,
+    audited_only: bool,
+    max_risk_score: u8,
+) -> Result<()> {
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.min_strategy_version_major = min_version.major;
+    vault.min_strategy_version_minor = min_version.minor;
+    vault.min_strategy_version_patch = min_version.patch;
+    vault.audited_strategies_only = audited_only as u8;
+    vault.max_strategy_risk_score = max_risk_score;
+
+    emit_cpi!(StrategyRequirementsSet {
+        vault: ctx.accounts.vault.key(),
+        event_seq: vault.next_event_seq(),
+        min_version_major: min_version.major,
+        min_version_minor: min_version.minor,
+        min_version_patch: min_version.patch,
+        audited_only,
+        max_risk_score,
+    });
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetStrategyRequirements,
+        &[
+            &min_version.major.to_le_bytes(),
+            &min_version.minor.to_le_bytes(),
+            &min_version.patch.to_le_bytes(),
+            &[audited_only as u8, max_risk_score],
+        ],
+    )?;
     Ok(())
 }
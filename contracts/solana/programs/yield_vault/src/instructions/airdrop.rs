@@ -0,0 +1,365 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{AirdropClaimed, AirdropClawedBack, AirdropCreated};
+use crate::instructions::audit_log::record_audit;
+use crate::state::{AirdropClaim, AirdropSnapshot, AuditAction, AuditLog, VaultState};
+use crate::utils::{airdrop_leaf, verify_sorted_proof};
+
+// ──────────────────────────────────────────
+// Create Snapshot
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(snapshot_slot: u64)]
+pub struct CreateSnapshot<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    /// Token paid out by the airdrop
+    pub reward_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = AirdropSnapshot::LEN,
+        seeds = [AIRDROP_SEED, vault.key().as_ref(), &snapshot_slot.to_le_bytes()],
+        bump,
+    )]
+    pub airdrop: Box<Account<'info, AirdropSnapshot>>,
+
+    /// Vault-owned token account the airdrop is paid from
+    #[account(
+        init,
+        payer = owner,
+        seeds = [AIRDROP_TOKEN_SEED, airdrop.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = vault,
+    )]
+    pub airdrop_token_account: Account<'info, TokenAccount>,
+
+    /// The owner's reward token account funding the airdrop
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = owner,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Fund an airdrop of `total_amount` to holders as of `snapshot_slot`, whose
+/// allocations are committed by `merkle_root`. Claims close at `expires_at`.
+pub fn handle_create_snapshot(
+    ctx: Context<CreateSnapshot>,
+    snapshot_slot: u64,
+    merkle_root: [u8; 32],
+    total_amount: u64,
+    expires_at: i64,
+) -> Result<()> {
+    require!(total_amount > 0, VaultError::InvalidAmount);
+    let clock = Clock::get()?;
+    require!(snapshot_slot <= clock.slot, VaultError::InvalidConfig);
+    require!(expires_at > clock.unix_timestamp, VaultError::InvalidConfig);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.funder_token_account.to_account_info(),
+                to: ctx.accounts.airdrop_token_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        total_amount,
+    )?;
+
+    let airdrop = &mut ctx.accounts.airdrop;
+    airdrop.vault = ctx.accounts.vault.key();
+    airdrop.reward_mint = ctx.accounts.reward_mint.key();
+    airdrop.snapshot_slot = snapshot_slot;
+    airdrop.merkle_root = merkle_root;
+    airdrop.total_amount = total_amount;
+    airdrop.claimed_amount = 0;
+    airdrop.expires_at = expires_at;
+    airdrop.bump = ctx.bumps.airdrop;
+    airdrop.token_account_bump = ctx.bumps.airdrop_token_account;
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.open_airdrops = vault
+        .open_airdrops
+        .checked_add(1)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    emit_cpi!(AirdropCreated {
+        vault: airdrop.vault,
+        event_seq: vault.next_event_seq(),
+        airdrop: airdrop.key(),
+        reward_mint: airdrop.reward_mint,
+        snapshot_slot,
+        merkle_root,
+        total_amount,
+        expires_at,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::CreateSnapshot,
+        &[
+            ctx.accounts.reward_mint.key().as_ref(),
+            &snapshot_slot.to_le_bytes(),
+            &merkle_root,
+            &total_amount.to_le_bytes(),
+            &expires_at.to_le_bytes(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Claim Airdrop
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimAirdrop<'info> {
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [AIRDROP_SEED, vault.key().as_ref(), &airdrop.snapshot_slot.to_le_bytes()],
+        bump = airdrop.bump,
+        has_one = vault,
+    )]
+    pub airdrop: Box<Account<'info, AirdropSnapshot>>,
+
+    #[account(
+        mut,
+        seeds = [AIRDROP_TOKEN_SEED, airdrop.key().as_ref()],
+        bump = airdrop.token_account_bump,
+    )]
+    pub airdrop_token_account: Account<'info, TokenAccount>,
+
+    /// Created on claim, so each allocation is paid once
+    #[account(
+        init,
+        payer = claimant,
+        space = AirdropClaim::LEN,
+        seeds = [AIRDROP_CLAIM_SEED, airdrop.key().as_ref(), claimant.key().as_ref()],
+        bump,
+    )]
+    pub claim: Box<Account<'info, AirdropClaim>>,
+
+    /// Receives the claimed tokens
+    #[account(
+        mut,
+        token::mint = airdrop.reward_mint,
+        token::authority = claimant,
+    )]
+    pub claimant_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_claim_airdrop(
+    ctx: Context<ClaimAirdrop>,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let airdrop = &ctx.accounts.airdrop;
+    let claimant = ctx.accounts.claimant.key();
+    require!(
+        Clock::get()?.unix_timestamp < airdrop.expires_at,
+        VaultError::AirdropExpired
+    );
+    require!(
+        verify_sorted_proof(&proof, &airdrop.merkle_root, airdrop_leaf(&claimant, amount)),
+        VaultError::InvalidProof
+    );
+
+    let vault = ctx.accounts.vault.load()?;
+    let seeds = vault.signer_seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.airdrop_token_account.to_account_info(),
+                to: ctx.accounts.claimant_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+    drop(vault);
+
+    let claim = &mut ctx.accounts.claim;
+    claim.airdrop = ctx.accounts.airdrop.key();
+    claim.claimant = claimant;
+    claim.amount = amount;
+    claim.bump = ctx.bumps.claim;
+
+    let airdrop = &mut ctx.accounts.airdrop;
+    airdrop.claimed_amount = airdrop
+        .claimed_amount
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    emit_cpi!(AirdropClaimed {
+        vault: airdrop.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        airdrop: airdrop.key(),
+        claimant,
+        amount,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Clawback Airdrop
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClawbackAirdrop<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [AIRDROP_SEED, vault.key().as_ref(), &airdrop.snapshot_slot.to_le_bytes()],
+        bump = airdrop.bump,
+        has_one = vault,
+    )]
+    pub airdrop: Box<Account<'info, AirdropSnapshot>>,
+
+    #[account(
+        mut,
+        seeds = [AIRDROP_TOKEN_SEED, airdrop.key().as_ref()],
+        bump = airdrop.token_account_bump,
+    )]
+    pub airdrop_token_account: Account<'info, TokenAccount>,
+
+    /// Receives the unclaimed tokens
+    #[account(
+        mut,
+        token::mint = airdrop.reward_mint,
+    )]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Return an expired airdrop's unclaimed tokens and close its accounts.
+/// Owner only.
+pub fn handle_clawback_airdrop(ctx: Context<ClawbackAirdrop>) -> Result<()> {
+    require!(
+        Clock::get()?.unix_timestamp >= ctx.accounts.airdrop.expires_at,
+        VaultError::AirdropNotExpired
+    );
+
+    let vault = ctx.accounts.vault.load()?;
+    let seeds = vault.signer_seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+    let amount = ctx.accounts.airdrop_token_account.amount;
+    if amount > 0 {
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.airdrop_token_account.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+    }
+
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.airdrop_token_account.to_account_info(),
+            destination: ctx.accounts.owner.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+    drop(vault);
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.open_airdrops = vault.open_airdrops.saturating_sub(1);
+
+    emit_cpi!(AirdropClawedBack {
+        vault: ctx.accounts.vault.key(),
+        event_seq: vault.next_event_seq(),
+        airdrop: ctx.accounts.airdrop.key(),
+        amount,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::ClawbackAirdrop,
+        &[ctx.accounts.airdrop.key().as_ref(), &amount.to_le_bytes()],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::*;
+use crate::state::{AuditAction, AuditEntry, AuditLog, AuditRecord, VaultState};
+
+#[derive(Accounts)]
+pub struct GetAuditLog<'info> {
+    #[account(
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+/// Read-only: returns up to `AUDIT_LOG_PAGE_SIZE` entries starting `offset`
+/// entries after the oldest retained one, oldest first, as instruction
+/// return data. An empty page means the end of the log.
+pub fn handler(ctx: Context<GetAuditLog>, offset: u32) -> Result<Vec<AuditRecord>> {
+    let audit_log = ctx.accounts.audit_log.load()?;
+    (offset as usize..)
+        .take(AUDIT_LOG_PAGE_SIZE)
+        .map_while(|index| audit_log.get(index))
+        .map(AuditEntry::to_record)
+        .collect()
+}
+
+/// Append `action` by `actor` to the vault's audit log, stamped with the
+/// current time. `params` are the action's arguments as little-endian bytes.
+pub(crate) fn record_audit(
+    audit_log: &AccountLoader<AuditLog>,
+    actor: Pubkey,
+    action: AuditAction,
+    params: &[&[u8]],
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    audit_log.load_mut()?.record(actor, action, params, now);
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{BenchmarkSet, BenchmarkSynced};
+use crate::instructions::audit_log::record_audit;
+use crate::state::{AuditAction, AuditLog, FeedRegistry, VaultState};
+use crate::utils::{oracle_price, oracle_publish_time};
+
+// ──────────────────────────────────────────
+// Set Benchmark
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetBenchmark<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED],
+        bump = feed_registry.bump,
+    )]
+    pub feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    /// CHECK: checked against the feed registered for `benchmark_mint` and
+    /// parsed as a Pyth price account; ignored when clearing the benchmark
+    pub benchmark_oracle: UncheckedAccount<'info>,
+}
+
+/// Charge performance fees relative to the index priced by the feed the
+/// registry holds for `benchmark_mint` (default key = absolute returns).
+/// Returns are measured from the index's current level.
+pub fn handle_set_benchmark(ctx: Context<SetBenchmark>, benchmark_mint: Pubkey) -> Result<()> {
+    let (oracle, max_staleness, level, published_at) = if benchmark_mint == Pubkey::default() {
+        (Pubkey::default(), 0, 0, 0)
+    } else {
+        let feed = ctx
+            .accounts
+            .feed_registry
+            .feed(&benchmark_mint)
+            .filter(|feed| feed.oracle == ctx.accounts.benchmark_oracle.key())
+            .ok_or(VaultError::FeedNotRegistered)?;
+        let oracle = ctx.accounts.benchmark_oracle.to_account_info();
+        (
+            feed.oracle,
+            feed.max_staleness,
+            oracle_price(&oracle)?,
+            oracle_publish_time(&oracle)?,
+        )
+    };
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.benchmark_oracle = oracle;
+    vault.max_benchmark_staleness = max_staleness;
+    vault.benchmark_level = level;
+    vault.benchmark_latest = level;
+    vault.benchmark_published_at = published_at;
+    require!(
+        !vault.is_benchmark_stale(Clock::get()?.unix_timestamp),
+        VaultError::BenchmarkStale
+    );
+
+    emit_cpi!(BenchmarkSet {
+        vault: ctx.accounts.vault.key(),
+        event_seq: vault.next_event_seq(),
+        oracle,
+        level,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetBenchmark,
+        &[benchmark_mint.as_ref(), oracle.as_ref(), &level.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Sync Benchmark
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SyncBenchmark<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// CHECK: must be the vault's benchmark oracle; parsed as a Pyth price
+    /// account
+    #[account(address = vault.load()?.benchmark_oracle @ VaultError::InvalidOracle)]
+    pub benchmark_oracle: UncheckedAccount<'info>,
+}
+
+/// Pick up the benchmark oracle's latest level, which the next fee accrual
+/// measures returns against. Older updates than the synced one are rejected.
+pub fn handle_sync_benchmark(ctx: Context<SyncBenchmark>) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    require!(vault.has_benchmark(), VaultError::InvalidOracle);
+
+    let oracle = ctx.accounts.benchmark_oracle.to_account_info();
+    let level = oracle_price(&oracle)?;
+    let published_at = oracle_publish_time(&oracle)?;
+    require!(
+        published_at >= vault.benchmark_published_at,
+        VaultError::OracleStale
+    );
+    vault.benchmark_latest = level;
+    vault.benchmark_published_at = published_at;
+
+    emit_cpi!(BenchmarkSynced {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
+        level,
+        published_at,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,449 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{
+    ManagerBondPosted, ManagerBondSlashed, ManagerBondWithdrawn, SlashCancelled, SlashProposed,
+};
+use crate::instructions::audit_log::record_audit;
+use crate::state::{AuditAction, AuditLog, ManagerBond, ManagerProfile, SlashReason, VaultState};
+
+// ──────────────────────────────────────────
+// Post Bond
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PostBond<'info> {
+    /// The manager (or prospective manager) bonding
+    #[account(mut)]
+    pub manager: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(address = vault.load()?.mint)]
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = manager,
+        space = ManagerBond::LEN,
+        seeds = [MANAGER_BOND_SEED, vault.key().as_ref(), manager.key().as_ref()],
+        bump,
+    )]
+    pub bond: Box<Account<'info, ManagerBond>>,
+
+    /// Holds the bonded underlying, under the vault's authority
+    #[account(
+        init_if_needed,
+        payer = manager,
+        seeds = [MANAGER_BOND_TOKEN_SEED, bond.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault,
+    )]
+    pub bond_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = manager,
+    )]
+    pub manager_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Add `amount` of underlying to the signer's bond in the vault
+pub fn handle_post_bond(ctx: Context<PostBond>, amount: u64) -> Result<()> {
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.manager_token_account.to_account_info(),
+                to: ctx.accounts.bond_token_account.to_account_info(),
+                authority: ctx.accounts.manager.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let bond = &mut ctx.accounts.bond;
+    if bond.vault == Pubkey::default() {
+        bond.vault = ctx.accounts.vault.key();
+        bond.manager = ctx.accounts.manager.key();
+        bond.slash_reason = SlashReason::NavMisreport;
+        bond.bump = ctx.bumps.bond;
+        bond.token_account_bump = ctx.bumps.bond_token_account;
+    }
+    bond.amount = bond
+        .amount
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.total_bonded = vault
+        .total_bonded
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    emit_cpi!(ManagerBondPosted {
+        vault: bond.vault,
+        event_seq: vault.next_event_seq(),
+        manager: bond.manager,
+        amount,
+        bonded: bond.amount,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Propose Slash
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeSlash<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        seeds = [MANAGER_BOND_SEED, vault.key().as_ref(), bond.manager.as_ref()],
+        bump = bond.bump,
+        has_one = vault,
+    )]
+    pub bond: Box<Account<'info, ManagerBond>>,
+}
+
+/// Propose slashing `amount` of a manager's bond for a proven `reason`. It can
+/// be executed once the vault's appeal period has passed. Owner only.
+pub fn handle_propose_slash(
+    ctx: Context<ProposeSlash>,
+    amount: u64,
+    reason: SlashReason,
+) -> Result<()> {
+    let appeal_period = ctx.accounts.vault.load()?.slash_appeal_period;
+    let bond = &mut ctx.accounts.bond;
+    require!(!bond.has_pending_slash(), VaultError::SlashPending);
+    require!(amount > 0 && amount <= bond.amount, VaultError::InvalidAmount);
+
+    let executable_at = Clock::get()?
+        .unix_timestamp
+        .checked_add(appeal_period)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    bond.pending_slash = amount;
+    bond.slash_reason = reason;
+    bond.slash_executable_at = executable_at;
+
+    emit_cpi!(SlashProposed {
+        vault: bond.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        manager: bond.manager,
+        amount,
+        reason,
+        executable_at,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::ProposeSlash,
+        &[bond.manager.as_ref(), &amount.to_le_bytes(), &[reason as u8]],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Cancel Slash
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelSlash<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        seeds = [MANAGER_BOND_SEED, vault.key().as_ref(), bond.manager.as_ref()],
+        bump = bond.bump,
+        has_one = vault,
+    )]
+    pub bond: Box<Account<'info, ManagerBond>>,
+}
+
+/// Drop the pending slash, e.g. after upholding the manager's appeal. Owner only.
+pub fn handle_cancel_slash(ctx: Context<CancelSlash>) -> Result<()> {
+    let bond = &mut ctx.accounts.bond;
+    require!(bond.has_pending_slash(), VaultError::NoSlashPending);
+
+    let amount = bond.pending_slash;
+    bond.pending_slash = 0;
+    bond.slash_executable_at = 0;
+
+    emit_cpi!(SlashCancelled {
+        vault: bond.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        manager: bond.manager,
+        amount,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::CancelSlash,
+        &[bond.manager.as_ref()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Execute Slash
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteSlash<'info> {
+    /// Anyone may execute once the appeal period has ended
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        seeds = [MANAGER_BOND_SEED, vault.key().as_ref(), bond.manager.as_ref()],
+        bump = bond.bump,
+        has_one = vault,
+    )]
+    pub bond: Box<Account<'info, ManagerBond>>,
+
+    #[account(
+        mut,
+        seeds = [MANAGER_BOND_TOKEN_SEED, bond.key().as_ref()],
+        bump = bond.token_account_bump,
+    )]
+    pub bond_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// The vault's token account (receives the slashed bond)
+    #[account(
+        mut,
+        seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
+        bump = vault.load()?.token_account_bump,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// The manager's reputation profile, which records the incident
+    #[account(
+        mut,
+        seeds = [MANAGER_PROFILE_SEED, bond.manager.as_ref()],
+        bump = manager_profile.bump,
+    )]
+    pub manager_profile: Box<Account<'info, ManagerProfile>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Move the pending slash from the manager's bond into the vault, raising the
+/// share price for current holders
+pub fn handle_execute_slash(ctx: Context<ExecuteSlash>) -> Result<()> {
+    let bond = &ctx.accounts.bond;
+    require!(bond.has_pending_slash(), VaultError::NoSlashPending);
+    require!(
+        Clock::get()?.unix_timestamp >= bond.slash_executable_at,
+        VaultError::SlashAppealPeriodActive
+    );
+
+    let amount = bond.pending_slash.min(bond.amount);
+    let vault_key = ctx.accounts.vault.key();
+    let vault = ctx.accounts.vault.load()?;
+    let seeds = vault.signer_seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.bond_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    drop(vault);
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.total_assets = vault
+        .total_assets
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    vault.total_bonded = vault.total_bonded.saturating_sub(amount);
+
+    let bond = &mut ctx.accounts.bond;
+    bond.amount -= amount;
+    bond.pending_slash = 0;
+    bond.slash_executable_at = 0;
+    ctx.accounts.manager_profile.record_slashing();
+
+    emit_cpi!(ManagerBondSlashed {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
+        manager: bond.manager,
+        amount,
+        reason: bond.slash_reason,
+        total_assets: vault.total_assets,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.payer.key(),
+        AuditAction::ExecuteSlash,
+        &[bond.manager.as_ref(), &amount.to_le_bytes()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Withdraw Bond
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawBond<'info> {
+    pub manager: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [MANAGER_BOND_SEED, vault.key().as_ref(), manager.key().as_ref()],
+        bump = bond.bump,
+        has_one = vault,
+        has_one = manager,
+    )]
+    pub bond: Box<Account<'info, ManagerBond>>,
+
+    #[account(
+        mut,
+        seeds = [MANAGER_BOND_TOKEN_SEED, bond.key().as_ref()],
+        bump = bond.token_account_bump,
+    )]
+    pub bond_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+        token::authority = manager,
+    )]
+    pub manager_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Return `amount` of the bond to a key that no longer manages the vault and
+/// has no slash pending
+pub fn handle_withdraw_bond(ctx: Context<WithdrawBond>, amount: u64) -> Result<()> {
+    let vault = ctx.accounts.vault.load()?;
+    let bond = &mut ctx.accounts.bond;
+    require!(
+        !vault.is_manager(&bond.manager),
+        VaultError::ManagerStillActive
+    );
+    require!(!bond.has_pending_slash(), VaultError::SlashPending);
+    require!(amount > 0 && amount <= bond.amount, VaultError::InvalidAmount);
+
+    let seeds = vault.signer_seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.bond_token_account.to_account_info(),
+                to: ctx.accounts.manager_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+    drop(vault);
+    bond.amount -= amount;
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.total_bonded = vault.total_bonded.saturating_sub(amount);
+
+    emit_cpi!(ManagerBondWithdrawn {
+        vault: bond.vault,
+        event_seq: vault.next_event_seq(),
+        manager: bond.manager,
+        amount,
+    });
+
+    Ok(())
+}
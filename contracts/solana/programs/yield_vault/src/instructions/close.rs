@@ -1,10 +1,15 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{CloseAccount, Mint, Token, TokenAccount, self};
+use anchor_spl::token::spl_token::instruction::AuthorityType;
+use anchor_spl::token::{self, CloseAccount, Mint, SetAuthority, Token, TokenAccount, Transfer};
 
 use crate::constants::*;
 use crate::error::VaultError;
-use crate::state::VaultState;
+use crate::state::{
+    AuditLog, FeeReport, GlobalStats, PriceHistory, StrategyRegistry, VaultState, VaultStatus,
+};
+use crate::utils::{close_if_created, EventCpi};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CloseVault<'info> {
     /// The vault owner closing the vault
@@ -14,37 +19,155 @@ pub struct CloseVault<'info> {
     /// The vault state — will be closed and rent returned to owner
     #[account(
         mut,
-        seeds = [VAULT_SEED, vault.mint.as_ref(), vault.owner.as_ref()],
-        bump = vault.bump,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
         has_one = owner @ VaultError::Unauthorized,
         close = owner,
     )]
-    pub vault: Box<Account<'info, VaultState>>,
+    pub vault: AccountLoader<'info, VaultState>,
 
     /// The vault's token account — will be closed
     #[account(
         mut,
         seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
-        bump = vault.token_account_bump,
-        token::mint = vault.mint,
+        bump = vault.load()?.token_account_bump,
+        token::mint = vault.load()?.mint,
         token::authority = vault,
     )]
     pub vault_token_account: Account<'info, TokenAccount>,
 
-    /// The share mint — will be closed
+    /// The share mint — SPL mints cannot be closed, so its mint authority is
+    /// revoked instead, permanently retiring it at zero supply
     #[account(
         mut,
-        seeds = [SHARE_MINT_SEED, vault.key().as_ref()],
-        bump = vault.share_mint_bump,
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
         mint::authority = vault,
     )]
     pub share_mint: Account<'info, Mint>,
 
+    /// The vault's fee report — will be closed
+    #[account(
+        mut,
+        seeds = [FEE_REPORT_SEED, vault.key().as_ref()],
+        bump = fee_report.bump,
+        has_one = vault,
+        close = owner,
+    )]
+    pub fee_report: Box<Account<'info, FeeReport>>,
+
+    /// The vault's share-price history — will be closed
+    #[account(
+        mut,
+        seeds = [PRICE_HISTORY_SEED, vault.key().as_ref()],
+        bump = price_history.load()?.bump,
+        has_one = vault,
+        close = owner,
+    )]
+    pub price_history: AccountLoader<'info, PriceHistory>,
+
+    /// The vault's strategy registry — will be closed
+    #[account(
+        mut,
+        seeds = [STRATEGY_REGISTRY_SEED, vault.key().as_ref()],
+        bump = strategy_registry.load()?.bump,
+        has_one = vault,
+        close = owner,
+    )]
+    pub strategy_registry: AccountLoader<'info, StrategyRegistry>,
+
+    /// The vault's audit log — will be closed
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+        close = owner,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    /// The manager vote escrow — required (and closed) if it was ever created
+    #[account(
+        mut,
+        seeds = [VOTE_ESCROW_SEED, vault.key().as_ref()],
+        bump = vault.load()?.vote_escrow_bump,
+    )]
+    pub vote_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// The epoch escrow — required (and closed) if epoch gating was ever enabled
+    #[account(
+        mut,
+        seeds = [EPOCH_ESCROW_SEED, vault.key().as_ref()],
+        bump = vault.load()?.epoch_escrow_bump,
+    )]
+    pub epoch_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// The rewards pool — closed if it was ever created
+    /// CHECK: address checked by seeds; closed only if owned by this program
+    #[account(mut, seeds = [REWARDS_SEED, vault.key().as_ref()], bump)]
+    pub rewards: UncheckedAccount<'info>,
+
+    /// The rewards pool's token account — swept and closed if it was ever
+    /// created
+    /// CHECK: address checked by seeds; parsed only if owned by the token program
+    #[account(mut, seeds = [REWARDS_TOKEN_SEED, vault.key().as_ref()], bump)]
+    pub rewards_token_account: UncheckedAccount<'info>,
+
+    /// Receives rewards left in the pool, required when any are
+    #[account(mut)]
+    pub owner_reward_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// The crank bounty budget — closed if it was ever created
+    /// CHECK: address checked by seeds; closed only if owned by this program
+    #[account(mut, seeds = [CRANK_BOUNTY_SEED, vault.key().as_ref()], bump)]
+    pub crank_bounty: UncheckedAccount<'info>,
+
+    /// The crank bounty token account — must have been defunded, and is closed
+    /// if it was ever created
+    /// CHECK: address checked by seeds; parsed only if owned by the token program
+    #[account(mut, seeds = [CRANK_BOUNTY_TOKEN_SEED, vault.key().as_ref()], bump)]
+    pub crank_bounty_token_account: UncheckedAccount<'info>,
+
+    /// The strategy disclosure — closed if it was ever created
+    /// CHECK: address checked by seeds; closed only if owned by this program
+    #[account(mut, seeds = [DISCLOSURE_SEED, vault.key().as_ref()], bump)]
+    pub disclosure: UncheckedAccount<'info>,
+
+    /// The position history — closed if it was ever created
+    /// CHECK: address checked by seeds; closed only if owned by this program
+    #[account(mut, seeds = [POSITION_HISTORY_SEED, vault.key().as_ref()], bump)]
+    pub position_history: UncheckedAccount<'info>,
+
+    /// The deposit receipt tree — closed if it was ever created
+    /// CHECK: address checked by seeds; closed only if owned by this program
+    #[account(mut, seeds = [RECEIPT_TREE_SEED, vault.key().as_ref()], bump)]
+    pub receipt_tree: UncheckedAccount<'info>,
+
+    /// The manager policy — closed if it was ever created
+    /// CHECK: address checked by seeds; closed only if owned by this program
+    #[account(mut, seeds = [POLICY_SEED, vault.key().as_ref()], bump)]
+    pub policy: UncheckedAccount<'info>,
+
+    /// The proof-of-reserves verifier — closed if it was ever created
+    /// CHECK: address checked by seeds; closed only if owned by this program
+    #[account(mut, seeds = [RESERVES_VERIFIER_SEED, vault.key().as_ref()], bump)]
+    pub reserves_verifier: UncheckedAccount<'info>,
+
+    /// The protocol stats account, which stops counting the vault
+    /// CHECK: address checked by seeds; contents checked by `GlobalStats::unregister`
+    #[account(mut, seeds = [GLOBAL_STATS_SEED], bump)]
+    pub global_stats: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
 }
 
 pub fn handler(ctx: Context<CloseVault>) -> Result<()> {
-    let vault = &ctx.accounts.vault;
+    let mut vault = ctx.accounts.vault.load_mut()?;
+
+    // Wind-down must have completed: no new deposits and all strategies repaid
+    require!(vault.is_winding_down(), VaultError::WindDownNotComplete);
+    require!(vault.total_debt == 0, VaultError::WindDownNotComplete);
+    require!(vault.external_nav == 0, VaultError::WindDownNotComplete);
 
     // Vault must be completely empty
     require!(vault.total_shares == 0, VaultError::VaultNotEmpty);
@@ -53,16 +176,38 @@ pub fn handler(ctx: Context<CloseVault>) -> Result<()> {
         ctx.accounts.vault_token_account.amount == 0,
         VaultError::VaultNotEmpty
     );
+    require!(ctx.accounts.share_mint.supply == 0, VaultError::VaultNotEmpty);
+    // Bonds are held under the vault's authority, so each must be withdrawn
+    // or slashed first
+    require!(vault.total_bonded == 0, VaultError::VaultNotEmpty);
+    // Airdrop escrows are vault-owned too and go back through the clawback
+    require!(vault.open_airdrops == 0, VaultError::VaultNotEmpty);
+    // The escrow exists once manager voting was configured
+    require!(
+        vault.vote_escrow_bump == 0 || ctx.accounts.vote_escrow.is_some(),
+        VaultError::VaultNotEmpty
+    );
+    require!(
+        vault.epoch_escrow_bump == 0 || ctx.accounts.epoch_escrow.is_some(),
+        VaultError::VaultNotEmpty
+    );
 
-    let mint_key = vault.mint;
-    let owner_key = vault.owner;
-    let vault_bump = vault.bump;
-    let signer_seeds: &[&[&[u8]]] = &[&[
-        VAULT_SEED,
-        mint_key.as_ref(),
-        owner_key.as_ref(),
-        &[vault_bump],
-    ]];
+    GlobalStats::unregister(
+        &ctx.accounts.global_stats,
+        &mut vault,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    vault.transition(
+        VaultStatus::Closed,
+        EventCpi {
+            authority: &ctx.accounts.event_authority,
+            bump: ctx.bumps.event_authority,
+        },
+    )?;
+
+    let seeds = vault.signer_seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
 
     // Close vault token account — return rent to owner
     token::close_account(CpiContext::new_with_signer(
@@ -75,18 +220,116 @@ pub fn handler(ctx: Context<CloseVault>) -> Result<()> {
         signer_seeds,
     ))?;
 
-    // Close share mint — return rent to owner
-    token::close_account(CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        CloseAccount {
-            account: ctx.accounts.share_mint.to_account_info(),
-            destination: ctx.accounts.owner.to_account_info(),
-            authority: ctx.accounts.vault.to_account_info(),
-        },
-        signer_seeds,
-    ))?;
+    // Close the vote escrow, if any (empty once every vote has been released)
+    if let Some(vote_escrow) = &ctx.accounts.vote_escrow {
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: vote_escrow.to_account_info(),
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+    }
+
+    // Close the epoch escrow, if any (empty once every request has been claimed)
+    if let Some(epoch_escrow) = &ctx.accounts.epoch_escrow {
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: epoch_escrow.to_account_info(),
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+    }
+
+    // Sweep rewards holders left unclaimed through the wind-down to the owner,
+    // then close the pool so a reopened vault can create its own
+    let rewards_token_account = ctx.accounts.rewards_token_account.to_account_info();
+    if rewards_token_account.owner == &token::ID && !rewards_token_account.data_is_empty() {
+        let left = TokenAccount::try_deserialize(&mut &rewards_token_account.data.borrow()[..])?
+            .amount;
+        if left > 0 {
+            let destination = ctx
+                .accounts
+                .owner_reward_account
+                .as_ref()
+                .ok_or(VaultError::VaultNotEmpty)?;
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: rewards_token_account.clone(),
+                        to: destination.to_account_info(),
+                        authority: ctx.accounts.vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                left,
+            )?;
+        }
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: rewards_token_account,
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+    }
+    close_if_created(&ctx.accounts.rewards, &ctx.accounts.owner)?;
+
+    // Close the crank bounty budget, which the owner must have defunded
+    let bounty_token_account = ctx.accounts.crank_bounty_token_account.to_account_info();
+    if bounty_token_account.owner == &token::ID && !bounty_token_account.data_is_empty() {
+        let left = TokenAccount::try_deserialize(&mut &bounty_token_account.data.borrow()[..])?
+            .amount;
+        require!(left == 0, VaultError::VaultNotEmpty);
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: bounty_token_account,
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+    }
+    close_if_created(&ctx.accounts.crank_bounty, &ctx.accounts.owner)?;
+
+    // Close the optional per-vault accounts, so a vault reopened on the same
+    // seeds starts without them
+    for account in [
+        &ctx.accounts.disclosure,
+        &ctx.accounts.position_history,
+        &ctx.accounts.receipt_tree,
+        &ctx.accounts.policy,
+        &ctx.accounts.reserves_verifier,
+    ] {
+        close_if_created(account, &ctx.accounts.owner)?;
+    }
+
+    // Revoke the share mint authority so no shares can ever be minted against
+    // this retired mint. A vault reopened on the same seeds derives a fresh
+    // share mint from its own `created_slot`.
+    token::set_authority(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SetAuthority {
+                current_authority: ctx.accounts.vault.to_account_info(),
+                account_or_mint: ctx.accounts.share_mint.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        AuthorityType::MintTokens,
+        None,
+    )?;
 
-    // Vault state account is closed via `close = owner` constraint
+    // Vault state and its bookkeeping accounts are closed via `close = owner` constraints
 
     Ok(())
 }
@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::PositionClosed;
+use crate::state::{Portfolio, UserPosition, VaultState};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    /// The position holder — receives the reclaimed rent
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    /// The user's position — closed and rent returned to the user
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump = position.bump,
+        close = user,
+    )]
+    pub position: Box<Account<'info, UserPosition>>,
+
+    /// The user's share token account — must be empty; closed if `close_share_account`
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = user,
+    )]
+    pub user_share_account: Box<Account<'info, TokenAccount>>,
+
+    /// The vaults the user holds positions in; this vault is dropped from it
+    /// CHECK: address checked by seeds; contents checked by `Portfolio::remove`
+    #[account(mut, seeds = [PORTFOLIO_SEED, user.key().as_ref()], bump)]
+    pub portfolio: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<ClosePosition>, close_share_account: bool) -> Result<()> {
+    require!(
+        ctx.accounts.user_share_account.amount == 0,
+        VaultError::PositionNotEmpty
+    );
+    // Unclaimed rewards would be lost with the position
+    require!(
+        ctx.accounts.position.pending_rewards == 0,
+        VaultError::PositionNotEmpty
+    );
+
+    Portfolio::remove(&ctx.accounts.portfolio, &ctx.accounts.vault.key())?;
+
+    if close_share_account {
+        token::close_account(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.user_share_account.to_account_info(),
+                destination: ctx.accounts.user.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ))?;
+    }
+
+    emit_cpi!(PositionClosed {
+        vault: ctx.accounts.vault.key(),
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        user: ctx.accounts.user.key(),
+        share_account_closed: close_share_account,
+    });
+
+    Ok(())
+}
@@ -1,12 +1,13 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer};
 
 use crate::constants::*;
 use crate::error::VaultError;
 use crate::events::FeesCollected;
-use crate::state::VaultState;
+use crate::state::{FeePayoutMode, FeeReport, VaultState};
 use crate::utils::fee_amount_to_shares;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CollectFees<'info> {
     /// The vault owner collecting fees
@@ -15,79 +16,205 @@ pub struct CollectFees<'info> {
     /// The vault state
     #[account(
         mut,
-        seeds = [VAULT_SEED, vault.mint.as_ref(), vault.owner.as_ref()],
-        bump = vault.bump,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
         has_one = owner @ VaultError::Unauthorized,
     )]
-    pub vault: Box<Account<'info, VaultState>>,
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's lifetime fee report
+    #[account(
+        mut,
+        seeds = [FEE_REPORT_SEED, vault.key().as_ref()],
+        bump = fee_report.bump,
+        has_one = vault,
+    )]
+    pub fee_report: Box<Account<'info, FeeReport>>,
 
     /// The share token mint (fee shares are minted)
     #[account(
         mut,
-        seeds = [SHARE_MINT_SEED, vault.key().as_ref()],
-        bump = vault.share_mint_bump,
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
         mint::authority = vault,
     )]
     pub share_mint: Account<'info, Mint>,
 
-    /// The treasury's share token account (receives fee shares)
+    /// The vault's token account (pays fees in `Assets` mode)
+    #[account(
+        mut,
+        seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
+        bump = vault.load()?.token_account_bump,
+        token::mint = vault.load()?.mint,
+        token::authority = vault,
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// The treasury's share token account (receives fee shares in `Shares` mode)
     #[account(
         mut,
         token::mint = share_mint,
     )]
-    pub treasury_share_account: Account<'info, TokenAccount>,
+    pub treasury_share_account: Option<Account<'info, TokenAccount>>,
+
+    /// The treasury's underlying token account (receives fees in `Assets` mode)
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+    )]
+    pub treasury_asset_account: Option<Account<'info, TokenAccount>>,
+
+    /// The donation recipient's share token account (receives its cut of the
+    /// fee shares in `Shares` mode)
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = vault.load()?.donation_recipient,
+    )]
+    pub donation_share_account: Option<Account<'info, TokenAccount>>,
+
+    /// The donation recipient's underlying token account (receives its cut of
+    /// the fees in `Assets` mode)
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+        token::authority = vault.load()?.donation_recipient,
+    )]
+    pub donation_asset_account: Option<Account<'info, TokenAccount>>,
 
     pub token_program: Program<'info, Token>,
 }
 
 pub fn handler(ctx: Context<CollectFees>) -> Result<()> {
-    let vault = &ctx.accounts.vault;
+    let vault_key = ctx.accounts.vault.key();
+    let vault = ctx.accounts.vault.load()?;
 
     require!(vault.accrued_management_fee > 0, VaultError::NoFeesToCollect);
 
     let fee_amount = vault.accrued_management_fee;
+    let donated = vault.donation_cut(fee_amount);
+    let seeds = vault.signer_seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
 
-    // Convert fee amount to shares (dilutive minting)
-    let fee_shares = fee_amount_to_shares(fee_amount, vault.total_assets, vault.total_shares)?;
-
-    if fee_shares > 0 {
-        // Mint fee shares to treasury (vault PDA signs)
-        let mint_key = vault.mint;
-        let owner_key = vault.owner;
-        let vault_bump = vault.bump;
-        let signer_seeds: &[&[&[u8]]] = &[&[
-            VAULT_SEED,
-            mint_key.as_ref(),
-            owner_key.as_ref(),
-            &[vault_bump],
-        ]];
-
-        token::mint_to(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                MintTo {
-                    mint: ctx.accounts.share_mint.to_account_info(),
-                    to: ctx.accounts.treasury_share_account.to_account_info(),
-                    authority: ctx.accounts.vault.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            fee_shares,
-        )?;
-    }
+    let fee_shares = match vault.fee_payout_mode() {
+        FeePayoutMode::Shares => {
+            let treasury = ctx
+                .accounts
+                .treasury_share_account
+                .as_ref()
+                .ok_or(VaultError::FeeTreasuryMissing)?;
+
+            // Convert fee amount to shares (dilutive minting)
+            let fee_shares =
+                fee_amount_to_shares(fee_amount, vault.total_assets, vault.total_shares)?;
+            let donation_shares = vault.donation_cut(fee_shares);
+            let mut payouts = vec![(treasury.to_account_info(), fee_shares - donation_shares)];
+            if vault.donation_bps > 0 {
+                let donation = ctx
+                    .accounts
+                    .donation_share_account
+                    .as_ref()
+                    .ok_or(VaultError::DonationAccountMissing)?;
+                payouts.push((donation.to_account_info(), donation_shares));
+            }
+
+            // Mint fee shares to the treasury and donation recipient (vault
+            // PDA signs)
+            for (to, shares) in payouts.into_iter().filter(|(_, shares)| *shares > 0) {
+                token::mint_to(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        MintTo {
+                            mint: ctx.accounts.share_mint.to_account_info(),
+                            to,
+                            authority: ctx.accounts.vault.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    shares,
+                )?;
+            }
+            fee_shares
+        }
+        FeePayoutMode::Assets => {
+            let treasury = ctx
+                .accounts
+                .treasury_asset_account
+                .as_ref()
+                .ok_or(VaultError::FeeTreasuryMissing)?;
 
-    // Update vault state
-    let vault = &mut ctx.accounts.vault;
+            // Fees are paid from idle liquidity; deployed funds are not recalled
+            let balance = vault.token_balance(ctx.accounts.vault_token_account.amount);
+            require!(
+                vault.liquid_assets(balance) >= fee_amount,
+                VaultError::InsufficientAssets
+            );
+
+            let mut payouts = vec![(treasury.to_account_info(), fee_amount - donated)];
+            if vault.donation_bps > 0 {
+                let donation = ctx
+                    .accounts
+                    .donation_asset_account
+                    .as_ref()
+                    .ok_or(VaultError::DonationAccountMissing)?;
+                payouts.push((donation.to_account_info(), donated));
+            }
+
+            // Paper vaults only simulate the payout
+            if !vault.is_paper() {
+                for (to, amount) in payouts.into_iter().filter(|(_, amount)| *amount > 0) {
+                    token::transfer(
+                        CpiContext::new_with_signer(
+                            ctx.accounts.token_program.to_account_info(),
+                            Transfer {
+                                from: ctx.accounts.vault_token_account.to_account_info(),
+                                to,
+                                authority: ctx.accounts.vault.to_account_info(),
+                            },
+                            signer_seeds,
+                        ),
+                        amount,
+                    )?;
+                }
+            }
+            0
+        }
+    };
+
+    // Update vault state: minted shares dilute holders, paid-out assets
+    // leave the vault
+    drop(vault);
+    let mut vault = ctx.accounts.vault.load_mut()?;
     vault.total_shares = vault
         .total_shares
         .checked_add(fee_shares)
         .ok_or(VaultError::ArithmeticOverflow)?;
+    if vault.fee_payout_mode() == FeePayoutMode::Assets {
+        vault.total_assets = vault
+            .total_assets
+            .checked_sub(fee_amount)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        vault.debit_paper_balance(fee_amount)?;
+    }
     vault.accrued_management_fee = 0;
 
-    emit!(FeesCollected {
-        vault: vault.key(),
+    ctx.accounts.fee_report.record_crystallization(
+        fee_amount,
+        fee_shares,
+        donated,
+        vault.high_water_mark(),
+        vault.benchmark_level,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    vault.assert_invariants(None)?;
+
+    emit_cpi!(FeesCollected {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
         fee_shares_minted: fee_shares,
         fee_amount,
+        donated,
     });
 
     Ok(())
@@ -0,0 +1,393 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{CrankBountyDefunded, CrankBountyFunded, CrankBountyPaid, CrankBountySet};
+use crate::instructions::audit_log::record_audit;
+use crate::state::{AuditAction, AuditLog, CrankBounty, CrankKind, VaultState};
+use crate::utils::EventCpi;
+
+// ──────────────────────────────────────────
+// Init Crank Bounty
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitCrankBounty<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+        has_one = mint,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's underlying mint, in which bounties are paid
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = CrankBounty::LEN,
+        seeds = [CRANK_BOUNTY_SEED, vault.key().as_ref()],
+        bump,
+    )]
+    pub crank_bounty: Box<Account<'info, CrankBounty>>,
+
+    /// Vault-owned token account bounties are paid from, kept apart from the
+    /// vault's assets
+    #[account(
+        init,
+        payer = owner,
+        seeds = [CRANK_BOUNTY_TOKEN_SEED, vault.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault,
+    )]
+    pub crank_bounty_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_init_crank_bounty(
+    ctx: Context<InitCrankBounty>,
+    bounty: u64,
+    min_interval: i64,
+) -> Result<()> {
+    require!(min_interval >= 0, VaultError::InvalidConfig);
+
+    let crank_bounty = &mut ctx.accounts.crank_bounty;
+    crank_bounty.vault = ctx.accounts.vault.key();
+    crank_bounty.bounty = bounty;
+    crank_bounty.min_interval = min_interval;
+    crank_bounty.last_paid = [0; 2];
+    crank_bounty.total_paid = 0;
+    crank_bounty.bump = ctx.bumps.crank_bounty;
+    crank_bounty.token_account_bump = ctx.bumps.crank_bounty_token_account;
+
+    emit_cpi!(CrankBountySet {
+        vault: crank_bounty.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        bounty,
+        min_interval,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Crank Bounty
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetCrankBounty<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        seeds = [CRANK_BOUNTY_SEED, vault.key().as_ref()],
+        bump = crank_bounty.bump,
+        has_one = vault,
+    )]
+    pub crank_bounty: Box<Account<'info, CrankBounty>>,
+}
+
+/// Pay `bounty` per crank, at most once per `min_interval` seconds for each
+/// crank kind. `bounty = 0` stops payments.
+pub fn handle_set_crank_bounty(
+    ctx: Context<SetCrankBounty>,
+    bounty: u64,
+    min_interval: i64,
+) -> Result<()> {
+    require!(min_interval >= 0, VaultError::InvalidConfig);
+
+    let crank_bounty = &mut ctx.accounts.crank_bounty;
+    crank_bounty.bounty = bounty;
+    crank_bounty.min_interval = min_interval;
+
+    emit_cpi!(CrankBountySet {
+        vault: crank_bounty.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        bounty,
+        min_interval,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetCrankBounty,
+        &[&bounty.to_le_bytes(), &min_interval.to_le_bytes()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Fund Crank Bounty
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FundCrankBounty<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [CRANK_BOUNTY_SEED, vault.key().as_ref()],
+        bump = crank_bounty.bump,
+        has_one = vault,
+    )]
+    pub crank_bounty: Box<Account<'info, CrankBounty>>,
+
+    #[account(
+        mut,
+        seeds = [CRANK_BOUNTY_TOKEN_SEED, vault.key().as_ref()],
+        bump = crank_bounty.token_account_bump,
+    )]
+    pub crank_bounty_token_account: Account<'info, TokenAccount>,
+
+    /// The owner's underlying token account funding the budget
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+        token::authority = owner,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Add `amount` underlying to the crank bounty budget
+pub fn handle_fund_crank_bounty(ctx: Context<FundCrankBounty>, amount: u64) -> Result<()> {
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.funder_token_account.to_account_info(),
+                to: ctx.accounts.crank_bounty_token_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    ctx.accounts.crank_bounty_token_account.reload()?;
+
+    emit_cpi!(CrankBountyFunded {
+        vault: ctx.accounts.vault.key(),
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        amount,
+        balance: ctx.accounts.crank_bounty_token_account.amount,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Defund Crank Bounty
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DefundCrankBounty<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        seeds = [CRANK_BOUNTY_SEED, vault.key().as_ref()],
+        bump = crank_bounty.bump,
+        has_one = vault,
+    )]
+    pub crank_bounty: Box<Account<'info, CrankBounty>>,
+
+    #[account(
+        mut,
+        seeds = [CRANK_BOUNTY_TOKEN_SEED, vault.key().as_ref()],
+        bump = crank_bounty.token_account_bump,
+    )]
+    pub crank_bounty_token_account: Account<'info, TokenAccount>,
+
+    /// The owner's underlying token account receiving the withdrawal
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Withdraw `amount` underlying from the crank bounty budget back to the
+/// owner. The budget must be empty before the vault can be closed.
+pub fn handle_defund_crank_bounty(ctx: Context<DefundCrankBounty>, amount: u64) -> Result<()> {
+    require!(amount > 0, VaultError::InvalidAmount);
+    require!(
+        ctx.accounts.crank_bounty_token_account.amount >= amount,
+        VaultError::InsufficientAssets
+    );
+
+    {
+        let vault = ctx.accounts.vault.load()?;
+        let seeds = vault.signer_seeds();
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.crank_bounty_token_account.to_account_info(),
+                    to: ctx.accounts.owner_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                &[&seeds],
+            ),
+            amount,
+        )?;
+    }
+    ctx.accounts.crank_bounty_token_account.reload()?;
+
+    emit_cpi!(CrankBountyDefunded {
+        vault: ctx.accounts.vault.key(),
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        amount,
+        balance: ctx.accounts.crank_bounty_token_account.amount,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::DefundCrankBounty,
+        &[&amount.to_le_bytes()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Crank payout
+// ──────────────────────────────────────────
+
+/// Optional bounty accounts of a permissionless crank. A crank that passes
+/// none of them is not paid; one that passes some must pass all three.
+pub(crate) struct CrankPayout<'a, 'info> {
+    /// Signer of the crank
+    pub crank: Pubkey,
+    pub vault: &'a AccountLoader<'info, VaultState>,
+    pub crank_bounty: Option<&'a mut Account<'info, CrankBounty>>,
+    pub crank_bounty_token_account: Option<&'a Account<'info, TokenAccount>>,
+    /// Receives the bounty, in the vault's underlying
+    pub crank_token_account: Option<&'a Account<'info, TokenAccount>>,
+    pub token_program: &'a Program<'info, Token>,
+    pub events: EventCpi<'a, 'info>,
+}
+
+impl CrankPayout<'_, '_> {
+    /// Pay the bounty for a `kind` crank if one is due. A budget that is
+    /// empty or not due yet never fails the crank itself.
+    pub fn pay(self, kind: CrankKind) -> Result<()> {
+        let (crank_bounty, bounty_token_account, crank_token_account) = match (
+            self.crank_bounty,
+            self.crank_bounty_token_account,
+            self.crank_token_account,
+        ) {
+            (None, None, None) => return Ok(()),
+            (Some(bounty), Some(from), Some(to)) => (bounty, from, to),
+            _ => return err!(VaultError::InvalidCrankBountyAccounts),
+        };
+
+        let vault_key = self.vault.key();
+        let expected = Pubkey::create_program_address(
+            &[
+                CRANK_BOUNTY_TOKEN_SEED,
+                vault_key.as_ref(),
+                &[crank_bounty.token_account_bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| VaultError::InvalidCrankBountyAccounts)?;
+        require_keys_eq!(
+            bounty_token_account.key(),
+            expected,
+            VaultError::InvalidCrankBountyAccounts
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let amount = crank_bounty.claim(kind, bounty_token_account.amount, now)?;
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let vault = self.vault.load()?;
+        let seeds = vault.signer_seeds();
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: bounty_token_account.to_account_info(),
+                    to: crank_token_account.to_account_info(),
+                    authority: self.vault.to_account_info(),
+                },
+                &[&seeds],
+            ),
+            amount,
+        )?;
+        drop(vault);
+
+        self.events.emit(CrankBountyPaid {
+            vault: vault_key,
+            event_seq: self.vault.load_mut()?.next_event_seq(),
+            crank: self.crank,
+            kind,
+            amount,
+        })?;
+
+        Ok(())
+    }
+}
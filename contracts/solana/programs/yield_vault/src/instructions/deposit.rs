@@ -1,12 +1,20 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as ix_sysvar;
 use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer};
 
 use crate::constants::*;
 use crate::error::VaultError;
 use crate::events::Deposited;
-use crate::state::VaultState;
-use crate::utils::calculate_shares_to_mint;
+use crate::instructions::market::market_quote;
+use crate::instructions::update_nav::check_no_nav_update;
+use crate::state::{
+    GlobalStats, Portfolio, RewardsPool, UserPosition, VaultState, WalletFlag,
+};
+use crate::utils::{
+    calculate_shares_to_mint, check_stack_height, shares_to_mint_dust_q64, EventCpi,
+};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Deposit<'info> {
     /// The user depositing tokens
@@ -16,17 +24,17 @@ pub struct Deposit<'info> {
     /// The vault state
     #[account(
         mut,
-        seeds = [VAULT_SEED, vault.mint.as_ref(), vault.owner.as_ref()],
-        bump = vault.bump,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
     )]
-    pub vault: Box<Account<'info, VaultState>>,
+    pub vault: AccountLoader<'info, VaultState>,
 
     /// The vault's token account (receives deposited tokens)
     #[account(
         mut,
         seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
-        bump = vault.token_account_bump,
-        token::mint = vault.mint,
+        bump = vault.load()?.token_account_bump,
+        token::mint = vault.load()?.mint,
         token::authority = vault,
     )]
     pub vault_token_account: Account<'info, TokenAccount>,
@@ -34,8 +42,8 @@ pub struct Deposit<'info> {
     /// The share token mint (vault mints shares to depositor)
     #[account(
         mut,
-        seeds = [SHARE_MINT_SEED, vault.key().as_ref()],
-        bump = vault.share_mint_bump,
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
         mint::authority = vault,
     )]
     pub share_mint: Account<'info, Mint>,
@@ -43,7 +51,7 @@ pub struct Deposit<'info> {
     /// The user's token account for the underlying asset
     #[account(
         mut,
-        token::mint = vault.mint,
+        token::mint = vault.load()?.mint,
         token::authority = user,
     )]
     pub user_token_account: Account<'info, TokenAccount>,
@@ -56,84 +64,316 @@ pub struct Deposit<'info> {
     )]
     pub user_share_account: Account<'info, TokenAccount>,
 
+    /// The user's position in this vault (created on first deposit)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::LEN,
+        seeds = [POSITION_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub position: Box<Account<'info, UserPosition>>,
+
+    /// The vaults the user holds positions in (created on first deposit)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = Portfolio::LEN,
+        seeds = [PORTFOLIO_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub portfolio: Box<Account<'info, Portfolio>>,
+
+    /// The vault's rewards pool, if any — pass it so the position's earning
+    /// share balance is checkpointed
+    #[account(
+        mut,
+        seeds = [REWARDS_SEED, vault.key().as_ref()],
+        bump = rewards.bump,
+        has_one = vault,
+    )]
+    pub rewards: Option<Box<Account<'info, RewardsPool>>>,
+
+    /// The vault's price oracle, required when one is configured
+    /// CHECK: address and owner are checked against the vault config
+    pub oracle: Option<UncheckedAccount<'info>>,
+
+    /// The vault's secondary-market pool reserves, required when a market
+    /// guard is configured
+    pub market_share_reserve: Option<Box<Account<'info, TokenAccount>>>,
+    pub market_asset_reserve: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// The depositor's Civic Pass, required when the vault is permissioned
+    /// CHECK: owner, wallet, network, state and expiry are checked against
+    /// the vault config
+    pub gateway_token: Option<UncheckedAccount<'info>>,
+
+    /// The depositor's compliance flag address; deposits fail if the protocol
+    /// admin has flagged the wallet
+    /// CHECK: address checked by seeds; contents checked by `WalletFlag::check`
+    #[account(seeds = [WALLET_FLAG_SEED, user.key().as_ref()], bump)]
+    pub wallet_flag: UncheckedAccount<'info>,
+
+    /// The protocol stats account, pushed the vault's new assets once the
+    /// admin has created it
+    /// CHECK: address checked by seeds; contents checked by `GlobalStats::sync`
+    #[account(mut, seeds = [GLOBAL_STATS_SEED], bump)]
+    pub global_stats: UncheckedAccount<'info>,
+
+    /// Instructions sysvar, used to reject flows bundled with a NAV update
+    /// CHECK: address is checked against the sysvar id
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-    let vault = &ctx.accounts.vault;
+/// Returns the shares minted
+pub fn handler(ctx: Context<Deposit>, amount: u64, memo: Option<[u8; 32]>) -> Result<u64> {
+    // Don't let users pay NAV for shares the market sells materially cheaper
+    {
+        let vault = ctx.accounts.vault.load()?;
+        if let Some(quote) = market_quote(
+            &vault,
+            ctx.accounts.market_share_reserve.as_deref(),
+            ctx.accounts.market_asset_reserve.as_deref(),
+        )? {
+            require!(
+                quote.discount_bps <= vault.max_market_discount_bps as u64,
+                VaultError::MarketBelowNav
+            );
+        }
+    }
+
+    if ctx.accounts.position.first_deposit_at == 0 {
+        ctx.accounts.portfolio.insert(
+            ctx.accounts.user.key(),
+            ctx.accounts.vault.key(),
+            ctx.bumps.portfolio,
+        );
+    }
+
+    let shares = ShareIssuance {
+        user: ctx.accounts.user.key(),
+        vault: &ctx.accounts.vault,
+        vault_token_account: &ctx.accounts.vault_token_account,
+        share_mint: &ctx.accounts.share_mint,
+        user_token_account: &ctx.accounts.user_token_account,
+        user_share_account: &ctx.accounts.user_share_account,
+        position: &mut ctx.accounts.position,
+        position_bump: ctx.bumps.position,
+        rewards: ctx.accounts.rewards.as_deref_mut(),
+        oracle: ctx.accounts.oracle.as_deref(),
+        gateway_token: ctx.accounts.gateway_token.as_deref(),
+        wallet_flag: &ctx.accounts.wallet_flag,
+        instructions: &ctx.accounts.instructions,
+        token_program: &ctx.accounts.token_program,
+        transfer_authority: ctx.accounts.user.to_account_info(),
+        events: EventCpi {
+            authority: &ctx.accounts.event_authority,
+            bump: ctx.bumps.event_authority,
+        },
+    }
+    .issue(amount, memo, &[])?;
 
-    // Validation
-    require!(!vault.paused, VaultError::VaultPaused);
-    require!(amount > 0, VaultError::InvalidAmount);
-    require!(amount >= vault.min_deposit, VaultError::BelowMinDeposit);
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    GlobalStats::sync(&ctx.accounts.global_stats, &mut vault, Clock::get()?.unix_timestamp)?;
+    Ok(shares)
+}
 
-    if vault.deposit_cap > 0 {
-        let new_total = vault
+/// Accounts for minting shares against a holder's underlying, shared by
+/// `deposit` and `execute_intent`
+pub(crate) struct ShareIssuance<'a, 'info> {
+    /// Owner of the deposited underlying, the shares and the position
+    pub user: Pubkey,
+    pub vault: &'a AccountLoader<'info, VaultState>,
+    pub vault_token_account: &'a Account<'info, TokenAccount>,
+    pub share_mint: &'a Account<'info, Mint>,
+    pub user_token_account: &'a Account<'info, TokenAccount>,
+    pub user_share_account: &'a Account<'info, TokenAccount>,
+    pub position: &'a mut Account<'info, UserPosition>,
+    pub position_bump: u8,
+    pub rewards: Option<&'a mut Account<'info, RewardsPool>>,
+    pub oracle: Option<&'a AccountInfo<'info>>,
+    /// The user's Civic Pass, for permissioned vaults
+    pub gateway_token: Option<&'a AccountInfo<'info>>,
+    /// The user's compliance flag address
+    pub wallet_flag: &'a AccountInfo<'info>,
+    /// Instructions sysvar
+    pub instructions: &'a AccountInfo<'info>,
+    pub token_program: &'a Program<'info, Token>,
+    /// Owner or approved delegate of `user_token_account`
+    pub transfer_authority: AccountInfo<'info>,
+    pub events: EventCpi<'a, 'info>,
+}
+
+impl<'a, 'info> ShareIssuance<'a, 'info> {
+    /// Deposit `amount` of underlying and mint the matching shares, signing
+    /// the transfer with `transfer_signer` when the authority is a PDA.
+    /// Returns the shares minted.
+    pub fn issue(
+        mut self,
+        amount: u64,
+        memo: Option<[u8; 32]>,
+        transfer_signer: &[&[&[u8]]],
+    ) -> Result<u64> {
+        let vault_key = self.vault.key();
+        let vault = self.vault.load()?;
+        let price_before = vault.share_price_snapshot();
+
+        // Validation
+        vault.status().check_deposits()?;
+        require!(!vault.is_locked(), VaultError::Reentrancy);
+        check_stack_height()?;
+        check_no_nav_update(self.instructions, &vault_key)?;
+        require!(!vault.is_epoch_gated(), VaultError::EpochGated);
+        require!(amount > 0, VaultError::InvalidAmount);
+        require!(amount >= vault.min_deposit, VaultError::BelowMinDeposit);
+        vault.check_oracle(self.oracle)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        vault.check_credential(&self.user, self.gateway_token, now)?;
+        WalletFlag::check(self.wallet_flag)?;
+        require!(vault.flows_open(now), VaultError::FlowWindowClosed);
+        if let Some(cap) = vault.deposit_cap_at(now) {
+            let new_total = vault
+                .total_assets
+                .checked_add(amount)
+                .ok_or(VaultError::ArithmeticOverflow)?;
+            require!(new_total <= cap, VaultError::DepositCapExceeded);
+        }
+
+        // Calculate shares to mint on the deposit net of the liquidity entry
+        // fee, which stays in the vault for the existing holders
+        let (entry_fee_bps, _) =
+            vault.liquidity_fees_bps(vault.token_balance(self.vault_token_account.amount));
+        let entry_fee = (amount as u128 * entry_fee_bps as u128 / BPS_DENOMINATOR) as u64;
+        let net_amount = amount - entry_fee;
+        let shares_to_mint = calculate_shares_to_mint(
+            net_amount,
+            vault.total_assets,
+            vault.total_shares,
+            vault.share_scale,
+        )?;
+        require!(shares_to_mint > 0, VaultError::InvalidAmount);
+        let dust = shares_to_mint_dust_q64(net_amount, vault.total_assets, vault.total_shares);
+
+        // Transfer underlying tokens from user to vault (owner or delegate
+        // signs). Paper vaults only simulate the deposit.
+        if !vault.is_paper() {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    Transfer {
+                        from: self.user_token_account.to_account_info(),
+                        to: self.vault_token_account.to_account_info(),
+                        authority: self.transfer_authority,
+                    },
+                    transfer_signer,
+                ),
+                amount,
+            )?;
+        }
+
+        // Mint share tokens to user (vault PDA signs as mint authority)
+        let seeds = vault.signer_seeds();
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+        token::mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                MintTo {
+                    mint: self.share_mint.to_account_info(),
+                    to: self.user_share_account.to_account_info(),
+                    authority: self.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            shares_to_mint,
+        )?;
+        drop(vault);
+
+        // Update vault state
+        let mut vault = self.vault.load_mut()?;
+        let clock = Clock::get()?;
+        vault.record_inflow(amount, clock.unix_timestamp)?;
+        if let Some(rewards) = self.rewards.as_deref_mut() {
+            rewards.accrue(vault.total_shares, clock.unix_timestamp)?;
+        }
+        vault.total_assets = vault
             .total_assets
             .checked_add(amount)
             .ok_or(VaultError::ArithmeticOverflow)?;
-        require!(new_total <= vault.deposit_cap, VaultError::DepositCapExceeded);
+        vault.total_shares = vault
+            .total_shares
+            .checked_add(shares_to_mint)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        vault.add_dust(dust)?;
+        vault.credit_paper_balance(amount)?;
+
+        // Record the deposit on the user's position (initialized on first deposit)
+        let position = self.position;
+        position.ensure_initialized(vault_key, self.user, self.position_bump);
+        if let Some(rewards) = self.rewards.as_deref() {
+            let balance = self.user_share_account.amount;
+            let new_balance = balance
+                .checked_add(shares_to_mint)
+                .ok_or(VaultError::ArithmeticOverflow)?;
+            position.checkpoint_rewards(rewards, balance, new_balance)?;
+        }
+        if position.first_deposit_at == 0 {
+            position.first_deposit_at = clock.unix_timestamp;
+        }
+        // A holder starting from no shares pays the current fee rates
+        if self.user_share_account.amount == 0 {
+            position.fee_epoch = vault.fee_epoch;
+        }
+        position.total_deposited = position
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        position.last_deposit_at = clock.unix_timestamp;
+        position.last_deposit_slot = clock.slot;
+        vault.last_deposit_slot = clock.slot;
+
+        vault.assert_invariants(Some(price_before))?;
+
+        self.events.emit(Deposited {
+            vault: vault_key,
+            event_seq: vault.next_event_seq(),
+            user: self.user,
+            amount,
+            shares_minted: shares_to_mint,
+            entry_fee,
+            memo,
+        })?;
+
+        Ok(shares_to_mint)
     }
+}
 
-    // Calculate shares to mint
-    let shares_to_mint =
-        calculate_shares_to_mint(amount, vault.total_assets, vault.total_shares)?;
-    require!(shares_to_mint > 0, VaultError::InvalidAmount);
-
-    // Transfer underlying tokens from user to vault
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.user_token_account.to_account_info(),
-                to: ctx.accounts.vault_token_account.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            },
-        ),
-        amount,
-    )?;
-
-    // Mint share tokens to user (vault PDA signs as mint authority)
-    let mint_key = ctx.accounts.vault.mint;
-    let owner_key = ctx.accounts.vault.owner;
-    let vault_bump = ctx.accounts.vault.bump;
-    let signer_seeds: &[&[&[u8]]] = &[&[
-        VAULT_SEED,
-        mint_key.as_ref(),
-        owner_key.as_ref(),
-        &[vault_bump],
-    ]];
-
-    token::mint_to(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            MintTo {
-                mint: ctx.accounts.share_mint.to_account_info(),
-                to: ctx.accounts.user_share_account.to_account_info(),
-                authority: ctx.accounts.vault.to_account_info(),
-            },
-            signer_seeds,
-        ),
-        shares_to_mint,
-    )?;
-
-    // Update vault state
-    let vault = &mut ctx.accounts.vault;
-    vault.total_assets = vault
-        .total_assets
-        .checked_add(amount)
-        .ok_or(VaultError::ArithmeticOverflow)?;
-    vault.total_shares = vault
-        .total_shares
-        .checked_add(shares_to_mint)
-        .ok_or(VaultError::ArithmeticOverflow)?;
-
-    emit!(Deposited {
-        vault: vault.key(),
-        user: ctx.accounts.user.key(),
-        amount,
-        shares_minted: shares_to_mint,
-    });
+#[derive(Accounts)]
+pub struct DepositWithNonce<'info> {
+    pub deposit: Deposit<'info>,
+}
 
-    Ok(())
+/// Deposit as `deposit` does, once per `nonce`. Nonces must increase with
+/// each deposit to the position, so a client retrying a transaction whose
+/// outcome it never saw cannot deposit twice. Returns the shares minted.
+pub fn handle_deposit_with_nonce(
+    ctx: Context<DepositWithNonce>,
+    amount: u64,
+    memo: Option<[u8; 32]>,
+    nonce: u64,
+) -> Result<u64> {
+    let position = &mut ctx.accounts.deposit.position;
+    require!(nonce > position.deposit_nonce, VaultError::DuplicateDepositNonce);
+    position.deposit_nonce = nonce;
+
+    handler(
+        Context::new(ctx.program_id, &mut ctx.accounts.deposit, &[], ctx.bumps.deposit),
+        amount,
+        memo,
+    )
 }
@@ -0,0 +1,196 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{DisclosureProposed, DisclosureUpdated};
+use crate::instructions::audit_log::record_audit;
+use crate::state::{AuditAction, AuditLog, Disclosure, VaultState};
+
+// ──────────────────────────────────────────
+// Init Disclosure
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitDisclosure<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Disclosure::LEN,
+        seeds = [DISCLOSURE_SEED, vault.key().as_ref()],
+        bump,
+    )]
+    pub disclosure: Box<Account<'info, Disclosure>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Publish the vault's first strategy disclosure document, in force at once.
+/// Later changes go through `propose_disclosure`.
+pub fn handle_init_disclosure(
+    ctx: Context<InitDisclosure>,
+    content_hash: [u8; 32],
+    uri: String,
+) -> Result<()> {
+    require!(uri.len() <= MAX_DISCLOSURE_URI_LEN, VaultError::InvalidConfig);
+
+    let disclosure = &mut ctx.accounts.disclosure;
+    disclosure.vault = ctx.accounts.vault.key();
+    disclosure.content_hash = content_hash;
+    disclosure.uri = uri.clone();
+    disclosure.effective_at = Clock::get()?.unix_timestamp;
+    disclosure.bump = ctx.bumps.disclosure;
+
+    emit_cpi!(DisclosureUpdated {
+        vault: disclosure.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        content_hash,
+        uri,
+    });
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::InitDisclosure,
+        &[&content_hash],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Propose Disclosure
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeDisclosure<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        seeds = [DISCLOSURE_SEED, vault.key().as_ref()],
+        bump = disclosure.bump,
+        has_one = vault,
+    )]
+    pub disclosure: Box<Account<'info, Disclosure>>,
+}
+
+/// Propose replacing the strategy disclosure document. It can be applied once
+/// `DISCLOSURE_TIMELOCK` has passed; a new proposal replaces the pending one
+/// and restarts the wait. Owner only.
+pub fn handle_propose_disclosure(
+    ctx: Context<ProposeDisclosure>,
+    content_hash: [u8; 32],
+    uri: String,
+) -> Result<()> {
+    require!(uri.len() <= MAX_DISCLOSURE_URI_LEN, VaultError::InvalidConfig);
+
+    let effective_at = Clock::get()?
+        .unix_timestamp
+        .checked_add(DISCLOSURE_TIMELOCK)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    let disclosure = &mut ctx.accounts.disclosure;
+    disclosure.pending_content_hash = content_hash;
+    disclosure.pending_uri = uri.clone();
+    disclosure.pending_effective_at = effective_at;
+
+    emit_cpi!(DisclosureProposed {
+        vault: disclosure.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        content_hash,
+        uri,
+        effective_at,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::ProposeDisclosure,
+        &[&content_hash, &effective_at.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Apply Disclosure
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ApplyDisclosure<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [DISCLOSURE_SEED, vault.key().as_ref()],
+        bump = disclosure.bump,
+        has_one = vault,
+    )]
+    pub disclosure: Box<Account<'info, Disclosure>>,
+}
+
+/// Put the proposed disclosure document in force once its timelock has passed
+pub fn handle_apply_disclosure(ctx: Context<ApplyDisclosure>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let disclosure = &mut ctx.accounts.disclosure;
+    require!(disclosure.has_pending(), VaultError::NoDisclosurePending);
+    require!(
+        now >= disclosure.pending_effective_at,
+        VaultError::DisclosureTimelockActive
+    );
+
+    disclosure.content_hash = disclosure.pending_content_hash;
+    disclosure.uri = std::mem::take(&mut disclosure.pending_uri);
+    disclosure.effective_at = now;
+    disclosure.pending_content_hash = [0; 32];
+    disclosure.pending_effective_at = 0;
+
+    emit_cpi!(DisclosureUpdated {
+        vault: disclosure.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        content_hash: disclosure.content_hash,
+        uri: disclosure.uri.clone(),
+    });
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::Donated;
+use crate::state::VaultState;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Donate<'info> {
+    /// The account topping up the vault
+    pub donor: Signer<'info>,
+
+    /// The vault state
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's token account (receives donated tokens)
+    #[account(
+        mut,
+        seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
+        bump = vault.load()?.token_account_bump,
+        token::mint = vault.load()?.mint,
+        token::authority = vault,
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// The donor's token account for the underlying asset
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+        token::authority = donor,
+    )]
+    pub donor_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Add underlying to the vault without minting shares, raising the share price
+/// for all current holders (reward airdrops, loss reimbursements).
+pub fn handler(ctx: Context<Donate>, amount: u64) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let vault = ctx.accounts.vault.load()?;
+
+    require!(amount > 0, VaultError::InvalidAmount);
+    require!(!vault.is_paper(), VaultError::PaperVault);
+    // With no holders the next depositor would capture the donation
+    require!(vault.total_shares > 0, VaultError::NoSharesOutstanding);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.donor_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.donor.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    drop(vault);
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.total_assets = vault
+        .total_assets
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    emit_cpi!(Donated {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
+        donor: ctx.accounts.donor.key(),
+        amount,
+        total_assets: vault.total_assets,
+    });
+
+    Ok(())
+}
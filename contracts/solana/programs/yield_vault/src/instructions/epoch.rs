@@ -0,0 +1,849 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{
+    EpochClaimed, EpochDepositRequested, EpochRedeemRequested, EpochRequestTransferred,
+    EpochSettled, RedemptionGateSet,
+};
+use crate::instructions::audit_log::record_audit;
+use crate::instructions::crank::CrankPayout;
+use crate::state::{
+    AuditAction, AuditLog, CrankBounty, CrankKind, EpochRequest, EpochSettlement,
+    RedemptionQueue, VaultState, WalletFlag,
+};
+use crate::utils::{
+    assets_to_return_dust_q64, calculate_assets_to_return, calculate_shares_to_mint,
+    shares_to_mint_dust_q64, EventCpi,
+};
+
+// ──────────────────────────────────────────
+// Set Epoch Duration
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct SetEpochDuration<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    /// Escrow for queued redemptions and unclaimed epoch shares (PDA, created on first use)
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [EPOCH_ESCROW_SEED, vault.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = vault,
+    )]
+    pub epoch_escrow: Box<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Enable (non-zero), resize or disable (zero) epoch gating. Disabling is only
+/// possible once the current epoch has no queued requests.
+pub fn handle_set_epoch_duration(ctx: Context<SetEpochDuration>, duration: i64) -> Result<()> {
+    require!(duration >= 0, VaultError::InvalidConfig);
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    if duration == 0 {
+        require!(
+            vault.epoch_pending_deposits == 0 && vault.epoch_pending_redeems == 0,
+            VaultError::EpochRequestsPending
+        );
+    } else {
+        // Queued flows settle real tokens through escrow
+        require!(!vault.is_paper(), VaultError::PaperVault);
+        if !vault.is_epoch_gated() {
+            vault.epoch_started_at = Clock::get()?.unix_timestamp;
+        }
+    }
+    vault.epoch_duration = duration;
+    vault.epoch_escrow_bump = ctx.bumps.epoch_escrow;
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetEpochDuration,
+        &[&duration.to_le_bytes()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Redemption Gate
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetRedemptionGate<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+/// Cap the NAV one epoch's queued redemptions may take at `gate_bps`. Past
+/// it, settlement fills every request by the same fraction and returns the
+/// rest of the shares, so a run is shared out rather than won by whoever
+/// queued first. Zero removes the gate.
+pub fn handle_set_redemption_gate(ctx: Context<SetRedemptionGate>, gate_bps: u16) -> Result<()> {
+    require!(
+        u128::from(gate_bps) <= BPS_DENOMINATOR,
+        VaultError::InvalidConfig
+    );
+
+    ctx.accounts.vault.load_mut()?.redemption_gate_bps = gate_bps;
+
+    emit_cpi!(RedemptionGateSet {
+        vault: ctx.accounts.vault.key(),
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        redemption_gate_bps: gate_bps,
+    });
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetRedemptionGate,
+        &[&gate_bps.to_le_bytes()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Request Deposit
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RequestDeposit<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's token account (holds queued deposits until settlement)
+    #[account(
+        mut,
+        seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
+        bump = vault.load()?.token_account_bump,
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+        token::authority = user,
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = EpochRequest::LEN,
+        seeds = [EPOCH_REQUEST_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub request: Box<Account<'info, EpochRequest>>,
+
+    /// The depositor's Civic Pass, required when the vault is permissioned
+    /// CHECK: owner, wallet, network, state and expiry are checked against
+    /// the vault config
+    pub gateway_token: Option<UncheckedAccount<'info>>,
+
+    /// The depositor's compliance flag address; deposits fail if the protocol
+    /// admin has flagged the wallet
+    /// CHECK: address checked by seeds; contents checked by `WalletFlag::check`
+    #[account(seeds = [WALLET_FLAG_SEED, user.key().as_ref()], bump)]
+    pub wallet_flag: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Queue `amount` for deposit at the current epoch's settlement price
+pub fn handle_request_deposit(ctx: Context<RequestDeposit>, amount: u64) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let mut vault = ctx.accounts.vault.load_mut()?;
+
+    vault.status().check_deposits()?;
+    require!(vault.is_epoch_gated(), VaultError::EpochsDisabled);
+    require!(amount >= vault.min_deposit, VaultError::BelowMinDeposit);
+    let now = Clock::get()?.unix_timestamp;
+    vault.check_credential(
+        &ctx.accounts.user.key(),
+        ctx.accounts.gateway_token.as_deref(),
+        now,
+    )?;
+    WalletFlag::check(&ctx.accounts.wallet_flag)?;
+
+    if let Some(cap) = vault.deposit_cap_at(now) {
+        let new_total = vault
+            .total_assets
+            .checked_add(vault.epoch_pending_deposits)
+            .and_then(|total| total.checked_add(amount))
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        require!(new_total <= cap, VaultError::DepositCapExceeded);
+    }
+
+    let epoch = vault.current_epoch;
+    let request = &mut ctx.accounts.request;
+    open_request(request, vault_key, ctx.accounts.user.key(), epoch, ctx.bumps.request)?;
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    request.deposit_assets = request
+        .deposit_assets
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    vault.epoch_pending_deposits = vault
+        .epoch_pending_deposits
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    vault.epoch_reserved_assets = vault
+        .epoch_reserved_assets
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    emit_cpi!(EpochDepositRequested {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
+        user: ctx.accounts.user.key(),
+        epoch,
+        amount,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Request Redeem
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RequestRedeem<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [EPOCH_ESCROW_SEED, vault.key().as_ref()],
+        bump = vault.load()?.epoch_escrow_bump,
+    )]
+    pub epoch_escrow: Account<'info, TokenAccount>,
+
+    /// The user's share token account (queued shares move to escrow)
+    #[account(
+        mut,
+        token::mint = epoch_escrow.mint,
+        token::authority = user,
+    )]
+    pub user_share_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = EpochRequest::LEN,
+        seeds = [EPOCH_REQUEST_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub request: Box<Account<'info, EpochRequest>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Queue `shares` for redemption at the current epoch's settlement price
+pub fn handle_request_redeem(ctx: Context<RequestRedeem>, shares: u64) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let mut vault = ctx.accounts.vault.load_mut()?;
+
+    require!(vault.is_epoch_gated(), VaultError::EpochsDisabled);
+    vault.status().check_withdrawals()?;
+    require!(shares > 0, VaultError::InvalidAmount);
+
+    let epoch = vault.current_epoch;
+    let request = &mut ctx.accounts.request;
+    open_request(request, vault_key, ctx.accounts.user.key(), epoch, ctx.bumps.request)?;
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_share_account.to_account_info(),
+                to: ctx.accounts.epoch_escrow.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        shares,
+    )?;
+
+    request.redeem_shares = request
+        .redeem_shares
+        .checked_add(shares)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    vault.epoch_pending_redeems = vault
+        .epoch_pending_redeems
+        .checked_add(shares)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    emit_cpi!(EpochRedeemRequested {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
+        user: ctx.accounts.user.key(),
+        epoch,
+        shares,
+    });
+
+    Ok(())
+}
+
+/// Bind a (possibly fresh) request to the current epoch. A request from an
+/// earlier epoch must be claimed before the user queues again.
+fn open_request(
+    request: &mut EpochRequest,
+    vault: Pubkey,
+    user: Pubkey,
+    epoch: u64,
+    bump: u8,
+) -> Result<()> {
+    if request.is_empty() {
+        request.vault = vault;
+        request.user = user;
+        request.epoch = epoch;
+        request.bump = bump;
+    }
+    require!(request.epoch == epoch, VaultError::EpochRequestUnclaimed);
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Settle Epoch
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleEpoch<'info> {
+    /// Anyone may settle an epoch once it has ended; pays the record's rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
+        bump = vault.load()?.token_account_bump,
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+        mint::authority = vault,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [EPOCH_ESCROW_SEED, vault.key().as_ref()],
+        bump = vault.load()?.epoch_escrow_bump,
+    )]
+    pub epoch_escrow: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = EpochSettlement::LEN,
+        seeds = [
+            EPOCH_SETTLEMENT_SEED,
+            vault.key().as_ref(),
+            &vault.load()?.current_epoch.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub settlement: Box<Account<'info, EpochSettlement>>,
+
+    /// The vault's crank bounty config, to be paid for this crank
+    #[account(
+        mut,
+        seeds = [CRANK_BOUNTY_SEED, vault.key().as_ref()],
+        bump = crank_bounty.bump,
+        has_one = vault,
+    )]
+    pub crank_bounty: Option<Box<Account<'info, CrankBounty>>>,
+
+    /// The bounty budget; its address is checked against `crank_bounty`
+    #[account(mut)]
+    pub crank_bounty_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Receives the crank bounty
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+    )]
+    pub crank_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Process the ended epoch's queued flows at the current NAV, which the
+/// manager is expected to have crystallized with `update_nav`, and open the
+/// next epoch. Minted shares stay in escrow and redemption proceeds stay
+/// reserved in the vault token account until claimed. Pays the crank bounty
+/// when its accounts are given.
+pub fn handle_settle_epoch(ctx: Context<SettleEpoch>) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let vault = ctx.accounts.vault.load()?;
+    let price_before = vault.share_price_snapshot();
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(vault.is_epoch_gated(), VaultError::EpochsDisabled);
+    let epoch_end = vault
+        .epoch_started_at
+        .checked_add(vault.epoch_duration)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    require!(now >= epoch_end, VaultError::EpochNotEnded);
+
+    let (total_assets, total_shares) = (vault.total_assets, vault.total_shares);
+    let deposits = vault.epoch_pending_deposits;
+    let redeems = vault.epoch_pending_redeems;
+
+    let shares_minted = if deposits > 0 {
+        calculate_shares_to_mint(deposits, total_assets, total_shares, vault.share_scale)?
+    } else {
+        0
+    };
+    // Past the redemption gate or the outflow limit only part of each
+    // request is filled; the unfilled shares stay in escrow until claimed back
+    let redeems_filled = vault.gated_redeems(redeems, now)?;
+    let assets_paid = if redeems_filled > 0 {
+        calculate_assets_to_return(redeems_filled, total_assets, total_shares)?
+    } else {
+        0
+    };
+    let deposit_dust = shares_to_mint_dust_q64(deposits, total_assets, total_shares);
+    let redeem_dust = assets_to_return_dust_q64(redeems_filled, total_assets, total_shares);
+
+    // Queued deposits join NAV; redemption proceeds leave it but stay
+    // reserved in the token account, which must be able to cover them
+    let reserved = vault
+        .epoch_reserved_assets
+        .checked_sub(deposits)
+        .and_then(|r| r.checked_add(assets_paid))
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    require!(
+        ctx.accounts.vault_token_account.amount >= reserved,
+        VaultError::InsufficientAssets
+    );
+
+    let seeds = vault.signer_seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+    if shares_minted > 0 {
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.epoch_escrow.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            shares_minted,
+        )?;
+    }
+    if redeems_filled > 0 {
+        token::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    from: ctx.accounts.epoch_escrow.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            redeems_filled,
+        )?;
+    }
+
+    drop(vault);
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    let epoch = vault.current_epoch;
+    vault.record_outflow(assets_paid, now)?;
+    vault.total_assets = total_assets
+        .checked_add(deposits)
+        .and_then(|a| a.checked_sub(assets_paid))
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    vault.total_shares = total_shares
+        .checked_add(shares_minted)
+        .and_then(|s| s.checked_sub(redeems_filled))
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    vault.add_dust(deposit_dust)?;
+    vault.add_dust(redeem_dust)?;
+    vault.epoch_reserved_assets = reserved;
+    vault.epoch_pending_deposits = 0;
+    vault.epoch_pending_redeems = 0;
+    vault.current_epoch = epoch
+        .checked_add(1)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    vault.epoch_started_at = now;
+
+    let settlement = &mut ctx.accounts.settlement;
+    settlement.vault = vault_key;
+    settlement.epoch = epoch;
+    settlement.total_assets = total_assets;
+    settlement.total_shares = total_shares;
+    settlement.deposits = deposits;
+    settlement.shares_minted = shares_minted;
+    settlement.redeems = redeems;
+    settlement.redeems_filled = redeems_filled;
+    settlement.assets_paid = assets_paid;
+    settlement.settled_at = now;
+    settlement.bump = ctx.bumps.settlement;
+
+    vault.assert_invariants(Some(price_before))?;
+
+    emit_cpi!(EpochSettled {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
+        epoch,
+        total_assets,
+        total_shares,
+        deposits,
+        shares_minted,
+        redeems,
+        redeems_filled,
+        assets_paid,
+    });
+    drop(vault);
+
+    CrankPayout {
+        crank: ctx.accounts.payer.key(),
+        vault: &ctx.accounts.vault,
+        crank_bounty: ctx.accounts.crank_bounty.as_deref_mut(),
+        crank_bounty_token_account: ctx.accounts.crank_bounty_token_account.as_ref(),
+        crank_token_account: ctx.accounts.crank_token_account.as_ref(),
+        token_program: &ctx.accounts.token_program,
+        events: EventCpi {
+            authority: &ctx.accounts.event_authority,
+            bump: ctx.bumps.event_authority,
+        },
+    }
+    .pay(CrankKind::SettleEpoch)
+}
+
+// ──────────────────────────────────────────
+// Claim Epoch
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimEpoch<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
+        bump = vault.load()?.token_account_bump,
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [EPOCH_ESCROW_SEED, vault.key().as_ref()],
+        bump = vault.load()?.epoch_escrow_bump,
+    )]
+    pub epoch_escrow: Account<'info, TokenAccount>,
+
+    /// Receives redemption proceeds
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+        token::authority = user,
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    /// Receives shares minted for queued deposits
+    #[account(
+        mut,
+        token::mint = epoch_escrow.mint,
+        token::authority = user,
+    )]
+    pub user_share_account: Account<'info, TokenAccount>,
+
+    /// The user's request — closed once claimed
+    #[account(
+        mut,
+        seeds = [EPOCH_REQUEST_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump = request.bump,
+        close = user,
+    )]
+    pub request: Box<Account<'info, EpochRequest>>,
+
+    #[account(
+        seeds = [EPOCH_SETTLEMENT_SEED, vault.key().as_ref(), &request.epoch.to_le_bytes()],
+        bump = settlement.bump,
+    )]
+    pub settlement: Box<Account<'info, EpochSettlement>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Collect the shares and assets owed to a request from a settled epoch
+pub fn handle_claim_epoch(ctx: Context<ClaimEpoch>) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let vault = ctx.accounts.vault.load()?;
+    let request = &ctx.accounts.request;
+
+    let (shares, assets) = ctx.accounts.settlement.entitlement(request)?;
+
+    let seeds = vault.signer_seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+    if shares > 0 {
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.epoch_escrow.to_account_info(),
+                    to: ctx.accounts.user_share_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            shares,
+        )?;
+    }
+    if assets > 0 {
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            assets,
+        )?;
+    }
+
+    drop(vault);
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.epoch_reserved_assets = vault
+        .epoch_reserved_assets
+        .checked_sub(assets)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    emit_cpi!(EpochClaimed {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
+        user: ctx.accounts.user.key(),
+        epoch: request.epoch,
+        shares,
+        assets,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Transfer Epoch Request
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(new_owner: Pubkey)]
+pub struct TransferEpochRequest<'info> {
+    /// The request's owner; pays for the new owner's request if it is
+    /// created and gets this one's rent back
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The request being transferred — closed once reassigned
+    #[account(
+        mut,
+        seeds = [EPOCH_REQUEST_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump = request.bump,
+        close = user,
+        constraint = new_owner != user.key() @ VaultError::EpochRequestSameOwner,
+    )]
+    pub request: Box<Account<'info, EpochRequest>>,
+
+    /// The new owner's request, which takes over the transferred amounts
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = EpochRequest::LEN,
+        seeds = [EPOCH_REQUEST_SEED, vault.key().as_ref(), new_owner.as_ref()],
+        bump,
+    )]
+    pub new_request: Box<Account<'info, EpochRequest>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Assign a queued request to `new_owner`, who claims it in the user's place,
+/// so pending redemptions can be transferred or sold before settlement. The
+/// whole request moves, settled or not, merging into any request the new
+/// owner has in the same epoch.
+pub fn handle_transfer_epoch_request(
+    ctx: Context<TransferEpochRequest>,
+    new_owner: Pubkey,
+) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let request = &ctx.accounts.request;
+    let new_request = &mut ctx.accounts.new_request;
+    open_request(new_request, vault_key, new_owner, request.epoch, ctx.bumps.new_request)?;
+
+    new_request.deposit_assets = new_request
+        .deposit_assets
+        .checked_add(request.deposit_assets)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    new_request.redeem_shares = new_request
+        .redeem_shares
+        .checked_add(request.redeem_shares)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    emit_cpi!(EpochRequestTransferred {
+        vault: vault_key,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        from: ctx.accounts.user.key(),
+        to: new_owner,
+        epoch: request.epoch,
+        deposit_assets: request.deposit_assets,
+        redeem_shares: request.redeem_shares,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Get Redemption Queue
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct GetRedemptionQueue<'info> {
+    #[account(
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+}
+
+/// Read-only: returns the current epoch's queued redemptions and the share of
+/// each the redemption gate and outflow limit would fill at the current NAV.
+pub fn handle_get_redemption_queue(ctx: Context<GetRedemptionQueue>) -> Result<RedemptionQueue> {
+    let vault = ctx.accounts.vault.load()?;
+    let now = Clock::get()?.unix_timestamp;
+    let redeems = vault.epoch_pending_redeems;
+    let filled = vault.gated_redeems(redeems, now)?;
+    let fill_bps = if redeems == 0 {
+        BPS_DENOMINATOR
+    } else {
+        filled as u128 * BPS_DENOMINATOR / redeems as u128
+    };
+
+    Ok(RedemptionQueue {
+        epoch: vault.current_epoch,
+        epoch_ends_at: vault.epoch_started_at.saturating_add(vault.epoch_duration),
+        pending_redeems: redeems,
+        pending_redeem_assets: calculate_assets_to_return(
+            redeems,
+            vault.total_assets,
+            vault.total_shares,
+        )?,
+        redemption_gate_bps: vault.redemption_gate_bps,
+        gate_limit: vault.redemption_limit(now).unwrap_or(u64::MAX),
+        fill_bps: fill_bps as u16,
+    })
+}
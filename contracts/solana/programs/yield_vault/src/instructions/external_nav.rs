@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{ExternalNavReported, NavRelayerSet};
+use crate::instructions::audit_log::record_audit;
+use crate::state::{AuditAction, AuditLog, FeeReport, PriceHistory, PriceSample, VaultState};
+use crate::utils::EventCpi;
+
+// ──────────────────────────────────────────
+// Set NAV Relayer
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetNavRelayer<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+pub fn handle_set_nav_relayer(ctx: Context<SetNavRelayer>, relayer: Pubkey) -> Result<()> {
+    ctx.accounts.vault.load_mut()?.nav_relayer = relayer;
+
+    emit_cpi!(NavRelayerSet {
+        vault: ctx.accounts.vault.key(),
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        relayer,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetNavRelayer,
+        &[relayer.as_ref()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Report External NAV
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReportExternalNav<'info> {
+    /// The registered relayer attesting the sibling vaults' NAV
+    #[account(address = vault.load()?.nav_relayer @ VaultError::Unauthorized)]
+    pub relayer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's lifetime fee report
+    #[account(
+        mut,
+        seeds = [FEE_REPORT_SEED, vault.key().as_ref()],
+        bump = fee_report.bump,
+        has_one = vault,
+    )]
+    pub fee_report: Box<Account<'info, FeeReport>>,
+
+    /// The vault's share-price history
+    #[account(
+        mut,
+        seeds = [PRICE_HISTORY_SEED, vault.key().as_ref()],
+        bump = price_history.load()?.bump,
+        has_one = vault,
+    )]
+    pub price_history: AccountLoader<'info, PriceHistory>,
+}
+
+/// Replace the vault's external NAV component with `external_nav`, as
+/// observed on the source chain at `observed_at`. The difference from the
+/// previous attestation flows into `total_assets` like a strategy report.
+pub fn handle_report_external_nav(
+    ctx: Context<ReportExternalNav>,
+    external_nav: u64,
+    observed_at: i64,
+) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let mut vault = ctx.accounts.vault.load_mut()?;
+
+    // Attestations can reach Solana out of order; only apply newer ones
+    require!(
+        observed_at > vault.external_nav_observed_at,
+        VaultError::StaleNavAttestation
+    );
+
+    let old_external_nav = vault.external_nav;
+    let new_total_assets = vault
+        .total_assets
+        .checked_sub(old_external_nav)
+        .ok_or(VaultError::ArithmeticOverflow)?
+        .checked_add(external_nav)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    let clock = Clock::get()?;
+    let events = EventCpi {
+        authority: &ctx.accounts.event_authority,
+        bump: ctx.bumps.event_authority,
+    };
+    let (mgmt_fee, perf_fee) = vault.accrue_fees(new_total_assets, clock.unix_timestamp, events)?;
+    ctx.accounts.fee_report.record_accrual(mgmt_fee, perf_fee)?;
+
+    vault.external_nav = external_nav;
+    vault.external_nav_observed_at = observed_at;
+
+    ctx.accounts.price_history.load_mut()?.push(PriceSample {
+        timestamp: clock.unix_timestamp,
+        total_assets: vault.total_assets,
+        total_shares: vault.total_shares,
+    });
+
+    vault.assert_invariants(None)?;
+
+    emit_cpi!(ExternalNavReported {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
+        relayer: ctx.accounts.relayer.key(),
+        old_external_nav,
+        new_external_nav: external_nav,
+        observed_at,
+        total_assets: vault.total_assets,
+    });
+
+    Ok(())
+}
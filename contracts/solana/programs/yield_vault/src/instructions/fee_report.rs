@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::state::{FeePayoutMode, FeeReport, ShareValue, VaultState};
+use crate::utils::{calculate_assets_to_return, fee_amount_to_shares};
+
+#[derive(Accounts)]
+pub struct GetFeeReport<'info> {
+    #[account(
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [FEE_REPORT_SEED, vault.key().as_ref()],
+        bump = fee_report.bump,
+        has_one = vault,
+    )]
+    pub fee_report: Box<Account<'info, FeeReport>>,
+}
+
+/// Read-only: returns the vault's lifetime fee report as instruction return data.
+pub fn handler(ctx: Context<GetFeeReport>) -> Result<FeeReport> {
+    Ok((**ctx.accounts.fee_report).clone())
+}
+
+#[derive(Accounts)]
+pub struct GetShareValue<'info> {
+    #[account(
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    /// The holder's share token account
+    #[account(token::mint = share_mint)]
+    pub user_share_account: Account<'info, TokenAccount>,
+}
+
+/// Read-only: returns what the holder's shares are worth now and once the
+/// fees accrued so far are collected. Fees accruing since the last NAV
+/// update are not included.
+pub fn handle_get_share_value(ctx: Context<GetShareValue>) -> Result<ShareValue> {
+    let vault = ctx.accounts.vault.load()?;
+    let shares = ctx.accounts.user_share_account.amount;
+    let accrued_fees = vault.accrued_management_fee;
+
+    let gross_assets = calculate_assets_to_return(shares, vault.total_assets, vault.total_shares)?;
+    let net_assets = match vault.fee_payout_mode() {
+        // Fee shares minted to the treasury dilute every holder
+        FeePayoutMode::Shares => {
+            let fee_shares =
+                fee_amount_to_shares(accrued_fees, vault.total_assets, vault.total_shares)?;
+            calculate_assets_to_return(
+                shares,
+                vault.total_assets,
+                vault
+                    .total_shares
+                    .checked_add(fee_shares)
+                    .ok_or(VaultError::ArithmeticOverflow)?,
+            )?
+        }
+        // Fees paid out of the vault lower its NAV
+        FeePayoutMode::Assets => calculate_assets_to_return(
+            shares,
+            vault
+                .total_assets
+                .checked_sub(accrued_fees)
+                .ok_or(VaultError::ArithmeticOverflow)?,
+            vault.total_shares,
+        )?,
+    };
+
+    Ok(ShareValue {
+        shares,
+        gross_assets,
+        net_assets,
+        accrued_fees,
+    })
+}
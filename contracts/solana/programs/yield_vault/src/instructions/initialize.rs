@@ -4,7 +4,10 @@ use anchor_spl::token::{Mint, Token, TokenAccount};
 use crate::constants::*;
 use crate::error::VaultError;
 use crate::events::VaultInitialized;
-use crate::state::VaultState;
+use crate::state::{
+    AuditLog, FeePayoutMode, FeeReport, PriceHistory, StrategyRegistry, VaultState, VaultStatus,
+};
+use crate::utils::{decimals_offset, EventCpi, Q64_ONE};
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct InitializeVaultParams {
@@ -12,6 +15,13 @@ pub struct InitializeVaultParams {
     pub min_deposit: u64,
     pub management_fee_bps: u16,
     pub performance_fee_bps: u16,
+    /// Share mint decimals, e.g. 9 for finer share prices on a 6-decimal
+    /// stablecoin; `None` keeps the asset's, raised to `MIN_SHARE_DECIMALS`
+    pub share_decimals: Option<u8>,
+    /// Create a paper-trading vault: deposits, withdrawals and strategy moves
+    /// transfer no tokens, but shares, NAV and fees are accounted as if they
+    /// did, so strategies can be evaluated on-chain before handling real funds
+    pub paper: bool,
 }
 
 // ── Step 1: Create the vault state PDA only ──────────────────────────────────
@@ -33,7 +43,7 @@ pub struct CreateVault<'info> {
         seeds = [VAULT_SEED, mint.key().as_ref(), owner.key().as_ref()],
         bump,
     )]
-    pub vault: Box<Account<'info, VaultState>>,
+    pub vault: AccountLoader<'info, VaultState>,
 
     pub system_program: Program<'info, System>,
 }
@@ -51,39 +61,148 @@ pub fn handle_create_vault(
         VaultError::InvalidFeeConfig
     );
 
-    let vault = &mut ctx.accounts.vault;
+    let mut vault = ctx.accounts.vault.load_init()?;
+    init_vault_state(
+        &mut vault,
+        ctx.accounts.owner.key(),
+        ctx.accounts.mint.key(),
+        ctx.accounts.mint.decimals,
+        ctx.bumps.vault,
+        &params,
+    )
+}
+
+/// Populate a freshly created vault state. Bumps of the accounts created in
+/// step 2 are left zero until `init_vault_accounts` runs.
+pub(crate) fn init_vault_state(
+    vault: &mut VaultState,
+    owner: Pubkey,
+    mint: Pubkey,
+    mint_decimals: u8,
+    bump: u8,
+    params: &InitializeVaultParams,
+) -> Result<()> {
     let clock = Clock::get()?;
 
-    vault.owner = ctx.accounts.owner.key();
-    vault.mint = ctx.accounts.mint.key();
+    vault.owner = owner;
+    vault.mint = mint;
+    vault.created_slot = clock.slot;
 
     vault.total_assets = 0;
     vault.total_shares = 0;
+    vault.total_debt = 0;
+    vault.paper_balance = 0;
 
     vault.deposit_cap = params.deposit_cap;
     vault.min_deposit = params.min_deposit;
+    vault.cap_schedule_start_cap = 0;
+    vault.cap_schedule_increment = 0;
+    vault.cap_schedule_interval = 0;
+    vault.cap_schedule_started_at = 0;
+    vault.flow_window_period = 0;
+    vault.flow_window_open = 0;
+    vault.flow_window_close = 0;
 
     vault.management_fee_bps = params.management_fee_bps;
     vault.performance_fee_bps = params.performance_fee_bps;
-    vault.high_water_mark = 0;
+    vault.reserve_ratio_bps = 0;
+    vault.target_liquidity_bps = 0;
+    // Low-decimal assets get scaled-up shares so share prices stay precise
+    let (offset, scale) = decimals_offset(mint_decimals, params.share_decimals)?;
+    vault.asset_decimals = mint_decimals;
+    vault.decimals_offset = offset;
+    vault.share_scale = scale;
+    vault.set_high_water_mark(Q64_ONE / scale as u128);
+    vault.set_fee_remainder(0);
+    vault.set_dust(0);
     vault.last_fee_collection = clock.unix_timestamp;
     vault.accrued_management_fee = 0;
 
     vault.managers = [Pubkey::default(); MAX_MANAGERS];
     vault.manager_count = 0;
 
-    vault.paused = false;
+    vault.nav_relayer = Pubkey::default();
+    vault.price_oracle = Pubkey::default();
+    vault.benchmark_oracle = Pubkey::default();
+    vault.donation_recipient = Pubkey::default();
+    vault.market_share_reserve = Pubkey::default();
+    vault.market_asset_reserve = Pubkey::default();
+    vault.policy_commitment = [0; 32];
+    vault.gatekeeper_network = Pubkey::default();
+    vault.max_oracle_conf_bps = 0;
+    vault.max_oracle_staleness = 0;
+    vault.max_accrued_fee_bps = 0;
+    vault.max_market_discount_bps = 0;
+    vault.fee_holiday_start = 0;
+    vault.fee_holiday_end = 0;
+    vault.benchmark_level = 0;
+    vault.benchmark_latest = 0;
+    vault.benchmark_published_at = 0;
+    vault.max_benchmark_staleness = 0;
+    vault.event_seq = 0;
+    vault.stats_reported_assets = 0;
+    vault.stats_reported_shares = 0;
+    vault.external_nav = 0;
+    vault.external_nav_observed_at = 0;
+    vault.withdraw_delay_slots = 0;
+    vault.last_deposit_slot = 0;
+    vault.small_withdrawal_threshold = 0;
+    vault.max_window_outflow = 0;
+    vault.max_window_outflow_bps = 0;
+    vault.outflow_window_start = 0;
+    vault.outflow_window_used = 0;
+    vault.outflow_window_base = 0;
+    vault.max_window_inflow = 0;
+    vault.inflow_window_start = 0;
+    vault.inflow_window_used = 0;
+    vault.liquidity_fee_kink_bps = 0;
+    vault.max_liquidity_entry_fee_bps = 0;
+    vault.max_liquidity_exit_fee_bps = 0;
+    vault.deallocation_window = 0;
+    vault.pending_deallocation = 0;
+    vault.deallocation_deadline = 0;
+    vault.epoch_duration = 0;
+    vault.current_epoch = 0;
+    vault.epoch_started_at = 0;
+    vault.epoch_pending_deposits = 0;
+    vault.epoch_pending_redeems = 0;
+    vault.epoch_reserved_assets = 0;
+    vault.fee_payout_mode = FeePayoutMode::Shares as u8;
 
-    vault.bump = ctx.bumps.vault;
+    vault.status = VaultStatus::Uninitialized as u8;
+    vault.policy_enforced = 0;
+    vault.reserves_proof_enforced = 0;
+    vault.locked = 0;
+    vault.min_strategy_version_major = 0;
+    vault.min_strategy_version_minor = 0;
+    vault.min_strategy_version_patch = 0;
+    vault.redemption_gate_bps = 0;
+    vault.donation_bps = 0;
+    vault.open_airdrops = 0;
+    vault.paper = params.paper as u8;
+    vault.stats_registered = 0;
+    vault.audited_strategies_only = 0;
+    vault.max_strategy_risk_score = 0;
+    vault.manager_vote_period = 0;
+    vault.max_report_age = 0;
+    vault.min_manager_bond = 0;
+    vault.slash_appeal_period = 0;
+    vault.total_bonded = 0;
+
+    vault.bump = bump;
     // These will be set in init_vault_accounts
     vault.share_mint_bump = 0;
     vault.token_account_bump = 0;
+    vault.vote_escrow_bump = 0;
+    vault.epoch_escrow_bump = 0;
+    vault.from_template = 0;
 
     Ok(())
 }
 
-// ── Step 2: Create share mint and vault token account PDAs ───────────────────
+// ── Step 2: Create share mint, vault token account and bookkeeping PDAs ──────
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct InitVaultAccounts<'info> {
     /// The vault owner (must match vault.owner)
@@ -91,25 +210,25 @@ pub struct InitVaultAccounts<'info> {
     pub owner: Signer<'info>,
 
     /// The underlying token mint (must match vault.mint)
-    #[account(address = vault.mint)]
+    #[account(address = vault.load()?.mint)]
     pub mint: Box<Account<'info, Mint>>,
 
     /// The vault state — must already exist
     #[account(
         mut,
-        seeds = [VAULT_SEED, vault.mint.as_ref(), vault.owner.as_ref()],
-        bump = vault.bump,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
         has_one = owner @ VaultError::Unauthorized,
     )]
-    pub vault: Box<Account<'info, VaultState>>,
+    pub vault: AccountLoader<'info, VaultState>,
 
     /// The share token mint (PDA) — vault issues these to depositors
     #[account(
         init,
         payer = owner,
-        seeds = [SHARE_MINT_SEED, vault.key().as_ref()],
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
         bump,
-        mint::decimals = mint.decimals,
+        mint::decimals = vault.load()?.share_decimals(),
         mint::authority = vault,
     )]
     pub share_mint: Box<Account<'info, Mint>>,
@@ -125,25 +244,136 @@ pub struct InitVaultAccounts<'info> {
     )]
     pub vault_token_account: Box<Account<'info, TokenAccount>>,
 
+    /// The vault's lifetime fee report (PDA)
+    #[account(
+        init,
+        payer = owner,
+        space = FeeReport::LEN,
+        seeds = [FEE_REPORT_SEED, vault.key().as_ref()],
+        bump,
+    )]
+    pub fee_report: Box<Account<'info, FeeReport>>,
+
+    /// The vault's share-price history (PDA)
+    #[account(
+        init,
+        payer = owner,
+        space = PriceHistory::LEN,
+        seeds = [PRICE_HISTORY_SEED, vault.key().as_ref()],
+        bump,
+    )]
+    pub price_history: AccountLoader<'info, PriceHistory>,
+
+    /// The vault's strategy registry (PDA)
+    #[account(
+        init,
+        payer = owner,
+        space = StrategyRegistry::LEN,
+        seeds = [STRATEGY_REGISTRY_SEED, vault.key().as_ref()],
+        bump,
+    )]
+    pub strategy_registry: AccountLoader<'info, StrategyRegistry>,
+
+    /// The vault's audit log of administrative actions (PDA)
+    #[account(
+        init,
+        payer = owner,
+        space = AuditLog::LEN,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub rent: Sysvar<'info, Rent>,
 }
 
 pub fn handle_init_vault_accounts(ctx: Context<InitVaultAccounts>) -> Result<()> {
-    let vault = &mut ctx.accounts.vault;
+    let vault_key = ctx.accounts.vault.key();
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    require!(!vault.is_initialized(), VaultError::VaultAlreadyInitialized);
 
     vault.share_mint_bump = ctx.bumps.share_mint;
     vault.token_account_bump = ctx.bumps.vault_token_account;
+    vault.transition(
+        VaultStatus::Active,
+        EventCpi {
+            authority: &ctx.accounts.event_authority,
+            bump: ctx.bumps.event_authority,
+        },
+    )?;
+
+    let fee_report = &mut ctx.accounts.fee_report;
+    fee_report.vault = vault_key;
+    fee_report.total_management_fees = 0;
+    fee_report.total_performance_fees = 0;
+    fee_report.total_fees_collected = 0;
+    fee_report.total_fee_shares_minted = 0;
+    fee_report.total_fees_donated = 0;
+    fee_report.crystallization_count = 0;
+    fee_report.last_crystallized_at = 0;
+    fee_report.high_water_mark_at_crystallization = 0;
+    fee_report.benchmark_at_crystallization = 0;
+    fee_report.bump = ctx.bumps.fee_report;
 
-    emit!(VaultInitialized {
-        vault: vault.key(),
+    let mut price_history = ctx.accounts.price_history.load_init()?;
+    price_history.vault = vault_key;
+    price_history.bump = ctx.bumps.price_history;
+
+    let mut strategy_registry = ctx.accounts.strategy_registry.load_init()?;
+    strategy_registry.vault = vault_key;
+    strategy_registry.bump = ctx.bumps.strategy_registry;
+
+    let mut audit_log = ctx.accounts.audit_log.load_init()?;
+    audit_log.vault = vault_key;
+    audit_log.bump = ctx.bumps.audit_log;
+
+    emit_cpi!(VaultInitialized {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
         mint: ctx.accounts.mint.key(),
         owner: ctx.accounts.owner.key(),
         deposit_cap: vault.deposit_cap,
         management_fee_bps: vault.management_fee_bps,
         performance_fee_bps: vault.performance_fee_bps,
+        paper: vault.is_paper(),
     });
 
     Ok(())
 }
+
+// ── Cancel: close a vault whose step 2 never ran ────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelInit<'info> {
+    /// The vault owner — receives the reclaimed rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+        close = owner,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+}
+
+/// Close a vault left half-initialized by step 1, freeing its seeds so it can
+/// be created again. Step-1 state owns no other accounts, so only the vault
+/// state itself needs closing. The audit log is only created in step 2, so the
+/// `VaultStatusChanged` event is the record of the cancellation.
+pub fn handle_cancel_init(ctx: Context<CancelInit>) -> Result<()> {
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    require!(!vault.is_initialized(), VaultError::VaultAlreadyInitialized);
+    vault.transition(
+        VaultStatus::Closed,
+        EventCpi {
+            authority: &ctx.accounts.event_authority,
+            bump: ctx.bumps.event_authority,
+        },
+    )
+}
@@ -0,0 +1,439 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as ix_sysvar;
+use anchor_spl::token::{self, Approve, Mint, Revoke, Token, TokenAccount};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{IntentCancelled, IntentExecuted, IntentPosted};
+use crate::instructions::crank::CrankPayout;
+use crate::instructions::deposit::ShareIssuance;
+use crate::instructions::withdraw::ShareRedemption;
+use crate::state::{
+    CrankBounty, CrankKind, Intent, IntentKind, RewardsPool, UserPosition, VaultState,
+};
+use crate::utils::{share_price_q64, EventCpi};
+
+// ──────────────────────────────────────────
+// Post Intent
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct PostIntent<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    /// The account the order draws on: the user's underlying account for a
+    /// deposit, their share account for a withdrawal. The intent PDA is
+    /// approved as its delegate.
+    #[account(
+        mut,
+        token::authority = user,
+    )]
+    pub source_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = user,
+        space = Intent::LEN,
+        seeds = [INTENT_SEED, vault.key().as_ref(), user.key().as_ref(), &id.to_le_bytes()],
+        bump,
+    )]
+    pub intent: Box<Account<'info, Intent>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Post a standing order to deposit `amount` of underlying once the share
+/// price is at or below `limit_price_q64`, or to redeem `amount` shares once
+/// it is at or above it. A token account has a single delegate, so this
+/// replaces any earlier session, intent or approval on the source account.
+pub fn handle_post_intent(
+    ctx: Context<PostIntent>,
+    id: u64,
+    kind: IntentKind,
+    amount: u64,
+    limit_price_q64: u128,
+    expires_at: i64,
+) -> Result<()> {
+    require!(amount > 0, VaultError::InvalidAmount);
+    require!(limit_price_q64 > 0, VaultError::InvalidConfig);
+    require!(
+        expires_at > Clock::get()?.unix_timestamp,
+        VaultError::IntentExpired
+    );
+
+    let expected_mint = match kind {
+        IntentKind::Deposit => ctx.accounts.vault.load()?.mint,
+        IntentKind::Withdraw => ctx.accounts.share_mint.key(),
+    };
+    require_keys_eq!(
+        ctx.accounts.source_token_account.mint,
+        expected_mint,
+        VaultError::IntentSourceMismatch
+    );
+
+    token::approve(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Approve {
+                to: ctx.accounts.source_token_account.to_account_info(),
+                delegate: ctx.accounts.intent.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let intent = &mut ctx.accounts.intent;
+    intent.vault = ctx.accounts.vault.key();
+    intent.user = ctx.accounts.user.key();
+    intent.id = id;
+    intent.kind = kind;
+    intent.remaining = amount;
+    intent.limit_price_q64 = limit_price_q64;
+    intent.expires_at = expires_at;
+    intent.bump = ctx.bumps.intent;
+
+    emit_cpi!(IntentPosted {
+        vault: intent.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        user: intent.user,
+        intent: intent.key(),
+        kind,
+        amount,
+        limit_price_q64,
+        expires_at,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Execute Intent
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteIntent<'info> {
+    /// Anyone may fill an intent once its price condition holds; pays the
+    /// position's rent if the user has none
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    /// The intent's owner
+    /// CHECK: bound to the intent by `has_one`
+    pub user: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
+        bump = vault.load()?.token_account_bump,
+        token::mint = vault.load()?.mint,
+        token::authority = vault,
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+        mint::authority = vault,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    /// Funds deposit intents and receives withdrawals
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+        token::authority = user,
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    /// Receives deposit shares and funds withdrawals
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = user,
+    )]
+    pub user_share_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = executor,
+        space = UserPosition::LEN,
+        seeds = [POSITION_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub position: Box<Account<'info, UserPosition>>,
+
+    #[account(
+        mut,
+        seeds = [REWARDS_SEED, vault.key().as_ref()],
+        bump = rewards.bump,
+        has_one = vault,
+    )]
+    pub rewards: Option<Box<Account<'info, RewardsPool>>>,
+
+    /// The vault's price oracle, required for deposits when one is configured
+    /// CHECK: address and owner are checked against the vault config
+    pub oracle: Option<UncheckedAccount<'info>>,
+
+    /// The user's Civic Pass, required for deposits into a permissioned vault
+    /// CHECK: owner, wallet, network, state and expiry are checked against
+    /// the vault config
+    pub gateway_token: Option<UncheckedAccount<'info>>,
+
+    /// The user's compliance flag address; deposits fail if the protocol
+    /// admin has flagged the wallet
+    /// CHECK: address checked by seeds; contents checked by `WalletFlag::check`
+    #[account(seeds = [WALLET_FLAG_SEED, user.key().as_ref()], bump)]
+    pub wallet_flag: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            INTENT_SEED,
+            vault.key().as_ref(),
+            user.key().as_ref(),
+            &intent.id.to_le_bytes(),
+        ],
+        bump = intent.bump,
+        has_one = vault,
+        has_one = user,
+    )]
+    pub intent: Box<Account<'info, Intent>>,
+
+    /// The vault's crank bounty config, to be paid for this crank
+    #[account(
+        mut,
+        seeds = [CRANK_BOUNTY_SEED, vault.key().as_ref()],
+        bump = crank_bounty.bump,
+        has_one = vault,
+    )]
+    pub crank_bounty: Option<Box<Account<'info, CrankBounty>>>,
+
+    /// The bounty budget; its address is checked against `crank_bounty`
+    #[account(mut)]
+    pub crank_bounty_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Receives the crank bounty
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+    )]
+    pub crank_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Instructions sysvar, used to reject flows bundled with a NAV update
+    /// CHECK: address is checked against the sysvar id
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Fill `amount` of an intent (underlying for a deposit, shares for a
+/// withdrawal) at the current share price, paying the crank bounty when its
+/// accounts are given. A withdrawal past the vault's outflow limit fills as
+/// far as the window allows and the rest stays queued in the intent.
+/// Permissionless.
+pub fn handle_execute_intent(ctx: Context<ExecuteIntent>, amount: u64) -> Result<()> {
+    let intent = &ctx.accounts.intent;
+    let now = Clock::get()?.unix_timestamp;
+    require!(now < intent.expires_at, VaultError::IntentExpired);
+    require!(
+        amount > 0 && amount <= intent.remaining,
+        VaultError::InvalidAmount
+    );
+
+    let (price_q64, amount) = {
+        let vault = ctx.accounts.vault.load()?;
+        let price_q64 = share_price_q64(vault.total_assets, vault.total_shares)?;
+        let amount = match intent.kind {
+            IntentKind::Deposit => amount,
+            IntentKind::Withdraw => amount.min(vault.outflow_share_capacity(now)?),
+        };
+        (price_q64, amount)
+    };
+    require!(intent.is_triggered(price_q64), VaultError::IntentNotTriggered);
+    require!(amount > 0, VaultError::OutflowLimitReached);
+
+    let kind = intent.kind;
+    let intent_key = intent.key();
+    let vault_key = ctx.accounts.vault.key();
+    let user_key = ctx.accounts.user.key();
+    let id = intent.id.to_le_bytes();
+    let bump = [intent.bump];
+    let seeds: &[&[u8]] = &[INTENT_SEED, vault_key.as_ref(), user_key.as_ref(), &id, &bump];
+
+    match kind {
+        IntentKind::Deposit => {
+            ShareIssuance {
+                user: user_key,
+                vault: &ctx.accounts.vault,
+                vault_token_account: &ctx.accounts.vault_token_account,
+                share_mint: &ctx.accounts.share_mint,
+                user_token_account: &ctx.accounts.user_token_account,
+                user_share_account: &ctx.accounts.user_share_account,
+                position: &mut ctx.accounts.position,
+                position_bump: ctx.bumps.position,
+                rewards: ctx.accounts.rewards.as_deref_mut(),
+                oracle: ctx.accounts.oracle.as_deref(),
+                gateway_token: ctx.accounts.gateway_token.as_deref(),
+                wallet_flag: &ctx.accounts.wallet_flag,
+                instructions: &ctx.accounts.instructions,
+                token_program: &ctx.accounts.token_program,
+                transfer_authority: ctx.accounts.intent.to_account_info(),
+                events: EventCpi {
+                    authority: &ctx.accounts.event_authority,
+                    bump: ctx.bumps.event_authority,
+                },
+            }
+            .issue(amount, None, &[seeds])?;
+        }
+        IntentKind::Withdraw => {
+            // The limit price is the user's consent to the loss it implies
+            ShareRedemption {
+                user: user_key,
+                vault: &ctx.accounts.vault,
+                vault_token_account: &ctx.accounts.vault_token_account,
+                share_mint: &ctx.accounts.share_mint,
+                user_share_account: &ctx.accounts.user_share_account,
+                recipient: ctx.accounts.user_token_account.to_account_info(),
+                position: &mut ctx.accounts.position,
+                position_bump: ctx.bumps.position,
+                rewards: ctx.accounts.rewards.as_deref_mut(),
+                instructions: &ctx.accounts.instructions,
+                exit_fee_balance: None,
+                token_program: &ctx.accounts.token_program,
+                burn_authority: ctx.accounts.intent.to_account_info(),
+                events: EventCpi {
+                    authority: &ctx.accounts.event_authority,
+                    bump: ctx.bumps.event_authority,
+                },
+            }
+            .redeem(amount, BPS_DENOMINATOR as u16, None, &[seeds])?;
+        }
+    }
+
+    let intent = &mut ctx.accounts.intent;
+    intent.remaining -= amount;
+
+    emit_cpi!(IntentExecuted {
+        vault: vault_key,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        user: user_key,
+        intent: intent_key,
+        executor: ctx.accounts.executor.key(),
+        kind,
+        amount,
+        price_q64,
+        remaining: intent.remaining,
+    });
+
+    CrankPayout {
+        crank: ctx.accounts.executor.key(),
+        vault: &ctx.accounts.vault,
+        crank_bounty: ctx.accounts.crank_bounty.as_deref_mut(),
+        crank_bounty_token_account: ctx.accounts.crank_bounty_token_account.as_ref(),
+        crank_token_account: ctx.accounts.crank_token_account.as_ref(),
+        token_program: &ctx.accounts.token_program,
+        events: EventCpi {
+            authority: &ctx.accounts.event_authority,
+            bump: ctx.bumps.event_authority,
+        },
+    }
+    .pay(CrankKind::ExecuteIntent)
+}
+
+// ──────────────────────────────────────────
+// Cancel Intent
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelIntent<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The intent's vault, which numbers the event
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The account the intent drew on
+    #[account(
+        mut,
+        token::authority = user,
+    )]
+    pub source_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [
+            INTENT_SEED,
+            intent.vault.as_ref(),
+            user.key().as_ref(),
+            &intent.id.to_le_bytes(),
+        ],
+        bump = intent.bump,
+        has_one = vault,
+        has_one = user,
+    )]
+    pub intent: Box<Account<'info, Intent>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Close the intent and, if it is still the source account's delegate,
+/// revoke the delegation
+pub fn handle_cancel_intent(ctx: Context<CancelIntent>) -> Result<()> {
+    let intent_pda = ctx.accounts.intent.key();
+    if ctx.accounts.source_token_account.delegate == Some(intent_pda).into() {
+        token::revoke(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Revoke {
+                source: ctx.accounts.source_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ))?;
+    }
+
+    let intent = &ctx.accounts.intent;
+    emit_cpi!(IntentCancelled {
+        vault: intent.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        user: intent.user,
+        intent: intent_pda,
+        remaining: intent.remaining,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,478 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{ManagerAdded, ManagerProposalResolved, ManagerProposed, ManagerVoteCast};
+use crate::instructions::audit_log::record_audit;
+use crate::state::{
+    AuditAction, AuditLog, ManagerBond, ManagerProfile, ManagerProposal, ManagerVoteRecord,
+    VaultState,
+};
+
+// ──────────────────────────────────────────
+// Set Manager Vote Period
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct SetManagerVotePeriod<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    /// Escrow for shares locked by manager votes (PDA, created on first use)
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [VOTE_ESCROW_SEED, vault.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = vault,
+    )]
+    pub vote_escrow: Box<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Require manager proposals to pass a share-holder vote lasting `period`
+/// seconds. Voting cannot be turned off or shortened below
+/// `MIN_MANAGER_VOTE_PERIOD` once enabled, so the owner cannot route around
+/// a veto through `add_manager`.
+pub fn handle_set_manager_vote_period(
+    ctx: Context<SetManagerVotePeriod>,
+    period: i64,
+) -> Result<()> {
+    require!(
+        period >= MIN_MANAGER_VOTE_PERIOD,
+        VaultError::ManagerVotePeriodTooShort
+    );
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.manager_vote_period = period;
+    vault.vote_escrow_bump = ctx.bumps.vote_escrow;
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetManagerVotePeriod,
+        &[&period.to_le_bytes()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Propose Manager
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(manager: Pubkey)]
+pub struct ProposeManager<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = ManagerProposal::LEN,
+        seeds = [MANAGER_PROPOSAL_SEED, vault.key().as_ref(), manager.as_ref()],
+        bump,
+    )]
+    pub proposal: Box<Account<'info, ManagerProposal>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_propose_manager(ctx: Context<ProposeManager>, manager: Pubkey) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let mut vault = ctx.accounts.vault.load_mut()?;
+
+    require!(vault.manager_vote_period > 0, VaultError::ManagerVotingDisabled);
+    require!(
+        (vault.manager_count as usize) < MAX_MANAGERS,
+        VaultError::MaxManagersReached
+    );
+    require!(!vault.is_manager(&manager), VaultError::ManagerAlreadyExists);
+
+    let clock = Clock::get()?;
+    let voting_ends_at = clock
+        .unix_timestamp
+        .checked_add(vault.manager_vote_period)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    let event_seq = vault.next_event_seq();
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.vault = vault_key;
+    proposal.manager = manager;
+    proposal.votes_for = 0;
+    proposal.votes_against = 0;
+    proposal.voting_ends_at = voting_ends_at;
+    proposal.id = event_seq;
+    proposal.bump = ctx.bumps.proposal;
+
+    emit_cpi!(ManagerProposed {
+        vault: vault_key,
+        event_seq,
+        manager,
+        voting_ends_at,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::ProposeManager,
+        &[manager.as_ref()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Vote On Manager
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct VoteOnManager<'info> {
+    /// The share holder voting
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [MANAGER_PROPOSAL_SEED, vault.key().as_ref(), proposal.manager.as_ref()],
+        bump = proposal.bump,
+        has_one = vault,
+    )]
+    pub proposal: Box<Account<'info, ManagerProposal>>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = ManagerVoteRecord::LEN,
+        seeds = [
+            MANAGER_VOTE_SEED,
+            proposal.key().as_ref(),
+            &proposal.id.to_le_bytes(),
+            voter.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub vote_record: Box<Account<'info, ManagerVoteRecord>>,
+
+    /// The voter's share token account (shares are locked from here)
+    #[account(
+        mut,
+        token::mint = vote_escrow.mint,
+        token::authority = voter,
+    )]
+    pub voter_share_account: Box<Account<'info, TokenAccount>>,
+
+    /// Vote escrow (receives locked shares)
+    #[account(
+        mut,
+        seeds = [VOTE_ESCROW_SEED, vault.key().as_ref()],
+        bump = vault.load()?.vote_escrow_bump,
+    )]
+    pub vote_escrow: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_vote_on_manager(
+    ctx: Context<VoteOnManager>,
+    approve: bool,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    let clock = Clock::get()?;
+    require!(
+        clock.unix_timestamp < ctx.accounts.proposal.voting_ends_at,
+        VaultError::VotingClosed
+    );
+
+    // Locking the shares snapshots the vote weight: the same shares cannot be
+    // moved to another wallet and counted again for this proposal
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.voter_share_account.to_account_info(),
+                to: ctx.accounts.vote_escrow.to_account_info(),
+                authority: ctx.accounts.voter.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let proposal = &mut ctx.accounts.proposal;
+    if approve {
+        proposal.votes_for = proposal
+            .votes_for
+            .checked_add(amount)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+    } else {
+        proposal.votes_against = proposal
+            .votes_against
+            .checked_add(amount)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+    }
+
+    let vote_record = &mut ctx.accounts.vote_record;
+    vote_record.vault = ctx.accounts.vault.key();
+    vote_record.voter = ctx.accounts.voter.key();
+    vote_record.proposal = proposal.key();
+    vote_record.proposal_id = proposal.id;
+    vote_record.weight = amount;
+    vote_record.approve = approve;
+    vote_record.voting_ends_at = proposal.voting_ends_at;
+    vote_record.bump = ctx.bumps.vote_record;
+
+    emit_cpi!(ManagerVoteCast {
+        vault: vote_record.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        manager: proposal.manager,
+        voter: vote_record.voter,
+        approve,
+        weight: amount,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Finalize Manager Proposal
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizeManagerProposal<'info> {
+    /// Anyone may finalize once voting has ended
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Vault owner — receives the proposal's rent
+    /// CHECK: validated against vault.owner
+    #[account(mut, address = vault.load()?.owner @ VaultError::Unauthorized)]
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        seeds = [MANAGER_PROPOSAL_SEED, vault.key().as_ref(), proposal.manager.as_ref()],
+        bump = proposal.bump,
+        has_one = vault,
+        close = owner,
+    )]
+    pub proposal: Box<Account<'info, ManagerProposal>>,
+
+    /// The proposed manager's reputation profile (created on its first vault)
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ManagerProfile::LEN,
+        seeds = [MANAGER_PROFILE_SEED, proposal.manager.as_ref()],
+        bump,
+    )]
+    pub manager_profile: Box<Account<'info, ManagerProfile>>,
+
+    /// The proposed manager's bond, required when the vault sets a minimum bond
+    #[account(
+        seeds = [MANAGER_BOND_SEED, vault.key().as_ref(), proposal.manager.as_ref()],
+        bump = manager_bond.bump,
+    )]
+    pub manager_bond: Option<Box<Account<'info, ManagerBond>>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_finalize_manager_proposal(ctx: Context<FinalizeManagerProposal>) -> Result<()> {
+    let clock = Clock::get()?;
+    let proposal = &ctx.accounts.proposal;
+
+    require!(
+        clock.unix_timestamp >= proposal.voting_ends_at,
+        VaultError::VotingNotEnded
+    );
+
+    let approved = proposal.is_approved();
+    let manager = proposal.manager;
+    let vault_key = ctx.accounts.vault.key();
+    ctx.accounts
+        .manager_profile
+        .open(manager, ctx.bumps.manager_profile, clock.unix_timestamp);
+
+    if approved {
+        let mut vault = ctx.accounts.vault.load_mut()?;
+        let bonded = ctx.accounts.manager_bond.as_ref().map_or(0, |bond| bond.amount);
+        require!(bonded >= vault.min_manager_bond, VaultError::ManagerBondTooLow);
+        vault.add_manager(manager)?;
+        drop(vault);
+        ctx.accounts.manager_profile.record_vault_added();
+        emit_cpi!(ManagerAdded {
+            vault: vault_key,
+            event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+            manager,
+        });
+    }
+
+    emit_cpi!(ManagerProposalResolved {
+        vault: vault_key,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        manager,
+        approved,
+        votes_for: proposal.votes_for,
+        votes_against: proposal.votes_against,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.payer.key(),
+        AuditAction::FinalizeManagerProposal,
+        &[manager.as_ref(), &[approved as u8]],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Release Manager Vote
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct ReleaseManagerVote<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vote record — closed and rent returned to the voter
+    #[account(
+        mut,
+        seeds = [
+            MANAGER_VOTE_SEED,
+            vote_record.proposal.as_ref(),
+            &vote_record.proposal_id.to_le_bytes(),
+            voter.key().as_ref(),
+        ],
+        bump = vote_record.bump,
+        has_one = vault,
+        has_one = voter @ VaultError::Unauthorized,
+        close = voter,
+    )]
+    pub vote_record: Box<Account<'info, ManagerVoteRecord>>,
+
+    /// The voter's share token account (receives unlocked shares)
+    #[account(
+        mut,
+        token::mint = vote_escrow.mint,
+        token::authority = voter,
+    )]
+    pub voter_share_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [VOTE_ESCROW_SEED, vault.key().as_ref()],
+        bump = vault.load()?.vote_escrow_bump,
+    )]
+    pub vote_escrow: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle_release_manager_vote(ctx: Context<ReleaseManagerVote>) -> Result<()> {
+    let clock = Clock::get()?;
+    require!(
+        clock.unix_timestamp >= ctx.accounts.vote_record.voting_ends_at,
+        VaultError::VotingNotEnded
+    );
+
+    let vault = ctx.accounts.vault.load()?;
+    let seeds = vault.signer_seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vote_escrow.to_account_info(),
+                to: ctx.accounts.voter_share_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        ctx.accounts.vote_record.weight,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::MarketDiscountSignal;
+use crate::instructions::audit_log::record_audit;
+use crate::state::{AuditAction, AuditLog, VaultState};
+use crate::utils::share_price_q64;
+
+/// The configured market's share price against NAV
+pub(crate) struct MarketQuote {
+    pub market_price_q64: u128,
+    pub nav_price_q64: u128,
+    /// Discount of the market price to NAV in basis points (0 at or above NAV)
+    pub discount_bps: u64,
+}
+
+/// Price shares from the reserves of the vault's configured constant-product
+/// pool. Returns `None` when no market is configured.
+pub(crate) fn market_quote(
+    vault: &VaultState,
+    share_reserve: Option<&Account<TokenAccount>>,
+    asset_reserve: Option<&Account<TokenAccount>>,
+) -> Result<Option<MarketQuote>> {
+    if vault.market_share_reserve == Pubkey::default() {
+        return Ok(None);
+    }
+    let (share_reserve, asset_reserve) = share_reserve
+        .zip(asset_reserve)
+        .ok_or(VaultError::InvalidMarketPool)?;
+    require_keys_eq!(
+        share_reserve.key(),
+        vault.market_share_reserve,
+        VaultError::InvalidMarketPool
+    );
+    require_keys_eq!(
+        asset_reserve.key(),
+        vault.market_asset_reserve,
+        VaultError::InvalidMarketPool
+    );
+    require!(share_reserve.amount > 0, VaultError::InvalidMarketPool);
+
+    let market_price_q64 = share_price_q64(asset_reserve.amount, share_reserve.amount)?;
+    let nav_price_q64 = share_price_q64(vault.total_assets, vault.total_shares)?;
+    let discount_bps = if market_price_q64 < nav_price_q64 {
+        ((nav_price_q64 - market_price_q64)
+            .checked_mul(BPS_DENOMINATOR)
+            .ok_or(VaultError::ArithmeticOverflow)?
+            / nav_price_q64) as u64
+    } else {
+        0
+    };
+
+    Ok(Some(MarketQuote {
+        market_price_q64,
+        nav_price_q64,
+        discount_bps,
+    }))
+}
+
+// ──────────────────────────────────────────
+// Set Market Pool
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct SetMarketPool<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    /// The pool's share reserve; omit both reserves to remove the guard
+    #[account(token::mint = share_mint)]
+    pub market_share_reserve: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// The pool's underlying reserve
+    #[account(token::mint = vault.load()?.mint)]
+    pub market_asset_reserve: Option<Box<Account<'info, TokenAccount>>>,
+}
+
+/// Block deposits while the pool prices shares more than `max_discount_bps`
+/// below NAV. Owner only.
+pub fn handle_set_market_pool(ctx: Context<SetMarketPool>, max_discount_bps: u16) -> Result<()> {
+    require!(
+        (max_discount_bps as u128) < BPS_DENOMINATOR,
+        VaultError::InvalidConfig
+    );
+    let (share_reserve, asset_reserve) = match (
+        &ctx.accounts.market_share_reserve,
+        &ctx.accounts.market_asset_reserve,
+    ) {
+        (Some(shares), Some(assets)) => (shares.key(), assets.key()),
+        (None, None) => (Pubkey::default(), Pubkey::default()),
+        _ => return err!(VaultError::InvalidMarketPool),
+    };
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.market_share_reserve = share_reserve;
+    vault.market_asset_reserve = asset_reserve;
+    vault.max_market_discount_bps = max_discount_bps;
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetMarketPool,
+        &[share_reserve.as_ref(), asset_reserve.as_ref(), &max_discount_bps.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Signal Market Discount
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SignalMarketDiscount<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    pub market_share_reserve: Box<Account<'info, TokenAccount>>,
+    pub market_asset_reserve: Box<Account<'info, TokenAccount>>,
+}
+
+/// Publish the market's discount to NAV so agents can arbitrage it back,
+/// buying shares on the pool and redeeming them at NAV. Permissionless.
+pub fn handle_signal_market_discount(ctx: Context<SignalMarketDiscount>) -> Result<()> {
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    let quote = market_quote(
+        &vault,
+        Some(&ctx.accounts.market_share_reserve),
+        Some(&ctx.accounts.market_asset_reserve),
+    )?
+    .ok_or(VaultError::InvalidMarketPool)?;
+
+    emit_cpi!(MarketDiscountSignal {
+        vault: ctx.accounts.vault.key(),
+        event_seq: vault.next_event_seq(),
+        market_price_q64: quote.market_price_q64,
+        nav_price_q64: quote.nav_price_q64,
+        discount_bps: quote.discount_bps,
+        deposits_blocked: quote.discount_bps > vault.max_market_discount_bps as u64,
+    });
+    Ok(())
+}
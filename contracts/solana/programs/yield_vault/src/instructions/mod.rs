@@ -1,15 +1,71 @@
 pub mod initialize;
 pub mod deposit;
 pub mod withdraw;
+pub mod close_position;
+pub mod donate;
+pub mod skim;
 pub mod update_nav;
 pub mod collect_fees;
+pub mod sweep_dust;
+pub mod fee_report;
 pub mod admin;
 pub mod close;
+pub mod rescue;
+pub mod manager_vote;
+pub mod strategy;
+pub mod external_nav;
+pub mod rewards;
+pub mod epoch;
+pub mod protocol;
+pub mod zap;
+pub mod session;
+pub mod position_history;
+pub mod airdrop;
+pub mod tranche;
+pub mod bond;
+pub mod intent;
+pub mod stop_loss;
+pub mod market;
+pub mod policy;
+pub mod reserves;
+pub mod crank;
+pub mod audit_log;
+pub mod benchmark;
+pub mod receipt;
+pub mod disclosure;
 
 pub use initialize::*;
 pub use deposit::*;
 pub use withdraw::*;
+pub use close_position::*;
+pub use donate::*;
+pub use skim::*;
 pub use update_nav::*;
 pub use collect_fees::*;
+pub use sweep_dust::*;
+pub use fee_report::*;
 pub use admin::*;
 pub use close::*;
+pub use rescue::*;
+pub use manager_vote::*;
+pub use strategy::*;
+pub use external_nav::*;
+pub use rewards::*;
+pub use epoch::*;
+pub use protocol::*;
+pub use zap::*;
+pub use session::*;
+pub use position_history::*;
+pub use airdrop::*;
+pub use tranche::*;
+pub use bond::*;
+pub use intent::*;
+pub use stop_loss::*;
+pub use market::*;
+pub use policy::*;
+pub use reserves::*;
+pub use crank::*;
+pub use audit_log::*;
+pub use benchmark::*;
+pub use receipt::*;
+pub use disclosure::*;
@@ -0,0 +1,325 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{PolicySet, Rebalanced, StrategyAllocated};
+use crate::instructions::audit_log::record_audit;
+use crate::instructions::strategy::record_report;
+use crate::state::{
+    AuditAction, AuditLog, Policy, PolicyRule, StrategyState, StrategyWhitelist, VaultState,
+};
+use crate::utils::EventCpi;
+
+// ──────────────────────────────────────────
+// Set Policy
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPolicy<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = Policy::LEN,
+        seeds = [POLICY_SEED, vault.key().as_ref()],
+        bump,
+    )]
+    pub policy: Box<Account<'info, Policy>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Replace the vault's rebalancing rules. With `enforce`, managers can only
+/// move funds into strategies through `rebalance`.
+pub fn handle_set_policy(
+    ctx: Context<SetPolicy>,
+    rules: Vec<PolicyRule>,
+    enforce: bool,
+) -> Result<()> {
+    require!(rules.len() <= MAX_POLICY_RULES, VaultError::InvalidConfig);
+    let mut total_target = 0u128;
+    for (i, rule) in rules.iter().enumerate() {
+        require!(
+            (rule.band_bps as u128) <= BPS_DENOMINATOR,
+            VaultError::InvalidConfig
+        );
+        require!(
+            rules[..i].iter().all(|other| other.strategy != rule.strategy),
+            VaultError::InvalidConfig
+        );
+        total_target += rule.target_bps as u128;
+    }
+    require!(total_target <= BPS_DENOMINATOR, VaultError::InvalidConfig);
+
+    let vault_key = ctx.accounts.vault.key();
+    let policy = &mut ctx.accounts.policy;
+    policy.vault = vault_key;
+    policy.rules = [PolicyRule::default(); MAX_POLICY_RULES];
+    policy.rules[..rules.len()].copy_from_slice(&rules);
+    policy.rule_count = rules.len() as u8;
+    policy.bump = ctx.bumps.policy;
+
+    ctx.accounts.vault.load_mut()?.policy_enforced = enforce as u8;
+
+    emit_cpi!(PolicySet {
+        vault: vault_key,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        rule_count: policy.rule_count,
+        enforced: enforce,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetPolicy,
+        &[&rules.try_to_vec()?, &[enforce as u8]],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Rebalance
+// ──────────────────────────────────────────
+
+/// One strategy move in a `rebalance`: a positive `delta` allocates from the
+/// vault, a negative one returns funds to it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct RebalanceTrade {
+    pub delta: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Rebalance<'info> {
+    /// The manager or owner rebalancing
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        seeds = [POLICY_SEED, vault.key().as_ref()],
+        bump = policy.bump,
+        has_one = vault,
+    )]
+    pub policy: Box<Account<'info, Policy>>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
+        bump = vault.load()?.token_account_bump,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol strategy registry the vault's strategy requirements are
+    /// checked against
+    #[account(
+        seeds = [STRATEGY_WHITELIST_SEED],
+        bump = strategy_whitelist.bump,
+    )]
+    pub strategy_whitelist: Box<Account<'info, StrategyWhitelist>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Execute `trades` in order, each against the `(strategy, strategy token
+/// account, strategy authority)` triple at the same position in the
+/// remaining accounts. Every trade must conform to its strategy's policy
+/// rule; strategy authorities must sign trades that return funds. Returns
+/// are principal only: gains are realized through `deallocate` or `harvest`.
+pub fn handle_rebalance<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Rebalance<'info>>,
+    trades: Vec<RebalanceTrade>,
+) -> Result<()> {
+    require!(
+        !trades.is_empty() && ctx.remaining_accounts.len() == 3 * trades.len(),
+        VaultError::InvalidRebalanceAccounts
+    );
+    let vault_key = ctx.accounts.vault.key();
+    {
+        let vault = ctx.accounts.vault.load()?;
+        require!(
+            vault.is_authority(&ctx.accounts.authority.key()),
+            VaultError::Unauthorized
+        );
+        vault.status().check_allocations()?;
+        require!(!vault.is_locked(), VaultError::Reentrancy);
+        require!(!vault.is_paper(), VaultError::PaperVault);
+    }
+    let now = Clock::get()?.unix_timestamp;
+    let events = EventCpi {
+        authority: &ctx.accounts.event_authority,
+        bump: ctx.bumps.event_authority,
+    };
+
+    for (trade, accounts) in trades.iter().zip(ctx.remaining_accounts.chunks(3)) {
+        let mut strategy = Account::<StrategyState>::try_from(&accounts[0])?;
+        require_keys_eq!(strategy.vault, vault_key, VaultError::InvalidRebalanceAccounts);
+        require_keys_eq!(
+            accounts[1].key(),
+            strategy.token_account,
+            VaultError::InvalidRebalanceAccounts
+        );
+        let rule = ctx
+            .accounts
+            .policy
+            .rule(&strategy.key())
+            .ok_or(VaultError::PolicyViolation)?;
+
+        let size = trade.delta.unsigned_abs();
+        require!(size > 0, VaultError::InvalidAmount);
+        let before = strategy.current_debt;
+        let after = if trade.delta > 0 {
+            before
+                .checked_add(size)
+                .ok_or(VaultError::ArithmeticOverflow)?
+        } else {
+            before
+                .checked_sub(size)
+                .ok_or(VaultError::InsufficientStrategyFunds)?
+        };
+
+        let vault = ctx.accounts.vault.load()?;
+        require!(
+            rule.permits(before, after, vault.total_assets)?,
+            VaultError::PolicyViolation
+        );
+
+        if trade.delta > 0 {
+            require!(
+                strategy.within_debt_ceiling(after),
+                VaultError::DebtCeilingExceeded
+            );
+            require!(
+                !vault.is_deallocation_overdue(now),
+                VaultError::AllocationsSuspended
+            );
+            require!(
+                !strategy.is_stale(now, vault.max_report_age),
+                VaultError::StrategyStale
+            );
+            vault.check_strategy_requirements(
+                ctx.accounts
+                    .strategy_whitelist
+                    .metadata(&strategy.strategy_id),
+            )?;
+            let liquid = vault.liquid_assets(ctx.accounts.vault_token_account.amount);
+            require!(liquid >= size, VaultError::InsufficientAssets);
+            require!(
+                liquid - size >= vault.liquidity_reserve()?,
+                VaultError::LiquidityReserved
+            );
+
+            let seeds = vault.signer_seeds();
+            let signer_seeds: &[&[&[u8]]] = &[&seeds];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.vault_token_account.to_account_info(),
+                        to: accounts[1].clone(),
+                        authority: ctx.accounts.vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                size,
+            )?;
+            drop(vault);
+
+            let mut vault = ctx.accounts.vault.load_mut()?;
+            vault.total_debt = vault
+                .total_debt
+                .checked_add(size)
+                .ok_or(VaultError::ArithmeticOverflow)?;
+            strategy.current_debt = after;
+
+            emit_cpi!(StrategyAllocated {
+                vault: vault_key,
+                event_seq: vault.next_event_seq(),
+                strategy: strategy.key(),
+                amount: size,
+                current_debt: after,
+            });
+        } else {
+            drop(vault);
+            let strategy_authority = &accounts[2];
+            require!(
+                strategy_authority.is_signer && strategy_authority.key() == strategy.authority,
+                VaultError::Unauthorized
+            );
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: accounts[1].clone(),
+                        to: ctx.accounts.vault_token_account.to_account_info(),
+                        authority: strategy_authority.clone(),
+                    },
+                ),
+                size,
+            )?;
+
+            let mut vault = ctx.accounts.vault.load_mut()?;
+            vault.total_debt = vault
+                .total_debt
+                .checked_sub(size)
+                .ok_or(VaultError::ArithmeticOverflow)?;
+            vault.fulfil_deallocation(size);
+            record_report(&mut vault, &mut strategy, 0, 0, after, now, events)?;
+        }
+
+        strategy.exit(&crate::ID)?;
+        ctx.accounts.vault_token_account.reload()?;
+    }
+
+    emit_cpi!(Rebalanced {
+        vault: vault_key,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        trades: trades.len() as u8,
+    });
+
+    let deltas: Vec<u8> = trades.iter().flat_map(|t| t.delta.to_le_bytes()).collect();
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.authority.key(),
+        AuditAction::Rebalance,
+        &[&deltas],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,291 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::TokenAccount;
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{PositionHistoryRoundStarted, PositionSnapshotted};
+use crate::state::{PositionHistory, UserPosition, VaultState};
+use crate::utils::position_snapshot_leaf;
+
+/// Anchor discriminator of the compression program's `init_empty_merkle_tree`
+const INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR: [u8; 8] = [191, 11, 119, 7, 180, 107, 220, 110];
+
+/// Anchor discriminator of the compression program's `append`
+const APPEND_DISCRIMINATOR: [u8; 8] = [149, 120, 18, 222, 236, 225, 88, 203];
+
+/// Invoke the compression program on `merkle_tree`, with the history PDA
+/// signing as tree authority
+fn invoke_compression<'info>(
+    data: Vec<u8>,
+    merkle_tree: &AccountInfo<'info>,
+    history: &AccountInfo<'info>,
+    noop: &AccountInfo<'info>,
+    compression_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    invoke_signed(
+        &Instruction {
+            program_id: SPL_ACCOUNT_COMPRESSION_ID,
+            accounts: vec![
+                AccountMeta::new(merkle_tree.key(), false),
+                AccountMeta::new_readonly(history.key(), true),
+                AccountMeta::new_readonly(noop.key(), false),
+            ],
+            data,
+        },
+        &[
+            merkle_tree.clone(),
+            history.clone(),
+            noop.clone(),
+            compression_program.clone(),
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Init Position History
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct InitPositionHistory<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = PositionHistory::LEN,
+        seeds = [POSITION_HISTORY_SEED, vault.key().as_ref()],
+        bump,
+    )]
+    pub history: Box<Account<'info, PositionHistory>>,
+
+    /// Empty tree account, allocated earlier in the transaction and owned by
+    /// the compression program
+    /// CHECK: ownership is checked here; the compression program checks the size
+    #[account(mut, owner = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: address is checked
+    #[account(address = SPL_NOOP_ID)]
+    pub noop: UncheckedAccount<'info>,
+
+    /// CHECK: address is checked
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Create the vault's position history over a fresh tree of `max_depth`
+/// (2^max_depth snapshots) accepting `max_buffer_size` concurrent appends
+pub fn handle_init_position_history(
+    ctx: Context<InitPositionHistory>,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let bump = [ctx.bumps.history];
+    let seeds: &[&[u8]] = &[POSITION_HISTORY_SEED, vault_key.as_ref(), &bump];
+
+    let mut data = INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&max_depth.to_le_bytes());
+    data.extend_from_slice(&max_buffer_size.to_le_bytes());
+    invoke_compression(
+        data,
+        &ctx.accounts.merkle_tree.to_account_info(),
+        &ctx.accounts.history.to_account_info(),
+        &ctx.accounts.noop.to_account_info(),
+        &ctx.accounts.compression_program.to_account_info(),
+        &[seeds],
+    )?;
+
+    let history = &mut ctx.accounts.history;
+    history.vault = vault_key;
+    history.merkle_tree = ctx.accounts.merkle_tree.key();
+    history.round = 0;
+    history.round_started_at = Clock::get()?.unix_timestamp;
+    history.leaf_count = 0;
+    history.bump = ctx.bumps.history;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Start History Round
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct StartHistoryRound<'info> {
+    /// Owner or manager
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [POSITION_HISTORY_SEED, vault.key().as_ref()],
+        bump = history.bump,
+        has_one = vault,
+    )]
+    pub history: Box<Account<'info, PositionHistory>>,
+}
+
+/// Open the next snapshot round, typically once per vault epoch
+pub fn handle_start_history_round(ctx: Context<StartHistoryRound>) -> Result<()> {
+    require!(
+        ctx.accounts.vault.load()?.is_authority(&ctx.accounts.authority.key()),
+        VaultError::Unauthorized
+    );
+
+    let history = &mut ctx.accounts.history;
+    history.round = history
+        .round
+        .checked_add(1)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    history.round_started_at = Clock::get()?.unix_timestamp;
+
+    emit_cpi!(PositionHistoryRoundStarted {
+        vault: history.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        round: history.round,
+        started_at: history.round_started_at,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Record Position Snapshots
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecordPositionSnapshots<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [POSITION_HISTORY_SEED, vault.key().as_ref()],
+        bump = history.bump,
+        has_one = vault,
+        has_one = merkle_tree,
+    )]
+    pub history: Box<Account<'info, PositionHistory>>,
+
+    /// CHECK: bound to the history by `has_one`
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: address is checked
+    #[account(address = SPL_NOOP_ID)]
+    pub noop: UncheckedAccount<'info>,
+
+    /// CHECK: address is checked
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+}
+
+/// Append a snapshot leaf for each `(position, share account)` pair in the
+/// remaining accounts. Permissionless: leaves are built from on-chain state,
+/// so a crank can only record true balances. The off-chain proof service
+/// keeps the first snapshot of each holder per round.
+pub fn handle_record_position_snapshots<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RecordPositionSnapshots<'info>>,
+) -> Result<()> {
+    require!(
+        !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len().is_multiple_of(2),
+        VaultError::InvalidSnapshotAccounts
+    );
+
+    let vault_key = ctx.accounts.vault.key();
+    let (share_mint, total_assets, total_shares) = {
+        let vault = ctx.accounts.vault.load()?;
+        let share_mint = Pubkey::create_program_address(
+            &[
+                SHARE_MINT_SEED,
+                vault_key.as_ref(),
+                &vault.created_slot.to_le_bytes(),
+                &[vault.share_mint_bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| VaultError::InvalidConfig)?;
+        (share_mint, vault.total_assets, vault.total_shares)
+    };
+    let slot = Clock::get()?.slot;
+
+    let history_info = ctx.accounts.history.to_account_info();
+    let bump = [ctx.accounts.history.bump];
+    let seeds: &[&[u8]] = &[POSITION_HISTORY_SEED, vault_key.as_ref(), &bump];
+    let round = ctx.accounts.history.round;
+
+    for pair in ctx.remaining_accounts.chunks(2) {
+        let position = Account::<UserPosition>::try_from(&pair[0])?;
+        require_keys_eq!(position.vault, vault_key, VaultError::InvalidSnapshotAccounts);
+        let share_account = Account::<TokenAccount>::try_from(&pair[1])?;
+        require_keys_eq!(share_account.mint, share_mint, VaultError::InvalidSnapshotAccounts);
+        require_keys_eq!(share_account.owner, position.user, VaultError::InvalidSnapshotAccounts);
+
+        let leaf = position_snapshot_leaf(
+            &vault_key,
+            &position.user,
+            round,
+            share_account.amount,
+            total_assets,
+            total_shares,
+            slot,
+        );
+        let mut data = APPEND_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&leaf);
+        invoke_compression(
+            data,
+            &ctx.accounts.merkle_tree.to_account_info(),
+            &history_info,
+            &ctx.accounts.noop.to_account_info(),
+            &ctx.accounts.compression_program.to_account_info(),
+            &[seeds],
+        )?;
+
+        let history = &mut ctx.accounts.history;
+        emit_cpi!(PositionSnapshotted {
+            vault: vault_key,
+            event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+            user: position.user,
+            round,
+            leaf_index: history.leaf_count,
+            shares: share_account.amount,
+            total_assets,
+            total_shares,
+            slot,
+        });
+        history.leaf_count = history
+            .leaf_count
+            .checked_add(1)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+    }
+
+    Ok(())
+}
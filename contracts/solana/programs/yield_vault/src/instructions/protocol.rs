@@ -0,0 +1,594 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{
+    PriceFeedUpdated, ProtocolConfigUpdated, StrategyMetadataSet, StrategyWhitelistUpdated,
+    WalletFlagged, WalletUnflagged,
+};
+use crate::instructions::initialize::{init_vault_state, InitializeVaultParams};
+use crate::state::{
+    ComplianceFlag, FeedRegistry, GlobalStats, MintStats, PriceFeed, ProtocolConfig,
+    StrategyMetadata, StrategyWhitelist, VaultState, WalletFlag,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ProtocolConfigParams {
+    pub treasury: Pubkey,
+    pub guardian: Pubkey,
+    pub min_management_fee_bps: u16,
+    pub max_management_fee_bps: u16,
+    pub min_performance_fee_bps: u16,
+    pub max_performance_fee_bps: u16,
+    pub default_management_fee_bps: u16,
+    pub default_performance_fee_bps: u16,
+    pub default_deposit_cap: u64,
+    pub default_min_deposit: u64,
+    pub default_withdraw_delay_slots: u64,
+    pub default_reserve_ratio_bps: u16,
+    pub default_small_withdrawal_threshold: u64,
+    pub zap_programs: [Pubkey; MAX_ZAP_PROGRAMS],
+}
+
+impl ProtocolConfigParams {
+    /// Write validated params into the config
+    fn apply(&self, config: &mut ProtocolConfig) -> Result<()> {
+        require!(
+            self.min_management_fee_bps <= self.max_management_fee_bps
+                && self.max_management_fee_bps <= MAX_FEE_BPS,
+            VaultError::InvalidFeeConfig
+        );
+        require!(
+            self.min_performance_fee_bps <= self.max_performance_fee_bps
+                && self.max_performance_fee_bps <= MAX_FEE_BPS,
+            VaultError::InvalidFeeConfig
+        );
+        require!(
+            self.default_reserve_ratio_bps as u128 <= BPS_DENOMINATOR,
+            VaultError::InvalidConfig
+        );
+
+        config.treasury = self.treasury;
+        config.guardian = self.guardian;
+        config.min_management_fee_bps = self.min_management_fee_bps;
+        config.max_management_fee_bps = self.max_management_fee_bps;
+        config.min_performance_fee_bps = self.min_performance_fee_bps;
+        config.max_performance_fee_bps = self.max_performance_fee_bps;
+        config.default_management_fee_bps = self.default_management_fee_bps;
+        config.default_performance_fee_bps = self.default_performance_fee_bps;
+        config.default_deposit_cap = self.default_deposit_cap;
+        config.default_min_deposit = self.default_min_deposit;
+        config.default_withdraw_delay_slots = self.default_withdraw_delay_slots;
+        config.default_reserve_ratio_bps = self.default_reserve_ratio_bps;
+        config.default_small_withdrawal_threshold = self.default_small_withdrawal_threshold;
+        config.zap_programs = self.zap_programs;
+
+        // The defaults must themselves be compliant
+        config.check_fees(
+            config.default_management_fee_bps,
+            config.default_performance_fee_bps,
+        )
+    }
+}
+
+// ──────────────────────────────────────────
+// Init Protocol Config
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitProtocolConfig<'info> {
+    /// The program's upgrade authority, who becomes the config admin
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = ProtocolConfig::LEN,
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    /// The program's data account, holding its upgrade authority
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = UpgradeableLoaderState::owner(),
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ VaultError::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_init_protocol_config(
+    ctx: Context<InitProtocolConfig>,
+    params: ProtocolConfigParams,
+) -> Result<()> {
+    let config = &mut ctx.accounts.protocol_config;
+    config.admin = ctx.accounts.admin.key();
+    config.bump = ctx.bumps.protocol_config;
+    params.apply(config)?;
+
+    emit_cpi!(ProtocolConfigUpdated {
+        admin: config.admin,
+        max_management_fee_bps: config.max_management_fee_bps,
+        max_performance_fee_bps: config.max_performance_fee_bps,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Update Protocol Config
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateProtocolConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump = protocol_config.bump,
+        has_one = admin @ VaultError::Unauthorized,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+}
+
+/// Replace the template defaults and limits. Existing template vaults keep
+/// their current fees until their next `update_config`, which must comply.
+pub fn handle_update_protocol_config(
+    ctx: Context<UpdateProtocolConfig>,
+    params: ProtocolConfigParams,
+) -> Result<()> {
+    let config = &mut ctx.accounts.protocol_config;
+    params.apply(config)?;
+
+    emit_cpi!(ProtocolConfigUpdated {
+        admin: config.admin,
+        max_management_fee_bps: config.max_management_fee_bps,
+        max_performance_fee_bps: config.max_performance_fee_bps,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Create Vault From Template
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct CreateVaultFromTemplate<'info> {
+    /// The vault owner who is creating this vault
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The underlying token mint this vault accepts
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump = protocol_config.bump,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    /// The vault state account (PDA)
+    #[account(
+        init,
+        payer = owner,
+        space = VaultState::LEN,
+        seeds = [VAULT_SEED, mint.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Step 1 of vault initialization using the protocol defaults. The vault
+/// stays bound by the protocol fee limits for its lifetime. Bundle with
+/// `init_vault_accounts` to deploy in a single transaction.
+pub fn handle_create_vault_from_template(ctx: Context<CreateVaultFromTemplate>) -> Result<()> {
+    let config = &ctx.accounts.protocol_config;
+    let params = InitializeVaultParams {
+        deposit_cap: config.default_deposit_cap,
+        min_deposit: config.default_min_deposit,
+        management_fee_bps: config.default_management_fee_bps,
+        performance_fee_bps: config.default_performance_fee_bps,
+        share_decimals: None,
+        paper: false,
+    };
+
+    let mut vault = ctx.accounts.vault.load_init()?;
+    init_vault_state(
+        &mut vault,
+        ctx.accounts.owner.key(),
+        ctx.accounts.mint.key(),
+        ctx.accounts.mint.decimals,
+        ctx.bumps.vault,
+        &params,
+    )?;
+
+    vault.withdraw_delay_slots = config.default_withdraw_delay_slots;
+    vault.reserve_ratio_bps = config.default_reserve_ratio_bps;
+    vault.small_withdrawal_threshold = config.default_small_withdrawal_threshold;
+    vault.from_template = 1;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Init Strategy Whitelist
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct InitStrategyWhitelist<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump = protocol_config.bump,
+        has_one = admin @ VaultError::Unauthorized,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = StrategyWhitelist::LEN,
+        seeds = [STRATEGY_WHITELIST_SEED],
+        bump,
+    )]
+    pub strategy_whitelist: Box<Account<'info, StrategyWhitelist>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_init_strategy_whitelist(ctx: Context<InitStrategyWhitelist>) -> Result<()> {
+    let whitelist = &mut ctx.accounts.strategy_whitelist;
+    whitelist.strategy_ids = [Pubkey::default(); MAX_WHITELISTED_STRATEGIES];
+    whitelist.metadata = [StrategyMetadata::default(); MAX_WHITELISTED_STRATEGIES];
+    whitelist.bump = ctx.bumps.strategy_whitelist;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Strategy Whitelisted
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetStrategyWhitelisted<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump = protocol_config.bump,
+        has_one = admin @ VaultError::Unauthorized,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        seeds = [STRATEGY_WHITELIST_SEED],
+        bump = strategy_whitelist.bump,
+    )]
+    pub strategy_whitelist: Box<Account<'info, StrategyWhitelist>>,
+}
+
+/// Allow or disallow `strategy_id` for `add_strategy`. Strategies already
+/// registered with a vault are unaffected by removal.
+pub fn handle_set_strategy_whitelisted(
+    ctx: Context<SetStrategyWhitelisted>,
+    strategy_id: Pubkey,
+    allowed: bool,
+) -> Result<()> {
+    let whitelist = &mut ctx.accounts.strategy_whitelist;
+    if allowed {
+        whitelist.add(strategy_id)?;
+    } else {
+        whitelist.remove(&strategy_id);
+    }
+
+    emit_cpi!(StrategyWhitelistUpdated {
+        strategy_id,
+        allowed,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Strategy Metadata
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetStrategyMetadata<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump = protocol_config.bump,
+        has_one = admin @ VaultError::Unauthorized,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        seeds = [STRATEGY_WHITELIST_SEED],
+        bump = strategy_whitelist.bump,
+    )]
+    pub strategy_whitelist: Box<Account<'info, StrategyWhitelist>>,
+}
+
+/// Record the adapter version, audit status and risk score of a whitelisted
+/// `strategy_id`, e.g. after an upgrade or a new audit. Vaults check their
+/// strategy requirements against it on registration and every allocation.
+pub fn handle_set_strategy_metadata(
+    ctx: Context<SetStrategyMetadata>,
+    strategy_id: Pubkey,
+    metadata: StrategyMetadata,
+) -> Result<()> {
+    ctx.accounts
+        .strategy_whitelist
+        .set_metadata(&strategy_id, metadata)?;
+
+    emit_cpi!(StrategyMetadataSet {
+        strategy_id,
+        version_major: metadata.version.major,
+        version_minor: metadata.version.minor,
+        version_patch: metadata.version.patch,
+        audited: metadata.audited,
+        risk_score: metadata.risk_score,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Init Feed Registry
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct InitFeedRegistry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump = protocol_config.bump,
+        has_one = admin @ VaultError::Unauthorized,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = FeedRegistry::LEN,
+        seeds = [FEED_REGISTRY_SEED],
+        bump,
+    )]
+    pub feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_init_feed_registry(ctx: Context<InitFeedRegistry>) -> Result<()> {
+    let registry = &mut ctx.accounts.feed_registry;
+    registry.feeds = [PriceFeed::default(); MAX_PRICE_FEEDS];
+    registry.bump = ctx.bumps.feed_registry;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Init Global Stats
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct InitGlobalStats<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump = protocol_config.bump,
+        has_one = admin @ VaultError::Unauthorized,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = GlobalStats::LEN,
+        seeds = [GLOBAL_STATS_SEED],
+        bump,
+    )]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Vaults join the totals on their next deposit, withdrawal or NAV update,
+/// or when anyone calls `sync_global_stats` for them
+pub fn handle_init_global_stats(ctx: Context<InitGlobalStats>) -> Result<()> {
+    let stats = &mut ctx.accounts.global_stats;
+    stats.mints = [MintStats::default(); MAX_STATS_MINTS];
+    stats.vault_count = 0;
+    stats.updated_at = Clock::get()?.unix_timestamp;
+    stats.bump = ctx.bumps.global_stats;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Sync Global Stats
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct SyncGlobalStats<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATS_SEED],
+        bump = global_stats.bump,
+    )]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+}
+
+/// Push a vault's current assets and shares to the protocol totals. Permissionless, for
+/// vaults whose assets moved outside deposits, withdrawals and NAV updates.
+pub fn handle_sync_global_stats(ctx: Context<SyncGlobalStats>) -> Result<()> {
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    require!(vault.is_initialized(), VaultError::VaultNotInitialized);
+    ctx.accounts
+        .global_stats
+        .record(&mut vault, Clock::get()?.unix_timestamp)
+}
+
+// ──────────────────────────────────────────
+// Set Price Feed
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPriceFeed<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump = protocol_config.bump,
+        has_one = admin @ VaultError::Unauthorized,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        seeds = [FEED_REGISTRY_SEED],
+        bump = feed_registry.bump,
+    )]
+    pub feed_registry: Box<Account<'info, FeedRegistry>>,
+}
+
+/// Approve `oracle` for `mint` with a staleness limit, or remove the mint's
+/// feed when `oracle` is the default key. Vaults already using a replaced
+/// feed pick up the new one through `sync_price_feed`.
+pub fn handle_set_price_feed(
+    ctx: Context<SetPriceFeed>,
+    mint: Pubkey,
+    oracle: Pubkey,
+    max_staleness: i64,
+) -> Result<()> {
+    let registry = &mut ctx.accounts.feed_registry;
+    if oracle == Pubkey::default() {
+        registry.remove(&mint);
+    } else {
+        registry.set(PriceFeed {
+            mint,
+            oracle,
+            max_staleness,
+        })?;
+    }
+
+    emit_cpi!(PriceFeedUpdated {
+        mint,
+        oracle,
+        max_staleness,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Flag Wallet
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct FlagWallet<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump = protocol_config.bump,
+        has_one = admin @ VaultError::Unauthorized,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = WalletFlag::LEN,
+        seeds = [WALLET_FLAG_SEED, wallet.as_ref()],
+        bump,
+    )]
+    pub wallet_flag: Box<Account<'info, WalletFlag>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Block `wallet` from depositing into any vault, or change why it is
+/// blocked. Shares it already holds are unaffected.
+pub fn handle_flag_wallet(
+    ctx: Context<FlagWallet>,
+    wallet: Pubkey,
+    flag: ComplianceFlag,
+) -> Result<()> {
+    let wallet_flag = &mut ctx.accounts.wallet_flag;
+    wallet_flag.wallet = wallet;
+    wallet_flag.flag = flag;
+    wallet_flag.flagged_at = Clock::get()?.unix_timestamp;
+    wallet_flag.bump = ctx.bumps.wallet_flag;
+
+    emit_cpi!(WalletFlagged { wallet, flag });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Unflag Wallet
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UnflagWallet<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump = protocol_config.bump,
+        has_one = admin @ VaultError::Unauthorized,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [WALLET_FLAG_SEED, wallet_flag.wallet.as_ref()],
+        bump = wallet_flag.bump,
+    )]
+    pub wallet_flag: Box<Account<'info, WalletFlag>>,
+}
+
+/// Clear a wallet's flag, returning the rent to the admin
+pub fn handle_unflag_wallet(ctx: Context<UnflagWallet>) -> Result<()> {
+    emit_cpi!(WalletUnflagged {
+        wallet: ctx.accounts.wallet_flag.wallet,
+    });
+    Ok(())
+}
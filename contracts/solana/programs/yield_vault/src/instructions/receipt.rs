@@ -0,0 +1,397 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::ReceiptMinted;
+use crate::instructions::deposit::{self, *};
+use crate::instructions::withdraw::{self, *};
+use crate::state::{ReceiptKind, ReceiptTree, VaultState};
+use crate::utils::{share_price_q64, EventCpi};
+
+/// Anchor discriminator of Bubblegum's `create_tree`
+const CREATE_TREE_DISCRIMINATOR: [u8; 8] = [165, 83, 136, 142, 89, 202, 47, 220];
+
+/// Anchor discriminator of Bubblegum's `mint_v1`
+const MINT_V1_DISCRIMINATOR: [u8; 8] = [145, 98, 192, 118, 184, 147, 118, 104];
+
+const RECEIPT_SYMBOL: &str = "VRCPT";
+
+/// Bubblegum's `Creator`
+#[derive(AnchorSerialize)]
+struct Creator {
+    address: Pubkey,
+    verified: bool,
+    share: u8,
+}
+
+/// Bubblegum's `MetadataArgs`, in its borsh layout
+#[derive(AnchorSerialize)]
+struct MetadataArgs {
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    primary_sale_happened: bool,
+    is_mutable: bool,
+    edition_nonce: Option<u8>,
+    /// `TokenStandard` discriminant
+    token_standard: Option<u8>,
+    /// Receipts have no collection or uses; `None` encodes the same for any
+    /// payload type
+    collection: Option<u8>,
+    uses: Option<u8>,
+    /// `TokenProgramVersion` discriminant
+    token_program_version: u8,
+    creators: Vec<Creator>,
+}
+
+// ──────────────────────────────────────────
+// Init Receipt Tree
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct InitReceiptTree<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = ReceiptTree::LEN,
+        seeds = [RECEIPT_TREE_SEED, vault.key().as_ref()],
+        bump,
+    )]
+    pub receipt_tree: Box<Account<'info, ReceiptTree>>,
+
+    /// Bubblegum's config for the tree, created here
+    /// CHECK: address checked by seeds
+    #[account(
+        mut,
+        seeds = [merkle_tree.key().as_ref()],
+        bump,
+        seeds::program = BUBBLEGUM_ID,
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// Empty tree account, allocated earlier in the transaction and owned by
+    /// the compression program
+    /// CHECK: ownership is checked here; the compression program checks the size
+    #[account(mut, owner = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: address is checked
+    #[account(address = SPL_NOOP_ID)]
+    pub noop: UncheckedAccount<'info>,
+
+    /// CHECK: address is checked
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: address is checked
+    #[account(address = BUBBLEGUM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Create the vault's receipt tree as a private Bubblegum tree of `max_depth`
+/// (2^max_depth receipts) accepting `max_buffer_size` concurrent mints.
+/// Receipt URIs start with `base_uri`.
+pub fn handle_init_receipt_tree(
+    ctx: Context<InitReceiptTree>,
+    max_depth: u32,
+    max_buffer_size: u32,
+    base_uri: String,
+) -> Result<()> {
+    require!(
+        base_uri.len() <= MAX_RECEIPT_BASE_URI_LEN,
+        VaultError::InvalidConfig
+    );
+
+    let vault_key = ctx.accounts.vault.key();
+    let bump = [ctx.bumps.receipt_tree];
+    let seeds: &[&[u8]] = &[RECEIPT_TREE_SEED, vault_key.as_ref(), &bump];
+
+    let mut data = CREATE_TREE_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&max_depth.to_le_bytes());
+    data.extend_from_slice(&max_buffer_size.to_le_bytes());
+    // `public: Some(false)`: only the tree creator may mint
+    data.extend_from_slice(&[1, 0]);
+    invoke_signed(
+        &Instruction {
+            program_id: BUBBLEGUM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.tree_config.key(), false),
+                AccountMeta::new(ctx.accounts.merkle_tree.key(), false),
+                AccountMeta::new(ctx.accounts.owner.key(), true),
+                AccountMeta::new_readonly(ctx.accounts.receipt_tree.key(), true),
+                AccountMeta::new_readonly(SPL_NOOP_ID, false),
+                AccountMeta::new_readonly(SPL_ACCOUNT_COMPRESSION_ID, false),
+                AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+            ],
+            data,
+        },
+        &[
+            ctx.accounts.tree_config.to_account_info(),
+            ctx.accounts.merkle_tree.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.receipt_tree.to_account_info(),
+            ctx.accounts.noop.to_account_info(),
+            ctx.accounts.compression_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.bubblegum_program.to_account_info(),
+        ],
+        &[seeds],
+    )?;
+
+    let receipt_tree = &mut ctx.accounts.receipt_tree;
+    receipt_tree.vault = vault_key;
+    receipt_tree.merkle_tree = ctx.accounts.merkle_tree.key();
+    receipt_tree.base_uri = base_uri;
+    receipt_tree.minted = 0;
+    receipt_tree.bump = ctx.bumps.receipt_tree;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Receipt Mint
+// ──────────────────────────────────────────
+
+/// The vault's receipt tree and the programs minting into it
+#[derive(Accounts)]
+pub struct ReceiptMint<'info> {
+    #[account(
+        mut,
+        seeds = [RECEIPT_TREE_SEED, receipt_tree.vault.as_ref()],
+        bump = receipt_tree.bump,
+        has_one = merkle_tree,
+    )]
+    pub receipt_tree: Box<Account<'info, ReceiptTree>>,
+
+    /// CHECK: address checked by seeds
+    #[account(
+        mut,
+        seeds = [merkle_tree.key().as_ref()],
+        bump,
+        seeds::program = BUBBLEGUM_ID,
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: bound to the receipt tree by `has_one`
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: address is checked
+    #[account(address = SPL_NOOP_ID)]
+    pub noop: UncheckedAccount<'info>,
+
+    /// CHECK: address is checked
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: address is checked
+    #[account(address = BUBBLEGUM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ReceiptMint<'info> {
+    /// Mint `user` a receipt for a flow of `amount` underlying against
+    /// `shares`, paid for by the user. The metadata URI carries the terms.
+    fn mint(
+        &mut self,
+        vault: &AccountLoader<'info, VaultState>,
+        user: &AccountInfo<'info>,
+        kind: ReceiptKind,
+        amount: u64,
+        shares: u64,
+        events: EventCpi,
+    ) -> Result<()> {
+        let vault_key = vault.key();
+        require_keys_eq!(self.receipt_tree.vault, vault_key, VaultError::InvalidReceiptTree);
+
+        let share_price_q64 = share_price_q64(amount, shares)?;
+        let timestamp = Clock::get()?.unix_timestamp;
+        let name = match kind {
+            ReceiptKind::Deposit => "Vault Deposit Receipt",
+            ReceiptKind::Withdrawal => "Vault Withdrawal Receipt",
+        };
+        let receipt_tree_key = self.receipt_tree.key();
+        let metadata = MetadataArgs {
+            name: name.to_string(),
+            symbol: RECEIPT_SYMBOL.to_string(),
+            uri: format!(
+                "{}?amount={amount}&shares={shares}&price_q64={share_price_q64}&ts={timestamp}",
+                self.receipt_tree.base_uri
+            ),
+            seller_fee_basis_points: 0,
+            primary_sale_happened: true,
+            is_mutable: false,
+            edition_nonce: None,
+            // NonFungible
+            token_standard: Some(0),
+            collection: None,
+            uses: None,
+            // Original
+            token_program_version: 0,
+            creators: vec![Creator {
+                address: receipt_tree_key,
+                verified: true,
+                share: 100,
+            }],
+        };
+        let mut data = MINT_V1_DISCRIMINATOR.to_vec();
+        metadata.serialize(&mut data)?;
+
+        let bump = [self.receipt_tree.bump];
+        let seeds: &[&[u8]] = &[RECEIPT_TREE_SEED, vault_key.as_ref(), &bump];
+        invoke_signed(
+            &Instruction {
+                program_id: BUBBLEGUM_ID,
+                accounts: vec![
+                    AccountMeta::new(self.tree_config.key(), false),
+                    AccountMeta::new_readonly(user.key(), false),
+                    AccountMeta::new_readonly(user.key(), false),
+                    AccountMeta::new(self.merkle_tree.key(), false),
+                    AccountMeta::new(user.key(), true),
+                    AccountMeta::new_readonly(receipt_tree_key, true),
+                    AccountMeta::new_readonly(SPL_NOOP_ID, false),
+                    AccountMeta::new_readonly(SPL_ACCOUNT_COMPRESSION_ID, false),
+                    AccountMeta::new_readonly(self.system_program.key(), false),
+                ],
+                data,
+            },
+            &[
+                self.tree_config.to_account_info(),
+                user.clone(),
+                self.merkle_tree.to_account_info(),
+                self.receipt_tree.to_account_info(),
+                self.noop.to_account_info(),
+                self.compression_program.to_account_info(),
+                self.system_program.to_account_info(),
+                self.bubblegum_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        let leaf_index = self.receipt_tree.minted;
+        self.receipt_tree.minted = leaf_index
+            .checked_add(1)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+
+        events.emit(ReceiptMinted {
+            vault: vault_key,
+            event_seq: vault.load_mut()?.next_event_seq(),
+            owner: user.key(),
+            kind,
+            merkle_tree: self.merkle_tree.key(),
+            leaf_index,
+            amount,
+            shares,
+            share_price_q64,
+            timestamp,
+        })
+    }
+}
+
+// ──────────────────────────────────────────
+// Deposit With Receipt
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DepositWithReceipt<'info> {
+    /// The regular deposit accounts; `deposit.user` pays for the receipt
+    pub deposit: Deposit<'info>,
+
+    pub receipt: ReceiptMint<'info>,
+}
+
+/// Deposit as `deposit` does, then mint the user a compressed NFT receipt of
+/// the amount, shares and share price
+pub fn handle_deposit_with_receipt(
+    ctx: Context<DepositWithReceipt>,
+    amount: u64,
+    memo: Option<[u8; 32]>,
+) -> Result<()> {
+    // A receipt for simulated shares would prove terms nobody paid
+    require!(
+        !ctx.accounts.deposit.vault.load()?.is_paper(),
+        VaultError::PaperVault
+    );
+
+    let shares = deposit::handler(
+        Context::new(ctx.program_id, &mut ctx.accounts.deposit, &[], ctx.bumps.deposit),
+        amount,
+        memo,
+    )?;
+
+    ctx.accounts.receipt.mint(
+        &ctx.accounts.deposit.vault,
+        &ctx.accounts.deposit.user.to_account_info(),
+        ReceiptKind::Deposit,
+        amount,
+        shares,
+        EventCpi {
+            authority: &ctx.accounts.event_authority,
+            bump: ctx.bumps.event_authority,
+        },
+    )
+}
+
+// ──────────────────────────────────────────
+// Withdraw With Receipt
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawWithReceipt<'info> {
+    /// The regular withdraw accounts; `withdraw.user` pays for the receipt
+    pub withdraw: Withdraw<'info>,
+
+    pub receipt: ReceiptMint<'info>,
+}
+
+/// Withdraw as `withdraw` does, then mint the user a compressed NFT receipt
+/// of the assets paid, shares burned and share price
+pub fn handle_withdraw_with_receipt(
+    ctx: Context<WithdrawWithReceipt>,
+    shares: u64,
+    max_loss_bps: u16,
+    memo: Option<[u8; 32]>,
+) -> Result<()> {
+    require!(
+        !ctx.accounts.withdraw.vault.load()?.is_paper(),
+        VaultError::PaperVault
+    );
+
+    let amount = withdraw::handler(
+        Context::new(ctx.program_id, &mut ctx.accounts.withdraw, &[], ctx.bumps.withdraw),
+        shares,
+        max_loss_bps,
+        memo,
+    )?;
+
+    ctx.accounts.receipt.mint(
+        &ctx.accounts.withdraw.vault,
+        &ctx.accounts.withdraw.user.to_account_info(),
+        ReceiptKind::Withdrawal,
+        amount,
+        shares,
+        EventCpi {
+            authority: &ctx.accounts.event_authority,
+            bump: ctx.bumps.event_authority,
+        },
+    )
+}
@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::TokensRescued;
+use crate::instructions::audit_log::record_audit;
+use crate::state::{AuditAction, AuditLog, VaultState};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RescueTokens<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    /// The vault's share mint — its tokens can never be rescued
+    #[account(
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    /// The vault's associated token account for a foreign mint. Program PDA
+    /// token accounts, which hold tokens owed to share holders (rewards,
+    /// airdrops), are never accepted.
+    #[account(
+        mut,
+        token::authority = vault,
+        constraint = source_token_account.mint != vault.load()?.mint @ VaultError::CannotRescueVaultToken,
+        constraint = source_token_account.mint != share_mint.key() @ VaultError::CannotRescueVaultToken,
+        constraint = source_token_account.key()
+            == get_associated_token_address(&vault.key(), &source_token_account.mint)
+            @ VaultError::CannotRescueHolderTokens,
+    )]
+    pub source_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Where the rescued tokens are sent
+    #[account(
+        mut,
+        token::mint = source_token_account.mint,
+    )]
+    pub destination_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Return tokens mistakenly sent to the vault's associated token accounts.
+/// The underlying asset, the share mint and the program's own token accounts
+/// are excluded so depositor funds can never be moved this way.
+pub fn handler(ctx: Context<RescueTokens>, amount: u64) -> Result<()> {
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    let vault_key = ctx.accounts.vault.key();
+    let vault = ctx.accounts.vault.load()?;
+    let seeds = vault.signer_seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.source_token_account.to_account_info(),
+                to: ctx.accounts.destination_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+    drop(vault);
+
+    emit_cpi!(TokensRescued {
+        vault: vault_key,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        mint: ctx.accounts.source_token_account.mint,
+        destination: ctx.accounts.destination_token_account.key(),
+        amount,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::RescueTokens,
+        &[
+            ctx.accounts.source_token_account.mint.as_ref(),
+            ctx.accounts.destination_token_account.key().as_ref(),
+            &amount.to_le_bytes(),
+        ],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,176 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{ReservesProven, ReservesVerifierSet};
+use crate::instructions::audit_log::record_audit;
+use crate::state::{
+    AuditAction, AuditLog, Groth16Proof, Groth16VerifyingKey, ReservesVerifier, VaultState,
+};
+use crate::utils::verify_groth16;
+
+// ──────────────────────────────────────────
+// Set Reserves Verifier
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetReservesVerifier<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = ReservesVerifier::LEN,
+        seeds = [RESERVES_VERIFIER_SEED, vault.key().as_ref()],
+        bump,
+    )]
+    pub reserves_verifier: Box<Account<'info, ReservesVerifier>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Install the proof-of-reserves circuit's verifying key. With `enforce`,
+/// `update_nav` rejects increases above `max_unproven_increase_bps` unless a
+/// proof younger than `max_proof_age` covers them. A new key discards any
+/// proof verified under the previous one.
+pub fn handle_set_reserves_verifier(
+    ctx: Context<SetReservesVerifier>,
+    verifying_key: Groth16VerifyingKey,
+    max_unproven_increase_bps: u16,
+    max_proof_age: i64,
+    enforce: bool,
+) -> Result<()> {
+    require!(max_proof_age > 0, VaultError::InvalidConfig);
+
+    let vault_key = ctx.accounts.vault.key();
+    let verifier = &mut ctx.accounts.reserves_verifier;
+    if verifier.verifying_key != verifying_key {
+        verifier.proven_reserves = 0;
+        verifier.proven_at = 0;
+    }
+    verifier.vault = vault_key;
+    verifier.verifying_key = verifying_key;
+    verifier.max_unproven_increase_bps = max_unproven_increase_bps;
+    verifier.max_proof_age = max_proof_age;
+    verifier.bump = ctx.bumps.reserves_verifier;
+
+    ctx.accounts.vault.load_mut()?.reserves_proof_enforced = enforce as u8;
+
+    emit_cpi!(ReservesVerifierSet {
+        vault: vault_key,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        max_unproven_increase_bps,
+        max_proof_age,
+        enforced: enforce,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetReservesVerifier,
+        &[
+            &verifying_key.try_to_vec()?,
+            &max_unproven_increase_bps.to_le_bytes(),
+            &max_proof_age.to_le_bytes(),
+            &[enforce as u8],
+        ],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Prove Reserves
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProveReserves<'info> {
+    /// The manager or owner submitting the proof
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [RESERVES_VERIFIER_SEED, vault.key().as_ref()],
+        bump = reserves_verifier.bump,
+        has_one = vault,
+    )]
+    pub reserves_verifier: Box<Account<'info, ReservesVerifier>>,
+}
+
+/// Verify a proof that strategy positions held at least `reserves` at
+/// `observed_at` and record it for `update_nav`. Proofs must be newer than
+/// the one already recorded.
+pub fn handle_prove_reserves(
+    ctx: Context<ProveReserves>,
+    proof: Groth16Proof,
+    reserves: u64,
+    observed_at: i64,
+) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    require!(
+        vault.is_authority(&ctx.accounts.authority.key()),
+        VaultError::Unauthorized
+    );
+
+    let verifier = &mut ctx.accounts.reserves_verifier;
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        observed_at > verifier.proven_at && observed_at <= now,
+        VaultError::InvalidReservesProof
+    );
+
+    // Clearing the top byte keeps the vault address below the scalar modulus
+    let mut vault_input = vault_key.to_bytes();
+    vault_input[0] = 0;
+    let mut reserves_input = [0u8; 32];
+    reserves_input[24..].copy_from_slice(&reserves.to_be_bytes());
+    let mut observed_at_input = [0u8; 32];
+    observed_at_input[24..].copy_from_slice(&observed_at.to_be_bytes());
+    let public_inputs: [[u8; 32]; RESERVES_PUBLIC_INPUTS] =
+        [vault_input, reserves_input, observed_at_input];
+
+    require!(
+        verify_groth16(&verifier.verifying_key, &proof, &public_inputs)?,
+        VaultError::InvalidReservesProof
+    );
+
+    verifier.proven_reserves = reserves;
+    verifier.proven_at = observed_at;
+
+    emit_cpi!(ReservesProven {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
+        reserves,
+        observed_at,
+    });
+
+    Ok(())
+}
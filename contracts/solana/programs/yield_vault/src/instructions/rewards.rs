@@ -0,0 +1,396 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{RewardsClaimed, RewardsDefunded, RewardsFunded};
+use crate::instructions::audit_log::record_audit;
+use crate::state::{AuditAction, AuditLog, RewardsPool, UserPosition, VaultState};
+
+// ──────────────────────────────────────────
+// Init Rewards
+// ──────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct InitRewards<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    /// Token paid out as rewards
+    pub reward_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = RewardsPool::LEN,
+        seeds = [REWARDS_SEED, vault.key().as_ref()],
+        bump,
+    )]
+    pub rewards: Box<Account<'info, RewardsPool>>,
+
+    /// Vault-owned token account rewards are paid from
+    #[account(
+        init,
+        payer = owner,
+        seeds = [REWARDS_TOKEN_SEED, vault.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = vault,
+    )]
+    pub rewards_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_init_rewards(ctx: Context<InitRewards>) -> Result<()> {
+    let rewards = &mut ctx.accounts.rewards;
+    rewards.vault = ctx.accounts.vault.key();
+    rewards.reward_mint = ctx.accounts.reward_mint.key();
+    rewards.reward_rate = 0;
+    rewards.reward_per_share = 0;
+    rewards.last_update = Clock::get()?.unix_timestamp;
+    rewards.end_time = rewards.last_update;
+    rewards.total_claimed = 0;
+    rewards.total_emitted = 0;
+    rewards.bump = ctx.bumps.rewards;
+    rewards.token_account_bump = ctx.bumps.rewards_token_account;
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::InitRewards,
+        &[ctx.accounts.reward_mint.key().as_ref()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Fund Rewards
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        seeds = [REWARDS_SEED, vault.key().as_ref()],
+        bump = rewards.bump,
+        has_one = vault,
+    )]
+    pub rewards: Box<Account<'info, RewardsPool>>,
+
+    #[account(
+        mut,
+        seeds = [REWARDS_TOKEN_SEED, vault.key().as_ref()],
+        bump = rewards.token_account_bump,
+    )]
+    pub rewards_token_account: Account<'info, TokenAccount>,
+
+    /// The owner's reward token account funding the campaign
+    #[account(
+        mut,
+        token::mint = rewards.reward_mint,
+        token::authority = owner,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Add `amount` reward tokens and emit them, together with any not yet
+/// emitted by the running campaign, evenly over the next `duration` seconds.
+pub fn handle_fund_rewards(ctx: Context<FundRewards>, amount: u64, duration: i64) -> Result<()> {
+    require!(amount > 0, VaultError::InvalidAmount);
+    require!(duration > 0, VaultError::InvalidConfig);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.funder_token_account.to_account_info(),
+                to: ctx.accounts.rewards_token_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let total_shares = ctx.accounts.vault.load()?.total_shares;
+    let rewards = &mut ctx.accounts.rewards;
+    rewards.accrue(total_shares, now)?;
+
+    let to_emit = rewards
+        .remaining_emissions(now)?
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    rewards.reward_rate = to_emit / duration as u64;
+    require!(rewards.reward_rate > 0, VaultError::InvalidAmount);
+    rewards.last_update = now;
+    rewards.end_time = now
+        .checked_add(duration)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    emit_cpi!(RewardsFunded {
+        vault: ctx.accounts.vault.key(),
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        amount,
+        reward_rate: rewards.reward_rate,
+        end_time: rewards.end_time,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::FundRewards,
+        &[&amount.to_le_bytes(), &duration.to_le_bytes()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Defund Rewards
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DefundRewards<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        seeds = [REWARDS_SEED, vault.key().as_ref()],
+        bump = rewards.bump,
+        has_one = vault,
+    )]
+    pub rewards: Box<Account<'info, RewardsPool>>,
+
+    #[account(
+        mut,
+        seeds = [REWARDS_TOKEN_SEED, vault.key().as_ref()],
+        bump = rewards.token_account_bump,
+    )]
+    pub rewards_token_account: Account<'info, TokenAccount>,
+
+    /// Receives the unemitted reward tokens
+    #[account(
+        mut,
+        token::mint = rewards.reward_mint,
+    )]
+    pub owner_reward_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// End the running campaign now and take back the reward tokens not yet
+/// emitted. Rewards already emitted stay claimable by share holders. Owner
+/// only.
+pub fn handle_defund_rewards(ctx: Context<DefundRewards>) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let vault = ctx.accounts.vault.load()?;
+    let now = Clock::get()?.unix_timestamp;
+    let rewards = &mut ctx.accounts.rewards;
+    rewards.accrue(vault.total_shares, now)?;
+    rewards.end_time = rewards.end_time.min(now);
+
+    let amount = ctx
+        .accounts
+        .rewards_token_account
+        .amount
+        .saturating_sub(rewards.unclaimed());
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    let seeds = vault.signer_seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.rewards_token_account.to_account_info(),
+                to: ctx.accounts.owner_reward_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+    drop(vault);
+
+    emit_cpi!(RewardsDefunded {
+        vault: vault_key,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        amount,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::DefundRewards,
+        &[&amount.to_le_bytes()],
+    )?;
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Claim Rewards
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [REWARDS_SEED, vault.key().as_ref()],
+        bump = rewards.bump,
+        has_one = vault,
+    )]
+    pub rewards: Box<Account<'info, RewardsPool>>,
+
+    #[account(
+        mut,
+        seeds = [REWARDS_TOKEN_SEED, vault.key().as_ref()],
+        bump = rewards.token_account_bump,
+    )]
+    pub rewards_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    /// The user's share token account — its balance earns from now on
+    #[account(
+        token::mint = share_mint,
+        token::authority = user,
+    )]
+    pub user_share_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Box<Account<'info, UserPosition>>,
+
+    /// Receives the claimed reward tokens
+    #[account(
+        mut,
+        token::mint = rewards.reward_mint,
+    )]
+    pub user_reward_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Pay out the user's earned rewards and checkpoint their current share
+/// balance, so shares received by transfer start earning.
+pub fn handle_claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let vault = ctx.accounts.vault.load()?;
+
+    let rewards = &mut ctx.accounts.rewards;
+    rewards.accrue(vault.total_shares, Clock::get()?.unix_timestamp)?;
+
+    let balance = ctx.accounts.user_share_account.amount;
+    let position = &mut ctx.accounts.position;
+    position.checkpoint_rewards(rewards, balance, balance)?;
+
+    let amount = position.pending_rewards;
+    require!(amount > 0, VaultError::NoRewardsToClaim);
+    position.pending_rewards = 0;
+    rewards.total_claimed = rewards
+        .total_claimed
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    let seeds = vault.signer_seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.rewards_token_account.to_account_info(),
+                to: ctx.accounts.user_reward_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+    drop(vault);
+
+    emit_cpi!(RewardsClaimed {
+        vault: vault_key,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        user: ctx.accounts.user.key(),
+        amount,
+    });
+
+    Ok(())
+}
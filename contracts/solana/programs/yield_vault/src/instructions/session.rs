@@ -0,0 +1,312 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as ix_sysvar;
+use anchor_spl::token::{self, Approve, Mint, Revoke, Token, TokenAccount};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{SessionCreated, SessionRevoked};
+use crate::instructions::withdraw::ShareRedemption;
+use crate::state::{RewardsPool, Session, UserPosition, VaultState};
+use crate::utils::EventCpi;
+
+// ──────────────────────────────────────────
+// Create Session
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct CreateSession<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    /// The user's share account; the session PDA is approved as its delegate
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = user,
+    )]
+    pub user_share_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = user,
+        space = Session::LEN,
+        seeds = [SESSION_SEED, vault.key().as_ref(), user.key().as_ref(), session_key.as_ref()],
+        bump,
+    )]
+    pub session: Box<Account<'info, Session>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Let `session_key` withdraw up to `max_amount` of underlying to the user's
+/// own token account until `expires_at`. A token account has a single
+/// delegate, so this replaces any earlier session or approval on it.
+pub fn handle_create_session(
+    ctx: Context<CreateSession>,
+    session_key: Pubkey,
+    max_amount: u64,
+    expires_at: i64,
+) -> Result<()> {
+    require!(max_amount > 0, VaultError::InvalidAmount);
+    require!(
+        expires_at > Clock::get()?.unix_timestamp,
+        VaultError::SessionExpired
+    );
+
+    // The allowance is enforced in assets by the program, so the delegate may
+    // burn any number of shares
+    token::approve(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Approve {
+                to: ctx.accounts.user_share_account.to_account_info(),
+                delegate: ctx.accounts.session.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        u64::MAX,
+    )?;
+
+    let session = &mut ctx.accounts.session;
+    session.vault = ctx.accounts.vault.key();
+    session.user = ctx.accounts.user.key();
+    session.session_key = session_key;
+    session.max_amount = max_amount;
+    session.spent = 0;
+    session.expires_at = expires_at;
+    session.bump = ctx.bumps.session;
+
+    emit_cpi!(SessionCreated {
+        vault: session.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        user: session.user,
+        session_key,
+        max_amount,
+        expires_at,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Withdraw With Session
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawWithSession<'info> {
+    /// The hot key the session was granted to
+    pub session_key: Signer<'info>,
+
+    /// The position owner
+    /// CHECK: bound to the session by `has_one`
+    pub user: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
+        bump = vault.load()?.token_account_bump,
+        token::mint = vault.load()?.mint,
+        token::authority = vault,
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+        mint::authority = vault,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    /// Session withdrawals always pay the user's own account
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+        token::authority = user,
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = user,
+    )]
+    pub user_share_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Box<Account<'info, UserPosition>>,
+
+    #[account(
+        mut,
+        seeds = [REWARDS_SEED, vault.key().as_ref()],
+        bump = rewards.bump,
+        has_one = vault,
+    )]
+    pub rewards: Option<Box<Account<'info, RewardsPool>>>,
+
+    #[account(
+        mut,
+        seeds = [
+            SESSION_SEED,
+            vault.key().as_ref(),
+            user.key().as_ref(),
+            session_key.key().as_ref(),
+        ],
+        bump = session.bump,
+        has_one = vault,
+        has_one = user,
+        has_one = session_key,
+    )]
+    pub session: Box<Account<'info, Session>>,
+
+    /// Instructions sysvar, used to reject flows bundled with a NAV update
+    /// CHECK: address is checked against the sysvar id
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle_withdraw_with_session(
+    ctx: Context<WithdrawWithSession>,
+    shares: u64,
+    max_loss_bps: u16,
+    memo: Option<[u8; 32]>,
+) -> Result<()> {
+    require!(
+        Clock::get()?.unix_timestamp < ctx.accounts.session.expires_at,
+        VaultError::SessionExpired
+    );
+
+    let vault_key = ctx.accounts.vault.key();
+    let user_key = ctx.accounts.user.key();
+    let session_key = ctx.accounts.session_key.key();
+    let bump = [ctx.accounts.session.bump];
+    let seeds: &[&[u8]] = &[
+        SESSION_SEED,
+        vault_key.as_ref(),
+        user_key.as_ref(),
+        session_key.as_ref(),
+        &bump,
+    ];
+    let position_bump = ctx.accounts.position.bump;
+
+    let assets = ShareRedemption {
+        user: user_key,
+        vault: &ctx.accounts.vault,
+        vault_token_account: &ctx.accounts.vault_token_account,
+        share_mint: &ctx.accounts.share_mint,
+        user_share_account: &ctx.accounts.user_share_account,
+        recipient: ctx.accounts.user_token_account.to_account_info(),
+        position: &mut ctx.accounts.position,
+        position_bump,
+        rewards: ctx.accounts.rewards.as_deref_mut(),
+        instructions: &ctx.accounts.instructions,
+        exit_fee_balance: None,
+        token_program: &ctx.accounts.token_program,
+        burn_authority: ctx.accounts.session.to_account_info(),
+        events: EventCpi {
+            authority: &ctx.accounts.event_authority,
+            bump: ctx.bumps.event_authority,
+        },
+    }
+    .redeem(shares, max_loss_bps, memo, &[seeds])?;
+
+    ctx.accounts.session.spend(assets)
+}
+
+// ──────────────────────────────────────────
+// Revoke Session
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevokeSession<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The session's vault, which numbers the event
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        token::authority = user,
+    )]
+    pub user_share_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [
+            SESSION_SEED,
+            session.vault.as_ref(),
+            user.key().as_ref(),
+            session.session_key.as_ref(),
+        ],
+        bump = session.bump,
+        has_one = vault,
+        has_one = user,
+    )]
+    pub session: Box<Account<'info, Session>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Close the session and, if it is still the share account's delegate,
+/// revoke the delegation
+pub fn handle_revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+    let session_pda = ctx.accounts.session.key();
+    if ctx.accounts.user_share_account.delegate == Some(session_pda).into() {
+        token::revoke(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Revoke {
+                source: ctx.accounts.user_share_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ))?;
+    }
+
+    let session = &ctx.accounts.session;
+    emit_cpi!(SessionRevoked {
+        vault: session.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        user: session.user,
+        session_key: session.session_key,
+        spent: session.spent,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::Skimmed;
+use crate::state::VaultState;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Skim<'info> {
+    /// Anyone may reconcile the vault's balance
+    pub caller: Signer<'info>,
+
+    /// The vault state
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's token account
+    #[account(
+        mut,
+        seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
+        bump = vault.load()?.token_account_bump,
+        token::mint = vault.load()?.mint,
+        token::authority = vault,
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// The owner's treasury token account (receives swept dust)
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+        token::authority = vault.load()?.owner,
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Reconcile the vault token balance, less assets reserved for queued epoch
+/// flows, with its tracked idle assets (`total_assets - total_debt - external_nav`).
+///
+/// While shares are outstanding, any untracked surplus (e.g. a raw transfer to
+/// the PDA) is absorbed into NAV for existing holders. With no shares
+/// outstanding nobody can be credited, so the whole balance — surplus plus any
+/// rounding dust left in `total_assets` — is swept to the owner's treasury.
+pub fn handler(ctx: Context<Skim>) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let vault = ctx.accounts.vault.load()?;
+    require!(!vault.is_paper(), VaultError::PaperVault);
+    let balance = vault.liquid_assets(ctx.accounts.vault_token_account.amount);
+    let tracked_idle = vault.idle_assets();
+    let surplus = balance.saturating_sub(tracked_idle);
+
+    if vault.total_shares > 0 {
+        require!(surplus > 0, VaultError::NothingToSkim);
+
+        drop(vault);
+        let mut vault = ctx.accounts.vault.load_mut()?;
+        vault.total_assets = vault
+            .total_assets
+            .checked_add(surplus)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+
+        emit_cpi!(Skimmed {
+            vault: vault_key,
+            event_seq: vault.next_event_seq(),
+            amount: surplus,
+            absorbed: true,
+            total_assets: vault.total_assets,
+        });
+
+        return Ok(());
+    }
+
+    require!(vault.total_debt == 0, VaultError::StrategyHasDebt);
+    require!(vault.external_nav == 0, VaultError::ExternalNavOutstanding);
+    require!(balance > 0, VaultError::NothingToSkim);
+
+    let seeds = vault.signer_seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        balance,
+    )?;
+
+    drop(vault);
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.total_assets = 0;
+
+    emit_cpi!(Skimmed {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
+        amount: balance,
+        absorbed: false,
+        total_assets: 0,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,280 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as ix_sysvar;
+use anchor_spl::token::{self, Approve, Mint, Revoke, Token, TokenAccount};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{StopLossExecuted, StopLossSet};
+use crate::instructions::withdraw::ShareRedemption;
+use crate::state::{RewardsPool, UserPosition, VaultState};
+use crate::utils::{share_price_q64, EventCpi};
+
+// ──────────────────────────────────────────
+// Set Stop-Loss
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetStopLoss<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    /// The user's share account; the position PDA is approved as its delegate
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = user,
+    )]
+    pub user_share_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Box<Account<'info, UserPosition>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Exit the whole position once the share price falls `stop_loss_bps` below
+/// the current price, paying `bounty_bps` of the exit to whoever executes it.
+/// `stop_loss_bps = 0` clears the rule. A token account has a single
+/// delegate, so this replaces any session or intent on the share account.
+pub fn handle_set_stop_loss(
+    ctx: Context<SetStopLoss>,
+    stop_loss_bps: u16,
+    bounty_bps: u16,
+) -> Result<()> {
+    require!(
+        (stop_loss_bps as u128) < BPS_DENOMINATOR,
+        VaultError::InvalidConfig
+    );
+    require!(bounty_bps <= MAX_STOP_LOSS_BOUNTY_BPS, VaultError::InvalidConfig);
+
+    let reference_price_q64 = if stop_loss_bps > 0 {
+        let vault = ctx.accounts.vault.load()?;
+        share_price_q64(vault.total_assets, vault.total_shares)?
+    } else {
+        0
+    };
+
+    let position_pda = ctx.accounts.position.key();
+    if stop_loss_bps > 0 {
+        // The program only exercises the approval when the rule triggers
+        token::approve(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Approve {
+                    to: ctx.accounts.user_share_account.to_account_info(),
+                    delegate: ctx.accounts.position.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            u64::MAX,
+        )?;
+    } else if ctx.accounts.user_share_account.delegate == Some(position_pda).into() {
+        token::revoke(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Revoke {
+                source: ctx.accounts.user_share_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ))?;
+    }
+
+    let position = &mut ctx.accounts.position;
+    position.stop_loss_ref_price_q64 = reference_price_q64;
+    position.stop_loss_bps = stop_loss_bps;
+    position.stop_loss_bounty_bps = bounty_bps;
+
+    emit_cpi!(StopLossSet {
+        vault: position.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        user: position.user,
+        reference_price_q64,
+        stop_loss_bps,
+        bounty_bps,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Execute Stop-Loss
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteStopLoss<'info> {
+    /// Anyone may execute a triggered stop-loss
+    pub crank: Signer<'info>,
+
+    /// The position owner
+    /// CHECK: bound to the position by `has_one`
+    pub user: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
+        bump = vault.load()?.token_account_bump,
+        token::mint = vault.load()?.mint,
+        token::authority = vault,
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+        mint::authority = vault,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    /// Stop-loss exits always pay the user's own account
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+        token::authority = user,
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = user,
+    )]
+    pub user_share_account: Account<'info, TokenAccount>,
+
+    /// Receives the crank's bounty
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+    )]
+    pub crank_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump = position.bump,
+        has_one = user,
+    )]
+    pub position: Box<Account<'info, UserPosition>>,
+
+    #[account(
+        mut,
+        seeds = [REWARDS_SEED, vault.key().as_ref()],
+        bump = rewards.bump,
+        has_one = vault,
+    )]
+    pub rewards: Option<Box<Account<'info, RewardsPool>>>,
+
+    /// Instructions sysvar, used to reject flows bundled with a NAV update
+    /// CHECK: address is checked against the sysvar id
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Redeem every share the position PDA may burn once the share price is at
+/// or below the stop-loss level, splitting off the crank's bounty. The rule
+/// is cleared afterwards. Permissionless.
+pub fn handle_execute_stop_loss(ctx: Context<ExecuteStopLoss>) -> Result<()> {
+    let position = &ctx.accounts.position;
+    require!(position.has_stop_loss(), VaultError::StopLossNotSet);
+
+    let price_q64 = {
+        let vault = ctx.accounts.vault.load()?;
+        share_price_q64(vault.total_assets, vault.total_shares)?
+    };
+    require!(
+        position.is_stop_loss_triggered(price_q64),
+        VaultError::StopLossNotTriggered
+    );
+
+    let share_account = &ctx.accounts.user_share_account;
+    let delegated = if share_account.delegate == Some(position.key()).into() {
+        share_account.delegated_amount
+    } else {
+        0
+    };
+    let total = share_account.amount.min(delegated);
+    require!(total > 0, VaultError::InsufficientShares);
+    let bounty_shares = (total as u128 * position.stop_loss_bounty_bps as u128
+        / BPS_DENOMINATOR) as u64;
+    let shares = total - bounty_shares;
+
+    let vault_key = ctx.accounts.vault.key();
+    let user_key = ctx.accounts.user.key();
+    let bump = [position.bump];
+    let seeds: &[&[u8]] = &[POSITION_SEED, vault_key.as_ref(), user_key.as_ref(), &bump];
+    let position_bump = position.bump;
+    let position_info = ctx.accounts.position.to_account_info();
+
+    // The stop-loss level is the user's consent to the loss it implies
+    let mut recipients = vec![(shares, ctx.accounts.user_token_account.to_account_info())];
+    if bounty_shares > 0 {
+        recipients.push((bounty_shares, ctx.accounts.crank_token_account.to_account_info()));
+    }
+    for (amount, recipient) in recipients {
+        ShareRedemption {
+            user: user_key,
+            vault: &ctx.accounts.vault,
+            vault_token_account: &ctx.accounts.vault_token_account,
+            share_mint: &ctx.accounts.share_mint,
+            user_share_account: &ctx.accounts.user_share_account,
+            recipient,
+            position: &mut ctx.accounts.position,
+            position_bump,
+            rewards: ctx.accounts.rewards.as_deref_mut(),
+            instructions: &ctx.accounts.instructions,
+            exit_fee_balance: None,
+            token_program: &ctx.accounts.token_program,
+            burn_authority: position_info.clone(),
+            events: EventCpi {
+                authority: &ctx.accounts.event_authority,
+                bump: ctx.bumps.event_authority,
+            },
+        }
+        .redeem(amount, BPS_DENOMINATOR as u16, None, &[seeds])?;
+        ctx.accounts.vault_token_account.reload()?;
+        ctx.accounts.user_share_account.reload()?;
+    }
+
+    let position = &mut ctx.accounts.position;
+    position.stop_loss_ref_price_q64 = 0;
+    position.stop_loss_bps = 0;
+    position.stop_loss_bounty_bps = 0;
+
+    emit_cpi!(StopLossExecuted {
+        vault: vault_key,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        user: user_key,
+        crank: ctx.accounts.crank.key(),
+        price_q64,
+        shares,
+        bounty_shares,
+    });
+
+    Ok(())
+}
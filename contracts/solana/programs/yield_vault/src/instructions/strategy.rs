@@ -0,0 +1,888 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{
+    DeallocationRequested, DebtCeilingBoundsSet, StrategyAdded, StrategyAllocated,
+    StrategyRemoved, StrategyReport, StrategyStale, WithdrawRouteSet,
+};
+use crate::instructions::audit_log::record_audit;
+use crate::state::{
+    AuditAction, AuditLog, FeeReport, StrategyRegistry, StrategyState, StrategyWhitelist,
+    VaultState,
+};
+use crate::utils::EventCpi;
+
+// ──────────────────────────────────────────
+// Add Strategy
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(strategy_id: Pubkey)]
+pub struct AddStrategy<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    /// The strategy account (PDA)
+    #[account(
+        init,
+        payer = owner,
+        space = StrategyState::LEN,
+        seeds = [STRATEGY_SEED, vault.key().as_ref(), strategy_id.as_ref()],
+        bump,
+    )]
+    pub strategy: Box<Account<'info, StrategyState>>,
+
+    /// The vault's strategy registry
+    #[account(
+        mut,
+        seeds = [STRATEGY_REGISTRY_SEED, vault.key().as_ref()],
+        bump = strategy_registry.load()?.bump,
+        has_one = vault,
+    )]
+    pub strategy_registry: AccountLoader<'info, StrategyRegistry>,
+
+    /// Protocol whitelist `strategy_id` must be on
+    #[account(
+        seeds = [STRATEGY_WHITELIST_SEED],
+        bump = strategy_whitelist.bump,
+    )]
+    pub strategy_whitelist: Box<Account<'info, StrategyWhitelist>>,
+
+    /// Authority over the strategy's funds (agent key or adapter PDA)
+    /// CHECK: only recorded; must sign deallocations
+    pub strategy_authority: UncheckedAccount<'info>,
+
+    /// Token account that receives allocated funds
+    #[account(
+        token::mint = vault.load()?.mint,
+        token::authority = strategy_authority,
+    )]
+    pub strategy_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_add_strategy(ctx: Context<AddStrategy>, strategy_id: Pubkey) -> Result<()> {
+    require!(
+        ctx.accounts.strategy_whitelist.contains(&strategy_id),
+        VaultError::StrategyNotWhitelisted
+    );
+    ctx.accounts
+        .vault
+        .load()?
+        .check_strategy_requirements(ctx.accounts.strategy_whitelist.metadata(&strategy_id))?;
+
+    let clock = Clock::get()?;
+    let strategy = &mut ctx.accounts.strategy;
+
+    strategy.vault = ctx.accounts.vault.key();
+    strategy.strategy_id = strategy_id;
+    strategy.authority = ctx.accounts.strategy_authority.key();
+    strategy.token_account = ctx.accounts.strategy_token_account.key();
+    strategy.current_debt = 0;
+    strategy.total_gain = 0;
+    strategy.total_loss = 0;
+    strategy.last_report = clock.unix_timestamp;
+    strategy.last_health_check = clock.unix_timestamp;
+    strategy.debt_ceiling = 0;
+    strategy.min_debt_ceiling = 0;
+    strategy.max_debt_ceiling = 0;
+    strategy.instant_withdraw_limit = 0;
+    strategy.withdraw_priority = 0;
+    strategy.bump = ctx.bumps.strategy;
+
+    ctx.accounts
+        .strategy_registry
+        .load_mut()?
+        .register(strategy.key())?;
+
+    emit_cpi!(StrategyAdded {
+        vault: strategy.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        strategy: strategy.key(),
+        strategy_id,
+        authority: strategy.authority,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::AddStrategy,
+        &[strategy_id.as_ref()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Remove Strategy
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RemoveStrategy<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        seeds = [STRATEGY_SEED, vault.key().as_ref(), strategy.strategy_id.as_ref()],
+        bump = strategy.bump,
+        has_one = vault,
+        close = owner,
+    )]
+    pub strategy: Box<Account<'info, StrategyState>>,
+
+    /// The vault's strategy registry
+    #[account(
+        mut,
+        seeds = [STRATEGY_REGISTRY_SEED, vault.key().as_ref()],
+        bump = strategy_registry.load()?.bump,
+        has_one = vault,
+    )]
+    pub strategy_registry: AccountLoader<'info, StrategyRegistry>,
+}
+
+pub fn handle_remove_strategy(ctx: Context<RemoveStrategy>) -> Result<()> {
+    require!(
+        ctx.accounts.strategy.current_debt == 0,
+        VaultError::StrategyHasDebt
+    );
+
+    ctx.accounts
+        .strategy_registry
+        .load_mut()?
+        .unregister(&ctx.accounts.strategy.key())?;
+
+    emit_cpi!(StrategyRemoved {
+        vault: ctx.accounts.vault.key(),
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        strategy: ctx.accounts.strategy.key(),
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::RemoveStrategy,
+        &[ctx.accounts.strategy.key().as_ref()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Debt Ceiling Bounds
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetDebtCeilingBounds<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        seeds = [STRATEGY_SEED, vault.key().as_ref(), strategy.strategy_id.as_ref()],
+        bump = strategy.bump,
+        has_one = vault,
+    )]
+    pub strategy: Box<Account<'info, StrategyState>>,
+}
+
+/// Bound the strategy's debt ceiling to `[min, max]`. The ceiling is clamped
+/// into the new bounds, so a newly bounded strategy starts at `min` and earns
+/// capacity through reported gains. `(0, 0)` removes the ceiling.
+pub fn handle_set_debt_ceiling_bounds(
+    ctx: Context<SetDebtCeilingBounds>,
+    min: u64,
+    max: u64,
+) -> Result<()> {
+    require!(min <= max, VaultError::InvalidConfig);
+
+    let strategy = &mut ctx.accounts.strategy;
+    strategy.min_debt_ceiling = min;
+    strategy.max_debt_ceiling = max;
+    strategy.debt_ceiling = strategy.debt_ceiling.clamp(min, max);
+
+    emit_cpi!(DebtCeilingBoundsSet {
+        vault: strategy.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        strategy: strategy.key(),
+        min_debt_ceiling: min,
+        max_debt_ceiling: max,
+        debt_ceiling: strategy.debt_ceiling,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetDebtCeilingBounds,
+        &[strategy.key().as_ref(), &min.to_le_bytes(), &max.to_le_bytes()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Set Withdraw Route
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetWithdrawRoute<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        seeds = [STRATEGY_SEED, vault.key().as_ref(), strategy.strategy_id.as_ref()],
+        bump = strategy.bump,
+        has_one = vault,
+    )]
+    pub strategy: Box<Account<'info, StrategyState>>,
+}
+
+/// Let `withdraw_routed` pull up to `instant_withdraw_limit` per withdrawal
+/// from the strategy, after every strategy with a lower `withdraw_priority`.
+/// The strategy authority must also approve the vault as delegate of the
+/// strategy's token account. A zero limit takes the strategy off the route.
+pub fn handle_set_withdraw_route(
+    ctx: Context<SetWithdrawRoute>,
+    withdraw_priority: u8,
+    instant_withdraw_limit: u64,
+) -> Result<()> {
+    let strategy = &mut ctx.accounts.strategy;
+    strategy.withdraw_priority = withdraw_priority;
+    strategy.instant_withdraw_limit = instant_withdraw_limit;
+
+    emit_cpi!(WithdrawRouteSet {
+        vault: strategy.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        strategy: strategy.key(),
+        withdraw_priority,
+        instant_withdraw_limit,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.owner.key(),
+        AuditAction::SetWithdrawRoute,
+        &[
+            strategy.key().as_ref(),
+            &[withdraw_priority],
+            &instant_withdraw_limit.to_le_bytes(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Allocate
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Allocate<'info> {
+    /// The manager or owner moving funds into the strategy
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        seeds = [STRATEGY_SEED, vault.key().as_ref(), strategy.strategy_id.as_ref()],
+        bump = strategy.bump,
+        has_one = vault,
+    )]
+    pub strategy: Box<Account<'info, StrategyState>>,
+
+    /// The vault's token account (sends idle funds)
+    #[account(
+        mut,
+        seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
+        bump = vault.load()?.token_account_bump,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// The strategy's token account (receives funds)
+    #[account(mut, address = strategy.token_account)]
+    pub strategy_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol strategy registry the vault's strategy requirements are
+    /// checked against
+    #[account(
+        seeds = [STRATEGY_WHITELIST_SEED],
+        bump = strategy_whitelist.bump,
+    )]
+    pub strategy_whitelist: Box<Account<'info, StrategyWhitelist>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle_allocate(ctx: Context<Allocate>, amount: u64) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let vault = ctx.accounts.vault.load()?;
+
+    require!(
+        vault.is_authority(&ctx.accounts.authority.key()),
+        VaultError::Unauthorized
+    );
+    vault.status().check_allocations()?;
+    require!(!vault.is_locked(), VaultError::Reentrancy);
+    // Under an enforced policy only the owner may allocate outside `rebalance`
+    require!(
+        !vault.is_policy_enforced() || vault.owner == ctx.accounts.authority.key(),
+        VaultError::PolicyRequired
+    );
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        !vault.is_deallocation_overdue(now),
+        VaultError::AllocationsSuspended
+    );
+    require!(
+        !ctx.accounts.strategy.is_stale(now, vault.max_report_age),
+        VaultError::StrategyStale
+    );
+    vault.check_strategy_requirements(
+        ctx.accounts
+            .strategy_whitelist
+            .metadata(&ctx.accounts.strategy.strategy_id),
+    )?;
+    require!(amount > 0, VaultError::InvalidAmount);
+    let new_debt = ctx
+        .accounts
+        .strategy
+        .current_debt
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    require!(
+        ctx.accounts.strategy.within_debt_ceiling(new_debt),
+        VaultError::DebtCeilingExceeded
+    );
+    let liquid = vault.liquid_assets(vault.token_balance(ctx.accounts.vault_token_account.amount));
+    require!(liquid >= amount, VaultError::InsufficientAssets);
+    // Keep the small-withdrawal reserve undeployed
+    require!(
+        liquid - amount >= vault.liquidity_reserve()?,
+        VaultError::LiquidityReserved
+    );
+
+    // Paper vaults only simulate the allocation
+    if !vault.is_paper() {
+        let seeds = vault.signer_seeds();
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    to: ctx.accounts.strategy_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+    }
+    drop(vault);
+
+    let strategy = &mut ctx.accounts.strategy;
+    strategy.current_debt = new_debt;
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.total_debt = vault
+        .total_debt
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    vault.debit_paper_balance(amount)?;
+
+    emit_cpi!(StrategyAllocated {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
+        strategy: strategy.key(),
+        amount,
+        current_debt: strategy.current_debt,
+    });
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.authority.key(),
+        AuditAction::Allocate,
+        &[strategy.key().as_ref(), &amount.to_le_bytes()],
+    )?;
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Deallocate
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Deallocate<'info> {
+    /// The manager or owner pulling funds back to the vault
+    pub authority: Signer<'info>,
+
+    /// The strategy's fund authority (may be the same key as `authority`)
+    #[account(address = strategy.authority @ VaultError::Unauthorized)]
+    pub strategy_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        seeds = [STRATEGY_SEED, vault.key().as_ref(), strategy.strategy_id.as_ref()],
+        bump = strategy.bump,
+        has_one = vault,
+    )]
+    pub strategy: Box<Account<'info, StrategyState>>,
+
+    /// The vault's lifetime fee report
+    #[account(
+        mut,
+        seeds = [FEE_REPORT_SEED, vault.key().as_ref()],
+        bump = fee_report.bump,
+        has_one = vault,
+    )]
+    pub fee_report: Box<Account<'info, FeeReport>>,
+
+    /// The vault's token account (receives returned funds)
+    #[account(
+        mut,
+        seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
+        bump = vault.load()?.token_account_bump,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// The strategy's token account (sends funds)
+    #[account(mut, address = strategy.token_account)]
+    pub strategy_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle_deallocate(ctx: Context<Deallocate>, amount: u64) -> Result<()> {
+    require!(
+        ctx.accounts
+            .vault
+            .load()?
+            .is_authority(&ctx.accounts.authority.key()),
+        VaultError::Unauthorized
+    );
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    // A paper strategy simulates its returns: whatever it hands back beyond
+    // its debt is booked as profit, as for a real one
+    if !ctx.accounts.vault.load()?.is_paper() {
+        require!(
+            ctx.accounts.strategy_token_account.amount >= amount,
+            VaultError::InsufficientStrategyFunds
+        );
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.strategy_token_account.to_account_info(),
+                    to: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: ctx.accounts.strategy_authority.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+    }
+
+    // Anything returned above the outstanding debt is realized profit
+    let strategy = &ctx.accounts.strategy;
+    let repaid = amount.min(strategy.current_debt);
+    let gain = amount - repaid;
+    let new_debt = strategy.current_debt - repaid;
+
+    let clock = Clock::get()?;
+    let events = EventCpi {
+        authority: &ctx.accounts.event_authority,
+        bump: ctx.bumps.event_authority,
+    };
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.total_debt = vault
+        .total_debt
+        .checked_sub(repaid)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    vault.fulfil_deallocation(amount);
+    vault.credit_paper_balance(amount)?;
+    if gain > 0 {
+        let new_total_assets = vault
+            .total_assets
+            .checked_add(gain)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        let (mgmt_fee, perf_fee) =
+            vault.accrue_fees(new_total_assets, clock.unix_timestamp, events)?;
+        ctx.accounts.fee_report.record_accrual(mgmt_fee, perf_fee)?;
+    }
+
+    record_report(
+        &mut vault,
+        &mut ctx.accounts.strategy,
+        gain,
+        0,
+        new_debt,
+        clock.unix_timestamp,
+        events,
+    )?;
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.authority.key(),
+        AuditAction::Deallocate,
+        &[ctx.accounts.strategy.key().as_ref(), &amount.to_le_bytes()],
+    )
+}
+
+// ──────────────────────────────────────────
+// Request Deallocation
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RequestDeallocation<'info> {
+    /// Anyone may request a deallocation while liquidity is below target
+    pub requester: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [STRATEGY_SEED, vault.key().as_ref(), strategy.strategy_id.as_ref()],
+        bump = strategy.bump,
+        has_one = vault,
+    )]
+    pub strategy: Box<Account<'info, StrategyState>>,
+
+    #[account(
+        seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
+        bump = vault.load()?.token_account_bump,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+}
+
+/// Oblige the manager to return up to `amount` from `strategy` within the
+/// vault's deallocation window, capped at the liquidity shortfall and the
+/// strategy's debt. New allocations are suspended once the deadline passes
+/// with the request unfulfilled.
+pub fn handle_request_deallocation(ctx: Context<RequestDeallocation>, amount: u64) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    require!(vault.pending_deallocation == 0, VaultError::DeallocationAlreadyRequested);
+
+    let liquid = vault.liquid_assets(vault.token_balance(ctx.accounts.vault_token_account.amount));
+    let target = vault.liquidity_target()?;
+    require!(liquid < target, VaultError::LiquiditySufficient);
+
+    let amount = amount
+        .min(target - liquid)
+        .min(ctx.accounts.strategy.current_debt);
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    let deadline = Clock::get()?
+        .unix_timestamp
+        .checked_add(vault.deallocation_window)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    vault.pending_deallocation = amount;
+    vault.deallocation_deadline = deadline;
+
+    emit_cpi!(DeallocationRequested {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
+        strategy: ctx.accounts.strategy.key(),
+        requester: ctx.accounts.requester.key(),
+        amount,
+        deadline,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Harvest
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Harvest<'info> {
+    /// The manager or owner reporting the strategy's value
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        mut,
+        seeds = [STRATEGY_SEED, vault.key().as_ref(), strategy.strategy_id.as_ref()],
+        bump = strategy.bump,
+        has_one = vault,
+    )]
+    pub strategy: Box<Account<'info, StrategyState>>,
+
+    /// The vault's lifetime fee report
+    #[account(
+        mut,
+        seeds = [FEE_REPORT_SEED, vault.key().as_ref()],
+        bump = fee_report.bump,
+        has_one = vault,
+    )]
+    pub fee_report: Box<Account<'info, FeeReport>>,
+}
+
+/// Mark the strategy to `current_value`: the difference from its outstanding
+/// debt is realized as profit or loss in the vault's NAV.
+pub fn handle_harvest(ctx: Context<Harvest>, current_value: u64) -> Result<()> {
+    let events = EventCpi {
+        authority: &ctx.accounts.event_authority,
+        bump: ctx.bumps.event_authority,
+    };
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    require!(
+        vault.is_authority(&ctx.accounts.authority.key()),
+        VaultError::Unauthorized
+    );
+
+    let current_debt = ctx.accounts.strategy.current_debt;
+    let (gain, loss) = if current_value >= current_debt {
+        (current_value - current_debt, 0)
+    } else {
+        (0, current_debt - current_value)
+    };
+
+    let clock = Clock::get()?;
+    let new_total_assets = vault
+        .total_assets
+        .checked_add(gain)
+        .ok_or(VaultError::ArithmeticOverflow)?
+        .checked_sub(loss)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    vault.total_debt = vault
+        .total_debt
+        .checked_add(gain)
+        .ok_or(VaultError::ArithmeticOverflow)?
+        .checked_sub(loss)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    let (mgmt_fee, perf_fee) = vault.accrue_fees(new_total_assets, clock.unix_timestamp, events)?;
+    ctx.accounts.fee_report.record_accrual(mgmt_fee, perf_fee)?;
+
+    record_report(
+        &mut vault,
+        &mut ctx.accounts.strategy,
+        gain,
+        loss,
+        current_value,
+        clock.unix_timestamp,
+        events,
+    )?;
+
+    record_audit(
+        &ctx.accounts.audit_log,
+        ctx.accounts.authority.key(),
+        AuditAction::Harvest,
+        &[ctx.accounts.strategy.key().as_ref(), &current_value.to_le_bytes()],
+    )
+}
+
+/// Update a strategy's cumulative P&L and debt, and emit a `StrategyReport`.
+// ──────────────────────────────────────────
+// Ping Strategy
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PingStrategy<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [STRATEGY_SEED, vault.key().as_ref(), strategy.strategy_id.as_ref()],
+        bump = strategy.bump,
+        has_one = vault,
+    )]
+    pub strategy: Box<Account<'info, StrategyState>>,
+}
+
+/// Health-check a strategy's report age, emitting `StrategyStale` when it
+/// exceeds the vault's `max_report_age`. Allocations check staleness
+/// themselves, so the crank only feeds monitoring.
+pub fn handle_ping_strategy(ctx: Context<PingStrategy>) -> Result<()> {
+    let max_report_age = ctx.accounts.vault.load()?.max_report_age;
+    let now = Clock::get()?.unix_timestamp;
+    let strategy = &mut ctx.accounts.strategy;
+    strategy.last_health_check = now;
+
+    if strategy.is_stale(now, max_report_age) {
+        emit_cpi!(StrategyStale {
+            vault: strategy.vault,
+            event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+            strategy: strategy.key(),
+            strategy_id: strategy.strategy_id,
+            last_report: strategy.last_report,
+            current_debt: strategy.current_debt,
+        });
+    }
+
+    Ok(())
+}
+
+pub(crate) fn record_report(
+    vault: &mut VaultState,
+    strategy: &mut Account<StrategyState>,
+    gain: u64,
+    loss: u64,
+    new_debt: u64,
+    now: i64,
+    events: EventCpi,
+) -> Result<()> {
+    strategy.total_gain = strategy
+        .total_gain
+        .checked_add(gain)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    strategy.total_loss = strategy
+        .total_loss
+        .checked_add(loss)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    let prior_debt = strategy.current_debt;
+    strategy.adjust_debt_ceiling(prior_debt, gain, loss);
+    strategy.current_debt = new_debt;
+    strategy.last_report = now;
+
+    events.emit(StrategyReport {
+        vault: strategy.vault,
+        event_seq: vault.next_event_seq(),
+        strategy: strategy.key(),
+        strategy_id: strategy.strategy_id,
+        gain,
+        loss,
+        current_debt: new_debt,
+        total_gain: strategy.total_gain,
+        total_loss: strategy.total_loss,
+        debt_ceiling: strategy.debt_ceiling,
+    })
+}
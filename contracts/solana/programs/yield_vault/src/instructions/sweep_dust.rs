@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::DustSwept;
+use crate::state::VaultState;
+use crate::utils::{fee_amount_to_shares, q64_to_amount};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SweepDust<'info> {
+    /// The vault owner sweeping dust
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The share token mint (dust shares are minted)
+    #[account(
+        mut,
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+        mint::authority = vault,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    /// The treasury's share token account (receives the dust shares)
+    #[account(
+        mut,
+        token::mint = share_mint,
+    )]
+    pub treasury_share_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Mint the treasury shares worth the whole asset units of rounding dust
+/// accumulated by share conversions. The fraction of a unit left over carries
+/// to the next sweep.
+pub fn handler(ctx: Context<SweepDust>) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let vault = ctx.accounts.vault.load()?;
+
+    let (dust_amount, _) = q64_to_amount(vault.dust())?;
+    let dust_shares = fee_amount_to_shares(dust_amount, vault.total_assets, vault.total_shares)?;
+    require!(dust_shares > 0, VaultError::NoDustToSweep);
+
+    let seeds = vault.signer_seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&seeds];
+    token::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                to: ctx.accounts.treasury_share_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        dust_shares,
+    )?;
+
+    drop(vault);
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    vault.total_shares = vault
+        .total_shares
+        .checked_add(dust_shares)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    let dust = vault.dust() - ((dust_amount as u128) << 64);
+    vault.set_dust(dust);
+
+    vault.assert_invariants(None)?;
+
+    emit_cpi!(DustSwept {
+        vault: vault_key,
+        event_seq: vault.next_event_seq(),
+        dust_amount,
+        shares_minted: dust_shares,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,421 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::{TrancheCreated, TrancheMinted, TrancheRedeemed, TrancheSettled};
+use crate::state::{Tranche, VaultState};
+use crate::utils::share_price_q64;
+
+// ──────────────────────────────────────────
+// Create Tranche
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(maturity: i64)]
+pub struct CreateTranche<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Tranche::LEN,
+        seeds = [TRANCHE_SEED, vault.key().as_ref(), &maturity.to_le_bytes()],
+        bump,
+    )]
+    pub tranche: Box<Account<'info, Tranche>>,
+
+    /// Principal token (PT), in the underlying's decimals
+    #[account(
+        init,
+        payer = owner,
+        seeds = [TRANCHE_PRINCIPAL_SEED, tranche.key().as_ref()],
+        bump,
+        mint::decimals = vault.load()?.asset_decimals,
+        mint::authority = tranche,
+    )]
+    pub principal_mint: Box<Account<'info, Mint>>,
+
+    /// Yield token (YT), in the underlying's decimals
+    #[account(
+        init,
+        payer = owner,
+        seeds = [TRANCHE_YIELD_SEED, tranche.key().as_ref()],
+        bump,
+        mint::decimals = vault.load()?.asset_decimals,
+        mint::authority = tranche,
+    )]
+    pub yield_mint: Box<Account<'info, Mint>>,
+
+    /// Holds the shares backing the tranche's tokens
+    #[account(
+        init,
+        payer = owner,
+        seeds = [TRANCHE_ESCROW_SEED, tranche.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = tranche,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Open a tranche maturing at `maturity`, priced at the current share price.
+/// Owner only.
+pub fn handle_create_tranche(ctx: Context<CreateTranche>, maturity: i64) -> Result<()> {
+    require!(
+        maturity > Clock::get()?.unix_timestamp,
+        VaultError::InvalidConfig
+    );
+    let start_price = {
+        let vault = ctx.accounts.vault.load()?;
+        require!(vault.total_shares > 0, VaultError::NoSharesOutstanding);
+        share_price_q64(vault.total_assets, vault.total_shares)?
+    };
+    require!(start_price > 0, VaultError::InvalidConfig);
+
+    let tranche = &mut ctx.accounts.tranche;
+    tranche.vault = ctx.accounts.vault.key();
+    tranche.principal_mint = ctx.accounts.principal_mint.key();
+    tranche.yield_mint = ctx.accounts.yield_mint.key();
+    tranche.maturity = maturity;
+    tranche.start_price_q64 = start_price;
+    tranche.settlement_price_q64 = 0;
+    tranche.settled_at = 0;
+    tranche.bump = ctx.bumps.tranche;
+    tranche.principal_mint_bump = ctx.bumps.principal_mint;
+    tranche.yield_mint_bump = ctx.bumps.yield_mint;
+    tranche.escrow_bump = ctx.bumps.escrow;
+
+    emit_cpi!(TrancheCreated {
+        vault: tranche.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        tranche: tranche.key(),
+        maturity,
+        start_price_q64: start_price,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Mint Tranche
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MintTranche<'info> {
+    pub user: Signer<'info>,
+
+    /// The tranche's vault, which numbers the event
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [TRANCHE_SEED, tranche.vault.as_ref(), &tranche.maturity.to_le_bytes()],
+        bump = tranche.bump,
+        has_one = vault,
+        has_one = principal_mint,
+        has_one = yield_mint,
+    )]
+    pub tranche: Box<Account<'info, Tranche>>,
+
+    #[account(mut)]
+    pub principal_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub yield_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [TRANCHE_ESCROW_SEED, tranche.key().as_ref()],
+        bump = tranche.escrow_bump,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    /// The user's vault shares being escrowed
+    #[account(
+        mut,
+        token::mint = escrow.mint,
+        token::authority = user,
+    )]
+    pub user_share_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = principal_mint,
+        token::authority = user,
+    )]
+    pub user_principal_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = yield_mint,
+        token::authority = user,
+    )]
+    pub user_yield_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Escrow `shares` and mint equal amounts of PT and YT worth their value at
+/// the tranche's start price. Shares minted after creation carry the yield
+/// accrued since, which is shared by all YT.
+pub fn handle_mint_tranche(ctx: Context<MintTranche>, shares: u64) -> Result<()> {
+    let tranche = &ctx.accounts.tranche;
+    require!(
+        Clock::get()?.unix_timestamp < tranche.maturity,
+        VaultError::TrancheMatured
+    );
+    require!(shares > 0, VaultError::InvalidAmount);
+    let amount = tranche.pairs_for_shares(shares)?;
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_share_account.to_account_info(),
+                to: ctx.accounts.escrow.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        shares,
+    )?;
+
+    let maturity = tranche.maturity.to_le_bytes();
+    let bump = [tranche.bump];
+    let seeds: &[&[u8]] = &[TRANCHE_SEED, tranche.vault.as_ref(), &maturity, &bump];
+
+    for (mint, to) in [
+        (&ctx.accounts.principal_mint, &ctx.accounts.user_principal_account),
+        (&ctx.accounts.yield_mint, &ctx.accounts.user_yield_account),
+    ] {
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: mint.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: tranche.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )?;
+    }
+
+    emit_cpi!(TrancheMinted {
+        vault: tranche.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        tranche: tranche.key(),
+        user: ctx.accounts.user.key(),
+        shares,
+        amount,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Settle Tranche
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleTranche<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [TRANCHE_SEED, vault.key().as_ref(), &tranche.maturity.to_le_bytes()],
+        bump = tranche.bump,
+        has_one = vault,
+    )]
+    pub tranche: Box<Account<'info, Tranche>>,
+}
+
+/// Fix the tranche's payouts at the current share price. Permissionless once
+/// the tranche has matured.
+pub fn handle_settle_tranche(ctx: Context<SettleTranche>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let tranche = &mut ctx.accounts.tranche;
+    require!(now >= tranche.maturity, VaultError::TrancheNotMatured);
+    require!(!tranche.is_settled(), VaultError::TrancheAlreadySettled);
+
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    tranche.settlement_price_q64 = share_price_q64(vault.total_assets, vault.total_shares)?;
+    tranche.settled_at = now;
+
+    emit_cpi!(TrancheSettled {
+        vault: tranche.vault,
+        event_seq: vault.next_event_seq(),
+        tranche: tranche.key(),
+        settlement_price_q64: tranche.settlement_price_q64,
+    });
+
+    Ok(())
+}
+
+// ──────────────────────────────────────────
+// Redeem Tranche
+// ──────────────────────────────────────────
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RedeemTranche<'info> {
+    pub user: Signer<'info>,
+
+    /// The tranche's vault, which numbers the event
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
+    )]
+    pub vault: AccountLoader<'info, VaultState>,
+
+    #[account(
+        seeds = [TRANCHE_SEED, tranche.vault.as_ref(), &tranche.maturity.to_le_bytes()],
+        bump = tranche.bump,
+        has_one = vault,
+        has_one = principal_mint,
+        has_one = yield_mint,
+    )]
+    pub tranche: Box<Account<'info, Tranche>>,
+
+    #[account(mut)]
+    pub principal_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub yield_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [TRANCHE_ESCROW_SEED, tranche.key().as_ref()],
+        bump = tranche.escrow_bump,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    /// Receives the released shares
+    #[account(
+        mut,
+        token::mint = escrow.mint,
+    )]
+    pub user_share_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = principal_mint,
+        token::authority = user,
+    )]
+    pub user_principal_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = yield_mint,
+        token::authority = user,
+    )]
+    pub user_yield_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Burn PT and YT for escrowed shares. Before settlement only whole pairs
+/// redeem, for their backing; after it each token redeems on its own.
+pub fn handle_redeem_tranche(
+    ctx: Context<RedeemTranche>,
+    principal_amount: u64,
+    yield_amount: u64,
+) -> Result<()> {
+    let tranche = &ctx.accounts.tranche;
+    require!(
+        principal_amount > 0 || yield_amount > 0,
+        VaultError::InvalidAmount
+    );
+
+    let shares = if tranche.is_settled() {
+        tranche
+            .principal_payout(principal_amount)?
+            .checked_add(tranche.yield_payout(yield_amount)?)
+            .ok_or(VaultError::ArithmeticOverflow)?
+    } else {
+        require!(principal_amount == yield_amount, VaultError::TrancheNotSettled);
+        tranche.pair_backing(principal_amount)?
+    };
+
+    for (mint, from, amount) in [
+        (&ctx.accounts.principal_mint, &ctx.accounts.user_principal_account, principal_amount),
+        (&ctx.accounts.yield_mint, &ctx.accounts.user_yield_account, yield_amount),
+    ] {
+        if amount > 0 {
+            token::burn(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Burn {
+                        mint: mint.to_account_info(),
+                        from: from.to_account_info(),
+                        authority: ctx.accounts.user.to_account_info(),
+                    },
+                ),
+                amount,
+            )?;
+        }
+    }
+
+    if shares > 0 {
+        let maturity = tranche.maturity.to_le_bytes();
+        let bump = [tranche.bump];
+        let seeds: &[&[u8]] = &[TRANCHE_SEED, tranche.vault.as_ref(), &maturity, &bump];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow.to_account_info(),
+                    to: ctx.accounts.user_share_account.to_account_info(),
+                    authority: tranche.to_account_info(),
+                },
+                &[seeds],
+            ),
+            shares,
+        )?;
+    }
+
+    emit_cpi!(TrancheRedeemed {
+        vault: tranche.vault,
+        event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+        tranche: tranche.key(),
+        user: ctx.accounts.user.key(),
+        principal_amount,
+        yield_amount,
+        shares,
+    });
+
+    Ok(())
+}
@@ -1,11 +1,20 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as ix_sysvar, load_instruction_at_checked,
+};
+use anchor_lang::Discriminator;
 
 use crate::constants::*;
 use crate::error::VaultError;
 use crate::events::NavUpdated;
-use crate::state::VaultState;
-use crate::utils::{calculate_management_fee, calculate_performance_fee};
+use crate::instructions::audit_log::record_audit;
+use crate::state::{
+    AuditAction, AuditLog, FeeReport, GlobalStats, ManagerProfile, PriceHistory, PriceSample,
+    ReservesVerifier, VaultState,
+};
+use crate::utils::{check_stack_height, EventCpi};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdateNav<'info> {
     /// The manager or owner updating the NAV
@@ -14,65 +23,349 @@ pub struct UpdateNav<'info> {
     /// The vault state
     #[account(
         mut,
-        seeds = [VAULT_SEED, vault.mint.as_ref(), vault.owner.as_ref()],
-        bump = vault.bump,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
     )]
-    pub vault: Box<Account<'info, VaultState>>,
+    pub vault: AccountLoader<'info, VaultState>,
+
+    /// The vault's lifetime fee report
+    #[account(
+        mut,
+        seeds = [FEE_REPORT_SEED, vault.key().as_ref()],
+        bump = fee_report.bump,
+        has_one = vault,
+    )]
+    pub fee_report: Box<Account<'info, FeeReport>>,
+
+    /// The vault's share-price history
+    #[account(
+        mut,
+        seeds = [PRICE_HISTORY_SEED, vault.key().as_ref()],
+        bump = price_history.load()?.bump,
+        has_one = vault,
+    )]
+    pub price_history: AccountLoader<'info, PriceHistory>,
+
+    /// The vault's audit log
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED, vault.key().as_ref()],
+        bump = audit_log.load()?.bump,
+        has_one = vault,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    /// Instructions sysvar, used to reject NAV updates bundled with user flows
+    /// CHECK: address is checked against the sysvar id
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// The vault's price oracle, required when one is configured
+    /// CHECK: address and owner are checked against the vault config
+    pub oracle: Option<UncheckedAccount<'info>>,
+
+    /// The authority's reputation profile, required when it is a manager
+    #[account(
+        mut,
+        seeds = [MANAGER_PROFILE_SEED, authority.key().as_ref()],
+        bump = manager_profile.bump,
+    )]
+    pub manager_profile: Option<Box<Account<'info, ManagerProfile>>>,
+
+    /// The vault's proof-of-reserves verifier, required when it enforces proofs
+    #[account(
+        seeds = [RESERVES_VERIFIER_SEED, vault.key().as_ref()],
+        bump = reserves_verifier.bump,
+        has_one = vault,
+    )]
+    pub reserves_verifier: Option<Box<Account<'info, ReservesVerifier>>>,
+
+    /// The protocol stats account, pushed the vault's new assets once the
+    /// admin has created it
+    /// CHECK: address checked by seeds; contents checked by `GlobalStats::sync`
+    #[account(mut, seeds = [GLOBAL_STATS_SEED], bump)]
+    pub global_stats: UncheckedAccount<'info>,
 }
 
-pub fn handler(ctx: Context<UpdateNav>, new_total_assets: u64) -> Result<()> {
-    let vault = &mut ctx.accounts.vault;
-    let authority_key = ctx.accounts.authority.key();
-
-    // Only owner or authorized manager can update NAV
-    require!(vault.is_authority(&authority_key), VaultError::Unauthorized);
-
-    let clock = Clock::get()?;
-    let old_total_assets = vault.total_assets;
-
-    // Accrue management fees based on time elapsed
-    let seconds_elapsed = clock
-        .unix_timestamp
-        .checked_sub(vault.last_fee_collection)
-        .ok_or(VaultError::ArithmeticOverflow)?;
-
-    let mgmt_fee = calculate_management_fee(
-        vault.total_assets,
-        vault.management_fee_bps,
-        seconds_elapsed,
-    )?;
-
-    vault.accrued_management_fee = vault
-        .accrued_management_fee
-        .checked_add(mgmt_fee)
-        .ok_or(VaultError::ArithmeticOverflow)?;
-
-    // Calculate performance fee if NAV increased above high-water mark
-    let (perf_fee, new_hwm) = calculate_performance_fee(
-        new_total_assets,
-        vault.high_water_mark,
-        vault.performance_fee_bps,
-        vault.total_shares,
-    )?;
-
-    if perf_fee > 0 {
-        vault.accrued_management_fee = vault
-            .accrued_management_fee
-            .checked_add(perf_fee)
-            .ok_or(VaultError::ArithmeticOverflow)?;
+pub fn handler(
+    ctx: Context<UpdateNav>,
+    new_total_assets: u64,
+    valuation_commitment: Option<[u8; 32]>,
+) -> Result<()> {
+    NavUpdate {
+        authority: ctx.accounts.authority.key(),
+        vault: &ctx.accounts.vault,
+        fee_report: &mut ctx.accounts.fee_report,
+        price_history: &ctx.accounts.price_history,
+        audit_log: &ctx.accounts.audit_log,
+        instructions: &ctx.accounts.instructions,
+        oracle: ctx.accounts.oracle.as_deref(),
+        reserves_verifier: ctx.accounts.reserves_verifier.as_deref(),
+        manager_profile: ctx.accounts.manager_profile.as_deref_mut(),
+        global_stats: &ctx.accounts.global_stats,
+        events: EventCpi {
+            authority: &ctx.accounts.event_authority,
+            bump: ctx.bumps.event_authority,
+        },
     }
+    .apply(new_total_assets, valuation_commitment)
+}
+
+// ──────────────────────────────────────────
+// Update NAV Batch
+// ──────────────────────────────────────────
+
+/// Remaining accounts per vault in `update_nav_batch`
+const BATCH_ACCOUNTS_PER_VAULT: usize = 6;
 
-    // Update state
-    vault.total_assets = new_total_assets;
-    vault.high_water_mark = new_hwm;
-    vault.last_fee_collection = clock.unix_timestamp;
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateNavBatch<'info> {
+    /// The manager or owner of every vault in the batch
+    pub authority: Signer<'info>,
+
+    /// Instructions sysvar, used to reject NAV updates bundled with user flows
+    /// CHECK: address is checked against the sysvar id
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// The authority's reputation profile, required when it manages any of
+    /// the vaults
+    #[account(
+        mut,
+        seeds = [MANAGER_PROFILE_SEED, authority.key().as_ref()],
+        bump = manager_profile.bump,
+    )]
+    pub manager_profile: Option<Box<Account<'info, ManagerProfile>>>,
 
-    emit!(NavUpdated {
-        vault: vault.key(),
-        old_total_assets,
-        new_total_assets,
-        manager: authority_key,
-    });
+    /// The protocol stats account, pushed the vault's new assets once the
+    /// admin has created it
+    /// CHECK: address checked by seeds; contents checked by `GlobalStats::sync`
+    #[account(mut, seeds = [GLOBAL_STATS_SEED], bump)]
+    pub global_stats: UncheckedAccount<'info>,
+}
 
+/// Apply `new_total_assets[i]` to the i-th vault, each described by the
+/// `(vault, fee report, price history, audit log, oracle, reserves verifier)`
+/// accounts at the same position in the remaining accounts. Pass the program id for an
+/// absent oracle or verifier. Every update is checked exactly as in
+/// `update_nav`, and `valuation_commitment` covers the whole batch.
+pub fn handle_update_nav_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, UpdateNavBatch<'info>>,
+    new_total_assets: Vec<u64>,
+    valuation_commitment: Option<[u8; 32]>,
+) -> Result<()> {
+    require!(
+        !new_total_assets.is_empty()
+            && ctx.remaining_accounts.len() == BATCH_ACCOUNTS_PER_VAULT * new_total_assets.len(),
+        VaultError::InvalidNavBatchAccounts
+    );
+    let optional = |info: &'info AccountInfo<'info>| (info.key() != crate::ID).then_some(info);
+
+    for (nav, accounts) in new_total_assets
+        .iter()
+        .zip(ctx.remaining_accounts.chunks(BATCH_ACCOUNTS_PER_VAULT))
+    {
+        let vault = AccountLoader::<VaultState>::try_from(&accounts[0])?;
+        let vault_key = vault.key();
+        let mut fee_report = Account::<FeeReport>::try_from(&accounts[1])?;
+        require_keys_eq!(fee_report.vault, vault_key, VaultError::InvalidNavBatchAccounts);
+        let price_history = AccountLoader::<PriceHistory>::try_from(&accounts[2])?;
+        require_keys_eq!(
+            price_history.load()?.vault,
+            vault_key,
+            VaultError::InvalidNavBatchAccounts
+        );
+        let audit_log = AccountLoader::<AuditLog>::try_from(&accounts[3])?;
+        require_keys_eq!(
+            audit_log.load()?.vault,
+            vault_key,
+            VaultError::InvalidNavBatchAccounts
+        );
+        let reserves_verifier = optional(&accounts[5])
+            .map(Account::<ReservesVerifier>::try_from)
+            .transpose()?;
+        if let Some(verifier) = &reserves_verifier {
+            require_keys_eq!(verifier.vault, vault_key, VaultError::InvalidNavBatchAccounts);
+        }
+
+        NavUpdate {
+            authority: ctx.accounts.authority.key(),
+            vault: &vault,
+            fee_report: &mut fee_report,
+            price_history: &price_history,
+            audit_log: &audit_log,
+            instructions: &ctx.accounts.instructions,
+            oracle: optional(&accounts[4]),
+            reserves_verifier: reserves_verifier.as_ref(),
+            manager_profile: ctx.accounts.manager_profile.as_deref_mut(),
+            global_stats: &ctx.accounts.global_stats,
+            events: EventCpi {
+                authority: &ctx.accounts.event_authority,
+                bump: ctx.bumps.event_authority,
+            },
+        }
+        .apply(*nav, valuation_commitment)?;
+
+        fee_report.exit(&crate::ID)?;
+    }
+
+    Ok(())
+}
+
+/// Accounts for applying one vault's NAV update, shared by `update_nav` and
+/// `update_nav_batch`
+struct NavUpdate<'a, 'info> {
+    /// The manager or owner updating the NAV
+    authority: Pubkey,
+    vault: &'a AccountLoader<'info, VaultState>,
+    fee_report: &'a mut Account<'info, FeeReport>,
+    price_history: &'a AccountLoader<'info, PriceHistory>,
+    audit_log: &'a AccountLoader<'info, AuditLog>,
+    instructions: &'a AccountInfo<'info>,
+    oracle: Option<&'a AccountInfo<'info>>,
+    reserves_verifier: Option<&'a Account<'info, ReservesVerifier>>,
+    manager_profile: Option<&'a mut Account<'info, ManagerProfile>>,
+    /// The protocol stats address
+    global_stats: &'a AccountInfo<'info>,
+    events: EventCpi<'a, 'info>,
+}
+
+impl NavUpdate<'_, '_> {
+    fn apply(
+        self,
+        new_total_assets: u64,
+        valuation_commitment: Option<[u8; 32]>,
+    ) -> Result<()> {
+        let vault_key = self.vault.key();
+        let mut vault = self.vault.load_mut()?;
+        let authority_key = self.authority;
+
+        // Only owner or authorized manager can update NAV
+        require!(vault.is_authority(&authority_key), VaultError::Unauthorized);
+        check_stack_height()?;
+
+        // A manager must not sandwich deposits or withdrawals around a NAV change
+        require!(
+            !has_user_flow(self.instructions, &vault_key)?,
+            VaultError::NavUpdateWithUserFlow
+        );
+
+        vault.check_oracle(self.oracle)?;
+
+        let clock = Clock::get()?;
+        let old_total_assets = vault.total_assets;
+
+        if vault.is_reserves_proof_enforced() {
+            let verifier = self
+                .reserves_verifier
+                .ok_or(VaultError::ReservesNotProven)?;
+            verifier.check_nav_increase(
+                old_total_assets,
+                vault.total_debt,
+                new_total_assets,
+                clock.unix_timestamp,
+            )?;
+        }
+
+        // Accrue management and performance fees, then apply the new NAV
+        let (mgmt_fee, perf_fee) =
+            vault.accrue_fees(new_total_assets, clock.unix_timestamp, self.events)?;
+        self.fee_report.record_accrual(mgmt_fee, perf_fee)?;
+
+        self.price_history.load_mut()?.push(PriceSample {
+            timestamp: clock.unix_timestamp,
+            total_assets: vault.total_assets,
+            total_shares: vault.total_shares,
+        });
+
+        vault.assert_invariants(None)?;
+        GlobalStats::sync(self.global_stats, &mut vault, clock.unix_timestamp)?;
+
+        match self.manager_profile {
+            Some(profile) => profile.record_nav_update(old_total_assets, new_total_assets),
+            None => require!(!vault.is_manager(&authority_key), VaultError::ManagerProfileRequired),
+        }
+
+        self.events.emit(NavUpdated {
+            vault: vault_key,
+            event_seq: vault.next_event_seq(),
+            old_total_assets,
+            new_total_assets,
+            manager: authority_key,
+            valuation_commitment,
+        })?;
+
+        record_audit(
+            self.audit_log,
+            authority_key,
+            AuditAction::UpdateNav,
+            &[
+                &new_total_assets.to_le_bytes(),
+                &valuation_commitment.unwrap_or_default(),
+            ],
+        )
+    }
+}
+
+/// Discriminators of every instruction that mints or burns shares at the
+/// current NAV. A new deposit or withdraw entry point must be listed here,
+/// or a manager could bundle it with a NAV update.
+const USER_FLOW_INSTRUCTIONS: &[&[u8]] = &[
+    crate::instruction::Deposit::DISCRIMINATOR,
+    crate::instruction::DepositWithNonce::DISCRIMINATOR,
+    crate::instruction::Withdraw::DISCRIMINATOR,
+    crate::instruction::WithdrawAll::DISCRIMINATOR,
+    crate::instruction::WithdrawRouted::DISCRIMINATOR,
+    crate::instruction::ZapDeposit::DISCRIMINATOR,
+    crate::instruction::WithdrawWithSession::DISCRIMINATOR,
+    crate::instruction::ExecuteIntent::DISCRIMINATOR,
+    crate::instruction::ExecuteStopLoss::DISCRIMINATOR,
+    crate::instruction::DepositWithReceipt::DISCRIMINATOR,
+    crate::instruction::WithdrawWithReceipt::DISCRIMINATOR,
+];
+
+/// Discriminators of the instructions that set a vault's NAV
+const NAV_UPDATE_INSTRUCTIONS: &[&[u8]] = &[
+    crate::instruction::UpdateNav::DISCRIMINATOR,
+    crate::instruction::UpdateNavBatch::DISCRIMINATOR,
+];
+
+/// Reject a deposit or withdrawal against `vault` in a transaction that also
+/// updates its NAV. Flows can reach the vault by CPI, where `has_user_flow`
+/// cannot see them from the NAV side, so they check for themselves.
+pub(crate) fn check_no_nav_update(instructions: &AccountInfo, vault: &Pubkey) -> Result<()> {
+    require!(
+        !has_instruction(instructions, vault, NAV_UPDATE_INSTRUCTIONS)?,
+        VaultError::NavUpdateWithUserFlow
+    );
     Ok(())
 }
+
+/// Whether the transaction contains a deposit or withdraw against `vault`
+fn has_user_flow(instructions: &AccountInfo, vault: &Pubkey) -> Result<bool> {
+    has_instruction(instructions, vault, USER_FLOW_INSTRUCTIONS)
+}
+
+/// Whether the transaction contains a top-level call to this program with one
+/// of `discriminators` that names `vault`
+fn has_instruction(
+    instructions: &AccountInfo,
+    vault: &Pubkey,
+    discriminators: &[&[u8]],
+) -> Result<bool> {
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions) {
+        index += 1;
+        if ix.program_id != crate::ID {
+            continue;
+        }
+        let matches = discriminators
+            .iter()
+            .any(|discriminator| ix.data.starts_with(discriminator));
+        if matches && ix.accounts.iter().any(|meta| meta.pubkey == *vault) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
@@ -1,12 +1,18 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as ix_sysvar;
 use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount, Transfer};
 
 use crate::constants::*;
 use crate::error::VaultError;
-use crate::events::Withdrawn;
-use crate::state::VaultState;
-use crate::utils::calculate_assets_to_return;
+use crate::events::{LiquidityLow, WithdrawalRouted, Withdrawn};
+use crate::instructions::strategy::record_report;
+use crate::instructions::update_nav::check_no_nav_update;
+use crate::state::{GlobalStats, RewardsPool, StrategyState, UserPosition, VaultState};
+use crate::utils::{
+    assets_to_return_dust_q64, calculate_assets_to_return, check_stack_height, EventCpi,
+};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
     /// The user withdrawing tokens
@@ -16,17 +22,17 @@ pub struct Withdraw<'info> {
     /// The vault state
     #[account(
         mut,
-        seeds = [VAULT_SEED, vault.mint.as_ref(), vault.owner.as_ref()],
-        bump = vault.bump,
+        seeds = [VAULT_SEED, vault.load()?.mint.as_ref(), vault.load()?.owner.as_ref()],
+        bump = vault.load()?.bump,
     )]
-    pub vault: Box<Account<'info, VaultState>>,
+    pub vault: AccountLoader<'info, VaultState>,
 
     /// The vault's token account (sends underlying tokens to user)
     #[account(
         mut,
         seeds = [VAULT_TOKEN_SEED, vault.key().as_ref()],
-        bump = vault.token_account_bump,
-        token::mint = vault.mint,
+        bump = vault.load()?.token_account_bump,
+        token::mint = vault.load()?.mint,
         token::authority = vault,
     )]
     pub vault_token_account: Account<'info, TokenAccount>,
@@ -34,20 +40,29 @@ pub struct Withdraw<'info> {
     /// The share token mint (shares are burned on withdrawal)
     #[account(
         mut,
-        seeds = [SHARE_MINT_SEED, vault.key().as_ref()],
-        bump = vault.share_mint_bump,
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref(), &vault.load()?.created_slot.to_le_bytes()],
+        bump = vault.load()?.share_mint_bump,
         mint::authority = vault,
     )]
     pub share_mint: Account<'info, Mint>,
 
-    /// The user's token account for the underlying asset (receives withdrawn tokens)
+    /// The user's token account for the underlying asset (receives withdrawn
+    /// tokens unless a `recipient` is given)
     #[account(
         mut,
-        token::mint = vault.mint,
+        token::mint = vault.load()?.mint,
         token::authority = user,
     )]
     pub user_token_account: Account<'info, TokenAccount>,
 
+    /// Underlying token account to pay instead of `user_token_account`, e.g. a
+    /// cold wallet or a payment address. Any owner is accepted.
+    #[account(
+        mut,
+        token::mint = vault.load()?.mint,
+    )]
+    pub recipient: Option<Box<Account<'info, TokenAccount>>>,
+
     /// The user's share token account (shares are burned from here)
     #[account(
         mut,
@@ -56,85 +71,452 @@ pub struct Withdraw<'info> {
     )]
     pub user_share_account: Account<'info, TokenAccount>,
 
+    /// The user's position in this vault (created here for holders who
+    /// received shares by transfer rather than by depositing)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::LEN,
+        seeds = [POSITION_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub position: Box<Account<'info, UserPosition>>,
+
+    /// The vault's rewards pool, if any — pass it so the position's earning
+    /// share balance is checkpointed
+    #[account(
+        mut,
+        seeds = [REWARDS_SEED, vault.key().as_ref()],
+        bump = rewards.bump,
+        has_one = vault,
+    )]
+    pub rewards: Option<Box<Account<'info, RewardsPool>>>,
+
+    /// The protocol stats account, pushed the vault's new assets once the
+    /// admin has created it
+    /// CHECK: address checked by seeds; contents checked by `GlobalStats::sync`
+    #[account(mut, seeds = [GLOBAL_STATS_SEED], bump)]
+    pub global_stats: UncheckedAccount<'info>,
+
+    /// Instructions sysvar, used to reject flows bundled with a NAV update
+    /// CHECK: address is checked against the sysvar id
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<Withdraw>, shares: u64) -> Result<()> {
-    let vault = &ctx.accounts.vault;
+/// Returns the assets paid
+pub fn handler(
+    ctx: Context<Withdraw>,
+    shares: u64,
+    max_loss_bps: u16,
+    memo: Option<[u8; 32]>,
+) -> Result<u64> {
+    redeem_shares(ctx, shares, max_loss_bps, memo, None)
+}
 
-    // Validation
-    require!(!vault.paused, VaultError::VaultPaused);
-    require!(shares > 0, VaultError::InvalidAmount);
-    require!(
-        ctx.accounts.user_share_account.amount >= shares,
-        VaultError::InsufficientShares
-    );
+/// Redeem `shares` for the signer, pricing the exit fee at
+/// `exit_fee_balance` when given. Returns the assets paid.
+fn redeem_shares(
+    ctx: Context<Withdraw>,
+    shares: u64,
+    max_loss_bps: u16,
+    memo: Option<[u8; 32]>,
+    exit_fee_balance: Option<u64>,
+) -> Result<u64> {
+    let recipient = match ctx.accounts.recipient.as_ref() {
+        Some(recipient) => recipient.to_account_info(),
+        None => ctx.accounts.user_token_account.to_account_info(),
+    };
 
-    // Calculate assets to return
-    let assets_to_return =
-        calculate_assets_to_return(shares, vault.total_assets, vault.total_shares)?;
-    require!(assets_to_return > 0, VaultError::InvalidAmount);
+    let assets = ShareRedemption {
+        user: ctx.accounts.user.key(),
+        vault: &ctx.accounts.vault,
+        vault_token_account: &ctx.accounts.vault_token_account,
+        share_mint: &ctx.accounts.share_mint,
+        user_share_account: &ctx.accounts.user_share_account,
+        recipient,
+        position: &mut ctx.accounts.position,
+        position_bump: ctx.bumps.position,
+        rewards: ctx.accounts.rewards.as_deref_mut(),
+        instructions: &ctx.accounts.instructions,
+        exit_fee_balance,
+        token_program: &ctx.accounts.token_program,
+        burn_authority: ctx.accounts.user.to_account_info(),
+        events: EventCpi {
+            authority: &ctx.accounts.event_authority,
+            bump: ctx.bumps.event_authority,
+        },
+    }
+    .redeem(shares, max_loss_bps, memo, &[])?;
 
-    // Check vault has enough liquid assets
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    GlobalStats::sync(&ctx.accounts.global_stats, &mut vault, Clock::get()?.unix_timestamp)?;
+    Ok(assets)
+}
+
+/// Withdraw the user's entire share balance. Returns the assets paid.
+pub fn handle_withdraw_all(
+    ctx: Context<Withdraw>,
+    max_loss_bps: u16,
+    memo: Option<[u8; 32]>,
+) -> Result<u64> {
+    let shares = ctx.accounts.user_share_account.amount;
+    handler(ctx, shares, max_loss_bps, memo)
+}
+
+/// Withdraw as `withdraw` does, first pulling any liquidity shortfall back
+/// from the `(strategy, strategy token account)` pairs in the remaining
+/// accounts. Pairs must be ordered by the strategies' withdraw priority and
+/// each is drawn on up to its instant-withdraw limit, through the vault's
+/// delegation on the strategy token account. Pulls are principal only.
+pub fn handle_withdraw_routed<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Withdraw<'info>>,
+    shares: u64,
+    max_loss_bps: u16,
+    memo: Option<[u8; 32]>,
+) -> Result<()> {
     require!(
-        ctx.accounts.vault_token_account.amount >= assets_to_return,
-        VaultError::InsufficientAssets
+        !ctx.remaining_accounts.is_empty()
+            && ctx.remaining_accounts.chunks_exact(2).remainder().is_empty(),
+        VaultError::InvalidWithdrawRoute
     );
+    let vault_key = ctx.accounts.vault.key();
+    let now = Clock::get()?.unix_timestamp;
+    let events = EventCpi {
+        authority: &ctx.accounts.event_authority,
+        bump: ctx.bumps.event_authority,
+    };
 
-    // Burn user's share tokens (user signs as authority over their token account)
-    token::burn(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Burn {
-                mint: ctx.accounts.share_mint.to_account_info(),
-                from: ctx.accounts.user_share_account.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            },
-        ),
-        shares,
-    )?;
-
-    // Transfer underlying tokens from vault to user (vault PDA signs)
-    let mint_key = ctx.accounts.vault.mint;
-    let owner_key = ctx.accounts.vault.owner;
-    let vault_bump = ctx.accounts.vault.bump;
-    let signer_seeds: &[&[&[u8]]] = &[&[
-        VAULT_SEED,
-        mint_key.as_ref(),
-        owner_key.as_ref(),
-        &[vault_bump],
-    ]];
-
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.vault_token_account.to_account_info(),
-                to: ctx.accounts.user_token_account.to_account_info(),
-                authority: ctx.accounts.vault.to_account_info(),
-            },
-            signer_seeds,
-        ),
-        assets_to_return,
-    )?;
-
-    // Update vault state
-    let vault = &mut ctx.accounts.vault;
-    vault.total_assets = vault
-        .total_assets
-        .checked_sub(assets_to_return)
-        .ok_or(VaultError::ArithmeticOverflow)?;
-    vault.total_shares = vault
-        .total_shares
-        .checked_sub(shares)
-        .ok_or(VaultError::ArithmeticOverflow)?;
-
-    emit!(Withdrawn {
-        vault: vault.key(),
-        user: ctx.accounts.user.key(),
-        shares_burned: shares,
-        amount_returned: assets_to_return,
-    });
+    // Liquidity the redemption needs, including the reserve it may not touch.
+    // The exit fee is priced at the liquidity before the pulls, as a plain
+    // withdrawal would be, not at the liquidity the pulls restore.
+    let liquid = ctx.accounts.vault.load()?.token_balance(ctx.accounts.vault_token_account.amount);
+    let needed = {
+        let vault = ctx.accounts.vault.load()?;
+        require!(!vault.is_locked(), VaultError::Reentrancy);
+        require!(!vault.is_paper(), VaultError::PaperVault);
+        let (assets, _) = redemption_value(&vault, &ctx.accounts.position, shares, liquid, now)?;
+        if assets > vault.small_withdrawal_threshold {
+            assets.saturating_add(vault.liquidity_reserve()?)
+        } else {
+            assets
+        }
+    };
+    let mut shortfall = needed.saturating_sub(liquid);
+
+    let mut pulled = 0u64;
+    let mut drawn = 0u8;
+    let mut previous: Option<(u8, Pubkey)> = None;
+    for accounts in ctx.remaining_accounts.chunks_exact(2) {
+        if shortfall == 0 {
+            break;
+        }
+        let mut strategy = Account::<StrategyState>::try_from(&accounts[0])?;
+        require_keys_eq!(strategy.vault, vault_key, VaultError::InvalidWithdrawRoute);
+        require_keys_eq!(
+            accounts[1].key(),
+            strategy.token_account,
+            VaultError::InvalidWithdrawRoute
+        );
+        // Strictly increasing (priority, key) also rules out repeated strategies
+        let rank = (strategy.withdraw_priority, strategy.key());
+        require!(
+            strategy.instant_withdraw_limit > 0 && previous < Some(rank),
+            VaultError::InvalidWithdrawRoute
+        );
+        previous = Some(rank);
 
+        let strategy_token_account = Account::<TokenAccount>::try_from(&accounts[1])?;
+        let delegated = if strategy_token_account.delegate == Some(vault_key).into() {
+            strategy_token_account.delegated_amount
+        } else {
+            0
+        };
+        let amount = strategy
+            .instant_withdrawable(strategy_token_account.amount, delegated)
+            .min(shortfall);
+        if amount == 0 {
+            continue;
+        }
+
+        let vault = ctx.accounts.vault.load()?;
+        let seeds = vault.signer_seeds();
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: accounts[1].clone(),
+                    to: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+        drop(vault);
+
+        let mut vault = ctx.accounts.vault.load_mut()?;
+        vault.total_debt = vault
+            .total_debt
+            .checked_sub(amount)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        vault.fulfil_deallocation(amount);
+        let new_debt = strategy.current_debt - amount;
+        record_report(&mut vault, &mut strategy, 0, 0, new_debt, now, events)?;
+        drop(vault);
+        strategy.exit(&crate::ID)?;
+
+        shortfall -= amount;
+        pulled += amount;
+        drawn += 1;
+    }
+
+    if pulled > 0 {
+        ctx.accounts.vault_token_account.reload()?;
+        emit_cpi!(WithdrawalRouted {
+            vault: vault_key,
+            event_seq: ctx.accounts.vault.load_mut()?.next_event_seq(),
+            user: ctx.accounts.user.key(),
+            pulled,
+            strategies: drawn,
+        });
+    }
+
+    redeem_shares(ctx, shares, max_loss_bps, memo, Some(liquid))?;
     Ok(())
 }
+
+/// Underlying a holder's `shares` redeem for after the exit fee, and the fee.
+/// Exits soon after a deposit pay the decaying exit fee, and exits while the
+/// vault's liquid `token_balance` is below the liquidity fee curve's kink pay
+/// the curve's exit fee; both stay in the vault for the remaining holders.
+/// Holders without a recorded deposit, whose shares came by transfer, pay the
+/// full first fee, and exits during a wind-down pay neither.
+fn redemption_value(
+    vault: &VaultState,
+    position: &UserPosition,
+    shares: u64,
+    token_balance: u64,
+    now: i64,
+) -> Result<(u64, u64)> {
+    let assets = calculate_assets_to_return(shares, vault.total_assets, vault.total_shares)?;
+    if vault.is_winding_down() {
+        return Ok((assets, 0));
+    }
+    let held = if position.last_deposit_at == 0 {
+        0
+    } else {
+        now.saturating_sub(position.last_deposit_at)
+    };
+    let decaying_fee = vault.exit_fee(assets, held)?;
+    let (_, liquidity_fee_bps) = vault.liquidity_fees_bps(token_balance);
+    let liquidity_fee = (assets as u128 * liquidity_fee_bps as u128 / BPS_DENOMINATOR) as u64;
+    let exit_fee = decaying_fee.saturating_add(liquidity_fee).min(assets);
+    Ok((assets - exit_fee, exit_fee))
+}
+
+/// Accounts for burning a holder's shares against the vault's underlying,
+/// shared by `withdraw` and `withdraw_with_session`
+pub(crate) struct ShareRedemption<'a, 'info> {
+    /// Owner of the shares and the position
+    pub user: Pubkey,
+    pub vault: &'a AccountLoader<'info, VaultState>,
+    pub vault_token_account: &'a Account<'info, TokenAccount>,
+    pub share_mint: &'a Account<'info, Mint>,
+    pub user_share_account: &'a Account<'info, TokenAccount>,
+    /// Underlying token account the assets are paid to
+    pub recipient: AccountInfo<'info>,
+    pub position: &'a mut Account<'info, UserPosition>,
+    pub position_bump: u8,
+    pub rewards: Option<&'a mut Account<'info, RewardsPool>>,
+    /// Instructions sysvar
+    pub instructions: &'a AccountInfo<'info>,
+    /// Liquid balance to price the exit fee at instead of the vault's
+    /// current one, for a redemption that first pulled liquidity back
+    pub exit_fee_balance: Option<u64>,
+    pub token_program: &'a Program<'info, Token>,
+    /// Owner or approved delegate of `user_share_account`
+    pub burn_authority: AccountInfo<'info>,
+    pub events: EventCpi<'a, 'info>,
+}
+
+impl<'a, 'info> ShareRedemption<'a, 'info> {
+    /// Burn `shares` and pay out their value, signing the burn with
+    /// `burn_signer` when the authority is a PDA. Returns the assets paid.
+    ///
+    /// While the vault carries an unrecovered loss, the holder must accept
+    /// it: the redemption fails if the share price is more than
+    /// `max_loss_bps` below its pre-loss level.
+    pub fn redeem(
+        mut self,
+        shares: u64,
+        max_loss_bps: u16,
+        memo: Option<[u8; 32]>,
+        burn_signer: &[&[&[u8]]],
+    ) -> Result<u64> {
+        let vault_key = self.vault.key();
+        let vault = self.vault.load()?;
+        let price_before = vault.share_price_snapshot();
+
+        // Validation
+        vault.status().check_withdrawals()?;
+        require!(!vault.is_locked(), VaultError::Reentrancy);
+        check_stack_height()?;
+        check_no_nav_update(self.instructions, &vault_key)?;
+        require!(!vault.is_epoch_gated(), VaultError::EpochGated);
+        // Exits stay open while the vault winds down
+        require!(
+            vault.is_winding_down() || vault.flows_open(Clock::get()?.unix_timestamp),
+            VaultError::FlowWindowClosed
+        );
+        require!(shares > 0, VaultError::InvalidAmount);
+        require!(
+            u128::from(max_loss_bps) <= BPS_DENOMINATOR,
+            VaultError::InvalidConfig
+        );
+        require!(
+            vault.loss_bps()? <= u64::from(max_loss_bps),
+            VaultError::LossExceedsMax
+        );
+        require!(
+            self.user_share_account.amount >= shares,
+            VaultError::InsufficientShares
+        );
+
+        // Reject deposit -> withdraw round-trips within the same slot (or the
+        // vault's configured delay), which could exploit NAV update ordering.
+        // The same-slot check is vault-wide, since shares may have been
+        // transferred to a wallet whose position never deposited.
+        let slot = Clock::get()?.slot;
+        require!(slot > vault.last_deposit_slot, VaultError::WithdrawTooSoon);
+        let last_deposit_slot = self.position.last_deposit_slot;
+        if last_deposit_slot > 0 {
+            let unlock_slot = last_deposit_slot
+                .checked_add(vault.withdraw_delay_slots)
+                .ok_or(VaultError::ArithmeticOverflow)?;
+            require!(slot > unlock_slot, VaultError::WithdrawTooSoon);
+        }
+
+        // Calculate assets to return, net of any exit fee
+        let liquid = vault.token_balance(self.vault_token_account.amount);
+        let fee_balance = self.exit_fee_balance.unwrap_or(liquid);
+        let (assets_to_return, exit_fee) = redemption_value(
+            &vault,
+            self.position,
+            shares,
+            fee_balance,
+            Clock::get()?.unix_timestamp,
+        )?;
+        require!(assets_to_return > 0, VaultError::InvalidAmount);
+        let dust = assets_to_return_dust_q64(shares, vault.total_assets, vault.total_shares);
+
+        // Check vault has enough liquid assets. Only small withdrawals may draw
+        // on the reserve, so retail exits are served even when liquidity is short.
+        require!(liquid >= assets_to_return, VaultError::InsufficientAssets);
+        if assets_to_return > vault.small_withdrawal_threshold {
+            require!(
+                liquid.saturating_sub(vault.liquidity_reserve()?) >= assets_to_return,
+                VaultError::LiquidityReserved
+            );
+        }
+
+        // Burn the holder's share tokens (owner or delegate signs)
+        token::burn(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Burn {
+                    mint: self.share_mint.to_account_info(),
+                    from: self.user_share_account.to_account_info(),
+                    authority: self.burn_authority,
+                },
+                burn_signer,
+            ),
+            shares,
+        )?;
+
+        // Transfer underlying tokens from vault to the recipient (vault PDA
+        // signs). Paper vaults only simulate the payout.
+        let recipient_key = self.recipient.key();
+        if !vault.is_paper() {
+            let seeds = vault.signer_seeds();
+            let signer_seeds: &[&[&[u8]]] = &[&seeds];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    Transfer {
+                        from: self.vault_token_account.to_account_info(),
+                        to: self.recipient,
+                        authority: self.vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                assets_to_return,
+            )?;
+        }
+
+        // Update vault state
+        drop(vault);
+        let mut vault = self.vault.load_mut()?;
+        vault.record_outflow(assets_to_return, Clock::get()?.unix_timestamp)?;
+        if let Some(rewards) = self.rewards.as_deref_mut() {
+            rewards.accrue(vault.total_shares, Clock::get()?.unix_timestamp)?;
+        }
+        vault.total_assets = vault
+            .total_assets
+            .checked_sub(assets_to_return)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        vault.total_shares = vault
+            .total_shares
+            .checked_sub(shares)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        vault.add_dust(dust)?;
+        vault.debit_paper_balance(assets_to_return)?;
+
+        let position = self.position;
+        position.ensure_initialized(vault_key, self.user, self.position_bump);
+        if let Some(rewards) = self.rewards.as_deref() {
+            // The burn has already been checked against this balance
+            let balance = self.user_share_account.amount;
+            position.checkpoint_rewards(rewards, balance, balance - shares)?;
+        }
+        position.total_withdrawn = position
+            .total_withdrawn
+            .checked_add(assets_to_return)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        // Shares held since before the last fee increase leave the grace pool
+        if position.fee_epoch < vault.fee_epoch {
+            vault.grandfathered_shares = vault.grandfathered_shares.saturating_sub(shares);
+        }
+
+        vault.assert_invariants(Some(price_before))?;
+
+        self.events.emit(Withdrawn {
+            vault: vault_key,
+            event_seq: vault.next_event_seq(),
+            user: self.user,
+            recipient: recipient_key,
+            shares_burned: shares,
+            amount_returned: assets_to_return,
+            exit_fee,
+            memo,
+        })?;
+
+        // Signal keepers to request a deallocation once liquidity drops below target
+        let liquid_after = liquid - assets_to_return;
+        let target = vault.liquidity_target()?;
+        if liquid_after < target {
+            self.events.emit(LiquidityLow {
+                vault: vault_key,
+                event_seq: vault.next_event_seq(),
+                liquid_assets: liquid_after,
+                target,
+            })?;
+        }
+
+        Ok(assets_to_return)
+    }
+}
@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+use anchor_spl::token::TokenAccount;
+
+use crate::constants::*;
+use crate::error::VaultError;
+use crate::events::ZapDeposited;
+use crate::instructions::deposit::{self, *};
+use crate::state::ProtocolConfig;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ZapDeposit<'info> {
+    /// The regular deposit accounts; `deposit.user` also signs the swap
+    pub deposit: Deposit<'info>,
+
+    /// Holds the DEX whitelist
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump = protocol_config.bump,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    /// The user's account for the token being swapped in
+    #[account(
+        mut,
+        token::authority = deposit.user,
+        constraint = input_token_account.mint != deposit.vault_token_account.mint
+            @ VaultError::ZapInputIsUnderlying,
+    )]
+    pub input_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// The whitelisted DEX program performing the swap
+    /// CHECK: checked against the protocol whitelist in the handler
+    #[account(executable)]
+    pub dex_program: UncheckedAccount<'info>,
+}
+
+/// Swap `input_token_account` into the vault's underlying through
+/// `dex_program`, then deposit exactly what the swap delivered.
+///
+/// `swap_data` is passed to the DEX verbatim with `remaining_accounts` as its
+/// account list; the route must deliver into `deposit.user_token_account`.
+/// The proceeds are measured from that account's balance, so a route that
+/// pays elsewhere deposits nothing and fails `min_out`.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, ZapDeposit<'info>>,
+    swap_data: Vec<u8>,
+    min_out: u64,
+    memo: Option<[u8; 32]>,
+) -> Result<()> {
+    let dex_program = ctx.accounts.dex_program.key();
+    require!(
+        ctx.accounts.protocol_config.is_zap_program(&dex_program),
+        VaultError::ZapProgramNotWhitelisted
+    );
+    require!(min_out > 0, VaultError::InvalidAmount);
+    require!(
+        !ctx.accounts.deposit.vault.load()?.is_paper(),
+        VaultError::PaperVault
+    );
+
+    let input_before = ctx.accounts.input_token_account.amount;
+    let underlying_before = ctx.accounts.deposit.user_token_account.amount;
+
+    // Only the user's signature is forwarded; the vault PDA never signs the swap
+    let metas = ctx
+        .remaining_accounts
+        .iter()
+        .map(|info| AccountMeta {
+            pubkey: info.key(),
+            is_signer: info.is_signer,
+            is_writable: info.is_writable,
+        })
+        .collect();
+    // The DEX is outside our control: lock the vault so nothing it calls can
+    // move funds in or out until the swap returns
+    ctx.accounts.deposit.vault.load_mut()?.lock()?;
+    invoke(
+        &Instruction {
+            program_id: dex_program,
+            accounts: metas,
+            data: swap_data,
+        },
+        ctx.remaining_accounts,
+    )?;
+    ctx.accounts.deposit.vault.load_mut()?.unlock();
+
+    ctx.accounts.input_token_account.reload()?;
+    ctx.accounts.deposit.user_token_account.reload()?;
+
+    let input_spent = input_before
+        .checked_sub(ctx.accounts.input_token_account.amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    let received = ctx
+        .accounts
+        .deposit
+        .user_token_account
+        .amount
+        .checked_sub(underlying_before)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    require!(received >= min_out, VaultError::SlippageExceeded);
+
+    let vault_key = ctx.accounts.deposit.vault.key();
+    let user_key = ctx.accounts.deposit.user.key();
+    let input_mint = ctx.accounts.input_token_account.mint;
+
+    deposit::handler(
+        Context::new(ctx.program_id, &mut ctx.accounts.deposit, &[], ctx.bumps.deposit),
+        received,
+        memo,
+    )?;
+
+    emit_cpi!(ZapDeposited {
+        vault: vault_key,
+        event_seq: ctx.accounts.deposit.vault.load_mut()?.next_event_seq(),
+        user: user_key,
+        input_mint,
+        input_amount: input_spent,
+        amount: received,
+        dex_program,
+    });
+
+    Ok(())
+}
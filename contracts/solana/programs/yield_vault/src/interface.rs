@@ -0,0 +1,133 @@
+//! Calling the vault from other programs.
+//!
+//! Enable the `cpi` feature to depend on this crate from another Anchor
+//! program (e.g. a zap router). `deposit` and `withdraw` are invoked through
+//! the Anchor-generated `crate::cpi` module; this module adds the PDA
+//! derivations a caller needs and thin wrappers that build the `CpiContext`.
+//!
+//! Signer expectations:
+//! - `user` must sign. A router acting for itself passes its own PDA and signs
+//!   with its seeds; the PDA then owns the resulting position and shares.
+//! - `user` also pays rent for the position and its `portfolio()` on first use,
+//!   so a PDA `user` must be a system-owned account holding enough lamports.
+//! - `user_token_account` / `user_share_account` must be owned by `user`.
+//!   Withdrawals may instead pay any underlying account passed as `recipient`.
+//! - Pass the rewards pool as `rewards` when the vault runs a campaign, or the
+//!   position earns nothing until its next checkpoint.
+//! - Pass the vault's `price_oracle` as `oracle` when one is configured;
+//!   deposits fail with `OracleUntrusted` while its confidence is too wide.
+//! - Pass the vault's market reserves when a market guard is configured;
+//!   deposits fail with `MarketBelowNav` while shares trade too far below NAV.
+//! - Pass `wallet_flag(user)` as `wallet_flag` on every deposit, whether or not
+//!   the account exists; deposits fail with `WalletSanctioned` or
+//!   `WalletGeofenced` once the protocol admin has flagged `user`.
+//! - Pass `user`'s Civic Pass as `gateway_token` when the vault is
+//!   permissioned; deposits fail with `CredentialRequired` without one.
+//! - Pass the instructions sysvar as `instructions`; deposits and withdrawals
+//!   fail with `NavUpdateWithUserFlow` in a transaction that also updates the
+//!   vault's NAV.
+//! - Pass `global_stats()` as `global_stats`, writable; the vault pushes its
+//!   new assets to it once the protocol admin has created it.
+//! - Pass `event_authority()` and the vault program itself as `event_authority`
+//!   / `program`; the vault emits its events through a CPI to itself.
+//!
+//! Epoch-gated vaults reject direct deposits and withdrawals; callers must use
+//! the `request_deposit` / `request_redeem` flow instead.
+
+use anchor_lang::prelude::*;
+
+use crate::constants::*;
+use crate::cpi::accounts::{Deposit, Withdraw};
+
+/// Vault token account PDA
+pub fn vault_token_account(vault: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[VAULT_TOKEN_SEED, vault.as_ref()], &crate::ID).0
+}
+
+/// Share mint PDA for a vault created in `created_slot`
+pub fn share_mint(vault: &Pubkey, created_slot: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[SHARE_MINT_SEED, vault.as_ref(), &created_slot.to_le_bytes()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// Position PDA of `user` in `vault`
+pub fn position(vault: &Pubkey, user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[POSITION_SEED, vault.as_ref(), user.as_ref()], &crate::ID).0
+}
+
+/// Portfolio PDA listing the vaults `user` holds positions in
+pub fn portfolio(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[PORTFOLIO_SEED, user.as_ref()], &crate::ID).0
+}
+
+/// Compliance flag PDA of `user`, which need not exist
+pub fn wallet_flag(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[WALLET_FLAG_SEED, user.as_ref()], &crate::ID).0
+}
+
+/// Rewards pool PDA of `vault`
+pub fn rewards(vault: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[REWARDS_SEED, vault.as_ref()], &crate::ID).0
+}
+
+/// Protocol stats PDA every vault pushes its assets to
+pub fn global_stats() -> Pubkey {
+    Pubkey::find_program_address(&[GLOBAL_STATS_SEED], &crate::ID).0
+}
+
+/// Event authority PDA the vault signs its event CPIs with
+pub fn event_authority() -> Pubkey {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], &crate::ID).0
+}
+
+/// Deposit `amount` on behalf of `accounts.user`, which signs with `signer_seeds`
+/// (pass `&[]` when `user` signed the outer transaction)
+pub fn deposit<'info>(
+    program: AccountInfo<'info>,
+    accounts: Deposit<'info>,
+    amount: u64,
+    memo: Option<[u8; 32]>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    crate::cpi::deposit(
+        CpiContext::new_with_signer(program, accounts, signer_seeds),
+        amount,
+        memo,
+    )
+}
+
+/// Redeem `shares` on behalf of `accounts.user`, which signs with `signer_seeds`
+/// (pass `&[]` when `user` signed the outer transaction)
+pub fn withdraw<'info>(
+    program: AccountInfo<'info>,
+    accounts: Withdraw<'info>,
+    shares: u64,
+    max_loss_bps: u16,
+    memo: Option<[u8; 32]>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    crate::cpi::withdraw(
+        CpiContext::new_with_signer(program, accounts, signer_seeds),
+        shares,
+        max_loss_bps,
+        memo,
+    )
+}
+
+/// Redeem every share `accounts.user` holds, as read when the vault executes
+pub fn withdraw_all<'info>(
+    program: AccountInfo<'info>,
+    accounts: Withdraw<'info>,
+    max_loss_bps: u16,
+    memo: Option<[u8; 32]>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    crate::cpi::withdraw_all(
+        CpiContext::new_with_signer(program, accounts, signer_seeds),
+        max_loss_bps,
+        memo,
+    )
+}
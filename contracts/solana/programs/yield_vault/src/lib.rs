@@ -4,10 +4,17 @@ pub mod constants;
 pub mod error;
 pub mod events;
 pub mod instructions;
+#[cfg(feature = "cpi")]
+pub mod interface;
 pub mod state;
 pub mod utils;
 
 use instructions::*;
+use state::{
+    AuditRecord, ComplianceFlag, FeePayoutMode, FeeReport, Groth16Proof, Groth16VerifyingKey,
+    IntentKind, PolicyRule, RedemptionQueue, ShareValue, SlashReason, StrategyMetadata,
+    StrategyVersion,
+};
 
 declare_id!("VLT1111111111111111111111111111111111111111");
 
@@ -23,33 +30,230 @@ pub mod yield_vault {
         instructions::initialize::handle_create_vault(ctx, params)
     }
 
-    /// Step 2 of vault initialization: create share mint and vault token account PDAs.
+    /// Step 1 of vault initialization using the protocol template's defaults.
+    /// The vault stays bound by the protocol fee limits.
+    pub fn create_vault_from_template(ctx: Context<CreateVaultFromTemplate>) -> Result<()> {
+        instructions::protocol::handle_create_vault_from_template(ctx)
+    }
+
+    /// Step 2 of vault initialization: create share mint, vault token account,
+    /// fee report, price history, strategy registry and audit log PDAs.
     pub fn init_vault_accounts(ctx: Context<InitVaultAccounts>) -> Result<()> {
         instructions::initialize::handle_init_vault_accounts(ctx)
     }
 
+    /// Close a vault whose step 2 never ran and reclaim its rent. Owner only.
+    pub fn cancel_init(ctx: Context<CancelInit>) -> Result<()> {
+        instructions::initialize::handle_cancel_init(ctx)
+    }
+
     /// Deposit underlying tokens into the vault and receive share tokens.
-    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-        instructions::deposit::handler(ctx, amount)
+    /// An optional `memo` is echoed in the `Deposited` event for reconciliation.
+    /// Callable via CPI; see `interface` for signer expectations.
+    pub fn deposit(ctx: Context<Deposit>, amount: u64, memo: Option<[u8; 32]>) -> Result<()> {
+        instructions::deposit::handler(ctx, amount, memo)?;
+        Ok(())
+    }
+
+    /// Deposit as `deposit` does, rejecting a `nonce` not above the last one
+    /// recorded on the user's position, so auto-retried transactions cannot
+    /// deposit twice
+    pub fn deposit_with_nonce(
+        ctx: Context<DepositWithNonce>,
+        amount: u64,
+        memo: Option<[u8; 32]>,
+        nonce: u64,
+    ) -> Result<()> {
+        instructions::deposit::handle_deposit_with_nonce(ctx, amount, memo, nonce)?;
+        Ok(())
+    }
+
+    /// Swap another token into the underlying through a protocol-whitelisted
+    /// DEX and deposit the proceeds in one instruction. `swap_data` and the
+    /// remaining accounts form the DEX instruction; at least `min_out`
+    /// underlying must reach the user's token account.
+    pub fn zap_deposit<'info>(
+        ctx: Context<'_, '_, '_, 'info, ZapDeposit<'info>>,
+        swap_data: Vec<u8>,
+        min_out: u64,
+        memo: Option<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::zap::handler(ctx, swap_data, min_out, memo)
+    }
+
+    /// Burn share tokens and withdraw underlying tokens from the vault, paid to
+    /// the optional `recipient` token account or else the user's own.
+    /// After a reported loss, fails unless the user accepts a share price up to
+    /// `max_loss_bps` below the pre-loss price.
+    /// An optional `memo` is echoed in the `Withdrawn` event for reconciliation.
+    /// Callable via CPI; see `interface` for signer expectations.
+    pub fn withdraw(
+        ctx: Context<Withdraw>,
+        shares: u64,
+        max_loss_bps: u16,
+        memo: Option<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::withdraw::handler(ctx, shares, max_loss_bps, memo)?;
+        Ok(())
+    }
+
+    /// Withdraw as `withdraw` does, redeeming the user's whole share balance
+    /// as read on-chain rather than a count a client may have read stale
+    pub fn withdraw_all(
+        ctx: Context<Withdraw>,
+        max_loss_bps: u16,
+        memo: Option<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::withdraw::handle_withdraw_all(ctx, max_loss_bps, memo)?;
+        Ok(())
+    }
+
+    /// Withdraw as `withdraw` does, first pulling any liquidity shortfall from
+    /// the `(strategy, strategy token account)` pairs in the remaining
+    /// accounts, in withdraw priority order and within each strategy's
+    /// instant-withdraw limit.
+    pub fn withdraw_routed<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Withdraw<'info>>,
+        shares: u64,
+        max_loss_bps: u16,
+        memo: Option<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::withdraw::handle_withdraw_routed(ctx, shares, max_loss_bps, memo)
+    }
+
+    /// Grant `session_key` permission to withdraw up to `max_amount` of
+    /// underlying to the user's own token account until `expires_at`.
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        session_key: Pubkey,
+        max_amount: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::session::handle_create_session(ctx, session_key, max_amount, expires_at)
+    }
+
+    /// Withdraw a user's shares, signed by one of their session keys.
+    pub fn withdraw_with_session(
+        ctx: Context<WithdrawWithSession>,
+        shares: u64,
+        max_loss_bps: u16,
+        memo: Option<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::session::handle_withdraw_with_session(ctx, shares, max_loss_bps, memo)
+    }
+
+    /// Close a session and revoke its share delegation. User only.
+    pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+        instructions::session::handle_revoke_session(ctx)
+    }
+
+    /// Post a standing order to deposit underlying at or below a share price,
+    /// or redeem shares at or above one. The intent PDA becomes the source
+    /// account's delegate for the order size.
+    pub fn post_intent(
+        ctx: Context<PostIntent>,
+        id: u64,
+        kind: IntentKind,
+        amount: u64,
+        limit_price_q64: u128,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::intent::handle_post_intent(ctx, id, kind, amount, limit_price_q64, expires_at)
+    }
+
+    /// Fill an intent whose price condition holds, paying the crank bounty
+    /// when its accounts are given. Withdrawals are capped at what the
+    /// vault's outflow limit still allows. Permissionless.
+    pub fn execute_intent(ctx: Context<ExecuteIntent>, amount: u64) -> Result<()> {
+        instructions::intent::handle_execute_intent(ctx, amount)
+    }
+
+    /// Close an intent and revoke its delegation. User only.
+    pub fn cancel_intent(ctx: Context<CancelIntent>) -> Result<()> {
+        instructions::intent::handle_cancel_intent(ctx)
+    }
+
+    /// Set (or with `stop_loss_bps = 0` clear) a stop-loss on the caller's
+    /// position, measured from the current share price. User only.
+    pub fn set_stop_loss(
+        ctx: Context<SetStopLoss>,
+        stop_loss_bps: u16,
+        bounty_bps: u16,
+    ) -> Result<()> {
+        instructions::stop_loss::handle_set_stop_loss(ctx, stop_loss_bps, bounty_bps)
+    }
+
+    /// Exit a position whose stop-loss has triggered, paying the crank its
+    /// bounty out of the exit. Permissionless.
+    pub fn execute_stop_loss(ctx: Context<ExecuteStopLoss>) -> Result<()> {
+        instructions::stop_loss::handle_execute_stop_loss(ctx)
+    }
+
+    /// Close an emptied user position, optionally closing the share token
+    /// account too, and refund the rent to the user.
+    pub fn close_position(ctx: Context<ClosePosition>, close_share_account: bool) -> Result<()> {
+        instructions::close_position::handler(ctx, close_share_account)
     }
 
-    /// Burn share tokens and withdraw underlying tokens from the vault.
-    pub fn withdraw(ctx: Context<Withdraw>, shares: u64) -> Result<()> {
-        instructions::withdraw::handler(ctx, shares)
+    /// Donate underlying tokens to the vault without minting shares.
+    pub fn donate(ctx: Context<Donate>, amount: u64) -> Result<()> {
+        instructions::donate::handler(ctx, amount)
+    }
+
+    /// Reconcile untracked token balances: absorb surplus into NAV, or sweep
+    /// the balance to the owner's treasury when no shares are outstanding.
+    /// Permissionless.
+    pub fn skim(ctx: Context<Skim>) -> Result<()> {
+        instructions::skim::handler(ctx)
     }
 
     /// Update the vault's net asset value. Only callable by owner or authorized manager.
     /// Accrues management and performance fees automatically.
-    pub fn update_nav(ctx: Context<UpdateNav>, new_total_assets: u64) -> Result<()> {
-        instructions::update_nav::handler(ctx, new_total_assets)
+    /// Rejected if the transaction also deposits into or withdraws from the vault.
+    /// Managers pass their reputation profile, which records the update.
+    /// When the vault enforces proofs of reserves, large increases must be
+    /// covered by the latest proof (see `prove_reserves`).
+    /// An optional `valuation_commitment` (hash of the valuation inputs and
+    /// model output kept off-chain) is echoed in the `NavUpdated` event.
+    pub fn update_nav(
+        ctx: Context<UpdateNav>,
+        new_total_assets: u64,
+        valuation_commitment: Option<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::update_nav::handler(ctx, new_total_assets, valuation_commitment)
     }
 
-    /// Collect accrued fees by minting new share tokens to the treasury.
+    /// Collect accrued fees, by minting new share tokens to the treasury or by
+    /// transferring underlying tokens to it, per the vault's fee payout mode.
     /// Only callable by the vault owner.
     pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
         instructions::collect_fees::handler(ctx)
     }
 
+    /// Mint the treasury the shares worth the rounding dust share conversions
+    /// have accumulated, so value lost to truncation is accounted for.
+    /// Only callable by the vault owner.
+    pub fn sweep_dust(ctx: Context<SweepDust>) -> Result<()> {
+        instructions::sweep_dust::handler(ctx)
+    }
+
+    /// Return the vault's lifetime fee accrual and crystallization report. Read-only.
+    pub fn get_fee_report(ctx: Context<GetFeeReport>) -> Result<FeeReport> {
+        instructions::fee_report::handler(ctx)
+    }
+
+    /// Return what the holder's shares are worth at the current NAV and net
+    /// of the fees accrued but not yet collected. Read-only.
+    pub fn get_share_value(ctx: Context<GetShareValue>) -> Result<ShareValue> {
+        instructions::fee_report::handle_get_share_value(ctx)
+    }
+
+    /// Return a page of the vault's audit log of administrative actions,
+    /// starting `offset` entries after the oldest retained one. Read-only.
+    pub fn get_audit_log(ctx: Context<GetAuditLog>, offset: u32) -> Result<Vec<AuditRecord>> {
+        instructions::audit_log::handler(ctx, offset)
+    }
+
     /// Pause the vault — disables deposits and withdrawals. Owner only.
     pub fn pause(ctx: Context<Pause>) -> Result<()> {
         instructions::admin::handle_pause(ctx)
@@ -60,11 +264,146 @@ pub mod yield_vault {
         instructions::admin::handle_unpause(ctx)
     }
 
+    /// Begin winding down the vault: deposits and allocations stop, strategies
+    /// must be deallocated, and fees stop accruing. Required before closing. Owner only.
+    pub fn begin_winddown(ctx: Context<BeginWinddown>) -> Result<()> {
+        instructions::admin::handle_begin_winddown(ctx)
+    }
+
     /// Update vault configuration parameters. Owner only.
     pub fn update_config(ctx: Context<UpdateConfig>, params: UpdateConfigParams) -> Result<()> {
         instructions::admin::handle_update_config(ctx, params)
     }
 
+    /// Ramp the deposit cap from `start_cap`, adding `increment` every
+    /// `interval` seconds up to `deposit_cap` (if set), for guarded launches.
+    /// `interval = 0` clears the schedule. Owner only.
+    pub fn schedule_deposit_cap(
+        ctx: Context<ScheduleDepositCap>,
+        start_cap: u64,
+        increment: u64,
+        interval: i64,
+    ) -> Result<()> {
+        instructions::admin::handle_schedule_deposit_cap(ctx, start_cap, increment, interval)
+    }
+
+    /// Only accept deposits and withdrawals between the `open` and `close`
+    /// offsets (seconds, UTC-aligned) of every `period`; all zero = always
+    /// open. Exits stay open during wind-down. Owner only.
+    pub fn set_flow_window(
+        ctx: Context<SetFlowWindow>,
+        period: i64,
+        open: i64,
+        close: i64,
+    ) -> Result<()> {
+        instructions::admin::handle_set_flow_window(ctx, period, open, close)
+    }
+
+    /// Charge withdrawals within `full_period` seconds of the holder's last
+    /// deposit a `fee_bps` exit fee, decaying linearly to zero at `decay_end`
+    /// seconds. Holders with no deposit of their own pay the full fee. The fee
+    /// stays in the vault; exits during wind-down are exempt. Owner only.
+    pub fn set_exit_fee(
+        ctx: Context<SetExitFee>,
+        fee_bps: u16,
+        full_period: i64,
+        decay_end: i64,
+    ) -> Result<()> {
+        instructions::admin::handle_set_exit_fee(ctx, fee_bps, full_period, decay_end)
+    }
+
+    /// Set the liquidity fee curve: as the liquid share of assets falls below
+    /// `kink_bps`, the exit fee rises linearly to `max_exit_fee_bps` and the
+    /// entry fee falls from `max_entry_fee_bps` to zero. Both fees stay in the
+    /// vault; exits during wind-down are exempt. Owner only.
+    pub fn set_liquidity_fee_curve(
+        ctx: Context<SetLiquidityFeeCurve>,
+        kink_bps: u16,
+        max_entry_fee_bps: u16,
+        max_exit_fee_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::handle_set_liquidity_fee_curve(
+            ctx,
+            kink_bps,
+            max_entry_fee_bps,
+            max_exit_fee_bps,
+        )
+    }
+
+    /// Set how many slots a user must wait after depositing before withdrawing.
+    /// Same-slot withdrawals are always rejected. Owner only.
+    pub fn set_withdraw_delay(ctx: Context<SetWithdrawDelay>, slots: u64) -> Result<()> {
+        instructions::admin::handle_set_withdraw_delay(ctx, slots)
+    }
+
+    /// Set the share of assets kept undeployed and the size up to which
+    /// withdrawals may draw on it. Owner only.
+    pub fn set_liquidity_reserve(
+        ctx: Context<SetLiquidityReserve>,
+        reserve_ratio_bps: u16,
+        small_withdrawal_threshold: u64,
+    ) -> Result<()> {
+        instructions::admin::handle_set_liquidity_reserve(
+            ctx,
+            reserve_ratio_bps,
+            small_withdrawal_threshold,
+        )
+    }
+
+    /// Cap the underlying withdrawals and epoch settlements may pay out per
+    /// 24-hour window, in absolute terms and as a share of total assets
+    /// (0 = no limit). An instant withdrawal over the cap fails, as it holds
+    /// nothing in custody to queue; withdraw intents and epoch redemptions
+    /// fill as far as the window allows and keep the rest queued. Owner only.
+    pub fn set_outflow_limit(
+        ctx: Context<SetOutflowLimit>,
+        max_window_outflow: u64,
+        max_window_outflow_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::handle_set_outflow_limit(
+            ctx,
+            max_window_outflow,
+            max_window_outflow_bps,
+        )
+    }
+
+    /// Cap the underlying deposits may bring in per hour, so the manager can
+    /// deploy inflows gradually (0 = no limit). Owner only.
+    pub fn set_inflow_limit(ctx: Context<SetInflowLimit>, max_window_inflow: u64) -> Result<()> {
+        instructions::admin::handle_set_inflow_limit(ctx, max_window_inflow)
+    }
+
+    /// Set the liquid share of assets the vault aims to hold and how long the
+    /// manager has to fulfil a deallocation request. Owner only.
+    pub fn set_liquidity_target(
+        ctx: Context<SetLiquidityTarget>,
+        target_liquidity_bps: u16,
+        deallocation_window: i64,
+    ) -> Result<()> {
+        instructions::admin::handle_set_liquidity_target(
+            ctx,
+            target_liquidity_bps,
+            deallocation_window,
+        )
+    }
+
+    /// Choose whether `collect_fees` mints shares to the treasury or pays it
+    /// in underlying tokens from idle liquidity. Owner only.
+    pub fn set_fee_payout_mode(ctx: Context<SetFeePayoutMode>, mode: FeePayoutMode) -> Result<()> {
+        instructions::admin::handle_set_fee_payout_mode(ctx, mode)
+    }
+
+    /// Donate `donation_bps` of collected fees to a charity or public-goods
+    /// `recipient`, for impact vaults (default key and 0 = no donation).
+    /// Owner only.
+    pub fn set_fee_donation(
+        ctx: Context<SetFeeDonation>,
+        recipient: Pubkey,
+        donation_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::handle_set_fee_donation(ctx, recipient, donation_bps)
+    }
+
     /// Add an authorized manager to the vault. Owner only.
     pub fn add_manager(ctx: Context<AddManager>, manager: Pubkey) -> Result<()> {
         instructions::admin::handle_add_manager(ctx, manager)
@@ -75,6 +414,672 @@ pub mod yield_vault {
         instructions::admin::handle_remove_manager(ctx, manager)
     }
 
+    /// Set the bond managers must post before being added and the appeal
+    /// period slashes wait out (0 = no bond). Owner only.
+    pub fn set_manager_bond(
+        ctx: Context<SetManagerBond>,
+        min_bond: u64,
+        appeal_period: i64,
+    ) -> Result<()> {
+        instructions::admin::handle_set_manager_bond(ctx, min_bond, appeal_period)
+    }
+
+    /// Add underlying to the signer's manager bond in the vault.
+    pub fn post_bond(ctx: Context<PostBond>, amount: u64) -> Result<()> {
+        instructions::bond::handle_post_bond(ctx, amount)
+    }
+
+    /// Propose slashing a manager's bond for a proven NAV misreport or
+    /// unauthorized allocation. Executable after the appeal period. Owner only.
+    pub fn propose_slash(
+        ctx: Context<ProposeSlash>,
+        amount: u64,
+        reason: SlashReason,
+    ) -> Result<()> {
+        instructions::bond::handle_propose_slash(ctx, amount, reason)
+    }
+
+    /// Drop a pending slash, e.g. after an upheld appeal. Owner only.
+    pub fn cancel_slash(ctx: Context<CancelSlash>) -> Result<()> {
+        instructions::bond::handle_cancel_slash(ctx)
+    }
+
+    /// Move a pending slash into the vault once its appeal period has ended.
+    /// Permissionless.
+    pub fn execute_slash(ctx: Context<ExecuteSlash>) -> Result<()> {
+        instructions::bond::handle_execute_slash(ctx)
+    }
+
+    /// Withdraw a manager bond. Only once the key no longer manages the vault
+    /// and no slash is pending.
+    pub fn withdraw_bond(ctx: Context<WithdrawBond>, amount: u64) -> Result<()> {
+        instructions::bond::handle_withdraw_bond(ctx, amount)
+    }
+
+    /// Remove a manager for cause, recording a slashing incident on its
+    /// reputation profile. Owner only.
+    pub fn slash_manager(ctx: Context<SlashManager>, manager: Pubkey) -> Result<()> {
+        instructions::admin::handle_slash_manager(ctx, manager)
+    }
+
+    /// Register a strategy the vault can allocate funds to. The strategy id
+    /// must be on the protocol strategy whitelist. Owner only.
+    pub fn add_strategy(ctx: Context<AddStrategy>, strategy_id: Pubkey) -> Result<()> {
+        instructions::strategy::handle_add_strategy(ctx, strategy_id)
+    }
+
+    /// Block new allocations to strategies that have not reported for
+    /// `seconds` (0 = no limit). Owner only.
+    pub fn set_max_report_age(ctx: Context<SetMaxReportAge>, seconds: i64) -> Result<()> {
+        instructions::admin::handle_set_max_report_age(ctx, seconds)
+    }
+
+    /// Check deposits and NAV updates against a Pyth price account, rejecting
+    /// them with `OracleUntrusted` while its confidence interval exceeds
+    /// `max_conf_bps` of the price and `OracleStale` while it is older than
+    /// the feed's staleness limit. The oracle must be the feed registered
+    /// for the vault's asset (default key = no oracle). Owner only.
+    pub fn set_price_oracle(
+        ctx: Context<SetPriceOracle>,
+        oracle: Pubkey,
+        max_conf_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::handle_set_price_oracle(ctx, oracle, max_conf_bps)
+    }
+
+    /// Install the verifying key of the vault's proof-of-reserves circuit and
+    /// the NAV increase limits it enforces. Owner only.
+    pub fn set_reserves_verifier(
+        ctx: Context<SetReservesVerifier>,
+        verifying_key: Groth16VerifyingKey,
+        max_unproven_increase_bps: u16,
+        max_proof_age: i64,
+        enforce: bool,
+    ) -> Result<()> {
+        instructions::reserves::handle_set_reserves_verifier(
+            ctx,
+            verifying_key,
+            max_unproven_increase_bps,
+            max_proof_age,
+            enforce,
+        )
+    }
+
+    /// Verify a Groth16 proof that strategy positions across external venues
+    /// held at least `reserves` at `observed_at`, and record it for
+    /// `update_nav`. Owner or manager.
+    pub fn prove_reserves(
+        ctx: Context<ProveReserves>,
+        proof: Groth16Proof,
+        reserves: u64,
+        observed_at: i64,
+    ) -> Result<()> {
+        instructions::reserves::handle_prove_reserves(ctx, proof, reserves, observed_at)
+    }
+
+    /// Update the NAV of several vaults in one transaction, each checked as in
+    /// `update_nav`; remaining accounts are `(vault, fee report, price
+    /// history, audit log, oracle, reserves verifier)` groups, with the program
+    /// id for an absent oracle or verifier. Owner or manager of every vault.
+    pub fn update_nav_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateNavBatch<'info>>,
+        new_total_assets: Vec<u64>,
+        valuation_commitment: Option<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::update_nav::handle_update_nav_batch(
+            ctx,
+            new_total_assets,
+            valuation_commitment,
+        )
+    }
+
+    /// Require depositors to hold a Civic Pass of `gatekeeper_network`, checked
+    /// on-chain in every deposit path (default key = open vault). Owner only.
+    pub fn set_gatekeeper_network(
+        ctx: Context<SetGatekeeperNetwork>,
+        gatekeeper_network: Pubkey,
+    ) -> Result<()> {
+        instructions::admin::handle_set_gatekeeper_network(ctx, gatekeeper_network)
+    }
+
+    /// Create the vault's crank bounty budget, paying `bounty` underlying per
+    /// permissionless crank at most once per `min_interval` seconds for each
+    /// crank kind. Owner only.
+    pub fn init_crank_bounty(
+        ctx: Context<InitCrankBounty>,
+        bounty: u64,
+        min_interval: i64,
+    ) -> Result<()> {
+        instructions::crank::handle_init_crank_bounty(ctx, bounty, min_interval)
+    }
+
+    /// Change the crank bounty and its minimum interval. Owner only.
+    pub fn set_crank_bounty(
+        ctx: Context<SetCrankBounty>,
+        bounty: u64,
+        min_interval: i64,
+    ) -> Result<()> {
+        instructions::crank::handle_set_crank_bounty(ctx, bounty, min_interval)
+    }
+
+    /// Add underlying to the crank bounty budget. Owner only.
+    pub fn fund_crank_bounty(ctx: Context<FundCrankBounty>, amount: u64) -> Result<()> {
+        instructions::crank::handle_fund_crank_bounty(ctx, amount)
+    }
+
+    /// Withdraw underlying from the crank bounty budget back to the owner.
+    /// Owner only.
+    pub fn defund_crank_bounty(ctx: Context<DefundCrankBounty>, amount: u64) -> Result<()> {
+        instructions::crank::handle_defund_crank_bounty(ctx, amount)
+    }
+
+    /// Commit to the SHA-256 of the agent policy file depositors approved;
+    /// agents refuse to run on a config that hashes to anything else (zero
+    /// clears the commitment). Owner only.
+    pub fn set_policy_commitment(
+        ctx: Context<SetPolicyCommitment>,
+        commitment: [u8; 32],
+    ) -> Result<()> {
+        instructions::admin::handle_set_policy_commitment(ctx, commitment)
+    }
+
+    /// Re-point the vault's oracle at the feed registered for its asset.
+    /// Permissionless.
+    pub fn sync_price_feed(ctx: Context<SyncPriceFeed>) -> Result<()> {
+        instructions::admin::handle_sync_price_feed(ctx)
+    }
+
+    /// Charge performance fees only on returns above the index priced by the
+    /// feed registered for `benchmark_mint`, e.g. a staked-SOL index (default
+    /// key = absolute returns). Owner only.
+    pub fn set_benchmark(ctx: Context<SetBenchmark>, benchmark_mint: Pubkey) -> Result<()> {
+        instructions::benchmark::handle_set_benchmark(ctx, benchmark_mint)
+    }
+
+    /// Sync the latest level of the vault's benchmark index. Permissionless.
+    pub fn sync_benchmark(ctx: Context<SyncBenchmark>) -> Result<()> {
+        instructions::benchmark::handle_sync_benchmark(ctx)
+    }
+
+    /// Cap outstanding accrued fees at `max_accrued_fee_bps` of total assets;
+    /// accruals beyond it are forgone (0 = no cap). Owner only.
+    pub fn set_max_accrued_fee(
+        ctx: Context<SetMaxAccruedFee>,
+        max_accrued_fee_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::handle_set_max_accrued_fee(ctx, max_accrued_fee_bps)
+    }
+
+    /// Schedule a fee holiday between `start` and `end` during which neither
+    /// management nor performance fees accrue (both 0 = clear). Owner only.
+    pub fn schedule_fee_holiday(
+        ctx: Context<ScheduleFeeHoliday>,
+        start: i64,
+        end: i64,
+    ) -> Result<()> {
+        instructions::admin::handle_schedule_fee_holiday(ctx, start, end)
+    }
+
+    /// Check a strategy's report age and flag it if stale. Permissionless.
+    pub fn ping_strategy(ctx: Context<PingStrategy>) -> Result<()> {
+        instructions::strategy::handle_ping_strategy(ctx)
+    }
+
+    /// Remove a strategy with no outstanding debt. Owner only.
+    pub fn remove_strategy(ctx: Context<RemoveStrategy>) -> Result<()> {
+        instructions::strategy::handle_remove_strategy(ctx)
+    }
+
+    /// Place a strategy in the withdrawal route: `withdraw_routed` pulls up to
+    /// `instant_withdraw_limit` from it per withdrawal, in ascending
+    /// `withdraw_priority`. A zero limit removes it. Owner only.
+    pub fn set_withdraw_route(
+        ctx: Context<SetWithdrawRoute>,
+        withdraw_priority: u8,
+        instant_withdraw_limit: u64,
+    ) -> Result<()> {
+        instructions::strategy::handle_set_withdraw_route(
+            ctx,
+            withdraw_priority,
+            instant_withdraw_limit,
+        )
+    }
+
+    /// Require strategies to run at least `min_version`, be audited when
+    /// `audited_only`, and be rated at most `max_risk_score` (0 = no limit) in
+    /// the protocol strategy registry to be registered or allocated to.
+    /// Owner only.
+    pub fn set_strategy_requirements(
+        ctx: Context<SetStrategyRequirements>,
+        min_version: StrategyVersion,
+        audited_only: bool,
+        max_risk_score: u8,
+    ) -> Result<()> {
+        instructions::admin::handle_set_strategy_requirements(
+            ctx,
+            min_version,
+            audited_only,
+            max_risk_score,
+        )
+    }
+
+    /// Bound a strategy's debt ceiling, which harvests and deallocations then
+    /// move up on realized gains and down on losses. `(0, 0)` removes the
+    /// ceiling. Owner only.
+    pub fn set_debt_ceiling_bounds(
+        ctx: Context<SetDebtCeilingBounds>,
+        min: u64,
+        max: u64,
+    ) -> Result<()> {
+        instructions::strategy::handle_set_debt_ceiling_bounds(ctx, min, max)
+    }
+
+    /// Move idle funds from the vault into a strategy, up to its debt ceiling.
+    /// Owner or manager only.
+    pub fn allocate(ctx: Context<Allocate>, amount: u64) -> Result<()> {
+        instructions::strategy::handle_allocate(ctx, amount)
+    }
+
+    /// Return funds from a strategy to the vault and report realized profit.
+    /// Owner or manager, co-signed by the strategy authority.
+    pub fn deallocate(ctx: Context<Deallocate>, amount: u64) -> Result<()> {
+        instructions::strategy::handle_deallocate(ctx, amount)
+    }
+
+    /// Ask the manager to return funds from a strategy while vault liquidity is
+    /// below target. Permissionless.
+    pub fn request_deallocation(ctx: Context<RequestDeallocation>, amount: u64) -> Result<()> {
+        instructions::strategy::handle_request_deallocation(ctx, amount)
+    }
+
+    /// Report a strategy's current value, realizing profit or loss into NAV.
+    /// Owner or manager only.
+    pub fn harvest(ctx: Context<Harvest>, current_value: u64) -> Result<()> {
+        instructions::strategy::handle_harvest(ctx, current_value)
+    }
+
+    /// Register the relayer allowed to attest the vault's cross-chain NAV
+    /// component (default key disables attestations). Owner only.
+    pub fn set_nav_relayer(ctx: Context<SetNavRelayer>, relayer: Pubkey) -> Result<()> {
+        instructions::external_nav::handle_set_nav_relayer(ctx, relayer)
+    }
+
+    /// Attest the NAV held by sibling vaults on other chains. Registered relayer only.
+    pub fn report_external_nav(
+        ctx: Context<ReportExternalNav>,
+        external_nav: u64,
+        observed_at: i64,
+    ) -> Result<()> {
+        instructions::external_nav::handle_report_external_nav(ctx, external_nav, observed_at)
+    }
+
+    /// Transfer out tokens of foreign mints sent to the vault's associated token
+    /// accounts. Owner only.
+    pub fn rescue_tokens(ctx: Context<RescueTokens>, amount: u64) -> Result<()> {
+        instructions::rescue::handler(ctx, amount)
+    }
+
+    /// Require share-holder votes of `period` seconds on manager proposals.
+    /// Irreversible: the period cannot go below `MIN_MANAGER_VOTE_PERIOD`
+    /// afterwards. Owner only.
+    pub fn set_manager_vote_period(ctx: Context<SetManagerVotePeriod>, period: i64) -> Result<()> {
+        instructions::manager_vote::handle_set_manager_vote_period(ctx, period)
+    }
+
+    /// Propose a new manager for share-holder approval. Owner only.
+    pub fn propose_manager(ctx: Context<ProposeManager>, manager: Pubkey) -> Result<()> {
+        instructions::manager_vote::handle_propose_manager(ctx, manager)
+    }
+
+    /// Lock shares to approve or veto a pending manager proposal.
+    pub fn vote_on_manager(ctx: Context<VoteOnManager>, approve: bool, amount: u64) -> Result<()> {
+        instructions::manager_vote::handle_vote_on_manager(ctx, approve, amount)
+    }
+
+    /// Resolve a manager proposal after voting ends, adding the manager unless vetoed.
+    pub fn finalize_manager_proposal(ctx: Context<FinalizeManagerProposal>) -> Result<()> {
+        instructions::manager_vote::handle_finalize_manager_proposal(ctx)
+    }
+
+    /// Return shares locked by a manager vote once voting has ended.
+    pub fn release_manager_vote(ctx: Context<ReleaseManagerVote>) -> Result<()> {
+        instructions::manager_vote::handle_release_manager_vote(ctx)
+    }
+
+    /// Create the vault's depositor rewards pool paying out `reward_mint`. Owner only.
+    pub fn init_rewards(ctx: Context<InitRewards>) -> Result<()> {
+        instructions::rewards::handle_init_rewards(ctx)
+    }
+
+    /// Fund the rewards pool and stream it, plus any unemitted balance, over
+    /// `duration` seconds. Owner only.
+    pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64, duration: i64) -> Result<()> {
+        instructions::rewards::handle_fund_rewards(ctx, amount, duration)
+    }
+
+    /// End the rewards campaign and take back the rewards not yet emitted;
+    /// emitted rewards stay claimable. Owner only.
+    pub fn defund_rewards(ctx: Context<DefundRewards>) -> Result<()> {
+        instructions::rewards::handle_defund_rewards(ctx)
+    }
+
+    /// Claim rewards earned on vault shares and start earning on the current balance.
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        instructions::rewards::handle_claim_rewards(ctx)
+    }
+
+    /// Enable, resize or disable epoch gating, under which deposits and
+    /// withdrawals are queued and processed at epoch boundaries. Owner only.
+    pub fn set_epoch_duration(ctx: Context<SetEpochDuration>, duration: i64) -> Result<()> {
+        instructions::epoch::handle_set_epoch_duration(ctx, duration)
+    }
+
+    /// Cap the share of NAV one epoch's queued redemptions may take; past it
+    /// every request is filled pro rata and the rest returned. Zero removes
+    /// the gate. Owner only.
+    pub fn set_redemption_gate(ctx: Context<SetRedemptionGate>, gate_bps: u16) -> Result<()> {
+        instructions::epoch::handle_set_redemption_gate(ctx, gate_bps)
+    }
+
+    /// Return the current epoch's redemption queue and how much of it the
+    /// redemption gate would fill. Read-only.
+    pub fn get_redemption_queue(ctx: Context<GetRedemptionQueue>) -> Result<RedemptionQueue> {
+        instructions::epoch::handle_get_redemption_queue(ctx)
+    }
+
+    /// Queue a deposit for the current epoch.
+    pub fn request_deposit(ctx: Context<RequestDeposit>, amount: u64) -> Result<()> {
+        instructions::epoch::handle_request_deposit(ctx, amount)
+    }
+
+    /// Queue a redemption of shares for the current epoch.
+    pub fn request_redeem(ctx: Context<RequestRedeem>, shares: u64) -> Result<()> {
+        instructions::epoch::handle_request_redeem(ctx, shares)
+    }
+
+    /// Settle the ended epoch's queued flows at the current NAV, paying the
+    /// crank bounty when its accounts are given. Permissionless.
+    pub fn settle_epoch(ctx: Context<SettleEpoch>) -> Result<()> {
+        instructions::epoch::handle_settle_epoch(ctx)
+    }
+
+    /// Claim the shares and assets owed to a request from a settled epoch.
+    pub fn claim_epoch(ctx: Context<ClaimEpoch>) -> Result<()> {
+        instructions::epoch::handle_claim_epoch(ctx)
+    }
+
+    /// Assign the user's queued epoch request to `new_owner`, who claims it
+    /// instead, so pending redemptions can change hands before settlement.
+    /// Merges into the new owner's request for the same epoch.
+    pub fn transfer_epoch_request(
+        ctx: Context<TransferEpochRequest>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        instructions::epoch::handle_transfer_epoch_request(ctx, new_owner)
+    }
+
+    /// Create the protocol config holding template defaults and fee limits.
+    /// Upgrade authority only.
+    pub fn init_protocol_config(
+        ctx: Context<InitProtocolConfig>,
+        params: ProtocolConfigParams,
+    ) -> Result<()> {
+        instructions::protocol::handle_init_protocol_config(ctx, params)
+    }
+
+    /// Replace the protocol template defaults and fee limits. Protocol admin only.
+    pub fn update_protocol_config(
+        ctx: Context<UpdateProtocolConfig>,
+        params: ProtocolConfigParams,
+    ) -> Result<()> {
+        instructions::protocol::handle_update_protocol_config(ctx, params)
+    }
+
+    /// Create the protocol strategy whitelist. Protocol admin only.
+    pub fn init_strategy_whitelist(ctx: Context<InitStrategyWhitelist>) -> Result<()> {
+        instructions::protocol::handle_init_strategy_whitelist(ctx)
+    }
+
+    /// Record a whitelisted strategy's adapter version, audit status and risk
+    /// score, which vaults' strategy requirements are checked against.
+    /// Protocol admin only.
+    pub fn set_strategy_metadata(
+        ctx: Context<SetStrategyMetadata>,
+        strategy_id: Pubkey,
+        metadata: StrategyMetadata,
+    ) -> Result<()> {
+        instructions::protocol::handle_set_strategy_metadata(ctx, strategy_id, metadata)
+    }
+
+    /// Allow or disallow a strategy id for `add_strategy`. Protocol admin only.
+    pub fn set_strategy_whitelisted(
+        ctx: Context<SetStrategyWhitelisted>,
+        strategy_id: Pubkey,
+        allowed: bool,
+    ) -> Result<()> {
+        instructions::protocol::handle_set_strategy_whitelisted(ctx, strategy_id, allowed)
+    }
+
+    /// Create the protocol oracle feed registry. Protocol admin only.
+    pub fn init_feed_registry(ctx: Context<InitFeedRegistry>) -> Result<()> {
+        instructions::protocol::handle_init_feed_registry(ctx)
+    }
+
+    /// Create the protocol-wide stats account, with TVL and share price per
+    /// underlying mint and the vault count, which vaults update on deposits,
+    /// withdrawals and NAV updates and leave on close. Protocol admin only.
+    pub fn init_global_stats(ctx: Context<InitGlobalStats>) -> Result<()> {
+        instructions::protocol::handle_init_global_stats(ctx)
+    }
+
+    /// Push a vault's current assets and shares to the protocol stats.
+    /// Permissionless.
+    pub fn sync_global_stats(ctx: Context<SyncGlobalStats>) -> Result<()> {
+        instructions::protocol::handle_sync_global_stats(ctx)
+    }
+
+    /// Approve the oracle vaults holding `mint` may price it with, and its
+    /// staleness limit; the default key removes the feed. Protocol admin only.
+    pub fn set_price_feed(
+        ctx: Context<SetPriceFeed>,
+        mint: Pubkey,
+        oracle: Pubkey,
+        max_staleness: i64,
+    ) -> Result<()> {
+        instructions::protocol::handle_set_price_feed(ctx, mint, oracle, max_staleness)
+    }
+
+    /// Block a wallet from depositing into any vault, recording why (the
+    /// reason picks the error deposits fail with). Protocol admin only.
+    pub fn flag_wallet(
+        ctx: Context<FlagWallet>,
+        wallet: Pubkey,
+        flag: ComplianceFlag,
+    ) -> Result<()> {
+        instructions::protocol::handle_flag_wallet(ctx, wallet, flag)
+    }
+
+    /// Clear a wallet's compliance flag. Protocol admin only.
+    pub fn unflag_wallet(ctx: Context<UnflagWallet>) -> Result<()> {
+        instructions::protocol::handle_unflag_wallet(ctx)
+    }
+
+    /// Create the vault's compressed position history over an empty SPL
+    /// account-compression tree. Owner only.
+    pub fn init_position_history(
+        ctx: Context<InitPositionHistory>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        instructions::position_history::handle_init_position_history(
+            ctx,
+            max_depth,
+            max_buffer_size,
+        )
+    }
+
+    /// Open the next position snapshot round. Owner or manager.
+    pub fn start_history_round(ctx: Context<StartHistoryRound>) -> Result<()> {
+        instructions::position_history::handle_start_history_round(ctx)
+    }
+
+    /// Append snapshots of the `(position, share account)` pairs passed as
+    /// remaining accounts to the position history. Permissionless.
+    pub fn record_position_snapshots<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RecordPositionSnapshots<'info>>,
+    ) -> Result<()> {
+        instructions::position_history::handle_record_position_snapshots(ctx)
+    }
+
+    /// Create the vault's compressed NFT receipt tree over an empty SPL
+    /// account-compression tree, with receipt URIs starting at `base_uri`.
+    /// Owner only.
+    pub fn init_receipt_tree(
+        ctx: Context<InitReceiptTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+        base_uri: String,
+    ) -> Result<()> {
+        instructions::receipt::handle_init_receipt_tree(ctx, max_depth, max_buffer_size, base_uri)
+    }
+
+    /// Deposit as `deposit` does and mint the user a compressed NFT receipt
+    /// of the amount, shares and share price, paid for by the user
+    pub fn deposit_with_receipt(
+        ctx: Context<DepositWithReceipt>,
+        amount: u64,
+        memo: Option<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::receipt::handle_deposit_with_receipt(ctx, amount, memo)
+    }
+
+    /// Withdraw as `withdraw` does and mint the user a compressed NFT receipt
+    /// of the assets paid, shares burned and share price
+    pub fn withdraw_with_receipt(
+        ctx: Context<WithdrawWithReceipt>,
+        shares: u64,
+        max_loss_bps: u16,
+        memo: Option<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::receipt::handle_withdraw_with_receipt(ctx, shares, max_loss_bps, memo)
+    }
+
+    /// Publish the hash and URI of the vault's strategy disclosure document,
+    /// in force at once. Owner only.
+    pub fn init_disclosure(
+        ctx: Context<InitDisclosure>,
+        content_hash: [u8; 32],
+        uri: String,
+    ) -> Result<()> {
+        instructions::disclosure::handle_init_disclosure(ctx, content_hash, uri)
+    }
+
+    /// Propose a replacement strategy disclosure document, applicable after
+    /// a 14-day timelock. Owner only.
+    pub fn propose_disclosure(
+        ctx: Context<ProposeDisclosure>,
+        content_hash: [u8; 32],
+        uri: String,
+    ) -> Result<()> {
+        instructions::disclosure::handle_propose_disclosure(ctx, content_hash, uri)
+    }
+
+    /// Put a proposed disclosure document in force once its timelock has
+    /// passed. Permissionless.
+    pub fn apply_disclosure(ctx: Context<ApplyDisclosure>) -> Result<()> {
+        instructions::disclosure::handle_apply_disclosure(ctx)
+    }
+
+    /// Fund an airdrop of `total_amount` reward tokens to share holders as of
+    /// `snapshot_slot`, with allocations committed by `merkle_root`. Owner only.
+    pub fn create_snapshot(
+        ctx: Context<CreateSnapshot>,
+        snapshot_slot: u64,
+        merkle_root: [u8; 32],
+        total_amount: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::airdrop::handle_create_snapshot(
+            ctx,
+            snapshot_slot,
+            merkle_root,
+            total_amount,
+            expires_at,
+        )
+    }
+
+    /// Claim an airdrop allocation of `amount` with its Merkle `proof`.
+    pub fn claim_airdrop(
+        ctx: Context<ClaimAirdrop>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::airdrop::handle_claim_airdrop(ctx, amount, proof)
+    }
+
+    /// Reclaim an expired airdrop's unclaimed tokens. Owner only.
+    pub fn clawback_airdrop(ctx: Context<ClawbackAirdrop>) -> Result<()> {
+        instructions::airdrop::handle_clawback_airdrop(ctx)
+    }
+
+    /// Open a principal/yield tranche of the vault's shares maturing at
+    /// `maturity`. Owner only.
+    pub fn create_tranche(ctx: Context<CreateTranche>, maturity: i64) -> Result<()> {
+        instructions::tranche::handle_create_tranche(ctx, maturity)
+    }
+
+    /// Escrow `shares` in a tranche for equal amounts of principal and yield tokens.
+    pub fn mint_tranche(ctx: Context<MintTranche>, shares: u64) -> Result<()> {
+        instructions::tranche::handle_mint_tranche(ctx, shares)
+    }
+
+    /// Fix a matured tranche's payouts at the current share price. Permissionless.
+    pub fn settle_tranche(ctx: Context<SettleTranche>) -> Result<()> {
+        instructions::tranche::handle_settle_tranche(ctx)
+    }
+
+    /// Burn principal and yield tokens for escrowed shares: whole pairs before
+    /// settlement, either token after it.
+    pub fn redeem_tranche(
+        ctx: Context<RedeemTranche>,
+        principal_amount: u64,
+        yield_amount: u64,
+    ) -> Result<()> {
+        instructions::tranche::handle_redeem_tranche(ctx, principal_amount, yield_amount)
+    }
+
+    /// Guard deposits with a secondary market for the share token: while the
+    /// pool prices shares more than `max_discount_bps` below NAV, deposits
+    /// fail with `MarketBelowNav`. Omit the reserves to remove it. Owner only.
+    pub fn set_market_pool(ctx: Context<SetMarketPool>, max_discount_bps: u16) -> Result<()> {
+        instructions::market::handle_set_market_pool(ctx, max_discount_bps)
+    }
+
+    /// Emit the market's discount to NAV as an arbitrage signal. Permissionless.
+    pub fn signal_market_discount(ctx: Context<SignalMarketDiscount>) -> Result<()> {
+        instructions::market::handle_signal_market_discount(ctx)
+    }
+
+    /// Replace the vault's rebalancing policy: per-strategy target weights,
+    /// drift bands and maximum trade sizes. With `enforce`, managers can only
+    /// allocate through `rebalance`. Owner only.
+    pub fn set_policy(
+        ctx: Context<SetPolicy>,
+        rules: Vec<PolicyRule>,
+        enforce: bool,
+    ) -> Result<()> {
+        instructions::policy::handle_set_policy(ctx, rules, enforce)
+    }
+
+    /// Move funds between the vault and its strategies, rejecting any trade
+    /// that breaks the policy. Manager or owner.
+    pub fn rebalance<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Rebalance<'info>>,
+        trades: Vec<RebalanceTrade>,
+    ) -> Result<()> {
+        instructions::policy::handle_rebalance(ctx, trades)
+    }
+
     /// Close an empty vault and reclaim all rent. Owner only.
     pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
         instructions::close::handler(ctx)
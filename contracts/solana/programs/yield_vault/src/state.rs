@@ -1,57 +1,1106 @@
 use anchor_lang::prelude::*;
 
-use crate::constants::MAX_MANAGERS;
+use crate::constants::{
+    AUDIT_LOG_CAPACITY, BPS_DENOMINATOR, DEBT_CEILING_RESPONSE, FEE_INCREASE_GRACE_PERIOD,
+    INVARIANT_DUST_TOLERANCE, MAX_DISCLOSURE_URI_LEN, MAX_MANAGERS, MAX_POLICY_RULES,
+    MAX_PORTFOLIO_VAULTS, MAX_PRICE_FEEDS, MAX_RECEIPT_BASE_URI_LEN, MAX_STATS_MINTS,
+    MAX_STRATEGIES, MAX_WHITELISTED_STRATEGIES, INFLOW_WINDOW, MAX_ZAP_PROGRAMS, OUTFLOW_WINDOW,
+    PRICE_HISTORY_CAPACITY, REWARD_PRECISION, STRICT_MAX_ORACLE_STALENESS, VAULT_SEED,
+};
 use crate::error::VaultError;
+use crate::events::{AccruedFeeCapReached, VaultStatusChanged};
+use crate::utils::{
+    calculate_assets_to_return, calculate_management_fee, calculate_performance_fee,
+    check_gateway_token, div_by_q64, liquidity_fees_bps, mul_q64, oracle_conf_bps,
+    oracle_publish_time, q64_to_amount, scale_q64, share_price_q64, EventCpi,
+};
 
-#[account]
+/// How `collect_fees` pays accrued fees to the treasury
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum FeePayoutMode {
+    /// Mint new shares to the treasury (dilutes holders, keeps assets deployed)
+    Shares = 0,
+    /// Transfer underlying tokens from the vault to the treasury
+    Assets = 1,
+}
+
+/// Where a vault is in its lifecycle, stored in `VaultState::status` as u8
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum VaultStatus {
+    /// Created by step 1; awaiting `init_vault_accounts` or `cancel_init`
+    Uninitialized = 0,
+    /// Accepting deposits, withdrawals and allocations
+    Active = 1,
+    /// Deposits and withdrawals halted by the owner; managers may still
+    /// move funds between strategies
+    Paused = 2,
+    /// No deposits or allocations; holders exit fee-free ahead of closing
+    WindingDown = 3,
+    /// Closed by `close_vault` or `cancel_init`; only seen in the final event
+    Closed = 4,
+}
+
+impl VaultStatus {
+    /// Whether a vault may move from `self` to `next`
+    pub fn can_become(self, next: VaultStatus) -> bool {
+        use VaultStatus::*;
+        match (self, next) {
+            (Uninitialized, Active | Closed) => true,
+            (Active, Paused | WindingDown) => true,
+            (Paused, Active | WindingDown) => true,
+            (WindingDown, Closed) => true,
+            (Uninitialized | Active | Paused | WindingDown | Closed, _) => false,
+        }
+    }
+
+    /// Whether deposits, direct or queued, are accepted
+    pub fn check_deposits(self) -> Result<()> {
+        match self {
+            VaultStatus::Active => Ok(()),
+            VaultStatus::Paused => err!(VaultError::VaultPaused),
+            VaultStatus::WindingDown => err!(VaultError::VaultWindingDown),
+            VaultStatus::Uninitialized | VaultStatus::Closed => {
+                err!(VaultError::VaultNotInitialized)
+            }
+        }
+    }
+
+    /// Whether withdrawals, direct or queued, are accepted
+    pub fn check_withdrawals(self) -> Result<()> {
+        match self {
+            VaultStatus::Active | VaultStatus::WindingDown => Ok(()),
+            VaultStatus::Paused => err!(VaultError::VaultPaused),
+            VaultStatus::Uninitialized | VaultStatus::Closed => {
+                err!(VaultError::VaultNotInitialized)
+            }
+        }
+    }
+
+    /// Whether managers may move funds into strategies
+    pub fn check_allocations(self) -> Result<()> {
+        match self {
+            VaultStatus::Active | VaultStatus::Paused => Ok(()),
+            VaultStatus::WindingDown => err!(VaultError::VaultWindingDown),
+            VaultStatus::Uninitialized | VaultStatus::Closed => {
+                err!(VaultError::VaultNotInitialized)
+            }
+        }
+    }
+}
+
+/// Why the owner proposed slashing a manager's bond
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SlashReason {
+    /// The manager reported a NAV the vault's holdings did not support
+    NavMisreport,
+    /// The manager moved funds to a strategy or counterparty it was not
+    /// permitted to
+    UnauthorizedAllocation,
+}
+
+/// Groth16 verifying key over BN254, points in the EIP-197 big-endian
+/// encoding the alt_bn128 syscalls take
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Groth16VerifyingKey {
+    pub alpha_g1: [u8; 64],
+    pub beta_g2: [u8; 128],
+    pub gamma_g2: [u8; 128],
+    pub delta_g2: [u8; 128],
+    /// Public input commitments: the constant term, then one per input
+    /// (`RESERVES_PUBLIC_INPUTS`)
+    pub ic: [[u8; 64]; 4],
+}
+
+impl Groth16VerifyingKey {
+    /// Serialized size: 64 + 128*3 + 64*4
+    pub const LEN: usize = 704;
+}
+
+/// Groth16 proof, points in the EIP-197 big-endian encoding
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Groth16Proof {
+    pub a: [u8; 64],
+    pub b: [u8; 128],
+    pub c: [u8; 64],
+}
+
+/// One strategy's allowance under a vault's rebalancing `Policy`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct PolicyRule {
+    /// Strategy account the rule covers
+    pub strategy: Pubkey,
+    /// Share of total assets the strategy should hold, in basis points
+    pub target_bps: u16,
+    /// Drift from the target a trade may leave behind, in basis points of
+    /// total assets
+    pub band_bps: u16,
+    /// Largest amount a single trade may move in or out (0 = no limit)
+    pub max_trade: u64,
+}
+
+impl PolicyRule {
+    /// Serialized size: 32 + 2 + 2 + 8
+    pub const LEN: usize = 44;
+
+    /// Whether moving the strategy's debt from `before` to `after` conforms:
+    /// the trade is within `max_trade`, and it either lands inside the band
+    /// or moves toward the target without crossing it
+    pub fn permits(&self, before: u64, after: u64, total_assets: u64) -> Result<bool> {
+        let size = before.abs_diff(after);
+        if self.max_trade > 0 && size > self.max_trade {
+            return Ok(false);
+        }
+        let of_total = |bps: u16| -> Result<u64> {
+            let amount = (total_assets as u128)
+                .checked_mul(bps as u128)
+                .ok_or(VaultError::ArithmeticOverflow)?
+                / BPS_DENOMINATOR;
+            Ok(amount as u64)
+        };
+        let target = of_total(self.target_bps)?;
+        if after.abs_diff(target) <= of_total(self.band_bps)? {
+            return Ok(true);
+        }
+        Ok(before.min(target) <= after && after <= before.max(target))
+    }
+}
+
+/// Direction of a standing intent
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IntentKind {
+    /// Deposit underlying once the share price is at or below the limit
+    Deposit,
+    /// Redeem shares once the share price is at or above the limit
+    Withdraw,
+}
+
+/// Permissionless instruction a crank bounty is paid for
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CrankKind {
+    /// `settle_epoch`
+    SettleEpoch,
+    /// `execute_intent`
+    ExecuteIntent,
+}
+
+/// Administrative action recorded in a vault's audit log
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum AuditAction {
+    Pause = 0,
+    Unpause = 1,
+    BeginWinddown = 2,
+    UpdateConfig = 3,
+    ScheduleDepositCap = 4,
+    SetFlowWindow = 5,
+    SetWithdrawDelay = 6,
+    SetLiquidityReserve = 7,
+    SetLiquidityTarget = 8,
+    SetFeePayoutMode = 9,
+    SetMaxReportAge = 10,
+    SetPriceOracle = 11,
+    SetPolicyCommitment = 12,
+    SetGatekeeperNetwork = 13,
+    SetMaxAccruedFee = 14,
+    ScheduleFeeHoliday = 15,
+    SetEpochDuration = 16,
+    SetNavRelayer = 17,
+    SetMarketPool = 18,
+    SetPolicy = 19,
+    SetReservesVerifier = 20,
+    SetCrankBounty = 21,
+    AddManager = 22,
+    RemoveManager = 23,
+    SlashManager = 24,
+    SetManagerBond = 25,
+    SetManagerVotePeriod = 26,
+    ProposeManager = 27,
+    FinalizeManagerProposal = 28,
+    ProposeSlash = 29,
+    CancelSlash = 30,
+    AddStrategy = 31,
+    RemoveStrategy = 32,
+    SetExitFee = 33,
+    SetDebtCeilingBounds = 34,
+    SetStrategyRequirements = 35,
+    SetWithdrawRoute = 36,
+    SetRedemptionGate = 37,
+    SetBenchmark = 38,
+    SetFeeDonation = 39,
+    SetOutflowLimit = 40,
+    SetInflowLimit = 41,
+    SetLiquidityFeeCurve = 42,
+    ProposeDisclosure = 43,
+    DefundRewards = 44,
+    DefundCrankBounty = 45,
+    RescueTokens = 46,
+    ClawbackAirdrop = 47,
+    CreateSnapshot = 48,
+    InitRewards = 49,
+    FundRewards = 50,
+    ExecuteSlash = 51,
+    Allocate = 52,
+    Deallocate = 53,
+    Harvest = 54,
+    Rebalance = 55,
+    UpdateNav = 56,
+    InitDisclosure = 57,
+}
+
+/// Why the protocol admin blocks a wallet; each maps to its own error code
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ComplianceFlag {
+    /// Listed on a sanctions list
+    Sanctioned,
+    /// Attributed to a restricted jurisdiction
+    Geofenced,
+}
+
+/// Flow a compressed NFT receipt records
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReceiptKind {
+    /// Underlying deposited for newly minted shares
+    Deposit,
+    /// Shares burned for underlying paid out
+    Withdrawal,
+}
+
+/// Vault state is zero-copy: handlers read and write it in place through an
+/// `AccountLoader` instead of deserializing and re-serializing the whole
+/// account on every instruction. Fields are ordered by alignment (32-byte
+/// keys, then 8/2/1-byte scalars) so the `repr(C)` layout has no implicit
+/// padding; flags are stored as `u8`.
+#[account(zero_copy)]
 pub struct VaultState {
     /// Vault owner / admin — can change config, pause, manage managers
     pub owner: Pubkey,
     /// Underlying token mint (e.g. USDC, SOL, etc.)
     pub mint: Pubkey,
     // Note: share_mint and vault_token_account are derived via PDA seeds,
-    // so we don't store them — saves 64 bytes for BPF stack compliance.
+    // so we don't store them.
+
+    /// Authorized managers (agents) who can execute strategies and update NAV
+    pub managers: [Pubkey; MAX_MANAGERS],
+
+    /// Relayer allowed to attest the cross-chain NAV component
+    /// (default key = no relayer registered)
+    pub nav_relayer: Pubkey,
+
+    /// Pyth price account deposits and NAV updates are checked against
+    /// (default key = no oracle configured)
+    pub price_oracle: Pubkey,
+
+    /// Pyth feed of the index performance fees are charged relative to, e.g.
+    /// a staked-SOL index (default key = fees on absolute returns)
+    pub benchmark_oracle: Pubkey,
+
+    /// Charity or public-goods wallet receiving `donation_bps` of collected
+    /// fees (default key = no donation)
+    pub donation_recipient: Pubkey,
+
+    /// Pool token account holding the share side of the secondary market
+    /// deposits are checked against (default key = no market guard)
+    pub market_share_reserve: Pubkey,
+    /// Pool token account holding the underlying side of that market
+    pub market_asset_reserve: Pubkey,
+
+    /// SHA-256 of the agent policy file depositors approved; managers' agents
+    /// refuse to run on any other config (zero = no commitment)
+    pub policy_commitment: [u8; 32],
+
+    /// Civic gatekeeper network whose pass depositors must hold, making the
+    /// vault permissioned (default = open to anyone)
+    pub gatekeeper_network: Pubkey,
+
+    /// Slot the vault was created in — seeds the share mint so a vault
+    /// reopened on the same seeds gets a fresh mint
+    pub created_slot: u64,
 
     /// Total assets under management (in vault + deployed to strategies)
     pub total_assets: u64,
     /// Total outstanding share tokens
     pub total_shares: u64,
+    /// Portion of total_assets currently deployed to strategies
+    pub total_debt: u64,
+    /// Simulated balance of the vault token account of a paper vault, which
+    /// stands in for the real balance in its accounting
+    pub paper_balance: u64,
+    /// Portion of total_assets held by sibling vaults on other chains,
+    /// as last attested by the NAV relayer
+    pub external_nav: u64,
+    /// Source-chain observation time of the last external NAV attestation
+    pub external_nav_observed_at: i64,
+    /// Slots a user must wait after depositing before withdrawing
+    /// (withdrawals in the deposit slot itself are always rejected)
+    pub withdraw_delay_slots: u64,
+    /// Slot of the most recent deposit into the vault. No withdrawal may land
+    /// in the same slot, whichever position it redeems from, so a round trip
+    /// cannot be split across wallets by transferring the shares.
+    pub last_deposit_slot: u64,
+    /// Withdrawals of at most this many assets may draw on the liquidity reserve
+    pub small_withdrawal_threshold: u64,
+    /// Seconds the manager has to fulfil a deallocation request
+    pub deallocation_window: i64,
+    /// Outstanding amount the manager has been asked to deallocate
+    pub pending_deallocation: u64,
+    /// When the pending deallocation falls due (unix timestamp, 0 = none)
+    pub deallocation_deadline: i64,
+
+    /// Epoch length in seconds; when non-zero, deposits and withdrawals are
+    /// queued and only processed at epoch boundaries (0 = disabled)
+    pub epoch_duration: i64,
+    /// Index of the epoch currently accepting requests
+    pub current_epoch: u64,
+    /// When the current epoch started (unix timestamp)
+    pub epoch_started_at: i64,
+    /// Assets queued for deposit in the current epoch
+    pub epoch_pending_deposits: u64,
+    /// Shares queued for redemption in the current epoch (held in escrow)
+    pub epoch_pending_redeems: u64,
+    /// Assets in the vault token account that are not part of NAV: queued
+    /// deposits plus settled redemptions awaiting their claim
+    pub epoch_reserved_assets: u64,
+
+    /// Shares minted per asset unit on the first deposit, `10^decimals_offset`
+    pub share_scale: u64,
 
     /// Maximum total deposits allowed (0 = unlimited)
     pub deposit_cap: u64,
     /// Minimum deposit amount per transaction
     pub min_deposit: u64,
+    /// Deposit cap when the cap schedule started (see `deposit_cap_at`)
+    pub cap_schedule_start_cap: u64,
+    /// Amount the scheduled cap grows by every `cap_schedule_interval`
+    pub cap_schedule_increment: u64,
+    /// Seconds between scheduled cap increases (0 = no schedule)
+    pub cap_schedule_interval: i64,
+    /// When the cap schedule started (unix timestamp)
+    pub cap_schedule_started_at: i64,
+    /// Length of the repeating period deposits and withdrawals are windowed
+    /// in, e.g. 86400 for daily windows (0 = always open)
+    pub flow_window_period: i64,
+    /// Offset within the period at which flows open, in seconds from the
+    /// period start (periods are aligned to the unix epoch, i.e. UTC)
+    pub flow_window_open: i64,
+    /// Offset within the period at which flows close; before `flow_window_open`
+    /// for a window spanning the period boundary
+    pub flow_window_close: i64,
 
-    /// Annual management fee in basis points (e.g. 200 = 2%)
-    pub management_fee_bps: u16,
-    /// Performance fee in basis points on profits above high-water mark
-    pub performance_fee_bps: u16,
-
-    /// High-water mark for performance fee calculation (per-share basis)
-    pub high_water_mark: u64,
     /// Last time fees were collected (unix timestamp)
     pub last_fee_collection: i64,
     /// Accumulated management fees not yet collected (in asset units)
     pub accrued_management_fee: u64,
 
-    /// Authorized managers (agents) who can execute strategies and update NAV
-    pub managers: [Pubkey; MAX_MANAGERS],
+    /// Share-holder voting window for manager proposals in seconds
+    /// (0 = owner adds managers directly). Once set, at least
+    /// `MIN_MANAGER_VOTE_PERIOD` and never turned off again.
+    pub manager_vote_period: i64,
+    /// Seconds a strategy may go without reporting before new allocations
+    /// to it are blocked (0 = no limit)
+    pub max_report_age: i64,
+    /// Oldest oracle publish time accepted, in seconds before the current
+    /// time, copied from the feed registry (0 = no limit)
+    pub max_oracle_staleness: i64,
+    /// Underlying a manager must have bonded before being added (0 = no bond)
+    pub min_manager_bond: u64,
+    /// Seconds a proposed bond slash waits before it can be executed, leaving
+    /// room for the manager to appeal
+    pub slash_appeal_period: i64,
+    /// Underlying held in manager bonds across every bonded key; the vault
+    /// cannot be closed while any is left
+    pub total_bonded: u64,
+    /// Start of the scheduled fee holiday, during which no fees accrue
+    /// (unix timestamp)
+    pub fee_holiday_start: i64,
+    /// End of the scheduled fee holiday (unix timestamp, 0 = none scheduled)
+    pub fee_holiday_end: i64,
+    /// Number of fee increases so far; positions that held shares before
+    /// the latest one carry an older `fee_epoch`
+    pub fee_epoch: u64,
+    /// Shares outstanding at the latest fee increase and not yet redeemed
+    /// by their holders, charged the previous rates during the grace period
+    pub grandfathered_shares: u64,
+    /// End of the grace period after the latest fee increase (unix timestamp)
+    pub fee_grace_ends_at: i64,
+    /// Seconds after a holder's last deposit during which the full exit fee
+    /// applies
+    pub exit_fee_full_period: i64,
+    /// Seconds after a holder's last deposit at which the exit fee, falling
+    /// linearly from the end of the full period, reaches zero
+    pub exit_fee_decay_end: i64,
+    /// Benchmark level the high-water mark was last set at; the mark is scaled
+    /// by the index's move since then before fees are charged
+    pub benchmark_level: u64,
+    /// Latest benchmark level synced from the benchmark oracle
+    pub benchmark_latest: u64,
+    /// Publish time of `benchmark_latest` (unix timestamp)
+    pub benchmark_published_at: i64,
+    /// Oldest benchmark publish time accepted, in seconds before the current
+    /// time, copied from the feed registry (0 = no limit)
+    pub max_benchmark_staleness: i64,
+    /// Sequence number of the vault's latest event. Every event the vault
+    /// emits carries the next one, so a gap shows an indexer what it missed.
+    pub event_seq: u64,
+    /// `total_assets` as last pushed to the protocol `GlobalStats`
+    pub stats_reported_assets: u64,
+    /// `total_shares` as last pushed to the protocol `GlobalStats`
+    pub stats_reported_shares: u64,
+    /// Most underlying withdrawals may pay out per `OUTFLOW_WINDOW`
+    /// (0 = no absolute limit)
+    pub max_window_outflow: u64,
+    /// Start of the current outflow window (unix timestamp)
+    pub outflow_window_start: i64,
+    /// Underlying paid out by withdrawals in the current window
+    pub outflow_window_used: u64,
+    /// Total assets when the current window opened, the base of
+    /// `max_window_outflow_bps`
+    pub outflow_window_base: u64,
+    /// Most underlying deposits may bring in per `INFLOW_WINDOW`
+    /// (0 = no limit)
+    pub max_window_inflow: u64,
+    /// Start of the current inflow window (unix timestamp)
+    pub inflow_window_start: i64,
+    /// Underlying deposited in the current window
+    pub inflow_window_used: u64,
+
+    /// Annual management fee in basis points (e.g. 200 = 2%)
+    pub management_fee_bps: u16,
+    /// Performance fee in basis points on profits above high-water mark
+    pub performance_fee_bps: u16,
+    /// Share of total assets kept undeployed for small withdrawals, in basis points
+    pub reserve_ratio_bps: u16,
+    /// Share of total assets the vault aims to hold liquid, in basis points
+    /// (0 = no target)
+    pub target_liquidity_bps: u16,
+    /// Widest oracle confidence interval, as a share of the price in basis
+    /// points, at which the price is still trusted
+    pub max_oracle_conf_bps: u16,
+    /// Cap on outstanding accrued fees as a share of total assets, in basis
+    /// points (0 = no cap)
+    pub max_accrued_fee_bps: u16,
+    /// Largest discount of the market share price to NAV, in basis points,
+    /// at which deposits are still accepted
+    pub max_market_discount_bps: u16,
+    /// Management fee before the latest increase, in basis points
+    pub grandfathered_management_fee_bps: u16,
+    /// Performance fee before the latest increase, in basis points
+    pub grandfathered_performance_fee_bps: u16,
+    /// Fee on withdrawals shortly after a deposit, in basis points, kept in
+    /// the vault for the remaining holders (0 = no exit fee)
+    pub exit_fee_bps: u16,
+    /// Lowest adapter version, per the protocol strategy registry, a strategy
+    /// must run to be registered or allocated to (all zero = any version)
+    pub min_strategy_version_major: u16,
+    pub min_strategy_version_minor: u16,
+    pub min_strategy_version_patch: u16,
+    /// Share of NAV, in basis points, queued epoch redemptions may take in one
+    /// settlement before every request is filled pro rata (0 = no gate)
+    pub redemption_gate_bps: u16,
+    /// Share of collected fees, in basis points, paid to the donation
+    /// recipient instead of the treasury (0 = no donation)
+    pub donation_bps: u16,
+    /// Airdrop snapshots created and not yet clawed back; the vault cannot be
+    /// closed while any is open
+    pub open_airdrops: u16,
+    /// Most underlying withdrawals may pay out per `OUTFLOW_WINDOW`, as a
+    /// share of total assets at the window's start in basis points
+    /// (0 = no relative limit)
+    pub max_window_outflow_bps: u16,
+    /// Liquid share of total assets, in basis points, below which the
+    /// liquidity fee curve applies (0 = no curve)
+    pub liquidity_fee_kink_bps: u16,
+    /// Entry fee at or above the kink, falling linearly to zero as liquidity
+    /// runs out, in basis points
+    pub max_liquidity_entry_fee_bps: u16,
+    /// Exit fee with no liquidity left, falling linearly to zero at the kink,
+    /// in basis points
+    pub max_liquidity_exit_fee_bps: u16,
+
     /// Current number of active managers
     pub manager_count: u8,
 
-    /// Whether the vault is paused (deposits/withdrawals disabled)
-    pub paused: bool,
+    /// Where the vault is in its lifecycle, a `VaultStatus` as u8
+    pub status: u8,
 
     /// PDA bump seeds
     pub bump: u8,
     pub share_mint_bump: u8,
     pub token_account_bump: u8,
+    pub vote_escrow_bump: u8,
+
+    /// How collected fees are paid to the treasury, a `FeePayoutMode` as u8
+    pub fee_payout_mode: u8,
+    pub epoch_escrow_bump: u8,
+
+    /// Whether the vault was created from the protocol template and stays
+    /// bound by its fee limits, 0 or 1
+    pub from_template: u8,
+
+    /// Decimals of the underlying mint
+    pub asset_decimals: u8,
+    /// Extra decimals shares carry over the underlying (see `share_scale`)
+    pub decimals_offset: u8,
+
+    /// Whether managers must move funds through `rebalance` under the
+    /// vault's `Policy` rather than allocating directly, 0 or 1
+    pub policy_enforced: u8,
+    /// Whether large NAV increases need a proof verified by the vault's
+    /// `ReservesVerifier`, 0 or 1
+    pub reserves_proof_enforced: u8,
+    /// Set while the vault has handed control to an external program, so
+    /// flows cannot re-enter it mid-call, 0 or 1
+    pub locked: u8,
+    /// Whether strategies must be marked audited in the protocol strategy
+    /// registry to be registered or allocated to, 0 or 1
+    pub audited_strategies_only: u8,
+    /// Highest registry risk score a strategy may carry to be registered or
+    /// allocated to (0 = no limit)
+    pub max_strategy_risk_score: u8,
+    /// Whether the vault is a paper-trading vault, whose flows move no
+    /// underlying and only update its accounting, 0 or 1. Set at creation.
+    pub paper: u8,
+    /// Whether the vault has been counted in the protocol `GlobalStats`, 0 or 1
+    pub stats_registered: u8,
+
+    /// High-water mark for performance fees: the share price in Q64.64, as
+    /// little-endian bytes (see `high_water_mark`)
+    pub high_water_mark_q64: [u8; 16],
+    /// Fraction of an asset unit of accrued fees carried between accruals, in
+    /// Q64.64 little-endian bytes (see `fee_remainder`)
+    pub fee_remainder_q64: [u8; 16],
+    /// Share price before the current unrecovered loss, in Q64.64
+    /// little-endian bytes (zero = no loss outstanding, see `pre_loss_price`)
+    pub pre_loss_price_q64: [u8; 16],
+    /// Value share conversions truncated away and not yet swept to the
+    /// treasury, in Q64.64 asset units, little-endian bytes (see `dust`)
+    pub dust_q64: [u8; 16],
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 54],
 }
 
+const _: () = assert!(std::mem::size_of::<VaultState>() == 1056);
+const _: () = assert!(std::mem::align_of::<VaultState>() == 8);
+
 impl VaultState {
     /// Account discriminator (8) + all fields
-    /// 32 + 32 + 8 + 8 + 8 + 8 + 2 + 2 + 8 + 8 + 8 + (32*3) + 1 + 1 + 1 + 1 + 1 = 225
-    pub const LEN: usize = 8 + 225;
+    /// 32 + 32 + (32*3) + 32 + 32 + 32 + 32 + 32*2 + 32 + 32 + 8*58 + 2*20 + 1*18 + 16*4
+    /// + 54 = 1056
+    pub const LEN: usize = 8 + std::mem::size_of::<VaultState>();
+
+    /// Seeds the vault PDA signs with, computed once per instruction
+    pub fn signer_seeds(&self) -> [&[u8]; 4] {
+        [
+            VAULT_SEED,
+            self.mint.as_ref(),
+            self.owner.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+
+    pub fn status(&self) -> VaultStatus {
+        match self.status {
+            0 => VaultStatus::Uninitialized,
+            1 => VaultStatus::Active,
+            2 => VaultStatus::Paused,
+            3 => VaultStatus::WindingDown,
+            _ => VaultStatus::Closed,
+        }
+    }
+
+    /// Move the vault to `next`, if its lifecycle allows it
+    pub fn transition(&mut self, next: VaultStatus, events: EventCpi) -> Result<()> {
+        let previous = self.status();
+        require!(
+            previous.can_become(next),
+            VaultError::InvalidStatusTransition
+        );
+        self.status = next as u8;
+        events.emit(VaultStatusChanged {
+            vault: Pubkey::create_program_address(&self.signer_seeds(), &crate::ID)
+                .map_err(|_| VaultError::InvariantViolation)?,
+            event_seq: self.next_event_seq(),
+            previous,
+            status: next,
+        })
+    }
+
+    pub fn is_winding_down(&self) -> bool {
+        self.status() == VaultStatus::WindingDown
+    }
+
+    pub fn min_strategy_version(&self) -> StrategyVersion {
+        StrategyVersion {
+            major: self.min_strategy_version_major,
+            minor: self.min_strategy_version_minor,
+            patch: self.min_strategy_version_patch,
+        }
+    }
+
+    /// Check a strategy's registry metadata (`None` = not in the registry)
+    /// against the vault's strategy requirements
+    pub fn check_strategy_requirements(&self, metadata: Option<&StrategyMetadata>) -> Result<()> {
+        let unrestricted = self.min_strategy_version() == StrategyVersion::default()
+            && self.audited_strategies_only == 0
+            && self.max_strategy_risk_score == 0;
+        if unrestricted {
+            return Ok(());
+        }
+        let metadata = metadata.ok_or(VaultError::StrategyNotWhitelisted)?;
+        require!(
+            metadata.version >= self.min_strategy_version(),
+            VaultError::StrategyVersionTooLow
+        );
+        require!(
+            self.audited_strategies_only == 0 || metadata.audited,
+            VaultError::StrategyNotAudited
+        );
+        // An unrated strategy fails any risk limit
+        require!(
+            self.max_strategy_risk_score == 0
+                || (1..=self.max_strategy_risk_score).contains(&metadata.risk_score),
+            VaultError::StrategyRiskTooHigh
+        );
+        Ok(())
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked != 0
+    }
+
+    /// Hold the reentrancy lock across a call into an external program;
+    /// fails if the vault is already mid-call
+    pub fn lock(&mut self) -> Result<()> {
+        require!(!self.is_locked(), VaultError::Reentrancy);
+        self.locked = 1;
+        Ok(())
+    }
+
+    pub fn unlock(&mut self) {
+        self.locked = 0;
+    }
+
+    pub fn is_policy_enforced(&self) -> bool {
+        self.policy_enforced != 0
+    }
+
+    pub fn is_reserves_proof_enforced(&self) -> bool {
+        self.reserves_proof_enforced != 0
+    }
+
+    pub fn fee_payout_mode(&self) -> FeePayoutMode {
+        if self.fee_payout_mode == FeePayoutMode::Assets as u8 {
+            FeePayoutMode::Assets
+        } else {
+            FeePayoutMode::Shares
+        }
+    }
+
+    /// Assets expected in the vault token account: NAV minus funds deployed
+    /// to strategies and to sibling vaults on other chains
+    pub fn idle_assets(&self) -> u64 {
+        self.total_assets
+            .saturating_sub(self.total_debt)
+            .saturating_sub(self.external_nav)
+    }
+
+    /// Check the configured price oracle, if any, is the account passed, its
+    /// confidence interval is within `max_oracle_conf_bps` and its price is
+    /// no older than `max_oracle_staleness`
+    pub fn check_oracle(&self, oracle: Option<&AccountInfo>) -> Result<()> {
+        if self.price_oracle == Pubkey::default() {
+            return Ok(());
+        }
+        let oracle = oracle.ok_or(VaultError::InvalidOracle)?;
+        require_keys_eq!(oracle.key(), self.price_oracle, VaultError::InvalidOracle);
+        require!(
+            oracle_conf_bps(oracle)? <= self.max_oracle_conf_bps as u64,
+            VaultError::OracleUntrusted
+        );
+        // Strict builds never trust an oracle of unbounded age
+        let max_staleness = match self.max_oracle_staleness {
+            0 if cfg!(feature = "strict-checks") => STRICT_MAX_ORACLE_STALENESS,
+            limit => limit,
+        };
+        if max_staleness > 0 {
+            let age = Clock::get()?
+                .unix_timestamp
+                .saturating_sub(oracle_publish_time(oracle)?);
+            require!(age <= max_staleness, VaultError::OracleStale);
+        }
+        Ok(())
+    }
+
+    /// Deposit cap in effect at `now`, or `None` when deposits are uncapped.
+    /// While a schedule runs, the cap starts at `cap_schedule_start_cap` and
+    /// grows by `cap_schedule_increment` every `cap_schedule_interval`, never
+    /// beyond a non-zero `deposit_cap`.
+    pub fn deposit_cap_at(&self, now: i64) -> Option<u64> {
+        let ceiling = (self.deposit_cap > 0).then_some(self.deposit_cap);
+        if self.cap_schedule_interval == 0 {
+            return ceiling;
+        }
+        let steps = now.saturating_sub(self.cap_schedule_started_at) / self.cap_schedule_interval;
+        let scheduled = self
+            .cap_schedule_start_cap
+            .saturating_add(self.cap_schedule_increment.saturating_mul(steps as u64));
+        Some(ceiling.map_or(scheduled, |ceiling| scheduled.min(ceiling)))
+    }
+
+    /// Check `wallet` holds a valid pass of the vault's gatekeeper network,
+    /// if one is configured
+    pub fn check_credential(
+        &self,
+        wallet: &Pubkey,
+        gateway_token: Option<&AccountInfo>,
+        now: i64,
+    ) -> Result<()> {
+        if self.gatekeeper_network == Pubkey::default() {
+            return Ok(());
+        }
+        let gateway_token = gateway_token.ok_or(VaultError::CredentialRequired)?;
+        check_gateway_token(gateway_token, wallet, &self.gatekeeper_network, now)
+    }
+
+    /// Whether deposits and withdrawals are accepted at `now` under the
+    /// vault's flow window
+    pub fn flows_open(&self, now: i64) -> bool {
+        if self.flow_window_period == 0 {
+            return true;
+        }
+        let offset = now.rem_euclid(self.flow_window_period);
+        if self.flow_window_open < self.flow_window_close {
+            offset >= self.flow_window_open && offset < self.flow_window_close
+        } else {
+            offset >= self.flow_window_open || offset < self.flow_window_close
+        }
+    }
+
+    /// Decimals of the share mint
+    pub fn share_decimals(&self) -> u8 {
+        self.asset_decimals + self.decimals_offset
+    }
+
+    /// Performance fee high-water mark, a Q64.64 share price
+    pub fn high_water_mark(&self) -> u128 {
+        u128::from_le_bytes(self.high_water_mark_q64)
+    }
+
+    pub fn set_high_water_mark(&mut self, price: u128) {
+        self.high_water_mark_q64 = price.to_le_bytes();
+    }
+
+    /// Accrued fees below one asset unit, in Q64.64
+    pub fn fee_remainder(&self) -> u128 {
+        u128::from_le_bytes(self.fee_remainder_q64)
+    }
+
+    pub fn set_fee_remainder(&mut self, remainder: u128) {
+        self.fee_remainder_q64 = remainder.to_le_bytes();
+    }
+
+    /// Rounding dust not yet swept, in Q64.64 asset units
+    pub fn dust(&self) -> u128 {
+        u128::from_le_bytes(self.dust_q64)
+    }
+
+    pub fn set_dust(&mut self, dust: u128) {
+        self.dust_q64 = dust.to_le_bytes();
+    }
+
+    /// Record value a share conversion truncated away
+    pub fn add_dust(&mut self, dust: u128) -> Result<()> {
+        let total = self
+            .dust()
+            .checked_add(dust)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        self.set_dust(total);
+        Ok(())
+    }
+
+    /// Share price before the vault's outstanding loss, in Q64.64 (0 = none)
+    pub fn pre_loss_price(&self) -> u128 {
+        u128::from_le_bytes(self.pre_loss_price_q64)
+    }
+
+    /// Loss in basis points a holder redeeming now takes against the
+    /// pre-loss share price
+    pub fn loss_bps(&self) -> Result<u64> {
+        let reference = self.pre_loss_price();
+        let price = share_price_q64(self.total_assets, self.total_shares)?;
+        if reference == 0 || price >= reference {
+            return Ok(0);
+        }
+        let loss = (reference - price)
+            .checked_mul(BPS_DENOMINATOR)
+            .ok_or(VaultError::ArithmeticOverflow)?
+            .div_ceil(reference);
+        u64::try_from(loss).map_err(|_| error!(VaultError::ArithmeticOverflow))
+    }
+
+    /// Remember the pre-loss share price when a NAV change lowers it, and
+    /// forget it once the price recovers
+    fn track_loss(&mut self, old_price: u128, new_price: u128) {
+        let reference = self.pre_loss_price();
+        let reference = if new_price < old_price && reference == 0 {
+            old_price
+        } else if new_price >= reference {
+            0
+        } else {
+            reference
+        };
+        self.pre_loss_price_q64 = reference.to_le_bytes();
+    }
+
+    /// Whether step 2 (`init_vault_accounts`) has run. Until then the vault
+    /// accepts no flows and can be cancelled with `cancel_init`.
+    pub fn is_initialized(&self) -> bool {
+        self.status() != VaultStatus::Uninitialized
+    }
+
+    pub fn is_from_template(&self) -> bool {
+        self.from_template != 0
+    }
+
+    pub fn is_epoch_gated(&self) -> bool {
+        self.epoch_duration > 0
+    }
+
+    /// Most NAV the redemptions queued in one epoch may take before the
+    /// redemption gate fills them pro rata, or `None` without a gate
+    pub fn redemption_gate_limit(&self) -> Option<u64> {
+        if self.redemption_gate_bps == 0 {
+            return None;
+        }
+        let limit =
+            self.total_assets as u128 * self.redemption_gate_bps as u128 / BPS_DENOMINATOR;
+        Some(limit as u64)
+    }
+
+    /// Most NAV the redemptions queued in one epoch may take when settled at
+    /// `now`: the lower of the redemption gate and what is left of the
+    /// outflow window, or `None` without either limit
+    pub fn redemption_limit(&self, now: i64) -> Option<u64> {
+        let outflow = self.outflow_capacity(now);
+        match self.redemption_gate_limit() {
+            Some(gate) => Some(gate.min(outflow)),
+            None => (outflow != u64::MAX).then_some(outflow),
+        }
+    }
+
+    /// Shares of `redeems` queued this epoch that settle at `now`: all of
+    /// them, or once they are worth more than `redemption_limit` allows, the
+    /// fraction worth at most the limit, so every request is filled pro rata
+    /// instead of the earliest in full
+    pub fn gated_redeems(&self, redeems: u64, now: i64) -> Result<u64> {
+        let Some(limit) = self.redemption_limit(now) else {
+            return Ok(redeems);
+        };
+        let value = calculate_assets_to_return(redeems, self.total_assets, self.total_shares)?;
+        if value <= limit {
+            return Ok(redeems);
+        }
+        // Rounded so the filled shares never pay out more than the limit
+        let filled = pro_rata(redeems, limit, value)?;
+        Ok(filled.min(pro_rata(limit, self.total_shares, self.total_assets)?))
+    }
+
+    /// Sequence number for the vault's next event
+    pub fn next_event_seq(&mut self) -> u64 {
+        self.event_seq += 1;
+        self.event_seq
+    }
+
+    pub fn is_paper(&self) -> bool {
+        self.paper != 0
+    }
+
+    /// Balance of the vault token account as the vault accounts for it: the
+    /// simulated balance for a paper vault, else the real `token_balance`
+    pub fn token_balance(&self, token_balance: u64) -> u64 {
+        if self.is_paper() {
+            self.paper_balance
+        } else {
+            token_balance
+        }
+    }
+
+    /// Record `amount` simulated as received by a paper vault (no-op otherwise)
+    pub fn credit_paper_balance(&mut self, amount: u64) -> Result<()> {
+        if self.is_paper() {
+            self.paper_balance = self
+                .paper_balance
+                .checked_add(amount)
+                .ok_or(VaultError::ArithmeticOverflow)?;
+        }
+        Ok(())
+    }
+
+    /// Record `amount` simulated as paid out by a paper vault (no-op otherwise)
+    pub fn debit_paper_balance(&mut self, amount: u64) -> Result<()> {
+        if self.is_paper() {
+            self.paper_balance = self
+                .paper_balance
+                .checked_sub(amount)
+                .ok_or(VaultError::InsufficientAssets)?;
+        }
+        Ok(())
+    }
+
+    /// Part of the vault token balance that belongs to NAV, excluding queued
+    /// epoch deposits and settled redemptions awaiting their claim
+    pub fn liquid_assets(&self, token_balance: u64) -> u64 {
+        token_balance.saturating_sub(self.epoch_reserved_assets)
+    }
+
+    /// Entry and exit fees on the liquidity fee curve, in basis points, given
+    /// the vault's liquid `token_balance`
+    pub fn liquidity_fees_bps(&self, token_balance: u64) -> (u64, u64) {
+        liquidity_fees_bps(
+            self.liquid_assets(token_balance),
+            self.total_assets,
+            self.liquidity_fee_kink_bps,
+            self.max_liquidity_entry_fee_bps,
+            self.max_liquidity_exit_fee_bps,
+        )
+    }
+
+    /// Liquidity held back for withdrawals at or below `small_withdrawal_threshold`
+    pub fn liquidity_reserve(&self) -> Result<u64> {
+        let reserve = (self.total_assets as u128)
+            .checked_mul(self.reserve_ratio_bps as u128)
+            .ok_or(VaultError::ArithmeticOverflow)?
+            / BPS_DENOMINATOR;
+        Ok(reserve as u64)
+    }
+
+    fn outflow_window_expired(&self, now: i64) -> bool {
+        now.saturating_sub(self.outflow_window_start) >= OUTFLOW_WINDOW
+    }
+
+    /// Underlying withdrawals and epoch settlements may still pay out in the
+    /// outflow window open at `now` (`u64::MAX` without a limit)
+    pub fn outflow_capacity(&self, now: i64) -> u64 {
+        let (used, base) = if self.outflow_window_expired(now) {
+            (0, self.total_assets)
+        } else {
+            (self.outflow_window_used, self.outflow_window_base)
+        };
+        let mut limit = u64::MAX;
+        if self.max_window_outflow > 0 {
+            limit = self.max_window_outflow;
+        }
+        if self.max_window_outflow_bps > 0 {
+            let relative = base as u128 * self.max_window_outflow_bps as u128 / BPS_DENOMINATOR;
+            limit = limit.min(relative as u64);
+        }
+        limit.saturating_sub(used)
+    }
+
+    /// Most shares a withdrawal can redeem at the current NAV without passing
+    /// the outflow limit at `now` (`u64::MAX` without a limit)
+    pub fn outflow_share_capacity(&self, now: i64) -> Result<u64> {
+        let capacity = self.outflow_capacity(now);
+        if capacity == u64::MAX {
+            return Ok(u64::MAX);
+        }
+        pro_rata(capacity, self.total_shares, self.total_assets)
+    }
+
+    /// Count `assets` paid out by a withdrawal or epoch settlement against
+    /// the outflow limit, opening a new window once the current one has run
+    /// `OUTFLOW_WINDOW`. Call before `total_assets` is reduced by the payout.
+    pub fn record_outflow(&mut self, assets: u64, now: i64) -> Result<()> {
+        if self.max_window_outflow == 0 && self.max_window_outflow_bps == 0 {
+            return Ok(());
+        }
+        require!(
+            assets <= self.outflow_capacity(now),
+            VaultError::OutflowLimitReached
+        );
+        if self.outflow_window_expired(now) {
+            self.outflow_window_start = now;
+            self.outflow_window_used = 0;
+            self.outflow_window_base = self.total_assets;
+        }
+        self.outflow_window_used = self
+            .outflow_window_used
+            .checked_add(assets)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Count `assets` deposited against the inflow limit, opening a new
+    /// window once the current one has run `INFLOW_WINDOW`. A deposit over
+    /// the limit fails, logging when the window reopens.
+    pub fn record_inflow(&mut self, assets: u64, now: i64) -> Result<()> {
+        if self.max_window_inflow == 0 {
+            return Ok(());
+        }
+        if now.saturating_sub(self.inflow_window_start) >= INFLOW_WINDOW {
+            self.inflow_window_start = now;
+            self.inflow_window_used = 0;
+        }
+
+        let used = self
+            .inflow_window_used
+            .checked_add(assets)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        if used > self.max_window_inflow {
+            // A failed transaction drops its events, so the retry time is logged
+            msg!(
+                "Inflow limit reached; retry after {}",
+                self.inflow_window_start.saturating_add(INFLOW_WINDOW)
+            );
+            return err!(VaultError::InflowRateLimited);
+        }
+        self.inflow_window_used = used;
+        Ok(())
+    }
+
+    /// Exit fee on `assets` withdrawn `held` seconds after the holder's last
+    /// deposit: the full `exit_fee_bps` through `exit_fee_full_period`, then
+    /// falling linearly to zero at `exit_fee_decay_end`
+    pub fn exit_fee(&self, assets: u64, held: i64) -> Result<u64> {
+        if self.exit_fee_bps == 0 || held >= self.exit_fee_decay_end {
+            return Ok(0);
+        }
+        let mut fee_bps = self.exit_fee_bps as u128;
+        if held > self.exit_fee_full_period {
+            // full_period < held < decay_end, so the span is non-zero
+            let remaining = (self.exit_fee_decay_end - held) as u128;
+            let span = (self.exit_fee_decay_end - self.exit_fee_full_period) as u128;
+            fee_bps = fee_bps * remaining / span;
+        }
+        let fee = (assets as u128)
+            .checked_mul(fee_bps)
+            .ok_or(VaultError::ArithmeticOverflow)?
+            / BPS_DENOMINATOR;
+        Ok(fee as u64)
+    }
+
+    /// Liquid assets the vault aims to hold, per `target_liquidity_bps`
+    pub fn liquidity_target(&self) -> Result<u64> {
+        let target = (self.total_assets as u128)
+            .checked_mul(self.target_liquidity_bps as u128)
+            .ok_or(VaultError::ArithmeticOverflow)?
+            / BPS_DENOMINATOR;
+        Ok(target as u64)
+    }
+
+    /// Whether a deallocation request has passed its deadline unfulfilled
+    pub fn is_deallocation_overdue(&self, now: i64) -> bool {
+        self.pending_deallocation > 0 && now > self.deallocation_deadline
+    }
+
+    /// Credit funds returned from strategies against the pending request
+    pub fn fulfil_deallocation(&mut self, amount: u64) {
+        self.pending_deallocation = self.pending_deallocation.saturating_sub(amount);
+        if self.pending_deallocation == 0 {
+            self.deallocation_deadline = 0;
+        }
+    }
 
     /// Check if a pubkey is an authorized manager
     pub fn is_manager(&self, key: &Pubkey) -> bool {
@@ -81,21 +1130,2108 @@ impl VaultState {
         Ok(())
     }
 
-    /// Remove a manager from the vault (swap-remove for gas efficiency)
-    pub fn remove_manager(&mut self, key: Pubkey) -> Result<()> {
-        let mut found = false;
-        for i in 0..self.manager_count as usize {
-            if self.managers[i] == key {
-                // Swap with last element and decrement count
-                let last_idx = (self.manager_count - 1) as usize;
-                self.managers[i] = self.managers[last_idx];
-                self.managers[last_idx] = Pubkey::default();
-                self.manager_count -= 1;
-                found = true;
-                break;
-            }
+    /// Accrue management fees for the time elapsed since the last accrual and
+    /// performance fees on any increase above the high-water mark, then move
+    /// the vault to `new_total_assets`.
+    ///
+    /// Returns the (management, performance) fees accrued by this call.
+    /// No fees accrue while the vault is winding down. Management fees skip
+    /// any part of the period inside the fee holiday, and performance fees
+    /// are waived for a period that touches it. A period touching a fee
+    /// increase's grace period is charged the blended grace rates for it.
+    pub fn accrue_fees(
+        &mut self,
+        new_total_assets: u64,
+        now: i64,
+        events: EventCpi,
+    ) -> Result<(u64, u64)> {
+        if self.total_shares > 0 {
+            self.track_loss(
+                share_price_q64(self.total_assets, self.total_shares)?,
+                share_price_q64(new_total_assets, self.total_shares)?,
+            );
         }
-        require!(found, VaultError::ManagerNotFound);
-        Ok(())
+
+        if self.is_winding_down() {
+            self.total_assets = new_total_assets;
+            self.last_fee_collection = now;
+            return Ok((0, 0));
+        }
+
+        let seconds_elapsed = now
+            .checked_sub(self.last_fee_collection)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+
+        let holiday = self.fee_holiday_overlap(self.last_fee_collection, now);
+
+        // Charge the part of the period inside a fee increase's grace period
+        // at the blended grace rates, the rest at the current rates
+        let (grace_mgmt_bps, grace_perf_bps) = self.grace_fee_bps();
+        let grace_end = now
+            .min(self.fee_grace_ends_at)
+            .max(self.last_fee_collection);
+        let grace_span = grace_end - self.last_fee_collection;
+        let in_grace =
+            grace_span - self.fee_holiday_overlap(self.last_fee_collection, grace_end);
+        let after_grace =
+            seconds_elapsed - grace_span - self.fee_holiday_overlap(grace_end, now);
+        let mgmt_fee = calculate_management_fee(self.total_assets, grace_mgmt_bps, in_grace)?
+            .checked_add(calculate_management_fee(
+                self.total_assets,
+                self.management_fee_bps,
+                after_grace,
+            )?)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+
+        let perf_bps = if self.last_fee_collection < self.fee_grace_ends_at {
+            grace_perf_bps
+        } else {
+            self.performance_fee_bps
+        };
+        let (mut perf_fee, new_hwm) = calculate_performance_fee(
+            new_total_assets,
+            self.benchmark_hurdle()?,
+            perf_bps,
+            self.total_shares,
+        )?;
+        // The mark still moves up, so holiday gains are never charged later;
+        // neither are gains measured against a benchmark level gone stale
+        if holiday > 0 || self.is_benchmark_stale(now) {
+            perf_fee = 0;
+        }
+
+        // Carry the sub-unit remainder so repeated accruals don't truncate to zero
+        let (fee_units, remainder) = q64_to_amount(
+            mgmt_fee
+                .checked_add(perf_fee)
+                .and_then(|fee| fee.checked_add(self.fee_remainder()))
+                .ok_or(VaultError::ArithmeticOverflow)?,
+        )?;
+        let accrued = self
+            .accrued_management_fee
+            .checked_add(fee_units)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        let cap = self.accrued_fee_cap(new_total_assets)?.max(self.accrued_management_fee);
+        let mut mgmt_units = q64_to_amount(mgmt_fee)?.0;
+        let mut perf_units = q64_to_amount(perf_fee)?.0;
+        if accrued > cap {
+            // Forgo the excess, performance fee first, rather than let the
+            // claim grow until crystallizing it dilutes holders
+            let forgone = accrued - cap;
+            let perf_forgone = forgone.min(perf_units);
+            perf_units -= perf_forgone;
+            mgmt_units = mgmt_units.saturating_sub(forgone - perf_forgone);
+            self.accrued_management_fee = cap;
+            self.set_fee_remainder(0);
+            events.emit(AccruedFeeCapReached {
+                vault: Pubkey::create_program_address(&self.signer_seeds(), &crate::ID)
+                    .map_err(|_| VaultError::InvariantViolation)?,
+                event_seq: self.next_event_seq(),
+                cap,
+                forgone,
+            })?;
+        } else {
+            self.accrued_management_fee = accrued;
+            self.set_fee_remainder(remainder);
+        }
+
+        self.total_assets = new_total_assets;
+        self.set_high_water_mark(new_hwm);
+        self.benchmark_level = self.benchmark_latest;
+        self.last_fee_collection = now;
+        Ok((mgmt_units, perf_units))
+    }
+
+    /// Part of `amount` in collected fees paid to the donation recipient,
+    /// rounded down
+    pub fn donation_cut(&self, amount: u64) -> u64 {
+        (amount as u128 * self.donation_bps as u128 / BPS_DENOMINATOR) as u64
+    }
+
+    pub fn has_benchmark(&self) -> bool {
+        self.benchmark_oracle != Pubkey::default()
+    }
+
+    /// High-water mark performance fees are charged above: with a benchmark,
+    /// the mark scaled by the index's move since it was set, so only returns
+    /// beyond the index's are charged
+    pub fn benchmark_hurdle(&self) -> Result<u128> {
+        if !self.has_benchmark() || self.benchmark_level == 0 {
+            return Ok(self.high_water_mark());
+        }
+        scale_q64(
+            self.high_water_mark(),
+            self.benchmark_latest,
+            self.benchmark_level,
+        )
+    }
+
+    /// Whether the synced benchmark level is older than the feed's staleness
+    /// limit, so returns cannot be measured against it
+    pub fn is_benchmark_stale(&self, now: i64) -> bool {
+        self.has_benchmark()
+            && self.max_benchmark_staleness > 0
+            && now.saturating_sub(self.benchmark_published_at) > self.max_benchmark_staleness
+    }
+
+    /// Seconds of `[from, to)` that fall inside the fee holiday
+    pub fn fee_holiday_overlap(&self, from: i64, to: i64) -> i64 {
+        let start = from.max(self.fee_holiday_start);
+        let end = to.min(self.fee_holiday_end);
+        (end - start).max(0)
+    }
+
+    /// (management, performance) fee rates charged while a fee increase's
+    /// grace period runs: the previous rates on grandfathered shares and the
+    /// current rates on the rest, never above the current rates
+    pub fn grace_fee_bps(&self) -> (u16, u16) {
+        let total = self.total_shares as u128;
+        if total == 0 {
+            return (self.management_fee_bps, self.performance_fee_bps);
+        }
+        let grandfathered = self.grandfathered_shares.min(self.total_shares) as u128;
+        let blend = |previous: u16, current: u16| {
+            let previous = previous.min(current) as u128;
+            let current = current as u128;
+            ((previous * grandfathered + current * (total - grandfathered)) / total) as u16
+        };
+        (
+            blend(self.grandfathered_management_fee_bps, self.management_fee_bps),
+            blend(self.grandfathered_performance_fee_bps, self.performance_fee_bps),
+        )
+    }
+
+    /// Whether a fee increase's grace period is running at `now`
+    pub fn fee_grace_active(&self, now: i64) -> bool {
+        now < self.fee_grace_ends_at
+    }
+
+    /// Start a grace period after raising fees from the given previous rates:
+    /// shares outstanding now keep those rates until it ends, unless their
+    /// holders redeem them
+    pub fn start_fee_grace(
+        &mut self,
+        previous_management_fee_bps: u16,
+        previous_performance_fee_bps: u16,
+        now: i64,
+    ) -> Result<()> {
+        self.fee_epoch = self
+            .fee_epoch
+            .checked_add(1)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        self.grandfathered_shares = self.total_shares;
+        self.grandfathered_management_fee_bps = previous_management_fee_bps;
+        self.grandfathered_performance_fee_bps = previous_performance_fee_bps;
+        self.fee_grace_ends_at = now
+            .checked_add(FEE_INCREASE_GRACE_PERIOD)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Whether rescheduling the fee holiday would change fees on a period
+    /// not yet accrued
+    pub fn fee_holiday_in_effect(&self, now: i64) -> bool {
+        self.fee_holiday_start <= now && self.last_fee_collection < self.fee_holiday_end
+    }
+
+    /// Most accrued fees a vault holding `total_assets` may carry
+    fn accrued_fee_cap(&self, total_assets: u64) -> Result<u64> {
+        if self.max_accrued_fee_bps == 0 {
+            return Ok(u64::MAX);
+        }
+        let cap = (total_assets as u128)
+            .checked_mul(self.max_accrued_fee_bps as u128)
+            .ok_or(VaultError::ArithmeticOverflow)?
+            / BPS_DENOMINATOR;
+        Ok(cap as u64)
+    }
+
+    /// (total_assets, total_shares) pair used to compare share prices across an instruction
+    pub fn share_price_snapshot(&self) -> (u64, u64) {
+        (self.total_assets, self.total_shares)
+    }
+
+    /// Fail fast on accounting corruption. Compiled to a no-op unless the
+    /// `invariant-checks` feature (implied by `strict-checks`) is enabled, to
+    /// save compute on mainnet builds.
+    ///
+    /// - `total_shares == 0` implies `total_assets` is at most dust
+    /// - accrued fees never exceed total assets
+    /// - if `before` is given, the share price did not decrease
+    pub fn assert_invariants(&self, before: Option<(u64, u64)>) -> Result<()> {
+        if !cfg!(feature = "invariant-checks") {
+            return Ok(());
+        }
+
+        if self.total_shares == 0 {
+            require!(
+                self.total_assets <= INVARIANT_DUST_TOLERANCE,
+                VaultError::InvariantViolation
+            );
+        }
+        require!(
+            self.accrued_management_fee <= self.total_assets,
+            VaultError::InvariantViolation
+        );
+
+        if let Some((assets_before, shares_before)) = before {
+            if shares_before > 0 && self.total_shares > 0 {
+                // assets_after / shares_after >= assets_before / shares_before
+                let lhs = (self.total_assets as u128)
+                    .checked_mul(shares_before as u128)
+                    .ok_or(VaultError::ArithmeticOverflow)?;
+                let rhs = (assets_before as u128)
+                    .checked_mul(self.total_shares as u128)
+                    .ok_or(VaultError::ArithmeticOverflow)?;
+                require!(lhs >= rhs, VaultError::InvariantViolation);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove a manager from the vault (swap-remove for gas efficiency)
+    pub fn remove_manager(&mut self, key: Pubkey) -> Result<()> {
+        let mut found = false;
+        for i in 0..self.manager_count as usize {
+            if self.managers[i] == key {
+                // Swap with last element and decrement count
+                let last_idx = (self.manager_count - 1) as usize;
+                self.managers[i] = self.managers[last_idx];
+                self.managers[last_idx] = Pubkey::default();
+                self.manager_count -= 1;
+                found = true;
+                break;
+            }
+        }
+        require!(found, VaultError::ManagerNotFound);
+        Ok(())
+    }
+}
+
+#[account]
+pub struct ManagerProposal {
+    /// Vault the manager would be added to
+    pub vault: Pubkey,
+    /// Proposed manager key
+    pub manager: Pubkey,
+    /// Shares voted in favour
+    pub votes_for: u64,
+    /// Shares voted against (veto)
+    pub votes_against: u64,
+    /// End of the voting window (unix timestamp)
+    pub voting_ends_at: i64,
+    /// Event sequence number of the proposal's `ManagerProposed` event,
+    /// telling apart repeated proposals of the same manager
+    pub id: u64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl ManagerProposal {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 8 + 8 + 8 + 8 + 1 + 64 = 161
+    pub const LEN: usize = 8 + 161;
+
+    /// A proposal is approved unless holders vetoed it with a majority
+    pub fn is_approved(&self) -> bool {
+        self.votes_for >= self.votes_against
+    }
+}
+
+#[account]
+pub struct ManagerVoteRecord {
+    /// Vault the vote was cast in
+    pub vault: Pubkey,
+    /// Share holder who locked shares
+    pub voter: Pubkey,
+    /// Proposal voted on, and its `id`; both seed the record, so a vote on
+    /// an earlier proposal of the same manager never blocks a new one
+    pub proposal: Pubkey,
+    pub proposal_id: u64,
+    /// Shares locked in the vote escrow (vote weight snapshot)
+    pub weight: u64,
+    /// Whether the vote approves the manager
+    pub approve: bool,
+    /// When the locked shares become releasable
+    pub voting_ends_at: i64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl ManagerVoteRecord {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 32 + 8 + 8 + 1 + 8 + 1 + 64 = 186
+    pub const LEN: usize = 8 + 186;
+}
+
+#[account]
+pub struct StrategyState {
+    /// Vault this strategy belongs to
+    pub vault: Pubkey,
+    /// Strategy identifier (adapter program id or agent-chosen key)
+    pub strategy_id: Pubkey,
+    /// Authority over the strategy's token account — must sign deallocations
+    pub authority: Pubkey,
+    /// Token account holding funds deployed to this strategy
+    pub token_account: Pubkey,
+
+    /// Underlying owed to the vault by this strategy, as of the last report
+    pub current_debt: u64,
+    /// Cumulative realized profit reported by this strategy
+    pub total_gain: u64,
+    /// Cumulative realized loss reported by this strategy
+    pub total_loss: u64,
+    /// Last time the strategy reported (unix timestamp)
+    pub last_report: i64,
+    /// Last time `ping_strategy` checked the report age (unix timestamp)
+    pub last_health_check: i64,
+
+    /// Most the vault may have allocated to the strategy, moved by reported
+    /// returns between the owner's bounds
+    pub debt_ceiling: u64,
+    /// Lowest the debt ceiling can fall to
+    pub min_debt_ceiling: u64,
+    /// Highest the debt ceiling can rise to (0 = no ceiling)
+    pub max_debt_ceiling: u64,
+
+    /// Most `withdraw_routed` may pull from the strategy per withdrawal
+    /// (0 = not drawn on for withdrawals)
+    pub instant_withdraw_limit: u64,
+    /// Order `withdraw_routed` draws on strategies in, lowest first
+    pub withdraw_priority: u8,
+
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl StrategyState {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 64 = 266
+    pub const LEN: usize = 8 + 266;
+
+    /// Whether the last report is older than `max_report_age` (0 = no limit)
+    pub fn is_stale(&self, now: i64, max_report_age: i64) -> bool {
+        max_report_age > 0 && now.saturating_sub(self.last_report) > max_report_age
+    }
+
+    /// Whether the owner has bounded the strategy's debt
+    pub fn has_debt_ceiling(&self) -> bool {
+        self.max_debt_ceiling > 0
+    }
+
+    /// Whether `debt` fits under the strategy's debt ceiling, if any
+    pub fn within_debt_ceiling(&self, debt: u64) -> bool {
+        !self.has_debt_ceiling() || debt <= self.debt_ceiling
+    }
+
+    /// Move the debt ceiling by `DEBT_CEILING_RESPONSE` times the return a
+    /// report realized on `prior_debt`, up for gains and down for losses,
+    /// staying within the owner's bounds
+    pub fn adjust_debt_ceiling(&mut self, prior_debt: u64, gain: u64, loss: u64) {
+        if !self.has_debt_ceiling() || prior_debt == 0 {
+            return;
+        }
+        let ceiling = self.debt_ceiling as u128;
+        let step = |pnl: u64| {
+            let step = ceiling
+                .saturating_mul(pnl as u128)
+                .saturating_mul(DEBT_CEILING_RESPONSE)
+                / prior_debt as u128;
+            step.min(u64::MAX as u128) as u64
+        };
+        self.debt_ceiling = self
+            .debt_ceiling
+            .saturating_add(step(gain))
+            .saturating_sub(step(loss))
+            .clamp(self.min_debt_ceiling, self.max_debt_ceiling);
+    }
+
+    /// Most `withdraw_routed` can pull from the strategy right now: its
+    /// instant-withdraw limit, capped at its debt, its token `balance` and the
+    /// amount `delegated` to the vault
+    pub fn instant_withdrawable(&self, balance: u64, delegated: u64) -> u64 {
+        self.instant_withdraw_limit
+            .min(self.current_debt)
+            .min(balance)
+            .min(delegated)
+    }
+}
+
+#[account]
+pub struct FeeReport {
+    /// Vault this report belongs to
+    pub vault: Pubkey,
+    /// Lifetime management fees accrued (in asset units)
+    pub total_management_fees: u64,
+    /// Lifetime performance fees accrued (in asset units)
+    pub total_performance_fees: u64,
+    /// Lifetime fees crystallized by `collect_fees` (in asset units)
+    pub total_fees_collected: u64,
+    /// Lifetime fee shares minted to the treasury
+    pub total_fee_shares_minted: u64,
+    /// Lifetime collected fees paid to the donation recipient (in asset units)
+    pub total_fees_donated: u64,
+    /// Number of times fees have been crystallized
+    pub crystallization_count: u64,
+    /// Time of the last crystallization (unix timestamp)
+    pub last_crystallized_at: i64,
+    /// High-water mark (Q64.64 share price) snapshotted at the last crystallization
+    pub high_water_mark_at_crystallization: u128,
+    /// Benchmark level snapshotted at the last crystallization (0 = no benchmark)
+    pub benchmark_at_crystallization: u64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl FeeReport {
+    /// Account discriminator (8) + all fields
+    /// 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 16 + 8 + 1 + 64 = 177
+    pub const LEN: usize = 8 + 177;
+
+    /// Add newly accrued fees to the lifetime totals
+    pub fn record_accrual(&mut self, management_fee: u64, performance_fee: u64) -> Result<()> {
+        self.total_management_fees = self
+            .total_management_fees
+            .checked_add(management_fee)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        self.total_performance_fees = self
+            .total_performance_fees
+            .checked_add(performance_fee)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Record a fee crystallization and snapshot the high-water mark and
+    /// benchmark level
+    pub fn record_crystallization(
+        &mut self,
+        fee_amount: u64,
+        fee_shares: u64,
+        donated: u64,
+        high_water_mark: u128,
+        benchmark_level: u64,
+        now: i64,
+    ) -> Result<()> {
+        self.total_fees_collected = self
+            .total_fees_collected
+            .checked_add(fee_amount)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        self.total_fee_shares_minted = self
+            .total_fee_shares_minted
+            .checked_add(fee_shares)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        self.total_fees_donated = self
+            .total_fees_donated
+            .checked_add(donated)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        self.crystallization_count = self
+            .crystallization_count
+            .checked_add(1)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        self.last_crystallized_at = now;
+        self.high_water_mark_at_crystallization = high_water_mark;
+        self.benchmark_at_crystallization = benchmark_level;
+        Ok(())
+    }
+}
+
+/// A holding's value before and after the vault's accrued fees are
+/// collected, as returned by `get_share_value`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ShareValue {
+    pub shares: u64,
+    /// Underlying the shares are worth at the current NAV
+    pub gross_assets: u64,
+    /// Underlying they are worth once the accrued fees are collected, by
+    /// dilution or payout per the vault's fee payout mode
+    pub net_assets: u64,
+    /// Fees accrued across the vault and not yet collected (in asset units)
+    pub accrued_fees: u64,
+}
+
+#[account]
+pub struct UserPosition {
+    /// Vault the position is held in
+    pub vault: Pubkey,
+    /// Position holder
+    pub user: Pubkey,
+    /// Lifetime underlying deposited
+    pub total_deposited: u64,
+    /// Lifetime underlying withdrawn
+    pub total_withdrawn: u64,
+    /// First deposit time (unix timestamp)
+    pub first_deposit_at: i64,
+    /// Most recent deposit time (unix timestamp)
+    pub last_deposit_at: i64,
+    /// Slot of the most recent deposit
+    pub last_deposit_slot: u64,
+    /// Share balance earning rewards, as of the last rewards checkpoint
+    pub reward_shares: u64,
+    /// Rewards-per-share accumulator value at the last checkpoint
+    pub reward_per_share_paid: u128,
+    /// Rewards earned but not yet claimed
+    pub pending_rewards: u64,
+    /// Share price the stop-loss is measured from, in Q64.64
+    pub stop_loss_ref_price_q64: u128,
+    /// Drop below the reference price that triggers the stop-loss, in basis
+    /// points (0 = no stop-loss)
+    pub stop_loss_bps: u16,
+    /// Share of the exit paid to the crank that executes it, in basis points
+    pub stop_loss_bounty_bps: u16,
+    /// Vault `fee_epoch` when the holder last went from no shares to some;
+    /// behind the vault's means the shares are grandfathered
+    pub fee_epoch: u64,
+    /// Highest client nonce passed to `deposit_with_nonce` (0 = none yet)
+    pub deposit_nonce: u64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl UserPosition {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 16 + 8 + 16 + 2 + 2 + 8 + 8 + 1 + 64 = 237
+    pub const LEN: usize = 8 + 237;
+
+    pub fn has_stop_loss(&self) -> bool {
+        self.stop_loss_bps > 0
+    }
+
+    /// Whether a share price of `price_q64` is at or below the stop-loss level
+    pub fn is_stop_loss_triggered(&self, price_q64: u128) -> bool {
+        let threshold = self.stop_loss_ref_price_q64 / BPS_DENOMINATOR
+            * (BPS_DENOMINATOR - self.stop_loss_bps as u128);
+        self.has_stop_loss() && price_q64 <= threshold
+    }
+
+    /// Populate identity fields on a freshly created (`init_if_needed`) position
+    pub fn ensure_initialized(&mut self, vault: Pubkey, user: Pubkey, bump: u8) {
+        if self.user == Pubkey::default() {
+            self.vault = vault;
+            self.user = user;
+            self.bump = bump;
+        }
+    }
+
+    /// Credit rewards earned since the last checkpoint and start earning on
+    /// `new_balance`. Shares moved in from outside the vault only earn from the
+    /// holder's next checkpoint, and shares moved out stop earning immediately,
+    /// so rewards are paid on the lesser of the checkpointed and current balance.
+    pub fn checkpoint_rewards(
+        &mut self,
+        pool: &RewardsPool,
+        current_balance: u64,
+        new_balance: u64,
+    ) -> Result<()> {
+        let earning = self.reward_shares.min(current_balance) as u128;
+        let earned = pool
+            .reward_per_share
+            .checked_sub(self.reward_per_share_paid)
+            .and_then(|delta| delta.checked_mul(earning))
+            .ok_or(VaultError::ArithmeticOverflow)?
+            / REWARD_PRECISION;
+
+        self.pending_rewards = self
+            .pending_rewards
+            .checked_add(u64::try_from(earned).map_err(|_| VaultError::ArithmeticOverflow)?)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        self.reward_per_share_paid = pool.reward_per_share;
+        self.reward_shares = new_balance;
+        Ok(())
+    }
+}
+
+/// The vaults a user holds positions in, so a wallet can enumerate them with
+/// one fetch. A vault is listed on the user's first deposit and dropped when
+/// the position is closed; vaults past `MAX_PORTFOLIO_VAULTS` are not listed.
+#[account]
+pub struct Portfolio {
+    pub user: Pubkey,
+    /// Listed vaults; only the first `vault_count` are meaningful
+    pub vaults: [Pubkey; MAX_PORTFOLIO_VAULTS],
+    pub vault_count: u8,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl Portfolio {
+    /// Account discriminator (8) + all fields
+    /// 32 + (32*32) + 1 + 1 + 64 = 1122
+    pub const LEN: usize = 8 + 1122;
+
+    pub fn listed(&self) -> &[Pubkey] {
+        &self.vaults[..self.vault_count as usize]
+    }
+
+    /// List `vault`, populating identity fields on a freshly created
+    /// (`init_if_needed`) portfolio. A full portfolio is left as is.
+    pub fn insert(&mut self, user: Pubkey, vault: Pubkey, bump: u8) {
+        if self.user == Pubkey::default() {
+            self.user = user;
+            self.bump = bump;
+        }
+        let count = self.vault_count as usize;
+        if !self.listed().contains(&vault) && count < MAX_PORTFOLIO_VAULTS {
+            self.vaults[count] = vault;
+            self.vault_count += 1;
+        }
+    }
+
+    /// Drop `vault` from the portfolio at `portfolio_account`, if the user
+    /// has one. The address itself is checked by the instruction's seeds
+    /// constraint.
+    pub fn remove(portfolio_account: &AccountInfo, vault: &Pubkey) -> Result<()> {
+        if portfolio_account.owner != &crate::ID || portfolio_account.data_is_empty() {
+            return Ok(());
+        }
+        let mut data = portfolio_account.try_borrow_mut_data()?;
+        let mut portfolio = Portfolio::try_deserialize(&mut &data[..])?;
+        let Some(index) = portfolio.listed().iter().position(|v| v == vault) else {
+            return Ok(());
+        };
+        let last = portfolio.vault_count as usize - 1;
+        portfolio.vaults[index] = portfolio.vaults[last];
+        portfolio.vaults[last] = Pubkey::default();
+        portfolio.vault_count -= 1;
+        portfolio.try_serialize(&mut &mut data[..])
+    }
+}
+
+/// A user's queued deposit and/or redemption for one epoch, settled at the
+/// epoch's crystallized share price and claimed afterwards
+#[account]
+pub struct EpochRequest {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    /// Epoch the request was queued in
+    pub epoch: u64,
+    /// Assets queued for deposit
+    pub deposit_assets: u64,
+    /// Shares queued for redemption
+    pub redeem_shares: u64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl EpochRequest {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 8 + 8 + 8 + 1 + 64 = 153
+    pub const LEN: usize = 8 + 153;
+
+    pub fn is_empty(&self) -> bool {
+        self.deposit_assets == 0 && self.redeem_shares == 0
+    }
+}
+
+/// Outcome of settling one epoch: the share price it was crystallized at and
+/// the totals that queued requests are paid out of, pro rata
+#[account]
+pub struct EpochSettlement {
+    pub vault: Pubkey,
+    pub epoch: u64,
+    /// NAV and share supply the epoch settled at (before its own flows)
+    pub total_assets: u64,
+    pub total_shares: u64,
+    /// Assets deposited and shares minted for them
+    pub deposits: u64,
+    pub shares_minted: u64,
+    /// Shares queued for redemption, those the redemption gate let through
+    /// and the assets paid for them
+    pub redeems: u64,
+    pub redeems_filled: u64,
+    pub assets_paid: u64,
+    /// Settlement time (unix timestamp)
+    pub settled_at: i64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl EpochSettlement {
+    /// Account discriminator (8) + all fields
+    /// 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 64 = 169
+    pub const LEN: usize = 8 + 169;
+
+    /// (shares, assets) owed to a request queued in this epoch. Shares cover
+    /// those minted for its deposit and its redemption's unfilled part.
+    pub fn entitlement(&self, request: &EpochRequest) -> Result<(u64, u64)> {
+        let minted = pro_rata(request.deposit_assets, self.shares_minted, self.deposits)?;
+        let unfilled = pro_rata(
+            request.redeem_shares,
+            self.redeems - self.redeems_filled,
+            self.redeems,
+        )?;
+        let assets = pro_rata(request.redeem_shares, self.assets_paid, self.redeems)?;
+        let shares = minted
+            .checked_add(unfilled)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        Ok((shares, assets))
+    }
+}
+
+/// Queued redemptions of the current epoch and how the redemption gate
+/// would fill them, as returned by `get_redemption_queue`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RedemptionQueue {
+    pub epoch: u64,
+    /// When the epoch can be settled (unix timestamp)
+    pub epoch_ends_at: i64,
+    /// Shares queued for redemption and their value at the current NAV
+    pub pending_redeems: u64,
+    pub pending_redeem_assets: u64,
+    pub redemption_gate_bps: u16,
+    /// Most NAV the epoch's redemptions may take under the redemption gate
+    /// and outflow limit (`u64::MAX` without either)
+    pub gate_limit: u64,
+    /// Share of each request settlement would fill now, in basis points
+    pub fill_bps: u16,
+}
+
+/// `part * total / whole`, rounded down (0 when `whole` is 0)
+fn pro_rata(part: u64, total: u64, whole: u64) -> Result<u64> {
+    if whole == 0 {
+        return Ok(0);
+    }
+    let value = (part as u128)
+        .checked_mul(total as u128)
+        .ok_or(VaultError::ArithmeticOverflow)?
+        / whole as u128;
+    Ok(value as u64)
+}
+
+/// Protocol-wide defaults and limits for vaults created from the template.
+/// A single PDA administered by the program's upgrade authority.
+#[account]
+pub struct ProtocolConfig {
+    /// May update this config
+    pub admin: Pubkey,
+    /// Protocol treasury template vaults are expected to pay fees to
+    pub treasury: Pubkey,
+    /// Protocol guardian for template vaults (default key = none)
+    pub guardian: Pubkey,
+
+    /// Fee floors and ceilings enforced on template vaults, in basis points
+    pub min_management_fee_bps: u16,
+    pub max_management_fee_bps: u16,
+    pub min_performance_fee_bps: u16,
+    pub max_performance_fee_bps: u16,
+
+    /// Defaults applied by `create_vault_from_template`
+    pub default_management_fee_bps: u16,
+    pub default_performance_fee_bps: u16,
+    pub default_deposit_cap: u64,
+    pub default_min_deposit: u64,
+    pub default_withdraw_delay_slots: u64,
+    pub default_reserve_ratio_bps: u16,
+    pub default_small_withdrawal_threshold: u64,
+
+    /// DEX programs `zap_deposit` may swap through (default key = empty slot)
+    pub zap_programs: [Pubkey; MAX_ZAP_PROGRAMS],
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl ProtocolConfig {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 32 + 2*4 + 2 + 2 + 8 + 8 + 8 + 2 + 8 + (32*4) + 1 + 64 = 335
+    pub const LEN: usize = 8 + 335;
+
+    pub fn is_zap_program(&self, program: &Pubkey) -> bool {
+        *program != Pubkey::default() && self.zap_programs.contains(program)
+    }
+
+    /// Check fees against the protocol floors and ceilings
+    pub fn check_fees(&self, management_fee_bps: u16, performance_fee_bps: u16) -> Result<()> {
+        require!(
+            (self.min_management_fee_bps..=self.max_management_fee_bps)
+                .contains(&management_fee_bps),
+            VaultError::InvalidFeeConfig
+        );
+        require!(
+            (self.min_performance_fee_bps..=self.max_performance_fee_bps)
+                .contains(&performance_fee_bps),
+            VaultError::InvalidFeeConfig
+        );
+        Ok(())
+    }
+}
+
+/// Liquidity-mining campaign for a vault's depositors: a funded token account
+/// streams `reward_rate` tokens per second, split pro rata across shares.
+#[account]
+pub struct RewardsPool {
+    /// Vault whose depositors earn these rewards
+    pub vault: Pubkey,
+    /// Token paid out as rewards
+    pub reward_mint: Pubkey,
+    /// Reward tokens emitted per second while the campaign runs
+    pub reward_rate: u64,
+    /// Cumulative rewards per share, scaled by `REWARD_PRECISION`
+    pub reward_per_share: u128,
+    /// Time the accumulator was last advanced (unix timestamp)
+    pub last_update: i64,
+    /// Time emissions stop (unix timestamp)
+    pub end_time: i64,
+    /// Lifetime reward tokens claimed
+    pub total_claimed: u64,
+    /// Lifetime reward tokens emitted to share holders
+    pub total_emitted: u64,
+    pub bump: u8,
+    pub token_account_bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl RewardsPool {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 8 + 16 + 8 + 8 + 8 + 8 + 1 + 1 + 64 = 186
+    pub const LEN: usize = 8 + 186;
+
+    /// Advance the accumulator to `now` (or the campaign end, if earlier).
+    /// Emissions during periods with no shares outstanding stay in the pool.
+    pub fn accrue(&mut self, total_shares: u64, now: i64) -> Result<()> {
+        let until = now.min(self.end_time);
+        if until > self.last_update && total_shares > 0 {
+            let elapsed = (until - self.last_update) as u128;
+            let increment = elapsed
+                .checked_mul(self.reward_rate as u128)
+                .and_then(|r| r.checked_mul(REWARD_PRECISION))
+                .ok_or(VaultError::ArithmeticOverflow)?
+                / total_shares as u128;
+            self.reward_per_share = self
+                .reward_per_share
+                .checked_add(increment)
+                .ok_or(VaultError::ArithmeticOverflow)?;
+            self.total_emitted = (elapsed as u64)
+                .checked_mul(self.reward_rate)
+                .and_then(|emitted| emitted.checked_add(self.total_emitted))
+                .ok_or(VaultError::ArithmeticOverflow)?;
+        }
+        self.last_update = self.last_update.max(until);
+        Ok(())
+    }
+
+    /// Emitted rewards holders have yet to claim, which the owner may not
+    /// take back
+    pub fn unclaimed(&self) -> u64 {
+        self.total_emitted.saturating_sub(self.total_claimed)
+    }
+
+    /// Rewards still to be emitted by the running campaign
+    pub fn remaining_emissions(&self, now: i64) -> Result<u64> {
+        if now >= self.end_time {
+            return Ok(0);
+        }
+        let remaining = ((self.end_time - now) as u128)
+            .checked_mul(self.reward_rate as u128)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        Ok(u64::try_from(remaining).map_err(|_| VaultError::ArithmeticOverflow)?)
+    }
+}
+
+/// One NAV observation in a vault's price history
+#[zero_copy]
+pub struct PriceSample {
+    /// When the sample was taken (unix timestamp)
+    pub timestamp: i64,
+    /// Total assets at the sample
+    pub total_assets: u64,
+    /// Total shares at the sample
+    pub total_shares: u64,
+}
+
+/// Ring buffer of the vault's most recent NAV updates. Zero-copy so the
+/// ~6 KB sample array is never copied onto the BPF stack; entries are only
+/// reachable through `push`/`get`/`latest`, which keep the cursor in range.
+#[account(zero_copy)]
+pub struct PriceHistory {
+    /// Vault this history belongs to
+    pub vault: Pubkey,
+    /// Slot the next sample is written to
+    pub next_index: u64,
+    /// Number of valid samples (saturates at capacity)
+    pub count: u64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+    pub samples: [PriceSample; PRICE_HISTORY_CAPACITY],
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+const _: () = assert!(std::mem::size_of::<PriceSample>() == 24);
+const _: () = assert!(std::mem::size_of::<PriceHistory>() == 120 + 24 * PRICE_HISTORY_CAPACITY);
+const _: () = assert!(std::mem::align_of::<PriceHistory>() == 8);
+
+impl PriceHistory {
+    /// Account discriminator (8) + all fields
+    /// 32 + 8 + 8 + 1 + 7 + (24*256) + 64 = 6264
+    pub const LEN: usize = 8 + std::mem::size_of::<PriceHistory>();
+
+    pub fn len(&self) -> usize {
+        self.count as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Sample `index` positions after the oldest retained sample
+    pub fn get(&self, index: usize) -> Option<&PriceSample> {
+        if index >= self.len() {
+            return None;
+        }
+        let oldest = (self.next_index as usize + PRICE_HISTORY_CAPACITY - self.len())
+            % PRICE_HISTORY_CAPACITY;
+        Some(&self.samples[(oldest + index) % PRICE_HISTORY_CAPACITY])
+    }
+
+    /// Most recent sample, if any
+    pub fn latest(&self) -> Option<&PriceSample> {
+        self.len().checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    /// Append a sample, overwriting the oldest once the buffer is full
+    pub fn push(&mut self, sample: PriceSample) {
+        let index = self.next_index as usize % PRICE_HISTORY_CAPACITY;
+        self.samples[index] = sample;
+        self.next_index = ((index + 1) % PRICE_HISTORY_CAPACITY) as u64;
+        if self.len() < PRICE_HISTORY_CAPACITY {
+            self.count += 1;
+        }
+    }
+}
+
+/// One administrative action in a vault's audit log
+#[zero_copy]
+pub struct AuditEntry {
+    /// Signer that performed the action
+    pub actor: Pubkey,
+    /// Keccak-256 of the action's parameters, so a reviewer can check them
+    /// against the instruction data
+    pub params_hash: [u8; 32],
+    /// When the action was taken (unix timestamp)
+    pub timestamp: i64,
+    /// The `AuditAction`, as u8
+    pub action: u8,
+    pub _padding: [u8; 7],
+}
+
+impl AuditEntry {
+    /// The entry as returned by `get_audit_log`, with its action decoded
+    pub fn to_record(&self) -> Result<AuditRecord> {
+        Ok(AuditRecord {
+            actor: self.actor,
+            // A fieldless enum's borsh encoding is its discriminant byte
+            action: AuditAction::try_from_slice(&[self.action])?,
+            timestamp: self.timestamp,
+            params_hash: self.params_hash,
+        })
+    }
+}
+
+/// An audit log entry as returned by `get_audit_log`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AuditRecord {
+    pub actor: Pubkey,
+    pub action: AuditAction,
+    /// When the action was taken (unix timestamp)
+    pub timestamp: i64,
+    /// Keccak-256 of the action's parameters
+    pub params_hash: [u8; 32],
+}
+
+/// Append-only ring buffer of a vault's administrative actions, so
+/// depositors can review governance activity without having watched its
+/// events. Zero-copy for the same reason as `PriceHistory`.
+#[account(zero_copy)]
+pub struct AuditLog {
+    /// Vault this log belongs to
+    pub vault: Pubkey,
+    /// Slot the next entry is written to
+    pub next_index: u64,
+    /// Number of retained entries (saturates at capacity)
+    pub count: u64,
+    /// Entries ever recorded, including those since overwritten
+    pub total_recorded: u64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+    pub entries: [AuditEntry; AUDIT_LOG_CAPACITY],
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+const _: () = assert!(std::mem::size_of::<AuditEntry>() == 80);
+const _: () = assert!(std::mem::size_of::<AuditLog>() == 128 + 80 * AUDIT_LOG_CAPACITY);
+const _: () = assert!(std::mem::align_of::<AuditLog>() == 8);
+
+impl AuditLog {
+    /// Account discriminator (8) + all fields
+    /// 32 + 8 + 8 + 8 + 1 + 7 + (80*64) + 64 = 5248
+    pub const LEN: usize = 8 + std::mem::size_of::<AuditLog>();
+
+    pub fn len(&self) -> usize {
+        self.count as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Entry `index` positions after the oldest retained entry
+    pub fn get(&self, index: usize) -> Option<&AuditEntry> {
+        if index >= self.len() {
+            return None;
+        }
+        let oldest =
+            (self.next_index as usize + AUDIT_LOG_CAPACITY - self.len()) % AUDIT_LOG_CAPACITY;
+        Some(&self.entries[(oldest + index) % AUDIT_LOG_CAPACITY])
+    }
+
+    /// Record `action` by `actor`, hashing `params` (the action's arguments
+    /// as little-endian bytes) and overwriting the oldest entry once full
+    pub fn record(&mut self, actor: Pubkey, action: AuditAction, params: &[&[u8]], now: i64) {
+        let index = self.next_index as usize % AUDIT_LOG_CAPACITY;
+        self.entries[index] = AuditEntry {
+            actor,
+            params_hash: anchor_lang::solana_program::keccak::hashv(params).to_bytes(),
+            timestamp: now,
+            action: action as u8,
+            _padding: [0; 7],
+        };
+        self.next_index = ((index + 1) % AUDIT_LOG_CAPACITY) as u64;
+        if self.len() < AUDIT_LOG_CAPACITY {
+            self.count += 1;
+        }
+        self.total_recorded = self.total_recorded.saturating_add(1);
+    }
+}
+
+/// Every strategy currently attached to a vault, so clients and on-chain
+/// consumers can enumerate them without a `getProgramAccounts` scan.
+#[account(zero_copy)]
+pub struct StrategyRegistry {
+    /// Vault this registry belongs to
+    pub vault: Pubkey,
+    /// Number of registered strategies
+    pub count: u64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+    /// Strategy account keys; only the first `count` entries are valid
+    pub strategies: [Pubkey; MAX_STRATEGIES],
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+const _: () = assert!(std::mem::size_of::<StrategyRegistry>() == 112 + 32 * MAX_STRATEGIES);
+const _: () = assert!(std::mem::align_of::<StrategyRegistry>() == 8);
+
+impl StrategyRegistry {
+    /// Account discriminator (8) + all fields
+    /// 32 + 8 + 1 + 7 + (32*64) + 64 = 2160
+    pub const LEN: usize = 8 + std::mem::size_of::<StrategyRegistry>();
+
+    /// Registered strategy accounts
+    pub fn strategies(&self) -> &[Pubkey] {
+        &self.strategies[..self.count as usize]
+    }
+
+    pub fn contains(&self, strategy: &Pubkey) -> bool {
+        self.strategies().contains(strategy)
+    }
+
+    /// Register a strategy account
+    pub fn register(&mut self, strategy: Pubkey) -> Result<()> {
+        require!(
+            (self.count as usize) < MAX_STRATEGIES,
+            VaultError::StrategyRegistryFull
+        );
+        self.strategies[self.count as usize] = strategy;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Unregister a strategy account (swap-remove)
+    pub fn unregister(&mut self, strategy: &Pubkey) -> Result<()> {
+        let index = self
+            .strategies()
+            .iter()
+            .position(|s| s == strategy)
+            .ok_or(VaultError::StrategyNotRegistered)?;
+        let last = self.count as usize - 1;
+        self.strategies[index] = self.strategies[last];
+        self.strategies[last] = Pubkey::default();
+        self.count -= 1;
+        Ok(())
+    }
+}
+
+/// A hot key's scoped permission to withdraw a user's position to the user's
+/// own token account, up to `max_amount` of underlying until `expires_at`
+#[account]
+pub struct Session {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub session_key: Pubkey,
+    /// Underlying the session may withdraw over its lifetime
+    pub max_amount: u64,
+    /// Underlying withdrawn through the session so far
+    pub spent: u64,
+    pub expires_at: i64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl Session {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 32 + 8 + 8 + 8 + 1 + 64 = 185
+    pub const LEN: usize = 8 + 185;
+
+    /// Record a withdrawal of `amount` against the allowance
+    pub fn spend(&mut self, amount: u64) -> Result<()> {
+        let spent = self
+            .spent
+            .checked_add(amount)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        require!(spent <= self.max_amount, VaultError::SessionLimitExceeded);
+        self.spent = spent;
+        Ok(())
+    }
+}
+
+/// A vault's log of per-round position snapshots, kept as leaves of an SPL
+/// concurrent Merkle tree so every holder can be recorded at negligible rent
+#[account]
+pub struct PositionHistory {
+    pub vault: Pubkey,
+    pub merkle_tree: Pubkey,
+    /// Current snapshot round; snapshots are tagged with it
+    pub round: u64,
+    /// Unix time the current round started
+    pub round_started_at: i64,
+    /// Leaves appended so far, i.e. the index of the next leaf
+    pub leaf_count: u64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl PositionHistory {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 8 + 8 + 8 + 1 + 64 = 153
+    pub const LEN: usize = 8 + 153;
+}
+
+/// A vault's Bubblegum tree of compressed NFT receipts. The PDA creates the
+/// tree and is the receipts' verified creator, so wallets can tell them from
+/// lookalikes and only this program can mint into it.
+#[account]
+pub struct ReceiptTree {
+    pub vault: Pubkey,
+    pub merkle_tree: Pubkey,
+    /// Receipt metadata URIs are this prefix followed by the receipt's terms
+    /// as a query string
+    pub base_uri: String,
+    /// Receipts minted so far, i.e. the leaf index of the next
+    pub minted: u64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl ReceiptTree {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + (4 + MAX_RECEIPT_BASE_URI_LEN) + 8 + 1 + 64
+    /// = 141 + MAX_RECEIPT_BASE_URI_LEN
+    pub const LEN: usize = 8 + 141 + MAX_RECEIPT_BASE_URI_LEN;
+}
+
+/// Content hash and URI of the document disclosing a vault's strategy and
+/// risks. Depositors hash the document at the URI and compare. Replacing it
+/// takes a proposal that waits out `DISCLOSURE_TIMELOCK`, so the disclosed
+/// strategy cannot change without notice.
+#[account]
+pub struct Disclosure {
+    pub vault: Pubkey,
+    /// Hash of the document in force, e.g. its SHA-256
+    pub content_hash: [u8; 32],
+    pub uri: String,
+    /// When the document in force took effect (unix timestamp)
+    pub effective_at: i64,
+    /// Proposed replacement document
+    pub pending_content_hash: [u8; 32],
+    pub pending_uri: String,
+    /// When the proposed document may be applied (0 = none proposed)
+    pub pending_effective_at: i64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl Disclosure {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + (4 + MAX_DISCLOSURE_URI_LEN) + 8 + 32 + (4 + MAX_DISCLOSURE_URI_LEN)
+    /// + 8 + 1 + 64 = 185 + 2 * MAX_DISCLOSURE_URI_LEN
+    pub const LEN: usize = 8 + 185 + 2 * MAX_DISCLOSURE_URI_LEN;
+
+    /// Whether a replacement document has been proposed
+    pub fn has_pending(&self) -> bool {
+        self.pending_effective_at > 0
+    }
+}
+
+/// A reward token distribution to share holders as of `snapshot_slot`. The
+/// pro-rata allocations are computed off-chain and committed as a Merkle root;
+/// each holder claims theirs with a proof.
+#[account]
+pub struct AirdropSnapshot {
+    pub vault: Pubkey,
+    pub reward_mint: Pubkey,
+    /// Slot the share balances were taken at
+    pub snapshot_slot: u64,
+    /// Root over `airdrop_leaf(claimant, amount)` leaves, sorted-pair hashed
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    /// After this time the owner may claw back unclaimed tokens
+    pub expires_at: i64,
+    pub bump: u8,
+    pub token_account_bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl AirdropSnapshot {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 8 + 32 + 8 + 8 + 8 + 1 + 1 + 64 = 194
+    pub const LEN: usize = 8 + 194;
+}
+
+/// Marks a claimant's airdrop allocation as paid
+#[account]
+pub struct AirdropClaim {
+    pub airdrop: Pubkey,
+    pub claimant: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl AirdropClaim {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 8 + 1 + 64 = 137
+    pub const LEN: usize = 8 + 137;
+}
+
+/// The oracle approved for one asset mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct PriceFeed {
+    pub mint: Pubkey,
+    /// Pyth `PriceUpdateV2` account pricing the mint
+    pub oracle: Pubkey,
+    /// Oldest publish time vaults accept, in seconds before the current time
+    /// (0 = no limit)
+    pub max_staleness: i64,
+}
+
+impl PriceFeed {
+    /// Serialized size: 32 + 32 + 8
+    pub const LEN: usize = 72;
+}
+
+/// Oracle accounts approved for each asset mint, with the staleness limits
+/// vaults enforce on them. Maintained by the protocol admin; vault owners can
+/// only point `set_price_oracle` at the feed registered for their asset.
+#[account]
+pub struct FeedRegistry {
+    /// Registered feeds (default mint = empty slot)
+    pub feeds: [PriceFeed; MAX_PRICE_FEEDS],
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl FeedRegistry {
+    /// Account discriminator (8) + all fields
+    /// (72*32) + 1 + 64 = 2369
+    pub const LEN: usize = 8 + 2369;
+
+    pub fn feed(&self, mint: &Pubkey) -> Option<&PriceFeed> {
+        if *mint == Pubkey::default() {
+            return None;
+        }
+        self.feeds.iter().find(|feed| feed.mint == *mint)
+    }
+
+    /// Register `feed`, replacing any feed for the same mint
+    pub fn set(&mut self, feed: PriceFeed) -> Result<()> {
+        require!(
+            feed.mint != Pubkey::default() && feed.oracle != Pubkey::default(),
+            VaultError::InvalidConfig
+        );
+        require!(feed.max_staleness >= 0, VaultError::InvalidConfig);
+        let slot = match self.feeds.iter().position(|f| f.mint == feed.mint) {
+            Some(index) => index,
+            None => self
+                .feeds
+                .iter()
+                .position(|f| f.mint == Pubkey::default())
+                .ok_or(VaultError::FeedRegistryFull)?,
+        };
+        self.feeds[slot] = feed;
+        Ok(())
+    }
+
+    /// Drop the feed for `mint`
+    pub fn remove(&mut self, mint: &Pubkey) {
+        for feed in self.feeds.iter_mut().filter(|feed| feed.mint == *mint) {
+            *feed = PriceFeed::default();
+        }
+    }
+}
+
+/// One underlying mint's slice of the protocol totals. Amounts of different
+/// mints are never added together.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct MintStats {
+    /// Underlying mint (default key = empty slot)
+    pub mint: Pubkey,
+    /// Sum of the counted vaults' last pushed `total_assets`, in the mint's
+    /// base units
+    pub tvl: u128,
+    /// Sum of the counted vaults' last pushed `total_shares`
+    pub total_shares: u128,
+    /// Assets per share across those vaults (`tvl / total_shares`), in Q64.64
+    pub share_price_q64: u128,
+    /// Vaults counted in this slice
+    pub vault_count: u64,
+}
+
+impl MintStats {
+    /// Move the vault's contribution from `(old_assets, old_shares)` to
+    /// `(new_assets, new_shares)` and reprice
+    fn replace(
+        &mut self,
+        old_assets: u64,
+        old_shares: u64,
+        new_assets: u64,
+        new_shares: u64,
+    ) -> Result<()> {
+        self.tvl = self
+            .tvl
+            .checked_sub(old_assets as u128)
+            .and_then(|tvl| tvl.checked_add(new_assets as u128))
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        self.total_shares = self
+            .total_shares
+            .checked_sub(old_shares as u128)
+            .and_then(|shares| shares.checked_add(new_shares as u128))
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        // Scale both sums into u64 range; the ratio is what matters
+        let shift = (128 - (self.tvl | self.total_shares).leading_zeros()).saturating_sub(64);
+        self.share_price_q64 = share_price_q64(
+            (self.tvl >> shift) as u64,
+            (self.total_shares >> shift) as u64,
+        )?;
+        Ok(())
+    }
+}
+
+/// Protocol-wide totals each vault pushes as its assets change, so a
+/// dashboard reads ecosystem TVL and share prices from one account instead
+/// of every vault
+#[account]
+pub struct GlobalStats {
+    /// Totals per underlying mint. Vaults of a mint beyond the first
+    /// `MAX_STATS_MINTS` are not counted.
+    pub mints: [MintStats; MAX_STATS_MINTS],
+    /// Vaults counted across all mints
+    pub vault_count: u64,
+    /// Last time any vault pushed (unix timestamp)
+    pub updated_at: i64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl GlobalStats {
+    /// Account discriminator (8) + all fields
+    /// (32 + 16 + 16 + 16 + 8)*16 + 8 + 8 + 1 + 64 = 1489
+    pub const LEN: usize = 8 + 1489;
+
+    /// Push `vault`'s current assets to the stats account at `stats_account`.
+    /// Does nothing until the admin has created the account. The address
+    /// itself is checked by the instruction's seeds constraint.
+    pub fn sync(stats_account: &AccountInfo, vault: &mut VaultState, now: i64) -> Result<()> {
+        Self::update(stats_account, |stats| stats.record(vault, now))
+    }
+
+    /// Take a closing `vault` out of the stats account at `stats_account`, so
+    /// a vault reopened on the same seeds is not counted twice
+    pub fn unregister(stats_account: &AccountInfo, vault: &mut VaultState, now: i64) -> Result<()> {
+        Self::update(stats_account, |stats| stats.remove(vault, now))
+    }
+
+    fn update(stats_account: &AccountInfo, f: impl FnOnce(&mut Self) -> Result<()>) -> Result<()> {
+        if stats_account.owner != &crate::ID || stats_account.data_is_empty() {
+            return Ok(());
+        }
+        let mut data = stats_account.try_borrow_mut_data()?;
+        let mut stats = GlobalStats::try_deserialize(&mut &data[..])?;
+        f(&mut stats)?;
+        stats.try_serialize(&mut &mut data[..])
+    }
+
+    /// Slot holding `mint`'s totals, or a free one for a mint not yet counted
+    fn mint_slot(&self, mint: &Pubkey) -> Option<usize> {
+        self.mints
+            .iter()
+            .position(|entry| entry.mint == *mint)
+            .or_else(|| self.mints.iter().position(|entry| entry.mint == Pubkey::default()))
+    }
+
+    /// Replace what `vault` last reported with its current assets and shares,
+    /// counting the vault on its first report
+    pub fn record(&mut self, vault: &mut VaultState, now: i64) -> Result<()> {
+        let Some(slot) = self.mint_slot(&vault.mint) else {
+            return Ok(());
+        };
+        let entry = &mut self.mints[slot];
+        if vault.stats_registered == 0 {
+            vault.stats_registered = 1;
+            vault.stats_reported_assets = 0;
+            vault.stats_reported_shares = 0;
+            entry.mint = vault.mint;
+            entry.vault_count = entry
+                .vault_count
+                .checked_add(1)
+                .ok_or(VaultError::ArithmeticOverflow)?;
+            self.vault_count = self
+                .vault_count
+                .checked_add(1)
+                .ok_or(VaultError::ArithmeticOverflow)?;
+        }
+        entry.replace(
+            vault.stats_reported_assets,
+            vault.stats_reported_shares,
+            vault.total_assets,
+            vault.total_shares,
+        )?;
+        self.updated_at = now;
+        vault.stats_reported_assets = vault.total_assets;
+        vault.stats_reported_shares = vault.total_shares;
+        Ok(())
+    }
+
+    /// Drop `vault`'s last report and stop counting it, freeing its mint's
+    /// slot once no counted vault uses it
+    pub fn remove(&mut self, vault: &mut VaultState, now: i64) -> Result<()> {
+        if vault.stats_registered == 0 {
+            return Ok(());
+        }
+        let Some(slot) = self.mints.iter().position(|entry| entry.mint == vault.mint) else {
+            return Ok(());
+        };
+        let entry = &mut self.mints[slot];
+        entry.replace(vault.stats_reported_assets, vault.stats_reported_shares, 0, 0)?;
+        entry.vault_count = entry.vault_count.saturating_sub(1);
+        if entry.vault_count == 0 {
+            *entry = MintStats::default();
+        }
+        self.vault_count = self.vault_count.saturating_sub(1);
+        self.updated_at = now;
+        vault.stats_registered = 0;
+        vault.stats_reported_assets = 0;
+        vault.stats_reported_shares = 0;
+        Ok(())
+    }
+}
+
+/// Semantic version of a strategy adapter; orders as semver does
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Debug,
+)]
+pub struct StrategyVersion {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+}
+
+/// What the protocol knows about a whitelisted strategy
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct StrategyMetadata {
+    /// Adapter version deployed under the strategy id
+    pub version: StrategyVersion,
+    /// Whether that version has passed a security audit
+    pub audited: bool,
+    /// Protocol risk rating, higher is riskier (0 = unrated)
+    pub risk_score: u8,
+}
+
+/// Strategy ids (adapter program ids or adapter types) that vault owners may
+/// register with `add_strategy`, with the metadata vaults' strategy
+/// requirements are checked against. Maintained by the protocol admin.
+#[account]
+pub struct StrategyWhitelist {
+    /// Allowed strategy ids (default key = empty slot)
+    pub strategy_ids: [Pubkey; MAX_WHITELISTED_STRATEGIES],
+    pub bump: u8,
+    /// Metadata of the strategy id in the same slot
+    pub metadata: [StrategyMetadata; MAX_WHITELISTED_STRATEGIES],
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl StrategyWhitelist {
+    /// Account discriminator (8) + all fields
+    /// (32*32) + 1 + (8*32) + 64 = 1345
+    pub const LEN: usize = 8 + 1345;
+
+    pub fn contains(&self, strategy_id: &Pubkey) -> bool {
+        *strategy_id != Pubkey::default() && self.strategy_ids.contains(strategy_id)
+    }
+
+    fn slot(&self, strategy_id: &Pubkey) -> Option<usize> {
+        if *strategy_id == Pubkey::default() {
+            return None;
+        }
+        self.strategy_ids.iter().position(|id| id == strategy_id)
+    }
+
+    /// Metadata of `strategy_id`, if it is whitelisted
+    pub fn metadata(&self, strategy_id: &Pubkey) -> Option<&StrategyMetadata> {
+        self.slot(strategy_id).map(|slot| &self.metadata[slot])
+    }
+
+    /// Record metadata for a whitelisted `strategy_id`
+    pub fn set_metadata(
+        &mut self,
+        strategy_id: &Pubkey,
+        metadata: StrategyMetadata,
+    ) -> Result<()> {
+        let slot = self
+            .slot(strategy_id)
+            .ok_or(VaultError::StrategyNotWhitelisted)?;
+        self.metadata[slot] = metadata;
+        Ok(())
+    }
+
+    /// Allow `strategy_id`; a no-op if it is already listed
+    pub fn add(&mut self, strategy_id: Pubkey) -> Result<()> {
+        require!(strategy_id != Pubkey::default(), VaultError::InvalidConfig);
+        if self.contains(&strategy_id) {
+            return Ok(());
+        }
+        let slot = self
+            .strategy_ids
+            .iter_mut()
+            .find(|id| **id == Pubkey::default())
+            .ok_or(VaultError::StrategyWhitelistFull)?;
+        *slot = strategy_id;
+        Ok(())
+    }
+
+    /// Disallow `strategy_id` for new registrations, dropping its metadata
+    pub fn remove(&mut self, strategy_id: &Pubkey) {
+        if let Some(slot) = self.slot(strategy_id) {
+            self.strategy_ids[slot] = Pubkey::default();
+            self.metadata[slot] = StrategyMetadata::default();
+        }
+    }
+}
+
+/// A wallet the protocol admin has blocked from depositing into any vault.
+/// Deposits pass the wallet's flag address; an address holding no flag
+/// account means the wallet is clear.
+#[account]
+pub struct WalletFlag {
+    pub wallet: Pubkey,
+    pub flag: ComplianceFlag,
+    /// When the flag was last set (unix timestamp)
+    pub flagged_at: i64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl WalletFlag {
+    /// Account discriminator (8) + all fields
+    /// 32 + 1 + 8 + 1 + 64 = 106
+    pub const LEN: usize = 8 + 106;
+
+    /// Reject a wallet whose flag account at `flag_account` is live. The
+    /// address itself is checked by the instruction's seeds constraint.
+    pub fn check(flag_account: &AccountInfo) -> Result<()> {
+        if flag_account.owner != &crate::ID || flag_account.data_is_empty() {
+            return Ok(());
+        }
+        let flag = WalletFlag::try_deserialize(&mut &flag_account.try_borrow_data()?[..])?;
+        match flag.flag {
+            ComplianceFlag::Sanctioned => err!(VaultError::WalletSanctioned),
+            ComplianceFlag::Geofenced => err!(VaultError::WalletGeofenced),
+        }
+    }
+}
+
+/// Splits vault shares into principal tokens (PT) and yield tokens (YT) that
+/// mature at `maturity`. Both are denominated in the underlying: each PT/YT
+/// pair is backed by `1 / start_price` escrowed shares. At maturity a PT
+/// redeems for one unit of underlying (less any loss since creation) and a YT
+/// for the appreciation above the start price.
+#[account]
+pub struct Tranche {
+    pub vault: Pubkey,
+    pub principal_mint: Pubkey,
+    pub yield_mint: Pubkey,
+    pub maturity: i64,
+    /// Share price at creation, in Q64.64 assets per share
+    pub start_price_q64: u128,
+    /// Share price at settlement, in Q64.64 assets per share
+    pub settlement_price_q64: u128,
+    /// Unix time the tranche was settled (0 = not yet)
+    pub settled_at: i64,
+    pub bump: u8,
+    pub principal_mint_bump: u8,
+    pub yield_mint_bump: u8,
+    pub escrow_bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl Tranche {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 32 + 8 + 16 + 16 + 8 + 1 + 1 + 1 + 1 + 64 = 212
+    pub const LEN: usize = 8 + 212;
+
+    pub fn is_settled(&self) -> bool {
+        self.settled_at != 0
+    }
+
+    /// PT and YT minted for `shares` escrowed shares
+    pub fn pairs_for_shares(&self, shares: u64) -> Result<u64> {
+        mul_q64(shares, self.start_price_q64)
+    }
+
+    /// Shares backing `amount` PT/YT pairs
+    pub fn pair_backing(&self, amount: u64) -> Result<u64> {
+        div_by_q64(amount, self.start_price_q64, false)
+    }
+
+    /// Shares paid for `amount` PT after settlement: one unit of underlying
+    /// each, capped at the pair backing if the share price fell
+    pub fn principal_payout(&self, amount: u64) -> Result<u64> {
+        let price = self.settlement_price_q64.max(self.start_price_q64);
+        div_by_q64(amount, price, false)
+    }
+
+    /// Shares paid for `amount` YT after settlement: the pair backing less
+    /// the principal, rounded so PT and YT together never exceed the backing
+    pub fn yield_payout(&self, amount: u64) -> Result<u64> {
+        if self.settlement_price_q64 <= self.start_price_q64 {
+            return Ok(0);
+        }
+        let principal = div_by_q64(amount, self.settlement_price_q64, true)?;
+        Ok(self.pair_backing(amount)?.saturating_sub(principal))
+    }
+}
+
+/// Lifetime track record of a manager key across every vault it has managed.
+/// One per key and never closed, so the history stays with the key.
+#[account]
+pub struct ManagerProfile {
+    pub manager: Pubkey,
+    /// Vaults the key has been added to as a manager
+    pub vaults_managed: u32,
+    /// NAV updates the key has submitted
+    pub nav_updates: u64,
+    /// Sum of the NAV changes the key has reported, in base units of each
+    /// vault's underlying
+    pub cumulative_pnl: i64,
+    /// Times the key was removed from a vault for cause
+    pub slashing_incidents: u32,
+    /// Unix time the profile was opened
+    pub created_at: i64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl ManagerProfile {
+    /// Account discriminator (8) + all fields
+    /// 32 + 4 + 8 + 8 + 4 + 8 + 1 + 64 = 129
+    pub const LEN: usize = 8 + 129;
+
+    /// Set up a freshly created profile; a no-op for an existing one
+    pub fn open(&mut self, manager: Pubkey, bump: u8, now: i64) {
+        if self.manager == Pubkey::default() {
+            self.manager = manager;
+            self.bump = bump;
+            self.created_at = now;
+        }
+    }
+
+    pub fn record_vault_added(&mut self) {
+        self.vaults_managed = self.vaults_managed.saturating_add(1);
+    }
+
+    /// Record a NAV update moving total assets from `old_total_assets` to
+    /// `new_total_assets`
+    pub fn record_nav_update(&mut self, old_total_assets: u64, new_total_assets: u64) {
+        let change = new_total_assets as i128 - old_total_assets as i128;
+        let pnl = (self.cumulative_pnl as i128).saturating_add(change);
+        self.cumulative_pnl = pnl.clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+        self.nav_updates = self.nav_updates.saturating_add(1);
+    }
+
+    pub fn record_slashing(&mut self) {
+        self.slashing_incidents = self.slashing_incidents.saturating_add(1);
+    }
+}
+
+/// Underlying a manager has posted as collateral against misconduct in a
+/// vault. The owner can slash it into the vault after an appeal period.
+#[account]
+pub struct ManagerBond {
+    pub vault: Pubkey,
+    pub manager: Pubkey,
+    /// Underlying posted and not yet slashed or withdrawn
+    pub amount: u64,
+    /// Amount of the proposed slash awaiting its appeal period (0 = none)
+    pub pending_slash: u64,
+    pub slash_reason: SlashReason,
+    /// When the pending slash may be executed (unix timestamp)
+    pub slash_executable_at: i64,
+    pub bump: u8,
+    pub token_account_bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl ManagerBond {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 8 + 8 + 1 + 8 + 1 + 1 + 64 = 155
+    pub const LEN: usize = 8 + 155;
+
+    pub fn has_pending_slash(&self) -> bool {
+        self.pending_slash > 0
+    }
+}
+
+/// A user's standing order against a vault, filled by anyone once the share
+/// price crosses `limit_price_q64`. The user's token account approves the
+/// intent PDA as delegate for the order size, so nothing is held in custody.
+#[account]
+pub struct Intent {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    /// User-chosen id, part of the PDA seeds
+    pub id: u64,
+    pub kind: IntentKind,
+    /// Underlying still to deposit, or shares still to redeem
+    pub remaining: u64,
+    /// Share price bound in Q64.64 assets per share
+    pub limit_price_q64: u128,
+    pub expires_at: i64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl Intent {
+    /// Account discriminator (8) + all fields
+    /// 32 + 32 + 8 + 1 + 8 + 16 + 8 + 1 + 64 = 170
+    pub const LEN: usize = 8 + 170;
+
+    /// Whether a share price of `price_q64` satisfies the order
+    pub fn is_triggered(&self, price_q64: u128) -> bool {
+        match self.kind {
+            IntentKind::Deposit => price_q64 <= self.limit_price_q64,
+            IntentKind::Withdraw => price_q64 >= self.limit_price_q64,
+        }
+    }
+}
+
+/// Owner-approved rules a vault's managers rebalance strategies under. Each
+/// trade in `rebalance` is checked against its strategy's rule on-chain.
+#[account]
+pub struct Policy {
+    pub vault: Pubkey,
+    pub rules: [PolicyRule; MAX_POLICY_RULES],
+    pub rule_count: u8,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl Policy {
+    /// Account discriminator (8) + all fields
+    /// 32 + 44*8 + 1 + 1 + 64 = 450
+    pub const LEN: usize = 8 + 450;
+
+    pub fn rules(&self) -> &[PolicyRule] {
+        &self.rules[..self.rule_count as usize]
+    }
+
+    pub fn rule(&self, strategy: &Pubkey) -> Option<&PolicyRule> {
+        self.rules().iter().find(|rule| rule.strategy == *strategy)
+    }
+}
+
+/// Verifying key of a vault's proof-of-reserves circuit and the latest proof
+/// it accepted. The circuit attests that strategy positions across external
+/// venues were worth at least `reserves` at `observed_at`; its public inputs
+/// are the vault address with the first byte cleared, `reserves` and
+/// `observed_at`, each as a big-endian scalar.
+#[account]
+pub struct ReservesVerifier {
+    pub vault: Pubkey,
+    pub verifying_key: Groth16VerifyingKey,
+    /// NAV increases up to this share of total assets need no proof, in
+    /// basis points
+    pub max_unproven_increase_bps: u16,
+    /// How long after its observation a proof covers NAV increases, in seconds
+    pub max_proof_age: i64,
+    /// Strategy reserves attested by the latest verified proof
+    pub proven_reserves: u64,
+    /// Observation time of the latest verified proof (0 = none yet)
+    pub proven_at: i64,
+    pub bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl ReservesVerifier {
+    /// Account discriminator (8) + all fields
+    /// 32 + 704 + 2 + 8 + 8 + 8 + 1 + 64 = 827
+    pub const LEN: usize = 8 + 827;
+
+    /// Check a NAV update from `old_total_assets` to `new_total_assets` at
+    /// `now`. Increases beyond `max_unproven_increase_bps` must be covered by
+    /// the undeployed balance plus the reserves of a proof no older than
+    /// `max_proof_age`.
+    pub fn check_nav_increase(
+        &self,
+        old_total_assets: u64,
+        total_debt: u64,
+        new_total_assets: u64,
+        now: i64,
+    ) -> Result<()> {
+        let unproven_limit = old_total_assets as u128
+            + old_total_assets as u128 * self.max_unproven_increase_bps as u128 / BPS_DENOMINATOR;
+        if new_total_assets as u128 <= unproven_limit {
+            return Ok(());
+        }
+        require!(
+            self.proven_at != 0 && now.saturating_sub(self.proven_at) <= self.max_proof_age,
+            VaultError::ReservesNotProven
+        );
+        let idle = old_total_assets.saturating_sub(total_debt) as u128;
+        require!(
+            idle + self.proven_reserves as u128 >= new_total_assets as u128,
+            VaultError::ReservesNotProven
+        );
+        Ok(())
+    }
+}
+
+/// Budget paying permissionless cranks, so the vault keeps running without
+/// the operator's own keeper. Each crank kind earns `bounty` underlying at
+/// most once per `min_interval`, which bounds how fast splitting work into
+/// many small cranks can drain the budget.
+#[account]
+pub struct CrankBounty {
+    pub vault: Pubkey,
+    /// Underlying paid per crank, in base units
+    pub bounty: u64,
+    /// Minimum seconds between two paid cranks of the same kind
+    pub min_interval: i64,
+    /// Time each `CrankKind` was last paid (unix timestamp)
+    pub last_paid: [i64; 2],
+    /// Lifetime bounties paid
+    pub total_paid: u64,
+    pub bump: u8,
+    pub token_account_bump: u8,
+    /// Zeroed space for future fields
+    pub _reserved: [u8; 64],
+}
+
+impl CrankBounty {
+    /// Account discriminator (8) + all fields
+    /// 32 + 8 + 8 + 16 + 8 + 1 + 1 + 64 = 138
+    pub const LEN: usize = 8 + 138;
+
+    /// Bounty owed for a `kind` crank at `now` given the budget `balance`, or
+    /// 0 within `min_interval` of the last paid one or once the budget cannot
+    /// cover it. Records the payment.
+    pub fn claim(&mut self, kind: CrankKind, balance: u64, now: i64) -> Result<u64> {
+        let last_paid = &mut self.last_paid[kind as usize];
+        if self.bounty == 0
+            || balance < self.bounty
+            || now.saturating_sub(*last_paid) < self.min_interval
+        {
+            return Ok(0);
+        }
+        *last_paid = now;
+        self.total_paid = self
+            .total_paid
+            .checked_add(self.bounty)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        Ok(self.bounty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn vault(total_assets: u64) -> VaultState {
+        let mut vault: VaultState = bytemuck::Zeroable::zeroed();
+        vault.total_assets = total_assets;
+        vault.total_shares = total_assets;
+        vault
+    }
+
+    fn is_outflow_limit(result: Result<()>) -> bool {
+        result.unwrap_err() == VaultError::OutflowLimitReached.into()
+    }
+
+    /// An account deserialized from `len` zeroed bytes, failing unless every
+    /// byte is read
+    fn zeroed<T: AnchorDeserialize>(len: usize) -> T {
+        T::try_from_slice(&vec![0; len]).unwrap()
+    }
+
+    /// Space an account takes, discriminator included
+    fn space<T: AnchorSerialize>(account: &T) -> usize {
+        8 + account.try_to_vec().unwrap().len()
+    }
+
+    #[test]
+    fn account_lens_match_serialized_size() {
+        macro_rules! assert_len {
+            ($($account:ty),* $(,)?) => {$(
+                let account = zeroed::<$account>(<$account>::LEN - 8);
+                assert_eq!(space(&account), <$account>::LEN, stringify!($account));
+            )*};
+        }
+        assert_len!(
+            ManagerProposal,
+            ManagerVoteRecord,
+            StrategyState,
+            FeeReport,
+            UserPosition,
+            Portfolio,
+            EpochRequest,
+            EpochSettlement,
+            RewardsPool,
+            Session,
+            PositionHistory,
+            AirdropSnapshot,
+            AirdropClaim,
+            FeedRegistry,
+            GlobalStats,
+            StrategyWhitelist,
+            WalletFlag,
+            Tranche,
+            ManagerProfile,
+            ManagerBond,
+            Intent,
+            Policy,
+            ReservesVerifier,
+            CrankBounty,
+            ProtocolConfig,
+        );
+
+        // Accounts holding strings are sized for the longest one allowed
+        let mut tree = zeroed::<ReceiptTree>(ReceiptTree::LEN - 8 - MAX_RECEIPT_BASE_URI_LEN);
+        tree.base_uri = "x".repeat(MAX_RECEIPT_BASE_URI_LEN);
+        assert_eq!(space(&tree), ReceiptTree::LEN);
+
+        let mut disclosure =
+            zeroed::<Disclosure>(Disclosure::LEN - 8 - 2 * MAX_DISCLOSURE_URI_LEN);
+        disclosure.uri = "x".repeat(MAX_DISCLOSURE_URI_LEN);
+        disclosure.pending_uri = "x".repeat(MAX_DISCLOSURE_URI_LEN);
+        assert_eq!(space(&disclosure), Disclosure::LEN);
+    }
+
+    #[test]
+    fn outflow_is_unlimited_by_default() {
+        let mut vault = vault(1_000);
+        assert_eq!(vault.outflow_capacity(0), u64::MAX);
+        vault.record_outflow(1_000, 0).unwrap();
+        assert_eq!(vault.outflow_window_used, 0);
+    }
+
+    #[test]
+    fn outflow_absolute_limit() {
+        let mut vault = vault(1_000);
+        vault.max_window_outflow = 300;
+        vault.record_outflow(200, NOW).unwrap();
+        assert_eq!(vault.outflow_capacity(NOW), 100);
+        assert!(is_outflow_limit(vault.record_outflow(101, NOW)));
+        vault.record_outflow(100, NOW).unwrap();
+        assert_eq!(vault.outflow_capacity(NOW), 0);
+    }
+
+    #[test]
+    fn outflow_bps_limit_uses_assets_at_window_start() {
+        let mut vault = vault(10_000);
+        vault.max_window_outflow_bps = 1_000;
+        vault.record_outflow(600, NOW).unwrap();
+        // Withdrawals shrink total assets but not the window's base
+        vault.total_assets -= 600;
+        assert_eq!(vault.outflow_window_base, 10_000);
+        assert_eq!(vault.outflow_capacity(NOW), 400);
+        assert!(is_outflow_limit(vault.record_outflow(401, NOW)));
+        vault.record_outflow(400, NOW).unwrap();
+    }
+
+    #[test]
+    fn outflow_lower_limit_applies() {
+        let mut vault = vault(10_000);
+        vault.max_window_outflow = 500;
+        vault.max_window_outflow_bps = 1_000;
+        assert_eq!(vault.outflow_capacity(NOW), 500);
+        vault.max_window_outflow = 5_000;
+        assert_eq!(vault.outflow_capacity(NOW), 1_000);
+    }
+
+    #[test]
+    fn outflow_window_rolls_over() {
+        let mut vault = vault(10_000);
+        vault.max_window_outflow_bps = 1_000;
+        vault.record_outflow(1_000, NOW).unwrap();
+        vault.total_assets -= 1_000;
+        assert!(is_outflow_limit(vault.record_outflow(1, NOW + OUTFLOW_WINDOW - 1)));
+
+        // The next window opens on the assets left at its start
+        assert_eq!(vault.outflow_capacity(NOW + OUTFLOW_WINDOW), 900);
+        vault.record_outflow(900, NOW + OUTFLOW_WINDOW).unwrap();
+        assert_eq!(vault.outflow_window_start, NOW + OUTFLOW_WINDOW);
+        assert_eq!(vault.outflow_window_base, 9_000);
+        assert_eq!(vault.outflow_window_used, 900);
+    }
+
+    #[test]
+    fn epoch_redeems_fill_within_outflow_capacity() {
+        let mut vault = vault(10_000);
+        vault.max_window_outflow = 300;
+        vault.record_outflow(100, NOW).unwrap();
+        let filled = vault.gated_redeems(1_000, NOW).unwrap();
+        assert_eq!(filled, 200);
+        assert_eq!(vault.outflow_share_capacity(NOW).unwrap(), 200);
+        // All of them fill once the window rolls over
+        assert_eq!(vault.gated_redeems(300, NOW + OUTFLOW_WINDOW).unwrap(), 300);
     }
 }
@@ -1,11 +1,22 @@
+use anchor_lang::event::EVENT_IX_TAG_LE;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{get_stack_height, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::Event;
+use solana_bn254::prelude::{alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing};
 
-use crate::constants::{BPS_DENOMINATOR, SECONDS_PER_YEAR};
+use crate::constants::{
+    BPS_DENOMINATOR, CIVIC_GATEWAY_ID, EVENT_AUTHORITY_SEED, MAX_SHARE_DECIMALS,
+    MIN_SHARE_DECIMALS, PYTH_RECEIVER_ID, SECONDS_PER_YEAR, STRICT_MAX_STACK_HEIGHT,
+};
 use crate::error::VaultError;
+use crate::state::{Groth16Proof, Groth16VerifyingKey};
 
 /// Calculate shares to mint for a given deposit amount.
 ///
-/// First depositor gets 1:1 ratio. Subsequent depositors get proportional shares:
+/// First depositor gets `share_scale` shares per asset unit (1:1 unless the
+/// vault scales up a low-decimal asset). Subsequent depositors get
+/// proportional shares:
 ///   shares = (deposit_amount * total_shares) / total_assets
 ///
 /// Uses u128 intermediates to prevent overflow on large values.
@@ -13,10 +24,13 @@ pub fn calculate_shares_to_mint(
     deposit_amount: u64,
     total_assets: u64,
     total_shares: u64,
+    share_scale: u64,
 ) -> Result<u64> {
     if total_shares == 0 || total_assets == 0 {
-        // First deposit: 1:1 ratio
-        return Ok(deposit_amount);
+        // First deposit: one asset unit buys `share_scale` shares
+        return deposit_amount
+            .checked_mul(share_scale)
+            .ok_or(error!(VaultError::ArithmeticOverflow));
     }
 
     let shares = (deposit_amount as u128)
@@ -29,6 +43,31 @@ pub fn calculate_shares_to_mint(
     u64::try_from(shares).map_err(|_| error!(VaultError::ArithmeticOverflow))
 }
 
+/// Extra share decimals for an asset with `asset_decimals`, and the matching
+/// first-deposit scale factor. Shares get `share_decimals` when given, which
+/// must lie between the asset's decimals (at least `MIN_SHARE_DECIMALS`) and
+/// `MAX_SHARE_DECIMALS`; otherwise the asset's, raised to the minimum.
+pub fn decimals_offset(asset_decimals: u8, share_decimals: Option<u8>) -> Result<(u8, u64)> {
+    let min = asset_decimals.max(MIN_SHARE_DECIMALS);
+    let share_decimals = share_decimals.unwrap_or(min);
+    require!(
+        share_decimals >= min && share_decimals <= MAX_SHARE_DECIMALS.max(asset_decimals),
+        VaultError::InvalidConfig
+    );
+    let offset = share_decimals - asset_decimals;
+    Ok((offset, 10u64.pow(offset as u32)))
+}
+
+/// Value `calculate_shares_to_mint` rounds away from a depositor, in Q64.64
+/// asset units
+pub fn shares_to_mint_dust_q64(deposit_amount: u64, total_assets: u64, total_shares: u64) -> u128 {
+    if total_shares == 0 || total_assets == 0 {
+        return 0;
+    }
+    let remainder = deposit_amount as u128 * total_shares as u128 % total_assets as u128;
+    (remainder << 64) / total_shares as u128
+}
+
 /// Calculate assets to return for a given number of shares burned.
 ///
 ///   assets = (shares_to_burn * total_assets) / total_shares
@@ -52,64 +91,312 @@ pub fn calculate_assets_to_return(
     u64::try_from(assets).map_err(|_| error!(VaultError::ArithmeticOverflow))
 }
 
-/// Calculate time-weighted management fee.
+/// Value `calculate_assets_to_return` rounds away from a redeemer, in Q64.64
+/// asset units
+pub fn assets_to_return_dust_q64(
+    shares_to_burn: u64,
+    total_assets: u64,
+    total_shares: u64,
+) -> u128 {
+    if total_shares == 0 {
+        return 0;
+    }
+    let remainder = shares_to_burn as u128 * total_assets as u128 % total_shares as u128;
+    (remainder << 64) / total_shares as u128
+}
+
+/// Entry and exit fees, in basis points, of a vault holding `liquid` of its
+/// `total_assets` undeployed. At or above `kink_bps` of liquidity deposits pay
+/// `max_entry_bps` and withdrawals nothing; below it the entry fee falls and
+/// the exit fee rises linearly, to zero and `max_exit_bps` when nothing is
+/// liquid. A zero kink, or an empty vault, charges neither.
+pub fn liquidity_fees_bps(
+    liquid: u64,
+    total_assets: u64,
+    kink_bps: u16,
+    max_entry_bps: u16,
+    max_exit_bps: u16,
+) -> (u64, u64) {
+    if kink_bps == 0 || total_assets == 0 {
+        return (0, 0);
+    }
+    let kink = kink_bps as u128;
+    let ratio_bps = (liquid as u128 * BPS_DENOMINATOR / total_assets as u128).min(kink);
+    let entry_bps = max_entry_bps as u128 * ratio_bps / kink;
+    let exit_bps = max_exit_bps as u128 * (kink - ratio_bps) / kink;
+    (entry_bps as u64, exit_bps as u64)
+}
+
+/// One in Q64.64 fixed point: the share price of a vault holding one asset
+/// unit per share
+pub const Q64_ONE: u128 = 1 << 64;
+
+/// Share price (assets per share) in Q64.64. An empty vault prices at 1.
+pub fn share_price_q64(total_assets: u64, total_shares: u64) -> Result<u128> {
+    if total_shares == 0 {
+        return Ok(Q64_ONE);
+    }
+    ((total_assets as u128) << 64)
+        .checked_div(total_shares as u128)
+        .ok_or(error!(VaultError::ArithmeticOverflow))
+}
+
+/// Split a Q64.64 asset amount into whole asset units and the fractional rest
+pub fn q64_to_amount(value: u128) -> Result<(u64, u128)> {
+    let whole = u64::try_from(value >> 64).map_err(|_| error!(VaultError::ArithmeticOverflow))?;
+    Ok((whole, value & (Q64_ONE - 1)))
+}
+
+/// `amount * value`, for a Q64.64 `value`, rounded down
+pub fn mul_q64(amount: u64, value: u128) -> Result<u64> {
+    let whole = (amount as u128)
+        .checked_mul(value >> 64)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    let fraction = ((amount as u128) * (value & (Q64_ONE - 1))) >> 64;
+    let product = whole
+        .checked_add(fraction)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    u64::try_from(product).map_err(|_| error!(VaultError::ArithmeticOverflow))
+}
+
+/// `amount / value`, for a nonzero Q64.64 `value`, rounded down or up
+pub fn div_by_q64(amount: u64, value: u128, round_up: bool) -> Result<u64> {
+    let numerator = (amount as u128) << 64;
+    let quotient = if round_up {
+        numerator.div_ceil(value)
+    } else {
+        numerator
+            .checked_div(value)
+            .ok_or(VaultError::ArithmeticOverflow)?
+    };
+    u64::try_from(quotient).map_err(|_| error!(VaultError::ArithmeticOverflow))
+}
+
+/// `numerator / denominator` in Q64.64, without overflowing for integer
+/// results below 2^64
+fn div_q64(numerator: u128, denominator: u128) -> Result<u128> {
+    let whole = numerator
+        .checked_div(denominator)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    require!(whole < Q64_ONE, VaultError::ArithmeticOverflow);
+    let fraction = ((numerator % denominator) << 64) / denominator;
+    Ok((whole << 64) | fraction)
+}
+
+/// Confidence interval of a Pyth `PriceUpdateV2` account as a share of the
+/// price, in basis points (rounded up).
+///
+/// Layout: discriminator (8), write authority (32), verification level (1,
+/// `Full` only), then the price message: feed id (32), price (i64),
+/// conf (u64), exponent (i32). Partially verified updates and non-positive
+/// prices are untrusted.
+pub fn oracle_conf_bps(oracle: &AccountInfo) -> Result<u64> {
+    require_keys_eq!(*oracle.owner, PYTH_RECEIVER_ID, VaultError::InvalidOracle);
+    let data = oracle.try_borrow_data()?;
+    require!(data.len() >= 89, VaultError::InvalidOracle);
+    require!(data[40] == 1, VaultError::OracleUntrusted);
+
+    let price = i64::from_le_bytes(data[73..81].try_into().unwrap());
+    let conf = u64::from_le_bytes(data[81..89].try_into().unwrap());
+    require!(price > 0, VaultError::OracleUntrusted);
+
+    let bps = ((conf as u128) * BPS_DENOMINATOR).div_ceil(price as u128);
+    Ok(u64::try_from(bps).unwrap_or(u64::MAX))
+}
+
+/// Price of a Pyth `PriceUpdateV2` account, in the feed's own exponent. Only
+/// fully verified, positive prices are trusted.
+pub fn oracle_price(oracle: &AccountInfo) -> Result<u64> {
+    require_keys_eq!(*oracle.owner, PYTH_RECEIVER_ID, VaultError::InvalidOracle);
+    let data = oracle.try_borrow_data()?;
+    require!(data.len() >= 81, VaultError::InvalidOracle);
+    require!(data[40] == 1, VaultError::OracleUntrusted);
+
+    let price = i64::from_le_bytes(data[73..81].try_into().unwrap());
+    require!(price > 0, VaultError::OracleUntrusted);
+    Ok(price as u64)
+}
+
+/// Publish time of a Pyth `PriceUpdateV2` account's price, in Unix seconds.
+/// It follows the exponent in the price message.
+pub fn oracle_publish_time(oracle: &AccountInfo) -> Result<i64> {
+    require_keys_eq!(*oracle.owner, PYTH_RECEIVER_ID, VaultError::InvalidOracle);
+    let data = oracle.try_borrow_data()?;
+    require!(data.len() >= 101, VaultError::InvalidOracle);
+    Ok(i64::from_le_bytes(data[93..101].try_into().unwrap()))
+}
+
+/// Close the program account at `info` if it was ever created, returning its
+/// rent to `destination`. Callers check the address by seeds.
+pub fn close_if_created<'info>(
+    info: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    if info.owner != &crate::ID || info.data_is_empty() {
+        return Ok(());
+    }
+    let lamports = destination
+        .lamports()
+        .checked_add(info.lamports())
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    **destination.try_borrow_mut_lamports()? = lamports;
+    **info.try_borrow_mut_lamports()? = 0;
+    info.assign(&System::id());
+    info.resize(0)?;
+    Ok(())
+}
+
+/// Reject a call nested deeper than `STRICT_MAX_STACK_HEIGHT`. Compiled to a
+/// no-op unless the `strict-checks` feature is enabled.
+pub fn check_stack_height() -> Result<()> {
+    if !cfg!(feature = "strict-checks") {
+        return Ok(());
+    }
+    require!(
+        get_stack_height() <= STRICT_MAX_STACK_HEIGHT,
+        VaultError::CpiTooDeep
+    );
+    Ok(())
+}
+
+/// Check a Civic Pass gateway token is active, unexpired and issued to
+/// `wallet` by `gatekeeper_network`.
+///
+/// Layout (borsh): features (1), parent token (`Option<Pubkey>`), owner
+/// wallet (32), owner identity (`Option<Pubkey>`), gatekeeper network (32),
+/// issuing gatekeeper (32), state (1, `Active` = 0), expiry
+/// (`Option<i64>`).
+pub fn check_gateway_token(
+    token: &AccountInfo,
+    wallet: &Pubkey,
+    gatekeeper_network: &Pubkey,
+    now: i64,
+) -> Result<()> {
+    require_keys_eq!(*token.owner, CIVIC_GATEWAY_ID, VaultError::CredentialRequired);
+    let data = token.try_borrow_data()?;
+    let mut fields = BorshCursor { data: &data, offset: 1 };
+    fields.skip_optional(32)?;
+    let owner_wallet = fields.pubkey()?;
+    fields.skip_optional(32)?;
+    let network = fields.pubkey()?;
+    fields.take(32)?;
+    let active = fields.take(1)?[0] == 0;
+    let expire_time = match fields.take(1)?[0] {
+        1 => Some(i64::from_le_bytes(fields.take(8)?.try_into().unwrap())),
+        _ => None,
+    };
+
+    require_keys_eq!(owner_wallet, *wallet, VaultError::CredentialRequired);
+    require_keys_eq!(network, *gatekeeper_network, VaultError::CredentialRequired);
+    require!(active, VaultError::CredentialRequired);
+    require!(
+        expire_time.is_none_or(|expiry| now < expiry),
+        VaultError::CredentialExpired
+    );
+    Ok(())
+}
+
+/// Reads consecutive borsh fields of a foreign account, failing with
+/// `CredentialRequired` when the data runs out
+struct BorshCursor<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> BorshCursor<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + len)
+            .ok_or(VaultError::CredentialRequired)?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn pubkey(&mut self) -> Result<Pubkey> {
+        Ok(Pubkey::try_from(self.take(32)?).unwrap())
+    }
+
+    /// Skip an `Option` whose value is `len` bytes
+    fn skip_optional(&mut self, len: usize) -> Result<()> {
+        if self.take(1)?[0] == 1 {
+            self.take(len)?;
+        }
+        Ok(())
+    }
+}
+
+/// Calculate time-weighted management fee, in Q64.64 asset units.
 ///
 ///   fee = total_assets * management_fee_bps * seconds_elapsed / (BPS_DENOMINATOR * SECONDS_PER_YEAR)
 ///
 /// This gives a pro-rata annual fee based on time elapsed since last collection.
+/// The fractional part is kept so frequent accruals on small vaults still add up.
 pub fn calculate_management_fee(
     total_assets: u64,
     fee_bps: u16,
     seconds_elapsed: i64,
-) -> Result<u64> {
+) -> Result<u128> {
     if fee_bps == 0 || seconds_elapsed <= 0 || total_assets == 0 {
         return Ok(0);
     }
 
-    let fee = (total_assets as u128)
+    let numerator = (total_assets as u128)
         .checked_mul(fee_bps as u128)
         .ok_or(VaultError::ArithmeticOverflow)?
         .checked_mul(seconds_elapsed as u128)
-        .ok_or(VaultError::ArithmeticOverflow)?
-        .checked_div(
-            BPS_DENOMINATOR
-                .checked_mul(SECONDS_PER_YEAR)
-                .ok_or(VaultError::ArithmeticOverflow)?,
-        )
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    let denominator = BPS_DENOMINATOR
+        .checked_mul(SECONDS_PER_YEAR)
         .ok_or(VaultError::ArithmeticOverflow)?;
 
-    u64::try_from(fee).map_err(|_| error!(VaultError::ArithmeticOverflow))
+    div_q64(numerator, denominator)
 }
 
-/// Calculate performance fee on profits above the high-water mark.
+/// Calculate performance fee on share-price gains above the high-water mark.
 ///
-/// Returns (fee_in_asset_units, new_high_water_mark).
+/// Returns (fee in Q64.64 asset units, new high-water mark in Q64.64).
 ///
-/// Performance fee is only charged on the increase above the previous high-water mark,
-/// measured as total_assets per share to be fair to all depositors.
+/// The high-water mark is a Q64.64 share price, so deposits and withdrawals do
+/// not move it; the fee is charged on `(price - hwm) * total_shares`. A zero
+/// high-water mark is initialized to the current price without a fee.
 pub fn calculate_performance_fee(
     total_assets: u64,
-    high_water_mark: u64,
+    high_water_mark: u128,
     fee_bps: u16,
     total_shares: u64,
-) -> Result<(u64, u64)> {
-    if fee_bps == 0 || total_shares == 0 || total_assets <= high_water_mark {
+) -> Result<(u128, u128)> {
+    if total_shares == 0 {
+        return Ok((0, high_water_mark));
+    }
+    let price = share_price_q64(total_assets, total_shares)?;
+    if high_water_mark == 0 {
+        return Ok((0, price));
+    }
+    if fee_bps == 0 || price <= high_water_mark {
         return Ok((0, high_water_mark));
     }
 
-    let profit = (total_assets as u128)
-        .checked_sub(high_water_mark as u128)
-        .ok_or(VaultError::ArithmeticOverflow)?;
-
-    let fee = profit
+    let fee = (price - high_water_mark)
+        .checked_mul(total_shares as u128)
+        .ok_or(VaultError::ArithmeticOverflow)?
         .checked_mul(fee_bps as u128)
         .ok_or(VaultError::ArithmeticOverflow)?
-        .checked_div(BPS_DENOMINATOR)
-        .ok_or(VaultError::ArithmeticOverflow)?;
+        / BPS_DENOMINATOR;
 
-    let fee_u64 = u64::try_from(fee).map_err(|_| error!(VaultError::ArithmeticOverflow))?;
+    Ok((fee, price))
+}
 
-    Ok((fee_u64, total_assets))
+/// Scale a Q64.64 value by `numerator / denominator`, rounding down, without
+/// the full product having to fit in a u128.
+pub fn scale_q64(value: u128, numerator: u64, denominator: u64) -> Result<u128> {
+    require!(denominator > 0, VaultError::ArithmeticOverflow);
+    let (numerator, denominator) = (numerator as u128, denominator as u128);
+    let scaled = (value / denominator)
+        .checked_mul(numerator)
+        .and_then(|whole| whole.checked_add(value % denominator * numerator / denominator))
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    Ok(scaled)
 }
 
 /// Convert a fee amount (in asset units) to the equivalent number of shares to mint.
@@ -142,3 +429,185 @@ pub fn fee_amount_to_shares(
 
     u64::try_from(shares).map_err(|_| error!(VaultError::ArithmeticOverflow))
 }
+
+/// Leaf recorded for a position snapshot: keccak256 over the borsh fields
+/// `vault, user, round, shares, total_assets, total_shares, slot`
+pub fn position_snapshot_leaf(
+    vault: &Pubkey,
+    user: &Pubkey,
+    round: u64,
+    shares: u64,
+    total_assets: u64,
+    total_shares: u64,
+    slot: u64,
+) -> [u8; 32] {
+    anchor_lang::solana_program::keccak::hashv(&[
+        vault.as_ref(),
+        user.as_ref(),
+        &round.to_le_bytes(),
+        &shares.to_le_bytes(),
+        &total_assets.to_le_bytes(),
+        &total_shares.to_le_bytes(),
+        &slot.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Leaf of an airdrop allocation: keccak256(claimant || amount)
+pub fn airdrop_leaf(claimant: &Pubkey, amount: u64) -> [u8; 32] {
+    anchor_lang::solana_program::keccak::hashv(&[claimant.as_ref(), &amount.to_le_bytes()])
+        .to_bytes()
+}
+
+/// Verify a Merkle proof whose nodes hash each pair in sorted order
+pub fn verify_sorted_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let node = proof.iter().fold(leaf, |node, sibling| {
+        let (a, b) = if node <= *sibling {
+            (node, *sibling)
+        } else {
+            (*sibling, node)
+        };
+        anchor_lang::solana_program::keccak::hashv(&[&a, &b]).to_bytes()
+    });
+    node == *root
+}
+
+/// BN254 base field modulus, big-endian
+const BN254_FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+/// Negate a G1 point: `(x, y)` becomes `(x, p - y)`
+fn negate_g1(point: &[u8; 64]) -> [u8; 64] {
+    let mut negated = *point;
+    if point[32..].iter().all(|byte| *byte == 0) {
+        return negated;
+    }
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let mut digit = BN254_FIELD_MODULUS[i] as i16 - point[32 + i] as i16 - borrow;
+        borrow = (digit < 0) as i16;
+        if digit < 0 {
+            digit += 256;
+        }
+        negated[32 + i] = digit as u8;
+    }
+    negated
+}
+
+/// Verify a Groth16 proof over BN254 with the alt_bn128 syscalls:
+///
+///   e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1
+///
+/// where `vk_x = ic[0] + sum(input_i * ic[i + 1])`. Public inputs are
+/// big-endian scalars and must be below the scalar field modulus.
+pub fn verify_groth16(
+    vk: &Groth16VerifyingKey,
+    proof: &Groth16Proof,
+    public_inputs: &[[u8; 32]],
+) -> Result<bool> {
+    require!(
+        public_inputs.len() + 1 == vk.ic.len(),
+        VaultError::InvalidReservesProof
+    );
+    let invalid = |_| error!(VaultError::InvalidReservesProof);
+
+    let mut vk_x = vk.ic[0];
+    for (input, ic) in public_inputs.iter().zip(&vk.ic[1..]) {
+        let term = alt_bn128_multiplication(&[ic.as_slice(), input].concat()).map_err(invalid)?;
+        let sum = alt_bn128_addition(&[vk_x.as_slice(), &term].concat()).map_err(invalid)?;
+        vk_x.copy_from_slice(&sum);
+    }
+
+    let pairing = alt_bn128_pairing(
+        &[
+            negate_g1(&proof.a).as_slice(),
+            &proof.b,
+            &vk.alpha_g1,
+            &vk.beta_g2,
+            &vk_x,
+            &vk.gamma_g2,
+            &proof.c,
+            &vk.delta_g2,
+        ]
+        .concat(),
+    )
+    .map_err(invalid)?;
+    Ok(pairing.len() == 32 && pairing[..31].iter().all(|byte| *byte == 0) && pairing[31] == 1)
+}
+
+/// The event authority of an instruction's `#[event_cpi]` accounts, carried
+/// into helpers shared by several instructions, which have no `ctx` in scope
+/// for `emit_cpi!`
+#[derive(Clone, Copy)]
+pub struct EventCpi<'a, 'info> {
+    pub authority: &'a AccountInfo<'info>,
+    pub bump: u8,
+}
+
+impl EventCpi<'_, '_> {
+    /// Emit `event` through a self-CPI signed by the event authority, as
+    /// `emit_cpi!` does
+    pub fn emit(&self, event: impl Event) -> Result<()> {
+        let ix = Instruction::new_with_bytes(
+            crate::ID,
+            &[EVENT_IX_TAG_LE, &event.data()].concat(),
+            vec![AccountMeta::new_readonly(self.authority.key(), true)],
+        );
+        invoke_signed(
+            &ix,
+            std::slice::from_ref(self.authority),
+            &[&[EVENT_AUTHORITY_SEED, &[self.bump]]],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn liquidity_fees_off_without_kink() {
+        assert_eq!(liquidity_fees_bps(0, 1_000, 0, 50, 200), (0, 0));
+        assert_eq!(liquidity_fees_bps(1_000, 1_000, 0, 50, 200), (0, 0));
+    }
+
+    #[test]
+    fn liquidity_fees_off_for_empty_vault() {
+        assert_eq!(liquidity_fees_bps(0, 0, 2_000, 50, 200), (0, 0));
+    }
+
+    #[test]
+    fn liquidity_fees_at_kink() {
+        // 20% liquid against a 20% kink
+        assert_eq!(liquidity_fees_bps(200, 1_000, 2_000, 50, 200), (50, 0));
+    }
+
+    #[test]
+    fn liquidity_fees_above_kink() {
+        assert_eq!(liquidity_fees_bps(500, 1_000, 2_000, 50, 200), (50, 0));
+        assert_eq!(liquidity_fees_bps(1_000, 1_000, 2_000, 50, 200), (50, 0));
+    }
+
+    #[test]
+    fn liquidity_fees_below_kink() {
+        // Half the kink: half the entry fee, half the exit fee
+        assert_eq!(liquidity_fees_bps(100, 1_000, 2_000, 50, 200), (25, 100));
+        // A quarter of the kink
+        assert_eq!(liquidity_fees_bps(50, 1_000, 2_000, 50, 200), (12, 150));
+    }
+
+    #[test]
+    fn liquidity_fees_fully_illiquid() {
+        assert_eq!(liquidity_fees_bps(0, 1_000, 2_000, 50, 200), (0, 200));
+    }
+
+    #[test]
+    fn liquidity_fees_with_full_kink() {
+        // A 100% kink charges the full exit fee only when nothing is liquid
+        assert_eq!(liquidity_fees_bps(0, 1_000, 10_000, 50, 200), (0, 200));
+        assert_eq!(liquidity_fees_bps(1_000, 1_000, 10_000, 50, 200), (50, 0));
+    }
+}
@@ -0,0 +1,206 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import {
+  createMint,
+  createAccount,
+  mintTo,
+  getAccount,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import { PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import { expect } from "chai";
+
+describe("governance", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.Governance as Program<any>;
+  const owner = provider.wallet;
+
+  const GOVERNANCE_SEED = Buffer.from("governance");
+  const ESCROW_SEED = Buffer.from("escrow");
+  const PROPOSAL_SEED = Buffer.from("proposal");
+  const VOTE_SEED = Buffer.from("vote");
+
+  let shareMint: PublicKey;
+  let voterShareAccount: PublicKey;
+  let governancePda: PublicKey;
+  let escrowPda: PublicKey;
+  let proposalPda: PublicKey;
+  let voteRecordPda: PublicKey;
+
+  before(async () => {
+    // Stand-in for a vault share mint
+    shareMint = await createMint(
+      provider.connection,
+      (owner as any).payer,
+      owner.publicKey,
+      null,
+      6
+    );
+    voterShareAccount = await createAccount(
+      provider.connection,
+      (owner as any).payer,
+      shareMint,
+      owner.publicKey
+    );
+    await mintTo(
+      provider.connection,
+      (owner as any).payer,
+      shareMint,
+      voterShareAccount,
+      owner.publicKey,
+      1_000_000_000
+    );
+
+    [governancePda] = PublicKey.findProgramAddressSync(
+      [GOVERNANCE_SEED, shareMint.toBuffer(), owner.publicKey.toBuffer()],
+      program.programId
+    );
+    [escrowPda] = PublicKey.findProgramAddressSync(
+      [ESCROW_SEED, governancePda.toBuffer()],
+      program.programId
+    );
+    [proposalPda] = PublicKey.findProgramAddressSync(
+      [PROPOSAL_SEED, governancePda.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    [voteRecordPda] = PublicKey.findProgramAddressSync(
+      [VOTE_SEED, proposalPda.toBuffer(), owner.publicKey.toBuffer()],
+      program.programId
+    );
+  });
+
+  it("should create a governance with escrow", async () => {
+    await program.methods
+      .createGovernance({
+        votingPeriod: new anchor.BN(3_600),
+        timelockDelay: new anchor.BN(86_400),
+        quorum: new anchor.BN(100_000_000),
+      })
+      .accounts({
+        creator: owner.publicKey,
+        shareMint,
+        governance: governancePda,
+        escrow: escrowPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const governance = await program.account.governance.fetch(governancePda);
+    expect(governance.shareMint.toString()).to.equal(shareMint.toString());
+    expect(governance.proposalCount.toNumber()).to.equal(0);
+  });
+
+  it("should create a proposal", async () => {
+    await program.methods
+      .createProposal({
+        descriptionHash: Array(32).fill(1),
+        programId: SystemProgram.programId,
+        accounts: [],
+        data: Buffer.from([]),
+      })
+      .accounts({
+        proposer: owner.publicKey,
+        governance: governancePda,
+        proposerShareAccount: voterShareAccount,
+        proposal: proposalPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const proposal = await program.account.proposal.fetch(proposalPda);
+    expect(proposal.index.toNumber()).to.equal(0);
+    expect(proposal.executableAt.toNumber()).to.equal(
+      proposal.votingEndsAt.toNumber() + 86_400
+    );
+  });
+
+  it("should lock shares when voting", async () => {
+    await program.methods
+      .castVote(true, new anchor.BN(400_000_000))
+      .accounts({
+        voter: owner.publicKey,
+        governance: governancePda,
+        proposal: proposalPda,
+        voterShareAccount,
+        escrow: escrowPda,
+        voteRecord: voteRecordPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const proposal = await program.account.proposal.fetch(proposalPda);
+    expect(proposal.votesFor.toNumber()).to.equal(400_000_000);
+
+    const escrow = await getAccount(provider.connection, escrowPda);
+    expect(Number(escrow.amount)).to.equal(400_000_000);
+  });
+
+  it("should reject execution before the timelock expires", async () => {
+    try {
+      await program.methods
+        .executeProposal()
+        .accounts({
+          governance: governancePda,
+          proposal: proposalPda,
+        })
+        .remainingAccounts([
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ])
+        .rpc();
+      expect.fail("Should have thrown an error");
+    } catch (err: any) {
+      expect(err.error.errorCode.code).to.equal("TimelockNotExpired");
+    }
+  });
+
+  it("should reject relinquishing a vote while voting is open", async () => {
+    try {
+      await program.methods
+        .relinquishVote()
+        .accounts({
+          voter: owner.publicKey,
+          governance: governancePda,
+          proposal: proposalPda,
+          voteRecord: voteRecordPda,
+          voterShareAccount,
+          escrow: escrowPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      expect.fail("Should have thrown an error");
+    } catch (err: any) {
+      expect(err.error.errorCode.code).to.equal("VotingNotEnded");
+    }
+  });
+
+  it("should release shares after the proposal is cancelled", async () => {
+    await program.methods
+      .cancelProposal()
+      .accounts({
+        proposer: owner.publicKey,
+        proposal: proposalPda,
+      })
+      .rpc();
+
+    await program.methods
+      .relinquishVote()
+      .accounts({
+        voter: owner.publicKey,
+        governance: governancePda,
+        proposal: proposalPda,
+        voteRecord: voteRecordPda,
+        voterShareAccount,
+        escrow: escrowPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const shares = await getAccount(provider.connection, voterShareAccount);
+    expect(Number(shares.amount)).to.equal(1_000_000_000);
+  });
+});
@@ -0,0 +1,357 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import {
+  createMint,
+  createAccount,
+  mintTo,
+  getAccount,
+  getMint,
+  transfer,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import {
+  PublicKey,
+  SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  SYSVAR_RENT_PUBKEY,
+} from "@solana/web3.js";
+import { expect } from "chai";
+
+describe("meta_vault", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MetaVault as Program<any>;
+  const vaultProgram = anchor.workspace.YieldVault as Program<any>;
+  const owner = provider.wallet;
+
+  let mint: PublicKey;
+  let childVault: PublicKey;
+  let childVaultToken: PublicKey;
+  let childShareMint: PublicKey;
+  let metaVaultPda: PublicKey;
+  let vaultAuthority: PublicKey;
+  let shareMint: PublicKey;
+  let assetsPda: PublicKey;
+  let childSharesPda: PublicKey;
+  let userTokenAccount: PublicKey;
+  let userShareAccount: PublicKey;
+
+  const pda = (seeds: Buffer[], programId: PublicKey) =>
+    PublicKey.findProgramAddressSync(seeds, programId)[0];
+
+  before(async () => {
+    // A fresh yield vault to allocate to
+    mint = await createMint(provider.connection, (owner as any).payer, owner.publicKey, null, 6);
+    childVault = pda(
+      [Buffer.from("vault"), mint.toBuffer(), owner.publicKey.toBuffer()],
+      vaultProgram.programId
+    );
+    childVaultToken = pda(
+      [Buffer.from("vault_token"), childVault.toBuffer()],
+      vaultProgram.programId
+    );
+
+    await vaultProgram.methods
+      .createVault({
+        depositCap: new anchor.BN(0),
+        minDeposit: new anchor.BN(0),
+        managementFeeBps: 0,
+        performanceFeeBps: 0,
+        shareDecimals: null,
+        paper: false,
+      })
+      .accounts({
+        owner: owner.publicKey,
+        mint,
+        vault: childVault,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const { createdSlot } = await vaultProgram.account.vaultState.fetch(childVault);
+    childShareMint = pda(
+      [Buffer.from("share_mint"), childVault.toBuffer(), createdSlot.toArrayLike(Buffer, "le", 8)],
+      vaultProgram.programId
+    );
+
+    await vaultProgram.methods
+      .initVaultAccounts()
+      .accounts({
+        owner: owner.publicKey,
+        mint,
+        vault: childVault,
+        shareMint: childShareMint,
+        vaultTokenAccount: childVaultToken,
+        feeReport: pda([Buffer.from("fee_report"), childVault.toBuffer()], vaultProgram.programId),
+        priceHistory: pda(
+          [Buffer.from("price_history"), childVault.toBuffer()],
+          vaultProgram.programId
+        ),
+        strategyRegistry: pda(
+          [Buffer.from("strategy_registry"), childVault.toBuffer()],
+          vaultProgram.programId
+        ),
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    userTokenAccount = await createAccount(
+      provider.connection,
+      (owner as any).payer,
+      mint,
+      owner.publicKey
+    );
+    await mintTo(
+      provider.connection,
+      (owner as any).payer,
+      mint,
+      userTokenAccount,
+      owner.publicKey,
+      10_000_000
+    );
+
+    metaVaultPda = pda(
+      [Buffer.from("meta_vault"), mint.toBuffer(), owner.publicKey.toBuffer()],
+      program.programId
+    );
+    vaultAuthority = pda(
+      [Buffer.from("meta_authority"), metaVaultPda.toBuffer()],
+      program.programId
+    );
+    shareMint = pda([Buffer.from("meta_share_mint"), metaVaultPda.toBuffer()], program.programId);
+    assetsPda = pda([Buffer.from("meta_assets"), metaVaultPda.toBuffer()], program.programId);
+    childSharesPda = pda(
+      [Buffer.from("child_shares"), metaVaultPda.toBuffer(), childVault.toBuffer()],
+      program.programId
+    );
+  });
+
+  const childPairs = () => [
+    { pubkey: childVault, isWritable: false, isSigner: false },
+    { pubkey: childSharesPda, isWritable: false, isSigner: false },
+  ];
+
+  const flowAccounts = () => ({
+    user: owner.publicKey,
+    metaVault: metaVaultPda,
+    vaultAuthority,
+    shareMint,
+    assets: assetsPda,
+    userTokenAccount,
+    userShareAccount,
+    tokenProgram: TOKEN_PROGRAM_ID,
+  });
+
+  const allocateIx = (amount: number) =>
+    program.methods
+      .allocate(0, new anchor.BN(amount))
+      .accounts({
+        manager: owner.publicKey,
+        metaVault: metaVaultPda,
+        vaultAuthority,
+        assets: assetsPda,
+        childVault,
+        childShares: childSharesPda,
+        childVaultTokenAccount: childVaultToken,
+        childShareMint,
+        childPosition: pda(
+          [Buffer.from("position"), childVault.toBuffer(), vaultAuthority.toBuffer()],
+          vaultProgram.programId
+        ),
+        childRewards: null,
+        childOracle: null,
+        childMarketShareReserve: null,
+        childMarketAssetReserve: null,
+        childGatewayToken: null,
+        childWalletFlag: pda(
+          [Buffer.from("wallet_flag"), vaultAuthority.toBuffer()],
+          vaultProgram.programId
+        ),
+        childPortfolio: pda(
+          [Buffer.from("portfolio"), vaultAuthority.toBuffer()],
+          vaultProgram.programId
+        ),
+        childEventAuthority: pda([Buffer.from("__event_authority")], vaultProgram.programId),
+        yieldVaultProgram: vaultProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(childPairs());
+
+  const allocate = (amount: number) => allocateIx(amount).rpc();
+
+  it("should create a meta vault and add a child", async () => {
+    await program.methods
+      .createMetaVault(owner.publicKey)
+      .accounts({
+        owner: owner.publicKey,
+        mint,
+        metaVault: metaVaultPda,
+        vaultAuthority,
+        shareMint,
+        assets: assetsPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    await program.methods
+      .addChild()
+      .accounts({
+        owner: owner.publicKey,
+        metaVault: metaVaultPda,
+        vaultAuthority,
+        childVault,
+        childShareMint,
+        childShares: childSharesPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    userShareAccount = await createAccount(
+      provider.connection,
+      (owner as any).payer,
+      shareMint,
+      owner.publicKey
+    );
+
+    const metaVault = await program.account.metaVault.fetch(metaVaultPda);
+    expect(metaVault.childCount).to.equal(1);
+    expect(metaVault.children[0].vault.toString()).to.equal(childVault.toString());
+  });
+
+  it("should mint share_scale meta shares per unit on the first deposit", async () => {
+    await program.methods
+      .deposit(new anchor.BN(10_000_000))
+      .accounts(flowAccounts())
+      .remainingAccounts(childPairs())
+      .rpc();
+
+    const shares = await getAccount(provider.connection, userShareAccount);
+    expect(Number(shares.amount)).to.equal(10_000_000_000);
+    const metaVault = await program.account.metaVault.fetch(metaVaultPda);
+    expect(metaVault.shareScale.toNumber()).to.equal(1_000);
+    expect(metaVault.idleAssets.toNumber()).to.equal(10_000_000);
+  });
+
+  it("should cap allocations at the child's weight", async () => {
+    await program.methods
+      .setWeights([5_000])
+      .accounts({ manager: owner.publicKey, metaVault: metaVaultPda })
+      .rpc();
+
+    try {
+      await allocate(6_000_000);
+      expect.fail("Should have thrown an error");
+    } catch (err: any) {
+      expect(err.error.errorCode.code).to.equal("WeightExceeded");
+    }
+
+    await allocate(5_000_000);
+    const childShares = await getAccount(provider.connection, childSharesPda);
+    expect(Number(childShares.amount)).to.be.greaterThan(0);
+    const assets = await getAccount(provider.connection, assetsPda);
+    expect(Number(assets.amount)).to.equal(5_000_000);
+    const metaVault = await program.account.metaVault.fetch(metaVaultPda);
+    expect(metaVault.idleAssets.toNumber()).to.equal(5_000_000);
+  });
+
+  it("should reject an allocation bundled with the child's NAV update", async () => {
+    await program.methods
+      .setWeights([10_000])
+      .accounts({ manager: owner.publicKey, metaVault: metaVaultPda })
+      .rpc();
+
+    // The deposit reaches the child by CPI, out of sight of `update_nav`
+    const { totalAssets } = await vaultProgram.account.vaultState.fetch(childVault);
+    const navIx = await vaultProgram.methods
+      .updateNav(totalAssets, null)
+      .accounts({
+        authority: owner.publicKey,
+        vault: childVault,
+        feeReport: pda([Buffer.from("fee_report"), childVault.toBuffer()], vaultProgram.programId),
+        priceHistory: pda(
+          [Buffer.from("price_history"), childVault.toBuffer()],
+          vaultProgram.programId
+        ),
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        oracle: null,
+        managerProfile: null,
+        reservesVerifier: null,
+      })
+      .instruction();
+
+    try {
+      await allocateIx(1_000_000).preInstructions([navIx]).rpc();
+      expect.fail("Should have thrown an error");
+    } catch (err: any) {
+      expect((err.logs ?? []).join("\n")).to.include("NavUpdateWithUserFlow");
+    }
+
+    await program.methods
+      .setWeights([5_000])
+      .accounts({ manager: owner.publicKey, metaVault: metaVaultPda })
+      .rpc();
+  });
+
+  it("should pay withdrawals from unallocated underlying only", async () => {
+    try {
+      await program.methods
+        .withdraw(new anchor.BN(6_000_000_000))
+        .accounts(flowAccounts())
+        .remainingAccounts(childPairs())
+        .rpc();
+      expect.fail("Should have thrown an error");
+    } catch (err: any) {
+      expect(err.error.errorCode.code).to.equal("InsufficientLiquidity");
+    }
+
+    await program.methods
+      .withdraw(new anchor.BN(4_000_000_000))
+      .accounts(flowAccounts())
+      .remainingAccounts(childPairs())
+      .rpc();
+
+    const underlying = await getAccount(provider.connection, userTokenAccount);
+    expect(Number(underlying.amount)).to.equal(4_000_000);
+  });
+
+  it("should not let a donation to the assets account move the share price", async () => {
+    const before = await program.account.metaVault.fetch(metaVaultPda);
+    const supplyBefore = (await getMint(provider.connection, shareMint)).supply;
+
+    // Tokens sent straight to the assets account are not counted in NAV
+    await transfer(
+      provider.connection,
+      (owner as any).payer,
+      userTokenAccount,
+      assetsPda,
+      owner.publicKey,
+      2_000_000
+    );
+
+    const sharesBefore = (await getAccount(provider.connection, userShareAccount)).amount;
+    await program.methods
+      .deposit(new anchor.BN(1_000_000))
+      .accounts(flowAccounts())
+      .remainingAccounts(childPairs())
+      .rpc();
+    const minted = (await getAccount(provider.connection, userShareAccount)).amount - sharesBefore;
+
+    // Priced on the recorded NAV: 1 asset unit still buys ~1_000 shares
+    const childValue = 5_000_000; // Nothing has moved the child's share price
+    const nav = BigInt(before.idleAssets.toNumber() + childValue);
+    const expected =
+      (1_000_000n * (supplyBefore + BigInt(before.shareScale.toNumber()))) / (nav + 1n);
+    expect(minted).to.equal(expected);
+
+    const after = await program.account.metaVault.fetch(metaVaultPda);
+    expect(after.idleAssets.toNumber()).to.equal(before.idleAssets.toNumber() + 1_000_000);
+  });
+});
@@ -0,0 +1,257 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import {
+  createMint,
+  createAccount,
+  mintTo,
+  getAccount,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import { PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import { expect } from "chai";
+
+describe("risk_tranches", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.RiskTranches as Program<any>;
+  const vaultProgram = anchor.workspace.YieldVault as Program<any>;
+  const owner = provider.wallet;
+
+  let vaultPda: PublicKey;
+  let shareMint: PublicKey;
+  let userShareAccount: PublicKey;
+  let trancheVaultPda: PublicKey;
+  let seniorMint: PublicKey;
+  let juniorMint: PublicKey;
+  let escrowPda: PublicKey;
+  let seniorAccount: PublicKey;
+  let juniorAccount: PublicKey;
+
+  const pda = (seeds: Buffer[], programId: PublicKey) =>
+    PublicKey.findProgramAddressSync(seeds, programId)[0];
+
+  before(async () => {
+    // A fresh yield vault the owner holds shares of
+    const mint = await createMint(
+      provider.connection,
+      (owner as any).payer,
+      owner.publicKey,
+      null,
+      6
+    );
+    vaultPda = pda(
+      [Buffer.from("vault"), mint.toBuffer(), owner.publicKey.toBuffer()],
+      vaultProgram.programId
+    );
+    const vaultTokenPda = pda(
+      [Buffer.from("vault_token"), vaultPda.toBuffer()],
+      vaultProgram.programId
+    );
+
+    await vaultProgram.methods
+      .createVault({
+        depositCap: new anchor.BN(0),
+        minDeposit: new anchor.BN(0),
+        managementFeeBps: 0,
+        performanceFeeBps: 0,
+        shareDecimals: null,
+        paper: false,
+      })
+      .accounts({
+        owner: owner.publicKey,
+        mint,
+        vault: vaultPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const { createdSlot } = await vaultProgram.account.vaultState.fetch(vaultPda);
+    shareMint = pda(
+      [Buffer.from("share_mint"), vaultPda.toBuffer(), createdSlot.toArrayLike(Buffer, "le", 8)],
+      vaultProgram.programId
+    );
+
+    await vaultProgram.methods
+      .initVaultAccounts()
+      .accounts({
+        owner: owner.publicKey,
+        mint,
+        vault: vaultPda,
+        shareMint,
+        vaultTokenAccount: vaultTokenPda,
+        feeReport: pda([Buffer.from("fee_report"), vaultPda.toBuffer()], vaultProgram.programId),
+        priceHistory: pda(
+          [Buffer.from("price_history"), vaultPda.toBuffer()],
+          vaultProgram.programId
+        ),
+        strategyRegistry: pda(
+          [Buffer.from("strategy_registry"), vaultPda.toBuffer()],
+          vaultProgram.programId
+        ),
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const userTokenAccount = await createAccount(
+      provider.connection,
+      (owner as any).payer,
+      mint,
+      owner.publicKey
+    );
+    await mintTo(
+      provider.connection,
+      (owner as any).payer,
+      mint,
+      userTokenAccount,
+      owner.publicKey,
+      100_000_000
+    );
+    userShareAccount = await createAccount(
+      provider.connection,
+      (owner as any).payer,
+      shareMint,
+      owner.publicKey
+    );
+
+    await vaultProgram.methods
+      .deposit(new anchor.BN(100_000_000), null)
+      .accounts({
+        user: owner.publicKey,
+        vault: vaultPda,
+        vaultTokenAccount: vaultTokenPda,
+        shareMint,
+        userTokenAccount,
+        userShareAccount,
+        position: pda(
+          [Buffer.from("position"), vaultPda.toBuffer(), owner.publicKey.toBuffer()],
+          vaultProgram.programId
+        ),
+        rewards: null,
+        oracle: null,
+        marketShareReserve: null,
+        marketAssetReserve: null,
+        gatewayToken: null,
+        walletFlag: pda(
+          [Buffer.from("wallet_flag"), owner.publicKey.toBuffer()],
+          vaultProgram.programId
+        ),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    trancheVaultPda = pda(
+      [Buffer.from("tranche_vault"), vaultPda.toBuffer(), owner.publicKey.toBuffer()],
+      program.programId
+    );
+    seniorMint = pda([Buffer.from("senior_mint"), trancheVaultPda.toBuffer()], program.programId);
+    juniorMint = pda([Buffer.from("junior_mint"), trancheVaultPda.toBuffer()], program.programId);
+    escrowPda = pda([Buffer.from("escrow"), trancheVaultPda.toBuffer()], program.programId);
+  });
+
+  const depositAccounts = (trancheMint: PublicKey, userTrancheAccount: PublicKey) => ({
+    user: owner.publicKey,
+    vault: vaultPda,
+    trancheVault: trancheVaultPda,
+    trancheMint,
+    escrow: escrowPda,
+    userShareAccount,
+    userTrancheAccount,
+    tokenProgram: TOKEN_PROGRAM_ID,
+  });
+
+  it("should create a tranche vault over the yield vault's shares", async () => {
+    await program.methods
+      .createTrancheVault({ seniorRateBps: 500, minSubordinationBps: 2_000 })
+      .accounts({
+        authority: owner.publicKey,
+        vault: vaultPda,
+        shareMint,
+        trancheVault: trancheVaultPda,
+        seniorMint,
+        juniorMint,
+        escrow: escrowPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    seniorAccount = await createAccount(
+      provider.connection,
+      (owner as any).payer,
+      seniorMint,
+      owner.publicKey
+    );
+    juniorAccount = await createAccount(
+      provider.connection,
+      (owner as any).payer,
+      juniorMint,
+      owner.publicKey
+    );
+
+    const trancheVault = await program.account.trancheVault.fetch(trancheVaultPda);
+    expect(trancheVault.vault.toString()).to.equal(vaultPda.toString());
+    expect(trancheVault.seniorRateBps).to.equal(500);
+  });
+
+  it("should reject senior deposits without junior cover", async () => {
+    try {
+      await program.methods
+        .deposit({ senior: {} }, new anchor.BN(10_000_000))
+        .accounts(depositAccounts(seniorMint, seniorAccount))
+        .rpc();
+      expect.fail("Should have thrown an error");
+    } catch (err: any) {
+      expect(err.error.errorCode.code).to.equal("InsufficientSubordination");
+    }
+  });
+
+  it("should accept senior deposits once the junior covers them", async () => {
+    await program.methods
+      .deposit({ junior: {} }, new anchor.BN(20_000_000))
+      .accounts(depositAccounts(juniorMint, juniorAccount))
+      .rpc();
+    await program.methods
+      .deposit({ senior: {} }, new anchor.BN(60_000_000))
+      .accounts(depositAccounts(seniorMint, seniorAccount))
+      .rpc();
+
+    const trancheVault = await program.account.trancheVault.fetch(trancheVaultPda);
+    expect(trancheVault.juniorAssets.toNumber()).to.equal(20_000_000);
+    expect(trancheVault.seniorAssets.toNumber()).to.equal(60_000_000);
+
+    const escrow = await getAccount(provider.connection, escrowPda);
+    expect(Number(escrow.amount)).to.equal(80_000_000);
+  });
+
+  it("should block junior withdrawals that would uncover the senior", async () => {
+    try {
+      await program.methods
+        .withdraw({ junior: {} }, new anchor.BN(10_000_000))
+        .accounts(depositAccounts(juniorMint, juniorAccount))
+        .rpc();
+      expect.fail("Should have thrown an error");
+    } catch (err: any) {
+      expect(err.error.errorCode.code).to.equal("InsufficientSubordination");
+    }
+  });
+
+  it("should return senior shares on withdrawal", async () => {
+    const sharesBefore = await getAccount(provider.connection, userShareAccount);
+
+    await program.methods
+      .withdraw({ senior: {} }, new anchor.BN(60_000_000))
+      .accounts(depositAccounts(seniorMint, seniorAccount))
+      .rpc();
+
+    const sharesAfter = await getAccount(provider.connection, userShareAccount);
+    expect(Number(sharesAfter.amount - sharesBefore.amount)).to.equal(60_000_000);
+
+    const trancheVault = await program.account.trancheVault.fetch(trancheVaultPda);
+    expect(trancheVault.seniorAssets.toNumber()).to.equal(0);
+  });
+});
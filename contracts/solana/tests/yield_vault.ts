@@ -1,14 +1,24 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import {
+  approve,
   createMint,
   createAccount,
+  createTransferInstruction,
   mintTo,
   getAccount,
+  getMint,
   TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
-import { PublicKey, Keypair, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  SYSVAR_RENT_PUBKEY,
+} from "@solana/web3.js";
 import { expect } from "chai";
+import { createHash } from "crypto";
 
 // Type will be generated after first build
 // import { YieldVault } from "../target/types/yield_vault";
@@ -27,14 +37,55 @@ describe("yield_vault", () => {
   let shareMintBump: number;
   let vaultTokenPda: PublicKey;
   let vaultTokenBump: number;
+  let feeReportPda: PublicKey;
+  let priceHistoryPda: PublicKey;
+  let strategyRegistryPda: PublicKey;
+  let auditLogPda: PublicKey;
+  let protocolConfigPda: PublicKey;
+  let strategyWhitelistPda: PublicKey;
+  let feedRegistryPda: PublicKey;
+  let globalStatsPda: PublicKey;
 
   // User token accounts
   let userTokenAccount: PublicKey;
   let userShareAccount: PublicKey;
+  let positionPda: PublicKey;
+  let walletFlagPda: PublicKey;
 
   const VAULT_SEED = Buffer.from("vault");
   const SHARE_MINT_SEED = Buffer.from("share_mint");
   const VAULT_TOKEN_SEED = Buffer.from("vault_token");
+  const FEE_REPORT_SEED = Buffer.from("fee_report");
+  const PRICE_HISTORY_SEED = Buffer.from("price_history");
+  const STRATEGY_REGISTRY_SEED = Buffer.from("strategy_registry");
+  const AUDIT_LOG_SEED = Buffer.from("audit_log");
+
+  // Events are emitted as CPIs to the program itself: the inner instruction's
+  // data is Anchor's event tag followed by the encoded event
+  const EVENT_IX_TAG = Buffer.from("e445a52e51cb9a1d", "hex");
+  const eventAuthorityPda = PublicKey.findProgramAddressSync(
+    [Buffer.from("__event_authority")],
+    program.programId
+  )[0];
+  const eventsOf = async (signature: string) => {
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    return tx!.meta!.innerInstructions!.flatMap((inner) => inner.instructions)
+      .map((ix) => Buffer.from(anchor.utils.bytes.bs58.decode(ix.data)))
+      .filter((data) => data.subarray(0, 8).equals(EVENT_IX_TAG))
+      .map((data) =>
+        program.coder.events.decode(anchor.utils.bytes.base64.encode(data.subarray(8)))
+      )
+      .filter((event) => event !== null);
+  };
+
+  const managerProfilePda = (manager: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("manager_profile"), manager.toBuffer()],
+      program.programId
+    )[0];
 
   before(async () => {
     // Create underlying token mint (e.g. fake USDC with 6 decimals)
@@ -52,17 +103,119 @@ describe("yield_vault", () => {
       program.programId
     );
 
-    // Derive share mint PDA
-    [shareMintPda, shareMintBump] = PublicKey.findProgramAddressSync(
-      [SHARE_MINT_SEED, vaultPda.toBuffer()],
-      program.programId
-    );
-
     // Derive vault token account PDA
     [vaultTokenPda, vaultTokenBump] = PublicKey.findProgramAddressSync(
       [VAULT_TOKEN_SEED, vaultPda.toBuffer()],
       program.programId
     );
+
+    // Derive the owner's position PDA (owner acts as the depositor)
+    [positionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), vaultPda.toBuffer(), owner.publicKey.toBuffer()],
+      program.programId
+    );
+    [walletFlagPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("wallet_flag"), owner.publicKey.toBuffer()],
+      program.programId
+    );
+
+    // Derive fee report PDA
+    [feeReportPda] = PublicKey.findProgramAddressSync(
+      [FEE_REPORT_SEED, vaultPda.toBuffer()],
+      program.programId
+    );
+
+    // Derive price history and strategy registry PDAs
+    [priceHistoryPda] = PublicKey.findProgramAddressSync(
+      [PRICE_HISTORY_SEED, vaultPda.toBuffer()],
+      program.programId
+    );
+    [strategyRegistryPda] = PublicKey.findProgramAddressSync(
+      [STRATEGY_REGISTRY_SEED, vaultPda.toBuffer()],
+      program.programId
+    );
+    [auditLogPda] = PublicKey.findProgramAddressSync(
+      [AUDIT_LOG_SEED, vaultPda.toBuffer()],
+      program.programId
+    );
+
+    // Protocol config and strategy whitelist, administered by the upgrade authority
+    [protocolConfigPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("protocol_config")],
+      program.programId
+    );
+    [strategyWhitelistPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("strategy_whitelist")],
+      program.programId
+    );
+    [feedRegistryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("feed_registry")],
+      program.programId
+    );
+    [globalStatsPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("global_stats")],
+      program.programId
+    );
+    const [programData] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+
+    await program.methods
+      .initProtocolConfig({
+        treasury: owner.publicKey,
+        guardian: PublicKey.default,
+        minManagementFeeBps: 0,
+        maxManagementFeeBps: 300,
+        minPerformanceFeeBps: 0,
+        maxPerformanceFeeBps: 2500,
+        defaultManagementFeeBps: 150,
+        defaultPerformanceFeeBps: 1000,
+        defaultDepositCap: new anchor.BN(0),
+        defaultMinDeposit: new anchor.BN(1_000),
+        defaultWithdrawDelaySlots: new anchor.BN(2),
+        defaultReserveRatioBps: 500,
+        defaultSmallWithdrawalThreshold: new anchor.BN(1_000_000),
+        zapPrograms: Array(4).fill(PublicKey.default),
+      })
+      .accounts({
+        admin: owner.publicKey,
+        protocolConfig: protocolConfigPda,
+        program: program.programId,
+        programData,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .initStrategyWhitelist()
+      .accounts({
+        admin: owner.publicKey,
+        protocolConfig: protocolConfigPda,
+        strategyWhitelist: strategyWhitelistPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .initFeedRegistry()
+      .accounts({
+        admin: owner.publicKey,
+        protocolConfig: protocolConfigPda,
+        feedRegistry: feedRegistryPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .initGlobalStats()
+      .accounts({
+        admin: owner.publicKey,
+        protocolConfig: protocolConfigPda,
+        globalStats: globalStatsPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
   });
 
   describe("initialize_vault", () => {
@@ -73,6 +226,8 @@ describe("yield_vault", () => {
           minDeposit: new anchor.BN(1_000_000), // 1 USDC
           managementFeeBps: 200, // 2%
           performanceFeeBps: 2000, // 20%
+          shareDecimals: null,
+          paper: false,
         })
         .accounts({
           owner: owner.publicKey,
@@ -92,7 +247,21 @@ describe("yield_vault", () => {
       expect(vaultState.minDeposit.toNumber()).to.equal(1_000_000);
       expect(vaultState.managementFeeBps).to.equal(200);
       expect(vaultState.performanceFeeBps).to.equal(2000);
-      expect(vaultState.paused).to.equal(false);
+      expect(vaultState.status).to.equal(0); // Uninitialized
+
+      // Layout includes the reserved space for future fields
+      const vaultInfo = await provider.connection.getAccountInfo(vaultPda);
+      expect(vaultInfo!.data.length).to.equal(8 + 1056);
+
+      // Share mint PDA is seeded by the vault's creation slot
+      [shareMintPda, shareMintBump] = PublicKey.findProgramAddressSync(
+        [
+          SHARE_MINT_SEED,
+          vaultPda.toBuffer(),
+          vaultState.createdSlot.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
     });
 
     it("should initialize vault accounts (step 2)", async () => {
@@ -104,6 +273,9 @@ describe("yield_vault", () => {
           vault: vaultPda,
           shareMint: shareMintPda,
           vaultTokenAccount: vaultTokenPda,
+          feeReport: feeReportPda,
+          priceHistory: priceHistoryPda,
+          strategyRegistry: strategyRegistryPda,
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
           rent: SYSVAR_RENT_PUBKEY,
@@ -114,6 +286,67 @@ describe("yield_vault", () => {
       const vaultState = await program.account.vaultState.fetch(vaultPda);
       expect(vaultState.shareMintBump).to.be.greaterThan(0);
       expect(vaultState.tokenAccountBump).to.be.greaterThan(0);
+      expect(vaultState.status).to.equal(1); // Active
+
+      const history = await program.account.priceHistory.fetch(priceHistoryPda);
+      expect(history.vault.toString()).to.equal(vaultPda.toString());
+      expect(history.count.toNumber()).to.equal(0);
+    });
+
+    it("should cancel a vault whose step 2 never ran", async () => {
+      const otherMint = await createMint(
+        provider.connection,
+        (owner as any).payer,
+        owner.publicKey,
+        null,
+        6
+      );
+      const [otherVaultPda] = PublicKey.findProgramAddressSync(
+        [VAULT_SEED, otherMint.toBuffer(), owner.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .createVault({
+          depositCap: new anchor.BN(0),
+          minDeposit: new anchor.BN(1_000_000),
+          managementFeeBps: 0,
+          performanceFeeBps: 0,
+          shareDecimals: null,
+          paper: false,
+        })
+        .accounts({
+          owner: owner.publicKey,
+          mint: otherMint,
+          vault: otherVaultPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .cancelInit()
+        .accounts({
+          owner: owner.publicKey,
+          vault: otherVaultPda,
+        })
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(otherVaultPda)).to.be.null;
+    });
+
+    it("should reject cancelling an initialized vault", async () => {
+      try {
+        await program.methods
+          .cancelInit()
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("VaultAlreadyInitialized");
+      }
     });
   });
 
@@ -150,7 +383,7 @@ describe("yield_vault", () => {
       const depositAmount = new anchor.BN(1_000_000_000); // 1000 USDC
 
       await program.methods
-        .deposit(depositAmount)
+        .deposit(depositAmount, null)
         .accounts({
           user: owner.publicKey,
           vault: vaultPda,
@@ -158,7 +391,15 @@ describe("yield_vault", () => {
           shareMint: shareMintPda,
           userTokenAccount: userTokenAccount,
           userShareAccount: userShareAccount,
+          position: positionPda,
+          rewards: null,
+          oracle: null,
+          marketShareReserve: null,
+          marketAssetReserve: null,
+          gatewayToken: null,
+          walletFlag: walletFlagPda,
           tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
         })
         .rpc();
 
@@ -174,12 +415,36 @@ describe("yield_vault", () => {
       // Verify vault received tokens
       const vaultToken = await getAccount(provider.connection, vaultTokenPda);
       expect(Number(vaultToken.amount)).to.equal(1_000_000_000);
+
+      // Verify the deposit was pushed to the protocol stats
+      const stats = await program.account.globalStats.fetch(globalStatsPda);
+      expect(stats.vaultCount.toNumber()).to.equal(1);
+      // Totals are kept per underlying mint, never summed across mints
+      const mintStats = stats.mints.find((entry: any) => entry.mint.equals(mint));
+      expect(mintStats.tvl.toNumber()).to.equal(1_000_000_000);
+      expect(mintStats.totalShares.toNumber()).to.equal(1_000_000_000);
+      expect(mintStats.sharePriceQ64.toString()).to.equal(new anchor.BN(1).shln(64).toString());
+      expect(vaultState.statsReportedAssets.toNumber()).to.equal(1_000_000_000);
+
+      // Verify the vault was listed in the user's portfolio
+      const [portfolioPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("portfolio"), owner.publicKey.toBuffer()],
+        program.programId
+      );
+      const portfolio = await program.account.portfolio.fetch(portfolioPda);
+      expect(portfolio.vaultCount).to.equal(1);
+      expect(portfolio.vaults[0].toBase58()).to.equal(vaultPda.toBase58());
+
+      const statsInfo = await provider.connection.getAccountInfo(globalStatsPda);
+      expect(statsInfo!.data.length).to.equal(8 + 1489);
+      const portfolioInfo = await provider.connection.getAccountInfo(portfolioPda);
+      expect(portfolioInfo!.data.length).to.equal(8 + 1122);
     });
 
     it("should reject deposit below minimum", async () => {
       try {
         await program.methods
-          .deposit(new anchor.BN(100)) // Below min_deposit of 1_000_000
+          .deposit(new anchor.BN(100), null) // Below min_deposit of 1_000_000
           .accounts({
             user: owner.publicKey,
             vault: vaultPda,
@@ -187,7 +452,15 @@ describe("yield_vault", () => {
             shareMint: shareMintPda,
             userTokenAccount: userTokenAccount,
             userShareAccount: userShareAccount,
+            position: positionPda,
+            rewards: null,
+            oracle: null,
+            marketShareReserve: null,
+            marketAssetReserve: null,
+            gatewayToken: null,
+            walletFlag: walletFlagPda,
             tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
           })
           .rpc();
         expect.fail("Should have thrown an error");
@@ -198,9 +471,10 @@ describe("yield_vault", () => {
 
     it("should mint proportional shares for second deposit", async () => {
       const depositAmount = new anchor.BN(500_000_000); // 500 USDC
+      const memo = Array.from(Buffer.alloc(32, "ref:agent-7"));
 
-      await program.methods
-        .deposit(depositAmount)
+      const sig = await program.methods
+        .deposit(depositAmount, memo)
         .accounts({
           user: owner.publicKey,
           vault: vaultPda,
@@ -208,11 +482,25 @@ describe("yield_vault", () => {
           shareMint: shareMintPda,
           userTokenAccount: userTokenAccount,
           userShareAccount: userShareAccount,
+          position: positionPda,
+          rewards: null,
+          oracle: null,
+          marketShareReserve: null,
+          marketAssetReserve: null,
+          gatewayToken: null,
+          walletFlag: walletFlagPda,
           tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
         })
-        .rpc();
+        .rpc({ commitment: "confirmed" });
+
+      // The memo is echoed in the Deposited event
+      const deposited = (await eventsOf(sig)).find((e) => e.data.memo);
+      expect(deposited!.data.memo).to.deep.equal(memo);
 
       const vaultState = await program.account.vaultState.fetch(vaultPda);
+      // Every event numbers itself from the vault's counter
+      expect(deposited!.data.eventSeq.toNumber()).to.equal(vaultState.eventSeq.toNumber());
       // 1000 + 500 = 1500 USDC
       expect(vaultState.totalAssets.toNumber()).to.equal(1_500_000_000);
       // At 1:1 ratio, shares = 1000 + 500 = 1500
@@ -221,6 +509,31 @@ describe("yield_vault", () => {
   });
 
   describe("withdraw", () => {
+    const withdrawAccounts = () => ({
+      user: owner.publicKey,
+      vault: vaultPda,
+      vaultTokenAccount: vaultTokenPda,
+      shareMint: shareMintPda,
+      userTokenAccount: userTokenAccount,
+      recipient: null,
+      userShareAccount: userShareAccount,
+      position: positionPda,
+      rewards: null,
+      oracle: null,
+      marketShareReserve: null,
+      marketAssetReserve: null,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    });
+
+    before(async () => {
+      // Withdrawals in the deposit slot are rejected; let the chain move on
+      const depositSlot = await provider.connection.getSlot();
+      while ((await provider.connection.getSlot()) <= depositSlot) {
+        await new Promise((resolve) => setTimeout(resolve, 100));
+      }
+    });
+
     it("should withdraw tokens by burning shares", async () => {
       const sharesToBurn = new anchor.BN(500_000_000); // 500 shares
 
@@ -228,15 +541,19 @@ describe("yield_vault", () => {
       const balanceBefore = Number(userTokenBefore.amount);
 
       await program.methods
-        .withdraw(sharesToBurn)
+        .withdraw(sharesToBurn, 10_000, null)
         .accounts({
           user: owner.publicKey,
           vault: vaultPda,
           vaultTokenAccount: vaultTokenPda,
           shareMint: shareMintPda,
           userTokenAccount: userTokenAccount,
+          recipient: null,
           userShareAccount: userShareAccount,
+          position: positionPda,
+          rewards: null,
           tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
         })
         .rpc();
 
@@ -249,198 +566,4238 @@ describe("yield_vault", () => {
       expect(Number(userTokenAfter.amount) - balanceBefore).to.equal(500_000_000);
     });
 
-    it("should reject withdrawal with insufficient shares", async () => {
+    it("should pay the withdrawal to a separate recipient", async () => {
+      const coldWallet = Keypair.generate();
+      const recipient = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        mint,
+        coldWallet.publicKey
+      );
+      const userTokenBefore = await getAccount(provider.connection, userTokenAccount);
+
+      await program.methods
+        .withdraw(new anchor.BN(1_000_000), 10_000, null)
+        .accounts({ ...withdrawAccounts(), recipient })
+        .rpc();
+
+      const recipientAfter = await getAccount(provider.connection, recipient);
+      expect(Number(recipientAfter.amount)).to.equal(1_000_000);
+      const userTokenAfter = await getAccount(provider.connection, userTokenAccount);
+      expect(userTokenAfter.amount).to.equal(userTokenBefore.amount);
+    });
+
+    it("should leave the exit fee in the vault on an early withdrawal", async () => {
+      const setExitFee = (feeBps: number, fullPeriod: number, decayEnd: number) =>
+        program.methods
+          .setExitFee(feeBps, new anchor.BN(fullPeriod), new anchor.BN(decayEnd))
+          .accounts({ owner: owner.publicKey, vault: vaultPda })
+          .rpc();
+
+      // 1% within a week of the last deposit, decaying to nothing at 30 days
+      await setExitFee(100, 7 * 86_400, 30 * 86_400);
+      const vaultBefore = await program.account.vaultState.fetch(vaultPda);
+      const userTokenBefore = await getAccount(provider.connection, userTokenAccount);
+
+      await program.methods
+        .withdraw(new anchor.BN(1_000_000), 10_000, null)
+        .accounts(withdrawAccounts())
+        .rpc();
+
+      const userTokenAfter = await getAccount(provider.connection, userTokenAccount);
+      expect(Number(userTokenAfter.amount - userTokenBefore.amount)).to.equal(990_000);
+      const vaultAfter = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultBefore.totalAssets.sub(vaultAfter.totalAssets).toNumber()).to.equal(990_000);
+
+      // Shares moved to a wallet that never deposited pay the full fee
+      const fresh = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(fresh.publicKey, 1_000_000_000);
+      await provider.connection.confirmTransaction(sig);
+      const freshTokenAccount = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        mint,
+        fresh.publicKey
+      );
+      const freshShareAccount = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        shareMintPda,
+        fresh.publicKey
+      );
+      const [freshPosition] = PublicKey.findProgramAddressSync(
+        [Buffer.from("position"), vaultPda.toBuffer(), fresh.publicKey.toBuffer()],
+        program.programId
+      );
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          createTransferInstruction(userShareAccount, freshShareAccount, owner.publicKey, 1_000_000)
+        )
+      );
+      await program.methods
+        .withdraw(new anchor.BN(1_000_000), 10_000, null)
+        .accounts({
+          ...withdrawAccounts(),
+          user: fresh.publicKey,
+          userTokenAccount: freshTokenAccount,
+          userShareAccount: freshShareAccount,
+          position: freshPosition,
+        })
+        .signers([fresh])
+        .rpc();
+      const freshToken = await getAccount(provider.connection, freshTokenAccount);
+      expect(Number(freshToken.amount)).to.equal(990_000);
+
       try {
-        await program.methods
-          .withdraw(new anchor.BN(999_999_999_999)) // Way more than available
+        await setExitFee(500, 7 * 86_400, 30 * 86_400);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidFeeConfig");
+      }
+      await setExitFee(0, 0, 0);
+    });
+
+    it("should withdraw through a session key within its allowance", async () => {
+      const hotKey = Keypair.generate();
+      const [sessionPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("session"),
+          vaultPda.toBuffer(),
+          owner.publicKey.toBuffer(),
+          hotKey.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+      const expiresAt = Math.floor(Date.now() / 1000) + 3600;
+
+      await program.methods
+        .createSession(hotKey.publicKey, new anchor.BN(2_000_000), new anchor.BN(expiresAt))
+        .accounts({
+          user: owner.publicKey,
+          vault: vaultPda,
+          shareMint: shareMintPda,
+          userShareAccount: userShareAccount,
+          session: sessionPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const sessionWithdraw = (shares: number) =>
+        program.methods
+          .withdrawWithSession(new anchor.BN(shares), 10_000, null)
           .accounts({
+            sessionKey: hotKey.publicKey,
             user: owner.publicKey,
             vault: vaultPda,
             vaultTokenAccount: vaultTokenPda,
             shareMint: shareMintPda,
             userTokenAccount: userTokenAccount,
             userShareAccount: userShareAccount,
+            position: positionPda,
+            rewards: null,
+            session: sessionPda,
             tokenProgram: TOKEN_PROGRAM_ID,
           })
+          .signers([hotKey])
           .rpc();
+
+      await sessionWithdraw(1_000_000);
+      const session = await program.account.session.fetch(sessionPda);
+      expect(session.spent.toNumber()).to.equal(1_000_000);
+
+      try {
+        await sessionWithdraw(1_500_000);
         expect.fail("Should have thrown an error");
       } catch (err: any) {
-        expect(err.error.errorCode.code).to.equal("InsufficientShares");
+        expect(err.error.errorCode.code).to.equal("SessionLimitExceeded");
       }
-    });
-  });
 
-  describe("admin operations", () => {
-    it("should pause the vault", async () => {
       await program.methods
-        .pause()
+        .revokeSession()
         .accounts({
-          owner: owner.publicKey,
+          user: owner.publicKey,
           vault: vaultPda,
+          userShareAccount: userShareAccount,
+          session: sessionPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .rpc();
-
-      const vaultState = await program.account.vaultState.fetch(vaultPda);
-      expect(vaultState.paused).to.equal(true);
+      const shareAccount = await getAccount(provider.connection, userShareAccount);
+      expect(shareAccount.delegate).to.be.null;
     });
 
-    it("should reject deposits when paused", async () => {
+    it("should reject withdrawal with insufficient shares", async () => {
       try {
         await program.methods
-          .deposit(new anchor.BN(1_000_000))
+          .withdraw(new anchor.BN(999_999_999_999), 10_000, null) // Way more than available
           .accounts({
             user: owner.publicKey,
             vault: vaultPda,
             vaultTokenAccount: vaultTokenPda,
             shareMint: shareMintPda,
             userTokenAccount: userTokenAccount,
+            recipient: null,
             userShareAccount: userShareAccount,
+            position: positionPda,
+            rewards: null,
             tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
           })
           .rpc();
         expect.fail("Should have thrown an error");
       } catch (err: any) {
-        expect(err.error.errorCode.code).to.equal("VaultPaused");
+        expect(err.error.errorCode.code).to.equal("InsufficientShares");
       }
     });
 
-    it("should unpause the vault", async () => {
-      await program.methods
-        .unpause()
-        .accounts({
-          owner: owner.publicKey,
-          vault: vaultPda,
-        })
-        .rpc();
+    it("should reject a withdrawal in the same slot as a deposit", async () => {
+      const depositIx = await program.methods
+        .deposit(new anchor.BN(1_000_000), null)
+        .accounts(withdrawAccounts())
+        .instruction();
 
-      const vaultState = await program.account.vaultState.fetch(vaultPda);
-      expect(vaultState.paused).to.equal(false);
+      try {
+        await program.methods
+          .withdraw(new anchor.BN(1_000_000), 10_000, null)
+          .accounts(withdrawAccounts())
+          .preInstructions([depositIx])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("WithdrawTooSoon");
+      }
     });
 
-    it("should add a manager", async () => {
-      const manager = Keypair.generate();
-
-      await program.methods
-        .addManager(manager.publicKey)
-        .accounts({
-          owner: owner.publicKey,
-          vault: vaultPda,
-        })
-        .rpc();
-
-      const vaultState = await program.account.vaultState.fetch(vaultPda);
-      expect(vaultState.managerCount).to.equal(1);
-      expect(vaultState.managers[0].toString()).to.equal(
-        manager.publicKey.toString()
+    it("should reject a same-slot withdrawal of shares moved to a fresh wallet", async () => {
+      const fresh = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(fresh.publicKey, 1_000_000_000);
+      await provider.connection.confirmTransaction(sig);
+      const freshTokenAccount = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        mint,
+        fresh.publicKey
+      );
+      const freshShareAccount = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        shareMintPda,
+        fresh.publicKey
+      );
+      const [freshPosition] = PublicKey.findProgramAddressSync(
+        [Buffer.from("position"), vaultPda.toBuffer(), fresh.publicKey.toBuffer()],
+        program.programId
       );
-    });
-
-    it("should update config", async () => {
-      await program.methods
-        .updateConfig({
-          depositCap: new anchor.BN(5_000_000_000_000),
-          minDeposit: new anchor.BN(500_000),
-          managementFeeBps: 100,
-          performanceFeeBps: 1500,
-        })
-        .accounts({
-          owner: owner.publicKey,
-          vault: vaultPda,
-        })
-        .rpc();
-
-      const vaultState = await program.account.vaultState.fetch(vaultPda);
-      expect(vaultState.depositCap.toNumber()).to.equal(5_000_000_000_000);
-      expect(vaultState.minDeposit.toNumber()).to.equal(500_000);
-      expect(vaultState.managementFeeBps).to.equal(100);
-      expect(vaultState.performanceFeeBps).to.equal(1500);
-    });
-
-    it("should reject non-owner admin operations", async () => {
-      const attacker = Keypair.generate();
 
-      // Airdrop SOL to attacker
-      const sig = await provider.connection.requestAirdrop(
-        attacker.publicKey,
-        1_000_000_000
+      const depositIx = await program.methods
+        .deposit(new anchor.BN(1_000_000), null)
+        .accounts(withdrawAccounts())
+        .instruction();
+      const transferIx = createTransferInstruction(
+        userShareAccount,
+        freshShareAccount,
+        owner.publicKey,
+        1_000_000
       );
-      await provider.connection.confirmTransaction(sig);
 
       try {
         await program.methods
-          .pause()
+          .withdraw(new anchor.BN(1_000_000), 10_000, null)
           .accounts({
-            owner: attacker.publicKey,
-            vault: vaultPda,
+            ...withdrawAccounts(),
+            user: fresh.publicKey,
+            userTokenAccount: freshTokenAccount,
+            userShareAccount: freshShareAccount,
+            position: freshPosition,
           })
-          .signers([attacker])
+          .preInstructions([depositIx, transferIx])
+          .signers([fresh])
           .rpc();
         expect.fail("Should have thrown an error");
       } catch (err: any) {
-        // Should fail due to has_one = owner constraint
-        expect(err).to.exist;
+        expect(err.error.errorCode.code).to.equal("WithdrawTooSoon");
       }
     });
   });
 
-  describe("update_nav", () => {
-    it("should allow manager to update NAV", async () => {
-      const vaultStateBefore = await program.account.vaultState.fetch(vaultPda);
-      const manager = Keypair.generate();
+  describe("deposit_with_nonce", () => {
+    it("should deposit once per nonce and reject a replay", async () => {
+      const depositWithNonce = (nonce: number) =>
+        program.methods
+          .depositWithNonce(new anchor.BN(1_000_000), null, new anchor.BN(nonce))
+          .accounts({
+            deposit: {
+              user: owner.publicKey,
+              vault: vaultPda,
+              vaultTokenAccount: vaultTokenPda,
+              shareMint: shareMintPda,
+              userTokenAccount: userTokenAccount,
+              userShareAccount: userShareAccount,
+              position: positionPda,
+              rewards: null,
+              oracle: null,
+              marketShareReserve: null,
+              marketAssetReserve: null,
+              gatewayToken: null,
+              walletFlag: walletFlagPda,
+              tokenProgram: TOKEN_PROGRAM_ID,
+              systemProgram: SystemProgram.programId,
+            },
+          })
+          .rpc();
 
-      // First add this specific manager
-      await program.methods
-        .addManager(manager.publicKey)
-        .accounts({
-          owner: owner.publicKey,
+      const before = await program.account.vaultState.fetch(vaultPda);
+      const sharesBefore = (await getAccount(provider.connection, userShareAccount)).amount;
+      await depositWithNonce(1);
+      const sharesAfter = (await getAccount(provider.connection, userShareAccount)).amount;
+      const after = await program.account.vaultState.fetch(vaultPda);
+      expect(after.totalAssets.sub(before.totalAssets).toNumber()).to.equal(1_000_000);
+      expect(Number(sharesAfter - sharesBefore)).to.equal(
+        after.totalShares.sub(before.totalShares).toNumber()
+      );
+      const position = await program.account.userPosition.fetch(positionPda);
+      expect(position.depositNonce.toNumber()).to.equal(1);
+
+      // A retried transaction carrying the same nonce deposits nothing
+      try {
+        await depositWithNonce(1);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("DuplicateDepositNonce");
+      }
+      const sharesReplayed = (await getAccount(provider.connection, userShareAccount)).amount;
+      expect(sharesReplayed).to.equal(sharesAfter);
+    });
+  });
+
+  describe("close_position", () => {
+    it("should reject closing a position that still holds shares", async () => {
+      try {
+        await program.methods
+          .closePosition(false)
+          .accounts({
+            user: owner.publicKey,
+            vault: vaultPda,
+            shareMint: shareMintPda,
+            position: positionPda,
+            userShareAccount: userShareAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("PositionNotEmpty");
+      }
+    });
+  });
+
+  describe("donate", () => {
+    it("should raise total assets without minting shares", async () => {
+      const before = await program.account.vaultState.fetch(vaultPda);
+
+      await program.methods
+        .donate(new anchor.BN(10_000_000))
+        .accounts({
+          donor: owner.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: vaultTokenPda,
+          donorTokenAccount: userTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const after = await program.account.vaultState.fetch(vaultPda);
+      expect(after.totalAssets.toNumber()).to.equal(before.totalAssets.toNumber() + 10_000_000);
+      expect(after.totalShares.toNumber()).to.equal(before.totalShares.toNumber());
+    });
+  });
+
+  describe("liquidity reserve", () => {
+    const withdraw = (shares: number) =>
+      program.methods
+        .withdraw(new anchor.BN(shares), 10_000, null)
+        .accounts({
+          user: owner.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: vaultTokenPda,
+          shareMint: shareMintPda,
+          userTokenAccount: userTokenAccount,
+          recipient: null,
+          userShareAccount: userShareAccount,
+          position: positionPda,
+          rewards: null,
+          oracle: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    const setReserve = (reserveRatioBps: number, smallWithdrawalThreshold: number) =>
+      program.methods
+        .setLiquidityReserve(reserveRatioBps, new anchor.BN(smallWithdrawalThreshold))
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+        })
+        .rpc();
+
+    before(async () => {
+      // Hold back half the vault for withdrawals of at most 10 USDC
+      await setReserve(5_000, 10_000_000);
+    });
+
+    after(async () => {
+      await setReserve(0, 0);
+    });
+
+    it("should keep large withdrawals out of the reserve", async () => {
+      try {
+        await withdraw(600_000_000);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("LiquidityReserved");
+      }
+    });
+
+    it("should hold a full exit to the same reserve limit", async () => {
+      try {
+        await program.methods
+          .withdrawAll(10_000, null)
+          .accounts({
+            user: owner.publicKey,
+            vault: vaultPda,
+            vaultTokenAccount: vaultTokenPda,
+            shareMint: shareMintPda,
+            userTokenAccount: userTokenAccount,
+            recipient: null,
+            userShareAccount: userShareAccount,
+            position: positionPda,
+            rewards: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("LiquidityReserved");
+      }
+    });
+
+    it("should serve small withdrawals from the reserve", async () => {
+      const before = await program.account.vaultState.fetch(vaultPda);
+
+      await withdraw(5_000_000);
+
+      const after = await program.account.vaultState.fetch(vaultPda);
+      expect(after.totalShares.toNumber()).to.equal(before.totalShares.toNumber() - 5_000_000);
+    });
+  });
+
+  describe("outflow limit", () => {
+    const setOutflowLimit = (maxAssets: number, maxBps: number) =>
+      program.methods
+        .setOutflowLimit(new anchor.BN(maxAssets), maxBps)
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+        })
+        .rpc();
+
+    after(async () => {
+      await setOutflowLimit(0, 0);
+    });
+
+    const withdrawShares = (shares: number) =>
+      program.methods
+        .withdraw(new anchor.BN(shares), 10_000, null)
+        .accounts({
+          user: owner.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: vaultTokenPda,
+          shareMint: shareMintPda,
+          userTokenAccount: userTokenAccount,
+          recipient: null,
+          userShareAccount: userShareAccount,
+          position: positionPda,
+          rewards: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    it("should reject withdrawals beyond the window's outflow limit", async () => {
+      await setOutflowLimit(1_000_000, 0);
+
+      try {
+        await withdrawShares(5_000_000);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("OutflowLimitReached");
+      }
+    });
+
+    it("should reject withdrawals beyond the window's share of total assets", async () => {
+      // 0.01% of total assets at the window's start
+      await setOutflowLimit(0, 1);
+      const vault = await program.account.vaultState.fetch(vaultPda);
+      const limit = vault.totalAssets.toNumber() / 10_000;
+
+      try {
+        await withdrawShares(Math.ceil(limit) * 2);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("OutflowLimitReached");
+      }
+    });
+
+    it("should reject a relative limit above 100%", async () => {
+      try {
+        await setOutflowLimit(0, 10_001);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidConfig");
+      }
+    });
+  });
+
+  describe("inflow limit", () => {
+    const setInflowLimit = (maxAssets: number) =>
+      program.methods
+        .setInflowLimit(new anchor.BN(maxAssets))
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+        })
+        .rpc();
+
+    after(async () => {
+      await setInflowLimit(0);
+    });
+
+    it("should reject deposits beyond the hour's inflow limit", async () => {
+      await setInflowLimit(1_000_000);
+
+      try {
+        await program.methods
+          .deposit(new anchor.BN(2_000_000), null)
+          .accounts({
+            user: owner.publicKey,
+            vault: vaultPda,
+            vaultTokenAccount: vaultTokenPda,
+            shareMint: shareMintPda,
+            userTokenAccount: userTokenAccount,
+            userShareAccount: userShareAccount,
+            position: positionPda,
+            rewards: null,
+            oracle: null,
+            marketShareReserve: null,
+            marketAssetReserve: null,
+            gatewayToken: null,
+            walletFlag: walletFlagPda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InflowRateLimited");
+        expect(err.logs.some((log: string) => log.includes("retry after"))).to.be.true;
+      }
+    });
+  });
+
+  describe("liquidity fee curve", () => {
+    const setLiquidityFeeCurve = (kinkBps: number, maxEntryBps: number, maxExitBps: number) =>
+      program.methods
+        .setLiquidityFeeCurve(kinkBps, maxEntryBps, maxExitBps)
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+        })
+        .rpc();
+
+    after(async () => {
+      await setLiquidityFeeCurve(0, 0, 0);
+    });
+
+    it("should charge the full entry fee while liquidity is above the kink", async () => {
+      // The vault holds everything liquid, above a 50% kink
+      await setLiquidityFeeCurve(5_000, 100, 200);
+      const before = await program.account.vaultState.fetch(vaultPda);
+      const sharesBefore = await getAccount(provider.connection, userShareAccount);
+
+      await program.methods
+        .deposit(new anchor.BN(1_000_000), null)
+        .accounts({
+          user: owner.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: vaultTokenPda,
+          shareMint: shareMintPda,
+          userTokenAccount: userTokenAccount,
+          userShareAccount: userShareAccount,
+          position: positionPda,
+          rewards: null,
+          oracle: null,
+          marketShareReserve: null,
+          marketAssetReserve: null,
+          gatewayToken: null,
+          walletFlag: walletFlagPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      // Shares are minted on the deposit net of 1%; all of it joins the assets
+      const after = await program.account.vaultState.fetch(vaultPda);
+      expect(after.totalAssets.sub(before.totalAssets).toNumber()).to.equal(1_000_000);
+      const sharesAfter = await getAccount(provider.connection, userShareAccount);
+      const expected = before.totalShares.muln(990_000).div(before.totalAssets);
+      expect(Number(sharesAfter.amount - sharesBefore.amount)).to.equal(expected.toNumber());
+    });
+
+    it("should reject curve fees above the maximum", async () => {
+      try {
+        await setLiquidityFeeCurve(5_000, 0, 201);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidFeeConfig");
+      }
+    });
+  });
+
+  describe("strategy disclosure", () => {
+    const [disclosurePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("disclosure"), vaultPda.toBuffer()],
+      program.programId
+    );
+    const documentHash = (text: string) =>
+      Array.from(createHash("sha256").update(text).digest());
+
+    it("should only replace the disclosure after its timelock", async () => {
+      await program.methods
+        .initDisclosure(documentHash("strategy v1"), "https://example.com/strategy-v1.pdf")
+        .accounts({ owner: owner.publicKey, vault: vaultPda, disclosure: disclosurePda })
+        .rpc();
+
+      await program.methods
+        .proposeDisclosure(documentHash("strategy v2"), "https://example.com/strategy-v2.pdf")
+        .accounts({ owner: owner.publicKey, vault: vaultPda, disclosure: disclosurePda })
+        .rpc();
+
+      try {
+        await program.methods
+          .applyDisclosure()
+          .accounts({ vault: vaultPda, disclosure: disclosurePda })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("DisclosureTimelockActive");
+      }
+
+      // The document in force is unchanged until the proposal is applied
+      const disclosure = await program.account.disclosure.fetch(disclosurePda);
+      expect(disclosure.contentHash).to.deep.equal(documentHash("strategy v1"));
+      expect(disclosure.uri).to.equal("https://example.com/strategy-v1.pdf");
+      expect(disclosure.pendingContentHash).to.deep.equal(documentHash("strategy v2"));
+      expect(disclosure.pendingEffectiveAt.toNumber()).to.be.greaterThan(0);
+    });
+  });
+
+  describe("admin operations", () => {
+    it("should pause the vault", async () => {
+      await program.methods
+        .pause()
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+        })
+        .rpc();
+
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.status).to.equal(2); // Paused
+    });
+
+    it("should reject deposits when paused", async () => {
+      try {
+        await program.methods
+          .deposit(new anchor.BN(1_000_000), null)
+          .accounts({
+            user: owner.publicKey,
+            vault: vaultPda,
+            vaultTokenAccount: vaultTokenPda,
+            shareMint: shareMintPda,
+            userTokenAccount: userTokenAccount,
+            userShareAccount: userShareAccount,
+            position: positionPda,
+            rewards: null,
+            oracle: null,
+            marketShareReserve: null,
+            marketAssetReserve: null,
+            gatewayToken: null,
+            walletFlag: walletFlagPda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("VaultPaused");
+      }
+    });
+
+    it("should unpause the vault", async () => {
+      await program.methods
+        .unpause()
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+        })
+        .rpc();
+
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.status).to.equal(1); // Active
+    });
+
+    it("should reject unpausing a vault that is not paused", async () => {
+      try {
+        await program.methods
+          .unpause()
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidStatusTransition");
+      }
+    });
+
+    it("should reject deposits against a missing or non-Pyth price oracle", async () => {
+      const setPriceOracle = (oracle: PublicKey) =>
+        program.methods
+          .setPriceOracle(oracle, 100)
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            feedRegistry: feedRegistryPda,
+          })
+          .rpc();
+
+      // Only the feed the protocol admin registered for the asset is accepted
+      try {
+        await setPriceOracle(userTokenAccount);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("FeedNotRegistered");
+      }
+      await program.methods
+        .setPriceFeed(mint, userTokenAccount, new anchor.BN(60))
+        .accounts({
+          admin: owner.publicKey,
+          protocolConfig: protocolConfigPda,
+          feedRegistry: feedRegistryPda,
+        })
+        .rpc();
+      const deposit = (oracle: PublicKey | null) =>
+        program.methods
+          .deposit(new anchor.BN(1_000_000), null)
+          .accounts({
+            user: owner.publicKey,
+            vault: vaultPda,
+            vaultTokenAccount: vaultTokenPda,
+            shareMint: shareMintPda,
+            userTokenAccount: userTokenAccount,
+            userShareAccount: userShareAccount,
+            position: positionPda,
+            rewards: null,
+            oracle,
+            marketShareReserve: null,
+            marketAssetReserve: null,
+            gatewayToken: null,
+            walletFlag: walletFlagPda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+
+      // Any account not owned by the Pyth receiver is rejected as an oracle
+      await setPriceOracle(userTokenAccount);
+      for (const oracle of [null, userTokenAccount]) {
+        try {
+          await deposit(oracle);
+          expect.fail("Should have thrown an error");
+        } catch (err: any) {
+          expect(err.error.errorCode.code).to.equal("InvalidOracle");
+        }
+      }
+
+      await setPriceOracle(PublicKey.default);
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.priceOracle.toString()).to.equal(PublicKey.default.toString());
+      expect(vaultState.maxOracleStaleness.toNumber()).to.equal(0);
+    });
+
+    it("should only benchmark performance fees against a registered Pyth feed", async () => {
+      const benchmarkMint = Keypair.generate().publicKey;
+      const setBenchmark = (benchmark: PublicKey) =>
+        program.methods
+          .setBenchmark(benchmark)
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            feedRegistry: feedRegistryPda,
+            benchmarkOracle: userTokenAccount,
+          })
+          .rpc();
+
+      try {
+        await setBenchmark(benchmarkMint);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("FeedNotRegistered");
+      }
+      await program.methods
+        .setPriceFeed(benchmarkMint, userTokenAccount, new anchor.BN(60))
+        .accounts({
+          admin: owner.publicKey,
+          protocolConfig: protocolConfigPda,
+          feedRegistry: feedRegistryPda,
+        })
+        .rpc();
+      // The registered feed must still be a Pyth price account
+      try {
+        await setBenchmark(benchmarkMint);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidOracle");
+      }
+
+      await setBenchmark(PublicKey.default);
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.benchmarkOracle.toString()).to.equal(PublicKey.default.toString());
+      expect(vaultState.benchmarkLevel.toNumber()).to.equal(0);
+      try {
+        await program.methods
+          .syncBenchmark()
+          .accounts({ vault: vaultPda, benchmarkOracle: PublicKey.default })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidOracle");
+      }
+    });
+
+    it("should require the market pool reserves once a market guard is set", async () => {
+      const setMarketPool = (shareReserve: PublicKey | null, assetReserve: PublicKey | null) =>
+        program.methods
+          .setMarketPool(500)
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            shareMint: shareMintPda,
+            marketShareReserve: shareReserve,
+            marketAssetReserve: assetReserve,
+          })
+          .rpc();
+
+      try {
+        await setMarketPool(userShareAccount, null);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidMarketPool");
+      }
+
+      // Any share and underlying token accounts stand in for a pool's reserves
+      await setMarketPool(userShareAccount, userTokenAccount);
+      try {
+        await program.methods
+          .deposit(new anchor.BN(1_000_000), null)
+          .accounts({
+            user: owner.publicKey,
+            vault: vaultPda,
+            vaultTokenAccount: vaultTokenPda,
+            shareMint: shareMintPda,
+            userTokenAccount: userTokenAccount,
+            userShareAccount: userShareAccount,
+            position: positionPda,
+            rewards: null,
+            oracle: null,
+            marketShareReserve: null,
+            marketAssetReserve: null,
+            gatewayToken: null,
+            walletFlag: walletFlagPda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidMarketPool");
+      }
+
+      await program.methods
+        .signalMarketDiscount()
+        .accounts({
+          vault: vaultPda,
+          marketShareReserve: userShareAccount,
+          marketAssetReserve: userTokenAccount,
+        })
+        .rpc();
+
+      await setMarketPool(null, null);
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.marketShareReserve.toString()).to.equal(PublicKey.default.toString());
+    });
+
+    it("should cap outstanding accrued fees at a share of total assets", async () => {
+      const setMaxAccruedFee = (bps: number) =>
+        program.methods
+          .setMaxAccruedFee(bps)
+          .accounts({ owner: owner.publicKey, vault: vaultPda })
+          .rpc();
+
+      try {
+        await setMaxAccruedFee(10_001);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidConfig");
+      }
+
+      await setMaxAccruedFee(500);
+      let vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.maxAccruedFeeBps).to.equal(500);
+
+      await setMaxAccruedFee(0);
+      vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.maxAccruedFeeBps).to.equal(0);
+    });
+
+    it("should schedule and clear a fee holiday", async () => {
+      const scheduleFeeHoliday = (start: number, end: number) =>
+        program.methods
+          .scheduleFeeHoliday(new anchor.BN(start), new anchor.BN(end))
+          .accounts({ owner: owner.publicKey, vault: vaultPda })
+          .rpc();
+      const now = Math.floor(Date.now() / 1000);
+
+      // Holidays cannot be backdated
+      try {
+        await scheduleFeeHoliday(now - 3600, now + 3600);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidConfig");
+      }
+
+      await scheduleFeeHoliday(now + 86400, now + 2 * 86400);
+      let vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.feeHolidayStart.toNumber()).to.equal(now + 86400);
+      expect(vaultState.feeHolidayEnd.toNumber()).to.equal(now + 2 * 86400);
+
+      await scheduleFeeHoliday(0, 0);
+      vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.feeHolidayEnd.toNumber()).to.equal(0);
+    });
+
+    it("should add a manager", async () => {
+      const manager = Keypair.generate();
+
+      await program.methods
+        .addManager(manager.publicKey)
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          managerProfile: managerProfilePda(manager.publicKey),
+          managerBond: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.managerCount).to.equal(1);
+      expect(vaultState.managers[0].toString()).to.equal(
+        manager.publicKey.toString()
+      );
+    });
+
+    it("should record a slashing on the removed manager's profile", async () => {
+      const manager = Keypair.generate();
+      const managerProfile = managerProfilePda(manager.publicKey);
+
+      await program.methods
+        .addManager(manager.publicKey)
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          managerProfile,
+          managerBond: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      await program.methods
+        .slashManager(manager.publicKey)
+        .accounts({ owner: owner.publicKey, vault: vaultPda, managerProfile })
+        .rpc();
+
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.managerCount).to.equal(1);
+      const profile = await program.account.managerProfile.fetch(managerProfile);
+      expect(profile.vaultsManaged).to.equal(1);
+      expect(profile.slashingIncidents).to.equal(1);
+    });
+
+    it("should require a bond to add a manager and slash it after the appeal period", async () => {
+      const manager = Keypair.generate();
+      const managerProfile = managerProfilePda(manager.publicKey);
+      const [bondPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("manager_bond"), vaultPda.toBuffer(), manager.publicKey.toBuffer()],
+        program.programId
+      );
+      const [bondTokenPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("manager_bond_token"), bondPda.toBuffer()],
+        program.programId
+      );
+      const setManagerBond = (minBond: number) =>
+        program.methods
+          .setManagerBond(new anchor.BN(minBond), new anchor.BN(0))
+          .accounts({ owner: owner.publicKey, vault: vaultPda })
+          .rpc();
+      const addManager = (managerBond: PublicKey | null) =>
+        program.methods
+          .addManager(manager.publicKey)
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            managerProfile,
+            managerBond,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+
+      await setManagerBond(1_000_000);
+      try {
+        await addManager(null);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ManagerBondTooLow");
+      }
+
+      const sig = await provider.connection.requestAirdrop(manager.publicKey, 1_000_000_000);
+      await provider.connection.confirmTransaction(sig);
+      const managerTokenAccount = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        mint,
+        manager.publicKey
+      );
+      await mintTo(
+        provider.connection,
+        (owner as any).payer,
+        mint,
+        managerTokenAccount,
+        owner.publicKey,
+        1_000_000
+      );
+      await program.methods
+        .postBond(new anchor.BN(1_000_000))
+        .accounts({
+          manager: manager.publicKey,
+          vault: vaultPda,
+          mint,
+          bond: bondPda,
+          bondTokenAccount: bondTokenPda,
+          managerTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([manager])
+        .rpc();
+      await addManager(bondPda);
+
+      // A proven misreport: slash half the bond into the vault
+      const before = await program.account.vaultState.fetch(vaultPda);
+      await program.methods
+        .proposeSlash(new anchor.BN(500_000), { navMisreport: {} })
+        .accounts({ owner: owner.publicKey, vault: vaultPda, bond: bondPda })
+        .rpc();
+      await program.methods
+        .executeSlash()
+        .accounts({
+          payer: owner.publicKey,
+          vault: vaultPda,
+          bond: bondPda,
+          bondTokenAccount: bondTokenPda,
+          vaultTokenAccount: vaultTokenPda,
+          managerProfile,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const after = await program.account.vaultState.fetch(vaultPda);
+      expect(after.totalAssets.sub(before.totalAssets).toNumber()).to.equal(500_000);
+      const bond = await program.account.managerBond.fetch(bondPda);
+      expect(bond.amount.toNumber()).to.equal(500_000);
+      expect(after.totalBonded.toNumber()).to.equal(500_000);
+      expect(bond.pendingSlash.toNumber()).to.equal(0);
+      const profile = await program.account.managerProfile.fetch(managerProfile);
+      expect(profile.slashingIncidents).to.equal(1);
+
+      await program.methods
+        .removeManager(manager.publicKey)
+        .accounts({ owner: owner.publicKey, vault: vaultPda })
+        .rpc();
+      await setManagerBond(0);
+    });
+
+    it("should reject manager proposals while voting is disabled", async () => {
+      const manager = Keypair.generate();
+      const [proposalPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("manager_proposal"), vaultPda.toBuffer(), manager.publicKey.toBuffer()],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .proposeManager(manager.publicKey)
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            proposal: proposalPda,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ManagerVotingDisabled");
+      }
+    });
+
+    it("should update config", async () => {
+      await program.methods
+        .updateConfig({
+          depositCap: new anchor.BN(5_000_000_000_000),
+          minDeposit: new anchor.BN(500_000),
+          managementFeeBps: 100,
+          performanceFeeBps: 1500,
+        })
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          protocolConfig: null,
+          feeReport: feeReportPda,
+        })
+        .rpc();
+
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.depositCap.toNumber()).to.equal(5_000_000_000_000);
+      expect(vaultState.minDeposit.toNumber()).to.equal(500_000);
+      expect(vaultState.managementFeeBps).to.equal(100);
+      expect(vaultState.performanceFeeBps).to.equal(1500);
+    });
+
+    it("should keep existing holders on the old fees through the grace period", async () => {
+      const updateFees = (managementFeeBps: number, performanceFeeBps: number) =>
+        program.methods
+          .updateConfig({
+            depositCap: new anchor.BN(5_000_000_000_000),
+            minDeposit: new anchor.BN(500_000),
+            managementFeeBps,
+            performanceFeeBps,
+          })
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            protocolConfig: null,
+            feeReport: feeReportPda,
+          })
+          .rpc();
+
+      await updateFees(300, 1500);
+      let vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.feeEpoch.toNumber()).to.equal(1);
+      expect(vaultState.grandfatheredShares.toString()).to.equal(
+        vaultState.totalShares.toString()
+      );
+      expect(vaultState.grandfatheredManagementFeeBps).to.equal(100);
+      expect(vaultState.feeGraceEndsAt.toNumber()).to.be.greaterThan(Date.now() / 1000);
+
+      // No second increase until the grace period ends
+      try {
+        await updateFees(300, 2000);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("FeeGracePeriodActive");
+      }
+
+      // Lowering fees stays possible
+      await updateFees(100, 1500);
+      vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.managementFeeBps).to.equal(100);
+      expect(vaultState.feeEpoch.toNumber()).to.equal(1);
+    });
+
+    it("should cap deposits along the deposit cap schedule", async () => {
+      const scheduleDepositCap = (startCap: anchor.BN, increment: number, interval: number) =>
+        program.methods
+          .scheduleDepositCap(startCap, new anchor.BN(increment), new anchor.BN(interval))
+          .accounts({ owner: owner.publicKey, vault: vaultPda })
+          .rpc();
+      const { totalAssets } = await program.account.vaultState.fetch(vaultPda);
+
+      // The ramp starts at the current TVL, so the first step admits nothing more
+      await scheduleDepositCap(totalAssets, 1_000_000_000, 3_600);
+      try {
+        await program.methods
+          .deposit(new anchor.BN(1_000_000), null)
+          .accounts({
+            user: owner.publicKey,
+            vault: vaultPda,
+            vaultTokenAccount: vaultTokenPda,
+            shareMint: shareMintPda,
+            userTokenAccount: userTokenAccount,
+            userShareAccount: userShareAccount,
+            position: positionPda,
+            rewards: null,
+            oracle: null,
+            marketShareReserve: null,
+            marketAssetReserve: null,
+            gatewayToken: null,
+            walletFlag: walletFlagPda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("DepositCapExceeded");
+      }
+
+      await scheduleDepositCap(new anchor.BN(0), 0, 0);
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.capScheduleInterval.toNumber()).to.equal(0);
+    });
+
+    it("should reject deposits outside the flow window", async () => {
+      const setFlowWindow = (period: number, open: number, close: number) =>
+        program.methods
+          .setFlowWindow(new anchor.BN(period), new anchor.BN(open), new anchor.BN(close))
+          .accounts({ owner: owner.publicKey, vault: vaultPda })
+          .rpc();
+
+      // A daily two-hour window opening an hour from now
+      const day = 86_400;
+      const open = (Math.floor(Date.now() / 1000) + 3_600) % day;
+      await setFlowWindow(day, open, (open + 7_200) % day);
+      try {
+        await program.methods
+          .deposit(new anchor.BN(1_000_000), null)
+          .accounts({
+            user: owner.publicKey,
+            vault: vaultPda,
+            vaultTokenAccount: vaultTokenPda,
+            shareMint: shareMintPda,
+            userTokenAccount: userTokenAccount,
+            userShareAccount: userShareAccount,
+            position: positionPda,
+            rewards: null,
+            oracle: null,
+            marketShareReserve: null,
+            marketAssetReserve: null,
+            gatewayToken: null,
+            walletFlag: walletFlagPda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("FlowWindowClosed");
+      }
+
+      await setFlowWindow(0, 0, 0);
+    });
+
+    it("should require a gatekeeper pass once the vault is permissioned", async () => {
+      const setGatekeeperNetwork = (network: PublicKey) =>
+        program.methods
+          .setGatekeeperNetwork(network)
+          .accounts({ owner: owner.publicKey, vault: vaultPda })
+          .rpc();
+      const deposit = (gatewayToken: PublicKey | null) =>
+        program.methods
+          .deposit(new anchor.BN(1_000_000), null)
+          .accounts({
+            user: owner.publicKey,
+            vault: vaultPda,
+            vaultTokenAccount: vaultTokenPda,
+            shareMint: shareMintPda,
+            userTokenAccount: userTokenAccount,
+            userShareAccount: userShareAccount,
+            position: positionPda,
+            rewards: null,
+            oracle: null,
+            marketShareReserve: null,
+            marketAssetReserve: null,
+            gatewayToken,
+            walletFlag: walletFlagPda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+
+      // Without a pass, or with an account the Civic gateway does not own
+      await setGatekeeperNetwork(Keypair.generate().publicKey);
+      for (const gatewayToken of [null, userTokenAccount]) {
+        try {
+          await deposit(gatewayToken);
+          expect.fail("Should have thrown an error");
+        } catch (err: any) {
+          expect(err.error.errorCode.code).to.equal("CredentialRequired");
+        }
+      }
+
+      await setGatekeeperNetwork(PublicKey.default);
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.gatekeeperNetwork.toString()).to.equal(PublicKey.default.toString());
+    });
+
+    it("should reject deposits from flagged wallets with the flag's error code", async () => {
+      const deposit = () =>
+        program.methods
+          .deposit(new anchor.BN(1_000_000), null)
+          .accounts({
+            user: owner.publicKey,
+            vault: vaultPda,
+            vaultTokenAccount: vaultTokenPda,
+            shareMint: shareMintPda,
+            userTokenAccount: userTokenAccount,
+            userShareAccount: userShareAccount,
+            position: positionPda,
+            rewards: null,
+            oracle: null,
+            marketShareReserve: null,
+            marketAssetReserve: null,
+            gatewayToken: null,
+            walletFlag: walletFlagPda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+
+      const cases: [object, string][] = [
+        [{ sanctioned: {} }, "WalletSanctioned"],
+        [{ geofenced: {} }, "WalletGeofenced"],
+      ];
+      for (const [flag, code] of cases) {
+        await program.methods
+          .flagWallet(owner.publicKey, flag as any)
+          .accounts({
+            admin: owner.publicKey,
+            protocolConfig: protocolConfigPda,
+            walletFlag: walletFlagPda,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        try {
+          await deposit();
+          expect.fail("Should have thrown an error");
+        } catch (err: any) {
+          expect(err.error.errorCode.code).to.equal(code);
+        }
+      }
+
+      await program.methods
+        .unflagWallet()
+        .accounts({
+          admin: owner.publicKey,
+          protocolConfig: protocolConfigPda,
+          walletFlag: walletFlagPda,
+        })
+        .rpc();
+      expect(await provider.connection.getAccountInfo(walletFlagPda)).to.be.null;
+      await deposit();
+    });
+
+    it("should reject non-owner admin operations", async () => {
+      const attacker = Keypair.generate();
+
+      // Airdrop SOL to attacker
+      const sig = await provider.connection.requestAirdrop(
+        attacker.publicKey,
+        1_000_000_000
+      );
+      await provider.connection.confirmTransaction(sig);
+
+      try {
+        await program.methods
+          .pause()
+          .accounts({
+            owner: attacker.publicKey,
+            vault: vaultPda,
+          })
+          .signers([attacker])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        // Should fail due to has_one = owner constraint
+        expect(err).to.exist;
+      }
+    });
+
+    it("should not let manager voting be switched off or made too short", async () => {
+      const [voteEscrowPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vote_escrow"), vaultPda.toBuffer()],
+        program.programId
+      );
+      for (const period of [0, 60]) {
+        try {
+          await program.methods
+            .setManagerVotePeriod(new anchor.BN(period))
+            .accounts({
+              owner: owner.publicKey,
+              vault: vaultPda,
+              shareMint: shareMintPda,
+              voteEscrow: voteEscrowPda,
+            })
+            .rpc();
+          expect.fail("Should have thrown an error");
+        } catch (err: any) {
+          expect(err.error.errorCode.code).to.equal("ManagerVotePeriodTooShort");
+        }
+      }
+    });
+
+    it("should page through the audit log of admin actions", async () => {
+      const entries: any[] = [];
+      for (;;) {
+        const page = await program.methods
+          .getAuditLog(entries.length)
+          .accounts({ vault: vaultPda, auditLog: auditLogPda })
+          .view();
+        if (page.length === 0) break;
+        entries.push(...page);
+      }
+
+      const auditLog = await program.account.auditLog.fetch(auditLogPda);
+      expect(entries.length).to.equal(auditLog.count.toNumber());
+      // The attacker's rejected pause left no trace
+      for (const entry of entries) {
+        expect(entry.actor.toString()).to.equal(owner.publicKey.toString());
+      }
+      const pauseAt = entries.findIndex((entry) => entry.action.pause !== undefined);
+      expect(pauseAt).to.be.greaterThanOrEqual(0);
+      expect(entries[pauseAt + 1].action.unpause).to.not.be.undefined;
+      expect(entries[pauseAt + 1].timestamp.toNumber()).to.be.at.least(
+        entries[pauseAt].timestamp.toNumber()
+      );
+      // Fund movements are logged alongside config changes
+      expect(entries.some((entry) => entry.action.executeSlash !== undefined)).to.be.true;
+      expect(entries.some((entry) => entry.action.initDisclosure !== undefined)).to.be.true;
+    });
+  });
+
+  describe("update_nav", () => {
+    it("should allow manager to update NAV", async () => {
+      const vaultStateBefore = await program.account.vaultState.fetch(vaultPda);
+      const manager = Keypair.generate();
+
+      // First add this specific manager
+      await program.methods
+        .addManager(manager.publicKey)
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          managerProfile: managerProfilePda(manager.publicKey),
+          managerBond: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      // Airdrop SOL to manager for tx fees
+      const sig = await provider.connection.requestAirdrop(
+        manager.publicKey,
+        1_000_000_000
+      );
+      await provider.connection.confirmTransaction(sig);
+
+      // Manager updates NAV to simulate yield (10% gain)
+      const newNav = new anchor.BN(1_100_000_000); // 1100 USDC (was 1000)
+      const valuationCommitment = Array.from(Buffer.alloc(32, "valuation-run-1"));
+
+      const updateSig = await program.methods
+        .updateNav(newNav, valuationCommitment)
+        .accounts({
+          authority: manager.publicKey,
+          vault: vaultPda,
+          feeReport: feeReportPda,
+          priceHistory: priceHistoryPda,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          oracle: null,
+          managerProfile: managerProfilePda(manager.publicKey),
+          reservesVerifier: null,
+        })
+        .signers([manager])
+        .rpc({ commitment: "confirmed" });
+
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.totalAssets.toNumber()).to.equal(1_100_000_000);
+
+      // The valuation commitment is echoed in the NavUpdated event
+      const navUpdated = (await eventsOf(updateSig)).find(
+        (e) => e.data.valuationCommitment
+      );
+      expect(navUpdated!.data.valuationCommitment).to.deep.equal(
+        valuationCommitment
+      );
+
+      // The update is recorded in the price history
+      const history = await program.account.priceHistory.fetch(priceHistoryPda);
+      expect(history.count.toNumber()).to.equal(1);
+      expect(history.samples[0].totalAssets.toNumber()).to.equal(1_100_000_000);
+      expect(history.samples[0].totalShares.toString()).to.equal(
+        vaultState.totalShares.toString()
+      );
+
+      // And counted on the manager's reputation profile
+      const profile = await program.account.managerProfile.fetch(
+        managerProfilePda(manager.publicKey)
+      );
+      expect(profile.vaultsManaged).to.equal(1);
+      expect(profile.navUpdates.toNumber()).to.equal(1);
+      expect(profile.cumulativePnl.toString()).to.equal(
+        newNav.sub(vaultStateBefore.totalAssets).toString()
+      );
+    });
+
+    it("should record accrued fees in the fee report", async () => {
+      const report = await program.methods
+        .getFeeReport()
+        .accounts({
+          vault: vaultPda,
+          feeReport: feeReportPda,
+        })
+        .view();
+
+      // NAV rose above the high-water mark, so a performance fee accrued
+      expect(report.totalPerformanceFees.toNumber()).to.be.greaterThan(0);
+      expect(report.crystallizationCount.toNumber()).to.equal(0);
+    });
+
+    it("should value shares net of fees awaiting collection", async () => {
+      const value = await program.methods
+        .getShareValue()
+        .accounts({
+          vault: vaultPda,
+          shareMint: shareMintPda,
+          userShareAccount: userShareAccount,
+        })
+        .view();
+
+      expect(value.accruedFees.toNumber()).to.be.greaterThan(0);
+      expect(value.netAssets.toNumber()).to.be.lessThan(value.grossAssets.toNumber());
+    });
+
+    it("should reject NAV update from unauthorized account", async () => {
+      const unauthorized = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(
+        unauthorized.publicKey,
+        1_000_000_000
+      );
+      await provider.connection.confirmTransaction(sig);
+
+      try {
+        await program.methods
+          .updateNav(new anchor.BN(999_999_999), null)
+          .accounts({
+            authority: unauthorized.publicKey,
+            vault: vaultPda,
+            feeReport: feeReportPda,
+            priceHistory: priceHistoryPda,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            oracle: null,
+            managerProfile: null,
+            reservesVerifier: null,
+          })
+          .signers([unauthorized])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("Unauthorized");
+      }
+    });
+
+    it("should reject NAV update bundled with a deposit", async () => {
+      const depositIx = await program.methods
+        .deposit(new anchor.BN(1_000_000), null)
+        .accounts({
+          user: owner.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: vaultTokenPda,
+          shareMint: shareMintPda,
+          userTokenAccount: userTokenAccount,
+          userShareAccount: userShareAccount,
+          position: positionPda,
+          rewards: null,
+          oracle: null,
+          marketShareReserve: null,
+          marketAssetReserve: null,
+          gatewayToken: null,
+          walletFlag: walletFlagPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .instruction();
+      const { totalAssets } = await program.account.vaultState.fetch(vaultPda);
+
+      try {
+        await program.methods
+          .updateNav(totalAssets, null)
+          .accounts({
+            authority: owner.publicKey,
+            vault: vaultPda,
+            feeReport: feeReportPda,
+            priceHistory: priceHistoryPda,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            oracle: null,
+            managerProfile: null,
+            reservesVerifier: null,
+          })
+          .preInstructions([depositIx])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("NavUpdateWithUserFlow");
+      }
+    });
+
+    it("should update NAV in a batch of vaults", async () => {
+      const { totalAssets } = await program.account.vaultState.fetch(vaultPda);
+      const vaultAccounts = [
+        { pubkey: vaultPda, isWritable: true, isSigner: false },
+        { pubkey: feeReportPda, isWritable: true, isSigner: false },
+        { pubkey: priceHistoryPda, isWritable: true, isSigner: false },
+        { pubkey: auditLogPda, isWritable: true, isSigner: false },
+        { pubkey: program.programId, isWritable: false, isSigner: false },
+        { pubkey: program.programId, isWritable: false, isSigner: false },
+      ];
+      const updateNavBatch = (values: anchor.BN[]) =>
+        program.methods
+          .updateNavBatch(values, null)
+          .accounts({
+            authority: owner.publicKey,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            managerProfile: null,
+          })
+          .remainingAccounts(vaultAccounts)
+          .rpc();
+
+      // Every value needs its own group of vault accounts
+      try {
+        await updateNavBatch([totalAssets, totalAssets]);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidNavBatchAccounts");
+      }
+
+      const before = await program.account.priceHistory.fetch(priceHistoryPda);
+      await updateNavBatch([totalAssets]);
+      const after = await program.account.priceHistory.fetch(priceHistoryPda);
+      expect(after.count.toNumber()).to.equal(before.count.toNumber() + 1);
+    });
+
+    it("should require consent to withdraw below the pre-loss share price", async () => {
+      const { totalAssets } = await program.account.vaultState.fetch(vaultPda);
+      const updateNav = (nav: anchor.BN) =>
+        program.methods
+          .updateNav(nav, null)
+          .accounts({
+            authority: owner.publicKey,
+            vault: vaultPda,
+            feeReport: feeReportPda,
+            priceHistory: priceHistoryPda,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            oracle: null,
+            managerProfile: null,
+            reservesVerifier: null,
+          })
+          .rpc();
+
+      // Report a 5% loss
+      await updateNav(totalAssets.muln(95).divn(100));
+
+      try {
+        await program.methods
+          .withdraw(new anchor.BN(1_000_000), 100, null)
+          .accounts({
+            user: owner.publicKey,
+            vault: vaultPda,
+            vaultTokenAccount: vaultTokenPda,
+            shareMint: shareMintPda,
+            userTokenAccount: userTokenAccount,
+            recipient: null,
+            userShareAccount: userShareAccount,
+            position: positionPda,
+            rewards: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("LossExceedsMax");
+      }
+
+      // Recovering to the pre-loss price clears the reference
+      await updateNav(totalAssets);
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.preLossPriceQ64.every((b: number) => b === 0)).to.be.true;
+    });
+
+    it("should only execute a stop-loss once the price falls to its level", async () => {
+      const setStopLoss = (stopLossBps: number) =>
+        program.methods
+          .setStopLoss(stopLossBps, 50)
+          .accounts({
+            user: owner.publicKey,
+            vault: vaultPda,
+            shareMint: shareMintPda,
+            userShareAccount,
+            position: positionPda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+
+      await setStopLoss(1_000);
+      let position = await program.account.userPosition.fetch(positionPda);
+      expect(position.stopLossBps).to.equal(1_000);
+
+      try {
+        await program.methods
+          .executeStopLoss()
+          .accounts({
+            crank: owner.publicKey,
+            user: owner.publicKey,
+            vault: vaultPda,
+            vaultTokenAccount: vaultTokenPda,
+            shareMint: shareMintPda,
+            userTokenAccount,
+            userShareAccount,
+            crankTokenAccount: userTokenAccount,
+            position: positionPda,
+            rewards: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("StopLossNotTriggered");
+      }
+
+      // Clearing the rule also revokes the position's delegation
+      await setStopLoss(0);
+      position = await program.account.userPosition.fetch(positionPda);
+      expect(position.stopLossBps).to.equal(0);
+      const shares = await getAccount(provider.connection, userShareAccount);
+      expect(shares.delegate).to.be.null;
+    });
+  });
+
+  describe("collect_fees", () => {
+    after(async () => {
+      await program.methods
+        .setFeePayoutMode({ shares: {} })
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+        })
+        .rpc();
+    });
+
+    it("should require the donation recipient's account once fees are donated", async () => {
+      const recipient = Keypair.generate().publicKey;
+      const setFeeDonation = (to: PublicKey, bps: number) =>
+        program.methods
+          .setFeeDonation(to, bps)
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+          })
+          .rpc();
+      const collectFees = (donationAssetAccount: PublicKey | null) =>
+        program.methods
+          .collectFees()
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            feeReport: feeReportPda,
+            shareMint: shareMintPda,
+            vaultTokenAccount: vaultTokenPda,
+            treasuryShareAccount: null,
+            treasuryAssetAccount: userTokenAccount,
+            donationShareAccount: null,
+            donationAssetAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+
+      // A donation needs both a recipient and a share of fees
+      try {
+        await setFeeDonation(PublicKey.default, 2_500);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidConfig");
+      }
+      await program.methods
+        .setFeePayoutMode({ assets: {} })
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+        })
+        .rpc();
+      await setFeeDonation(recipient, 2_500);
+      let vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.donationRecipient.toString()).to.equal(recipient.toString());
+      expect(vaultState.donationBps).to.equal(2_500);
+
+      try {
+        await collectFees(null);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("DonationAccountMissing");
+      }
+      // The donation account must belong to the recipient
+      try {
+        await collectFees(userTokenAccount);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintTokenOwner");
+      }
+
+      await setFeeDonation(PublicKey.default, 0);
+      vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.donationBps).to.equal(0);
+    });
+
+    it("should pay fees in underlying tokens in Assets mode", async () => {
+      await program.methods
+        .setFeePayoutMode({ assets: {} })
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+        })
+        .rpc();
+
+      const before = await program.account.vaultState.fetch(vaultPda);
+      const fee = before.accruedManagementFee.toNumber();
+      expect(fee).to.be.greaterThan(0);
+      const treasuryBefore = await getAccount(provider.connection, userTokenAccount);
+
+      await program.methods
+        .collectFees()
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          feeReport: feeReportPda,
+          shareMint: shareMintPda,
+          vaultTokenAccount: vaultTokenPda,
+          treasuryShareAccount: null,
+          treasuryAssetAccount: userTokenAccount,
+          donationShareAccount: null,
+          donationAssetAccount: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.accruedManagementFee.toNumber()).to.equal(0);
+      expect(vaultState.totalAssets.toNumber()).to.equal(before.totalAssets.toNumber() - fee);
+      expect(vaultState.totalShares.toString()).to.equal(before.totalShares.toString());
+
+      const treasuryAfter = await getAccount(provider.connection, userTokenAccount);
+      expect(Number(treasuryAfter.amount - treasuryBefore.amount)).to.equal(fee);
+    });
+
+    it("should refuse to sweep rounding dust below one asset unit", async () => {
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      // Each conversion truncates less than a share's worth; the whole-unit
+      // half of the Q64.64 dust is still zero
+      const dustUnits = Buffer.from(vaultState.dustQ64).readBigUInt64LE(8);
+      expect(dustUnits).to.equal(BigInt(0));
+
+      try {
+        await program.methods
+          .sweepDust()
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            shareMint: shareMintPda,
+            treasuryShareAccount: userShareAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("NoDustToSweep");
+      }
+    });
+  });
+
+  describe("rewards", () => {
+    let rewardMint: PublicKey;
+    let rewardsPda: PublicKey;
+    let rewardsTokenPda: PublicKey;
+    let ownerRewardAccount: PublicKey;
+
+    before(async () => {
+      rewardMint = await createMint(
+        provider.connection,
+        (owner as any).payer,
+        owner.publicKey,
+        null,
+        6
+      );
+      ownerRewardAccount = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        rewardMint,
+        owner.publicKey
+      );
+      await mintTo(
+        provider.connection,
+        (owner as any).payer,
+        rewardMint,
+        ownerRewardAccount,
+        owner.publicKey,
+        1_000_000_000
+      );
+
+      [rewardsPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("rewards"), vaultPda.toBuffer()],
+        program.programId
+      );
+      [rewardsTokenPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("rewards_token"), vaultPda.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("should stream funded rewards to depositors", async () => {
+      await program.methods
+        .initRewards()
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          rewardMint,
+          rewards: rewardsPda,
+          rewardsTokenAccount: rewardsTokenPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .fundRewards(new anchor.BN(100_000_000), new anchor.BN(1_000))
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          rewards: rewardsPda,
+          rewardsTokenAccount: rewardsTokenPda,
+          funderTokenAccount: ownerRewardAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const pool = await program.account.rewardsPool.fetch(rewardsPda);
+      expect(pool.rewardRate.toNumber()).to.equal(100_000);
+
+      // Depositing with the pool checkpoints the position's earning balance
+      await program.methods
+        .deposit(new anchor.BN(1_000_000), null)
+        .accounts({
+          user: owner.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: vaultTokenPda,
+          shareMint: shareMintPda,
+          userTokenAccount: userTokenAccount,
+          userShareAccount: userShareAccount,
+          position: positionPda,
+          rewards: rewardsPda,
+          oracle: null,
+          marketShareReserve: null,
+          marketAssetReserve: null,
+          gatewayToken: null,
+          walletFlag: walletFlagPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await new Promise((resolve) => setTimeout(resolve, 2_000));
+      const before = await getAccount(provider.connection, ownerRewardAccount);
+
+      await program.methods
+        .claimRewards()
+        .accounts({
+          user: owner.publicKey,
+          vault: vaultPda,
+          rewards: rewardsPda,
+          rewardsTokenAccount: rewardsTokenPda,
+          shareMint: shareMintPda,
+          userShareAccount: userShareAccount,
+          position: positionPda,
+          userRewardAccount: ownerRewardAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const after = await getAccount(provider.connection, ownerRewardAccount);
+      expect(Number(after.amount - before.amount)).to.be.greaterThan(0);
+
+      const position = await program.account.userPosition.fetch(positionPda);
+      expect(position.pendingRewards.toNumber()).to.equal(0);
+    });
+
+    it("should keep emitted rewards from the owner", async () => {
+      try {
+        await program.methods
+          .rescueTokens(new anchor.BN(1))
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            shareMint: shareMintPda,
+            sourceTokenAccount: rewardsTokenPda,
+            destinationTokenAccount: ownerRewardAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("CannotRescueHolderTokens");
+      }
+
+      const before = await getAccount(provider.connection, ownerRewardAccount);
+      await program.methods
+        .defundRewards()
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          rewards: rewardsPda,
+          rewardsTokenAccount: rewardsTokenPda,
+          ownerRewardAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      // Only the unemitted part comes back; emitted rewards stay claimable
+      const after = await getAccount(provider.connection, ownerRewardAccount);
+      expect(Number(after.amount - before.amount)).to.be.greaterThan(0);
+      const pool = await program.account.rewardsPool.fetch(rewardsPda);
+      const left = await getAccount(provider.connection, rewardsTokenPda);
+      expect(Number(left.amount)).to.equal(pool.totalEmitted.sub(pool.totalClaimed).toNumber());
+    });
+  });
+
+  describe("epochs", () => {
+    let epochEscrowPda: PublicKey;
+    let requestPda: PublicKey;
+
+    const setEpochDuration = (duration: number) =>
+      program.methods
+        .setEpochDuration(new anchor.BN(duration))
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          shareMint: shareMintPda,
+          epochEscrow: epochEscrowPda,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+
+    const settlementPda = (epoch: anchor.BN) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("epoch_settlement"),
+          vaultPda.toBuffer(),
+          epoch.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+
+    before(async () => {
+      [epochEscrowPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("epoch_escrow"), vaultPda.toBuffer()],
+        program.programId
+      );
+      [requestPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("epoch_request"), vaultPda.toBuffer(), owner.publicKey.toBuffer()],
+        program.programId
+      );
+      await setEpochDuration(1);
+    });
+
+    after(async () => {
+      await setEpochDuration(0);
+    });
+
+    it("should reject direct deposits while epoch-gated", async () => {
+      try {
+        await program.methods
+          .deposit(new anchor.BN(1_000_000), null)
+          .accounts({
+            user: owner.publicKey,
+            vault: vaultPda,
+            vaultTokenAccount: vaultTokenPda,
+            shareMint: shareMintPda,
+            userTokenAccount: userTokenAccount,
+            userShareAccount: userShareAccount,
+            position: positionPda,
+            rewards: null,
+            oracle: null,
+            marketShareReserve: null,
+            marketAssetReserve: null,
+            gatewayToken: null,
+            walletFlag: walletFlagPda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("EpochGated");
+      }
+    });
+
+    it("should settle a queued deposit at the epoch boundary", async () => {
+      const amount = 5_000_000;
+      await program.methods
+        .requestDeposit(new anchor.BN(amount))
+        .accounts({
+          user: owner.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: vaultTokenPda,
+          userTokenAccount: userTokenAccount,
+          request: requestPda,
+          gatewayToken: null,
+          walletFlag: walletFlagPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const before = await program.account.vaultState.fetch(vaultPda);
+      expect(before.epochPendingDeposits.toNumber()).to.equal(amount);
+      expect(before.epochReservedAssets.toNumber()).to.equal(amount);
+
+      // Fund a crank bounty so settling pays whoever cranks it
+      const [crankBountyPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("crank_bounty"), vaultPda.toBuffer()],
+        program.programId
+      );
+      const [crankBountyTokenPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("crank_bounty_token"), vaultPda.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initCrankBounty(new anchor.BN(1_000), new anchor.BN(60))
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          mint,
+          crankBounty: crankBountyPda,
+          crankBountyTokenAccount: crankBountyTokenPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      await program.methods
+        .fundCrankBounty(new anchor.BN(10_000))
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          crankBounty: crankBountyPda,
+          crankBountyTokenAccount: crankBountyTokenPda,
+          funderTokenAccount: userTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      await new Promise((resolve) => setTimeout(resolve, 2_000));
+      const settlement = settlementPda(before.currentEpoch);
+
+      await program.methods
+        .settleEpoch()
+        .accounts({
+          payer: owner.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: vaultTokenPda,
+          shareMint: shareMintPda,
+          epochEscrow: epochEscrowPda,
+          settlement,
+          crankBounty: crankBountyPda,
+          crankBountyTokenAccount: crankBountyTokenPda,
+          crankTokenAccount: userTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const budget = await getAccount(provider.connection, crankBountyTokenPda);
+      expect(Number(budget.amount)).to.equal(9_000);
+
+      // The owner can take the unspent budget back
+      await program.methods
+        .defundCrankBounty(new anchor.BN(9_000))
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          crankBounty: crankBountyPda,
+          crankBountyTokenAccount: crankBountyTokenPda,
+          ownerTokenAccount: userTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      const defunded = await getAccount(provider.connection, crankBountyTokenPda);
+      expect(Number(defunded.amount)).to.equal(0);
+
+      const settled = await program.account.vaultState.fetch(vaultPda);
+      expect(settled.currentEpoch.toNumber()).to.equal(before.currentEpoch.toNumber() + 1);
+      expect(settled.totalAssets.toNumber()).to.equal(before.totalAssets.toNumber() + amount);
+      expect(settled.epochReservedAssets.toNumber()).to.equal(0);
+
+      const record = await program.account.epochSettlement.fetch(settlement);
+      const sharesBefore = await getAccount(provider.connection, userShareAccount);
+
+      await program.methods
+        .claimEpoch()
+        .accounts({
+          user: owner.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: vaultTokenPda,
+          epochEscrow: epochEscrowPda,
+          userTokenAccount: userTokenAccount,
+          userShareAccount: userShareAccount,
+          request: requestPda,
+          settlement,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const sharesAfter = await getAccount(provider.connection, userShareAccount);
+      expect((sharesAfter.amount - sharesBefore.amount).toString()).to.equal(
+        record.sharesMinted.toString()
+      );
+    });
+
+    it("should fill a redemption run pro rata past the redemption gate", async () => {
+      const setRedemptionGate = (gateBps: number) =>
+        program.methods
+          .setRedemptionGate(gateBps)
+          .accounts({ owner: owner.publicKey, vault: vaultPda })
+          .rpc();
+
+      // Queue shares worth about 2% of NAV against a 1% gate
+      await setRedemptionGate(100);
+      const before = await program.account.vaultState.fetch(vaultPda);
+      const shares = before.totalShares.divn(50);
+      await program.methods
+        .requestRedeem(shares)
+        .accounts({
+          user: owner.publicKey,
+          vault: vaultPda,
+          epochEscrow: epochEscrowPda,
+          userShareAccount: userShareAccount,
+          request: requestPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const queue = await program.methods
+        .getRedemptionQueue()
+        .accounts({ vault: vaultPda })
+        .view();
+      expect(queue.pendingRedeems.toString()).to.equal(shares.toString());
+      expect(queue.redemptionGateBps).to.equal(100);
+      expect(queue.fillBps).to.be.within(4_990, 5_000);
+
+      await new Promise((resolve) => setTimeout(resolve, 2_000));
+      const settlement = settlementPda(before.currentEpoch);
+      await program.methods
+        .settleEpoch()
+        .accounts({
+          payer: owner.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: vaultTokenPda,
+          shareMint: shareMintPda,
+          epochEscrow: epochEscrowPda,
+          settlement,
+          crankBounty: null,
+          crankBountyTokenAccount: null,
+          crankTokenAccount: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const record = await program.account.epochSettlement.fetch(settlement);
+      expect(record.redeems.toString()).to.equal(shares.toString());
+      expect(record.redeemsFilled.toNumber()).to.be.lessThan(shares.toNumber());
+      expect(record.assetsPaid.toNumber()).to.be.at.most(before.totalAssets.toNumber() / 100);
+
+      const sharesBefore = await getAccount(provider.connection, userShareAccount);
+      await program.methods
+        .claimEpoch()
+        .accounts({
+          user: owner.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: vaultTokenPda,
+          epochEscrow: epochEscrowPda,
+          userTokenAccount: userTokenAccount,
+          userShareAccount: userShareAccount,
+          request: requestPda,
+          settlement,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      // The unfilled half of the request comes back as shares
+      const sharesAfter = await getAccount(provider.connection, userShareAccount);
+      expect((sharesAfter.amount - sharesBefore.amount).toString()).to.equal(
+        record.redeems.sub(record.redeemsFilled).toString()
+      );
+
+      await setRedemptionGate(0);
+    });
+
+    it("should let a queued redemption be transferred and claimed by its new owner", async () => {
+      const buyer = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(buyer.publicKey, 1_000_000_000);
+      await provider.connection.confirmTransaction(sig);
+      const buyerTokenAccount = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        mint,
+        buyer.publicKey
+      );
+      const buyerShareAccount = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        shareMintPda,
+        buyer.publicKey
+      );
+      const [buyerRequestPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("epoch_request"), vaultPda.toBuffer(), buyer.publicKey.toBuffer()],
+        program.programId
+      );
+
+      const before = await program.account.vaultState.fetch(vaultPda);
+      const shares = new anchor.BN(1_000_000);
+      await program.methods
+        .requestRedeem(shares)
+        .accounts({
+          user: owner.publicKey,
+          vault: vaultPda,
+          epochEscrow: epochEscrowPda,
+          userShareAccount: userShareAccount,
+          request: requestPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .transferEpochRequest(buyer.publicKey)
+        .accounts({
+          user: owner.publicKey,
+          vault: vaultPda,
+          request: requestPda,
+          newRequest: buyerRequestPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      expect(await program.account.epochRequest.fetchNullable(requestPda)).to.be.null;
+      const ticket = await program.account.epochRequest.fetch(buyerRequestPda);
+      expect(ticket.user.toString()).to.equal(buyer.publicKey.toString());
+      expect(ticket.redeemShares.toString()).to.equal(shares.toString());
+
+      await new Promise((resolve) => setTimeout(resolve, 2_000));
+      const settlement = settlementPda(before.currentEpoch);
+      await program.methods
+        .settleEpoch()
+        .accounts({
+          payer: owner.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: vaultTokenPda,
+          shareMint: shareMintPda,
+          epochEscrow: epochEscrowPda,
+          settlement,
+          crankBounty: null,
+          crankBountyTokenAccount: null,
+          crankTokenAccount: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .claimEpoch()
+        .accounts({
+          user: buyer.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: vaultTokenPda,
+          epochEscrow: epochEscrowPda,
+          userTokenAccount: buyerTokenAccount,
+          userShareAccount: buyerShareAccount,
+          request: buyerRequestPda,
+          settlement,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const record = await program.account.epochSettlement.fetch(settlement);
+      const buyerTokens = await getAccount(provider.connection, buyerTokenAccount);
+      expect(buyerTokens.amount.toString()).to.equal(record.assetsPaid.toString());
+    });
+  });
+
+  describe("strategies", () => {
+    const strategyId = Keypair.generate().publicKey;
+    let strategyPda: PublicKey;
+
+    const addStrategyAccounts = (id: PublicKey) => ({
+      owner: owner.publicKey,
+      vault: vaultPda,
+      strategy: PublicKey.findProgramAddressSync(
+        [Buffer.from("strategy"), vaultPda.toBuffer(), id.toBuffer()],
+        program.programId
+      )[0],
+      strategyRegistry: strategyRegistryPda,
+      strategyWhitelist: strategyWhitelistPda,
+      strategyAuthority: owner.publicKey,
+      strategyTokenAccount: userTokenAccount,
+      systemProgram: SystemProgram.programId,
+    });
+
+    before(() => {
+      [strategyPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("strategy"), vaultPda.toBuffer(), strategyId.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("should reject strategies that are not whitelisted", async () => {
+      try {
+        await program.methods
+          .addStrategy(strategyId)
+          .accounts(addStrategyAccounts(strategyId))
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("StrategyNotWhitelisted");
+      }
+
+      await program.methods
+        .setStrategyWhitelisted(strategyId, true)
+        .accounts({
+          admin: owner.publicKey,
+          protocolConfig: protocolConfigPda,
+          strategyWhitelist: strategyWhitelistPda,
+        })
+        .rpc();
+    });
+
+    it("should add a strategy and allocate to it", async () => {
+      await program.methods
+        .addStrategy(strategyId)
+        .accounts(addStrategyAccounts(strategyId))
+        .rpc();
+
+      const registry = await program.account.strategyRegistry.fetch(strategyRegistryPda);
+      expect(registry.count.toNumber()).to.equal(1);
+      expect(registry.strategies[0].toString()).to.equal(strategyPda.toString());
+
+      await program.methods
+        .allocate(new anchor.BN(100_000_000))
+        .accounts({
+          authority: owner.publicKey,
+          vault: vaultPda,
+          strategy: strategyPda,
+          vaultTokenAccount: vaultTokenPda,
+          strategyTokenAccount: userTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const strategy = await program.account.strategyState.fetch(strategyPda);
+      expect(strategy.currentDebt.toNumber()).to.equal(100_000_000);
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.totalDebt.toNumber()).to.equal(100_000_000);
+    });
+
+    it("should record profit when more than the debt is returned", async () => {
+      const totalAssetsBefore = (
+        await program.account.vaultState.fetch(vaultPda)
+      ).totalAssets.toNumber();
+
+      await program.methods
+        .deallocate(new anchor.BN(110_000_000))
+        .accounts({
+          authority: owner.publicKey,
+          strategyAuthority: owner.publicKey,
+          vault: vaultPda,
+          strategy: strategyPda,
+          feeReport: feeReportPda,
+          vaultTokenAccount: vaultTokenPda,
+          strategyTokenAccount: userTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const strategy = await program.account.strategyState.fetch(strategyPda);
+      expect(strategy.currentDebt.toNumber()).to.equal(0);
+      expect(strategy.totalGain.toNumber()).to.equal(10_000_000);
+
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.totalDebt.toNumber()).to.equal(0);
+      expect(vaultState.totalAssets.toNumber()).to.equal(totalAssetsBefore + 10_000_000);
+    });
+
+    it("should charge the curve's exit fee while liquidity is below the kink", async () => {
+      const setLiquidityFeeCurve = (kinkBps: number, maxEntryBps: number, maxExitBps: number) =>
+        program.methods
+          .setLiquidityFeeCurve(kinkBps, maxEntryBps, maxExitBps)
+          .accounts({ owner: owner.publicKey, vault: vaultPda })
+          .rpc();
+      const allocateAccounts = {
+        authority: owner.publicKey,
+        vault: vaultPda,
+        strategy: strategyPda,
+        vaultTokenAccount: vaultTokenPda,
+        strategyTokenAccount: userTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+
+      // Deploy about half the vault's liquidity against a 100% kink
+      const deployed = Math.floor(
+        Number((await getAccount(provider.connection, vaultTokenPda)).amount) / 2
+      );
+      await program.methods
+        .allocate(new anchor.BN(deployed))
+        .accounts(allocateAccounts)
+        .rpc();
+      await setLiquidityFeeCurve(10_000, 0, 200);
+
+      const vault = await program.account.vaultState.fetch(vaultPda);
+      const liquid = new anchor.BN(
+        (await getAccount(provider.connection, vaultTokenPda)).amount.toString()
+      );
+      const ratioBps = liquid.muln(10_000).div(vault.totalAssets).toNumber();
+      const exitBps = Math.floor((200 * (10_000 - ratioBps)) / 10_000);
+      expect(exitBps).to.be.greaterThan(90);
+      expect(exitBps).to.be.lessThan(110);
+
+      const shares = new anchor.BN(1_000_000);
+      const assets = shares.mul(vault.totalAssets).div(vault.totalShares);
+      const fee = assets.muln(exitBps).divn(10_000);
+      const userBefore = (await getAccount(provider.connection, userTokenAccount)).amount;
+
+      await program.methods
+        .withdraw(shares, 10_000, null)
+        .accounts({
+          user: owner.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: vaultTokenPda,
+          shareMint: shareMintPda,
+          userTokenAccount: userTokenAccount,
+          recipient: null,
+          userShareAccount: userShareAccount,
+          position: positionPda,
+          rewards: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      // The fee stays in the vault for the remaining holders
+      const userAfter = (await getAccount(provider.connection, userTokenAccount)).amount;
+      expect(Number(userAfter - userBefore)).to.equal(assets.sub(fee).toNumber());
+      const after = await program.account.vaultState.fetch(vaultPda);
+      expect(after.totalAssets.toString()).to.equal(
+        vault.totalAssets.sub(assets).add(fee).toString()
+      );
+
+      await setLiquidityFeeCurve(0, 0, 0);
+      await program.methods
+        .deallocate(new anchor.BN(deployed))
+        .accounts({
+          ...allocateAccounts,
+          strategyAuthority: owner.publicKey,
+          feeReport: feeReportPda,
+        })
+        .rpc();
+    });
+
+    it("should raise the debt ceiling on reported gains within the owner's bounds", async () => {
+      const setBounds = (min: number, max: number) =>
+        program.methods
+          .setDebtCeilingBounds(new anchor.BN(min), new anchor.BN(max))
+          .accounts({ owner: owner.publicKey, vault: vaultPda, strategy: strategyPda })
+          .rpc();
+      const allocate = (amount: number) =>
+        program.methods
+          .allocate(new anchor.BN(amount))
+          .accounts({
+            authority: owner.publicKey,
+            vault: vaultPda,
+            strategy: strategyPda,
+            vaultTokenAccount: vaultTokenPda,
+            strategyTokenAccount: userTokenAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+
+      // A newly bounded strategy starts at the lower bound
+      await setBounds(50_000_000, 200_000_000);
+      let strategy = await program.account.strategyState.fetch(strategyPda);
+      expect(strategy.debtCeiling.toNumber()).to.equal(50_000_000);
+      try {
+        await allocate(60_000_000);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("DebtCeilingExceeded");
+      }
+      await allocate(50_000_000);
+
+      // A 10% return on the debt moves the ceiling up 10 times as much
+      await program.methods
+        .harvest(new anchor.BN(55_000_000))
+        .accounts({
+          authority: owner.publicKey,
+          vault: vaultPda,
+          strategy: strategyPda,
+          feeReport: feeReportPda,
+        })
+        .rpc();
+      strategy = await program.account.strategyState.fetch(strategyPda);
+      expect(strategy.debtCeiling.toNumber()).to.equal(100_000_000);
+      await allocate(45_000_000);
+
+      await program.methods
+        .deallocate(new anchor.BN(100_000_000))
+        .accounts({
+          authority: owner.publicKey,
+          strategyAuthority: owner.publicKey,
+          vault: vaultPda,
+          strategy: strategyPda,
+          feeReport: feeReportPda,
+          vaultTokenAccount: vaultTokenPda,
+          strategyTokenAccount: userTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      await setBounds(0, 0);
+      strategy = await program.account.strategyState.fetch(strategyPda);
+      expect(strategy.debtCeiling.toNumber()).to.equal(0);
+    });
+
+    it("should only allocate to strategies meeting the vault's registry requirements", async () => {
+      const setRequirements = (
+        version: [number, number, number],
+        auditedOnly: boolean,
+        maxRiskScore: number
+      ) =>
+        program.methods
+          .setStrategyRequirements(
+            { major: version[0], minor: version[1], patch: version[2] },
+            auditedOnly,
+            maxRiskScore
+          )
+          .accounts({ owner: owner.publicKey, vault: vaultPda })
+          .rpc();
+      const setMetadata = (version: [number, number, number], audited: boolean, riskScore: number) =>
+        program.methods
+          .setStrategyMetadata(strategyId, {
+            version: { major: version[0], minor: version[1], patch: version[2] },
+            audited,
+            riskScore,
+          })
+          .accounts({
+            admin: owner.publicKey,
+            protocolConfig: protocolConfigPda,
+            strategyWhitelist: strategyWhitelistPda,
+          })
+          .rpc();
+      const expectAllocateError = async (code: string) => {
+        try {
+          await program.methods
+            .allocate(new anchor.BN(1_000_000))
+            .accounts({
+              authority: owner.publicKey,
+              vault: vaultPda,
+              strategy: strategyPda,
+              vaultTokenAccount: vaultTokenPda,
+              strategyTokenAccount: userTokenAccount,
+              tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .rpc();
+          expect.fail("Should have thrown an error");
+        } catch (err: any) {
+          expect(err.error.errorCode.code).to.equal(code);
+        }
+      };
+
+      await setMetadata([1, 2, 0], false, 3);
+      await setRequirements([1, 3, 0], false, 0);
+      await expectAllocateError("StrategyVersionTooLow");
+
+      await setMetadata([1, 3, 1], false, 3);
+      await setRequirements([1, 3, 0], true, 0);
+      await expectAllocateError("StrategyNotAudited");
+
+      await setMetadata([1, 3, 1], true, 3);
+      await setRequirements([1, 3, 0], true, 2);
+      await expectAllocateError("StrategyRiskTooHigh");
+
+      await setRequirements([0, 0, 0], false, 0);
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.auditedStrategiesOnly).to.equal(0);
+      expect(vaultState.maxStrategyRiskScore).to.equal(0);
+    });
+
+    it("should pull a withdrawal's shortfall from routed strategies", async () => {
+      await program.methods
+        .setWithdrawRoute(0, new anchor.BN(50_000_000))
+        .accounts({ owner: owner.publicKey, vault: vaultPda, strategy: strategyPda })
+        .rpc();
+      await approve(
+        provider.connection,
+        (owner as any).payer,
+        userTokenAccount,
+        vaultPda,
+        owner.publicKey,
+        50_000_000
+      );
+
+      // Deploy all but 1 token of the vault's liquidity
+      const liquid = Number((await getAccount(provider.connection, vaultTokenPda)).amount);
+      await program.methods
+        .allocate(new anchor.BN(liquid - 1_000_000))
+        .accounts({
+          authority: owner.publicKey,
+          vault: vaultPda,
+          strategy: strategyPda,
+          vaultTokenAccount: vaultTokenPda,
+          strategyTokenAccount: userTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      const debtBefore = (await program.account.strategyState.fetch(strategyPda)).currentDebt;
+
+      const before = await program.account.vaultState.fetch(vaultPda);
+      const shares = new anchor.BN(5_000_000)
+        .mul(before.totalShares)
+        .div(before.totalAssets);
+      const withdrawRouted = (remainingAccounts: any[]) =>
+        program.methods
+          .withdrawRouted(shares, 10_000, null)
+          .accounts({
+            user: owner.publicKey,
+            vault: vaultPda,
+            vaultTokenAccount: vaultTokenPda,
+            shareMint: shareMintPda,
+            userTokenAccount: userTokenAccount,
+            recipient: null,
+            userShareAccount: userShareAccount,
+            position: positionPda,
+            rewards: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(remainingAccounts)
+          .rpc();
+
+      // The strategy token account alone is not a valid route
+      try {
+        await withdrawRouted([{ pubkey: userTokenAccount, isSigner: false, isWritable: true }]);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidWithdrawRoute");
+      }
+
+      await withdrawRouted([
+        { pubkey: strategyPda, isSigner: false, isWritable: true },
+        { pubkey: userTokenAccount, isSigner: false, isWritable: true },
+      ]);
+
+      const strategy = await program.account.strategyState.fetch(strategyPda);
+      const pulled = debtBefore.sub(strategy.currentDebt).toNumber();
+      expect(pulled).to.be.greaterThan(3_900_000);
+      expect(pulled).to.be.lessThan(4_100_000);
+      const after = await program.account.vaultState.fetch(vaultPda);
+      expect(after.totalDebt.toNumber()).to.equal(before.totalDebt.toNumber() - pulled);
+      const vaultToken = await getAccount(provider.connection, vaultTokenPda);
+      expect(Number(vaultToken.amount)).to.equal(0);
+
+      await program.methods
+        .setWithdrawRoute(0, new anchor.BN(0))
+        .accounts({ owner: owner.publicKey, vault: vaultPda, strategy: strategyPda })
+        .rpc();
+      await program.methods
+        .deallocate(strategy.currentDebt)
+        .accounts({
+          authority: owner.publicKey,
+          strategyAuthority: owner.publicKey,
+          vault: vaultPda,
+          strategy: strategyPda,
+          feeReport: feeReportPda,
+          vaultTokenAccount: vaultTokenPda,
+          strategyTokenAccount: userTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    });
+
+    it("should block allocations to a strategy that stopped reporting", async () => {
+      const setMaxReportAge = (seconds: number) =>
+        program.methods
+          .setMaxReportAge(new anchor.BN(seconds))
+          .accounts({ owner: owner.publicKey, vault: vaultPda })
+          .rpc();
+
+      await setMaxReportAge(1);
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+
+      const pingSig = await program.methods
+        .pingStrategy()
+        .accounts({ vault: vaultPda, strategy: strategyPda })
+        .rpc({ commitment: "confirmed" });
+      const staleEvent = (await eventsOf(pingSig)).find(
+        (e) => e.name === "strategyStale"
+      );
+      expect(staleEvent).to.not.be.undefined;
+      expect(staleEvent!.data.strategy.toString()).to.equal(strategyPda.toString());
+
+      try {
+        await program.methods
+          .allocate(new anchor.BN(1_000_000))
+          .accounts({
+            authority: owner.publicKey,
+            vault: vaultPda,
+            strategy: strategyPda,
+            vaultTokenAccount: vaultTokenPda,
+            strategyTokenAccount: userTokenAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("StrategyStale");
+      }
+
+      await setMaxReportAge(0);
+    });
+
+    it("should only rebalance within the owner's policy", async () => {
+      const totalAssets = (await program.account.vaultState.fetch(vaultPda)).totalAssets.toNumber();
+      const target = Math.floor(totalAssets / 100);
+      const policyPda = PublicKey.findProgramAddressSync(
+        [Buffer.from("policy"), vaultPda.toBuffer()],
+        program.programId
+      )[0];
+      const rebalance = (delta: number) =>
+        program.methods
+          .rebalance([{ delta: new anchor.BN(delta) }])
+          .accounts({
+            authority: owner.publicKey,
+            vault: vaultPda,
+            policy: policyPda,
+            vaultTokenAccount: vaultTokenPda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .remainingAccounts([
+            { pubkey: strategyPda, isWritable: true, isSigner: false },
+            { pubkey: userTokenAccount, isWritable: true, isSigner: false },
+            { pubkey: owner.publicKey, isWritable: false, isSigner: true },
+          ])
+          .rpc();
+      const setPolicy = (rules: any[]) =>
+        program.methods
+          .setPolicy(rules, rules.length > 0)
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            policy: policyPda,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+
+      // 1% target, 0.1% band
+      await setPolicy([
+        { strategy: strategyPda, targetBps: 100, bandBps: 10, maxTrade: new anchor.BN(0) },
+      ]);
+
+      // Overshooting the target by more than the band is rejected
+      try {
+        await rebalance(2 * target);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("PolicyViolation");
+      }
+
+      await rebalance(target);
+      let strategy = await program.account.strategyState.fetch(strategyPda);
+      expect(strategy.currentDebt.toNumber()).to.equal(target);
+
+      // Pulling everything back leaves the strategy outside its band
+      try {
+        await rebalance(-target);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("PolicyViolation");
+      }
+
+      await setPolicy([]);
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.policyEnforced).to.equal(0);
+      await program.methods
+        .deallocate(new anchor.BN(target))
+        .accounts({
+          authority: owner.publicKey,
+          strategyAuthority: owner.publicKey,
+          vault: vaultPda,
+          strategy: strategyPda,
+          feeReport: feeReportPda,
+          vaultTokenAccount: vaultTokenPda,
+          strategyTokenAccount: userTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      strategy = await program.account.strategyState.fetch(strategyPda);
+      expect(strategy.currentDebt.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("mock strategy", () => {
+    const mockProgram = anchor.workspace.MockStrategy as Program<any>;
+    let mockStrategyPda: PublicKey;
+    let fundsPda: PublicKey;
+    let strategyPda: PublicKey;
+
+    const allocate = (amount: number) =>
+      program.methods
+        .allocate(new anchor.BN(amount))
+        .accounts({
+          authority: owner.publicKey,
+          vault: vaultPda,
+          strategy: strategyPda,
+          vaultTokenAccount: vaultTokenPda,
+          strategyTokenAccount: fundsPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    const report = () =>
+      mockProgram.methods
+        .report()
+        .accounts({
+          authority: owner.publicKey,
+          mockStrategy: mockStrategyPda,
+          funds: fundsPda,
+          vault: vaultPda,
+          strategy: strategyPda,
+          feeReport: feeReportPda,
+          auditLog: auditLogPda,
+          eventAuthority: eventAuthorityPda,
+          yieldVaultProgram: program.programId,
+        })
+        .rpc();
+    const setReportLatency = (seconds: number) =>
+      mockProgram.methods
+        .setReportLatency(new anchor.BN(seconds))
+        .accounts({ admin: owner.publicKey, mockStrategy: mockStrategyPda })
+        .rpc();
+
+    before(async () => {
+      [mockStrategyPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("mock_strategy"), vaultPda.toBuffer()],
+        mockProgram.programId
+      );
+      [fundsPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("mock_funds"), mockStrategyPda.toBuffer()],
+        mockProgram.programId
+      );
+      [strategyPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("strategy"), vaultPda.toBuffer(), mockStrategyPda.toBuffer()],
+        program.programId
+      );
+
+      await mockProgram.methods
+        .initialize(new anchor.BN(0))
+        .accounts({
+          admin: owner.publicKey,
+          vault: vaultPda,
+          mint,
+          mockStrategy: mockStrategyPda,
+          funds: fundsPda,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+
+      // The mock's PDA is both the strategy id and the strategy authority
+      await program.methods
+        .setStrategyWhitelisted(mockStrategyPda, true)
+        .accounts({
+          admin: owner.publicKey,
+          protocolConfig: protocolConfigPda,
+          strategyWhitelist: strategyWhitelistPda,
+        })
+        .rpc();
+      await program.methods
+        .addStrategy(mockStrategyPda)
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          strategy: strategyPda,
+          strategyRegistry: strategyRegistryPda,
+          strategyWhitelist: strategyWhitelistPda,
+          strategyAuthority: mockStrategyPda,
+          strategyTokenAccount: fundsPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("should realize the mock's profit and loss through its reports", async () => {
+      await allocate(20_000_000);
+      const before = await program.account.vaultState.fetch(vaultPda);
+
+      await mockProgram.methods
+        .simulateProfit(new anchor.BN(2_000_000))
+        .accounts({
+          admin: owner.publicKey,
+          mockStrategy: mockStrategyPda,
+          funds: fundsPda,
+          adminTokenAccount: userTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      await report();
+      let strategy = await program.account.strategyState.fetch(strategyPda);
+      expect(strategy.totalGain.toNumber()).to.equal(2_000_000);
+      expect(strategy.currentDebt.toNumber()).to.equal(22_000_000);
+
+      await mockProgram.methods
+        .simulateLoss(new anchor.BN(5_000_000))
+        .accounts({
+          admin: owner.publicKey,
+          mockStrategy: mockStrategyPda,
+          funds: fundsPda,
+          sink: userTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      await report();
+      strategy = await program.account.strategyState.fetch(strategyPda);
+      expect(strategy.totalLoss.toNumber()).to.equal(5_000_000);
+      expect(strategy.currentDebt.toNumber()).to.equal(17_000_000);
+      const after = await program.account.vaultState.fetch(vaultPda);
+      expect(before.totalAssets.sub(after.totalAssets).toNumber()).to.equal(3_000_000);
+
+      // Outgrowing the loss clears the vault's pre-loss price
+      await mockProgram.methods
+        .simulateProfit(new anchor.BN(6_000_000))
+        .accounts({
+          admin: owner.publicKey,
+          mockStrategy: mockStrategyPda,
+          funds: fundsPda,
+          adminTokenAccount: userTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      await report();
+      const recovered = await program.account.vaultState.fetch(vaultPda);
+      expect(recovered.preLossPriceQ64.every((byte: number) => byte === 0)).to.be.true;
+
+      // Everything left comes back through the vault's deallocate
+      await mockProgram.methods
+        .withdraw(new anchor.BN(23_000_000))
+        .accounts({
+          authority: owner.publicKey,
+          mockStrategy: mockStrategyPda,
+          funds: fundsPda,
+          vault: vaultPda,
+          strategy: strategyPda,
+          feeReport: feeReportPda,
+          auditLog: auditLogPda,
+          eventAuthority: eventAuthorityPda,
+          vaultTokenAccount: vaultTokenPda,
+          yieldVaultProgram: program.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      strategy = await program.account.strategyState.fetch(strategyPda);
+      expect(strategy.currentDebt.toNumber()).to.equal(0);
+    });
+
+    it("should block allocations while a lagging mock withholds its report", async () => {
+      const setMaxReportAge = (seconds: number) =>
+        program.methods
+          .setMaxReportAge(new anchor.BN(seconds))
+          .accounts({ owner: owner.publicKey, vault: vaultPda })
+          .rpc();
+
+      await setReportLatency(3_600);
+      await setMaxReportAge(1);
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+
+      try {
+        await report();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ReportNotDue");
+      }
+      try {
+        await allocate(1_000_000);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("StrategyStale");
+      }
+
+      // A fresh report lifts the block
+      await setReportLatency(0);
+      await report();
+      await allocate(1_000_000);
+      await setMaxReportAge(0);
+    });
+  });
+
+  describe("external_nav", () => {
+    const relayer = Keypair.generate();
+
+    const report = (externalNav: number, observedAt: number, signer = relayer) =>
+      program.methods
+        .reportExternalNav(new anchor.BN(externalNav), new anchor.BN(observedAt))
+        .accounts({
+          relayer: signer.publicKey,
+          vault: vaultPda,
+          feeReport: feeReportPda,
+          priceHistory: priceHistoryPda,
+        })
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .setNavRelayer(relayer.publicKey)
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+        })
+        .rpc();
+    });
+
+    it("should add attested external NAV to total assets", async () => {
+      const before = (await program.account.vaultState.fetch(vaultPda)).totalAssets.toNumber();
+
+      await report(50_000_000, 1);
+
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.externalNav.toNumber()).to.equal(50_000_000);
+      expect(vaultState.totalAssets.toNumber()).to.equal(before + 50_000_000);
+    });
+
+    it("should reject stale attestations", async () => {
+      try {
+        await report(60_000_000, 1);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("StaleNavAttestation");
+      }
+    });
+
+    it("should reject attestations from other keys", async () => {
+      try {
+        await report(60_000_000, 2, Keypair.generate());
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("Unauthorized");
+      }
+    });
+
+    it("should remove the component when the sibling vault is emptied", async () => {
+      const before = (await program.account.vaultState.fetch(vaultPda)).totalAssets.toNumber();
+
+      await report(0, 2);
+
+      const vaultState = await program.account.vaultState.fetch(vaultPda);
+      expect(vaultState.externalNav.toNumber()).to.equal(0);
+      expect(vaultState.totalAssets.toNumber()).to.equal(before - 50_000_000);
+    });
+  });
+
+  // Creates and initializes a vault over a fresh mint, then deposits. A paper
+  // vault's depositor is given no tokens, since its deposits move none.
+  const depositIntoNewVault = async (
+    decimals: number,
+    amount: number,
+    shareDecimals: number | null = null,
+    paper = false
+  ) => {
+    const assetMint = await createMint(
+      provider.connection,
+      (owner as any).payer,
+      owner.publicKey,
+      null,
+      decimals
+    );
+    const [vault] = PublicKey.findProgramAddressSync(
+      [VAULT_SEED, assetMint.toBuffer(), owner.publicKey.toBuffer()],
+      program.programId
+    );
+    const pda = (seed: Buffer) =>
+      PublicKey.findProgramAddressSync([seed, vault.toBuffer()], program.programId)[0];
+
+    await program.methods
+      .createVault({
+        depositCap: new anchor.BN(0),
+        minDeposit: new anchor.BN(1),
+        managementFeeBps: 0,
+        performanceFeeBps: 0,
+        shareDecimals,
+        paper,
+      })
+      .accounts({
+        owner: owner.publicKey,
+        mint: assetMint,
+        vault,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    const created = await program.account.vaultState.fetch(vault);
+    const [shareMint] = PublicKey.findProgramAddressSync(
+      [SHARE_MINT_SEED, vault.toBuffer(), created.createdSlot.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const vaultToken = pda(VAULT_TOKEN_SEED);
+
+    await program.methods
+      .initVaultAccounts()
+      .accounts({
+        owner: owner.publicKey,
+        mint: assetMint,
+        vault,
+        shareMint,
+        vaultTokenAccount: vaultToken,
+        feeReport: pda(FEE_REPORT_SEED),
+        priceHistory: pda(PRICE_HISTORY_SEED),
+        strategyRegistry: pda(STRATEGY_REGISTRY_SEED),
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const assetAccount = await createAccount(
+      provider.connection,
+      (owner as any).payer,
+      assetMint,
+      owner.publicKey
+    );
+    if (!paper) {
+      await mintTo(
+        provider.connection,
+        (owner as any).payer,
+        assetMint,
+        assetAccount,
+        owner.publicKey,
+        amount
+      );
+    }
+    const shareAccount = await createAccount(
+      provider.connection,
+      (owner as any).payer,
+      shareMint,
+      owner.publicKey
+    );
+    const [position] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), vault.toBuffer(), owner.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .deposit(new anchor.BN(amount), null)
+      .accounts({
+        user: owner.publicKey,
+        vault,
+        vaultTokenAccount: vaultToken,
+        shareMint,
+        userTokenAccount: assetAccount,
+        userShareAccount: shareAccount,
+        position,
+        rewards: null,
+        oracle: null,
+        marketShareReserve: null,
+        marketAssetReserve: null,
+        gatewayToken: null,
+        walletFlag: walletFlagPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return {
+      state: await program.account.vaultState.fetch(vault),
+      shareMint: await getMint(provider.connection, shareMint),
+      accounts: { vault, vaultToken, shareMint, assetAccount, shareAccount, position },
+    };
+  };
+
+  describe("decimals", () => {
+    it("should scale shares of a 0-decimal asset up to 6 decimals", async () => {
+      const { state, shareMint } = await depositIntoNewVault(0, 5);
+      expect(state.assetDecimals).to.equal(0);
+      expect(state.decimalsOffset).to.equal(6);
+      expect(state.shareScale.toNumber()).to.equal(1_000_000);
+      expect(shareMint.decimals).to.equal(6);
+      expect(state.totalShares.toNumber()).to.equal(5_000_000);
+    });
+
+    it("should mint shares 1:1 for a 6-decimal asset", async () => {
+      const { state, shareMint } = await depositIntoNewVault(6, 2_500_000);
+      expect(state.decimalsOffset).to.equal(0);
+      expect(shareMint.decimals).to.equal(6);
+      expect(state.totalShares.toNumber()).to.equal(2_500_000);
+    });
+
+    it("should mint shares 1:1 for a 9-decimal asset", async () => {
+      const { state, shareMint } = await depositIntoNewVault(9, 3_000_000_000);
+      expect(state.decimalsOffset).to.equal(0);
+      expect(state.shareScale.toNumber()).to.equal(1);
+      expect(shareMint.decimals).to.equal(9);
+      expect(state.totalShares.toNumber()).to.equal(3_000_000_000);
+    });
+
+    it("should give a 6-decimal asset 9-decimal shares on request", async () => {
+      const { state, shareMint } = await depositIntoNewVault(6, 2_500_000, 9);
+      expect(state.decimalsOffset).to.equal(3);
+      expect(state.shareScale.toNumber()).to.equal(1_000);
+      expect(shareMint.decimals).to.equal(9);
+      expect(state.totalShares.toNumber()).to.equal(2_500_000_000);
+    });
+  });
+
+  describe("paper trading", () => {
+    it("should account for a paper vault's flows without moving tokens", async () => {
+      const { state, accounts } = await depositIntoNewVault(6, 10_000_000, null, true);
+      expect(state.paper).to.equal(1);
+      expect(state.totalAssets.toNumber()).to.equal(10_000_000);
+      expect(state.paperBalance.toNumber()).to.equal(10_000_000);
+      const vaultToken = await getAccount(provider.connection, accounts.vaultToken);
+      expect(Number(vaultToken.amount)).to.equal(0);
+
+      await program.methods
+        .withdraw(state.totalShares.divn(2), 10_000, null)
+        .accounts({
+          user: owner.publicKey,
+          vault: accounts.vault,
+          vaultTokenAccount: accounts.vaultToken,
+          shareMint: accounts.shareMint,
+          userTokenAccount: accounts.assetAccount,
+          recipient: null,
+          userShareAccount: accounts.shareAccount,
+          position: accounts.position,
+          rewards: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const after = await program.account.vaultState.fetch(accounts.vault);
+      expect(after.totalAssets.toNumber()).to.equal(5_000_000);
+      expect(after.paperBalance.toNumber()).to.equal(5_000_000);
+      const assetAccount = await getAccount(provider.connection, accounts.assetAccount);
+      expect(Number(assetAccount.amount)).to.equal(0);
+    });
+  });
+
+  describe("airdrop", () => {
+    it("should fund a snapshot airdrop and reject claims without a valid proof", async () => {
+      const airdropMint = await createMint(
+        provider.connection,
+        (owner as any).payer,
+        owner.publicKey,
+        null,
+        6
+      );
+      const ownerAirdropAccount = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        airdropMint,
+        owner.publicKey
+      );
+      await mintTo(
+        provider.connection,
+        (owner as any).payer,
+        airdropMint,
+        ownerAirdropAccount,
+        owner.publicKey,
+        1_000_000
+      );
+
+      const snapshotSlot = new anchor.BN(await provider.connection.getSlot());
+      const [airdropPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("airdrop"), vaultPda.toBuffer(), snapshotSlot.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [airdropTokenPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("airdrop_token"), airdropPda.toBuffer()],
+        program.programId
+      );
+      const [claimPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("airdrop_claim"), airdropPda.toBuffer(), owner.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .createSnapshot(
+          snapshotSlot,
+          Array.from(Keypair.generate().publicKey.toBytes()),
+          new anchor.BN(1_000_000),
+          new anchor.BN(Math.floor(Date.now() / 1000) + 3600)
+        )
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          rewardMint: airdropMint,
+          airdrop: airdropPda,
+          airdropTokenAccount: airdropTokenPda,
+          funderTokenAccount: ownerAirdropAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const escrow = await getAccount(provider.connection, airdropTokenPda);
+      expect(Number(escrow.amount)).to.equal(1_000_000);
+
+      try {
+        await program.methods
+          .claimAirdrop(new anchor.BN(1_000_000), [])
+          .accounts({
+            claimant: owner.publicKey,
+            vault: vaultPda,
+            airdrop: airdropPda,
+            airdropTokenAccount: airdropTokenPda,
+            claim: claimPda,
+            claimantTokenAccount: ownerAirdropAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidProof");
+      }
+
+      // Unclaimed allocations can only come back through the clawback
+      try {
+        await program.methods
+          .rescueTokens(new anchor.BN(1_000_000))
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            shareMint: shareMintPda,
+            sourceTokenAccount: airdropTokenPda,
+            destinationTokenAccount: ownerAirdropAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("CannotRescueHolderTokens");
+      }
+    });
+  });
+
+  describe("tranches", () => {
+    it("should split shares into principal and yield tokens and redeem pairs", async () => {
+      const maturity = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+      const [tranchePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tranche"), vaultPda.toBuffer(), maturity.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [principalMint] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tranche_principal"), tranchePda.toBuffer()],
+        program.programId
+      );
+      const [yieldMint] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tranche_yield"), tranchePda.toBuffer()],
+        program.programId
+      );
+      const [escrowPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tranche_escrow"), tranchePda.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .createTranche(maturity)
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          shareMint: shareMintPda,
+          tranche: tranchePda,
+          principalMint,
+          yieldMint,
+          escrow: escrowPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const principalAccount = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        principalMint,
+        owner.publicKey
+      );
+      const yieldAccount = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        yieldMint,
+        owner.publicKey
+      );
+      const holderAccounts = {
+        user: owner.publicKey,
+        vault: vaultPda,
+        tranche: tranchePda,
+        principalMint,
+        yieldMint,
+        escrow: escrowPda,
+        userShareAccount: userShareAccount,
+        userPrincipalAccount: principalAccount,
+        userYieldAccount: yieldAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+
+      const sharesBefore = await getAccount(provider.connection, userShareAccount);
+      await program.methods
+        .mintTranche(new anchor.BN(1_000_000))
+        .accounts(holderAccounts)
+        .rpc();
+
+      const principal = await getAccount(provider.connection, principalAccount);
+      const yieldTokens = await getAccount(provider.connection, yieldAccount);
+      expect(Number(principal.amount)).to.be.greaterThan(0);
+      expect(yieldTokens.amount).to.equal(principal.amount);
+
+      // Before maturity the tranche cannot settle, but whole pairs redeem
+      try {
+        await program.methods
+          .settleTranche()
+          .accounts({ vault: vaultPda, tranche: tranchePda })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("TrancheNotMatured");
+      }
+
+      const amount = new anchor.BN(principal.amount.toString());
+      await program.methods
+        .redeemTranche(amount, amount)
+        .accounts(holderAccounts)
+        .rpc();
+
+      const sharesAfter = await getAccount(provider.connection, userShareAccount);
+      const escrow = await getAccount(provider.connection, escrowPda);
+      expect(Number(sharesAfter.amount) + Number(escrow.amount)).to.equal(
+        Number(sharesBefore.amount)
+      );
+      expect(Number(escrow.amount)).to.be.lessThan(2);
+    });
+  });
+
+  describe("intents", () => {
+    const intentPda = (id: number) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("intent"),
+          vaultPda.toBuffer(),
+          owner.publicKey.toBuffer(),
+          new anchor.BN(id).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+    const farFuture = () => new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+    // Q64.64 share prices the vault is never near
+    const highPrice = new anchor.BN(1).shln(100);
+
+    const postIntent = (id: number, kind: any, amount: number, source: PublicKey) =>
+      program.methods
+        .postIntent(new anchor.BN(id), kind, new anchor.BN(amount), highPrice, farFuture())
+        .accounts({
+          user: owner.publicKey,
+          vault: vaultPda,
+          shareMint: shareMintPda,
+          sourceTokenAccount: source,
+          intent: intentPda(id),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    const cancelIntent = (id: number, source: PublicKey) =>
+      program.methods
+        .cancelIntent()
+        .accounts({
+          user: owner.publicKey,
           vault: vaultPda,
+          sourceTokenAccount: source,
+          intent: intentPda(id),
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .rpc();
 
-      // Airdrop SOL to manager for tx fees
-      const sig = await provider.connection.requestAirdrop(
-        manager.publicKey,
-        1_000_000_000
-      );
+    let executor: Keypair;
+    const executeIntent = (id: number, amount: number) =>
+      program.methods
+        .executeIntent(new anchor.BN(amount))
+        .accounts({
+          executor: executor.publicKey,
+          user: owner.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: vaultTokenPda,
+          shareMint: shareMintPda,
+          userTokenAccount,
+          userShareAccount,
+          position: positionPda,
+          rewards: null,
+          oracle: null,
+          gatewayToken: null,
+          walletFlag: walletFlagPda,
+          intent: intentPda(id),
+          crankBounty: null,
+          crankBountyTokenAccount: null,
+          crankTokenAccount: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([executor])
+        .rpc();
+
+    before(async () => {
+      executor = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(executor.publicKey, 1_000_000_000);
       await provider.connection.confirmTransaction(sig);
+    });
 
-      // Manager updates NAV to simulate yield (10% gain)
-      const newNav = new anchor.BN(1_100_000_000); // 1100 USDC (was 1000)
+    it("should leave a withdraw intent unfilled below its limit price", async () => {
+      await postIntent(1, { withdraw: {} }, 1_000, userShareAccount);
+      try {
+        await executeIntent(1, 1_000);
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("IntentNotTriggered");
+      }
+
+      await cancelIntent(1, userShareAccount);
+      const shares = await getAccount(provider.connection, userShareAccount);
+      expect(shares.delegate).to.be.null;
+    });
+
+    it("should let a third party partly fill a deposit intent", async () => {
+      await postIntent(2, { deposit: {} }, 1_000_000, userTokenAccount);
+
+      const sharesBefore = await getAccount(provider.connection, userShareAccount);
+      await executeIntent(2, 600_000);
+
+      const sharesAfter = await getAccount(provider.connection, userShareAccount);
+      expect(Number(sharesAfter.amount)).to.be.greaterThan(Number(sharesBefore.amount));
+      const intent = await program.account.intent.fetch(intentPda(2));
+      expect(intent.remaining.toNumber()).to.equal(400_000);
+
+      await cancelIntent(2, userTokenAccount);
+    });
+  });
+
+  describe("protocol template", () => {
+    it("should create a vault from the protocol defaults", async () => {
+      const templateMint = await createMint(
+        provider.connection,
+        (owner as any).payer,
+        owner.publicKey,
+        null,
+        6
+      );
+      const [templateVaultPda] = PublicKey.findProgramAddressSync(
+        [VAULT_SEED, templateMint.toBuffer(), owner.publicKey.toBuffer()],
+        program.programId
+      );
 
       await program.methods
-        .updateNav(newNav)
+        .createVaultFromTemplate()
         .accounts({
-          authority: manager.publicKey,
-          vault: vaultPda,
+          owner: owner.publicKey,
+          mint: templateMint,
+          protocolConfig: protocolConfigPda,
+          vault: templateVaultPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const vaultState = await program.account.vaultState.fetch(templateVaultPda);
+      expect(vaultState.managementFeeBps).to.equal(150);
+      expect(vaultState.performanceFeeBps).to.equal(1000);
+      expect(vaultState.withdrawDelaySlots.toNumber()).to.equal(2);
+      expect(vaultState.fromTemplate).to.equal(1);
+
+      const templatePda = (seed: Buffer) =>
+        PublicKey.findProgramAddressSync([seed, templateVaultPda.toBuffer()], program.programId)[0];
+      const templateFeeReportPda = templatePda(FEE_REPORT_SEED);
+      await program.methods
+        .initVaultAccounts()
+        .accounts({
+          owner: owner.publicKey,
+          mint: templateMint,
+          vault: templateVaultPda,
+          shareMint: PublicKey.findProgramAddressSync(
+            [
+              SHARE_MINT_SEED,
+              templateVaultPda.toBuffer(),
+              vaultState.createdSlot.toArrayLike(Buffer, "le", 8),
+            ],
+            program.programId
+          )[0],
+          vaultTokenAccount: templatePda(VAULT_TOKEN_SEED),
+          feeReport: templateFeeReportPda,
+          priceHistory: templatePda(PRICE_HISTORY_SEED),
+          strategyRegistry: templatePda(STRATEGY_REGISTRY_SEED),
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
         })
-        .signers([manager])
         .rpc();
 
+      // Template vaults cannot raise fees above the protocol ceiling
+      try {
+        await program.methods
+          .updateConfig({
+            depositCap: new anchor.BN(0),
+            minDeposit: new anchor.BN(1_000),
+            managementFeeBps: 400,
+            performanceFeeBps: 1000,
+          })
+          .accounts({
+            owner: owner.publicKey,
+            vault: templateVaultPda,
+            protocolConfig: protocolConfigPda,
+            feeReport: templateFeeReportPda,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidFeeConfig");
+      }
+    });
+
+    it("should reject a zap through a non-whitelisted DEX", async () => {
+      const inputMint = await createMint(
+        provider.connection,
+        (owner as any).payer,
+        owner.publicKey,
+        null,
+        6
+      );
+      const inputTokenAccount = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        inputMint,
+        owner.publicKey
+      );
+
+      try {
+        await program.methods
+          .zapDeposit(Buffer.from([]), new anchor.BN(1_000_000), null)
+          .accounts({
+            deposit: {
+              user: owner.publicKey,
+              vault: vaultPda,
+              vaultTokenAccount: vaultTokenPda,
+              shareMint: shareMintPda,
+              userTokenAccount: userTokenAccount,
+              userShareAccount: userShareAccount,
+              position: positionPda,
+              rewards: null,
+              oracle: null,
+              tokenProgram: TOKEN_PROGRAM_ID,
+              systemProgram: SystemProgram.programId,
+            },
+            protocolConfig: protocolConfigPda,
+            inputTokenAccount,
+            dexProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ZapProgramNotWhitelisted");
+      }
+    });
+
+    it("should reject a whitelisted DEX re-entering the vault mid-zap", async () => {
+      const maliciousProgram = anchor.workspace.MaliciousAdapter as Program<any>;
+      const setZapPrograms = (zapPrograms: PublicKey[]) =>
+        program.methods
+          .updateProtocolConfig({
+            treasury: owner.publicKey,
+            guardian: PublicKey.default,
+            minManagementFeeBps: 0,
+            maxManagementFeeBps: 300,
+            minPerformanceFeeBps: 0,
+            maxPerformanceFeeBps: 2500,
+            defaultManagementFeeBps: 150,
+            defaultPerformanceFeeBps: 1000,
+            defaultDepositCap: new anchor.BN(0),
+            defaultMinDeposit: new anchor.BN(1_000),
+            defaultWithdrawDelaySlots: new anchor.BN(2),
+            defaultReserveRatioBps: 500,
+            defaultSmallWithdrawalThreshold: new anchor.BN(1_000_000),
+            zapPrograms,
+          })
+          .accounts({ admin: owner.publicKey, protocolConfig: protocolConfigPda })
+          .rpc();
+      await setZapPrograms([maliciousProgram.programId, ...Array(3).fill(PublicKey.default)]);
+
+      const inputMint = await createMint(
+        provider.connection,
+        (owner as any).payer,
+        owner.publicKey,
+        null,
+        6
+      );
+      const inputTokenAccount = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        inputMint,
+        owner.publicKey
+      );
+      const depositAccounts = {
+        user: owner.publicKey,
+        vault: vaultPda,
+        vaultTokenAccount: vaultTokenPda,
+        shareMint: shareMintPda,
+        userTokenAccount: userTokenAccount,
+        userShareAccount: userShareAccount,
+        position: positionPda,
+        rewards: null,
+        oracle: null,
+        marketShareReserve: null,
+        marketAssetReserve: null,
+        gatewayToken: null,
+        walletFlag: walletFlagPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      };
+      // The "swap" is a deposit into the same vault, made with the accounts
+      // the zap forwards to the DEX
+      const reentry = await program.methods
+        .deposit(new anchor.BN(1_000_000), null)
+        .accounts(depositAccounts)
+        .instruction();
+      const swapData = maliciousProgram.coder.instruction.encode("swap", {
+        amount: new anchor.BN(1_000_000),
+      });
+      const sharesBefore = (await getAccount(provider.connection, userShareAccount)).amount;
+
+      try {
+        await program.methods
+          .zapDeposit(swapData, new anchor.BN(1), null)
+          .accounts({
+            deposit: depositAccounts,
+            protocolConfig: protocolConfigPda,
+            inputTokenAccount,
+            dexProgram: maliciousProgram.programId,
+          })
+          .remainingAccounts([
+            { pubkey: program.programId, isSigner: false, isWritable: false },
+            ...reentry.keys,
+          ])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        // The runtime rejects the indirect re-entry before the vault runs, so
+        // this covers the runtime rule only; the vault's own lock is not
+        // reached and stays defense in depth
+        expect(String(err.logs ?? err)).to.match(/reentrancy not allowed/i);
+      }
+      const sharesAfter = (await getAccount(provider.connection, userShareAccount)).amount;
+      expect(sharesAfter).to.equal(sharesBefore);
       const vaultState = await program.account.vaultState.fetch(vaultPda);
-      expect(vaultState.totalAssets.toNumber()).to.equal(1_100_000_000);
+      expect(vaultState.locked).to.equal(0);
+
+      await setZapPrograms(Array(4).fill(PublicKey.default));
     });
+  });
 
-    it("should reject NAV update from unauthorized account", async () => {
-      const unauthorized = Keypair.generate();
-      const sig = await provider.connection.requestAirdrop(
-        unauthorized.publicKey,
-        1_000_000_000
+  describe("close_vault", () => {
+    let closeMint: PublicKey;
+    let closeVaultPda: PublicKey;
+    let closeTokenPda: PublicKey;
+    let closeFeeReportPda: PublicKey;
+    let closePriceHistoryPda: PublicKey;
+    let closeStrategyRegistryPda: PublicKey;
+
+    const deriveShareMint = (createdSlot: anchor.BN) =>
+      PublicKey.findProgramAddressSync(
+        [SHARE_MINT_SEED, closeVaultPda.toBuffer(), createdSlot.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+
+    const openVault = async (): Promise<PublicKey> => {
+      await program.methods
+        .createVault({
+          depositCap: new anchor.BN(0),
+          minDeposit: new anchor.BN(0),
+          managementFeeBps: 0,
+          performanceFeeBps: 0,
+          shareDecimals: null,
+          paper: false,
+        })
+        .accounts({
+          owner: owner.publicKey,
+          mint: closeMint,
+          vault: closeVaultPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const state = await program.account.vaultState.fetch(closeVaultPda);
+      const shareMint = deriveShareMint(state.createdSlot);
+
+      await program.methods
+        .initVaultAccounts()
+        .accounts({
+          owner: owner.publicKey,
+          mint: closeMint,
+          vault: closeVaultPda,
+          shareMint,
+          vaultTokenAccount: closeTokenPda,
+          feeReport: closeFeeReportPda,
+          priceHistory: closePriceHistoryPda,
+          strategyRegistry: closeStrategyRegistryPda,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+
+      return shareMint;
+    };
+
+    before(async () => {
+      closeMint = await createMint(
+        provider.connection,
+        (owner as any).payer,
+        owner.publicKey,
+        null,
+        6
       );
-      await provider.connection.confirmTransaction(sig);
+      [closeVaultPda] = PublicKey.findProgramAddressSync(
+        [VAULT_SEED, closeMint.toBuffer(), owner.publicKey.toBuffer()],
+        program.programId
+      );
+      [closeTokenPda] = PublicKey.findProgramAddressSync(
+        [VAULT_TOKEN_SEED, closeVaultPda.toBuffer()],
+        program.programId
+      );
+      [closeFeeReportPda] = PublicKey.findProgramAddressSync(
+        [FEE_REPORT_SEED, closeVaultPda.toBuffer()],
+        program.programId
+      );
+      [closePriceHistoryPda] = PublicKey.findProgramAddressSync(
+        [PRICE_HISTORY_SEED, closeVaultPda.toBuffer()],
+        program.programId
+      );
+      [closeStrategyRegistryPda] = PublicKey.findProgramAddressSync(
+        [STRATEGY_REGISTRY_SEED, closeVaultPda.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("should reject closing before wind-down", async () => {
+      const shareMint = await openVault();
 
       try {
         await program.methods
-          .updateNav(new anchor.BN(999_999_999))
+          .closeVault()
           .accounts({
-            authority: unauthorized.publicKey,
-            vault: vaultPda,
+            owner: owner.publicKey,
+            vault: closeVaultPda,
+            vaultTokenAccount: closeTokenPda,
+            shareMint,
+            feeReport: closeFeeReportPda,
+            priceHistory: closePriceHistoryPda,
+            strategyRegistry: closeStrategyRegistryPda,
+            voteEscrow: null,
+            epochEscrow: null,
+            ownerRewardAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
           })
-          .signers([unauthorized])
           .rpc();
         expect.fail("Should have thrown an error");
       } catch (err: any) {
-        expect(err.error.errorCode.code).to.equal("Unauthorized");
+        expect(err.error.errorCode.code).to.equal("WindDownNotComplete");
+      }
+    });
+
+    it("should reject closing while a manager bond is outstanding", async () => {
+      const state = await program.account.vaultState.fetch(closeVaultPda);
+      const shareMint = deriveShareMint(state.createdSlot);
+
+      await program.methods
+        .beginWinddown()
+        .accounts({
+          owner: owner.publicKey,
+          vault: closeVaultPda,
+          feeReport: closeFeeReportPda,
+        })
+        .rpc();
+      const windingDown = await program.account.vaultState.fetch(closeVaultPda);
+      expect(windingDown.status).to.equal(3); // WindingDown
+
+      const [bondPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("manager_bond"), closeVaultPda.toBuffer(), owner.publicKey.toBuffer()],
+        program.programId
+      );
+      const [bondTokenPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("manager_bond_token"), bondPda.toBuffer()],
+        program.programId
+      );
+      const ownerTokenAccount = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        closeMint,
+        owner.publicKey
+      );
+      await mintTo(
+        provider.connection,
+        (owner as any).payer,
+        closeMint,
+        ownerTokenAccount,
+        owner.publicKey,
+        1_000_000
+      );
+      await program.methods
+        .postBond(new anchor.BN(1_000_000))
+        .accounts({
+          manager: owner.publicKey,
+          vault: closeVaultPda,
+          mint: closeMint,
+          bond: bondPda,
+          bondTokenAccount: bondTokenPda,
+          managerTokenAccount: ownerTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      const bonded = await program.account.vaultState.fetch(closeVaultPda);
+      expect(bonded.totalBonded.toNumber()).to.equal(1_000_000);
+
+      try {
+        await program.methods
+          .closeVault()
+          .accounts({
+            owner: owner.publicKey,
+            vault: closeVaultPda,
+            vaultTokenAccount: closeTokenPda,
+            shareMint,
+            feeReport: closeFeeReportPda,
+            priceHistory: closePriceHistoryPda,
+            strategyRegistry: closeStrategyRegistryPda,
+            voteEscrow: null,
+            epochEscrow: null,
+            ownerRewardAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("VaultNotEmpty");
+      }
+
+      await program.methods
+        .withdrawBond(new anchor.BN(1_000_000))
+        .accounts({
+          manager: owner.publicKey,
+          vault: closeVaultPda,
+          bond: bondPda,
+          bondTokenAccount: bondTokenPda,
+          managerTokenAccount: ownerTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      const unbonded = await program.account.vaultState.fetch(closeVaultPda);
+      expect(unbonded.totalBonded.toNumber()).to.equal(0);
+    });
+
+    it("should reject closing until every airdrop is clawed back", async () => {
+      const state = await program.account.vaultState.fetch(closeVaultPda);
+      const shareMint = deriveShareMint(state.createdSlot);
+
+      const airdropMint = await createMint(
+        provider.connection,
+        (owner as any).payer,
+        owner.publicKey,
+        null,
+        6
+      );
+      const ownerAirdropAccount = await createAccount(
+        provider.connection,
+        (owner as any).payer,
+        airdropMint,
+        owner.publicKey
+      );
+      await mintTo(
+        provider.connection,
+        (owner as any).payer,
+        airdropMint,
+        ownerAirdropAccount,
+        owner.publicKey,
+        1_000_000
+      );
+      const snapshotSlot = new anchor.BN(await provider.connection.getSlot());
+      const [airdropPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("airdrop"),
+          closeVaultPda.toBuffer(),
+          snapshotSlot.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      const [airdropTokenPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("airdrop_token"), airdropPda.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .createSnapshot(
+          snapshotSlot,
+          Array.from(Keypair.generate().publicKey.toBytes()),
+          new anchor.BN(1_000_000),
+          new anchor.BN(Math.floor(Date.now() / 1000) + 2)
+        )
+        .accounts({
+          owner: owner.publicKey,
+          vault: closeVaultPda,
+          rewardMint: airdropMint,
+          airdrop: airdropPda,
+          airdropTokenAccount: airdropTokenPda,
+          funderTokenAccount: ownerAirdropAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      const open = await program.account.vaultState.fetch(closeVaultPda);
+      expect(open.openAirdrops).to.equal(1);
+
+      try {
+        await program.methods
+          .closeVault()
+          .accounts({
+            owner: owner.publicKey,
+            vault: closeVaultPda,
+            vaultTokenAccount: closeTokenPda,
+            shareMint,
+            feeReport: closeFeeReportPda,
+            priceHistory: closePriceHistoryPda,
+            strategyRegistry: closeStrategyRegistryPda,
+            voteEscrow: null,
+            epochEscrow: null,
+            ownerRewardAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("VaultNotEmpty");
       }
+
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+      await program.methods
+        .clawbackAirdrop()
+        .accounts({
+          owner: owner.publicKey,
+          vault: closeVaultPda,
+          airdrop: airdropPda,
+          airdropTokenAccount: airdropTokenPda,
+          destination: ownerAirdropAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      const clawedBack = await program.account.vaultState.fetch(closeVaultPda);
+      expect(clawedBack.openAirdrops).to.equal(0);
+      const returned = await getAccount(provider.connection, ownerAirdropAccount);
+      expect(Number(returned.amount)).to.equal(1_000_000);
+    });
+
+    it("should revoke the share mint authority and allow reopening", async () => {
+      const state = await program.account.vaultState.fetch(closeVaultPda);
+      const oldShareMint = deriveShareMint(state.createdSlot);
+
+      // Count the vault in the protocol stats so closing has to take it out
+      await program.methods
+        .syncGlobalStats()
+        .accounts({ vault: closeVaultPda, globalStats: globalStatsPda })
+        .rpc();
+      const statsBefore = await program.account.globalStats.fetch(globalStatsPda);
+      expect(statsBefore.mints.some((entry: any) => entry.mint.equals(closeMint))).to.be.true;
+
+      // Optional per-vault accounts are closed with the vault
+      const [closeDisclosurePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("disclosure"), closeVaultPda.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initDisclosure(
+          Array.from(createHash("sha256").update("strategy").digest()),
+          "https://example.com/strategy.pdf"
+        )
+        .accounts({ owner: owner.publicKey, vault: closeVaultPda, disclosure: closeDisclosurePda })
+        .rpc();
+
+      await program.methods
+        .closeVault()
+        .accounts({
+          owner: owner.publicKey,
+          vault: closeVaultPda,
+          vaultTokenAccount: closeTokenPda,
+          shareMint: oldShareMint,
+          feeReport: closeFeeReportPda,
+          priceHistory: closePriceHistoryPda,
+          strategyRegistry: closeStrategyRegistryPda,
+          voteEscrow: null,
+          epochEscrow: null,
+          ownerRewardAccount: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const retired = await getMint(provider.connection, oldShareMint);
+      expect(retired.mintAuthority).to.equal(null);
+      expect(await provider.connection.getAccountInfo(closeVaultPda)).to.equal(null);
+      expect(await provider.connection.getAccountInfo(closeDisclosurePda)).to.equal(null);
+      const statsAfter = await program.account.globalStats.fetch(globalStatsPda);
+      expect(statsAfter.vaultCount.toNumber()).to.equal(statsBefore.vaultCount.toNumber() - 1);
+      expect(statsAfter.mints.some((entry: any) => entry.mint.equals(closeMint))).to.be.false;
+
+      // Reopen on the same seeds — a fresh share mint is derived
+      const newShareMint = await openVault();
+      expect(newShareMint.toString()).to.not.equal(oldShareMint.toString());
+
+      const reopened = await program.account.vaultState.fetch(closeVaultPda);
+      expect(reopened.totalShares.toNumber()).to.equal(0);
     });
   });
 });
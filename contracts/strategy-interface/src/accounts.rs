@@ -0,0 +1,34 @@
+use solana_program::instruction::AccountMeta;
+use solana_program::pubkey::Pubkey;
+
+/// Accounts every strategy instruction takes first, in field order; the
+/// adapter's own venue accounts follow them
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StrategyAccounts {
+    /// The yield vault PDA, signing as the caller
+    pub vault: Pubkey,
+    /// The adapter's state for this vault, owned by the adapter program
+    pub strategy_state: Pubkey,
+    /// Token account holding the adapter's undeployed underlying; its
+    /// authority is the strategy authority registered with the vault
+    pub funds: Pubkey,
+    /// The vault's underlying token account, which withdrawals pay into
+    pub vault_token_account: Pubkey,
+    pub token_program: Pubkey,
+}
+
+impl StrategyAccounts {
+    /// Number of leading accounts the interface fixes
+    pub const LEN: usize = 5;
+
+    /// Account metas in interface order
+    pub fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.vault, true),
+            AccountMeta::new(self.strategy_state, false),
+            AccountMeta::new(self.funds, false),
+            AccountMeta::new(self.vault_token_account, false),
+            AccountMeta::new_readonly(self.token_program, false),
+        ]
+    }
+}
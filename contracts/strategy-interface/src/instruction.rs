@@ -0,0 +1,101 @@
+use borsh::BorshDeserialize;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+use crate::accounts::StrategyAccounts;
+
+/// `sha256("global:deposit")[..8]`
+pub const DEPOSIT_DISCRIMINATOR: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
+/// `sha256("global:withdraw")[..8]`
+pub const WITHDRAW_DISCRIMINATOR: [u8; 8] = [183, 18, 70, 156, 148, 109, 161, 34];
+/// `sha256("global:report")[..8]`
+pub const REPORT_DISCRIMINATOR: [u8; 8] = [96, 121, 245, 84, 178, 45, 48, 91];
+/// `sha256("global:emergency_exit")[..8]`
+pub const EMERGENCY_EXIT_DISCRIMINATOR: [u8; 8] = [164, 174, 48, 163, 191, 65, 91, 245];
+
+/// An instruction of the strategy interface
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StrategyInstruction {
+    /// Deploy `amount` of underlying the vault has just transferred into
+    /// `funds`
+    Deposit { amount: u64 },
+    /// Return `amount` of underlying to the vault token account
+    Withdraw { amount: u64 },
+    /// Set the position's current value in underlying base units as return
+    /// data, a borsh `u64`
+    Report,
+    /// Unwind the whole position and return everything to the vault token
+    /// account, whatever the cost
+    EmergencyExit,
+}
+
+impl StrategyInstruction {
+    pub fn discriminator(&self) -> [u8; 8] {
+        match self {
+            Self::Deposit { .. } => DEPOSIT_DISCRIMINATOR,
+            Self::Withdraw { .. } => WITHDRAW_DISCRIMINATOR,
+            Self::Report => REPORT_DISCRIMINATOR,
+            Self::EmergencyExit => EMERGENCY_EXIT_DISCRIMINATOR,
+        }
+    }
+
+    /// Instruction data: the discriminator followed by the borsh arguments
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = self.discriminator().to_vec();
+        if let Self::Deposit { amount } | Self::Withdraw { amount } = self {
+            data.extend_from_slice(&amount.to_le_bytes());
+        }
+        data
+    }
+
+    /// Decode instruction data, for adapters not built with Anchor
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let (discriminator, mut args) = data
+            .split_first_chunk::<8>()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let mut amount = || {
+            u64::deserialize(&mut args).map_err(|_| ProgramError::InvalidInstructionData)
+        };
+        let instruction = match *discriminator {
+            DEPOSIT_DISCRIMINATOR => Self::Deposit { amount: amount()? },
+            WITHDRAW_DISCRIMINATOR => Self::Withdraw { amount: amount()? },
+            REPORT_DISCRIMINATOR => Self::Report,
+            EMERGENCY_EXIT_DISCRIMINATOR => Self::EmergencyExit,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        Ok(instruction)
+    }
+
+    /// Build the instruction for `program_id`, appending the adapter's
+    /// `venue_accounts` after the interface accounts
+    pub fn to_instruction(
+        &self,
+        program_id: Pubkey,
+        accounts: &StrategyAccounts,
+        venue_accounts: &[AccountMeta],
+    ) -> Instruction {
+        let mut metas = accounts.to_account_metas();
+        metas.extend_from_slice(venue_accounts);
+        Instruction {
+            program_id,
+            accounts: metas,
+            data: self.pack(),
+        }
+    }
+}
+
+/// Decode the value a `report` returned, given the return data and the
+/// program that set it. `None` if another program set it or it is malformed.
+pub fn report_value(strategy_program: &Pubkey, return_data: (Pubkey, Vec<u8>)) -> Option<u64> {
+    let (program_id, data) = return_data;
+    if program_id != *strategy_program {
+        return None;
+    }
+    u64::try_from_slice(&data).ok()
+}
+
+/// Encode a `report` value for `set_return_data`
+pub fn encode_report_value(value: u64) -> Vec<u8> {
+    borsh::to_vec(&value).expect("u64 serializes")
+}
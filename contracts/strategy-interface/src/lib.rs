@@ -0,0 +1,36 @@
+//! The interface a strategy program implements for DeFAI yield vaults to
+//! allocate to it.
+//!
+//! A strategy program (an "adapter") deploys a vault's underlying into some
+//! venue and answers four instructions, which the vault invokes by CPI with
+//! the vault PDA as signer:
+//!
+//! | Instruction      | Args          | Effect                                                  |
+//! |------------------|---------------|---------------------------------------------------------|
+//! | `deposit`        | `amount: u64` | Deploy `amount` just transferred into `funds`            |
+//! | `withdraw`       | `amount: u64` | Return `amount` of underlying to `vault_token_account`   |
+//! | `report`         | —             | Return the position's current value as return data       |
+//! | `emergency_exit` | —             | Unwind everything and return it to `vault_token_account` |
+//!
+//! Every instruction takes the accounts in [`StrategyAccounts`] first, in
+//! that order, followed by any venue accounts the adapter needs.
+//!
+//! Discriminators are Anchor's (`sha256("global:<name>")[..8]`) and
+//! arguments are borsh, so an Anchor program gets a compliant encoding by
+//! naming its handlers `deposit(amount: u64)`, `withdraw(amount: u64)`,
+//! `report()` and `emergency_exit()`. Native programs decode with
+//! [`StrategyInstruction::unpack`].
+//!
+//! Adapters must:
+//! - reject any caller but the vault recorded in their strategy state, by
+//!   requiring `vault` to sign;
+//! - hold undeployed underlying in `funds`, whose authority is the strategy
+//!   authority registered with the vault;
+//! - pay `withdraw` and `emergency_exit` in full or fail, never partially;
+//! - report value in underlying base units, net of any exit costs.
+
+pub mod accounts;
+pub mod instruction;
+
+pub use accounts::StrategyAccounts;
+pub use instruction::{encode_report_value, report_value, StrategyInstruction};
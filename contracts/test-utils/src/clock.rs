@@ -0,0 +1,76 @@
+//! Deterministic clock control.
+//!
+//! LiteSVM never advances the clock on its own. These helpers move slot and
+//! unix time together at the cluster's nominal slot time, so logic keyed on
+//! either (fee accrual, epochs, withdraw delays, report staleness) sees a
+//! consistent history. The blockhash is expired on every warp so repeated
+//! identical transactions are not rejected as duplicates.
+
+use anyhow::{ensure, Result};
+use solana_sdk::clock::Clock;
+
+use crate::VaultSim;
+
+/// Nominal slot time, in milliseconds
+pub const SLOT_MS: u64 = 400;
+
+impl VaultSim {
+    pub fn clock(&self) -> Clock {
+        self.svm.get_sysvar::<Clock>()
+    }
+
+    /// Move the clock forward to `unix_timestamp`, advancing the slot by the
+    /// elapsed time
+    pub fn warp_to(&mut self, unix_timestamp: i64) -> Result<()> {
+        let mut clock = self.clock();
+        let elapsed = unix_timestamp - clock.unix_timestamp;
+        ensure!(elapsed >= 0, "cannot warp back to {unix_timestamp}");
+
+        clock.slot += elapsed as u64 * 1_000 / SLOT_MS;
+        clock.unix_timestamp = unix_timestamp;
+        self.svm.set_sysvar(&clock);
+        self.svm.expire_blockhash();
+        Ok(())
+    }
+
+    /// Set slot and unix time exactly, e.g. to those of a historical block
+    pub fn set_clock(&mut self, slot: u64, unix_timestamp: i64) {
+        let mut clock = self.clock();
+        clock.slot = slot;
+        clock.unix_timestamp = unix_timestamp;
+        self.svm.set_sysvar(&clock);
+        self.svm.expire_blockhash();
+    }
+
+    /// Move the clock forward by `seconds`
+    pub fn advance(&mut self, seconds: i64) -> Result<()> {
+        self.warp_to(self.clock().unix_timestamp + seconds)
+    }
+
+    /// Move the clock forward by `slots`, advancing unix time by their
+    /// nominal duration
+    pub fn warp_slots(&mut self, slots: u64) {
+        let mut clock = self.clock();
+        clock.slot += slots;
+        clock.unix_timestamp += (slots * SLOT_MS / 1_000) as i64;
+        self.svm.set_sysvar(&clock);
+        self.svm.expire_blockhash();
+    }
+
+    /// Call `step` `steps` times, advancing the clock by `interval` seconds
+    /// before each call after the first. Stops at the first error.
+    pub fn run_every(
+        &mut self,
+        interval: i64,
+        steps: usize,
+        mut step: impl FnMut(&mut Self, usize) -> Result<()>,
+    ) -> Result<()> {
+        for index in 0..steps {
+            if index > 0 {
+                self.advance(interval)?;
+            }
+            step(self, index)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+//! Simulated yield vault environment for tests and backtests.
+//!
+//! `VaultSim` runs the compiled yield vault program inside LiteSVM, so agent
+//! policies exercise the real on-chain logic rather than a reimplementation.
+//! The clock is fully under the caller's control and Pyth price updates can be
+//! written directly, which keeps runs deterministic:
+//!
+//! ```ignore
+//! let mut sim = VaultSim::new()?;
+//! let oracle = sim.mock_oracle(100_000_000, 50_000, -8);
+//! sim.run_every(3_600, 24 * 30, |sim, hour| {
+//!     sim.set_oracle(oracle, price_at(hour), 50_000, -8);
+//!     let events = sim.send(&[policy.next_instruction(sim)?], &[&manager])?;
+//!     record(hour, events);
+//!     Ok(())
+//! })?;
+//! ```
+//!
+//! The program is loaded from `YIELD_VAULT_SO`, defaulting to the output of
+//! `anchor build`.
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use anyhow::{anyhow, Context, Result};
+use litesvm::LiteSVM;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::{Transaction, VersionedTransaction};
+use vault_events::VaultEvent;
+use yield_vault::state::VaultState;
+
+mod clock;
+mod oracle;
+mod token;
+
+pub use clock::SLOT_MS;
+
+const DEFAULT_PROGRAM_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../solana/target/deploy/yield_vault.so"
+);
+
+/// Lamports given to the payer and to each `new_user`
+const STARTING_LAMPORTS: u64 = 100_000_000_000;
+
+/// A LiteSVM bank with the yield vault program deployed
+pub struct VaultSim {
+    pub svm: LiteSVM,
+    /// Pays fees for every transaction sent through `send`
+    pub payer: Keypair,
+}
+
+impl VaultSim {
+    pub fn new() -> Result<Self> {
+        Self::with_svm(LiteSVM::new())
+    }
+
+    /// A bank that accepts historical transactions as they were signed:
+    /// signatures are not verified and any blockhash is accepted
+    pub fn replaying() -> Result<Self> {
+        Self::with_svm(
+            LiteSVM::new()
+                .with_sigverify(false)
+                .with_blockhash_check(false),
+        )
+    }
+
+    fn with_svm(mut svm: LiteSVM) -> Result<Self> {
+        let path = std::env::var("YIELD_VAULT_SO").unwrap_or_else(|_| DEFAULT_PROGRAM_PATH.into());
+        svm.add_program_from_file(yield_vault::ID, &path)
+            .with_context(|| format!("load program from {path}"))?;
+
+        let payer = Keypair::new();
+        svm.airdrop(&payer.pubkey(), STARTING_LAMPORTS)
+            .map_err(|failed| anyhow!("fund payer: {:?}", failed.err))?;
+        Ok(Self { svm, payer })
+    }
+
+    /// A funded keypair to act as a depositor, manager or crank
+    pub fn new_user(&mut self) -> Result<Keypair> {
+        let user = Keypair::new();
+        self.svm
+            .airdrop(&user.pubkey(), STARTING_LAMPORTS)
+            .map_err(|failed| anyhow!("fund user: {:?}", failed.err))?;
+        Ok(user)
+    }
+
+    /// A yield vault instruction from its Anchor account and argument structs,
+    /// e.g. `yield_vault::accounts::Deposit` and `yield_vault::instruction::Deposit`
+    pub fn ix(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: yield_vault::ID,
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        }
+    }
+
+    /// Send `ixs` in one transaction paid by `payer`, returning the vault
+    /// events it emitted. A failed transaction's error carries its logs.
+    pub fn send(&mut self, ixs: &[Instruction], signers: &[&Keypair]) -> Result<Vec<VaultEvent>> {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let tx = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.svm.latest_blockhash(),
+        );
+        self.send_signed(tx.into())
+    }
+
+    /// Send an already signed transaction as-is
+    pub fn send_signed(&mut self, tx: VersionedTransaction) -> Result<Vec<VaultEvent>> {
+        let meta = self
+            .svm
+            .send_transaction(tx)
+            .map_err(|failed| anyhow!("{:?}\n{}", failed.err, failed.meta.logs.join("\n")))?;
+        Ok(decode_events(
+            meta.inner_instructions
+                .iter()
+                .flatten()
+                .map(|inner| inner.instruction.data.as_slice()),
+        ))
+    }
+
+    /// An Anchor account, deserialized with its discriminator checked
+    pub fn account<T: AccountDeserialize>(&self, key: &Pubkey) -> Result<T> {
+        let account = self
+            .svm
+            .get_account(key)
+            .ok_or_else(|| anyhow!("account {key} not found"))?;
+        Ok(T::try_deserialize(&mut account.data.as_slice())?)
+    }
+
+    /// The zero-copy state of `vault`
+    pub fn vault_state(&self, vault: &Pubkey) -> Result<VaultState> {
+        let account = self
+            .svm
+            .get_account(vault)
+            .ok_or_else(|| anyhow!("vault {vault} not found"))?;
+        let body = account
+            .data
+            .get(8..8 + std::mem::size_of::<VaultState>())
+            .ok_or_else(|| anyhow!("{vault} is not a vault"))?;
+        Ok(bytemuck::pod_read_unaligned(body))
+    }
+}
+
+/// Vault events carried in a transaction's inner instructions, which the
+/// program emits through a CPI to itself tagged with `EVENT_IX_TAG_LE`. Other
+/// instructions and events of other programs are skipped.
+pub fn decode_events<'a>(inner_ixs: impl IntoIterator<Item = &'a [u8]>) -> Vec<VaultEvent> {
+    inner_ixs
+        .into_iter()
+        .filter_map(|data| data.strip_prefix(EVENT_IX_TAG_LE))
+        .filter_map(|bytes| VaultEvent::decode(bytes)?.ok())
+        .collect()
+}
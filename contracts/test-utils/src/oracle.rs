@@ -0,0 +1,61 @@
+//! Mock Pyth price feeds.
+//!
+//! Writes `PriceUpdateV2` accounts owned by the Pyth receiver with the layout
+//! `utils::oracle_conf_bps` reads, so `set_price_oracle` and the deposit and
+//! NAV confidence checks run against prices the test chooses.
+
+use anchor_lang::solana_program::hash::hash;
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use yield_vault::constants::PYTH_RECEIVER_ID;
+
+use crate::VaultSim;
+
+/// Discriminator, write authority, verification level, then the price
+/// message (feed id, price, conf, exponent, publish times, EMA price and
+/// conf) and the posted slot
+const PRICE_UPDATE_LEN: usize = 8 + 32 + 1 + 32 + 8 + 8 + 4 + 8 + 8 + 8 + 8 + 8;
+
+/// `VerificationLevel::Full`
+const VERIFIED_FULL: u8 = 1;
+
+impl VaultSim {
+    /// A new fully verified price feed
+    pub fn mock_oracle(&mut self, price: i64, conf: u64, exponent: i32) -> Pubkey {
+        let oracle = Pubkey::new_unique();
+        self.set_oracle(oracle, price, conf, exponent);
+        oracle
+    }
+
+    /// Overwrite `oracle` with a fully verified update published now
+    pub fn set_oracle(&mut self, oracle: Pubkey, price: i64, conf: u64, exponent: i32) {
+        let clock = self.clock();
+        let mut data = Vec::with_capacity(PRICE_UPDATE_LEN);
+        data.extend_from_slice(&hash(b"account:PriceUpdateV2").to_bytes()[..8]);
+        data.extend_from_slice(&[0; 32]);
+        data.push(VERIFIED_FULL);
+        data.extend_from_slice(oracle.as_ref());
+        data.extend_from_slice(&price.to_le_bytes());
+        data.extend_from_slice(&conf.to_le_bytes());
+        data.extend_from_slice(&exponent.to_le_bytes());
+        data.extend_from_slice(&clock.unix_timestamp.to_le_bytes());
+        data.extend_from_slice(&clock.unix_timestamp.to_le_bytes());
+        data.extend_from_slice(&price.to_le_bytes());
+        data.extend_from_slice(&conf.to_le_bytes());
+        data.extend_from_slice(&clock.slot.to_le_bytes());
+
+        let lamports = self.svm.minimum_balance_for_rent_exemption(data.len());
+        self.svm
+            .set_account(
+                oracle,
+                Account {
+                    lamports,
+                    data,
+                    owner: PYTH_RECEIVER_ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .expect("write mock oracle");
+    }
+}
@@ -0,0 +1,93 @@
+//! SPL token fixtures.
+//!
+//! Mints and token accounts are written straight into the bank instead of
+//! being created by transactions. Funding a token account this way does not
+//! change its mint's supply, which the vault never reads for the underlying.
+
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::state::{Account as TokenAccount, AccountState, Mint};
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+
+use crate::VaultSim;
+
+impl VaultSim {
+    fn set_token_program_account(&mut self, key: Pubkey, data: Vec<u8>) {
+        let lamports = self.svm.minimum_balance_for_rent_exemption(data.len());
+        self.svm
+            .set_account(
+                key,
+                Account {
+                    lamports,
+                    data,
+                    owner: spl_token::ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .expect("write token account");
+    }
+
+    /// A new mint with `payer` as its mint authority
+    pub fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        self.set_mint(mint, decimals);
+        mint
+    }
+
+    /// Overwrite `mint` as an empty mint with `payer` as its mint authority
+    pub fn set_mint(&mut self, mint: Pubkey, decimals: u8) {
+        let mut data = vec![0; Mint::LEN];
+        Mint {
+            mint_authority: COption::Some(self.payer.pubkey()),
+            supply: 0,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }
+        .pack_into_slice(&mut data);
+        self.set_token_program_account(mint, data);
+    }
+
+    /// A new token account of `mint` owned by `owner`, holding `amount`
+    pub fn create_token_account(&mut self, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+        let account = Pubkey::new_unique();
+        self.set_token_balance(account, mint, owner, amount);
+        account
+    }
+
+    /// Overwrite `account` as a token account of `mint` owned by `owner`
+    /// holding `amount`, e.g. to simulate strategy gains or losses
+    pub fn set_token_balance(
+        &mut self,
+        account: Pubkey,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) {
+        let mut data = vec![0; TokenAccount::LEN];
+        TokenAccount {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        }
+        .pack_into_slice(&mut data);
+        self.set_token_program_account(account, data);
+    }
+
+    /// Balance of a token account (0 when it does not exist)
+    pub fn token_balance(&self, account: &Pubkey) -> u64 {
+        self.svm
+            .get_account(account)
+            .and_then(|account| TokenAccount::unpack(&account.data).ok())
+            .map_or(0, |account| account.amount)
+    }
+}
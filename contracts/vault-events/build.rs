@@ -0,0 +1,193 @@
+//! Generates the event schema from the yield vault program's `events.rs`.
+//!
+//! Every `#[event]` struct is re-emitted with borsh and serde derives, its doc
+//! comments and its Anchor discriminator, so consumers decode exactly what the
+//! program emits without depending on `anchor-lang`.
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::{env, fs};
+
+use quote::ToTokens;
+use sha2::{Digest, Sha256};
+
+const EVENTS_SOURCE: &str = "../solana/programs/yield_vault/src/events.rs";
+const STATE_SOURCE: &str = "../solana/programs/yield_vault/src/state.rs";
+
+fn doc_lines(attrs: &[syn::Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(nv) => match &nv.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                }) => Some(s.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Re-emit the borsh-serialized enums of `state.rs` that event fields use
+fn write_enums(out: &mut String, field_types: &[String]) {
+    let source = fs::read_to_string(STATE_SOURCE).expect("read program state.rs");
+    let file = syn::parse_file(&source).expect("parse program state.rs");
+
+    for item in file.items {
+        let syn::Item::Enum(item) = item else {
+            continue;
+        };
+        let name = item.ident.to_string();
+        if !field_types.contains(&name) {
+            continue;
+        }
+
+        for line in doc_lines(&item.attrs) {
+            writeln!(out, "///{line}").unwrap();
+        }
+        writeln!(
+            out,
+            "#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]"
+        )
+        .unwrap();
+        writeln!(out, "pub enum {name} {{").unwrap();
+        for variant in &item.variants {
+            for line in doc_lines(&variant.attrs) {
+                writeln!(out, "    ///{line}").unwrap();
+            }
+            writeln!(out, "    {},", variant.ident).unwrap();
+        }
+        writeln!(out, "}}\n").unwrap();
+    }
+}
+
+/// Anchor event discriminator: `sha256("event:<Name>")[..8]`
+fn discriminator(name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("event:{name}").as_bytes());
+    hash[..8].try_into().unwrap()
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={EVENTS_SOURCE}");
+    println!("cargo:rerun-if-changed={STATE_SOURCE}");
+
+    let source = fs::read_to_string(EVENTS_SOURCE).expect("read program events.rs");
+    let file = syn::parse_file(&source).expect("parse program events.rs");
+
+    let mut out = String::new();
+    let mut names = Vec::new();
+    let mut sequenced = Vec::new();
+    let mut field_types = Vec::new();
+
+    for item in file.items {
+        let syn::Item::Struct(item) = item else {
+            continue;
+        };
+        if !item.attrs.iter().any(|attr| attr.path().is_ident("event")) {
+            continue;
+        }
+        let name = item.ident.to_string();
+
+        for line in doc_lines(&item.attrs) {
+            writeln!(out, "///{line}").unwrap();
+        }
+        writeln!(
+            out,
+            "#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]"
+        )
+        .unwrap();
+        writeln!(out, "pub struct {name} {{").unwrap();
+        for field in &item.fields {
+            for line in doc_lines(&field.attrs) {
+                writeln!(out, "    ///{line}").unwrap();
+            }
+            let ident = field.ident.as_ref().expect("named event field");
+            if ident == "event_seq" {
+                sequenced.push(name.clone());
+            }
+            let ty = field.ty.to_token_stream();
+            field_types.push(ty.to_string());
+            writeln!(out, "    pub {ident}: {ty},").unwrap();
+        }
+        writeln!(out, "}}\n").unwrap();
+
+        writeln!(out, "impl {name} {{").unwrap();
+        writeln!(
+            out,
+            "    pub const DISCRIMINATOR: [u8; 8] = {:?};",
+            discriminator(&name)
+        )
+        .unwrap();
+        writeln!(out, "}}\n").unwrap();
+
+        names.push(name);
+    }
+
+    write_enums(&mut out, &field_types);
+
+    writeln!(out, "/// Any event emitted by the yield vault program").unwrap();
+    writeln!(
+        out,
+        "#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]"
+    )
+    .unwrap();
+    writeln!(out, "#[serde(tag = \"name\", content = \"data\")]").unwrap();
+    writeln!(out, "pub enum VaultEvent {{").unwrap();
+    for name in &names {
+        writeln!(out, "    {name}({name}),").unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    writeln!(out, "impl VaultEvent {{").unwrap();
+    writeln!(
+        out,
+        "    /// Decode an event from its discriminator-prefixed bytes, as carried in \
+         event CPI instruction data after the event tag. Returns `None` for \
+         unknown discriminators."
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "    pub fn decode(data: &[u8]) -> Option<std::io::Result<Self>> {{"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "        let (disc, mut body) = data.split_at_checked(8)?;"
+    )
+    .unwrap();
+    writeln!(out, "        match <[u8; 8]>::try_from(disc).ok()? {{").unwrap();
+    for name in &names {
+        writeln!(
+            out,
+            "            {name}::DISCRIMINATOR => \
+             Some(<{name} as BorshDeserialize>::deserialize(&mut body).map(Self::{name})),"
+        )
+        .unwrap();
+    }
+    writeln!(out, "            _ => None,").unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}\n").unwrap();
+
+    writeln!(
+        out,
+        "    /// The vault's sequence number carried by the event, or `None` for \
+         protocol-wide events"
+    )
+    .unwrap();
+    writeln!(out, "    pub fn event_seq(&self) -> Option<u64> {{").unwrap();
+    writeln!(out, "        match self {{").unwrap();
+    for name in &sequenced {
+        writeln!(out, "            Self::{name}(event) => Some(event.event_seq),").unwrap();
+    }
+    writeln!(out, "            _ => None,").unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+
+    let dest = PathBuf::from(env::var("OUT_DIR").unwrap()).join("events.rs");
+    fs::write(dest, out).expect("write generated events");
+}
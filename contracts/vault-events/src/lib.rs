@@ -0,0 +1,33 @@
+//! Yield vault event schema shared by the indexer and agents.
+//!
+//! The types are generated at build time from the program's `events.rs`, so
+//! there is a single source of truth: adding or changing an event in the
+//! program updates this crate on the next build.
+//!
+//! ```ignore
+//! // The vault emits events as CPIs to itself: the inner instruction's data
+//! // is Anchor's event tag followed by the event's discriminator and body
+//! let bytes = inner_ix.data.strip_prefix(EVENT_IX_TAG_LE)?;
+//! match VaultEvent::decode(bytes) {
+//!     Some(Ok(VaultEvent::Deposited(ev))) => println!("{} deposited {}", ev.user, ev.amount),
+//!     Some(Ok(other)) => println!("{}", serde_json::to_string(&other)?),
+//!     Some(Err(err)) => eprintln!("malformed event: {err}"),
+//!     None => {} // not a vault event
+//! }
+//! ```
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+pub use solana_program::pubkey::Pubkey;
+
+include!(concat!(env!("OUT_DIR"), "/events.rs"));
+
+/// A vault event as written by the indexer: the serde form of `VaultEvent`
+/// plus the block time of the transaction that emitted it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    /// Unix timestamp, seconds
+    pub block_time: i64,
+    #[serde(flatten)]
+    pub event: VaultEvent,
+}
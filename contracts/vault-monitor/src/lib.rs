@@ -0,0 +1,441 @@
+//! Alert rules over a yield vault's indexed events.
+//!
+//! Rules and notifiers are defined in YAML:
+//!
+//! ```yaml
+//! rules:
+//!   - name: nav-drop
+//!     condition: nav_drop        # NAV reports compound to a >5% loss in 1h
+//!     max_drop_bps: 500
+//!     window_secs: 3600
+//!     notify: [ops-slack]
+//!   - name: nav-stale
+//!     condition: stale           # no NavUpdated in 24h
+//!     event: NavUpdated
+//!     max_age_secs: 86400
+//!     notify: [ops-slack, ops-telegram]
+//!   - name: large-withdrawal
+//!     condition: threshold       # any single withdrawal above 1M units
+//!     event: Withdrawn
+//!     field: amount_returned
+//!     above: 1000000
+//!     notify: [pager]
+//! notifiers:
+//!   ops-slack: { type: slack, webhook_url: "https://hooks.slack.com/..." }
+//!   ops-telegram: { type: telegram, bot_token: "...", chat_id: "..." }
+//!   pager: { type: webhook, url: "https://alerts.example.com/defai" }
+//! ```
+//!
+//! `nav_drop` and `stale` alert once when breached and re-arm when the
+//! condition clears; `threshold` alerts on every matching event.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use vault_events::{LoggedEvent, Pubkey, VaultEvent};
+
+pub mod notify;
+
+use notify::Notifier;
+
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    pub rules: Vec<Rule>,
+    pub notifiers: HashMap<String, Notifier>,
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Self> {
+        let yaml = std::fs::read_to_string(path).with_context(|| format!("open {path}"))?;
+        Self::parse(&yaml).with_context(|| format!("parse {path}"))
+    }
+
+    /// Parse a YAML config and check every rule's notifiers are defined
+    pub fn parse(yaml: &str) -> Result<Self> {
+        let config: Self = serde_yaml::from_str(yaml)?;
+        for rule in &config.rules {
+            for name in &rule.notify {
+                if !config.notifiers.contains_key(name) {
+                    bail!("rule {} notifies unknown notifier {name}", rule.name);
+                }
+            }
+        }
+        Ok(config)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    #[serde(flatten)]
+    pub condition: Condition,
+    /// Names of the notifiers to alert
+    pub notify: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "condition", rename_all = "snake_case")]
+pub enum Condition {
+    /// The NAV reports of the last `window_secs` compound to a loss above
+    /// `max_drop_bps`. Deposits and withdrawals do not count.
+    NavDrop { max_drop_bps: u64, window_secs: i64 },
+    /// No `event` has been seen for `max_age_secs`
+    Stale { event: String, max_age_secs: i64 },
+    /// An `event` whose numeric `field` is above `above`
+    Threshold {
+        event: String,
+        field: String,
+        above: u64,
+    },
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Alert {
+    pub rule: String,
+    pub message: String,
+    #[serde(skip)]
+    pub notify: Vec<String>,
+}
+
+/// Evaluates the rules against one vault's events
+pub struct Monitor {
+    vault: Value,
+    rules: Vec<Rule>,
+    /// Block time and new/old ratio of each NAV report, oldest first
+    nav_reports: VecDeque<(i64, f64)>,
+    /// Latest block time of each event name
+    last_seen: HashMap<String, i64>,
+    /// Rules currently breached, so they alert once
+    firing: HashSet<String>,
+    started_at: i64,
+}
+
+impl Monitor {
+    pub fn new(vault: &Pubkey, rules: Vec<Rule>, now: i64) -> Result<Self> {
+        Ok(Self {
+            vault: serde_json::to_value(vault)?,
+            rules,
+            nav_reports: VecDeque::new(),
+            last_seen: HashMap::new(),
+            firing: HashSet::new(),
+            started_at: now,
+        })
+    }
+
+    /// Alert for `rule` when it becomes breached; re-arm it once it clears
+    fn transition(&mut self, rule: &Rule, breached: bool, message: String) -> Option<Alert> {
+        if !breached {
+            self.firing.remove(&rule.name);
+            return None;
+        }
+        self.firing.insert(rule.name.clone()).then(|| Alert {
+            rule: rule.name.clone(),
+            message,
+            notify: rule.notify.clone(),
+        })
+    }
+
+    /// Compounded NAV change over the last `window_secs` before `now`, in
+    /// basis points of loss (negative for a gain)
+    fn nav_drop_bps(&self, now: i64, window_secs: i64) -> f64 {
+        let change: f64 = self
+            .nav_reports
+            .iter()
+            .filter(|(time, _)| now - time <= window_secs)
+            .map(|(_, ratio)| ratio)
+            .product();
+        (1.0 - change) * 10_000.0
+    }
+
+    /// Apply one event, returning the alerts it raises. Events of other
+    /// vaults are ignored.
+    pub fn observe(&mut self, logged: &LoggedEvent) -> Result<Vec<Alert>> {
+        let event = serde_json::to_value(&logged.event)?;
+        if event["data"]["vault"] != self.vault {
+            return Ok(Vec::new());
+        }
+        let name = event["name"].as_str().unwrap_or_default().to_string();
+        let now = logged.block_time;
+        self.last_seen.insert(name.clone(), now);
+
+        if let VaultEvent::NavUpdated(ev) = &logged.event {
+            if ev.old_total_assets > 0 {
+                let ratio = ev.new_total_assets as f64 / ev.old_total_assets as f64;
+                self.nav_reports.push_back((now, ratio));
+            }
+        }
+        let longest_window = self
+            .rules
+            .iter()
+            .filter_map(|rule| match rule.condition {
+                Condition::NavDrop { window_secs, .. } => Some(window_secs),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        while self
+            .nav_reports
+            .front()
+            .is_some_and(|(time, _)| now - time > longest_window)
+        {
+            self.nav_reports.pop_front();
+        }
+
+        let mut alerts = Vec::new();
+        for rule in self.rules.clone() {
+            match &rule.condition {
+                Condition::NavDrop {
+                    max_drop_bps,
+                    window_secs,
+                } => {
+                    let drop = self.nav_drop_bps(now, *window_secs);
+                    let message = format!("NAV dropped {:.2}% within {window_secs}s", drop / 100.0);
+                    alerts.extend(self.transition(&rule, drop > *max_drop_bps as f64, message));
+                }
+                Condition::Threshold {
+                    event: wanted,
+                    field,
+                    above,
+                } if *wanted == name => {
+                    let value = event["data"][field.as_str()].as_u64().unwrap_or(0);
+                    if value > *above {
+                        alerts.push(Alert {
+                            rule: rule.name.clone(),
+                            message: format!("{name}.{field} = {value} (above {above})"),
+                            notify: rule.notify.clone(),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(alerts)
+    }
+
+    /// Evaluate the time-based rules at `now`
+    pub fn tick(&mut self, now: i64) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        for rule in self.rules.clone() {
+            if let Condition::Stale {
+                event,
+                max_age_secs,
+            } = &rule.condition
+            {
+                let last = self.last_seen.get(event).copied();
+                let age = now - last.unwrap_or(self.started_at);
+                let message = match last {
+                    Some(last) => format!("no {event} since {last} ({age}s ago)"),
+                    None => format!("no {event} seen since monitoring started ({age}s ago)"),
+                };
+                alerts.extend(self.transition(&rule, age > *max_age_secs, message));
+            }
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vault_events::{Deposited, NavUpdated, Withdrawn};
+
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    const CONFIG: &str = r#"
+rules:
+  - name: nav-drop
+    condition: nav_drop
+    max_drop_bps: 500
+    window_secs: 3600
+    notify: [ops-slack]
+  - name: nav-stale
+    condition: stale
+    event: NavUpdated
+    max_age_secs: 86400
+    notify: [ops-slack, pager]
+  - name: large-withdrawal
+    condition: threshold
+    event: Withdrawn
+    field: amount_returned
+    above: 1000000
+    notify: [pager]
+notifiers:
+  ops-slack: { type: slack, webhook_url: "https://hooks.slack.com/test" }
+  pager: { type: webhook, url: "https://alerts.example.com/test" }
+"#;
+
+    fn vault() -> Pubkey {
+        Pubkey::new_from_array([1; 32])
+    }
+
+    fn monitor() -> Monitor {
+        Monitor::new(&vault(), Config::parse(CONFIG).unwrap().rules, NOW).unwrap()
+    }
+
+    fn nav_updated(block_time: i64, old_total_assets: u64, new_total_assets: u64) -> LoggedEvent {
+        LoggedEvent {
+            block_time,
+            event: VaultEvent::NavUpdated(NavUpdated {
+                vault: vault(),
+                event_seq: 0,
+                old_total_assets,
+                new_total_assets,
+                manager: Pubkey::default(),
+                valuation_commitment: None,
+            }),
+        }
+    }
+
+    fn withdrawn(vault: Pubkey, amount_returned: u64) -> LoggedEvent {
+        LoggedEvent {
+            block_time: NOW,
+            event: VaultEvent::Withdrawn(Withdrawn {
+                vault,
+                event_seq: 0,
+                user: Pubkey::default(),
+                recipient: Pubkey::default(),
+                shares_burned: amount_returned,
+                amount_returned,
+                exit_fee: 0,
+                memo: None,
+            }),
+        }
+    }
+
+    fn fired(alerts: &[Alert]) -> Vec<&str> {
+        alerts.iter().map(|alert| alert.rule.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_every_condition() {
+        let config = Config::parse(CONFIG).unwrap();
+        assert_eq!(config.rules.len(), 3);
+        assert!(matches!(
+            config.rules[0].condition,
+            Condition::NavDrop {
+                max_drop_bps: 500,
+                window_secs: 3600
+            }
+        ));
+        assert!(matches!(
+            &config.rules[1].condition,
+            Condition::Stale { event, max_age_secs: 86400 } if event == "NavUpdated"
+        ));
+        assert!(matches!(
+            &config.rules[2].condition,
+            Condition::Threshold { field, above: 1_000_000, .. } if field == "amount_returned"
+        ));
+        assert_eq!(config.rules[1].notify, ["ops-slack", "pager"]);
+        assert_eq!(config.notifiers.len(), 2);
+    }
+
+    #[test]
+    fn rejects_malformed_config() {
+        // Not YAML at all
+        assert!(Config::parse("rules: [unclosed").is_err());
+        // Unknown condition
+        let unknown = CONFIG.replace("condition: stale", "condition: sideways");
+        assert!(Config::parse(&unknown).is_err());
+        // Missing threshold bound
+        let missing = CONFIG.replace("    above: 1000000\n", "");
+        assert!(Config::parse(&missing).is_err());
+        // Notifier that is not defined
+        let undefined = CONFIG.replace("notify: [pager]", "notify: [nobody]");
+        let err = Config::parse(&undefined).unwrap_err();
+        assert!(err.to_string().contains("unknown notifier nobody"));
+    }
+
+    #[test]
+    fn threshold_alerts_on_every_event_above_it() {
+        let mut monitor = monitor();
+        assert!(monitor.observe(&withdrawn(vault(), 1_000_000)).unwrap().is_empty());
+
+        let alerts = monitor.observe(&withdrawn(vault(), 1_000_001)).unwrap();
+        assert_eq!(fired(&alerts), ["large-withdrawal"]);
+        assert_eq!(alerts[0].notify, ["pager"]);
+        assert_eq!(alerts[0].message, "Withdrawn.amount_returned = 1000001 (above 1000000)");
+        // Not a latched condition: the next breach alerts again
+        let alerts = monitor.observe(&withdrawn(vault(), 2_000_000)).unwrap();
+        assert_eq!(fired(&alerts), ["large-withdrawal"]);
+    }
+
+    #[test]
+    fn ignores_other_vaults_and_events() {
+        let mut monitor = monitor();
+        let other = Pubkey::new_from_array([2; 32]);
+        assert!(monitor.observe(&withdrawn(other, 5_000_000)).unwrap().is_empty());
+
+        let deposit = LoggedEvent {
+            block_time: NOW,
+            event: VaultEvent::Deposited(Deposited {
+                vault: vault(),
+                event_seq: 0,
+                user: Pubkey::default(),
+                amount: 5_000_000,
+                shares_minted: 5_000_000,
+                entry_fee: 0,
+                memo: None,
+            }),
+        };
+        assert!(monitor.observe(&deposit).unwrap().is_empty());
+    }
+
+    #[test]
+    fn nav_drop_compounds_within_the_window_and_rearms() {
+        let mut monitor = monitor();
+        // Two 3% drops compound to 5.91%, above the 5% limit
+        assert!(monitor.observe(&nav_updated(NOW, 1_000, 970)).unwrap().is_empty());
+        let alerts = monitor.observe(&nav_updated(NOW + 60, 970, 941)).unwrap();
+        assert_eq!(fired(&alerts), ["nav-drop"]);
+        assert_eq!(alerts[0].notify, ["ops-slack"]);
+
+        // Still breached: no repeat alert
+        assert!(monitor.observe(&nav_updated(NOW + 120, 941, 941)).unwrap().is_empty());
+
+        // Once both drops leave the window the rule clears and can fire again
+        assert!(monitor.observe(&nav_updated(NOW + 7_200, 941, 941)).unwrap().is_empty());
+        let alerts = monitor.observe(&nav_updated(NOW + 7_260, 941, 890)).unwrap();
+        assert_eq!(fired(&alerts), ["nav-drop"]);
+    }
+
+    #[test]
+    fn nav_drops_outside_the_window_do_not_compound() {
+        let mut monitor = monitor();
+        assert!(monitor.observe(&nav_updated(NOW, 1_000, 970)).unwrap().is_empty());
+        assert!(monitor.observe(&nav_updated(NOW + 3_601, 970, 941)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn stale_fires_once_and_rearms_on_the_event() {
+        let mut monitor = monitor();
+        // Measured from the monitor's start until the event is first seen
+        assert!(monitor.tick(NOW + 86_400).is_empty());
+        let alerts = monitor.tick(NOW + 86_401);
+        assert_eq!(fired(&alerts), ["nav-stale"]);
+        assert_eq!(alerts[0].notify, ["ops-slack", "pager"]);
+        assert!(alerts[0].message.starts_with("no NavUpdated seen since monitoring started"));
+        assert!(monitor.tick(NOW + 90_000).is_empty());
+
+        monitor.observe(&nav_updated(NOW + 90_000, 1_000, 1_000)).unwrap();
+        assert!(monitor.tick(NOW + 90_001).is_empty());
+        let alerts = monitor.tick(NOW + 90_000 + 86_401);
+        assert_eq!(fired(&alerts), ["nav-stale"]);
+        assert!(alerts[0].message.starts_with(&format!("no NavUpdated since {}", NOW + 90_000)));
+    }
+
+    #[test]
+    fn rules_evaluate_independently() {
+        let mut monitor = monitor();
+        // One event can breach one rule while the others stay quiet
+        let alerts = monitor.observe(&nav_updated(NOW, 1_000, 900)).unwrap();
+        assert_eq!(fired(&alerts), ["nav-drop"]);
+        let alerts = monitor.observe(&withdrawn(vault(), 3_000_000)).unwrap();
+        assert_eq!(fired(&alerts), ["large-withdrawal"]);
+        // The NAV report keeps the stale rule armed
+        assert!(monitor.tick(NOW + 86_400).is_empty());
+        assert_eq!(fired(&monitor.tick(NOW + 86_401)), ["nav-stale"]);
+    }
+}
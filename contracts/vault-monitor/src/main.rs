@@ -0,0 +1,84 @@
+//! Watches the indexer's event log and pushes alerts from YAML rules.
+//!
+//! Follows `VAULT_EVENTS` (JSON lines of `vault_events::LoggedEvent`) like
+//! `tail -f`, evaluating each new event of `SOLANA_VAULT` and the time-based
+//! rules every `POLL_INTERVAL_SECS` (default 15). Events already in the file
+//! at startup rebuild the rule state without alerting:
+//!
+//! ```text
+//! MONITOR_CONFIG=monitor.yaml SOLANA_VAULT=<vault> VAULT_EVENTS=events.jsonl vault-monitor
+//! ```
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use vault_events::{LoggedEvent, Pubkey};
+use vault_monitor::{Alert, Config, Monitor};
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn dispatch(config: &Config, alerts: Vec<Alert>) {
+    for alert in alerts {
+        eprintln!("alert [{}] {}", alert.rule, alert.message);
+        for name in &alert.notify {
+            if let Err(err) = config.notifiers[name].send(&alert) {
+                eprintln!("notifier {name} failed: {err:#}");
+            }
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let config_path = std::env::var("MONITOR_CONFIG").context("MONITOR_CONFIG is not set")?;
+    let vault: Pubkey = std::env::var("SOLANA_VAULT")
+        .context("SOLANA_VAULT is not set")?
+        .parse()?;
+    let events_path = std::env::var("VAULT_EVENTS").context("VAULT_EVENTS is not set")?;
+    let interval = Duration::from_secs(
+        std::env::var("POLL_INTERVAL_SECS")
+            .ok()
+            .and_then(|secs| secs.parse().ok())
+            .unwrap_or(15),
+    );
+
+    let config = Config::load(&config_path)?;
+    let mut monitor = Monitor::new(&vault, config.rules.clone(), now())?;
+    let mut events =
+        BufReader::new(File::open(&events_path).with_context(|| format!("open {events_path}"))?);
+    eprintln!(
+        "monitoring {vault} with {} rules from {config_path}",
+        config.rules.len()
+    );
+
+    let mut caught_up = false;
+    let mut line = String::new();
+    loop {
+        // A line without its newline is still being written; keep it and
+        // read the rest on the next poll
+        if events.read_line(&mut line)? > 0 && line.ends_with('\n') {
+            if !line.trim().is_empty() {
+                match serde_json::from_str::<LoggedEvent>(&line) {
+                    Ok(event) => {
+                        let alerts = monitor.observe(&event)?;
+                        if caught_up {
+                            dispatch(&config, alerts);
+                        }
+                    }
+                    Err(err) => eprintln!("skipping unreadable event: {err}"),
+                }
+            }
+            line.clear();
+            continue;
+        }
+        caught_up = true;
+        dispatch(&config, monitor.tick(now()));
+        std::thread::sleep(interval);
+    }
+}
@@ -0,0 +1,39 @@
+//! Alert delivery. Each notifier posts one message per alert.
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::Alert;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Notifier {
+    /// Slack incoming webhook
+    Slack { webhook_url: String },
+    /// Telegram bot posting to `chat_id`
+    Telegram { bot_token: String, chat_id: String },
+    /// Any endpoint accepting the alert as JSON: `{"rule": .., "message": ..}`
+    Webhook { url: String },
+}
+
+impl Notifier {
+    pub fn send(&self, alert: &Alert) -> Result<()> {
+        let text = format!("[{}] {}", alert.rule, alert.message);
+        match self {
+            Self::Slack { webhook_url } => {
+                ureq::post(webhook_url).send_json(json!({ "text": text }))?;
+            }
+            Self::Telegram { bot_token, chat_id } => {
+                ureq::post(&format!(
+                    "https://api.telegram.org/bot{bot_token}/sendMessage"
+                ))
+                .send_json(json!({ "chat_id": chat_id, "text": text }))?;
+            }
+            Self::Webhook { url } => {
+                ureq::post(url).send_json(alert)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+//! Replays a yield vault's on-chain history against the current program build.
+//!
+//! Each historical transaction is sent exactly as signed to a `VaultSim`
+//! whose clock is set to the transaction's block, so PDAs seeded by slot and
+//! time-based accounting line up with the original run. Token accounts are
+//! seeded from the balances recorded before each transaction, which lets a
+//! replay start from `create_vault` without a snapshot of the whole bank.
+//!
+//! A transaction diverges when it succeeds on one side only or emits different
+//! vault events. After the last one, `diff_state` compares the replayed vault
+//! with the live account. Transactions that need accounts the simulator cannot
+//! rebuild (strategy or DEX programs, Pyth updates, address lookup tables)
+//! fail to replay and show up as divergences.
+
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+use test_utils::VaultSim;
+use vault_events::VaultEvent;
+use yield_vault::state::VaultState;
+
+/// Lamports kept on every signer so replayed fees and rent never fail
+const SIGNER_LAMPORTS: u64 = 100_000_000_000;
+
+/// A token balance recorded in a transaction's status meta
+#[derive(Clone, Debug)]
+pub struct TokenBalance {
+    pub account: Pubkey,
+    pub mint: Pubkey,
+    /// Token account authority
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+/// A confirmed transaction that touched the vault
+#[derive(Clone, Debug)]
+pub struct HistoricalTx {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: i64,
+    pub transaction: VersionedTransaction,
+    pub succeeded: bool,
+    /// Vault events decoded from the original inner instructions
+    pub events: Vec<VaultEvent>,
+    pub pre_token_balances: Vec<TokenBalance>,
+}
+
+/// How a replayed transaction differed from the original
+#[derive(Clone, Debug)]
+pub enum Divergence {
+    /// Succeeded on chain, failed on replay
+    Failed { error: String },
+    /// Failed on chain, succeeded on replay
+    Succeeded,
+    EventsDiffer {
+        onchain: Vec<VaultEvent>,
+        replayed: Vec<VaultEvent>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct TxDivergence {
+    pub signature: String,
+    pub slot: u64,
+    pub divergence: Divergence,
+}
+
+/// Seed the accounts `tx` expects to find: mints and token accounts from its
+/// recorded pre-balances, and lamports for its signers.
+///
+/// Balances of token accounts under a vault-program authority are left to the
+/// replay, since they are what is being checked. Other accounts move outside
+/// the vault's history and are reset to their recorded balance.
+fn seed(sim: &mut VaultSim, tx: &HistoricalTx) {
+    for balance in &tx.pre_token_balances {
+        if sim.svm.get_account(&balance.mint).is_none() {
+            sim.set_mint(balance.mint, balance.decimals);
+        }
+        let vault_controlled = sim
+            .svm
+            .get_account(&balance.owner)
+            .is_some_and(|owner| owner.owner == yield_vault::ID);
+        if !vault_controlled || sim.svm.get_account(&balance.account).is_none() {
+            sim.set_token_balance(
+                balance.account,
+                &balance.mint,
+                &balance.owner,
+                balance.amount,
+            );
+        }
+    }
+
+    let message = &tx.transaction.message;
+    let signer_count = message.header().num_required_signatures as usize;
+    for signer in &message.static_account_keys()[..signer_count] {
+        if sim.svm.get_balance(signer).unwrap_or(0) < SIGNER_LAMPORTS {
+            // An airdrop only fails on a bank misconfiguration, which the
+            // replayed transaction itself will then report
+            let _ = sim.svm.airdrop(signer, SIGNER_LAMPORTS);
+        }
+    }
+}
+
+/// Replay `history` in order, returning the transactions that diverged
+pub fn replay(sim: &mut VaultSim, history: &[HistoricalTx]) -> Vec<TxDivergence> {
+    let mut divergences = Vec::new();
+    for tx in history {
+        sim.set_clock(tx.slot, tx.block_time);
+        seed(sim, tx);
+
+        let divergence = match (tx.succeeded, sim.send_signed(tx.transaction.clone())) {
+            (true, Ok(replayed)) if replayed != tx.events => Some(Divergence::EventsDiffer {
+                onchain: tx.events.clone(),
+                replayed,
+            }),
+            (true, Err(error)) => Some(Divergence::Failed {
+                error: error.to_string(),
+            }),
+            (false, Ok(_)) => Some(Divergence::Succeeded),
+            _ => None,
+        };
+        if let Some(divergence) = divergence {
+            divergences.push(TxDivergence {
+                signature: tx.signature.clone(),
+                slot: tx.slot,
+                divergence,
+            });
+        }
+    }
+    divergences
+}
+
+/// A vault state field whose replayed value differs from the live one
+#[derive(Clone, Debug)]
+pub struct FieldDiff {
+    pub field: &'static str,
+    pub onchain: String,
+    pub replayed: String,
+}
+
+/// Compare the accounting fields of the live and replayed vault state
+pub fn diff_state(onchain: &VaultState, replayed: &VaultState) -> Vec<FieldDiff> {
+    let fields: [(&'static str, fn(&VaultState) -> String); 14] = [
+        ("total_assets", |v| v.total_assets.to_string()),
+        ("total_shares", |v| v.total_shares.to_string()),
+        ("total_debt", |v| v.total_debt.to_string()),
+        ("high_water_mark", |v| v.high_water_mark().to_string()),
+        ("fee_remainder", |v| v.fee_remainder().to_string()),
+        ("accrued_management_fee", |v| {
+            v.accrued_management_fee.to_string()
+        }),
+        ("last_fee_collection", |v| v.last_fee_collection.to_string()),
+        ("pending_deallocation", |v| {
+            v.pending_deallocation.to_string()
+        }),
+        ("current_epoch", |v| v.current_epoch.to_string()),
+        ("epoch_pending_deposits", |v| {
+            v.epoch_pending_deposits.to_string()
+        }),
+        ("epoch_pending_redeems", |v| {
+            v.epoch_pending_redeems.to_string()
+        }),
+        ("epoch_reserved_assets", |v| {
+            v.epoch_reserved_assets.to_string()
+        }),
+        ("paused", |v| v.paused.to_string()),
+        ("winding_down", |v| v.winding_down.to_string()),
+    ];
+    fields
+        .into_iter()
+        .filter_map(|(field, value)| {
+            let (onchain, replayed) = (value(onchain), value(replayed));
+            (onchain != replayed).then_some(FieldDiff {
+                field,
+                onchain,
+                replayed,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,184 @@
+//! Replays a vault's mainnet history against the current program build.
+//!
+//! Pulls every finalized transaction that touched `SOLANA_VAULT` from an
+//! archive RPC, oldest first, replays them in a local `VaultSim` loaded with
+//! `YIELD_VAULT_SO` (default: the `anchor build` output), and reports each
+//! diverging transaction plus any difference between the replayed and live
+//! vault state. Exits non-zero when anything diverged, so it can gate
+//! accounting changes in CI:
+//!
+//! ```text
+//! SOLANA_RPC_URL=<archive rpc> SOLANA_VAULT=<vault> vault-replay
+//! ```
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::bs58;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{UiInstruction, UiTransactionEncoding};
+use test_utils::{decode_events, VaultSim};
+use vault_replay::{diff_state, replay, Divergence, HistoricalTx, TokenBalance};
+use yield_vault::state::VaultState;
+
+/// Signatures requested per page, the RPC maximum
+const SIGNATURE_PAGE: usize = 1_000;
+
+fn vault_signatures(client: &RpcClient, vault: &Pubkey) -> Result<Vec<Signature>> {
+    let mut signatures = Vec::new();
+    let mut before = None;
+    loop {
+        let page = client.get_signatures_for_address_with_config(
+            vault,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until: None,
+                limit: Some(SIGNATURE_PAGE),
+                commitment: Some(CommitmentConfig::finalized()),
+            },
+        )?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(Signature::from_str(&last.signature)?);
+        for status in &page {
+            signatures.push(Signature::from_str(&status.signature)?);
+        }
+    }
+    signatures.reverse();
+    Ok(signatures)
+}
+
+fn fetch_transaction(client: &RpcClient, signature: &Signature) -> Result<HistoricalTx> {
+    let confirmed = client.get_transaction_with_config(
+        signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::finalized()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+    let transaction = confirmed
+        .transaction
+        .transaction
+        .decode()
+        .ok_or_else(|| anyhow!("{signature}: undecodable transaction"))?;
+    let meta = confirmed
+        .transaction
+        .meta
+        .ok_or_else(|| anyhow!("{signature}: no status meta"))?;
+
+    // Token balance indexes run over the static keys, then the writable and
+    // readonly lookup table addresses
+    let mut keys = transaction.message.static_account_keys().to_vec();
+    if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+        for key in loaded.writable.iter().chain(&loaded.readonly) {
+            keys.push(Pubkey::from_str(key)?);
+        }
+    }
+    let mut pre_token_balances = Vec::new();
+    if let OptionSerializer::Some(balances) = &meta.pre_token_balances {
+        for balance in balances {
+            let OptionSerializer::Some(owner) = &balance.owner else {
+                continue;
+            };
+            pre_token_balances.push(TokenBalance {
+                account: keys[balance.account_index as usize],
+                mint: Pubkey::from_str(&balance.mint)?,
+                owner: Pubkey::from_str(owner)?,
+                amount: balance.ui_token_amount.amount.parse()?,
+                decimals: balance.ui_token_amount.decimals,
+            });
+        }
+    }
+    // Events are the data of the vault's CPIs to itself
+    let mut inner_ixs = Vec::new();
+    if let OptionSerializer::Some(inner) = &meta.inner_instructions {
+        for ix in inner.iter().flat_map(|inner| &inner.instructions) {
+            if let UiInstruction::Compiled(ix) = ix {
+                inner_ixs.push(bs58::decode(&ix.data).into_vec()?);
+            }
+        }
+    }
+
+    Ok(HistoricalTx {
+        signature: signature.to_string(),
+        slot: confirmed.slot,
+        block_time: confirmed
+            .block_time
+            .ok_or_else(|| anyhow!("{signature}: no block time"))?,
+        transaction,
+        succeeded: meta.err.is_none(),
+        events: decode_events(inner_ixs.iter().map(Vec::as_slice)),
+        pre_token_balances,
+    })
+}
+
+fn main() -> Result<()> {
+    let rpc_url = std::env::var("SOLANA_RPC_URL").context("SOLANA_RPC_URL is not set")?;
+    let vault: Pubkey = std::env::var("SOLANA_VAULT")
+        .context("SOLANA_VAULT is not set")?
+        .parse()?;
+    let client = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::finalized());
+
+    let history = vault_signatures(&client, &vault)?
+        .iter()
+        .map(|signature| fetch_transaction(&client, signature))
+        .collect::<Result<Vec<_>>>()?;
+    eprintln!("replaying {} transactions", history.len());
+
+    let mut sim = VaultSim::replaying()?;
+    let divergences = replay(&mut sim, &history);
+    for tx in &divergences {
+        match &tx.divergence {
+            Divergence::Failed { error } => {
+                println!(
+                    "{} (slot {}): failed on replay: {error}",
+                    tx.signature, tx.slot
+                )
+            }
+            Divergence::Succeeded => {
+                println!(
+                    "{} (slot {}): failed on chain, succeeded on replay",
+                    tx.signature, tx.slot
+                )
+            }
+            Divergence::EventsDiffer { onchain, replayed } => {
+                println!("{} (slot {}): events differ", tx.signature, tx.slot);
+                println!("  on chain: {onchain:?}");
+                println!("  replayed: {replayed:?}");
+            }
+        }
+    }
+
+    let account = client.get_account(&vault)?;
+    let onchain: VaultState = bytemuck::pod_read_unaligned(
+        account
+            .data
+            .get(8..8 + std::mem::size_of::<VaultState>())
+            .ok_or_else(|| anyhow!("{vault} is not a vault"))?,
+    );
+    let state_diffs = diff_state(&onchain, &sim.vault_state(&vault)?);
+    for diff in &state_diffs {
+        println!(
+            "vault.{}: on chain {}, replayed {}",
+            diff.field, diff.onchain, diff.replayed
+        );
+    }
+
+    if !divergences.is_empty() || !state_diffs.is_empty() {
+        bail!(
+            "{} of {} transactions and {} state fields diverged",
+            divergences.len(),
+            history.len(),
+            state_diffs.len()
+        );
+    }
+    println!("replayed {} transactions with no divergence", history.len());
+    Ok(())
+}
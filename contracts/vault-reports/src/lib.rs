@@ -0,0 +1,621 @@
+//! Fee and P&L statements for a yield vault's depositors.
+//!
+//! Replays the vault's events in log order to follow each depositor's shares
+//! and cost basis, then reports the deposits, withdrawals, fee shares and
+//! realized gains booked inside a date range. Positions are followed through
+//! vault events only: shares moved by plain token transfers are not tracked,
+//! and redeeming shares the ledger never saw arrive books the full proceeds
+//! as gain.
+
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use serde::{Serialize, Serializer};
+use vault_events::{LoggedEvent, Pubkey, VaultEvent};
+
+/// Statements print keys in base58, as explorers and wallets show them
+fn base58<S: Serializer>(key: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(key)
+}
+
+/// What a statement line records
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    /// Underlying paid in for shares (direct, zap, intent or settled epoch)
+    Deposit,
+    /// Shares redeemed for underlying
+    Withdrawal,
+    /// The holder's pro-rata part of fees collected by the vault
+    FeeShare,
+}
+
+impl EntryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Deposit => "deposit",
+            Self::Withdrawal => "withdrawal",
+            Self::FeeShare => "fee_share",
+        }
+    }
+}
+
+/// One line of a user statement. Amounts are in the vault's underlying.
+#[derive(Clone, Debug, Serialize)]
+pub struct Entry {
+    pub block_time: i64,
+    #[serde(serialize_with = "base58")]
+    pub user: Pubkey,
+    pub kind: EntryKind,
+    pub amount: u64,
+    /// Shares minted (deposit) or burned (withdrawal); 0 for fee shares
+    pub shares: u64,
+    /// Proceeds less the cost basis of the burned shares; 0 unless a withdrawal
+    pub realized_gain: i128,
+}
+
+/// A depositor's shares and what they paid for them
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct Position {
+    pub shares: u64,
+    pub cost_basis: u64,
+}
+
+/// Epoch requests not yet settled
+#[derive(Clone, Copy, Debug, Default)]
+struct PendingRequest {
+    epoch: u64,
+    deposit_assets: u64,
+    redeem_shares: u64,
+}
+
+/// `part * total / whole`, rounded down (0 when `whole` is 0), as on-chain
+fn pro_rata(part: u64, total: u64, whole: u64) -> u64 {
+    if whole == 0 {
+        return 0;
+    }
+    (part as u128 * total as u128 / whole as u128) as u64
+}
+
+/// Replayed positions and statement lines of one vault
+pub struct Ledger {
+    vault: Pubkey,
+    /// Share supply as seen through the vault's events, treasury fee shares
+    /// included
+    supply: u64,
+    positions: HashMap<Pubkey, Position>,
+    pending: HashMap<Pubkey, PendingRequest>,
+    entries: Vec<Entry>,
+}
+
+impl Ledger {
+    pub fn new(vault: Pubkey) -> Self {
+        Self {
+            vault,
+            supply: 0,
+            positions: HashMap::new(),
+            pending: HashMap::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Replay `events` in log order, stopping after `until` (inclusive).
+    /// Events of other vaults are skipped.
+    pub fn replay(
+        vault: Pubkey,
+        events: impl IntoIterator<Item = LoggedEvent>,
+        until: i64,
+    ) -> Self {
+        let mut ledger = Self::new(vault);
+        for logged in events {
+            if logged.block_time > until {
+                break;
+            }
+            ledger.apply(logged.block_time, &logged.event);
+        }
+        ledger
+    }
+
+    pub fn position(&self, user: &Pubkey) -> Position {
+        self.positions.get(user).copied().unwrap_or_default()
+    }
+
+    fn deposit(&mut self, block_time: i64, user: Pubkey, amount: u64, shares: u64) {
+        let position = self.positions.entry(user).or_default();
+        position.shares = position.shares.saturating_add(shares);
+        position.cost_basis = position.cost_basis.saturating_add(amount);
+        self.entries.push(Entry {
+            block_time,
+            user,
+            kind: EntryKind::Deposit,
+            amount,
+            shares,
+            realized_gain: 0,
+        });
+    }
+
+    fn withdraw(&mut self, block_time: i64, user: Pubkey, shares: u64, proceeds: u64) {
+        let position = self.positions.entry(user).or_default();
+        let basis = if shares >= position.shares {
+            position.cost_basis
+        } else {
+            pro_rata(shares, position.cost_basis, position.shares)
+        };
+        position.shares = position.shares.saturating_sub(shares);
+        position.cost_basis -= basis;
+        self.entries.push(Entry {
+            block_time,
+            user,
+            kind: EntryKind::Withdrawal,
+            amount: proceeds,
+            shares,
+            realized_gain: proceeds as i128 - basis as i128,
+        });
+    }
+
+    /// Split `fee_amount` over the tracked holders by share balance
+    fn charge_fees(&mut self, block_time: i64, fee_amount: u64) {
+        let supply = self.supply;
+        let mut holders: Vec<(Pubkey, u64)> = self
+            .positions
+            .iter()
+            .filter(|(_, position)| position.shares > 0)
+            .map(|(user, position)| (*user, position.shares))
+            .collect();
+        holders.sort_by_key(|(user, _)| user.to_bytes());
+        for (user, shares) in holders {
+            let amount = pro_rata(shares, fee_amount, supply);
+            if amount > 0 {
+                self.entries.push(Entry {
+                    block_time,
+                    user,
+                    kind: EntryKind::FeeShare,
+                    amount,
+                    shares: 0,
+                    realized_gain: 0,
+                });
+            }
+        }
+    }
+
+    /// Book the requests settled in `epoch`, mirroring
+    /// `EpochSettlement::entitlement`
+    fn settle_epoch(&mut self, block_time: i64, epoch: u64, flows: [u64; 4]) {
+        let [deposits, shares_minted, redeems, assets_paid] = flows;
+        let mut settled: Vec<(Pubkey, PendingRequest)> = self
+            .pending
+            .iter()
+            .filter(|(_, request)| request.epoch == epoch)
+            .map(|(user, request)| (*user, *request))
+            .collect();
+        settled.sort_by_key(|(user, _)| user.to_bytes());
+        for (user, request) in settled {
+            self.pending.remove(&user);
+            if request.deposit_assets > 0 {
+                let shares = pro_rata(request.deposit_assets, shares_minted, deposits);
+                self.deposit(block_time, user, request.deposit_assets, shares);
+            }
+            if request.redeem_shares > 0 {
+                let assets = pro_rata(request.redeem_shares, assets_paid, redeems);
+                self.withdraw(block_time, user, request.redeem_shares, assets);
+            }
+        }
+    }
+
+    pub fn apply(&mut self, block_time: i64, event: &VaultEvent) {
+        match event {
+            VaultEvent::Deposited(ev) if ev.vault == self.vault => {
+                self.supply = self.supply.saturating_add(ev.shares_minted);
+                self.deposit(block_time, ev.user, ev.amount, ev.shares_minted);
+            }
+            VaultEvent::Withdrawn(ev) if ev.vault == self.vault => {
+                self.supply = self.supply.saturating_sub(ev.shares_burned);
+                self.withdraw(block_time, ev.user, ev.shares_burned, ev.amount_returned);
+            }
+            VaultEvent::FeesCollected(ev) if ev.vault == self.vault => {
+                self.charge_fees(block_time, ev.fee_amount);
+                self.supply = self.supply.saturating_add(ev.fee_shares_minted);
+            }
+            VaultEvent::EpochDepositRequested(ev) if ev.vault == self.vault => {
+                let request = self.pending.entry(ev.user).or_default();
+                request.epoch = ev.epoch;
+                request.deposit_assets = request.deposit_assets.saturating_add(ev.amount);
+            }
+            VaultEvent::EpochRedeemRequested(ev) if ev.vault == self.vault => {
+                let request = self.pending.entry(ev.user).or_default();
+                request.epoch = ev.epoch;
+                request.redeem_shares = request.redeem_shares.saturating_add(ev.shares);
+            }
+            VaultEvent::EpochSettled(ev) if ev.vault == self.vault => {
+                self.supply = self
+                    .supply
+                    .saturating_add(ev.shares_minted)
+                    .saturating_sub(ev.redeems);
+                self.settle_epoch(
+                    block_time,
+                    ev.epoch,
+                    [ev.deposits, ev.shares_minted, ev.redeems, ev.assets_paid],
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Lines booked in `[from, to]`, optionally for one user
+    pub fn entries(&self, from: i64, to: i64, user: Option<&Pubkey>) -> Vec<Entry> {
+        self.entries
+            .iter()
+            .filter(|entry| (from..=to).contains(&entry.block_time))
+            .filter(|entry| user.is_none_or(|user| entry.user == *user))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Totals of a set of statement lines
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct Totals {
+    pub deposits: u64,
+    pub withdrawals: u64,
+    pub fee_share: u64,
+    pub realized_gain: i128,
+}
+
+impl Totals {
+    pub fn of<'a>(entries: impl IntoIterator<Item = &'a Entry>) -> Self {
+        let mut totals = Self::default();
+        for entry in entries {
+            match entry.kind {
+                EntryKind::Deposit => totals.deposits += entry.amount,
+                EntryKind::Withdrawal => {
+                    totals.withdrawals += entry.amount;
+                    totals.realized_gain += entry.realized_gain;
+                }
+                EntryKind::FeeShare => totals.fee_share += entry.amount,
+            }
+        }
+        totals
+    }
+}
+
+/// A depositor's activity over the range and their position at its end
+#[derive(Clone, Debug, Serialize)]
+pub struct UserStatement {
+    #[serde(serialize_with = "base58")]
+    pub user: Pubkey,
+    #[serde(flatten)]
+    pub totals: Totals,
+    /// Position at the end of the range
+    pub closing: Position,
+    pub entries: Vec<Entry>,
+}
+
+/// A vault's activity over the range, broken down by depositor
+#[derive(Clone, Debug, Serialize)]
+pub struct VaultStatement {
+    #[serde(serialize_with = "base58")]
+    pub vault: Pubkey,
+    pub from: i64,
+    pub to: i64,
+    #[serde(flatten)]
+    pub totals: Totals,
+    pub users: Vec<UserStatement>,
+}
+
+impl Ledger {
+    pub fn user_statement(&self, user: &Pubkey, from: i64, to: i64) -> UserStatement {
+        let entries = self.entries(from, to, Some(user));
+        UserStatement {
+            user: *user,
+            totals: Totals::of(&entries),
+            closing: self.position(user),
+            entries,
+        }
+    }
+
+    /// Statement of every depositor with activity in the range or shares at
+    /// its end; per-user lines are left out to keep it to one row each
+    pub fn vault_statement(&self, from: i64, to: i64) -> VaultStatement {
+        let entries = self.entries(from, to, None);
+        let mut users: Vec<Pubkey> = entries.iter().map(|entry| entry.user).collect();
+        users.extend(
+            self.positions
+                .iter()
+                .filter(|(_, position)| position.shares > 0)
+                .map(|(user, _)| *user),
+        );
+        users.sort_by_key(|user| user.to_bytes());
+        users.dedup();
+
+        VaultStatement {
+            vault: self.vault,
+            from,
+            to,
+            totals: Totals::of(&entries),
+            users: users
+                .iter()
+                .map(|user| UserStatement {
+                    entries: Vec::new(),
+                    ..self.user_statement(user, from, to)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// CSV of a vault statement: one row per depositor
+pub fn vault_csv(statement: &VaultStatement) -> String {
+    let mut out = String::from(
+        "user,deposits,withdrawals,fee_share,realized_gain,closing_shares,closing_cost_basis\n",
+    );
+    for user in &statement.users {
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            user.user,
+            user.totals.deposits,
+            user.totals.withdrawals,
+            user.totals.fee_share,
+            user.totals.realized_gain,
+            user.closing.shares,
+            user.closing.cost_basis,
+        ));
+    }
+    out
+}
+
+/// CSV of a user statement: one row per line
+pub fn user_csv(statement: &UserStatement) -> String {
+    let mut out = String::from("block_time,date,kind,amount,shares,realized_gain\n");
+    for entry in &statement.entries {
+        let date = DateTime::from_timestamp(entry.block_time, 0)
+            .map(|time| time.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            entry.block_time,
+            date,
+            entry.kind.as_str(),
+            entry.amount,
+            entry.shares,
+            entry.realized_gain,
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use vault_events::{
+        Deposited, EpochDepositRequested, EpochRedeemRequested, EpochSettled, FeesCollected,
+        Withdrawn,
+    };
+
+    use super::*;
+
+    /// 2023-11-14T22:13:20Z
+    const T0: i64 = 1_700_000_000;
+    const DAY: i64 = 86_400;
+
+    const VAULT: Pubkey = Pubkey::new_from_array([9; 32]);
+    const ALICE: Pubkey = Pubkey::new_from_array([1; 32]);
+    const BOB: Pubkey = Pubkey::new_from_array([2; 32]);
+
+    fn logged(block_time: i64, event: VaultEvent) -> LoggedEvent {
+        LoggedEvent { block_time, event }
+    }
+
+    fn deposited(block_time: i64, user: Pubkey, amount: u64, shares: u64) -> LoggedEvent {
+        logged(
+            block_time,
+            VaultEvent::Deposited(Deposited {
+                vault: VAULT,
+                event_seq: 0,
+                user,
+                amount,
+                shares_minted: shares,
+                entry_fee: 0,
+                memo: None,
+            }),
+        )
+    }
+
+    fn withdrawn(block_time: i64, user: Pubkey, shares: u64, amount: u64) -> LoggedEvent {
+        logged(
+            block_time,
+            VaultEvent::Withdrawn(Withdrawn {
+                vault: VAULT,
+                event_seq: 0,
+                user,
+                recipient: user,
+                shares_burned: shares,
+                amount_returned: amount,
+                exit_fee: 0,
+                memo: None,
+            }),
+        )
+    }
+
+    fn fees_collected(block_time: i64, fee_amount: u64, fee_shares: u64) -> LoggedEvent {
+        logged(
+            block_time,
+            VaultEvent::FeesCollected(FeesCollected {
+                vault: VAULT,
+                event_seq: 0,
+                fee_shares_minted: fee_shares,
+                fee_amount,
+                donated: 0,
+            }),
+        )
+    }
+
+    /// Alice and Bob each deposit 1000 for 1000 shares, the vault takes 100
+    /// in fees for 50 shares, and Alice redeems half her shares for 600
+    fn fixture() -> Vec<LoggedEvent> {
+        vec![
+            deposited(T0, ALICE, 1_000, 1_000),
+            deposited(T0 + DAY, BOB, 1_000, 1_000),
+            fees_collected(T0 + 2 * DAY, 100, 50),
+            withdrawn(T0 + 3 * DAY, ALICE, 500, 600),
+        ]
+    }
+
+    #[test]
+    fn empty_period_books_nothing() {
+        let ledger = Ledger::replay(VAULT, fixture(), T0 - 1);
+        let statement = ledger.vault_statement(T0 - DAY, T0 - 1);
+        assert!(statement.users.is_empty());
+        assert_eq!(statement.totals.deposits, 0);
+        assert_eq!(statement.totals.withdrawals, 0);
+        assert_eq!(statement.totals.fee_share, 0);
+        assert_eq!(statement.totals.realized_gain, 0);
+        assert_eq!(vault_csv(&statement).lines().count(), 1);
+    }
+
+    #[test]
+    fn quiet_period_lists_open_positions() {
+        let ledger = Ledger::replay(VAULT, fixture(), T0 + 10 * DAY);
+        let statement = ledger.vault_statement(T0 + 5 * DAY, T0 + 10 * DAY);
+        assert_eq!(statement.users.len(), 2);
+        for user in &statement.users {
+            assert_eq!(user.totals.deposits, 0);
+            assert_eq!(user.totals.withdrawals, 0);
+            assert_eq!(user.totals.fee_share, 0);
+        }
+        assert_eq!(statement.users[0].closing.shares, 500);
+        assert_eq!(statement.users[1].closing.shares, 1_000);
+    }
+
+    #[test]
+    fn fee_shares_split_by_balance() {
+        let ledger = Ledger::replay(VAULT, fixture(), T0 + 2 * DAY);
+        let fees: Vec<(Pubkey, u64)> = ledger
+            .entries(T0 + 2 * DAY, T0 + 2 * DAY, None)
+            .iter()
+            .map(|entry| {
+                assert_eq!(entry.kind, EntryKind::FeeShare);
+                (entry.user, entry.amount)
+            })
+            .collect();
+        // Both hold 1000 of the 2000 shares when the fees are taken
+        assert_eq!(fees, [(ALICE, 50), (BOB, 50)]);
+    }
+
+    #[test]
+    fn withdrawal_realizes_gain_over_cost_basis() {
+        let ledger = Ledger::replay(VAULT, fixture(), i64::MAX);
+        let statement = ledger.user_statement(&ALICE, T0, T0 + 3 * DAY);
+        let kinds: Vec<EntryKind> = statement.entries.iter().map(|entry| entry.kind).collect();
+        assert_eq!(
+            kinds,
+            [EntryKind::Deposit, EntryKind::FeeShare, EntryKind::Withdrawal]
+        );
+        assert_eq!(statement.totals.deposits, 1_000);
+        assert_eq!(statement.totals.fee_share, 50);
+        assert_eq!(statement.totals.withdrawals, 600);
+        // Half the shares carry half the 1000 basis
+        assert_eq!(statement.totals.realized_gain, 100);
+        assert_eq!(statement.closing.shares, 500);
+        assert_eq!(statement.closing.cost_basis, 500);
+
+        assert_eq!(
+            user_csv(&statement),
+            "block_time,date,kind,amount,shares,realized_gain\n\
+             1700000000,2023-11-14,deposit,1000,1000,0\n\
+             1700172800,2023-11-16,fee_share,50,0,0\n\
+             1700259200,2023-11-17,withdrawal,600,500,100\n"
+        );
+    }
+
+    #[test]
+    fn vault_statement_totals_every_user() {
+        let ledger = Ledger::replay(VAULT, fixture(), i64::MAX);
+        let statement = ledger.vault_statement(T0, T0 + 3 * DAY);
+        assert_eq!(statement.totals.deposits, 2_000);
+        assert_eq!(statement.totals.fee_share, 100);
+        assert_eq!(statement.totals.withdrawals, 600);
+        assert_eq!(statement.totals.realized_gain, 100);
+        // Per-user lines are left out of the vault statement
+        assert!(statement.users.iter().all(|user| user.entries.is_empty()));
+        assert_eq!(
+            vault_csv(&statement),
+            format!(
+                "user,deposits,withdrawals,fee_share,realized_gain,closing_shares,\
+                 closing_cost_basis\n\
+                 {ALICE},1000,600,50,100,500,500\n\
+                 {BOB},1000,0,50,0,1000,1000\n"
+            )
+        );
+    }
+
+    #[test]
+    fn replay_stops_at_the_end_of_the_range() {
+        let ledger = Ledger::replay(VAULT, fixture(), T0 + DAY);
+        assert_eq!(ledger.position(&ALICE).shares, 1_000);
+        assert_eq!(ledger.entries(T0, i64::MAX, None).len(), 2);
+    }
+
+    #[test]
+    fn skips_other_vaults() {
+        let mut events = fixture();
+        let mut other = deposited(T0, BOB, 5_000, 5_000);
+        if let VaultEvent::Deposited(ev) = &mut other.event {
+            ev.vault = Pubkey::new_from_array([8; 32]);
+        }
+        events.insert(0, other);
+        let ledger = Ledger::replay(VAULT, events, T0);
+        assert_eq!(ledger.position(&BOB).shares, 0);
+    }
+
+    #[test]
+    fn epoch_requests_book_at_settlement() {
+        let events = vec![
+            deposited(T0, BOB, 1_000, 1_000),
+            logged(
+                T0 + DAY,
+                VaultEvent::EpochDepositRequested(EpochDepositRequested {
+                    vault: VAULT,
+                    event_seq: 0,
+                    user: ALICE,
+                    epoch: 1,
+                    amount: 500,
+                }),
+            ),
+            logged(
+                T0 + DAY,
+                VaultEvent::EpochRedeemRequested(EpochRedeemRequested {
+                    vault: VAULT,
+                    event_seq: 0,
+                    user: BOB,
+                    epoch: 1,
+                    shares: 400,
+                }),
+            ),
+            logged(
+                T0 + 2 * DAY,
+                VaultEvent::EpochSettled(EpochSettled {
+                    vault: VAULT,
+                    event_seq: 0,
+                    epoch: 1,
+                    total_assets: 1_250,
+                    total_shares: 1_000,
+                    deposits: 500,
+                    shares_minted: 400,
+                    redeems: 400,
+                    redeems_filled: 400,
+                    assets_paid: 500,
+                }),
+            ),
+        ];
+        let ledger = Ledger::replay(VAULT, events, i64::MAX);
+        // Nothing is booked while the requests are pending
+        assert!(ledger.entries(T0 + DAY, T0 + DAY, None).is_empty());
+
+        let alice = ledger.user_statement(&ALICE, T0, i64::MAX);
+        assert_eq!(alice.totals.deposits, 500);
+        assert_eq!(alice.closing.shares, 400);
+        let bob = ledger.user_statement(&BOB, T0 + 2 * DAY, i64::MAX);
+        assert_eq!(bob.totals.withdrawals, 500);
+        assert_eq!(bob.totals.realized_gain, 100);
+        assert_eq!(bob.closing.shares, 600);
+    }
+}
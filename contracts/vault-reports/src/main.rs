@@ -0,0 +1,87 @@
+//! Exports fee and P&L statements for tax reporting and fund administration.
+//!
+//! Reads the vault's events as JSON lines (the serde form of
+//! `vault_events::VaultEvent` plus the `block_time` the indexer records).
+//! `report` prints the vault's statement over `[from, to]` (UTC dates,
+//! inclusive) with one row per depositor, or a single depositor's lines when
+//! `user` is given, as CSV or JSON:
+//!
+//! ```text
+//! SOLANA_VAULT=<vault> VAULT_EVENTS=events.jsonl REPORT_FORMAT=csv \
+//!     vault-reports report <from> <to> [user]
+//! ```
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::NaiveDate;
+use vault_events::{LoggedEvent, Pubkey};
+use vault_reports::{user_csv, vault_csv, Ledger};
+
+const USAGE: &str = "usage: vault-reports report <from YYYY-MM-DD> <to YYYY-MM-DD> [user]";
+
+fn load_events(path: &str) -> Result<Vec<LoggedEvent>> {
+    let mut events = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        events.push(serde_json::from_str(&line)?);
+    }
+    Ok(events)
+}
+
+/// First second of `date` (UTC)
+fn day_start(date: &str) -> Result<i64> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .with_context(|| format!("invalid date {date}"))?;
+    Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp())
+}
+
+fn main() -> Result<()> {
+    let vault: Pubkey = std::env::var("SOLANA_VAULT")
+        .context("SOLANA_VAULT is not set")?
+        .parse()?;
+    let events_path = std::env::var("VAULT_EVENTS").context("VAULT_EVENTS is not set")?;
+    let csv = match std::env::var("REPORT_FORMAT").as_deref() {
+        Ok("csv") => true,
+        Ok("json") | Err(_) => false,
+        Ok(other) => bail!("REPORT_FORMAT must be csv or json, not {other}"),
+    };
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let arg = |index: usize| args.get(index).ok_or_else(|| anyhow!(USAGE));
+
+    match arg(0)?.as_str() {
+        "report" => {
+            let from = day_start(arg(1)?)?;
+            let to = day_start(arg(2)?)? + 86_399;
+            anyhow::ensure!(from <= to, "range ends before it starts");
+
+            let ledger = Ledger::replay(vault, load_events(&events_path)?, to);
+            match args.get(3) {
+                Some(user) => {
+                    let user: Pubkey = user.parse()?;
+                    let statement = ledger.user_statement(&user, from, to);
+                    if csv {
+                        print!("{}", user_csv(&statement));
+                    } else {
+                        println!("{}", serde_json::to_string_pretty(&statement)?);
+                    }
+                }
+                None => {
+                    let statement = ledger.vault_statement(from, to);
+                    if csv {
+                        print!("{}", vault_csv(&statement));
+                    } else {
+                        println!("{}", serde_json::to_string_pretty(&statement)?);
+                    }
+                }
+            }
+        }
+        _ => bail!(USAGE),
+    }
+    Ok(())
+}